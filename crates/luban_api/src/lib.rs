@@ -27,6 +27,16 @@ pub struct AppSnapshot {
     pub ui: UiSnapshot,
     #[serde(default)]
     pub integrations: IntegrationsSnapshot,
+    #[serde(default)]
+    pub notifications: NotificationsSnapshot,
+    #[serde(default)]
+    pub attachment_text_limits: AttachmentTextLimitsSnapshot,
+    #[serde(default)]
+    pub url_attachments: UrlAttachmentSettingsSnapshot,
+    #[serde(default)]
+    pub task_issue_links: Vec<TaskIssueLinkSnapshot>,
+    #[serde(default)]
+    pub deletion: DeletionSettingsSnapshot,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -49,6 +59,89 @@ pub struct TelegramIntegrationSnapshot {
     pub config_rev: u64,
     #[serde(default)]
     pub last_error: Option<String>,
+    #[serde(default)]
+    pub group_chats: Vec<TelegramGroupChatSnapshot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TelegramGroupChatSnapshot {
+    pub chat_id: i64,
+    pub project_slug: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub members: Vec<TelegramGroupMemberSnapshot>,
+    #[serde(default)]
+    pub topic_count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TelegramGroupMemberSnapshot {
+    pub user_id: i64,
+    #[serde(default)]
+    pub display_name: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NotificationsSnapshot {
+    #[serde(default)]
+    pub desktop_enabled: bool,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHoursSnapshot>,
+}
+
+/// Limits for inlining text extracted from document attachments (PDF, DOCX, ...) into prompts.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AttachmentTextLimitsSnapshot {
+    pub max_source_bytes: u64,
+    pub max_inline_bytes: u64,
+}
+
+/// Hosts that URL attachments may be fetched from; subdomains of an entry are allowed too.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UrlAttachmentSettingsSnapshot {
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+}
+
+/// How long deleted projects and tasks stay restorable. Zero makes deletions permanent.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DeletionSettingsSnapshot {
+    pub retention_days: u32,
+}
+
+/// Caps on agent turns. Unset fields impose no limit; a task's limits override its
+/// project's field by field.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TurnLimitsSnapshot {
+    #[serde(default)]
+    pub turn_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_tool_calls_per_turn: Option<u64>,
+    /// Checked when a turn completes; going over pauses the queue.
+    #[serde(default, alias = "max_tokens_per_turn")]
+    pub turn_token_budget: Option<u64>,
+    /// Checked when a turn completes; once spent, new turns are refused.
+    #[serde(default, alias = "max_tokens_per_task")]
+    pub task_token_budget: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnLimitKind {
+    Timeout,
+    ToolCalls,
+    TurnTokens,
+    TaskTokens,
+}
+
+/// Do-not-disturb window in the user's local time, as minutes after midnight.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct QuietHoursSnapshot {
+    pub start_minute: u16,
+    pub end_minute: u16,
+    #[serde(default)]
+    pub utc_offset_minutes: i16,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -94,6 +187,18 @@ pub struct AgentSettingsSnapshot {
     pub default_runner: Option<AgentRunnerKind>,
     #[serde(default)]
     pub amp_mode: Option<String>,
+    /// Model catalog offered by the selector, across all runners.
+    #[serde(default)]
+    pub models: Vec<AgentModelSnapshot>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AgentModelSnapshot {
+    pub runner: AgentRunnerKind,
+    pub id: String,
+    pub label: String,
+    #[serde(default)]
+    pub supported_thinking_efforts: Vec<ThinkingEffort>,
 }
 
 fn default_true() -> bool {
@@ -112,6 +217,7 @@ impl Default for AgentSettingsSnapshot {
             default_thinking_effort: None,
             default_runner: None,
             amp_mode: None,
+            models: Vec::new(),
         }
     }
 }
@@ -135,6 +241,24 @@ pub struct TaskSettingsSnapshot {
     pub system_prompt_templates: Vec<SystemPromptTemplateSnapshot>,
     #[serde(default)]
     pub default_system_prompt_templates: Vec<SystemPromptTemplateSnapshot>,
+    /// Placeholder names accepted by task prompt templates (custom variables use `vars.<name>`).
+    #[serde(default)]
+    pub prompt_template_variables: Vec<String>,
+    /// Placeholder names accepted by system prompt templates.
+    #[serde(default)]
+    pub system_prompt_template_variables: Vec<String>,
+    #[serde(default)]
+    pub project_prompt_settings: Vec<ProjectPromptSettingsSnapshot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProjectPromptSettingsSnapshot {
+    pub project_id: ProjectId,
+    /// Per-project overrides layered on top of the global `prompt_templates`.
+    #[serde(default)]
+    pub prompt_templates: Vec<TaskPromptTemplateSnapshot>,
+    #[serde(default)]
+    pub variables: std::collections::BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -251,6 +375,10 @@ pub struct ProjectSnapshot {
     pub create_workspace_status: OperationStatus,
     #[serde(rename = "workdirs", alias = "workspaces")]
     pub workspaces: Vec<WorkspaceSnapshot>,
+    #[serde(default)]
+    pub notifications_muted: bool,
+    #[serde(default)]
+    pub turn_limits: TurnLimitsSnapshot,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -268,6 +396,17 @@ pub struct WorkspaceSnapshot {
     pub agent_run_status: OperationStatus,
     pub has_unread_completion: bool,
     pub pull_request: Option<PullRequestSnapshot>,
+    #[serde(default)]
+    pub disk_usage: Option<DiskUsageSnapshot>,
+}
+
+/// Bytes a workdir occupies on disk, as of the last background measurement.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DiskUsageSnapshot {
+    #[serde(rename = "workdir_bytes", alias = "worktree_bytes")]
+    pub worktree_bytes: u64,
+    pub context_blobs_bytes: u64,
+    pub measured_at_unix_ms: u64,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -417,6 +556,110 @@ pub struct ConversationSnapshot {
     pub queue_paused: bool,
     pub remote_thread_id: Option<String>,
     pub title: String,
+    /// The task's own limits, without the project fallback.
+    #[serde(default)]
+    pub turn_limits: TurnLimitsSnapshot,
+    /// Input plus output tokens spent across every turn of the task.
+    #[serde(default)]
+    pub tokens_used: u64,
+    #[serde(default)]
+    pub forked_from: Option<TaskForkOrigin>,
+    #[serde(default)]
+    pub review_comments: Vec<ReviewCommentSnapshot>,
+    #[serde(default)]
+    pub pull_request_review_sync: PullRequestReviewSyncSnapshot,
+    #[serde(default)]
+    pub ci_auto_fix: CiAutoFixSnapshot,
+}
+
+/// An inline comment on the task's diff. Pending comments (unsent and unresolved) go out
+/// together with the next `task_review_comments_send`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewCommentSnapshot {
+    pub id: u64,
+    pub path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    #[serde(default)]
+    pub quote: String,
+    pub body: String,
+    pub resolved: bool,
+    pub sent: bool,
+    pub created_at_unix_ms: u64,
+    pub updated_at_unix_ms: u64,
+    /// Set for comments imported from a pull request review.
+    #[serde(default)]
+    pub pull_request: Option<ReviewCommentOriginSnapshot>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewCommentOriginSnapshot {
+    pub pr_number: u64,
+    pub comment_id: u64,
+    pub author: String,
+    #[serde(default)]
+    pub url: String,
+    /// Whether Luban replied on the pull request that the comment was addressed.
+    pub replied: bool,
+}
+
+/// How a task follows review feedback on its workdir's pull request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullRequestReviewSyncSnapshot {
+    /// Import new review comments whenever the pull request refreshes.
+    #[serde(default)]
+    pub auto_pull: bool,
+    /// Reply on the pull request when an imported comment gets resolved.
+    #[serde(default)]
+    pub reply_when_addressed: bool,
+}
+
+/// The task's CI auto-fix loop: while enabled, each failed CI run on the workdir's pull request
+/// queues a "fix CI" turn with the failed job log attached, until CI passes or
+/// `attempts_used` reaches `max_attempts`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CiAutoFixSnapshot {
+    #[serde(default)]
+    pub enabled: bool,
+    pub max_attempts: u32,
+    #[serde(default)]
+    pub attempts_used: u32,
+}
+
+impl Default for CiAutoFixSnapshot {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_attempts: 3,
+            attempts_used: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CiAutoFixStopReason {
+    Passed,
+    AttemptsExhausted,
+}
+
+/// The task a fork branched off and the last entry it carried over.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskForkOrigin {
+    #[serde(rename = "workdir_id", alias = "workspace_id")]
+    pub workspace_id: WorkspaceId,
+    #[serde(rename = "task_id", alias = "thread_id")]
+    pub thread_id: WorkspaceThreadId,
+    pub entry_id: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskForkTarget {
+    #[default]
+    SameWorkdir,
+    /// A new workdir branched off the commit the source workdir has checked out.
+    NewWorkdir,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -494,6 +737,24 @@ mod conversation_system_event_tests {
             serde_json::from_str("{\"event_type\":\"task_archived\"}").expect("deserialize");
         assert!(matches!(parsed, ConversationSystemEvent::TaskArchived));
     }
+
+    #[test]
+    fn conversation_system_event_turn_interrupted_roundtrips() {
+        let json =
+            serde_json::to_string(&ConversationSystemEvent::TurnInterrupted { resumable: true })
+                .expect("serialize");
+        assert_eq!(
+            json,
+            "{\"event_type\":\"turn_interrupted\",\"resumable\":true}"
+        );
+
+        let parsed: ConversationSystemEvent =
+            serde_json::from_str("{\"event_type\":\"turn_interrupted\"}").expect("deserialize");
+        assert!(matches!(
+            parsed,
+            ConversationSystemEvent::TurnInterrupted { resumable: false }
+        ));
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
@@ -551,6 +812,32 @@ pub struct TaskIssueInfo {
     pub url: String,
 }
 
+/// What happens to a linked issue once the task's pull request merges.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueMergeAction {
+    #[default]
+    Close,
+    Comment,
+    Leave,
+}
+
+/// A task imported from a forge issue.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TaskIssueLinkSnapshot {
+    #[serde(rename = "workdir_id", alias = "workspace_id")]
+    pub workspace_id: WorkspaceId,
+    #[serde(rename = "task_id", alias = "thread_id")]
+    pub thread_id: WorkspaceThreadId,
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub on_merge: IssueMergeAction,
+    #[serde(default)]
+    pub resolved_by_pr: Option<u64>,
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FeedbackType {
@@ -592,6 +879,83 @@ pub struct TaskExecuteResult {
     pub mode: TaskExecuteMode,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct TaskBulkItem {
+    #[serde(rename = "workdir_id", alias = "workspace_id")]
+    pub workspace_id: WorkspaceId,
+    #[serde(rename = "task_id", alias = "thread_id")]
+    pub thread_id: WorkspaceThreadId,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskBulkOperation {
+    SetStatus {
+        task_status: TaskStatus,
+    },
+    SetStarred {
+        starred: bool,
+    },
+    /// Archives the workdir of each item; items sharing a workdir archive it once.
+    ArchiveWorkdir,
+    /// Sends the prompt to each task, queueing it behind a running turn.
+    SendPrompt {
+        text: String,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskBulkItemResult {
+    #[serde(rename = "workdir_id", alias = "workspace_id")]
+    pub workspace_id: WorkspaceId,
+    #[serde(rename = "task_id", alias = "thread_id")]
+    pub thread_id: WorkspaceThreadId,
+    pub ok: bool,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Status before a `set_status` operation changed it; used by `task_bulk_undo`.
+    #[serde(default)]
+    pub previous_status: Option<TaskStatus>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskBulkResult {
+    pub batch_id: String,
+    pub items: Vec<TaskBulkItemResult>,
+    /// Whether `task_bulk_undo` can revert this batch.
+    #[serde(default)]
+    pub undoable: bool,
+}
+
+/// One recorded state change, as returned by `GET /api/audit`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditEntrySnapshot {
+    pub id: u64,
+    pub created_at_unix_ms: u64,
+    /// `web`, `telegram`, `slack`, `cli`, `mcp` or `system`.
+    pub origin: String,
+    pub action: String,
+    pub summary: String,
+    #[serde(default, rename = "workdir_id", alias = "workspace_id")]
+    pub workspace_id: Option<WorkspaceId>,
+    #[serde(default, rename = "task_id", alias = "thread_id")]
+    pub thread_id: Option<WorkspaceThreadId>,
+    /// Whether the entry records how to revert it and has not been undone yet.
+    /// `undo_last_action` still refuses deletions older than the retention window.
+    #[serde(default)]
+    pub undoable: bool,
+    #[serde(default)]
+    pub undone_at_unix_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditLogSnapshot {
+    pub entries: Vec<AuditEntrySnapshot>,
+    /// Pass as `before` to fetch the next (older) page; absent on the last page.
+    #[serde(default)]
+    pub next_before: Option<u64>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ThreadsSnapshot {
     pub rev: u64,
@@ -686,6 +1050,64 @@ pub enum ConversationSystemEvent {
         #[serde(default)]
         explanation_markdown: String,
     },
+    TurnInterrupted {
+        #[serde(default)]
+        resumable: bool,
+    },
+    /// The worktree was snapshotted before the turn that follows; see `TaskRewind`.
+    TurnCheckpoint {
+        checkpoint_id: String,
+        commit: String,
+    },
+    TurnRewound {
+        checkpoint_id: String,
+        #[serde(default)]
+        truncated: bool,
+    },
+    /// Per-turn change summary; the full diff is served by `C-HTTP-TURN-DIFF`.
+    TurnChanges {
+        checkpoint_id: String,
+        commit: String,
+        files_changed: u64,
+        additions: u64,
+        deletions: u64,
+    },
+    /// A turn or task limit stopped the agent; `limit` and `observed` are seconds for
+    /// `timeout`, tool calls for `tool_calls`, and tokens otherwise.
+    TurnLimitReached {
+        kind: TurnLimitKind,
+        limit: u64,
+        observed: u64,
+    },
+    /// A reviewer's pull request comment was imported as review comment `review_comment_id`;
+    /// `start_line` 0 marks a comment on the whole file.
+    PullRequestReviewComment {
+        pr_number: u64,
+        review_comment_id: u64,
+        author: String,
+        path: String,
+        start_line: u32,
+        end_line: u32,
+        body: String,
+        #[serde(default)]
+        url: String,
+    },
+    /// The CI auto-fix loop queued a "fix CI" turn for failed workflow run `run_id`.
+    CiFixAttempt {
+        pr_number: u64,
+        attempt: u32,
+        max_attempts: u32,
+        run_id: u64,
+        #[serde(default)]
+        workflow: String,
+        #[serde(default)]
+        url: String,
+    },
+    /// The CI auto-fix loop turned itself off after `attempts` attempts.
+    CiAutoFixStopped {
+        reason: CiAutoFixStopReason,
+        attempts: u32,
+    },
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -709,6 +1131,8 @@ pub enum UserEvent {
 pub struct UserMessage {
     pub text: String,
     pub attachments: Vec<AttachmentRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -779,6 +1203,7 @@ pub enum AttachmentKind {
     Image,
     Text,
     File,
+    Url,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -925,6 +1350,9 @@ pub enum ClientAction {
         model_id: Option<String>,
         #[serde(default)]
         thinking_effort: Option<ThinkingEffort>,
+        /// Renders the prompt through the task template for this intent before sending.
+        #[serde(default)]
+        intent_kind: Option<TaskIntentKind>,
     },
     TelegramBotTokenSet {
         token: String,
@@ -932,6 +1360,72 @@ pub enum ClientAction {
     TelegramBotTokenClear,
     TelegramPairStart,
     TelegramUnpair,
+    /// Starts pairing a Telegram group with a project. The resulting link adds
+    /// the bot to a group, and whoever sends the code becomes its first member.
+    TelegramGroupPairStart {
+        project_id: ProjectId,
+    },
+    TelegramGroupUnpair {
+        chat_id: i64,
+    },
+    TelegramGroupMemberAdd {
+        chat_id: i64,
+        user_id: i64,
+        #[serde(default)]
+        display_name: String,
+    },
+    TelegramGroupMemberRemove {
+        chat_id: i64,
+        user_id: i64,
+    },
+    DiskUsageRefresh,
+    /// Deletes build artifacts matching `globs` from one workdir, or from every active workdir.
+    BuildArtifactsPurge {
+        #[serde(default, rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: Option<WorkspaceId>,
+        #[serde(default)]
+        globs: Vec<String>,
+    },
+    /// Removes the worktrees of workdirs archived more than `older_than_days` ago that are still
+    /// on disk.
+    StaleWorktreesDelete {
+        older_than_days: u32,
+    },
+    /// Deletes shared context blobs that no workdir references any more.
+    ContextBlobsGc,
+    DesktopNotificationsEnabledChanged {
+        enabled: bool,
+    },
+    NotificationQuietHoursChanged {
+        #[serde(default)]
+        quiet_hours: Option<QuietHoursSnapshot>,
+    },
+    ProjectNotificationsMutedChanged {
+        project_id: ProjectId,
+        muted: bool,
+    },
+    AttachmentTextLimitsChanged {
+        limits: AttachmentTextLimitsSnapshot,
+    },
+    UrlAttachmentDomainsChanged {
+        domains: Vec<String>,
+    },
+    /// Fetches an issue (`42`, `#42` or an issue URL) and creates a task seeded from it.
+    /// Answered with `task_executed`.
+    TaskImportIssue {
+        project_id: ProjectId,
+        issue: String,
+        mode: TaskExecuteMode,
+        #[serde(default)]
+        on_merge: IssueMergeAction,
+    },
+    TaskIssueMergeActionSet {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        on_merge: IssueMergeAction,
+    },
     TaskStarSet {
         #[serde(rename = "workdir_id", alias = "workspace_id")]
         workspace_id: WorkspaceId,
@@ -946,6 +1440,118 @@ pub enum ClientAction {
         thread_id: WorkspaceThreadId,
         task_status: TaskStatus,
     },
+    /// Applies one operation to many tasks and reports a result per item. Answered with
+    /// `task_bulk_applied`; repeating an `idempotency_key` replays the first result.
+    TaskBulkApply {
+        #[serde(default)]
+        idempotency_key: Option<String>,
+        items: Vec<TaskBulkItem>,
+        operation: TaskBulkOperation,
+    },
+    /// Reverts the status changes of an earlier `set_status` batch. Answered with
+    /// `task_bulk_applied`.
+    TaskBulkUndo {
+        batch_id: String,
+    },
+    /// Restores the task's worktree files to the checkpoint taken before a turn. With
+    /// `truncate_conversation`, that turn and every later entry are dropped as well.
+    TaskRewind {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        checkpoint_id: String,
+        #[serde(default)]
+        truncate_conversation: bool,
+    },
+    /// Starts a new task holding the conversation up to and including `entry_id`, along with
+    /// the task documents. The fork's agent session starts fresh, seeded with a summary.
+    TaskFork {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        entry_id: String,
+        #[serde(default)]
+        target: TaskForkTarget,
+    },
+    /// Reverts the most recent reversible entry of the audit log that has not been undone.
+    UndoLastAction,
+    DeletionRetentionChanged {
+        retention_days: u32,
+    },
+    ProjectTurnLimitsChanged {
+        project_id: ProjectId,
+        limits: TurnLimitsSnapshot,
+    },
+    TaskTurnLimitsChanged {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        limits: TurnLimitsSnapshot,
+    },
+    TaskReviewCommentAdd {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        path: String,
+        start_line: u32,
+        end_line: u32,
+        #[serde(default)]
+        quote: String,
+        body: String,
+    },
+    TaskReviewCommentEdit {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        comment_id: u64,
+        body: String,
+    },
+    TaskReviewCommentResolve {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        comment_id: u64,
+        resolved: bool,
+    },
+    /// Sends every pending review comment to the agent as one follow-up turn, led by `note`.
+    TaskReviewCommentsSend {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        #[serde(default)]
+        note: Option<String>,
+    },
+    /// Imports new comments from the unresolved review threads of the workdir's pull request.
+    TaskPullRequestReviewPull {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+    },
+    TaskPullRequestReviewSyncChanged {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        sync: PullRequestReviewSyncSnapshot,
+    },
+    /// Turns the task's CI auto-fix loop on or off. Turning it on resets the attempt count
+    /// and, if the pull request's CI is already failing, starts the first attempt.
+    TaskCiAutoFixChanged {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+        enabled: bool,
+        max_attempts: u32,
+    },
     FeedbackSubmit {
         title: String,
         body: String,
@@ -1051,6 +1657,8 @@ pub enum ClientAction {
         runner: Option<AgentRunnerKind>,
         #[serde(default)]
         amp_mode: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
     },
     CancelAndSendAgentMessage {
         #[serde(rename = "workdir_id", alias = "workspace_id")]
@@ -1124,6 +1732,12 @@ pub enum ClientAction {
         #[serde(rename = "task_id", alias = "thread_id")]
         thread_id: WorkspaceThreadId,
     },
+    ResumeInterruptedTurn {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+    },
     #[serde(rename = "create_task", alias = "create_workspace_thread")]
     CreateWorkspaceThread {
         #[serde(rename = "workdir_id", alias = "workspace_id")]
@@ -1206,6 +1820,13 @@ pub enum ClientAction {
     TaskPromptTemplateChanged {
         intent_kind: TaskIntentKind,
         template: String,
+        /// When set, stores a project-level override; an empty template clears it.
+        #[serde(default)]
+        project_id: Option<ProjectId>,
+    },
+    ProjectPromptVariablesChanged {
+        project_id: ProjectId,
+        variables: std::collections::BTreeMap<String, String>,
     },
     SystemPromptTemplateChanged {
         kind: SystemTaskKind,
@@ -1300,6 +1921,13 @@ pub enum ServerEvent {
     Toast {
         message: String,
     },
+    /// A desktop notification was clicked; clients should bring this task into view.
+    TaskFocusRequested {
+        #[serde(rename = "workdir_id", alias = "workspace_id")]
+        workspace_id: WorkspaceId,
+        #[serde(rename = "task_id", alias = "thread_id")]
+        thread_id: WorkspaceThreadId,
+    },
     ProjectPathPicked {
         request_id: String,
         path: Option<String>,
@@ -1314,6 +1942,10 @@ pub enum ServerEvent {
         request_id: String,
         result: TaskExecuteResult,
     },
+    TaskBulkApplied {
+        request_id: String,
+        result: TaskBulkResult,
+    },
     FeedbackSubmitted {
         request_id: String,
         result: FeedbackSubmitResult,
@@ -1427,4 +2059,6 @@ pub struct ThreadMeta {
     pub turn_status: TurnStatus,
     #[serde(default)]
     pub last_turn_result: Option<TurnResult>,
+    #[serde(default)]
+    pub forked_from: Option<TaskForkOrigin>,
 }
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS audit_log (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  created_at_unix_ms INTEGER NOT NULL,
+  origin TEXT NOT NULL,
+  action TEXT NOT NULL,
+  summary TEXT NOT NULL,
+  workspace_id INTEGER,
+  thread_local_id INTEGER,
+  undo_json TEXT,
+  undone_at_unix_ms INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_undoable
+  ON audit_log(id)
+  WHERE undo_json IS NOT NULL AND undone_at_unix_ms IS NULL;
+
+ALTER TABLE conversations ADD COLUMN deleted_at_unix_ms INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_conversations_deleted_at
+  ON conversations(deleted_at_unix_ms)
+  WHERE deleted_at_unix_ms IS NOT NULL;
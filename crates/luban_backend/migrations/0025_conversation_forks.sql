@@ -0,0 +1,3 @@
+ALTER TABLE conversations ADD COLUMN forked_from_workspace_id INTEGER;
+ALTER TABLE conversations ADD COLUMN forked_from_thread_local_id INTEGER;
+ALTER TABLE conversations ADD COLUMN forked_from_entry_id TEXT;
@@ -0,0 +1 @@
+ALTER TABLE workspaces ADD COLUMN archived_at INTEGER;
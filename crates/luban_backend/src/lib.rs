@@ -5,5 +5,5 @@ mod sqlite_store;
 mod test_support;
 mod time;
 
-pub use services::GitWorkspaceService;
+pub use services::{GitWorkspaceService, format_ci_fix_prompt, format_review_prompt};
 pub use sqlite_store::{SqliteStore, SqliteStoreOptions};
@@ -6,6 +6,7 @@ use luban_domain::{
     CreatedWorkspace, DroidConfigEntry, OpenTarget, PersistedAppState, ProjectWorkspaceService,
     PullRequestCiState, PullRequestInfo, PullRequestState, RunAgentTurnRequest, SystemTaskKind,
     TaskDocumentEvent, TaskDocumentEventType, TaskDocumentIndex, TaskDocumentKind, TaskIntentKind,
+    UrlAttachmentSettings,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -25,6 +26,8 @@ mod amp_cli;
 mod amp_mode;
 mod ansi;
 mod cancel_killer;
+mod checkpoint;
+mod ci_fix;
 mod claude_cli;
 pub mod claude_process;
 mod cli_check;
@@ -37,13 +40,19 @@ mod config_path;
 mod config_tree;
 mod context_blobs;
 mod conversations;
+mod disk_usage;
+mod document_text;
 mod droid_cli;
 mod feedback;
+mod forge;
 mod gh_cli;
 mod git;
 mod git_branch;
 mod github_url;
+mod issue_import;
+mod model_catalog;
 mod open_command;
+mod pr_review;
 mod prompt;
 mod pull_request;
 mod reconnect_notice;
@@ -53,6 +62,7 @@ mod task;
 #[cfg(test)]
 mod test_support;
 mod thread_io;
+mod url_snapshot;
 mod workspace_name;
 use amp_cli::AmpTurnParams;
 use amp_mode::detect_amp_mode_from_config_root;
@@ -66,6 +76,7 @@ use config_entries::{
 use droid_cli::DroidTurnParams;
 use git_branch::{branch_exists, normalize_branch_suffix};
 use prompt::{format_amp_prompt, format_codex_prompt, resolve_prompt_attachments};
+pub use prompt::{format_ci_fix_prompt, format_review_prompt};
 use pull_request::pull_request_ci_state_from_check_buckets;
 use reconnect_notice::is_transient_reconnect_notice;
 use roots::{
@@ -85,6 +96,7 @@ pub struct GitWorkspaceService {
     worktrees_root: PathBuf,
     conversations_root: PathBuf,
     task_prompts_root: PathBuf,
+    context_blobs_root: PathBuf,
     sqlite: SqliteStore,
 
     /// Persistent Claude processes mapped by (project_slug, workspace_name, thread_local_id).
@@ -106,6 +118,7 @@ impl GitWorkspaceService {
         let worktrees_root = paths::worktrees_root(&luban_root);
         let conversations_root = paths::conversations_root(&luban_root);
         let task_prompts_root = paths::task_prompts_root(&luban_root);
+        let context_blobs_root = paths::context_blobs_root(&luban_root);
         let sqlite_path = paths::sqlite_path(&luban_root);
         let sqlite = SqliteStore::new_with_options(sqlite_path, options)
             .context("failed to init sqlite store")?;
@@ -116,13 +129,17 @@ impl GitWorkspaceService {
             )
         })?;
 
-        Ok(Arc::new(Self {
+        let service = Self {
             worktrees_root,
             conversations_root,
             task_prompts_root,
+            context_blobs_root,
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
-        }))
+        };
+        // Best effort: anything left behind is picked up again on the next start.
+        let _ = service.migrate_workspace_context_blobs();
+        Ok(Arc::new(service))
     }
 
     fn generate_workspace_name(&self) -> anyhow::Result<String> {
@@ -136,6 +153,101 @@ impl GitWorkspaceService {
         path
     }
 
+    /// Adds a worktree on a new `luban/<name>` branch starting at `base_commit`, named after
+    /// `branch_name_hint` when it is usable and otherwise generated.
+    fn add_workspace_worktree(
+        &self,
+        project_path: &Path,
+        project_slug: &str,
+        branch_name_hint: Option<&str>,
+        base_commit: &str,
+    ) -> anyhow::Result<CreatedWorkspace> {
+        std::fs::create_dir_all(self.worktrees_root.join(project_slug))
+            .context("failed to create worktrees root")?;
+
+        if let Some(hint) = branch_name_hint.and_then(normalize_branch_suffix) {
+            for attempt in 0..64 {
+                let workspace_name = if attempt == 0 {
+                    hint.clone()
+                } else {
+                    format!("{hint}-v{}", attempt + 1)
+                };
+
+                let branch_name = format!("luban/{workspace_name}");
+                let worktree_path = self.worktree_path(project_slug, &workspace_name);
+
+                if worktree_path.exists() {
+                    continue;
+                }
+                if branch_exists(project_path, &branch_name) {
+                    continue;
+                }
+
+                self.run_git(
+                    project_path,
+                    [
+                        "worktree",
+                        "add",
+                        "-b",
+                        &branch_name,
+                        worktree_path
+                            .to_str()
+                            .ok_or_else(|| anyhow!("invalid worktree path"))?,
+                        base_commit,
+                    ],
+                )
+                .with_context(|| {
+                    format!("failed to create worktree at {}", worktree_path.display())
+                })?;
+
+                return Ok(CreatedWorkspace {
+                    workspace_name,
+                    branch_name,
+                    worktree_path,
+                });
+            }
+        }
+
+        for _ in 0..64 {
+            let workspace_name = self.generate_workspace_name()?;
+            let branch_name = format!("luban/{workspace_name}");
+            let worktree_path = self.worktree_path(project_slug, &workspace_name);
+
+            if worktree_path.exists() {
+                continue;
+            }
+
+            if branch_exists(project_path, &branch_name) {
+                continue;
+            }
+
+            self.run_git(
+                project_path,
+                [
+                    "worktree",
+                    "add",
+                    "-b",
+                    &branch_name,
+                    worktree_path
+                        .to_str()
+                        .ok_or_else(|| anyhow!("invalid worktree path"))?,
+                    base_commit,
+                ],
+            )
+            .with_context(|| format!("failed to create worktree at {}", worktree_path.display()))?;
+
+            return Ok(CreatedWorkspace {
+                workspace_name,
+                branch_name,
+                worktree_path,
+            });
+        }
+
+        Err(anyhow!(
+            "failed to generate a unique workspace name after retries"
+        ))
+    }
+
     fn task_prompt_template_path(&self, kind: TaskIntentKind) -> PathBuf {
         self.task_prompts_root.join(format!("{}.md", kind.as_key()))
     }
@@ -144,6 +256,44 @@ impl GitWorkspaceService {
         self.task_prompts_root.join(format!("{}.md", kind.as_key()))
     }
 
+    fn project_prompts_dir(&self, project_slug: &str) -> PathBuf {
+        self.task_prompts_root.join("projects").join(project_slug)
+    }
+
+    fn project_prompt_variables_path(&self, project_slug: &str) -> PathBuf {
+        self.project_prompts_dir(project_slug)
+            .join("variables.json")
+    }
+
+    fn write_project_prompt_file(
+        &self,
+        project_slug: &str,
+        file_name: &str,
+        contents: &[u8],
+    ) -> anyhow::Result<()> {
+        let dir = self.project_prompts_dir(project_slug);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create project prompts dir {}", dir.display()))?;
+
+        let path = dir.join(file_name);
+        let tmp = dir.join(format!(
+            ".{}.{}.{}.tmp",
+            file_name,
+            std::process::id(),
+            unix_epoch_nanos_now()
+        ));
+        std::fs::write(&tmp, contents)
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        if std::fs::rename(&tmp, &path).is_err() {
+            if path.exists() {
+                let _ = std::fs::remove_file(&path);
+            }
+            std::fs::rename(&tmp, &path)
+                .with_context(|| format!("failed to replace {}", path.display()))?;
+        }
+        Ok(())
+    }
+
     fn codex_executable(&self) -> PathBuf {
         codex_bin::codex_executable()
     }
@@ -416,95 +566,37 @@ impl ProjectWorkspaceService for GitWorkspaceService {
                 )
                 .context("failed to resolve origin/main commit")?;
 
-            std::fs::create_dir_all(self.worktrees_root.join(&project_slug))
-                .context("failed to create worktrees root")?;
-
-            if let Some(hint) = branch_name_hint
-                .as_deref()
-                .and_then(normalize_branch_suffix)
-            {
-                for attempt in 0..64 {
-                    let workspace_name = if attempt == 0 {
-                        hint.clone()
-                    } else {
-                        format!("{hint}-v{}", attempt + 1)
-                    };
-
-                    let branch_name = format!("luban/{workspace_name}");
-                    let worktree_path = self.worktree_path(&project_slug, &workspace_name);
-
-                    if worktree_path.exists() {
-                        continue;
-                    }
-                    if branch_exists(&project_path, &branch_name) {
-                        continue;
-                    }
-
-                    self.run_git(
-                        &project_path,
-                        [
-                            "worktree",
-                            "add",
-                            "-b",
-                            &branch_name,
-                            worktree_path
-                                .to_str()
-                                .ok_or_else(|| anyhow!("invalid worktree path"))?,
-                            upstream_commit.trim(),
-                        ],
-                    )
-                    .with_context(|| {
-                        format!("failed to create worktree at {}", worktree_path.display())
-                    })?;
-
-                    return Ok(CreatedWorkspace {
-                        workspace_name,
-                        branch_name,
-                        worktree_path,
-                    });
-                }
-            }
-
-            for _ in 0..64 {
-                let workspace_name = self.generate_workspace_name()?;
-                let branch_name = format!("luban/{workspace_name}");
-                let worktree_path = self.worktree_path(&project_slug, &workspace_name);
-
-                if worktree_path.exists() {
-                    continue;
-                }
+            self.add_workspace_worktree(
+                &project_path,
+                &project_slug,
+                branch_name_hint.as_deref(),
+                upstream_commit.trim(),
+            )
+        })();
 
-                if branch_exists(&project_path, &branch_name) {
-                    continue;
-                }
+        result.map_err(anyhow_error_to_string)
+    }
 
-                self.run_git(
-                    &project_path,
-                    [
-                        "worktree",
-                        "add",
-                        "-b",
-                        &branch_name,
-                        worktree_path
-                            .to_str()
-                            .ok_or_else(|| anyhow!("invalid worktree path"))?,
-                        upstream_commit.trim(),
-                    ],
+    fn create_workspace_from_worktree(
+        &self,
+        project_path: PathBuf,
+        project_slug: String,
+        source_worktree_path: PathBuf,
+        branch_name_hint: Option<String>,
+    ) -> Result<CreatedWorkspace, String> {
+        let result: anyhow::Result<CreatedWorkspace> = (|| {
+            let head = self
+                .run_git(
+                    &source_worktree_path,
+                    ["rev-parse", "--verify", "HEAD^{commit}"],
                 )
-                .with_context(|| {
-                    format!("failed to create worktree at {}", worktree_path.display())
-                })?;
-
-                return Ok(CreatedWorkspace {
-                    workspace_name,
-                    branch_name,
-                    worktree_path,
-                });
-            }
-
-            Err(anyhow!(
-                "failed to generate a unique workspace name after retries"
-            ))
+                .context("failed to resolve the source worktree HEAD")?;
+            self.add_workspace_worktree(
+                &project_path,
+                &project_slug,
+                branch_name_hint.as_deref(),
+                head.trim(),
+            )
         })();
 
         result.map_err(anyhow_error_to_string)
@@ -598,25 +690,7 @@ impl ProjectWorkspaceService for GitWorkspaceService {
         branch_name: String,
     ) -> Result<(), String> {
         let result: anyhow::Result<()> = (|| {
-            let path_str = worktree_path
-                .to_str()
-                .ok_or_else(|| anyhow!("invalid worktree path"))?;
-
-            let remove_result =
-                self.run_git(&project_path, ["worktree", "remove", "--force", path_str]);
-            if let Err(err) = remove_result {
-                let message = format!("{err:#}");
-                let missing = message.contains("not a working tree")
-                    || message.contains("is not a working tree")
-                    || message.contains("is not a git repository")
-                    || message.contains("no such file or directory")
-                    || message.contains("No such file or directory");
-                if !missing {
-                    return Err(err).with_context(|| {
-                        format!("failed to remove worktree at {}", worktree_path.display())
-                    });
-                }
-            }
+            self.remove_worktree(&project_path, &worktree_path)?;
 
             let branch_name = branch_name.trim();
             if branch_name.starts_with("luban/") && branch_name != "main" {
@@ -908,6 +982,100 @@ impl ProjectWorkspaceService for GitWorkspaceService {
             .map_err(anyhow_error_to_string)
     }
 
+    fn set_conversation_thread_deleted(
+        &self,
+        project_slug: String,
+        workspace_name: String,
+        thread_id: u64,
+        deleted_at_unix_ms: Option<u64>,
+    ) -> Result<(), String> {
+        self.sqlite
+            .set_conversation_thread_deleted(
+                project_slug,
+                workspace_name,
+                thread_id,
+                deleted_at_unix_ms,
+            )
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn truncate_conversation_at_checkpoint(
+        &self,
+        project_slug: String,
+        workspace_name: String,
+        thread_id: u64,
+        checkpoint_id: String,
+    ) -> Result<u64, String> {
+        self.sqlite
+            .truncate_conversation_at_checkpoint(
+                project_slug,
+                workspace_name,
+                thread_id,
+                checkpoint_id,
+            )
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn fork_conversation_thread(
+        &self,
+        project_slug: String,
+        source_workspace_name: String,
+        workspace_name: String,
+        thread_id: u64,
+        origin: luban_domain::ThreadForkOrigin,
+    ) -> Result<(), String> {
+        self.sqlite
+            .fork_conversation_thread(
+                project_slug,
+                source_workspace_name,
+                workspace_name,
+                thread_id,
+                origin,
+            )
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn purge_deleted_conversation_threads(
+        &self,
+        deleted_before_unix_ms: u64,
+    ) -> Result<u64, String> {
+        self.sqlite
+            .purge_deleted_conversation_threads(deleted_before_unix_ms)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn append_audit_entry(
+        &self,
+        origin: luban_domain::AuditOrigin,
+        record: luban_domain::AuditRecord,
+    ) -> Result<luban_domain::AuditEntry, String> {
+        self.sqlite
+            .append_audit_entry(origin, record)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn list_audit_entries(
+        &self,
+        before_id: Option<u64>,
+        limit: u64,
+    ) -> Result<Vec<luban_domain::AuditEntry>, String> {
+        self.sqlite
+            .list_audit_entries(before_id, limit)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn latest_undoable_audit_entry(&self) -> Result<Option<luban_domain::AuditEntry>, String> {
+        self.sqlite
+            .latest_undoable_audit_entry()
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn mark_audit_entry_undone(&self, entry_id: u64, undone_at_unix_ms: u64) -> Result<(), String> {
+        self.sqlite
+            .mark_audit_entry_undone(entry_id, undone_at_unix_ms)
+            .map_err(anyhow_error_to_string)
+    }
+
     fn save_conversation_queue_state(
         &self,
         project_slug: String,
@@ -1009,24 +1177,15 @@ impl ProjectWorkspaceService for GitWorkspaceService {
 
     fn store_context_image(
         &self,
-        project_slug: String,
-        workspace_name: String,
+        _project_slug: String,
+        _workspace_name: String,
         image: ContextImage,
     ) -> Result<AttachmentRef, String> {
         let byte_len = image.bytes.len() as u64;
-        let stored: anyhow::Result<(String, PathBuf)> = self.store_context_bytes(
-            &project_slug,
-            &workspace_name,
-            &image.bytes,
-            &image.extension,
-        );
+        let stored: anyhow::Result<(String, PathBuf)> =
+            self.store_context_bytes(&image.bytes, &image.extension);
         let (id, stored_path) = stored.map_err(anyhow_error_to_string)?;
-        let _ = self.maybe_store_context_image_thumbnail(
-            &project_slug,
-            &workspace_name,
-            &stored_path,
-            &image.bytes,
-        );
+        let _ = self.maybe_store_context_image_thumbnail(&stored_path, &image.bytes);
         let extension = stored_path
             .extension()
             .and_then(|s| s.to_str())
@@ -1044,15 +1203,15 @@ impl ProjectWorkspaceService for GitWorkspaceService {
 
     fn store_context_text(
         &self,
-        project_slug: String,
-        workspace_name: String,
+        _project_slug: String,
+        _workspace_name: String,
         text: String,
         extension: String,
     ) -> Result<AttachmentRef, String> {
         let bytes = text.into_bytes();
         let byte_len = bytes.len() as u64;
         let result: anyhow::Result<(String, PathBuf)> =
-            self.store_context_bytes(&project_slug, &workspace_name, &bytes, &extension);
+            self.store_context_bytes(&bytes, &extension);
         let (id, stored_path) = result.map_err(anyhow_error_to_string)?;
         let extension = stored_path
             .extension()
@@ -1071,8 +1230,8 @@ impl ProjectWorkspaceService for GitWorkspaceService {
 
     fn store_context_file(
         &self,
-        project_slug: String,
-        workspace_name: String,
+        _project_slug: String,
+        _workspace_name: String,
         source_path: PathBuf,
     ) -> Result<AttachmentRef, String> {
         let name = source_path
@@ -1081,7 +1240,7 @@ impl ProjectWorkspaceService for GitWorkspaceService {
             .unwrap_or("file")
             .to_owned();
         let result: anyhow::Result<(String, String, u64, PathBuf)> =
-            self.store_context_file_internal(&project_slug, &workspace_name, &source_path);
+            self.store_context_file_internal(&source_path);
         let (id, extension, byte_len, _path) = result.map_err(anyhow_error_to_string)?;
         Ok(AttachmentRef {
             id,
@@ -1093,6 +1252,17 @@ impl ProjectWorkspaceService for GitWorkspaceService {
         })
     }
 
+    fn store_context_url(
+        &self,
+        _project_slug: String,
+        _workspace_name: String,
+        url: String,
+        settings: UrlAttachmentSettings,
+    ) -> Result<AttachmentRef, String> {
+        self.store_context_url_snapshot(&url, &settings)
+            .map_err(anyhow_error_to_string)
+    }
+
     fn record_context_item(
         &self,
         project_slug: String,
@@ -1126,6 +1296,68 @@ impl ProjectWorkspaceService for GitWorkspaceService {
             .map_err(anyhow_error_to_string)
     }
 
+    fn create_turn_checkpoint(
+        &self,
+        worktree_path: PathBuf,
+        ref_name: String,
+    ) -> Result<String, String> {
+        checkpoint::create_turn_checkpoint(&worktree_path, &ref_name)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn snapshot_worktree_tree(&self, worktree_path: PathBuf) -> Result<String, String> {
+        checkpoint::worktree_tree(&worktree_path).map_err(anyhow_error_to_string)
+    }
+
+    fn restore_turn_checkpoint(
+        &self,
+        worktree_path: PathBuf,
+        ref_name: String,
+    ) -> Result<(), String> {
+        checkpoint::restore_turn_checkpoint(&worktree_path, &ref_name)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn delete_turn_checkpoints(
+        &self,
+        repo_path: PathBuf,
+        ref_prefix: String,
+    ) -> Result<(), String> {
+        checkpoint::delete_turn_checkpoints(&repo_path, &ref_prefix).map_err(anyhow_error_to_string)
+    }
+
+    fn workspace_disk_usage(
+        &self,
+        project_slug: String,
+        workspace_name: String,
+        worktree_path: PathBuf,
+    ) -> Result<luban_domain::WorkspaceDiskUsage, String> {
+        Ok(self.measure_workspace_disk_usage(&project_slug, &workspace_name, &worktree_path))
+    }
+
+    fn purge_build_artifacts(
+        &self,
+        worktree_path: PathBuf,
+        globs: Vec<String>,
+    ) -> Result<luban_domain::DiskCleanupReport, String> {
+        self.purge_build_artifacts_internal(&worktree_path, &globs)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn delete_stale_worktrees(
+        &self,
+        archived: Vec<luban_domain::ArchivedWorktree>,
+        keep: Vec<PathBuf>,
+    ) -> Result<luban_domain::DiskCleanupReport, String> {
+        self.delete_stale_worktrees_internal(&archived, &keep)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn gc_context_blobs(&self) -> Result<luban_domain::DiskCleanupReport, String> {
+        self.gc_context_blobs_internal()
+            .map_err(anyhow_error_to_string)
+    }
+
     fn list_new_task_drafts(&self) -> Result<Vec<luban_domain::NewTaskDraft>, String> {
         self.sqlite
             .list_new_task_drafts()
@@ -1258,6 +1490,8 @@ impl ProjectWorkspaceService for GitWorkspaceService {
             amp_mode,
             model,
             model_reasoning_effort,
+            attachment_text_limits,
+            checkpoint,
         } = request;
 
         let turn_started_at = Instant::now();
@@ -1295,23 +1529,36 @@ impl ProjectWorkspaceService for GitWorkspaceService {
                 existing_thread_id = Some(legacy_thread_id);
             }
 
+            let mut turn_entries = vec![ConversationEntry::UserEvent {
+                entry_id: String::new(),
+                created_at_unix_ms: 0,
+                event: luban_domain::UserEvent::Message {
+                    text: prompt.clone(),
+                    attachments: attachments.clone(),
+                    author: None,
+                },
+            }];
+            if let Some(checkpoint) = checkpoint {
+                turn_entries.push(ConversationEntry::SystemEvent {
+                    entry_id: String::new(),
+                    created_at_unix_ms: 0,
+                    event: luban_domain::ConversationSystemEvent::TurnCheckpoint {
+                        checkpoint_id: checkpoint.checkpoint_id,
+                        commit: checkpoint.commit,
+                    },
+                });
+            }
             self.sqlite.append_conversation_entries(
                 project_slug.clone(),
                 workspace_name.clone(),
                 thread_local_id,
-                vec![ConversationEntry::UserEvent {
-                    entry_id: String::new(),
-                    created_at_unix_ms: 0,
-                    event: luban_domain::UserEvent::Message {
-                        text: prompt.clone(),
-                        attachments: attachments.clone(),
-                    },
-                }],
+                turn_entries,
             )?;
 
             let resolved_thread_id = thread_id.or(existing_thread_id);
-            let blobs_dir = self.context_blobs_dir(&project_slug, &workspace_name);
-            let prompt_attachments = resolve_prompt_attachments(&blobs_dir, &attachments);
+            let blobs_dir = self.context_blobs_dir();
+            let mut prompt_attachments = resolve_prompt_attachments(&blobs_dir, &attachments);
+            self.attach_context_document_texts(&mut prompt_attachments, attachment_text_limits);
             let image_paths = prompt_attachments
                 .iter()
                 .filter(|a| a.kind == AttachmentKind::Image)
@@ -2297,6 +2544,10 @@ impl ProjectWorkspaceService for GitWorkspaceService {
         &self,
         worktree_path: PathBuf,
     ) -> Result<Option<PullRequestInfo>, String> {
+        if let Some(result) = self.forge_pull_request(&worktree_path) {
+            return Ok(result.map_err(anyhow_error_to_string)?.map(|pr| pr.info));
+        }
+
         #[derive(Clone, serde::Deserialize)]
         struct GhPullRequestCheck {
             #[serde(default)]
@@ -2407,10 +2658,37 @@ impl ProjectWorkspaceService for GitWorkspaceService {
         }))
     }
 
-    fn gh_open_pull_request(&self, worktree_path: PathBuf) -> Result<(), String> {
+    fn gh_pull_request_url(&self, worktree_path: PathBuf) -> Result<Option<String>, String> {
+        if let Some(result) = self.forge_pull_request(&worktree_path) {
+            return Ok(result.map_err(anyhow_error_to_string)?.map(|pr| pr.web_url));
+        }
+
         let output = Command::new("gh")
-            .args(["pr", "view", "--web"])
-            .current_dir(worktree_path)
+            .args(["pr", "view", "--json", "url", "--jq", ".url"])
+            .current_dir(&worktree_path)
+            .output();
+
+        let Ok(output) = output else {
+            return Ok(None);
+        };
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let url = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        Ok((!url.is_empty()).then_some(url))
+    }
+
+    fn gh_open_pull_request(&self, worktree_path: PathBuf) -> Result<(), String> {
+        if let Some(result) = self.forge_pull_request(&worktree_path) {
+            let pr = result
+                .map_err(anyhow_error_to_string)?
+                .ok_or_else(|| "No pull request found for this branch".to_owned())?;
+            return self.open_url(&pr.web_url).map_err(anyhow_error_to_string);
+        }
+
+        let output = Command::new("gh")
+            .args(["pr", "view", "--web"])
+            .current_dir(worktree_path)
             .output();
 
         let Ok(output) = output else {
@@ -2429,6 +2707,14 @@ impl ProjectWorkspaceService for GitWorkspaceService {
     }
 
     fn gh_open_pull_request_failed_action(&self, worktree_path: PathBuf) -> Result<(), String> {
+        if let Some(result) = self.forge_pull_request(&worktree_path) {
+            let pr = result
+                .map_err(anyhow_error_to_string)?
+                .ok_or_else(|| "No pull request found for this branch".to_owned())?;
+            let url = pr.failing_check_url.as_deref().unwrap_or(&pr.web_url);
+            return self.open_url(url).map_err(anyhow_error_to_string);
+        }
+
         #[derive(serde::Deserialize)]
         struct GhPullRequestCheck {
             #[serde(default)]
@@ -2510,6 +2796,54 @@ impl ProjectWorkspaceService for GitWorkspaceService {
         feedback::feedback_task_prompt(self, issue, intent_kind).map_err(anyhow_error_to_string)
     }
 
+    fn issue_import(
+        &self,
+        project_path: PathBuf,
+        issue_ref: String,
+    ) -> Result<luban_domain::ImportedIssue, String> {
+        self.import_issue(&project_path, &issue_ref)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn issue_resolve_on_merge(
+        &self,
+        issue_url: String,
+        action: luban_domain::IssueMergeAction,
+        pr_number: u64,
+    ) -> Result<(), String> {
+        self.resolve_issue_on_merge(&issue_url, action, pr_number)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn pull_request_review_comments(
+        &self,
+        worktree_path: PathBuf,
+        pr_number: u64,
+    ) -> Result<Vec<luban_domain::PullRequestReviewComment>, String> {
+        self.fetch_pull_request_review_comments(&worktree_path, pr_number)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn pull_request_review_comment_reply(
+        &self,
+        worktree_path: PathBuf,
+        pr_number: u64,
+        comment_id: u64,
+        body: String,
+    ) -> Result<(), String> {
+        self.reply_to_pull_request_review_comment(&worktree_path, pr_number, comment_id, &body)
+            .map_err(anyhow_error_to_string)
+    }
+
+    fn ci_failure_log(
+        &self,
+        worktree_path: PathBuf,
+        pr_number: u64,
+    ) -> Result<Option<luban_domain::CiFailureLog>, String> {
+        self.fetch_ci_failure_log(&worktree_path, pr_number)
+            .map_err(anyhow_error_to_string)
+    }
+
     fn task_prompt_templates_load(
         &self,
     ) -> Result<std::collections::HashMap<TaskIntentKind, String>, String> {
@@ -2697,6 +3031,153 @@ impl ProjectWorkspaceService for GitWorkspaceService {
         }
     }
 
+    fn model_catalog_load(&self) -> Result<luban_domain::LoadedModelCatalog, String> {
+        let codex_config = resolve_codex_root()
+            .ok()
+            .and_then(|root| config_file_io::read_small_utf8_file(&root.join("config.toml")).ok());
+        let droid_help = droid_cli::droid_exec_help();
+
+        let mut warnings = Vec::new();
+        let models_path = resolve_luban_root()
+            .map(|root| paths::models_path(&root))
+            .map_err(anyhow_error_to_string)?;
+        let models_file = match std::fs::read_to_string(&models_path) {
+            Ok(contents) => Some(contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                warnings.push(format!(
+                    "{:#}",
+                    anyhow!(err).context(format!("failed to read {}", models_path.display()))
+                ));
+                None
+            }
+        };
+
+        let (catalog, models_file_error) =
+            model_catalog::build_model_catalog(model_catalog::ModelCatalogSources {
+                codex_config,
+                droid_help,
+                models_file,
+            });
+        if let Some(err) = models_file_error {
+            warnings.push(format!(
+                "{:#}",
+                err.context(format!("ignoring {}", models_path.display()))
+            ));
+        }
+        Ok(luban_domain::LoadedModelCatalog { catalog, warnings })
+    }
+
+    fn project_prompt_settings_load(
+        &self,
+        project_slug: String,
+    ) -> Result<luban_domain::ProjectPromptSettings, String> {
+        fn inner(
+            service: &GitWorkspaceService,
+            project_slug: &str,
+        ) -> anyhow::Result<luban_domain::ProjectPromptSettings> {
+            let dir = service.project_prompts_dir(project_slug);
+            let mut settings = luban_domain::ProjectPromptSettings::default();
+            for kind in TaskIntentKind::ALL {
+                let path = dir.join(format!("{}.md", kind.as_key()));
+                let contents = match std::fs::read_to_string(&path) {
+                    Ok(contents) => contents,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) => {
+                        return Err(anyhow!(err).context(format!(
+                            "failed to read project prompt template {}",
+                            path.display()
+                        )));
+                    }
+                };
+                let trimmed = contents.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                settings
+                    .task_prompt_templates
+                    .insert(kind, trimmed.to_owned());
+            }
+
+            let path = service.project_prompt_variables_path(project_slug);
+            match std::fs::read(&path) {
+                Ok(bytes) => {
+                    settings.variables = serde_json::from_slice(&bytes).with_context(|| {
+                        format!(
+                            "failed to parse project prompt variables {}",
+                            path.display()
+                        )
+                    })?;
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(anyhow!(err).context(format!("failed to read {}", path.display())));
+                }
+            }
+
+            Ok(settings)
+        }
+
+        inner(self, &project_slug).map_err(anyhow_error_to_string)
+    }
+
+    fn project_task_prompt_template_store(
+        &self,
+        project_slug: String,
+        intent_kind: TaskIntentKind,
+        template: String,
+    ) -> Result<(), String> {
+        let mut normalized = template;
+        if !normalized.ends_with('\n') {
+            normalized.push('\n');
+        }
+        self.write_project_prompt_file(
+            &project_slug,
+            &format!("{}.md", intent_kind.as_key()),
+            normalized.as_bytes(),
+        )
+        .map_err(anyhow_error_to_string)
+    }
+
+    fn project_task_prompt_template_delete(
+        &self,
+        project_slug: String,
+        intent_kind: TaskIntentKind,
+    ) -> Result<(), String> {
+        let path = self
+            .project_prompts_dir(&project_slug)
+            .join(format!("{}.md", intent_kind.as_key()));
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(format!(
+                "{:#}",
+                anyhow!(err).context(format!("failed to remove {}", path.display()))
+            )),
+        }
+    }
+
+    fn project_prompt_variables_store(
+        &self,
+        project_slug: String,
+        variables: std::collections::BTreeMap<String, String>,
+    ) -> Result<(), String> {
+        if variables.is_empty() {
+            let path = self.project_prompt_variables_path(&project_slug);
+            return match std::fs::remove_file(&path) {
+                Ok(()) => Ok(()),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(err) => Err(format!(
+                    "{:#}",
+                    anyhow!(err).context(format!("failed to remove {}", path.display()))
+                )),
+            };
+        }
+        let bytes = serde_json::to_vec_pretty(&variables).map_err(|err| err.to_string())?;
+        self.write_project_prompt_file(&project_slug, "variables.json", &bytes)
+            .map_err(anyhow_error_to_string)
+    }
+
     fn task_suggest_branch_name(
         &self,
         input: String,
@@ -3168,7 +3649,7 @@ impl GitWorkspaceService {
 #[cfg(test)]
 mod tests {
     use super::codex_thread::qualify_codex_item;
-    use super::prompt::PromptAttachment;
+    use super::prompt::{PromptAttachment, PromptAttachmentText};
     use super::pull_request::is_merge_ready;
     use super::test_support::{
         EnvVarGuard, assert_git_success, git_rev_parse, lock_env, run_git, stored_blob_path,
@@ -3195,11 +3676,13 @@ mod tests {
                 kind: AttachmentKind::Image,
                 name: "a.png".to_owned(),
                 path: PathBuf::from("images/a.png"),
+                text: None,
             },
             PromptAttachment {
                 kind: AttachmentKind::File,
                 name: "b.bin".to_owned(),
                 path: PathBuf::from("/tmp/b.bin"),
+                text: None,
             },
         ];
         let formatted = format_amp_prompt(prompt, &attachments);
@@ -3208,6 +3691,77 @@ mod tests {
         assert!(formatted.contains("- b.bin: @/tmp/b.bin\n"));
     }
 
+    #[test]
+    fn review_prompt_renders_each_comment_for_the_runner() {
+        let comments = vec![
+            luban_domain::ReviewComment {
+                id: 1,
+                path: "src/lib.rs".to_owned(),
+                start_line: 3,
+                end_line: 4,
+                quote: "let a = 1;\nlet b = 2;".to_owned(),
+                body: "Use a const.".to_owned(),
+                resolved: false,
+                sent: false,
+                created_at_unix_ms: 0,
+                updated_at_unix_ms: 0,
+                pull_request: None,
+            },
+            luban_domain::ReviewComment {
+                id: 2,
+                path: "README.md".to_owned(),
+                start_line: 9,
+                end_line: 9,
+                quote: (0..20).map(|i| format!("line {i}\n")).collect(),
+                body: "Drop this section.".to_owned(),
+                resolved: false,
+                sent: false,
+                created_at_unix_ms: 0,
+                updated_at_unix_ms: 0,
+                pull_request: None,
+            },
+            luban_domain::ReviewComment::from_pull_request(
+                3,
+                12,
+                luban_domain::PullRequestReviewComment {
+                    comment_id: 901,
+                    author: "octocat".to_owned(),
+                    path: "Cargo.toml".to_owned(),
+                    start_line: 0,
+                    end_line: 0,
+                    body: "Why this dependency?".to_owned(),
+                    url: String::new(),
+                    diff_hunk: "@@ -1 +1,2 @@\n+serde = \"1\"".to_owned(),
+                },
+                0,
+            ),
+        ];
+
+        let codex = format_review_prompt(
+            Some("Fix these"),
+            &comments,
+            luban_domain::AgentRunnerKind::Codex,
+        );
+        assert!(codex.starts_with("Fix these\n\n1. src/lib.rs:3-4\n> let a = 1;\n> let b = 2;\n"));
+        assert!(codex.contains("\n2. README.md:9\n> line 0\n"));
+        assert!(codex.contains("> line 11\n> …\nDrop this section.\n"));
+        assert!(!codex.contains("line 12"));
+        assert!(
+            codex.ends_with("\n3. Cargo.toml (from octocat on PR #12)\nWhy this dependency?\n")
+        );
+
+        let amp = format_review_prompt(None, &comments, luban_domain::AgentRunnerKind::Amp);
+        assert!(amp.contains("1. @src/lib.rs:3-4\n"));
+
+        let claude = format_review_prompt(None, &comments, luban_domain::AgentRunnerKind::Claude);
+        assert!(claude.contains(
+            "<review_comment file=\"src/lib.rs\" lines=\"3-4\">\n<code>\nlet a = 1;\nlet b = 2;\n</code>\nUse a const.\n</review_comment>\n"
+        ));
+        assert!(claude.contains(
+            "<review_comment file=\"Cargo.toml\" author=\"octocat\">\nWhy this dependency?\n</review_comment>\n"
+        ));
+    }
+
     #[test]
     fn codex_prompt_includes_attachment_paths_without_amp_marker() {
         let prompt = "Hello";
@@ -3216,11 +3770,13 @@ mod tests {
                 kind: AttachmentKind::Text,
                 name: "notes.txt".to_owned(),
                 path: PathBuf::from("/tmp/notes.txt"),
+                text: None,
             },
             PromptAttachment {
                 kind: AttachmentKind::Image,
                 name: "image.png".to_owned(),
                 path: PathBuf::from("/tmp/image.png"),
+                text: None,
             },
         ];
         let formatted = format_codex_prompt(prompt, &attachments);
@@ -3231,6 +3787,29 @@ mod tests {
         assert!(!formatted.contains("@/tmp/image.png"));
     }
 
+    #[test]
+    fn codex_prompt_inlines_extracted_document_text() {
+        let attachments = vec![PromptAttachment {
+            kind: AttachmentKind::File,
+            name: "spec.pdf".to_owned(),
+            path: PathBuf::from("/blobs/abc.pdf"),
+            text: Some(PromptAttachmentText::new(
+                PathBuf::from("/blobs/abc.pdf.txt"),
+                "Section 1\n```code```\nSection 2",
+                20,
+            )),
+        }];
+        let formatted = format_codex_prompt("Summarize", &attachments);
+        assert!(
+            formatted.contains("- spec.pdf: /blobs/abc.pdf (extracted text: /blobs/abc.pdf.txt)\n")
+        );
+        assert!(formatted.contains("Text extracted from spec.pdf (truncated;"));
+        assert!(formatted.ends_with("\n````\nSection 1\n```code```\n````\n"));
+
+        let path_only = PromptAttachmentText::new(PathBuf::from("/blobs/abc.pdf.txt"), "text", 0);
+        assert!(path_only.inline.is_none());
+    }
+
     #[test]
     fn gh_pr_check_bucket_ci_state_mapping() {
         assert_eq!(
@@ -3348,6 +3927,72 @@ mod tests {
         let _ = std::fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn project_prompt_settings_roundtrip_via_files() {
+        let _guard = lock_env();
+
+        let unique = unix_epoch_nanos_now();
+        let root = std::env::temp_dir().join(format!(
+            "luban-project-prompts-{}-{}",
+            std::process::id(),
+            unique
+        ));
+        std::fs::create_dir_all(&root).expect("temp dir should be created");
+        {
+            let _env = EnvVarGuard::set(paths::LUBAN_ROOT_ENV, root.as_os_str());
+
+            let service = GitWorkspaceService::new().expect("service should init");
+            service
+                .project_task_prompt_template_store(
+                    "repo".to_owned(),
+                    TaskIntentKind::Review,
+                    "review {{vars.checklist}}".to_owned(),
+                )
+                .expect("store should succeed");
+            service
+                .project_prompt_variables_store(
+                    "repo".to_owned(),
+                    std::collections::BTreeMap::from([(
+                        "checklist".to_owned(),
+                        "- run tests".to_owned(),
+                    )]),
+                )
+                .expect("store variables should succeed");
+
+            let loaded = service
+                .project_prompt_settings_load("repo".to_owned())
+                .expect("load should succeed");
+            assert_eq!(
+                loaded
+                    .task_prompt_templates
+                    .get(&TaskIntentKind::Review)
+                    .map(String::as_str),
+                Some("review {{vars.checklist}}")
+            );
+            assert_eq!(
+                loaded.variables.get("checklist").map(String::as_str),
+                Some("- run tests")
+            );
+
+            let other = service
+                .project_prompt_settings_load("other".to_owned())
+                .expect("load should succeed");
+            assert!(other.is_empty());
+
+            service
+                .project_task_prompt_template_delete("repo".to_owned(), TaskIntentKind::Review)
+                .expect("delete should succeed");
+            service
+                .project_prompt_variables_store("repo".to_owned(), Default::default())
+                .expect("clear variables should succeed");
+            let loaded = service
+                .project_prompt_settings_load("repo".to_owned())
+                .expect("load should succeed");
+            assert!(loaded.is_empty());
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn codex_config_tree_is_shallow_and_codex_config_list_dir_pages() {
         let _guard = lock_env();
@@ -3381,6 +4026,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -3456,6 +4102,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -3522,6 +4169,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -3618,6 +4266,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -3685,6 +4334,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -3758,6 +4408,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -3817,6 +4468,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -3940,6 +4592,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4036,6 +4689,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4054,6 +4708,8 @@ mod tests {
                     amp_mode: None,
                     model: None,
                     model_reasoning_effort: None,
+                    attachment_text_limits: luban_domain::AttachmentTextLimits::default(),
+                    checkpoint: None,
                 },
                 Arc::new(AtomicBool::new(false)),
                 Arc::new(|_event| {}),
@@ -4154,6 +4810,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4172,6 +4829,8 @@ mod tests {
                     amp_mode: None,
                     model: None,
                     model_reasoning_effort: None,
+                    attachment_text_limits: luban_domain::AttachmentTextLimits::default(),
+                    checkpoint: None,
                 },
                 Arc::new(AtomicBool::new(false)),
                 Arc::new(|_event| {}),
@@ -4327,6 +4986,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4393,6 +5053,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4473,6 +5134,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4492,6 +5154,7 @@ mod tests {
                     worktree_path: worktree_path.clone(),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: None,
@@ -4529,6 +5192,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
 
         service
@@ -4628,6 +5301,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4680,6 +5354,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4700,7 +5375,7 @@ mod tests {
         assert_eq!(stored.extension, "png");
         assert_eq!(stored.id, blake3::hash(bytes).to_hex().to_string());
         assert!(
-            stored_blob_path(&service, "proj", "main", &stored).exists(),
+            stored_blob_path(&service, &stored).exists(),
             "stored blob should exist"
         );
 
@@ -4725,6 +5400,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4747,7 +5423,7 @@ mod tests {
             blake3::hash(b"not-a-real-png").to_hex().to_string()
         );
         assert!(
-            stored_blob_path(&service, "proj", "main", &stored).exists(),
+            stored_blob_path(&service, &stored).exists(),
             "stored blob should exist"
         );
 
@@ -4772,6 +5448,7 @@ mod tests {
             worktrees_root: paths::worktrees_root(&base_dir),
             conversations_root: paths::conversations_root(&base_dir),
             task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
             sqlite,
             claude_processes: Mutex::new(HashMap::new()),
         };
@@ -4795,7 +5472,7 @@ mod tests {
         )
         .expect("store_context_image should succeed");
 
-        let stored_path = stored_blob_path(&service, "proj", "main", &stored);
+        let stored_path = stored_blob_path(&service, &stored);
         let thumb = stored_path.with_file_name(format!("{}-thumb.png", stored.id));
 
         assert!(stored_path.exists(), "stored path should exist");
@@ -4821,5 +5498,331 @@ mod tests {
         let _ = std::fs::remove_dir_all(&base_dir);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn disk_cleanup_purges_artifacts_stale_worktrees_and_orphaned_blobs() {
+        let unique = unix_epoch_nanos_now();
+        let base_dir = temp_services_dir(unique);
+        std::fs::create_dir_all(&base_dir).expect("luban root should exist");
+        let sqlite =
+            SqliteStore::new(paths::sqlite_path(&base_dir)).expect("sqlite init should work");
+        let service = GitWorkspaceService {
+            worktrees_root: paths::worktrees_root(&base_dir),
+            conversations_root: paths::conversations_root(&base_dir),
+            task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
+            sqlite,
+            claude_processes: Mutex::new(HashMap::new()),
+        };
+
+        let repo_path = base_dir.join("repo");
+        std::fs::create_dir_all(&repo_path).unwrap();
+        assert_git_success(&repo_path, &["init"]);
+        assert_git_success(&repo_path, &["config", "user.name", "Test User"]);
+        assert_git_success(&repo_path, &["config", "user.email", "test@example.com"]);
+        std::fs::write(repo_path.join("tracked.txt"), "hello\n").unwrap();
+        assert_git_success(&repo_path, &["add", "."]);
+        assert_git_success(&repo_path, &["commit", "-m", "init"]);
+
+        let active = service.worktree_path("p", "active");
+        let stale = service.worktree_path("p", "stale");
+        let unknown = service.worktree_path("p", "unknown");
+        std::fs::create_dir_all(active.join("crates/app/target/debug")).unwrap();
+        std::fs::create_dir_all(active.join("web/node_modules/pkg")).unwrap();
+        std::fs::create_dir_all(active.join(".git")).unwrap();
+        std::fs::write(active.join("crates/app/target/debug/bin"), [0u8; 100]).unwrap();
+        std::fs::write(active.join("web/node_modules/pkg/index.js"), [0u8; 20]).unwrap();
+        std::fs::write(active.join("src.rs"), [0u8; 5]).unwrap();
+        std::fs::create_dir_all(stale.parent().unwrap()).unwrap();
+        assert_git_success(
+            &repo_path,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "luban/stale",
+                stale.to_str().unwrap(),
+            ],
+        );
+        std::fs::create_dir_all(&unknown).unwrap();
+        std::fs::write(unknown.join("notes"), [0u8; 3]).unwrap();
+
+        let usage = ProjectWorkspaceService::workspace_disk_usage(
+            &service,
+            "p".to_owned(),
+            "active".to_owned(),
+            active.clone(),
+        )
+        .unwrap();
+        assert_eq!(usage.worktree_bytes, 125);
+        assert_eq!(usage.context_blobs_bytes, 0);
+
+        let purged = ProjectWorkspaceService::purge_build_artifacts(
+            &service,
+            active.clone(),
+            vec!["target".to_owned(), "node_modules".to_owned()],
+        )
+        .unwrap();
+        assert_eq!(purged.removed_entries, 2);
+        assert_eq!(purged.freed_bytes, 120);
+        assert!(active.join("src.rs").exists());
+        assert!(active.join(".git").exists());
+
+        let outside = ProjectWorkspaceService::purge_build_artifacts(
+            &service,
+            base_dir.clone(),
+            vec!["target".to_owned()],
+        );
+        assert!(outside.is_err());
+
+        // The active worktree is offered through a symlink and a `..` detour; neither it nor the
+        // directory that belongs to no archived workdir may be touched.
+        let active_link = base_dir.join("active-link");
+        std::os::unix::fs::symlink(&active, &active_link).unwrap();
+        let removed = ProjectWorkspaceService::delete_stale_worktrees(
+            &service,
+            vec![
+                luban_domain::ArchivedWorktree {
+                    project_path: repo_path.clone(),
+                    worktree_path: stale.clone(),
+                },
+                luban_domain::ArchivedWorktree {
+                    project_path: repo_path.clone(),
+                    worktree_path: active_link.clone(),
+                },
+                luban_domain::ArchivedWorktree {
+                    project_path: repo_path.clone(),
+                    worktree_path: base_dir.join("repo"),
+                },
+            ],
+            vec![active.join("../active"), repo_path.clone()],
+        )
+        .unwrap();
+        assert_eq!(removed.removed_entries, 1);
+        assert!(active.join("src.rs").exists());
+        assert!(unknown.join("notes").exists());
+        assert!(repo_path.join("tracked.txt").exists());
+        assert!(!stale.exists());
+        let worktrees = run_git(&repo_path, &["worktree", "list", "--porcelain"]);
+        assert!(!String::from_utf8_lossy(&worktrees.stdout).contains("stale"));
+
+        let kept = ProjectWorkspaceService::store_context_text(
+            &service,
+            "p".to_owned(),
+            "active".to_owned(),
+            "kept".to_owned(),
+            "txt".to_owned(),
+        )
+        .unwrap();
+        ProjectWorkspaceService::record_context_item(
+            &service,
+            "p".to_owned(),
+            "active".to_owned(),
+            kept.clone(),
+            0,
+        )
+        .unwrap();
+        let orphan = ProjectWorkspaceService::store_context_text(
+            &service,
+            "p".to_owned(),
+            "active".to_owned(),
+            "orphan".to_owned(),
+            "txt".to_owned(),
+        )
+        .unwrap();
+        let fresh = ProjectWorkspaceService::store_context_text(
+            &service,
+            "p".to_owned(),
+            "active".to_owned(),
+            "fresh".to_owned(),
+            "txt".to_owned(),
+        )
+        .unwrap();
+        let store_text = |text: &str| {
+            ProjectWorkspaceService::store_context_text(
+                &service,
+                "p".to_owned(),
+                "active".to_owned(),
+                text.to_owned(),
+                "txt".to_owned(),
+            )
+            .unwrap()
+        };
+        let reattached_text = store_text("reattached text");
+        let reattached_file = store_text("reattached file");
+        let blobs_dir = service.context_blobs_dir();
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(3 * 24 * 60 * 60);
+        for id in [
+            &kept.id,
+            &orphan.id,
+            &reattached_text.id,
+            &reattached_file.id,
+        ] {
+            std::fs::File::options()
+                .write(true)
+                .open(blobs_dir.join(format!("{id}.txt")))
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+        // Attaching the same content again, before its context item is recorded, must not leave
+        // the old blob to GC.
+        assert_eq!(store_text("reattached text").id, reattached_text.id);
+        let source = base_dir.join("reattached.txt");
+        std::fs::write(&source, "reattached file").unwrap();
+        let from_file = ProjectWorkspaceService::store_context_file(
+            &service,
+            "p".to_owned(),
+            "active".to_owned(),
+            source,
+        )
+        .unwrap();
+        assert_eq!(from_file.id, reattached_file.id);
+
+        let collected = ProjectWorkspaceService::gc_context_blobs(&service).unwrap();
+        assert_eq!(collected.removed_entries, 1);
+        assert!(blobs_dir.join(format!("{}.txt", kept.id)).exists());
+        assert!(!blobs_dir.join(format!("{}.txt", orphan.id)).exists());
+        assert!(blobs_dir.join(format!("{}.txt", fresh.id)).exists());
+        assert!(
+            blobs_dir
+                .join(format!("{}.txt", reattached_text.id))
+                .exists()
+        );
+        assert!(
+            blobs_dir
+                .join(format!("{}.txt", reattached_file.id))
+                .exists()
+        );
+
+        drop(service);
+        let _ = std::fs::remove_dir_all(&base_dir);
+    }
+
+    #[test]
+    fn legacy_workspace_blobs_migrate_into_shared_store_and_dedupe() {
+        let unique = unix_epoch_nanos_now();
+        let base_dir = temp_services_dir(unique);
+        std::fs::create_dir_all(&base_dir).expect("luban root should exist");
+        let conversations_root = paths::conversations_root(&base_dir);
+        for workspace in ["a", "b"] {
+            let legacy = conversations_root.join("p").join(workspace).join("context");
+            std::fs::create_dir_all(legacy.join("blobs")).unwrap();
+            std::fs::create_dir_all(legacy.join("tmp")).unwrap();
+            std::fs::write(legacy.join("blobs/same.txt"), [1u8; 16]).unwrap();
+            std::fs::write(legacy.join(format!("blobs/only-{workspace}.txt")), [2u8; 4]).unwrap();
+        }
+
+        let sqlite =
+            SqliteStore::new(paths::sqlite_path(&base_dir)).expect("sqlite init should work");
+        let service = GitWorkspaceService {
+            worktrees_root: paths::worktrees_root(&base_dir),
+            conversations_root: conversations_root.clone(),
+            task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
+            sqlite,
+            claude_processes: Mutex::new(HashMap::new()),
+        };
+
+        let report = service.migrate_workspace_context_blobs().unwrap();
+        assert_eq!(report.removed_entries, 1);
+        assert_eq!(report.freed_bytes, 16);
+
+        let blobs_dir = service.context_blobs_dir();
+        for name in ["same.txt", "only-a.txt", "only-b.txt"] {
+            assert!(blobs_dir.join(name).exists(), "{name} should be migrated");
+        }
+        assert!(!conversations_root.join("p/a/context").exists());
+        assert!(!conversations_root.join("p/b/context").exists());
+
+        let first = ProjectWorkspaceService::store_context_text(
+            &service,
+            "p".to_owned(),
+            "a".to_owned(),
+            "shared".to_owned(),
+            "txt".to_owned(),
+        )
+        .unwrap();
+        let second = ProjectWorkspaceService::store_context_text(
+            &service,
+            "p".to_owned(),
+            "b".to_owned(),
+            "shared".to_owned(),
+            "txt".to_owned(),
+        )
+        .unwrap();
+        assert_eq!(first.id, second.id);
+
+        drop(service);
+        let _ = std::fs::remove_dir_all(&base_dir);
+    }
+
+    #[test]
+    fn document_attachments_get_extracted_text_sidecars() {
+        let unique = unix_epoch_nanos_now();
+        let base_dir = temp_services_dir(unique);
+        std::fs::create_dir_all(&base_dir).expect("luban root should exist");
+        let sqlite =
+            SqliteStore::new(paths::sqlite_path(&base_dir)).expect("sqlite init should work");
+        let service = GitWorkspaceService {
+            worktrees_root: paths::worktrees_root(&base_dir),
+            conversations_root: paths::conversations_root(&base_dir),
+            task_prompts_root: paths::task_prompts_root(&base_dir),
+            context_blobs_root: paths::context_blobs_root(&base_dir),
+            sqlite,
+            claude_processes: Mutex::new(HashMap::new()),
+        };
+
+        let content = b"BT /F1 12 Tf 72 720 Td (Quarterly report) Tj ET";
+        let mut pdf = format!(
+            "%PDF-1.4\n1 0 obj\n<< /Length {} >>\nstream\n",
+            content.len()
+        )
+        .into_bytes();
+        pdf.extend_from_slice(content);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF\n");
+        let source = base_dir.join("report.pdf");
+        std::fs::write(&source, &pdf).unwrap();
+
+        let attachment = ProjectWorkspaceService::store_context_file(
+            &service,
+            "p".to_owned(),
+            "w".to_owned(),
+            source,
+        )
+        .unwrap();
+        let blobs_dir = service.context_blobs_dir();
+        let attachments = std::slice::from_ref(&attachment);
+
+        let mut too_large = resolve_prompt_attachments(&blobs_dir, attachments);
+        service.attach_context_document_texts(
+            &mut too_large,
+            luban_domain::AttachmentTextLimits {
+                max_source_bytes: 16,
+                max_inline_bytes: 1024,
+            },
+        );
+        assert!(too_large[0].text.is_none());
+
+        let mut resolved = resolve_prompt_attachments(&blobs_dir, attachments);
+        service.attach_context_document_texts(
+            &mut resolved,
+            luban_domain::AttachmentTextLimits::default(),
+        );
+        let text = resolved[0]
+            .text
+            .as_ref()
+            .expect("pdf text should be extracted");
+        assert_eq!(
+            text.path,
+            blobs_dir.join(format!("{}.pdf.txt", attachment.id))
+        );
+        assert_eq!(text.inline.as_deref(), Some("Quarterly report"));
+        assert!(!text.truncated);
+
+        drop(service);
+        let _ = std::fs::remove_dir_all(&base_dir);
+    }
+
     // Linux open-command tests live in services/open_command.rs.
 }
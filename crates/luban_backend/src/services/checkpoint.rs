@@ -0,0 +1,335 @@
+use anyhow::{Context as _, anyhow};
+use luban_domain::TURN_CHECKPOINT_REF_PREFIX;
+use std::ffi::OsStr;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::time::unix_epoch_nanos_now;
+
+/// Checkpoint commits are internal bookkeeping, so they must not depend on the user's git
+/// identity being configured.
+const CHECKPOINT_IDENTITY: [(&str, &str); 4] = [
+    ("GIT_AUTHOR_NAME", "Luban"),
+    ("GIT_AUTHOR_EMAIL", "luban@localhost"),
+    ("GIT_COMMITTER_NAME", "Luban"),
+    ("GIT_COMMITTER_EMAIL", "luban@localhost"),
+];
+
+/// A throwaway index file so snapshots never touch the worktree's real index.
+struct ScratchIndex(PathBuf);
+
+impl ScratchIndex {
+    fn new(worktree_path: &Path) -> anyhow::Result<Self> {
+        let real = real_index_path(worktree_path)?;
+        let path = real.with_file_name(format!(
+            "luban-checkpoint-index-{}-{}",
+            std::process::id(),
+            unix_epoch_nanos_now()
+        ));
+        // Starting from the real index keeps its stat cache, so `git add -A` only rehashes
+        // files that actually changed.
+        if real.exists() {
+            std::fs::copy(&real, &path)
+                .with_context(|| format!("failed to copy index to {}", path.display()))?;
+        }
+        Ok(Self(path))
+    }
+}
+
+impl Drop for ScratchIndex {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn git<I, S>(
+    worktree_path: &Path,
+    index: Option<&ScratchIndex>,
+    stdin: Option<&[u8]>,
+    args: I,
+) -> anyhow::Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = Command::new("git");
+    command
+        .args(args)
+        .current_dir(worktree_path)
+        .envs(CHECKPOINT_IDENTITY)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", &index.0);
+    }
+    let mut child = command.spawn().context("failed to spawn git")?;
+    if let Some(input) = stdin
+        && let Some(mut pipe) = child.stdin.take()
+    {
+        pipe.write_all(input).context("failed to write git stdin")?;
+    }
+    let output = child.wait_with_output().context("failed to wait for git")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn real_index_path(worktree_path: &Path) -> anyhow::Result<PathBuf> {
+    let path = git(
+        worktree_path,
+        None,
+        None,
+        ["rev-parse", "--git-path", "index"],
+    )
+    .context("not a git worktree")?;
+    let path = PathBuf::from(path.trim());
+    Ok(if path.is_absolute() {
+        path
+    } else {
+        worktree_path.join(path)
+    })
+}
+
+fn head_commit(worktree_path: &Path) -> Option<String> {
+    git(
+        worktree_path,
+        None,
+        None,
+        ["rev-parse", "--verify", "--quiet", "HEAD^{commit}"],
+    )
+    .ok()
+    .map(|out| out.trim().to_owned())
+    .filter(|commit| !commit.is_empty())
+}
+
+/// Tree of the current worktree contents: tracked files plus untracked, non-ignored ones.
+pub(super) fn worktree_tree(worktree_path: &Path) -> anyhow::Result<String> {
+    let index = ScratchIndex::new(worktree_path)?;
+    git(
+        worktree_path,
+        Some(&index),
+        None,
+        ["add", "--all", "--", "."],
+    )?;
+    let tree = git(worktree_path, Some(&index), None, ["write-tree"])?;
+    Ok(tree.trim().to_owned())
+}
+
+fn ensure_checkpoint_ref(ref_name: &str) -> anyhow::Result<()> {
+    if !ref_name.starts_with(&format!("{TURN_CHECKPOINT_REF_PREFIX}/")) {
+        return Err(anyhow!("not a checkpoint ref: {ref_name}"));
+    }
+    Ok(())
+}
+
+fn changed_paths(
+    worktree_path: &Path,
+    from_tree: &str,
+    to_tree: &str,
+    diff_filter: &str,
+) -> anyhow::Result<Vec<String>> {
+    let out = git(
+        worktree_path,
+        None,
+        None,
+        [
+            "diff-tree",
+            "-r",
+            "-z",
+            "--name-only",
+            "--no-renames",
+            &format!("--diff-filter={diff_filter}"),
+            from_tree,
+            to_tree,
+        ],
+    )?;
+    Ok(out
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+/// Removes `path` and then any parent directories it leaves empty, up to `root`.
+fn remove_file_and_empty_parents(root: &Path, path: &Path) -> anyhow::Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to remove {}", path.display()));
+        }
+    }
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root || std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+    Ok(())
+}
+
+pub(super) fn create_turn_checkpoint(
+    worktree_path: &Path,
+    ref_name: &str,
+) -> anyhow::Result<String> {
+    ensure_checkpoint_ref(ref_name)?;
+    let tree = worktree_tree(worktree_path)?;
+    let mut args = vec!["commit-tree".to_owned(), tree];
+    if let Some(head) = head_commit(worktree_path) {
+        args.push("-p".to_owned());
+        args.push(head);
+    }
+    args.push("-m".to_owned());
+    args.push(format!("Luban checkpoint {ref_name}"));
+    let commit = git(worktree_path, None, None, &args)?.trim().to_owned();
+    git(worktree_path, None, None, ["update-ref", ref_name, &commit])?;
+    Ok(commit)
+}
+
+pub(super) fn restore_turn_checkpoint(worktree_path: &Path, ref_name: &str) -> anyhow::Result<()> {
+    ensure_checkpoint_ref(ref_name)?;
+    let checkpoint_tree = git(
+        worktree_path,
+        None,
+        None,
+        ["rev-parse", "--verify", &format!("{ref_name}^{{tree}}")],
+    )
+    .context("checkpoint not found")?
+    .trim()
+    .to_owned();
+    let current_tree = worktree_tree(worktree_path)?;
+    if current_tree == checkpoint_tree {
+        return Ok(());
+    }
+
+    for path in changed_paths(worktree_path, &checkpoint_tree, &current_tree, "A")? {
+        remove_file_and_empty_parents(worktree_path, &worktree_path.join(&path))?;
+    }
+
+    let restore = changed_paths(worktree_path, &checkpoint_tree, &current_tree, "DMT")?;
+    if restore.is_empty() {
+        return Ok(());
+    }
+    let index = ScratchIndex::new(worktree_path)?;
+    git(
+        worktree_path,
+        Some(&index),
+        None,
+        ["read-tree", checkpoint_tree.as_str()],
+    )?;
+    let mut stdin = Vec::new();
+    for path in &restore {
+        stdin.extend_from_slice(path.as_bytes());
+        stdin.push(0);
+    }
+    git(
+        worktree_path,
+        Some(&index),
+        Some(&stdin),
+        ["checkout-index", "--force", "-z", "--stdin"],
+    )?;
+    Ok(())
+}
+
+pub(super) fn delete_turn_checkpoints(repo_path: &Path, ref_prefix: &str) -> anyhow::Result<()> {
+    ensure_checkpoint_ref(ref_prefix)?;
+    let refs = git(
+        repo_path,
+        None,
+        None,
+        ["for-each-ref", "--format=delete %(refname)", ref_prefix],
+    )?;
+    if refs.trim().is_empty() {
+        return Ok(());
+    }
+    git(
+        repo_path,
+        None,
+        Some(refs.as_bytes()),
+        ["update-ref", "--stdin"],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::{assert_git_success, git_rev_parse};
+
+    #[test]
+    fn restore_rewinds_worktree_without_touching_head_or_index() {
+        let base_dir = std::env::temp_dir().join(format!(
+            "luban-checkpoint-{}-{}",
+            std::process::id(),
+            unix_epoch_nanos_now()
+        ));
+        let repo = base_dir.join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        assert_git_success(&repo, &["init"]);
+        assert_git_success(&repo, &["config", "user.name", "Test User"]);
+        assert_git_success(&repo, &["config", "user.email", "test@example.com"]);
+        std::fs::write(repo.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(repo.join("tracked.txt"), "v1\n").unwrap();
+        std::fs::write(repo.join("gone.txt"), "keep me\n").unwrap();
+        assert_git_success(&repo, &["add", "."]);
+        assert_git_success(&repo, &["commit", "-m", "init"]);
+        std::fs::write(repo.join("draft.txt"), "untracked\n").unwrap();
+        let head = git_rev_parse(&repo, "HEAD");
+
+        let ref_name = "refs/luban/checkpoints/1/1/100-1";
+        let commit = create_turn_checkpoint(&repo, ref_name).unwrap();
+        assert_eq!(git_rev_parse(&repo, ref_name), commit);
+        assert_eq!(git_rev_parse(&repo, &format!("{commit}^")), head);
+        assert_eq!(git_rev_parse(&repo, "HEAD"), head);
+        let status = git(&repo, None, None, ["status", "--porcelain"]).unwrap();
+        assert_eq!(status.trim(), "?? draft.txt");
+
+        std::fs::write(repo.join("tracked.txt"), "v2\n").unwrap();
+        std::fs::remove_file(repo.join("gone.txt")).unwrap();
+        std::fs::remove_file(repo.join("draft.txt")).unwrap();
+        std::fs::create_dir_all(repo.join("new/dir")).unwrap();
+        std::fs::write(repo.join("new/dir/file.txt"), "agent\n").unwrap();
+        std::fs::create_dir_all(repo.join("target")).unwrap();
+        std::fs::write(repo.join("target/out.bin"), "build\n").unwrap();
+
+        restore_turn_checkpoint(&repo, ref_name).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("tracked.txt")).unwrap(),
+            "v1\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo.join("gone.txt")).unwrap(),
+            "keep me\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo.join("draft.txt")).unwrap(),
+            "untracked\n"
+        );
+        assert!(!repo.join("new").exists());
+        assert!(repo.join("target/out.bin").exists());
+        assert_eq!(git_rev_parse(&repo, "HEAD"), head);
+        assert_eq!(
+            worktree_tree(&repo).unwrap(),
+            git_rev_parse(&repo, &format!("{ref_name}^{{tree}}"))
+        );
+
+        delete_turn_checkpoints(&repo, "refs/luban/checkpoints/1/").unwrap();
+        let refs = git(&repo, None, None, ["for-each-ref", "refs/luban"]).unwrap();
+        assert!(refs.trim().is_empty());
+        assert!(create_turn_checkpoint(&repo, "refs/heads/main").is_err());
+
+        let _ = std::fs::remove_dir_all(&base_dir);
+    }
+}
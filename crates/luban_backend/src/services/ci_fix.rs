@@ -0,0 +1,186 @@
+use super::GitWorkspaceService;
+use super::ansi::strip_ansi_control_sequences;
+use super::gh_cli::{ensure_gh_cli, run_gh_json_in, run_gh_text_in};
+use anyhow::Context as _;
+use luban_domain::CiFailureLog;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Upper bound for the log attached to a "fix CI" turn.
+const MAX_CI_LOG_BYTES: usize = 64 * 1024;
+/// Lines kept above the first error so the agent sees what led up to it.
+const CONTEXT_LINES_BEFORE_ERROR: usize = 30;
+const ERROR_MARKERS: &[&str] = &[
+    "##[error]",
+    "error:",
+    "error[",
+    "Error:",
+    "FAILED",
+    "FAIL:",
+    "panicked at",
+];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequestHead {
+    head_ref_oid: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkflowRun {
+    database_id: u64,
+    #[serde(default)]
+    conclusion: String,
+    #[serde(default)]
+    workflow_name: String,
+    #[serde(default)]
+    url: String,
+}
+
+fn is_failed_conclusion(conclusion: &str) -> bool {
+    matches!(conclusion, "failure" | "timed_out" | "startup_failure")
+}
+
+fn is_error_line(line: &str) -> bool {
+    ERROR_MARKERS.iter().any(|marker| line.contains(marker))
+}
+
+/// Drops the timestamp GitHub prefixes to every log line.
+fn strip_log_timestamp(text: &str) -> &str {
+    match text.split_once(' ') {
+        Some((stamp, rest))
+            if stamp.len() >= 20
+                && stamp.ends_with('Z')
+                && stamp.as_bytes().get(10) == Some(&b'T') =>
+        {
+            rest
+        }
+        _ => text,
+    }
+}
+
+/// Turns `gh run view --log-failed` output (`job<TAB>step<TAB>timestamp line`) into a readable
+/// log with one header per failed step, starting shortly before the first error and capped at
+/// `max_bytes` by dropping the oldest lines.
+fn trim_ci_log(raw: &str, max_bytes: usize) -> String {
+    let raw = strip_ansi_control_sequences(raw);
+    let mut lines = Vec::new();
+    let mut current_step: Option<(&str, &str)> = None;
+    for line in raw.lines() {
+        let mut parts = line.splitn(3, '\t');
+        let (job, step, text) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(job), Some(step), Some(text)) => (job, step, strip_log_timestamp(text)),
+            _ => {
+                lines.push(line.trim_end().to_owned());
+                continue;
+            }
+        };
+        if current_step != Some((job, step)) {
+            current_step = Some((job, step));
+            lines.push(format!("== {job} / {step} =="));
+        }
+        lines.push(text.trim_end().to_owned());
+    }
+
+    let first_error = lines.iter().position(|line| is_error_line(line));
+    let mut start = first_error.map_or(0, |idx| idx.saturating_sub(CONTEXT_LINES_BEFORE_ERROR));
+    let mut kept_bytes: usize = lines[start..].iter().map(|line| line.len() + 1).sum();
+    while kept_bytes > max_bytes && start + 1 < lines.len() {
+        kept_bytes -= lines[start].len() + 1;
+        start += 1;
+    }
+
+    let mut out = String::with_capacity(kept_bytes + 64);
+    if start > 0 {
+        out.push_str(&format!("… ({start} earlier log lines omitted)\n"));
+    }
+    for line in &lines[start..] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+impl GitWorkspaceService {
+    /// Finds the newest failed workflow run for the pull request's head commit and returns its
+    /// failed job logs, or `None` when no run on that commit failed.
+    pub(super) fn fetch_ci_failure_log(
+        &self,
+        worktree_path: &Path,
+        pr_number: u64,
+    ) -> anyhow::Result<Option<CiFailureLog>> {
+        self.ensure_github_remote(worktree_path, "CI failure logs")?;
+        ensure_gh_cli()?;
+
+        let number = pr_number.to_string();
+        let head: PullRequestHead = run_gh_json_in(
+            worktree_path,
+            &["pr", "view", &number, "--json", "headRefOid"],
+        )
+        .with_context(|| format!("failed to resolve the head commit of PR #{pr_number}"))?;
+        let runs: Vec<WorkflowRun> = run_gh_json_in(
+            worktree_path,
+            &[
+                "run",
+                "list",
+                "--commit",
+                &head.head_ref_oid,
+                "--limit",
+                "20",
+                "--json",
+                "databaseId,conclusion,workflowName,url",
+            ],
+        )
+        .with_context(|| format!("failed to list workflow runs for PR #{pr_number}"))?;
+        let Some(run) = runs
+            .into_iter()
+            .find(|run| is_failed_conclusion(&run.conclusion))
+        else {
+            return Ok(None);
+        };
+
+        let run_id = run.database_id.to_string();
+        let raw = run_gh_text_in(worktree_path, &["run", "view", &run_id, "--log-failed"])
+            .with_context(|| format!("failed to fetch failed job logs for run {run_id}"))?;
+        Ok(Some(CiFailureLog {
+            run_id: run.database_id,
+            workflow: run.workflow_name,
+            url: run.url,
+            log: trim_ci_log(&raw, MAX_CI_LOG_BYTES),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_ci_log_groups_steps_and_starts_near_the_first_error() {
+        let mut raw = String::new();
+        for i in 0..40 {
+            raw.push_str(&format!(
+                "build\tRun cargo test\t2024-05-01T10:00:{:02}.1234567Z \u{1b}[32mCompiling crate{i}\u{1b}[0m\n",
+                i % 60
+            ));
+        }
+        raw.push_str("build\tRun cargo test\t2024-05-01T10:01:00.0000000Z error[E0425]: cannot find value `x`\n");
+        raw.push_str("build\tRun cargo test\t2024-05-01T10:01:00.0000000Z ##[error]Process completed with exit code 101.\n");
+        raw.push_str("lint\tRun clippy\t2024-05-01T10:02:00.0000000Z warning: unused import\n");
+
+        let log = trim_ci_log(&raw, MAX_CI_LOG_BYTES);
+        let lines = log.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "… (11 earlier log lines omitted)");
+        assert_eq!(lines[1], "Compiling crate10");
+        assert!(!log.contains('\u{1b}'));
+        assert!(!log.contains("2024-05-01T"));
+        assert!(log.contains("error[E0425]: cannot find value `x`\n"));
+        assert!(log.ends_with("== lint / Run clippy ==\nwarning: unused import\n"));
+
+        let capped = trim_ci_log(&raw, 80);
+        assert!(capped.starts_with("… (4"));
+        assert!(capped.ends_with("warning: unused import\n"));
+        assert!(capped.len() < 140);
+    }
+}
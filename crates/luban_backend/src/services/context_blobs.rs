@@ -1,6 +1,9 @@
 use super::GitWorkspaceService;
+use super::document_text::{document_text_path, extract_document_text, is_extractable_extension};
+use super::prompt::{PromptAttachment, PromptAttachmentText};
 use anyhow::{Context as _, anyhow};
 use image::ImageFormat;
+use luban_domain::{AttachmentKind, AttachmentTextLimits, DiskCleanupReport};
 use std::{
     io::{Read as _, Write as _},
     path::{Path, PathBuf},
@@ -9,6 +12,16 @@ use std::{
 const CONTEXT_IMAGE_THUMB_MAX_WIDTH: u32 = 360;
 const CONTEXT_IMAGE_THUMB_MAX_HEIGHT: u32 = 220;
 
+/// Marks an existing blob as just used, so blob GC's grace period covers a re-attached blob until
+/// the context item that references it is recorded.
+fn touch_blob(path: &Path) -> anyhow::Result<()> {
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|f| f.set_modified(std::time::SystemTime::now()))
+        .with_context(|| format!("failed to touch {}", path.display()))
+}
+
 fn finalize_atomic_rename(tmp: &Path, dest: &Path) -> std::io::Result<()> {
     match std::fs::rename(tmp, dest) {
         Ok(()) => Ok(()),
@@ -24,19 +37,81 @@ fn finalize_atomic_rename(tmp: &Path, dest: &Path) -> std::io::Result<()> {
 }
 
 impl GitWorkspaceService {
-    pub(super) fn context_root_dir(&self, project_slug: &str, workspace_name: &str) -> PathBuf {
-        self.conversation_dir(project_slug, workspace_name)
-            .join("context")
+    pub(super) fn context_blobs_dir(&self) -> PathBuf {
+        self.context_blobs_root.clone()
     }
 
-    pub(super) fn context_blobs_dir(&self, project_slug: &str, workspace_name: &str) -> PathBuf {
-        self.context_root_dir(project_slug, workspace_name)
-            .join("blobs")
+    pub(super) fn context_tmp_dir(&self) -> PathBuf {
+        self.context_blobs_root.join(".tmp")
+    }
+
+    /// Moves blobs from the old per-workspace layout
+    /// (`<conversations>/<project>/<workspace>/context/blobs`) into the shared store. Identical
+    /// blobs collapse into one file; the report counts the duplicates that were dropped.
+    pub(super) fn migrate_workspace_context_blobs(&self) -> anyhow::Result<DiskCleanupReport> {
+        let mut report = DiskCleanupReport::default();
+        let Ok(projects) = std::fs::read_dir(&self.conversations_root) else {
+            return Ok(report);
+        };
+        let blobs_dir = self.context_blobs_dir();
+        for project in projects.flatten() {
+            let Ok(workspaces) = std::fs::read_dir(project.path()) else {
+                continue;
+            };
+            for workspace in workspaces.flatten() {
+                let context_dir = workspace.path().join("context");
+                let legacy_blobs_dir = context_dir.join("blobs");
+                if let Ok(entries) = std::fs::read_dir(&legacy_blobs_dir) {
+                    std::fs::create_dir_all(&blobs_dir)
+                        .with_context(|| format!("failed to create {}", blobs_dir.display()))?;
+                    for entry in entries.flatten() {
+                        let Ok(meta) = entry.metadata() else {
+                            continue;
+                        };
+                        if !meta.is_file() {
+                            continue;
+                        }
+                        let src = entry.path();
+                        let dest = blobs_dir.join(entry.file_name());
+                        if dest.exists() {
+                            std::fs::remove_file(&src)
+                                .with_context(|| format!("failed to remove {}", src.display()))?;
+                            report.merge(DiskCleanupReport {
+                                removed_entries: 1,
+                                freed_bytes: meta.len(),
+                            });
+                        } else {
+                            self.move_into_blob_store(&src, &dest)?;
+                        }
+                    }
+                }
+                let _ = std::fs::remove_dir_all(context_dir.join("tmp"));
+                let _ = std::fs::remove_dir(&legacy_blobs_dir);
+                let _ = std::fs::remove_dir(&context_dir);
+            }
+        }
+        Ok(report)
     }
 
-    pub(super) fn context_tmp_dir(&self, project_slug: &str, workspace_name: &str) -> PathBuf {
-        self.context_root_dir(project_slug, workspace_name)
-            .join("tmp")
+    fn move_into_blob_store(&self, src: &Path, dest: &Path) -> anyhow::Result<()> {
+        if std::fs::rename(src, dest).is_ok() {
+            return Ok(());
+        }
+
+        // Different filesystem: copy next to the destination, then swap it in atomically.
+        let tmp_dir = self.context_tmp_dir();
+        std::fs::create_dir_all(&tmp_dir)
+            .with_context(|| format!("failed to create {}", tmp_dir.display()))?;
+        let tmp = tmp_dir.join(format!("migrate-{}", rand::random::<u64>()));
+        std::fs::copy(src, &tmp).with_context(|| format!("failed to copy {}", src.display()))?;
+        finalize_atomic_rename(&tmp, dest).with_context(|| {
+            format!(
+                "failed to move context blob {} -> {}",
+                src.display(),
+                dest.display()
+            )
+        })?;
+        std::fs::remove_file(src).with_context(|| format!("failed to remove {}", src.display()))
     }
 
     fn normalize_extension(ext: &str) -> anyhow::Result<String> {
@@ -58,23 +133,22 @@ impl GitWorkspaceService {
 
     pub(super) fn store_context_bytes(
         &self,
-        project_slug: &str,
-        workspace_name: &str,
         bytes: &[u8],
         extension: &str,
     ) -> anyhow::Result<(String, PathBuf)> {
         let extension = Self::normalize_extension(extension)?;
         let hash = blake3::hash(bytes).to_hex().to_string();
-        let blobs_dir = self.context_blobs_dir(project_slug, workspace_name);
+        let blobs_dir = self.context_blobs_dir();
         std::fs::create_dir_all(&blobs_dir)
             .with_context(|| format!("failed to create {}", blobs_dir.display()))?;
 
         let dest = blobs_dir.join(format!("{hash}.{extension}"));
         if dest.exists() {
+            touch_blob(&dest)?;
             return Ok((hash, dest));
         }
 
-        let tmp_dir = self.context_tmp_dir(project_slug, workspace_name);
+        let tmp_dir = self.context_tmp_dir();
         std::fs::create_dir_all(&tmp_dir)
             .with_context(|| format!("failed to create {}", tmp_dir.display()))?;
         let tmp = tmp_dir.join(format!("import-{}", rand::random::<u64>()));
@@ -100,13 +174,12 @@ impl GitWorkspaceService {
 
     pub(super) fn maybe_store_context_image_thumbnail(
         &self,
-        project_slug: &str,
-        workspace_name: &str,
         original_path: &Path,
         bytes: &[u8],
     ) -> anyhow::Result<Option<PathBuf>> {
         let thumbnail_path = Self::context_image_thumbnail_path(original_path);
         if thumbnail_path.exists() {
+            touch_blob(&thumbnail_path)?;
             return Ok(Some(thumbnail_path));
         }
 
@@ -119,7 +192,7 @@ impl GitWorkspaceService {
             CONTEXT_IMAGE_THUMB_MAX_HEIGHT,
         );
 
-        let tmp_dir = self.context_tmp_dir(project_slug, workspace_name);
+        let tmp_dir = self.context_tmp_dir();
         std::fs::create_dir_all(&tmp_dir)
             .with_context(|| format!("failed to create {}", tmp_dir.display()))?;
         let tmp = tmp_dir.join(format!("thumb-{}", rand::random::<u64>()));
@@ -148,8 +221,6 @@ impl GitWorkspaceService {
 
     pub(super) fn store_context_file_internal(
         &self,
-        project_slug: &str,
-        workspace_name: &str,
         source_path: &Path,
     ) -> anyhow::Result<(String, String, u64, PathBuf)> {
         let extension = source_path
@@ -158,11 +229,11 @@ impl GitWorkspaceService {
             .unwrap_or("txt");
         let extension = Self::normalize_extension(extension)?;
 
-        let blobs_dir = self.context_blobs_dir(project_slug, workspace_name);
+        let blobs_dir = self.context_blobs_dir();
         std::fs::create_dir_all(&blobs_dir)
             .with_context(|| format!("failed to create {}", blobs_dir.display()))?;
 
-        let tmp_dir = self.context_tmp_dir(project_slug, workspace_name);
+        let tmp_dir = self.context_tmp_dir();
         std::fs::create_dir_all(&tmp_dir)
             .with_context(|| format!("failed to create {}", tmp_dir.display()))?;
         let tmp = tmp_dir.join(format!("import-{}", rand::random::<u64>()));
@@ -192,6 +263,7 @@ impl GitWorkspaceService {
         let dest = blobs_dir.join(format!("{hash}.{extension}"));
         if dest.exists() {
             let _ = std::fs::remove_file(&tmp);
+            touch_blob(&dest)?;
             return Ok((hash, extension, byte_len, dest));
         }
 
@@ -204,4 +276,76 @@ impl GitWorkspaceService {
         })?;
         Ok((hash, extension, byte_len, dest))
     }
+
+    /// Extracts the text of a document blob into its sidecar on first use.
+    pub(super) fn ensure_context_document_text(
+        &self,
+        blob_path: &Path,
+        max_source_bytes: u64,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        let sidecar = document_text_path(blob_path);
+        if sidecar.exists() {
+            return Ok(Some(sidecar));
+        }
+        let extension = blob_path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        if !is_extractable_extension(extension) {
+            return Ok(None);
+        }
+        let Ok(meta) = std::fs::metadata(blob_path) else {
+            return Ok(None);
+        };
+        if meta.len() > max_source_bytes {
+            return Ok(None);
+        }
+
+        let bytes = std::fs::read(blob_path)
+            .with_context(|| format!("failed to read {}", blob_path.display()))?;
+        let Some(text) = extract_document_text(extension, &bytes) else {
+            return Ok(None);
+        };
+
+        let tmp_dir = self.context_tmp_dir();
+        std::fs::create_dir_all(&tmp_dir)
+            .with_context(|| format!("failed to create {}", tmp_dir.display()))?;
+        let tmp = tmp_dir.join(format!("text-{}", rand::random::<u64>()));
+        std::fs::write(&tmp, text.as_bytes())
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        finalize_atomic_rename(&tmp, &sidecar).with_context(|| {
+            format!(
+                "failed to move extracted text {} -> {}",
+                tmp.display(),
+                sidecar.display()
+            )
+        })?;
+        Ok(Some(sidecar))
+    }
+
+    /// Adds extracted text to file attachments. Documents that cannot be read are left as paths.
+    pub(super) fn attach_context_document_texts(
+        &self,
+        attachments: &mut [PromptAttachment],
+        limits: AttachmentTextLimits,
+    ) {
+        for attachment in attachments
+            .iter_mut()
+            .filter(|a| a.kind == AttachmentKind::File)
+        {
+            let Ok(Some(path)) =
+                self.ensure_context_document_text(&attachment.path, limits.max_source_bytes)
+            else {
+                continue;
+            };
+            let Ok(text) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            attachment.text = Some(PromptAttachmentText::new(
+                path,
+                &text,
+                limits.max_inline_bytes,
+            ));
+        }
+    }
 }
@@ -87,7 +87,11 @@ fn migrate_legacy_entry(entry: LegacyConversationEntry) -> Option<ConversationEn
             Some(ConversationEntry::UserEvent {
                 entry_id: String::new(),
                 created_at_unix_ms: 0,
-                event: UserEvent::Message { text, attachments },
+                event: UserEvent::Message {
+                    text,
+                    attachments,
+                    author: None,
+                },
             })
         }
         LegacyConversationEntry::CodexItem { item } => match *item {
@@ -552,6 +556,7 @@ mod tests {
             worktrees_root,
             conversations_root: conversations_root.clone(),
             task_prompts_root: root.join("task-prompts"),
+            context_blobs_root: root.join("blobs"),
             sqlite,
             claude_processes: std::sync::Mutex::new(std::collections::HashMap::new()),
         };
@@ -600,6 +605,7 @@ mod tests {
             worktrees_root,
             conversations_root: conversations_root.clone(),
             task_prompts_root: root.join("task-prompts"),
+            context_blobs_root: root.join("blobs"),
             sqlite: sqlite.clone(),
             claude_processes: std::sync::Mutex::new(std::collections::HashMap::new()),
         };
@@ -625,6 +631,7 @@ mod tests {
                 event: UserEvent::Message {
                     text: "u1".to_owned(),
                     attachments: Vec::new(),
+                    author: None,
                 },
             },
             ConversationEntry::AgentEvent {
@@ -642,6 +649,7 @@ mod tests {
                 event: UserEvent::Message {
                     text: "u2".to_owned(),
                     attachments: Vec::new(),
+                    author: None,
                 },
             },
             ConversationEntry::AgentEvent {
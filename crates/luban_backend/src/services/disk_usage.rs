@@ -0,0 +1,323 @@
+use super::GitWorkspaceService;
+use anyhow::{Context as _, anyhow};
+use glob::{MatchOptions, Pattern};
+use luban_domain::{ArchivedWorktree, DiskCleanupReport, WorkspaceDiskUsage};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Blobs this recent may belong to a composer draft that has not been sent yet.
+const CONTEXT_BLOB_GC_GRACE: Duration = Duration::from_secs(24 * 60 * 60);
+
+const GLOB_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Total size of the files under `path`, without following symlinks. Unreadable entries are
+/// skipped.
+pub(super) fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+
+    let mut total = 0u64;
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else {
+                total = total.saturating_add(meta.len());
+            }
+        }
+    }
+    total
+}
+
+fn remove_entry(path: &Path) -> anyhow::Result<u64> {
+    let meta = std::fs::symlink_metadata(path)
+        .with_context(|| format!("failed to stat {}", path.display()))?;
+    let size = dir_size(path);
+    if meta.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+    .with_context(|| format!("failed to remove {}", path.display()))?;
+    Ok(size)
+}
+
+fn modified_unix_seconds(meta: &std::fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Patterns without a `/` match an entry name at any depth (`target`, `*.log`); patterns with a
+/// `/` match the path relative to the worktree root (`web/.next`, `**/dist`).
+fn compile_artifact_globs(globs: &[String]) -> anyhow::Result<Vec<(Pattern, bool)>> {
+    let mut out = Vec::new();
+    for raw in globs {
+        let raw = raw.trim().trim_end_matches('/');
+        if raw.is_empty() {
+            continue;
+        }
+        let pattern = Pattern::new(raw).map_err(|err| anyhow!("invalid glob '{raw}': {err}"))?;
+        out.push((pattern, raw.contains('/')));
+    }
+    if out.is_empty() {
+        return Err(anyhow!("no build artifact globs given"));
+    }
+    Ok(out)
+}
+
+/// Extracts the attachment id from a blob file name (`<id>.<ext>` or `<id>-thumb.png`).
+fn blob_attachment_id(file_name: &str) -> &str {
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+    stem.strip_suffix("-thumb").unwrap_or(stem)
+}
+
+impl GitWorkspaceService {
+    /// Worktree size plus the size of the shared blobs the workspace references.
+    pub(super) fn measure_workspace_disk_usage(
+        &self,
+        project_slug: &str,
+        workspace_name: &str,
+        worktree_path: &Path,
+    ) -> WorkspaceDiskUsage {
+        let referenced = self
+            .sqlite
+            .count_attachment_references(Some((project_slug.to_owned(), workspace_name.to_owned())))
+            .unwrap_or_default();
+        let mut context_blobs_bytes = 0u64;
+        if !referenced.is_empty()
+            && let Ok(entries) = std::fs::read_dir(self.context_blobs_dir())
+        {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                if !referenced.contains_key(blob_attachment_id(&name.to_string_lossy())) {
+                    continue;
+                }
+                if let Ok(meta) = entry.metadata()
+                    && meta.is_file()
+                {
+                    context_blobs_bytes = context_blobs_bytes.saturating_add(meta.len());
+                }
+            }
+        }
+        WorkspaceDiskUsage {
+            worktree_bytes: dir_size(worktree_path),
+            context_blobs_bytes,
+        }
+    }
+
+    pub(super) fn purge_build_artifacts_internal(
+        &self,
+        worktree_path: &Path,
+        globs: &[String],
+    ) -> anyhow::Result<DiskCleanupReport> {
+        if !worktree_path.starts_with(&self.worktrees_root) {
+            return Err(anyhow!(
+                "refusing to purge outside the worktrees root: {}",
+                worktree_path.display()
+            ));
+        }
+        let patterns = compile_artifact_globs(globs)?;
+
+        let mut report = DiskCleanupReport::default();
+        let mut stack = vec![PathBuf::new()];
+        while let Some(rel_dir) = stack.pop() {
+            let Ok(entries) = std::fs::read_dir(worktree_path.join(&rel_dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let Some(name) = name.to_str() else {
+                    continue;
+                };
+                if name == ".git" {
+                    continue;
+                }
+                let rel_path = rel_dir.join(name);
+                let rel_str = rel_path.to_string_lossy().replace('\\', "/");
+                let matched = patterns.iter().any(|(pattern, anchored)| {
+                    if *anchored {
+                        pattern.matches_with(&rel_str, GLOB_MATCH_OPTIONS)
+                    } else {
+                        pattern.matches_with(name, GLOB_MATCH_OPTIONS)
+                    }
+                });
+                if matched {
+                    let freed = remove_entry(&entry.path())?;
+                    report.merge(DiskCleanupReport {
+                        removed_entries: 1,
+                        freed_bytes: freed,
+                    });
+                    continue;
+                }
+                if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    stack.push(rel_path);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Paths are compared canonicalized, so a symlinked or relative path to a live worktree
+    /// still matches `keep`.
+    pub(super) fn delete_stale_worktrees_internal(
+        &self,
+        archived: &[ArchivedWorktree],
+        keep: &[PathBuf],
+    ) -> anyhow::Result<DiskCleanupReport> {
+        let mut report = DiskCleanupReport::default();
+        let Ok(root) = self.worktrees_root.canonicalize() else {
+            return Ok(report);
+        };
+        let keep = keep
+            .iter()
+            .filter_map(|path| path.canonicalize().ok())
+            .collect::<Vec<_>>();
+
+        let mut pruned_repos = Vec::<&Path>::new();
+        for worktree in archived {
+            let Ok(path) = worktree.worktree_path.canonicalize() else {
+                continue;
+            };
+            if path == root
+                || !path.starts_with(&root)
+                || keep
+                    .iter()
+                    .any(|k| k.starts_with(&path) || path.starts_with(k))
+            {
+                continue;
+            }
+            let size = dir_size(&path);
+            self.remove_worktree(&worktree.project_path, &path)?;
+            // A directory git did not track as a worktree is a leftover of an earlier archive.
+            if path.exists() {
+                remove_entry(&path)?;
+            }
+            report.merge(DiskCleanupReport {
+                removed_entries: 1,
+                freed_bytes: size,
+            });
+            if !pruned_repos.contains(&worktree.project_path.as_path()) {
+                pruned_repos.push(&worktree.project_path);
+            }
+        }
+        for repo in pruned_repos {
+            self.run_git(repo, ["worktree", "prune"])
+                .with_context(|| format!("failed to prune worktrees of {}", repo.display()))?;
+        }
+        Ok(report)
+    }
+
+    /// Deletes shared blobs with no references left in any workspace.
+    pub(super) fn gc_context_blobs_internal(&self) -> anyhow::Result<DiskCleanupReport> {
+        let references = self.sqlite.count_attachment_references(None)?;
+        let cutoff = SystemTime::now()
+            .checked_sub(CONTEXT_BLOB_GC_GRACE)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut report = DiskCleanupReport::default();
+        for (dir, keep_referenced) in [
+            (self.context_blobs_dir(), true),
+            (self.context_tmp_dir(), false),
+        ] {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                if !meta.is_file() || modified_unix_seconds(&meta) > cutoff {
+                    continue;
+                }
+                let name = entry.file_name();
+                if keep_referenced
+                    && references.contains_key(blob_attachment_id(&name.to_string_lossy()))
+                {
+                    continue;
+                }
+                let freed = remove_entry(&entry.path())?;
+                report.merge(DiskCleanupReport {
+                    removed_entries: 1,
+                    freed_bytes: freed,
+                });
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_attachment_id_strips_extension_and_thumbnail_suffix() {
+        assert_eq!(blob_attachment_id("abc123.png"), "abc123");
+        assert_eq!(blob_attachment_id("abc123-thumb.png"), "abc123");
+        assert_eq!(blob_attachment_id("abc123"), "abc123");
+    }
+
+    #[test]
+    fn artifact_globs_match_names_anywhere_and_paths_from_root() {
+        let patterns = compile_artifact_globs(&[
+            "target".to_owned(),
+            "web/.next/".to_owned(),
+            "**/*.log".to_owned(),
+        ])
+        .unwrap();
+        let matches = |name: &str, rel: &str| {
+            patterns.iter().any(|(p, anchored)| {
+                if *anchored {
+                    p.matches_with(rel, GLOB_MATCH_OPTIONS)
+                } else {
+                    p.matches_with(name, GLOB_MATCH_OPTIONS)
+                }
+            })
+        };
+        assert!(matches("target", "crates/app/target"));
+        assert!(matches(".next", "web/.next"));
+        assert!(!matches(".next", "docs/.next"));
+        assert!(matches("build.log", "logs/ci/build.log"));
+        assert!(!matches("targets", "targets"));
+
+        assert!(compile_artifact_globs(&[" ".to_owned()]).is_err());
+    }
+
+    #[test]
+    fn dir_size_sums_nested_files() {
+        let root = std::env::temp_dir().join(format!(
+            "luban-dir-size-{}-{}",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("one"), [0u8; 10]).unwrap();
+        std::fs::write(root.join("a/b/two"), [0u8; 32]).unwrap();
+
+        assert_eq!(dir_size(&root), 42);
+        assert_eq!(dir_size(&root.join("one")), 10);
+        assert_eq!(dir_size(&root.join("missing")), 0);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,654 @@
+use flate2::read::ZlibDecoder;
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use std::io::{Cursor, Read as _};
+use std::path::{Path, PathBuf};
+
+/// Keeps a pathological document from producing an unbounded sidecar.
+const MAX_EXTRACTED_TEXT_BYTES: usize = 8 * 1024 * 1024;
+/// Upper bound on inflated bytes per PDF stream or archive entry.
+const MAX_INFLATED_BYTES: u64 = 64 * 1024 * 1024;
+
+pub(super) fn is_extractable_extension(extension: &str) -> bool {
+    matches!(extension, "pdf" | "docx" | "pptx" | "odt" | "odp")
+}
+
+/// `<id>.<ext>` -> `<id>.<ext>.txt`. Keeping the id prefix lets blob GC and disk usage treat the
+/// sidecar as part of the original attachment.
+pub(super) fn document_text_path(blob_path: &Path) -> PathBuf {
+    let mut name = blob_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".txt");
+    blob_path.with_file_name(name)
+}
+
+/// Best-effort plain text of a document. Returns `None` when the format is unsupported or no
+/// readable text was found (scanned PDFs, fonts without a usable encoding).
+pub(super) fn extract_document_text(extension: &str, bytes: &[u8]) -> Option<String> {
+    let text = match extension {
+        "pdf" => Some(extract_pdf_text(bytes)).filter(|text| looks_like_text(text)),
+        "docx" => extract_zip_xml_text(bytes, XmlFlavor::OfficeOpenXml, |name| {
+            name == "word/document.xml"
+        }),
+        "pptx" => extract_zip_xml_text(bytes, XmlFlavor::OfficeOpenXml, |name| {
+            pptx_slide_number(name).is_some()
+        }),
+        "odt" | "odp" => {
+            extract_zip_xml_text(bytes, XmlFlavor::OpenDocument, |name| name == "content.xml")
+        }
+        _ => None,
+    }?;
+    let text = normalize_text(&text);
+    (!text.is_empty()).then_some(text)
+}
+
+fn normalize_text(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len().min(MAX_EXTRACTED_TEXT_BYTES));
+    let mut blank_run = 0usize;
+    for line in raw.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 || out.is_empty() {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        if out.len() + line.len() + 1 > MAX_EXTRACTED_TEXT_BYTES {
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_owned()
+}
+
+/// Rejects output that is mostly glyph ids rather than characters.
+fn looks_like_text(text: &str) -> bool {
+    let mut total = 0usize;
+    let mut readable = 0usize;
+    for ch in text.chars().filter(|ch| !ch.is_whitespace()) {
+        total += 1;
+        if ch.is_alphanumeric() || ".,;:!?'\"()-/%&$@#*+=".contains(ch) {
+            readable += 1;
+        }
+    }
+    total > 0 && readable * 10 >= total * 7
+}
+
+#[derive(Clone, Copy)]
+enum XmlFlavor {
+    /// DOCX/PPTX: text lives in `<w:t>`/`<a:t>` runs inside `<w:p>`/`<a:p>` paragraphs.
+    OfficeOpenXml,
+    /// ODT/ODP: text lives directly in `<text:p>`/`<text:h>`.
+    OpenDocument,
+}
+
+fn extract_zip_xml_text(
+    bytes: &[u8],
+    flavor: XmlFlavor,
+    wanted: impl Fn(&str) -> bool,
+) -> Option<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).ok()?;
+    let mut names = archive
+        .file_names()
+        .filter(|name| wanted(name))
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    names.sort_by_key(|name| (pptx_slide_number(name), name.clone()));
+
+    let mut out = String::new();
+    for name in names {
+        let Ok(entry) = archive.by_name(&name) else {
+            continue;
+        };
+        let mut xml = String::new();
+        if entry
+            .take(MAX_INFLATED_BYTES)
+            .read_to_string(&mut xml)
+            .is_err()
+        {
+            continue;
+        }
+        out.push_str(&xml_text(&xml, flavor));
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn pptx_slide_number(name: &str) -> Option<u32> {
+    name.strip_prefix("ppt/slides/slide")?
+        .strip_suffix(".xml")?
+        .parse()
+        .ok()
+}
+
+fn xml_text(xml: &str, flavor: XmlFlavor) -> String {
+    let captures = |local: &[u8]| match flavor {
+        XmlFlavor::OfficeOpenXml => local == b"t",
+        XmlFlavor::OpenDocument => local == b"p" || local == b"h",
+    };
+    let is_paragraph = |local: &[u8]| local == b"p" || local == b"h";
+
+    let mut reader = Reader::from_str(xml);
+    let mut out = String::new();
+    let mut capture_depth = 0usize;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                if captures(e.local_name().as_ref()) {
+                    capture_depth += 1;
+                }
+            }
+            Ok(Event::End(e)) => {
+                let local = e.local_name();
+                if captures(local.as_ref()) {
+                    capture_depth = capture_depth.saturating_sub(1);
+                }
+                if is_paragraph(local.as_ref()) {
+                    out.push('\n');
+                }
+            }
+            Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                // `<w:tab w:val=.../>` inside `<w:tabs>` defines a tab stop, not a tab.
+                b"tab" if e.attributes().next().is_none() => out.push('\t'),
+                b"br" | b"cr" | b"line-break" => out.push('\n'),
+                b"s" => out.push(' '),
+                b"p" | b"h" => out.push('\n'),
+                _ => {}
+            },
+            Ok(Event::Text(e)) if capture_depth > 0 => {
+                if let Ok(text) = e.decode() {
+                    out.push_str(&text);
+                }
+            }
+            Ok(Event::CData(e)) if capture_depth > 0 => {
+                if let Ok(text) = e.decode() {
+                    out.push_str(&text);
+                }
+            }
+            Ok(Event::GeneralRef(e)) if capture_depth > 0 => {
+                if let Ok(Some(ch)) = e.resolve_char_ref() {
+                    out.push(ch);
+                } else if let Ok(name) = e.decode()
+                    && let Some(value) = resolve_predefined_entity(&name)
+                {
+                    out.push_str(value);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    out
+}
+
+fn find(haystack: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| i + from)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+/// True if `dict` contains `key` followed (after optional whitespace) by the name `value`.
+fn dict_has_entry(dict: &[u8], key: &[u8], value: &[u8]) -> bool {
+    let mut from = 0;
+    while let Some(at) = find(dict, from, key) {
+        let mut i = at + key.len();
+        while dict.get(i).is_some_and(u8::is_ascii_whitespace) {
+            i += 1;
+        }
+        if dict[i..].starts_with(value)
+            && !dict
+                .get(i + value.len())
+                .is_some_and(u8::is_ascii_alphanumeric)
+        {
+            return true;
+        }
+        from = at + key.len();
+    }
+    false
+}
+
+/// Walks every stream in the file and collects the text shown by content streams. This covers
+/// PDFs written with standard or Unicode string encodings; fonts that need a ToUnicode CMap
+/// yield glyph ids, which `looks_like_text` then rejects.
+fn extract_pdf_text(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    let mut pos = 0;
+    while let Some(keyword) = find(bytes, pos, b"stream") {
+        pos = keyword + b"stream".len();
+        if keyword >= 3 && &bytes[keyword - 3..keyword] == b"end" {
+            continue;
+        }
+        let mut data_start = pos;
+        if bytes.get(data_start) == Some(&b'\r') {
+            data_start += 1;
+        }
+        if bytes.get(data_start) == Some(&b'\n') {
+            data_start += 1;
+        }
+        let Some(data_end) = find(bytes, data_start, b"endstream") else {
+            break;
+        };
+        pos = data_end + b"endstream".len();
+
+        let dict_start = rfind(&bytes[..keyword], b"obj").map_or(0, |i| i + 3);
+        let dict = &bytes[dict_start..keyword];
+        let skipped = [
+            (&b"/Subtype"[..], &b"/Image"[..]),
+            (b"/Subtype", b"/Type1C"),
+            (b"/Subtype", b"/CIDFontType0C"),
+            (b"/Subtype", b"/OpenType"),
+            (b"/Subtype", b"/XML"),
+            (b"/Type", b"/XRef"),
+            (b"/Type", b"/ObjStm"),
+            (b"/Type", b"/EmbeddedFile"),
+        ];
+        if skipped
+            .iter()
+            .any(|(key, value)| dict_has_entry(dict, key, value))
+            || find(dict, 0, b"/Length1").is_some()
+        {
+            continue;
+        }
+
+        let raw = &bytes[data_start..data_end];
+        let data = if dict_has_entry(dict, b"/Filter", b"/FlateDecode")
+            || dict_has_entry(dict, b"/Filter", b"[/FlateDecode]")
+        {
+            let mut inflated = Vec::new();
+            // Keep whatever inflated before a corrupt tail.
+            let _ = ZlibDecoder::new(raw)
+                .take(MAX_INFLATED_BYTES)
+                .read_to_end(&mut inflated);
+            inflated
+        } else if find(dict, 0, b"/Filter").is_some() {
+            continue;
+        } else {
+            raw.to_vec()
+        };
+
+        extract_content_stream_text(&data, &mut out);
+        if out.len() >= MAX_EXTRACTED_TEXT_BYTES {
+            break;
+        }
+    }
+    out
+}
+
+enum PdfOperand {
+    Number(f64),
+    Str(Vec<u8>),
+    ArrayStart,
+    Array(Vec<PdfOperand>),
+    Other,
+}
+
+fn is_pdf_delimiter(b: u8) -> bool {
+    b.is_ascii_whitespace() || b"()<>[]{}/%".contains(&b)
+}
+
+fn push_line_break(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+fn extract_content_stream_text(data: &[u8], out: &mut String) {
+    let mut operands = Vec::<PdfOperand>::new();
+    let mut in_text = false;
+    let mut last_line_y: Option<f64> = None;
+    let mut i = 0;
+    while i < data.len() {
+        let b = data[i];
+        match b {
+            _ if b.is_ascii_whitespace() => i += 1,
+            b'%' => {
+                while i < data.len() && data[i] != b'\n' && data[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            b'(' => {
+                let (value, next) = parse_literal_string(data, i + 1);
+                operands.push(PdfOperand::Str(value));
+                i = next;
+            }
+            b'<' if data.get(i + 1) == Some(&b'<') => {
+                // Inline dictionaries (marked-content properties); skip with nesting.
+                let mut depth = 0usize;
+                while i < data.len() {
+                    if data[i..].starts_with(b"<<") {
+                        depth += 1;
+                        i += 2;
+                    } else if data[i..].starts_with(b">>") {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+                operands.push(PdfOperand::Other);
+            }
+            b'<' => {
+                let end = find(data, i, b">").unwrap_or(data.len());
+                operands.push(PdfOperand::Str(parse_hex_string(&data[i + 1..end])));
+                i = end + 1;
+            }
+            b'[' => {
+                operands.push(PdfOperand::ArrayStart);
+                i += 1;
+            }
+            b']' => {
+                let start = operands
+                    .iter()
+                    .rposition(|op| matches!(op, PdfOperand::ArrayStart));
+                let items = match start {
+                    Some(start) => operands.drain(start..).skip(1).collect(),
+                    None => Vec::new(),
+                };
+                operands.push(PdfOperand::Array(items));
+                i += 1;
+            }
+            b'/' => {
+                i += 1;
+                while i < data.len() && !is_pdf_delimiter(data[i]) {
+                    i += 1;
+                }
+                operands.push(PdfOperand::Other);
+            }
+            b')' | b'>' | b'{' | b'}' => i += 1,
+            _ => {
+                let start = i;
+                while i < data.len() && !is_pdf_delimiter(data[i]) {
+                    i += 1;
+                }
+                let token = &data[start..i];
+                if let Some(number) = std::str::from_utf8(token)
+                    .ok()
+                    .and_then(|s| s.parse::<f64>().ok())
+                {
+                    operands.push(PdfOperand::Number(number));
+                    continue;
+                }
+                match token {
+                    b"BT" => {
+                        in_text = true;
+                    }
+                    b"ET" => {
+                        in_text = false;
+                        push_line_break(out);
+                    }
+                    b"BI" => {
+                        // Inline image data is binary; resume after `EI`.
+                        let data_at = find(data, i, b"ID").unwrap_or(data.len());
+                        i = find(data, data_at, b"EI").map_or(data.len(), |at| at + 2);
+                    }
+                    b"T*" if in_text => push_line_break(out),
+                    b"Td" | b"TD" if in_text => {
+                        if let [.., PdfOperand::Number(_), PdfOperand::Number(ty)] =
+                            operands.as_slice()
+                            && ty.abs() > f64::EPSILON
+                        {
+                            push_line_break(out);
+                        }
+                    }
+                    b"Tm" if in_text => {
+                        if let [.., PdfOperand::Number(y)] = operands.as_slice() {
+                            if last_line_y.is_some_and(|last| (last - y).abs() > f64::EPSILON) {
+                                push_line_break(out);
+                            }
+                            last_line_y = Some(*y);
+                        }
+                    }
+                    b"Tj" if in_text => {
+                        if let Some(PdfOperand::Str(value)) = operands.last() {
+                            out.push_str(&decode_pdf_string(value));
+                        }
+                    }
+                    b"'" | b"\"" if in_text => {
+                        push_line_break(out);
+                        if let Some(PdfOperand::Str(value)) = operands.last() {
+                            out.push_str(&decode_pdf_string(value));
+                        }
+                    }
+                    b"TJ" if in_text => {
+                        if let Some(PdfOperand::Array(items)) = operands.last() {
+                            for item in items {
+                                match item {
+                                    PdfOperand::Str(value) => {
+                                        out.push_str(&decode_pdf_string(value))
+                                    }
+                                    // Large negative kerning is how most writers encode spaces.
+                                    PdfOperand::Number(n) if *n < -200.0 && !out.ends_with(' ') => {
+                                        out.push(' ')
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                operands.clear();
+            }
+        }
+    }
+}
+
+fn parse_literal_string(data: &[u8], mut i: usize) -> (Vec<u8>, usize) {
+    let mut out = Vec::new();
+    let mut depth = 1usize;
+    while i < data.len() {
+        let b = data[i];
+        i += 1;
+        match b {
+            b'\\' => {
+                let Some(&next) = data.get(i) else {
+                    break;
+                };
+                i += 1;
+                match next {
+                    b'n' => out.push(b'\n'),
+                    b'r' => out.push(b'\r'),
+                    b't' => out.push(b'\t'),
+                    b'b' => out.push(0x08),
+                    b'f' => out.push(0x0c),
+                    b'\r' => {
+                        if data.get(i) == Some(&b'\n') {
+                            i += 1;
+                        }
+                    }
+                    b'\n' => {}
+                    b'0'..=b'7' => {
+                        let mut value = u32::from(next - b'0');
+                        for _ in 0..2 {
+                            match data.get(i) {
+                                Some(&d @ b'0'..=b'7') => {
+                                    value = value * 8 + u32::from(d - b'0');
+                                    i += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        out.push(value as u8);
+                    }
+                    other => out.push(other),
+                }
+            }
+            b'(' => {
+                depth += 1;
+                out.push(b);
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+                out.push(b);
+            }
+            _ => out.push(b),
+        }
+    }
+    (out, i)
+}
+
+fn parse_hex_string(data: &[u8]) -> Vec<u8> {
+    let digits = data
+        .iter()
+        .filter_map(|b| (*b as char).to_digit(16))
+        .map(|d| d as u8)
+        .collect::<Vec<_>>();
+    digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+fn decode_pdf_string(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8]| {
+        let units = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        String::from_utf16_lossy(&units)
+    };
+    if let Some(rest) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        return utf16(rest);
+    }
+    if bytes.len() >= 2 && bytes.len().is_multiple_of(2) && bytes.iter().step_by(2).all(|b| *b == 0)
+    {
+        return utf16(bytes);
+    }
+    bytes
+        .iter()
+        .filter(|b| !b.is_ascii_control() || matches!(b, b'\n' | b'\t'))
+        .map(|b| *b as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compression, write::ZlibEncoder};
+    use std::io::Write as _;
+    use zip::write::SimpleFileOptions;
+
+    fn pdf_with_streams(streams: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        for (n, (dict, data)) in streams.iter().enumerate() {
+            pdf.extend_from_slice(
+                format!(
+                    "{} 0 obj\n<< {dict} /Length {} >>\nstream\n",
+                    n + 1,
+                    data.len()
+                )
+                .as_bytes(),
+            );
+            pdf.extend_from_slice(data);
+            pdf.extend_from_slice(b"\nendstream\nendobj\n");
+        }
+        pdf.extend_from_slice(b"%%EOF\n");
+        pdf
+    }
+
+    fn zip_with(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, body) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(body.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn pdf_text_comes_from_plain_and_flate_content_streams() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(b"BT /F1 12 Tf 72 700 Td [(Second) -300 (page)] TJ 0 -14 Td (caf\\351 \\(ok\\)) Tj ET")
+            .unwrap();
+        let pdf = pdf_with_streams(&[
+            ("", b"BT /F1 12 Tf 72 720 Td (Hello, world.) Tj ET".to_vec()),
+            (
+                "/Subtype /Image /Filter /DCTDecode",
+                b"\xff\xd8 BT (nope) Tj ET".to_vec(),
+            ),
+            ("/Filter /FlateDecode", encoder.finish().unwrap()),
+        ]);
+
+        let text = extract_document_text("pdf", &pdf).unwrap();
+        assert_eq!(text, "Hello, world.\nSecond page\ncafé (ok)");
+    }
+
+    #[test]
+    fn pdf_strings_decode_hex_and_utf16() {
+        assert_eq!(decode_pdf_string(&parse_hex_string(b"48 69")), "Hi");
+        assert_eq!(decode_pdf_string(&parse_hex_string(b"FEFF00480069")), "Hi");
+        assert_eq!(decode_pdf_string(&parse_hex_string(b"00480069")), "Hi");
+    }
+
+    #[test]
+    fn pdf_without_readable_text_yields_none() {
+        let glyphs = pdf_with_streams(&[("", b"BT <0102030405060708> Tj ET".to_vec())]);
+        assert!(extract_document_text("pdf", &glyphs).is_none());
+        assert!(extract_document_text("pdf", b"not a pdf").is_none());
+    }
+
+    #[test]
+    fn docx_pptx_and_odt_text_keeps_paragraphs() {
+        let docx = zip_with(&[(
+            "word/document.xml",
+            r#"<w:document xmlns:w="w"><w:body><w:p><w:r><w:t>Fish &amp; chips</w:t></w:r><w:r><w:tab/><w:t>£4</w:t></w:r></w:p><w:p><w:r><w:instrText>PAGE</w:instrText><w:t>Second</w:t></w:r></w:p></w:body></w:document>"#,
+        )]);
+        assert_eq!(
+            extract_document_text("docx", &docx).unwrap(),
+            "Fish & chips\t£4\nSecond"
+        );
+
+        let pptx = zip_with(&[
+            (
+                "ppt/slides/slide10.xml",
+                r#"<p:sld xmlns:a="a" xmlns:p="p"><a:p><a:r><a:t>Ten</a:t></a:r></a:p></p:sld>"#,
+            ),
+            (
+                "ppt/slides/slide2.xml",
+                r#"<p:sld xmlns:a="a" xmlns:p="p"><a:p><a:r><a:t>Two</a:t></a:r></a:p></p:sld>"#,
+            ),
+            (
+                "ppt/notesSlides/notesSlide1.xml",
+                r#"<a:p xmlns:a="a"><a:t>Notes</a:t></a:p>"#,
+            ),
+        ]);
+        assert_eq!(extract_document_text("pptx", &pptx).unwrap(), "Two\n\nTen");
+
+        let odt = zip_with(&[(
+            "content.xml",
+            r#"<office:document-content xmlns:office="o" xmlns:text="t"><office:body><text:h>Title</text:h><text:p>One<text:s/>two<text:line-break/>three</text:p></office:body></office:document-content>"#,
+        )]);
+        assert_eq!(
+            extract_document_text("odt", &odt).unwrap(),
+            "Title\nOne two\nthree"
+        );
+    }
+
+    #[test]
+    fn sidecar_path_keeps_the_blob_id_prefix() {
+        assert_eq!(
+            document_text_path(Path::new("/blobs/abc.pdf")),
+            PathBuf::from("/blobs/abc.pdf.txt")
+        );
+        assert!(is_extractable_extension("pdf"));
+        assert!(!is_extractable_extension("png"));
+    }
+}
@@ -8,9 +8,10 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::io::{BufRead as _, BufReader, Write as _};
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use super::ansi::strip_ansi_control_sequences;
 use super::cancel_killer::spawn_cancel_killer;
@@ -26,12 +27,51 @@ pub(super) struct DroidTurnParams {
     pub(super) auto_level: Option<String>,
 }
 
-fn resolve_droid_exec() -> PathBuf {
+pub(super) fn resolve_droid_exec() -> PathBuf {
     std::env::var_os(paths::LUBAN_DROID_BIN_ENV)
         .map(PathBuf::from)
         .unwrap_or_else(|| PathBuf::from("droid"))
 }
 
+/// `droid exec --help` answers instantly when healthy; a hung binary must not stall startup.
+const DROID_HELP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returns the output of `droid exec --help`, or `None` when droid is missing, fails, or does
+/// not answer within [`DROID_HELP_TIMEOUT`].
+pub(super) fn droid_exec_help() -> Option<String> {
+    let mut command = Command::new(resolve_droid_exec());
+    command.args(["exec", "--help"]);
+    command_stdout_with_timeout(&mut command, DROID_HELP_TIMEOUT)
+}
+
+/// Runs `command` and returns its stdout when it exits successfully before `timeout`; the
+/// process is killed once the deadline passes.
+fn command_stdout_with_timeout(command: &mut Command, timeout: Duration) -> Option<String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let stdout = spawn_read_to_string(child.stdout.take()?);
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    let output = stdout.join().ok()?;
+    status.success().then_some(output)
+}
+
 /// State for parsing Droid's stream-json JSONL output.
 ///
 /// Droid emits JSONL with event types: system, message, tool_call, tool_result,
@@ -604,6 +644,33 @@ pub(super) fn run_droid_turn_streamed_via_cli(
 mod tests {
     use super::*;
 
+    #[cfg(unix)]
+    #[test]
+    fn command_stdout_with_timeout_kills_hung_commands() {
+        let mut ok = Command::new("sh");
+        ok.args(["-c", "echo ready"]);
+        assert_eq!(
+            command_stdout_with_timeout(&mut ok, Duration::from_secs(5)).as_deref(),
+            Some("ready\n")
+        );
+
+        let mut failing = Command::new("sh");
+        failing.args(["-c", "echo nope; exit 1"]);
+        assert_eq!(
+            command_stdout_with_timeout(&mut failing, Duration::from_secs(5)),
+            None
+        );
+
+        let started = Instant::now();
+        let mut hung = Command::new("sh");
+        hung.args(["-c", "exec sleep 30"]);
+        assert_eq!(
+            command_stdout_with_timeout(&mut hung, Duration::from_millis(200)),
+            None
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
     #[test]
     fn parses_system_event_as_thread_started() {
         let mut state = DroidStreamState::new();
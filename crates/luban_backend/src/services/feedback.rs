@@ -5,6 +5,7 @@ use anyhow::{Context as _, anyhow};
 use luban_domain::{ProjectWorkspaceService, TaskIntentKind, TaskIssueInfo};
 use rand::{Rng as _, rngs::OsRng};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Command;
 
@@ -170,6 +171,10 @@ fn feedback_task_prompt_with_repo(
         &input,
         intent_kind.label(),
         &known_context,
+        HashMap::from([(
+            "issue".to_owned(),
+            format!("#{} {} ({})", issue.number, issue.title, issue.url),
+        )]),
     ))
 }
 
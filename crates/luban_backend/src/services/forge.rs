@@ -0,0 +1,943 @@
+use super::GitWorkspaceService;
+use super::pull_request::{is_forge_merge_ready, pull_request_ci_state_from_check_buckets};
+use anyhow::{Context as _, anyhow};
+use luban_domain::{
+    ImportedIssue, ImportedIssueComment, PullRequestCiState, PullRequestInfo, PullRequestState,
+};
+use std::{path::Path, time::Duration};
+
+const GITLAB_HOSTS_ENV: &str = "LUBAN_GITLAB_HOSTS";
+const GITEA_HOSTS_ENV: &str = "LUBAN_GITEA_HOSTS";
+const GITLAB_TOKEN_ENV: &str = "GITLAB_TOKEN";
+const GITEA_TOKEN_ENV: &str = "GITEA_TOKEN";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const GITEA_PULLS_PAGE_SIZE: usize = 50;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum ForgeKind {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+/// A git remote URL split into its host and repository path.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct RemoteUrl<'a> {
+    /// `http` or `https` for web remotes, `None` for SSH remotes.
+    pub scheme: Option<&'a str>,
+    pub host: &'a str,
+    pub path: &'a str,
+}
+
+pub(super) fn parse_remote_url(url: &str) -> Option<RemoteUrl<'_>> {
+    let trimmed = url.trim().trim_end_matches('/');
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let (scheme, host, path) = if let Some(rest) = trimmed.strip_prefix("git@") {
+        // git@host:owner/repo(.git)
+        let (host, path) = rest.split_once(':')?;
+        (None, host, path)
+    } else if let Some(rest) = trimmed.strip_prefix("ssh://") {
+        // ssh://git@host[:port]/owner/repo(.git)
+        let rest = rest.strip_prefix("git@").unwrap_or(rest);
+        let (host, path) = rest.split_once('/')?;
+        (None, host, path)
+    } else if let Some((scheme, rest)) = trimmed
+        .strip_prefix("https://")
+        .map(|rest| ("https", rest))
+        .or_else(|| trimmed.strip_prefix("http://").map(|rest| ("http", rest)))
+    {
+        let mut parts = rest.splitn(2, '/');
+        let host = parts.next()?;
+        let path = parts.next().unwrap_or_default();
+        // Drop `user[:token]@` credentials embedded in HTTPS remotes.
+        let host = host.rsplit_once('@').map(|(_, host)| host).unwrap_or(host);
+        (Some(scheme), host, path)
+    } else {
+        return None;
+    };
+
+    if host.is_empty() {
+        return None;
+    }
+    let path = path.trim_start_matches('/').trim_end_matches(".git");
+    Some(RemoteUrl { scheme, host, path })
+}
+
+/// Where a repository's pull requests live, derived from its remote URL.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct ForgeRemote {
+    pub kind: ForgeKind,
+    /// `scheme://host[:port]` of the forge's web UI and API.
+    pub base_url: String,
+    /// `owner/repo` (GitLab allows nested groups: `group/subgroup/repo`).
+    pub repo_path: String,
+}
+
+/// Selects the forge for a remote URL.
+///
+/// `gitlab.com`, `gitlab.*` hosts and hosts listed in `LUBAN_GITLAB_HOSTS` map to GitLab;
+/// `codeberg.org`, `gitea.*` hosts and hosts listed in `LUBAN_GITEA_HOSTS` map to Gitea. Every
+/// other host, `github.com` included, maps to GitHub and is left to `gh`, which covers GitHub
+/// Enterprise hosts it has been authenticated against and reports hosts it does not know.
+/// URLs without an `owner/repo` path are an error.
+pub(super) fn forge_remote_from_url(url: &str) -> anyhow::Result<ForgeRemote> {
+    let gitlab_hosts = std::env::var(GITLAB_HOSTS_ENV).unwrap_or_default();
+    let gitea_hosts = std::env::var(GITEA_HOSTS_ENV).unwrap_or_default();
+    forge_remote_from_url_with_hosts(url, &gitlab_hosts, &gitea_hosts)
+}
+
+fn forge_remote_from_url_with_hosts(
+    url: &str,
+    gitlab_hosts: &str,
+    gitea_hosts: &str,
+) -> anyhow::Result<ForgeRemote> {
+    let remote = parse_remote_url(url)
+        .filter(|remote| remote.path.split('/').filter(|s| !s.is_empty()).count() >= 2)
+        .ok_or_else(|| anyhow!("unsupported remote url: {}", url.trim()))?;
+
+    let host_name = remote
+        .host
+        .split_once(':')
+        .map(|(name, _)| name)
+        .unwrap_or(remote.host)
+        .to_ascii_lowercase();
+    let listed = |hosts: &str| {
+        hosts
+            .split(',')
+            .map(str::trim)
+            .any(|h| !h.is_empty() && h.eq_ignore_ascii_case(&host_name))
+    };
+
+    let kind = if host_name == "github.com" {
+        ForgeKind::GitHub
+    } else if listed(gitlab_hosts) || host_name == "gitlab.com" || host_name.starts_with("gitlab.")
+    {
+        ForgeKind::GitLab
+    } else if listed(gitea_hosts) || host_name == "codeberg.org" || host_name.starts_with("gitea.")
+    {
+        ForgeKind::Gitea
+    } else {
+        ForgeKind::GitHub
+    };
+
+    // SSH ports say nothing about where the web API is served.
+    let base_url = match remote.scheme {
+        Some(scheme) => format!("{scheme}://{}", remote.host),
+        None => format!("https://{host_name}"),
+    };
+
+    Ok(ForgeRemote {
+        kind,
+        base_url,
+        repo_path: remote.path.to_owned(),
+    })
+}
+
+/// A pull (merge) request as reported by a REST forge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct ForgePullRequest {
+    pub info: PullRequestInfo,
+    pub web_url: String,
+    /// Link to the first failing pipeline or status check, when CI failed.
+    pub failing_check_url: Option<String>,
+}
+
+/// Pull request lookup for forges queried over HTTP. GitHub goes through `gh` instead.
+pub(super) trait PullRequestForge {
+    /// Returns the most recently updated pull request whose source branch is `branch`.
+    fn pull_request_for_branch(&self, branch: &str) -> anyhow::Result<Option<ForgePullRequest>>;
+}
+
+pub(super) fn rest_forge_for_remote(remote: &ForgeRemote) -> Option<Box<dyn PullRequestForge>> {
+    match remote.kind {
+        ForgeKind::GitHub => None,
+        ForgeKind::GitLab => Some(Box::new(GitLabForge::new(
+            &remote.base_url,
+            &remote.repo_path,
+            token_from_env(GITLAB_TOKEN_ENV),
+        ))),
+        ForgeKind::Gitea => Some(Box::new(GiteaForge::new(
+            &remote.base_url,
+            &remote.repo_path,
+            token_from_env(GITEA_TOKEN_ENV),
+        ))),
+    }
+}
+
+/// Issue access for forges queried over HTTP. GitHub goes through `gh` instead.
+pub(super) trait IssueForge {
+    fn issue(&self, number: u64) -> anyhow::Result<ImportedIssue>;
+
+    fn comment_on_issue(&self, number: u64, body: &str) -> anyhow::Result<()>;
+
+    fn close_issue(&self, number: u64) -> anyhow::Result<()>;
+}
+
+pub(super) fn rest_issue_forge_for_remote(remote: &ForgeRemote) -> Option<Box<dyn IssueForge>> {
+    match remote.kind {
+        ForgeKind::GitHub => None,
+        ForgeKind::GitLab => Some(Box::new(GitLabForge::new(
+            &remote.base_url,
+            &remote.repo_path,
+            token_from_env(GITLAB_TOKEN_ENV),
+        ))),
+        ForgeKind::Gitea => Some(Box::new(GiteaForge::new(
+            &remote.base_url,
+            &remote.repo_path,
+            token_from_env(GITEA_TOKEN_ENV),
+        ))),
+    }
+}
+
+fn token_from_env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+fn http_client() -> anyhow::Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent("luban")
+        .build()
+        .context("failed to build http client")
+}
+
+fn api_url<'a>(
+    base_url: &str,
+    segments: impl IntoIterator<Item = &'a str>,
+    query: &[(&str, &str)],
+) -> anyhow::Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(base_url)
+        .with_context(|| format!("invalid forge base url: {base_url}"))?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("invalid forge base url: {base_url}"))?
+        .pop_if_empty()
+        .extend(segments);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    Ok(url)
+}
+
+fn get_json<T: for<'de> serde::Deserialize<'de>>(
+    request: reqwest::blocking::RequestBuilder,
+) -> anyhow::Result<T> {
+    let response = request.send().context("forge request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        let url = response.url().clone();
+        return Err(anyhow!(
+            "forge request to {url} failed with status {status}"
+        ));
+    }
+    response
+        .json::<T>()
+        .context("failed to parse forge response")
+}
+
+fn send_ok(request: reqwest::blocking::RequestBuilder) -> anyhow::Result<()> {
+    let response = request.send().context("forge request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        let url = response.url().clone();
+        return Err(anyhow!(
+            "forge request to {url} failed with status {status}"
+        ));
+    }
+    Ok(())
+}
+
+pub(super) struct GitLabForge {
+    client: Option<reqwest::blocking::Client>,
+    base_url: String,
+    project: String,
+    token: Option<String>,
+}
+
+impl GitLabForge {
+    pub(super) fn new(base_url: &str, project: &str, token: Option<String>) -> Self {
+        Self {
+            client: http_client().ok(),
+            base_url: base_url.to_owned(),
+            project: project.to_owned(),
+            token,
+        }
+    }
+
+    fn request(
+        &self,
+        method: reqwest::Method,
+        segments: &[&str],
+        query: &[(&str, &str)],
+    ) -> anyhow::Result<reqwest::blocking::RequestBuilder> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| anyhow!("http client unavailable"))?;
+        // The project path is a single segment, so its slashes are percent-encoded.
+        let url = api_url(
+            &self.base_url,
+            ["api", "v4", "projects", self.project.as_str()]
+                .into_iter()
+                .chain(segments.iter().copied()),
+            query,
+        )?;
+        let mut request = client.request(method, url);
+        if let Some(token) = &self.token {
+            request = request.header("PRIVATE-TOKEN", token);
+        }
+        Ok(request)
+    }
+
+    fn get<T: for<'de> serde::Deserialize<'de>>(
+        &self,
+        segments: &[&str],
+        query: &[(&str, &str)],
+    ) -> anyhow::Result<T> {
+        get_json(self.request(reqwest::Method::GET, segments, query)?)
+    }
+}
+
+impl PullRequestForge for GitLabForge {
+    fn pull_request_for_branch(&self, branch: &str) -> anyhow::Result<Option<ForgePullRequest>> {
+        #[derive(serde::Deserialize)]
+        struct MergeRequestRef {
+            iid: u64,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Pipeline {
+            #[serde(default)]
+            status: String,
+            #[serde(default)]
+            web_url: Option<String>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct MergeRequest {
+            iid: u64,
+            #[serde(default)]
+            state: String,
+            #[serde(default)]
+            draft: bool,
+            #[serde(default)]
+            work_in_progress: bool,
+            #[serde(default)]
+            detailed_merge_status: String,
+            #[serde(default)]
+            head_pipeline: Option<Pipeline>,
+            #[serde(default)]
+            web_url: String,
+        }
+
+        let found: Vec<MergeRequestRef> = self.get(
+            &["merge_requests"],
+            &[
+                ("source_branch", branch),
+                ("state", "all"),
+                ("order_by", "updated_at"),
+                ("sort", "desc"),
+                ("per_page", "1"),
+            ],
+        )?;
+        let Some(found) = found.first() else {
+            return Ok(None);
+        };
+
+        // The list endpoint omits the head pipeline, so fetch the merge request itself.
+        let iid = found.iid.to_string();
+        let mr: MergeRequest = self.get(&["merge_requests", iid.as_str()], &[])?;
+
+        let state = match mr.state.as_str() {
+            "merged" => PullRequestState::Merged,
+            "closed" | "locked" => PullRequestState::Closed,
+            _ => PullRequestState::Open,
+        };
+        let is_draft = mr.draft || mr.work_in_progress;
+        let (ci_state, failing_check_url) = if state != PullRequestState::Open {
+            (None, None)
+        } else {
+            match &mr.head_pipeline {
+                None => (Some(PullRequestCiState::Success), None),
+                Some(pipeline) => {
+                    let ci_state =
+                        pull_request_ci_state_from_check_buckets([gitlab_pipeline_bucket(
+                            &pipeline.status,
+                        )]);
+                    let failing = (ci_state == Some(PullRequestCiState::Failure))
+                        .then(|| pipeline.web_url.clone())
+                        .flatten();
+                    (ci_state, failing)
+                }
+            }
+        };
+        let merge_ready = is_forge_merge_ready(
+            state,
+            is_draft,
+            mr.detailed_merge_status == "mergeable",
+            ci_state,
+        );
+
+        Ok(Some(ForgePullRequest {
+            info: PullRequestInfo {
+                number: mr.iid,
+                is_draft,
+                state,
+                ci_state,
+                merge_ready,
+            },
+            web_url: mr.web_url,
+            failing_check_url,
+        }))
+    }
+}
+
+impl IssueForge for GitLabForge {
+    fn issue(&self, number: u64) -> anyhow::Result<ImportedIssue> {
+        #[derive(serde::Deserialize)]
+        struct Author {
+            #[serde(default)]
+            username: String,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Issue {
+            iid: u64,
+            #[serde(default)]
+            title: String,
+            #[serde(default)]
+            description: Option<String>,
+            #[serde(default)]
+            labels: Vec<String>,
+            #[serde(default)]
+            web_url: String,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Note {
+            #[serde(default)]
+            body: String,
+            #[serde(default)]
+            system: bool,
+            #[serde(default)]
+            created_at: String,
+            author: Option<Author>,
+        }
+
+        let iid = number.to_string();
+        let issue: Issue = self.get(&["issues", iid.as_str()], &[])?;
+        let notes: Vec<Note> = self.get(
+            &["issues", iid.as_str(), "notes"],
+            &[
+                ("sort", "asc"),
+                ("order_by", "created_at"),
+                ("per_page", "100"),
+            ],
+        )?;
+
+        Ok(ImportedIssue {
+            number: issue.iid,
+            title: issue.title,
+            url: issue.web_url,
+            body: issue.description.unwrap_or_default(),
+            labels: issue.labels,
+            comments: notes
+                .into_iter()
+                .filter(|note| !note.system)
+                .map(|note| ImportedIssueComment {
+                    author: note.author.map(|a| a.username).unwrap_or_default(),
+                    body: note.body,
+                    created_at: note.created_at,
+                })
+                .collect(),
+        })
+    }
+
+    fn comment_on_issue(&self, number: u64, body: &str) -> anyhow::Result<()> {
+        let iid = number.to_string();
+        let request = self.request(
+            reqwest::Method::POST,
+            &["issues", iid.as_str(), "notes"],
+            &[],
+        )?;
+        send_ok(request.json(&serde_json::json!({ "body": body })))
+    }
+
+    fn close_issue(&self, number: u64) -> anyhow::Result<()> {
+        let iid = number.to_string();
+        let request = self.request(reqwest::Method::PUT, &["issues", iid.as_str()], &[])?;
+        send_ok(request.json(&serde_json::json!({ "state_event": "close" })))
+    }
+}
+
+/// Maps a GitLab pipeline status onto the `gh pr checks` bucket vocabulary.
+fn gitlab_pipeline_bucket(status: &str) -> &'static str {
+    match status {
+        "success" => "pass",
+        "failed" => "fail",
+        "canceled" => "cancel",
+        "skipped" | "manual" => "skipping",
+        _ => "pending",
+    }
+}
+
+pub(super) struct GiteaForge {
+    client: Option<reqwest::blocking::Client>,
+    base_url: String,
+    repo: String,
+    token: Option<String>,
+}
+
+impl GiteaForge {
+    pub(super) fn new(base_url: &str, repo: &str, token: Option<String>) -> Self {
+        Self {
+            client: http_client().ok(),
+            base_url: base_url.to_owned(),
+            repo: repo.to_owned(),
+            token,
+        }
+    }
+
+    fn request(
+        &self,
+        method: reqwest::Method,
+        segments: &[&str],
+        query: &[(&str, &str)],
+    ) -> anyhow::Result<reqwest::blocking::RequestBuilder> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| anyhow!("http client unavailable"))?;
+        let url = api_url(
+            &self.base_url,
+            ["api", "v1", "repos"]
+                .into_iter()
+                .chain(self.repo.split('/'))
+                .chain(segments.iter().copied()),
+            query,
+        )?;
+        let mut request = client.request(method, url);
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("token {token}"));
+        }
+        Ok(request)
+    }
+
+    fn get<T: for<'de> serde::Deserialize<'de>>(
+        &self,
+        segments: &[&str],
+        query: &[(&str, &str)],
+    ) -> anyhow::Result<T> {
+        get_json(self.request(reqwest::Method::GET, segments, query)?)
+    }
+}
+
+impl PullRequestForge for GiteaForge {
+    fn pull_request_for_branch(&self, branch: &str) -> anyhow::Result<Option<ForgePullRequest>> {
+        #[derive(serde::Deserialize)]
+        struct HeadRepo {
+            #[serde(default)]
+            full_name: String,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Head {
+            #[serde(default, rename = "ref")]
+            ref_name: String,
+            #[serde(default)]
+            sha: String,
+            /// `None` once the head repository (usually a fork) has been deleted.
+            #[serde(default)]
+            repo: Option<HeadRepo>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Pull {
+            number: u64,
+            #[serde(default)]
+            state: String,
+            #[serde(default)]
+            merged: bool,
+            #[serde(default)]
+            draft: bool,
+            #[serde(default)]
+            mergeable: bool,
+            #[serde(default)]
+            html_url: String,
+            head: Head,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct CommitStatus {
+            #[serde(default)]
+            status: String,
+            #[serde(default)]
+            target_url: Option<String>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct CombinedStatus {
+            #[serde(default)]
+            state: String,
+            #[serde(default)]
+            total_count: u64,
+            #[serde(default)]
+            statuses: Vec<CommitStatus>,
+        }
+
+        // Gitea has no source-branch filter: page through the most recently updated pull
+        // requests for one whose head is this branch of this repository (not a fork's branch of
+        // the same name).
+        let limit = GITEA_PULLS_PAGE_SIZE.to_string();
+        let mut found = None;
+        for page in 1.. {
+            let page = page.to_string();
+            let pulls: Vec<Pull> = self.get(
+                &["pulls"],
+                &[
+                    ("state", "all"),
+                    ("sort", "recentupdate"),
+                    ("limit", &limit),
+                    ("page", &page),
+                ],
+            )?;
+            let last_page = pulls.len() < GITEA_PULLS_PAGE_SIZE;
+            found = pulls.into_iter().find(|p| {
+                p.head.ref_name == branch
+                    && p.head
+                        .repo
+                        .as_ref()
+                        .is_some_and(|repo| repo.full_name.eq_ignore_ascii_case(&self.repo))
+            });
+            if found.is_some() || last_page {
+                break;
+            }
+        }
+        let Some(pull) = found else {
+            return Ok(None);
+        };
+
+        let state = if pull.merged {
+            PullRequestState::Merged
+        } else if pull.state == "closed" {
+            PullRequestState::Closed
+        } else {
+            PullRequestState::Open
+        };
+
+        let (ci_state, failing_check_url) = if state != PullRequestState::Open {
+            (None, None)
+        } else {
+            let status: CombinedStatus =
+                self.get(&["commits", pull.head.sha.as_str(), "status"], &[])?;
+            if status.total_count == 0 && status.statuses.is_empty() {
+                (Some(PullRequestCiState::Success), None)
+            } else {
+                let ci_state =
+                    pull_request_ci_state_from_check_buckets([gitea_status_bucket(&status.state)]);
+                let failing = status
+                    .statuses
+                    .iter()
+                    .find(|s| gitea_status_bucket(&s.status) == "fail")
+                    .and_then(|s| s.target_url.clone())
+                    .filter(|url| !url.is_empty());
+                (ci_state, failing)
+            }
+        };
+        let merge_ready = is_forge_merge_ready(state, pull.draft, pull.mergeable, ci_state);
+
+        Ok(Some(ForgePullRequest {
+            info: PullRequestInfo {
+                number: pull.number,
+                is_draft: pull.draft,
+                state,
+                ci_state,
+                merge_ready,
+            },
+            web_url: pull.html_url,
+            failing_check_url,
+        }))
+    }
+}
+
+impl IssueForge for GiteaForge {
+    fn issue(&self, number: u64) -> anyhow::Result<ImportedIssue> {
+        #[derive(serde::Deserialize)]
+        struct User {
+            #[serde(default)]
+            login: String,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Label {
+            #[serde(default)]
+            name: String,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Issue {
+            number: u64,
+            #[serde(default)]
+            title: String,
+            #[serde(default)]
+            body: String,
+            #[serde(default)]
+            labels: Vec<Label>,
+            #[serde(default)]
+            html_url: String,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Comment {
+            #[serde(default)]
+            body: String,
+            #[serde(default)]
+            created_at: String,
+            user: Option<User>,
+        }
+
+        let index = number.to_string();
+        let issue: Issue = self.get(&["issues", index.as_str()], &[])?;
+        let comments: Vec<Comment> = self.get(&["issues", index.as_str(), "comments"], &[])?;
+
+        Ok(ImportedIssue {
+            number: issue.number,
+            title: issue.title,
+            url: issue.html_url,
+            body: issue.body,
+            labels: issue.labels.into_iter().map(|l| l.name).collect(),
+            comments: comments
+                .into_iter()
+                .map(|comment| ImportedIssueComment {
+                    author: comment.user.map(|u| u.login).unwrap_or_default(),
+                    body: comment.body,
+                    created_at: comment.created_at,
+                })
+                .collect(),
+        })
+    }
+
+    fn comment_on_issue(&self, number: u64, body: &str) -> anyhow::Result<()> {
+        let index = number.to_string();
+        let request = self.request(
+            reqwest::Method::POST,
+            &["issues", index.as_str(), "comments"],
+            &[],
+        )?;
+        send_ok(request.json(&serde_json::json!({ "body": body })))
+    }
+
+    fn close_issue(&self, number: u64) -> anyhow::Result<()> {
+        let index = number.to_string();
+        let request = self.request(reqwest::Method::PATCH, &["issues", index.as_str()], &[])?;
+        send_ok(request.json(&serde_json::json!({ "state": "closed" })))
+    }
+}
+
+/// Maps a Gitea commit status onto the `gh pr checks` bucket vocabulary.
+fn gitea_status_bucket(state: &str) -> &'static str {
+    match state {
+        "success" | "warning" => "pass",
+        "failure" | "error" => "fail",
+        _ => "pending",
+    }
+}
+
+impl GitWorkspaceService {
+    /// Looks up the worktree branch's pull request on a GitLab or Gitea remote.
+    ///
+    /// Returns `None` when the remote is GitHub (or any host `gh` may know, such as GitHub
+    /// Enterprise), in which case callers use `gh`.
+    pub(super) fn forge_pull_request(
+        &self,
+        worktree_path: &Path,
+    ) -> Option<anyhow::Result<Option<ForgePullRequest>>> {
+        let remote = self
+            .select_remote_best_effort(worktree_path)
+            .ok()
+            .flatten()?;
+        let url = self
+            .run_git(worktree_path, ["remote", "get-url", &remote])
+            .ok()?;
+        let forge = rest_forge_for_remote(&forge_remote_from_url(&url).ok()?)?;
+        Some(
+            self.run_git(worktree_path, ["rev-parse", "--abbrev-ref", "HEAD"])
+                .and_then(|branch| forge.pull_request_for_branch(&branch)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::serve_json_stub;
+
+    #[test]
+    fn remote_urls_select_forge_and_api_base() {
+        let gitlab = forge_remote_from_url_with_hosts(
+            "git@git.corp.example:platform/tools/luban.git",
+            "git.corp.example",
+            "",
+        )
+        .expect("gitlab remote");
+        assert_eq!(gitlab.kind, ForgeKind::GitLab);
+        assert_eq!(gitlab.base_url, "https://git.corp.example");
+        assert_eq!(gitlab.repo_path, "platform/tools/luban");
+
+        let gitea = forge_remote_from_url_with_hosts(
+            "http://127.0.0.1:3000/owner/repo.git",
+            "",
+            "127.0.0.1",
+        )
+        .expect("gitea remote");
+        assert_eq!(gitea.kind, ForgeKind::Gitea);
+        assert_eq!(gitea.base_url, "http://127.0.0.1:3000");
+
+        let ssh_port =
+            forge_remote_from_url_with_hosts("ssh://git@gitlab.example.com:2222/a/b.git", "", "")
+                .expect("ssh remote");
+        assert_eq!(ssh_port.kind, ForgeKind::GitLab);
+        assert_eq!(ssh_port.base_url, "https://gitlab.example.com");
+
+        let github = forge_remote_from_url_with_hosts("https://github.com/a/b", "", "")
+            .expect("github remote");
+        assert_eq!(github.kind, ForgeKind::GitHub);
+        // GitHub Enterprise hosts are left to `gh`.
+        let enterprise = forge_remote_from_url_with_hosts("git@ghe.corp.example:a/b.git", "", "")
+            .expect("github enterprise remote");
+        assert_eq!(enterprise.kind, ForgeKind::GitHub);
+        assert_eq!(enterprise.base_url, "https://ghe.corp.example");
+
+        assert!(forge_remote_from_url_with_hosts("https://example.com/", "", "").is_err());
+    }
+
+    #[test]
+    fn gitlab_merge_request_status_is_read_from_stub_server() {
+        let base_url = serve_json_stub(vec![
+            (
+                "/api/v4/projects/group%2Frepo/merge_requests?source_branch=feature%2Fx&state=all&order_by=updated_at&sort=desc&per_page=1",
+                r#"[{"iid": 7}]"#,
+            ),
+            (
+                "/api/v4/projects/group%2Frepo/merge_requests/7",
+                r#"{
+                    "iid": 7,
+                    "state": "opened",
+                    "draft": false,
+                    "detailed_merge_status": "mergeable",
+                    "head_pipeline": {"status": "failed", "web_url": "http://ci/pipelines/1"},
+                    "web_url": "http://gitlab/group/repo/-/merge_requests/7"
+                }"#,
+            ),
+        ]);
+
+        let forge = GitLabForge::new(&base_url, "group/repo", None);
+        let pr = forge
+            .pull_request_for_branch("feature/x")
+            .expect("request should succeed")
+            .expect("merge request should be found");
+        assert_eq!(pr.info.number, 7);
+        assert_eq!(pr.info.state, PullRequestState::Open);
+        assert_eq!(pr.info.ci_state, Some(PullRequestCiState::Failure));
+        assert!(!pr.info.merge_ready);
+        assert_eq!(
+            pr.failing_check_url.as_deref(),
+            Some("http://ci/pipelines/1")
+        );
+        assert_eq!(pr.web_url, "http://gitlab/group/repo/-/merge_requests/7");
+
+        let err = forge
+            .pull_request_for_branch("other")
+            .expect_err("unknown route is an error");
+        assert!(err.to_string().contains("404"), "{err:#}");
+    }
+
+    #[test]
+    fn gitea_pull_request_status_is_read_from_stub_server() {
+        // A full first page without a match, including a fork's pull request from a branch of the
+        // same name, so the lookup has to filter by head repository and move on to page two.
+        let fork = r#"{"number": 9, "state": "open", "html_url": "http://gitea/owner/repo/pulls/9",
+            "head": {"ref": "feature", "sha": "fff", "repo": {"full_name": "fork/repo"}}}"#;
+        let other = r#"{"number": 3, "state": "open", "html_url": "http://gitea/owner/repo/pulls/3",
+            "head": {"ref": "other", "sha": "aaa", "repo": {"full_name": "owner/repo"}}}"#;
+        let first_page = std::iter::once(fork)
+            .chain(std::iter::repeat_n(other, GITEA_PULLS_PAGE_SIZE - 1))
+            .collect::<Vec<_>>()
+            .join(",");
+        let first_page: &'static str = format!("[{first_page}]").leak();
+
+        let base_url = serve_json_stub(vec![
+            (
+                "/api/v1/repos/owner/repo/pulls?state=all&sort=recentupdate&limit=50&page=1",
+                first_page,
+            ),
+            (
+                "/api/v1/repos/owner/repo/pulls?state=all&sort=recentupdate&limit=50&page=2",
+                r#"[
+                    {"number": 4, "state": "open", "mergeable": true,
+                     "html_url": "http://gitea/owner/repo/pulls/4",
+                     "head": {"ref": "feature", "sha": "bbb",
+                              "repo": {"full_name": "owner/repo"}}}
+                ]"#,
+            ),
+            (
+                "/api/v1/repos/owner/repo/commits/bbb/status",
+                r#"{"state": "success", "total_count": 1,
+                    "statuses": [{"status": "success", "target_url": "http://ci/1"}]}"#,
+            ),
+        ]);
+
+        let forge = GiteaForge::new(&base_url, "owner/repo", Some("secret".to_owned()));
+        let pr = forge
+            .pull_request_for_branch("feature")
+            .expect("request should succeed")
+            .expect("pull request should be found");
+        assert_eq!(pr.info.number, 4);
+        assert_eq!(pr.info.ci_state, Some(PullRequestCiState::Success));
+        assert!(pr.info.merge_ready);
+        assert_eq!(pr.failing_check_url, None);
+
+        assert_eq!(
+            forge
+                .pull_request_for_branch("missing")
+                .expect("request should succeed"),
+            None
+        );
+    }
+
+    #[test]
+    fn gitlab_issue_and_notes_are_read_from_stub_server() {
+        let base_url = serve_json_stub(vec![
+            (
+                "/api/v4/projects/group%2Frepo/issues/12",
+                r#"{"iid": 12, "title": "Crash on save", "description": "Steps...",
+                    "labels": ["bug"], "web_url": "http://gitlab/group/repo/-/issues/12"}"#,
+            ),
+            (
+                "/api/v4/projects/group%2Frepo/issues/12/notes?sort=asc&order_by=created_at&per_page=100",
+                r#"[
+                    {"body": "added ~bug label", "system": true, "author": {"username": "bot"}},
+                    {"body": "Same here.", "system": false, "created_at": "2026-01-01T00:00:00Z",
+                     "author": {"username": "alice"}}
+                ]"#,
+            ),
+        ]);
+
+        let forge = GitLabForge::new(&base_url, "group/repo", None);
+        let issue = forge.issue(12).expect("issue should load");
+        assert_eq!(issue.number, 12);
+        assert_eq!(issue.title, "Crash on save");
+        assert_eq!(issue.body, "Steps...");
+        assert_eq!(issue.labels, vec!["bug"]);
+        assert_eq!(issue.url, "http://gitlab/group/repo/-/issues/12");
+        assert_eq!(issue.comments.len(), 1);
+        assert_eq!(issue.comments[0].author, "alice");
+        assert_eq!(issue.comments[0].body, "Same here.");
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{Context as _, anyhow};
 use serde::Deserialize;
+use std::path::Path;
 use std::process::Command;
 
 pub(super) fn ensure_gh_cli() -> anyhow::Result<()> {
@@ -21,8 +22,14 @@ pub(super) fn ensure_gh_cli() -> anyhow::Result<()> {
     Ok(())
 }
 
-pub(super) fn run_gh_json<T: for<'de> Deserialize<'de>>(args: &[&str]) -> anyhow::Result<T> {
-    let out = Command::new("gh").args(args).output().map_err(|err| {
+fn gh_output(dir: Option<&Path>, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let mut command = Command::new("gh");
+    command.args(args);
+    if let Some(dir) = dir {
+        // Lets `{owner}` and `{repo}` placeholders in `gh api` calls resolve against `dir`.
+        command.current_dir(dir);
+    }
+    let out = command.output().map_err(|err| {
         if err.kind() == std::io::ErrorKind::NotFound {
             anyhow!(
                 "missing gh executable: install GitHub CLI (gh) and ensure it is available on PATH"
@@ -35,8 +42,34 @@ pub(super) fn run_gh_json<T: for<'de> Deserialize<'de>>(args: &[&str]) -> anyhow
         let stderr = String::from_utf8_lossy(&out.stderr);
         return Err(anyhow!("gh failed ({}): {}", out.status, stderr.trim()));
     }
-    let stdout = String::from_utf8_lossy(&out.stdout);
-    let parsed = serde_json::from_str::<T>(stdout.trim())
-        .with_context(|| format!("failed to parse gh json for args: {}", args.join(" ")))?;
-    Ok(parsed)
+    Ok(out.stdout)
+}
+
+fn parse_gh_json<T: for<'de> Deserialize<'de>>(stdout: &[u8], args: &[&str]) -> anyhow::Result<T> {
+    let stdout = String::from_utf8_lossy(stdout);
+    serde_json::from_str::<T>(stdout.trim())
+        .with_context(|| format!("failed to parse gh json for args: {}", args.join(" ")))
+}
+
+pub(super) fn run_gh(args: &[&str]) -> anyhow::Result<()> {
+    gh_output(None, args).map(|_| ())
+}
+
+pub(super) fn run_gh_json<T: for<'de> Deserialize<'de>>(args: &[&str]) -> anyhow::Result<T> {
+    parse_gh_json(&gh_output(None, args)?, args)
+}
+
+pub(super) fn run_gh_in(dir: &Path, args: &[&str]) -> anyhow::Result<()> {
+    gh_output(Some(dir), args).map(|_| ())
+}
+
+pub(super) fn run_gh_text_in(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    gh_output(Some(dir), args).map(|stdout| String::from_utf8_lossy(&stdout).into_owned())
+}
+
+pub(super) fn run_gh_json_in<T: for<'de> Deserialize<'de>>(
+    dir: &Path,
+    args: &[&str],
+) -> anyhow::Result<T> {
+    parse_gh_json(&gh_output(Some(dir), args)?, args)
 }
@@ -1,4 +1,5 @@
 use super::GitWorkspaceService;
+use super::forge::parse_remote_url;
 use anyhow::{Context as _, anyhow};
 use std::{ffi::OsStr, path::Path, path::PathBuf, process::Command};
 
@@ -38,6 +39,31 @@ impl GitWorkspaceService {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
     }
 
+    /// Runs `git worktree remove --force`, treating a worktree git no longer knows about as
+    /// already removed.
+    pub(super) fn remove_worktree(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+    ) -> anyhow::Result<()> {
+        let path_str = worktree_path
+            .to_str()
+            .ok_or_else(|| anyhow!("invalid worktree path"))?;
+        let Err(err) = self.run_git(repo_path, ["worktree", "remove", "--force", path_str]) else {
+            return Ok(());
+        };
+        let message = format!("{err:#}");
+        let missing = message.contains("not a working tree")
+            || message.contains("is not a git repository")
+            || message.contains("no such file or directory")
+            || message.contains("No such file or directory");
+        if missing {
+            return Ok(());
+        }
+        Err(err)
+            .with_context(|| format!("failed to remove worktree at {}", worktree_path.display()))
+    }
+
     pub(super) fn repo_root(&self, repo_path: &Path) -> anyhow::Result<PathBuf> {
         let root = self
             .run_git(repo_path, ["rev-parse", "--show-toplevel"])
@@ -70,37 +96,12 @@ impl GitWorkspaceService {
     }
 
     pub(super) fn github_repo_id_from_remote_url(url: &str) -> Option<String> {
-        let trimmed = url.trim().trim_end_matches('/');
-        if trimmed.is_empty() {
-            return None;
-        }
-
-        let (host, path) = if let Some(rest) = trimmed.strip_prefix("git@") {
-            // git@github.com:owner/repo(.git)
-            rest.split_once(':')?
-        } else if let Some(rest) = trimmed.strip_prefix("ssh://") {
-            // ssh://git@github.com/owner/repo(.git)
-            let rest = rest.strip_prefix("git@").unwrap_or(rest);
-            rest.split_once('/')?
-        } else if let Some(rest) = trimmed.strip_prefix("https://") {
-            let mut parts = rest.splitn(2, '/');
-            let host = parts.next()?;
-            let path = parts.next().unwrap_or_default();
-            (host, path)
-        } else if let Some(rest) = trimmed.strip_prefix("http://") {
-            let mut parts = rest.splitn(2, '/');
-            let host = parts.next()?;
-            let path = parts.next().unwrap_or_default();
-            (host, path)
-        } else {
-            return None;
-        };
-
-        if !host.eq_ignore_ascii_case("github.com") {
+        let remote = parse_remote_url(url)?;
+        if !remote.host.eq_ignore_ascii_case("github.com") {
             return None;
         }
 
-        let path = path.trim_start_matches('/').trim_end_matches(".git");
+        let path = remote.path;
         let mut iter = path.split('/').filter(|s| !s.is_empty());
         let owner = iter.next()?;
         let repo = iter.next()?;
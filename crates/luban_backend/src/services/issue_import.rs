@@ -0,0 +1,254 @@
+use super::GitWorkspaceService;
+use super::forge::{
+    ForgeKind, ForgeRemote, forge_remote_from_url, parse_remote_url, rest_issue_forge_for_remote,
+};
+use super::gh_cli::{ensure_gh_cli, run_gh, run_gh_json};
+use anyhow::{Context as _, anyhow};
+use luban_domain::{ImportedIssue, ImportedIssueComment, IssueMergeAction};
+use serde::Deserialize;
+use std::path::Path;
+
+/// An issue as typed by the user.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum IssueRef {
+    /// A number in the project's own repository: `42` or `#42`.
+    Number(u64),
+    /// A full issue URL, which may point at another repository.
+    Url { remote: ForgeRemote, number: u64 },
+}
+
+fn parse_issue_ref(raw: &str) -> anyhow::Result<IssueRef> {
+    let raw = raw.trim();
+    if let Ok(number) = raw.strip_prefix('#').unwrap_or(raw).parse::<u64>() {
+        return (number > 0)
+            .then_some(IssueRef::Number(number))
+            .ok_or_else(|| anyhow!("issue numbers start at 1"));
+    }
+
+    let without_fragment = raw.split(['?', '#']).next().unwrap_or_default();
+    // GitHub and Gitea: owner/repo/issues/N. GitLab: group/repo/-/issues/N.
+    let (scheme, host, repo_path, number) = parse_remote_url(without_fragment)
+        .and_then(|url| {
+            let (repo_path, number) = url.path.rsplit_once("/issues/")?;
+            let number = number.parse::<u64>().ok().filter(|n| *n > 0)?;
+            Some((url.scheme?, url.host, repo_path, number))
+        })
+        .ok_or_else(|| anyhow!("expected an issue number or issue URL"))?;
+    let repo_path = repo_path.strip_suffix("/-").unwrap_or(repo_path);
+    let remote = forge_remote_from_url(&format!("{scheme}://{host}/{repo_path}"))?;
+    Ok(IssueRef::Url { remote, number })
+}
+
+/// `gh -R` value for a remote: `owner/repo`, prefixed with the host outside github.com.
+fn gh_repo_arg(remote: &ForgeRemote) -> String {
+    let host = remote
+        .base_url
+        .split_once("://")
+        .map(|(_, host)| host)
+        .unwrap_or(&remote.base_url);
+    if host.eq_ignore_ascii_case("github.com") {
+        remote.repo_path.clone()
+    } else {
+        format!("{host}/{}", remote.repo_path)
+    }
+}
+
+fn merge_comment(kind: ForgeKind, pr_number: u64) -> String {
+    match kind {
+        ForgeKind::GitLab => format!("Resolved by merge request !{pr_number}."),
+        ForgeKind::GitHub | ForgeKind::Gitea => format!("Resolved by pull request #{pr_number}."),
+    }
+}
+
+#[derive(Deserialize)]
+struct GhLabel {
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GhAuthor {
+    #[serde(default)]
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct GhComment {
+    #[serde(default)]
+    author: Option<GhAuthor>,
+    #[serde(default)]
+    body: String,
+    #[serde(default, rename = "createdAt")]
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct GhIssue {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    body: String,
+    url: String,
+    #[serde(default)]
+    labels: Vec<GhLabel>,
+    #[serde(default)]
+    comments: Vec<GhComment>,
+}
+
+fn gh_issue_view(remote: &ForgeRemote, number: u64) -> anyhow::Result<ImportedIssue> {
+    ensure_gh_cli()?;
+    let number = number.to_string();
+    let repo = gh_repo_arg(remote);
+    let issue = run_gh_json::<GhIssue>(&[
+        "issue",
+        "view",
+        &number,
+        "-R",
+        &repo,
+        "--json",
+        "number,title,body,url,labels,comments",
+    ])?;
+    Ok(ImportedIssue {
+        number: issue.number,
+        title: issue.title,
+        url: issue.url,
+        body: issue.body,
+        labels: issue.labels.into_iter().map(|l| l.name).collect(),
+        comments: issue
+            .comments
+            .into_iter()
+            .map(|c| ImportedIssueComment {
+                author: c.author.map(|a| a.login).unwrap_or_default(),
+                body: c.body,
+                created_at: c.created_at,
+            })
+            .collect(),
+    })
+}
+
+impl GitWorkspaceService {
+    fn project_forge_remote(&self, project_path: &Path) -> anyhow::Result<ForgeRemote> {
+        let remote = self
+            .select_remote_best_effort(project_path)?
+            .ok_or_else(|| anyhow!("project has no git remote to import issues from"))?;
+        let url = self.run_git(project_path, ["remote", "get-url", &remote])?;
+        forge_remote_from_url(&url).context("cannot import issues from this remote")
+    }
+
+    pub(super) fn import_issue(
+        &self,
+        project_path: &Path,
+        issue_ref: &str,
+    ) -> anyhow::Result<ImportedIssue> {
+        let (remote, number) = match parse_issue_ref(issue_ref)? {
+            IssueRef::Number(number) => (self.project_forge_remote(project_path)?, number),
+            IssueRef::Url { remote, number } => (remote, number),
+        };
+
+        let issue = match rest_issue_forge_for_remote(&remote) {
+            Some(forge) => forge.issue(number)?,
+            None => gh_issue_view(&remote, number)?,
+        };
+        if issue.title.trim().is_empty() {
+            return Err(anyhow!("issue #{number} has no title"));
+        }
+        Ok(issue)
+    }
+
+    pub(super) fn resolve_issue_on_merge(
+        &self,
+        issue_url: &str,
+        action: IssueMergeAction,
+        pr_number: u64,
+    ) -> anyhow::Result<()> {
+        if action == IssueMergeAction::Leave {
+            return Ok(());
+        }
+        let Ok(IssueRef::Url { remote, number }) = parse_issue_ref(issue_url) else {
+            return Err(anyhow!("invalid issue url: {issue_url}"));
+        };
+        let comment = merge_comment(remote.kind, pr_number);
+
+        if let Some(forge) = rest_issue_forge_for_remote(&remote) {
+            forge.comment_on_issue(number, &comment)?;
+            if action == IssueMergeAction::Close {
+                forge.close_issue(number)?;
+            }
+            return Ok(());
+        }
+
+        ensure_gh_cli()?;
+        match action {
+            IssueMergeAction::Close => {
+                run_gh(&["issue", "close", issue_url, "--comment", &comment])
+            }
+            IssueMergeAction::Comment => {
+                run_gh(&["issue", "comment", issue_url, "--body", &comment])
+            }
+            IssueMergeAction::Leave => Ok(()),
+        }
+        .with_context(|| format!("failed to update issue {issue_url}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issue_refs_accept_numbers_and_forge_urls() {
+        assert_eq!(parse_issue_ref(" #42 ").ok(), Some(IssueRef::Number(42)));
+        assert_eq!(parse_issue_ref("7").ok(), Some(IssueRef::Number(7)));
+        assert!(parse_issue_ref("0").is_err());
+        assert!(parse_issue_ref("not an issue").is_err());
+
+        let Ok(IssueRef::Url { remote, number }) =
+            parse_issue_ref("https://github.com/acme/app/issues/42#issuecomment-1")
+        else {
+            panic!("github url should parse");
+        };
+        assert_eq!(number, 42);
+        assert_eq!(remote.kind, ForgeKind::GitHub);
+        assert_eq!(gh_repo_arg(&remote), "acme/app");
+
+        let Ok(IssueRef::Url { remote, number }) =
+            parse_issue_ref("https://gitlab.com/group/sub/app/-/issues/12")
+        else {
+            panic!("gitlab url should parse");
+        };
+        assert_eq!(number, 12);
+        assert_eq!(remote.kind, ForgeKind::GitLab);
+        assert_eq!(remote.repo_path, "group/sub/app");
+
+        let Ok(IssueRef::Url { remote, .. }) =
+            parse_issue_ref("https://codeberg.org/owner/repo/issues/3")
+        else {
+            panic!("gitea url should parse");
+        };
+        assert_eq!(remote.kind, ForgeKind::Gitea);
+
+        assert!(parse_issue_ref("https://github.com/acme/app/pull/42").is_err());
+
+        // GitHub Enterprise issue URLs go through `gh` with the host in `-R`.
+        let Ok(IssueRef::Url { remote, number }) =
+            parse_issue_ref("https://ghe.corp.example/acme/app/issues/9")
+        else {
+            panic!("github enterprise url should parse");
+        };
+        assert_eq!(number, 9);
+        assert_eq!(remote.kind, ForgeKind::GitHub);
+        assert_eq!(gh_repo_arg(&remote), "ghe.corp.example/acme/app");
+    }
+
+    #[test]
+    fn merge_comment_uses_forge_reference_syntax() {
+        assert_eq!(
+            merge_comment(ForgeKind::GitLab, 5),
+            "Resolved by merge request !5."
+        );
+        assert_eq!(
+            merge_comment(ForgeKind::GitHub, 5),
+            "Resolved by pull request #5."
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::{Context as _, anyhow};
+use luban_domain::{
+    AgentModel, AgentRunnerKind, ModelCatalog, STANDARD_THINKING_EFFORTS, parse_agent_runner_kind,
+    parse_thinking_effort,
+};
+use std::collections::BTreeMap;
+
+/// Sources merged on top of the built-in catalog, in increasing priority.
+#[derive(Default)]
+pub(super) struct ModelCatalogSources {
+    /// Contents of `~/.codex/config.toml`.
+    pub codex_config: Option<String>,
+    /// Output of `droid exec --help`.
+    pub droid_help: Option<String>,
+    /// Contents of the user's `models.json`.
+    pub models_file: Option<String>,
+}
+
+/// Builds the catalog from `sources`. A malformed models file is returned as an error next to
+/// the catalog built from the remaining sources, so discovered models stay available.
+pub(super) fn build_model_catalog(
+    sources: ModelCatalogSources,
+) -> (ModelCatalog, Option<anyhow::Error>) {
+    let mut catalog = ModelCatalog::builtin();
+
+    if let Some(contents) = sources.codex_config.as_deref() {
+        let discovered = codex_config_model_ids(contents)
+            .into_iter()
+            .filter(|id| catalog.runner_for_model(id) != Some(AgentRunnerKind::Codex))
+            .map(|id| AgentModel {
+                label: id.clone(),
+                id,
+                supported_thinking_efforts: STANDARD_THINKING_EFFORTS.to_vec(),
+            })
+            .collect::<Vec<_>>();
+        catalog.merge(AgentRunnerKind::Codex, discovered);
+    }
+
+    if let Some(output) = sources.droid_help.as_deref() {
+        let known = catalog
+            .models_for_runner(AgentRunnerKind::Droid)
+            .iter()
+            .map(|m| m.id.clone())
+            .collect::<Vec<_>>();
+        let discovered = droid_help_models(output)
+            .into_iter()
+            .filter(|m| !known.contains(&m.id))
+            .collect::<Vec<_>>();
+        catalog.merge(AgentRunnerKind::Droid, discovered);
+    }
+
+    let mut models_file_error = None;
+    if let Some(contents) = sources.models_file.as_deref() {
+        match parse_models_file(contents, &catalog) {
+            Ok(overrides) => {
+                for (runner, models) in overrides {
+                    catalog.merge(runner, models);
+                }
+            }
+            Err(err) => models_file_error = Some(err),
+        }
+    }
+
+    (catalog, models_file_error)
+}
+
+#[derive(serde::Deserialize)]
+struct CodexConfig {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, CodexProfile>,
+}
+
+#[derive(serde::Deserialize)]
+struct CodexProfile {
+    #[serde(default)]
+    model: Option<String>,
+}
+
+/// Collects the top-level `model` and the `model` of every `[profiles.*]` table. A config that
+/// does not parse contributes no models.
+fn codex_config_model_ids(contents: &str) -> Vec<String> {
+    let Ok(config) = toml::from_str::<CodexConfig>(contents) else {
+        return Vec::new();
+    };
+    let mut out: Vec<String> = Vec::new();
+    let profiles = config.profiles.into_values().map(|p| p.model);
+    for model in std::iter::once(config.model).chain(profiles).flatten() {
+        let model = model.trim();
+        if !model.is_empty() && !out.iter().any(|id| id == model) {
+            out.push(model.to_owned());
+        }
+    }
+    out
+}
+
+/// Parses the `Available Models:` / `Custom Models:` sections of `droid exec --help`.
+///
+/// Each entry line is `<id>  <label>`; Droid models have a fixed reasoning level, so no
+/// thinking efforts are declared.
+fn droid_help_models(output: &str) -> Vec<AgentModel> {
+    let mut out: Vec<AgentModel> = Vec::new();
+    let mut in_models = false;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.ends_with(':') {
+            let header = trimmed.to_ascii_lowercase();
+            in_models = header.ends_with("models:");
+            continue;
+        }
+        if !in_models {
+            continue;
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            in_models = false;
+            continue;
+        }
+        let mut parts = trimmed.splitn(2, char::is_whitespace);
+        let Some(id) = parts.next().filter(|id| !id.is_empty()) else {
+            continue;
+        };
+        let label = parts
+            .next()
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .unwrap_or(id);
+        if out.iter().any(|m| m.id == id) {
+            continue;
+        }
+        out.push(AgentModel {
+            id: id.to_owned(),
+            label: label.to_owned(),
+            supported_thinking_efforts: Vec::new(),
+        });
+    }
+    out
+}
+
+#[derive(serde::Deserialize)]
+struct ModelsFileEntry {
+    id: String,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    thinking_efforts: Option<Vec<String>>,
+}
+
+/// Parses the user's models file: an object keyed by runner (`codex`, `droid`) whose values
+/// are lists of `{ "id", "label"?, "thinking_efforts"? }`.
+///
+/// Entries without `thinking_efforts` keep the efforts of an existing entry with the same id,
+/// or get the runner's defaults (all efforts for Codex, none for Droid).
+fn parse_models_file(
+    contents: &str,
+    catalog: &ModelCatalog,
+) -> anyhow::Result<Vec<(AgentRunnerKind, Vec<AgentModel>)>> {
+    let parsed: BTreeMap<String, Vec<ModelsFileEntry>> =
+        serde_json::from_str(contents).context("invalid models file")?;
+
+    let mut out = Vec::new();
+    for (key, entries) in parsed {
+        let runner = parse_agent_runner_kind(&key)
+            .filter(|runner| matches!(runner, AgentRunnerKind::Codex | AgentRunnerKind::Droid))
+            .ok_or_else(|| anyhow!("unsupported runner in models file: {key}"))?;
+
+        let mut models = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let id = entry.id.trim().to_owned();
+            if id.is_empty() {
+                return Err(anyhow!("models file entry for {key} has an empty id"));
+            }
+            let existing = catalog
+                .models_for_runner(runner)
+                .iter()
+                .find(|m| m.id == id);
+            let supported_thinking_efforts = match entry.thinking_efforts {
+                Some(raw) => raw
+                    .iter()
+                    .map(|value| {
+                        parse_thinking_effort(value).ok_or_else(|| {
+                            anyhow!("unknown thinking effort {value:?} for model {id}")
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?,
+                None => match (existing, runner) {
+                    (Some(existing), _) => existing.supported_thinking_efforts.clone(),
+                    (None, AgentRunnerKind::Codex) => STANDARD_THINKING_EFFORTS.to_vec(),
+                    (None, _) => Vec::new(),
+                },
+            };
+            let label = entry
+                .label
+                .map(|label| label.trim().to_owned())
+                .filter(|label| !label.is_empty())
+                .or_else(|| existing.map(|m| m.label.clone()))
+                .unwrap_or_else(|| id.clone());
+            models.push(AgentModel {
+                id,
+                label,
+                supported_thinking_efforts,
+            });
+        }
+        out.push((runner, models));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luban_domain::ThinkingEffort;
+
+    #[test]
+    fn codex_config_models_come_from_top_level_and_profiles() {
+        let contents = r#"
+model = "gpt-5.4" # default
+model_reasoning_effort = "high"
+
+[profiles.fast]
+model = 'gpt-5.4-mini'
+
+[mcp_servers.docs]
+model = "not-a-model"
+
+[profiles.again]
+model = "gpt-5.4"
+"#;
+        assert_eq!(
+            codex_config_model_ids(contents),
+            vec!["gpt-5.4".to_owned(), "gpt-5.4-mini".to_owned()]
+        );
+    }
+
+    #[test]
+    fn codex_config_models_keep_hashes_inside_strings() {
+        let contents = r#"
+model = "team#1/model" # trailing comment
+
+[profiles.deep.tools]
+web_search = true
+
+[profiles.deep]
+model = "vendor/model#v2"
+
+[profiles."quoted.name"]
+model = "gpt-5.4-mini"
+"#;
+        assert_eq!(
+            codex_config_model_ids(contents),
+            vec![
+                "team#1/model".to_owned(),
+                "vendor/model#v2".to_owned(),
+                "gpt-5.4-mini".to_owned(),
+            ]
+        );
+        assert!(codex_config_model_ids("model = [unterminated").is_empty());
+    }
+
+    #[test]
+    fn droid_help_models_are_parsed_from_model_sections() {
+        let output = [
+            "Usage: droid exec [options] [prompt]",
+            "",
+            "Options:",
+            "  -m, --model <id>   Model ID",
+            "",
+            "Available Models:",
+            "  claude-opus-4-6         Claude Opus 4.6 (default)",
+            "  glm-5                   GLM-5",
+            "",
+            "Custom Models:",
+            "  custom:my-model",
+            "",
+            "Examples:",
+            "  droid exec \"fix the bug\"",
+        ]
+        .join("\n");
+        let models = droid_help_models(&output);
+        let ids = models.iter().map(|m| m.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["claude-opus-4-6", "glm-5", "custom:my-model"]);
+        assert_eq!(models[1].label, "GLM-5");
+        assert_eq!(models[2].label, "custom:my-model");
+        assert!(
+            models
+                .iter()
+                .all(|m| m.supported_thinking_efforts.is_empty())
+        );
+    }
+
+    #[test]
+    fn models_file_overrides_discovered_and_builtin_models() {
+        let (catalog, error) = build_model_catalog(ModelCatalogSources {
+            codex_config: Some("model = \"gpt-5.4\"\n".to_owned()),
+            droid_help: None,
+            models_file: Some(
+                r#"{
+                    "codex": [
+                        { "id": "gpt-5.4", "label": "GPT-5.4", "thinking_efforts": ["low", "high"] },
+                        { "id": "gpt-5.2" }
+                    ],
+                    "droid": [{ "id": "glm-5" }]
+                }"#
+                .to_owned(),
+            ),
+        });
+        assert!(error.is_none());
+
+        let model = catalog.find("gpt-5.4").expect("discovered model");
+        assert_eq!(model.label, "GPT-5.4");
+        assert_eq!(
+            catalog.normalize_thinking_effort("gpt-5.4", ThinkingEffort::XHigh),
+            ThinkingEffort::Low
+        );
+        assert!(catalog.thinking_effort_supported("gpt-5.2", ThinkingEffort::XHigh));
+        assert_eq!(catalog.model_label("gpt-5.2"), Some("GPT-5.2"));
+        assert!(catalog.model_valid_for_runner(AgentRunnerKind::Droid, "glm-5"));
+    }
+
+    #[test]
+    fn models_file_rejects_unknown_efforts_and_runners() {
+        let catalog = ModelCatalog::builtin();
+        assert!(
+            parse_models_file(
+                r#"{"codex": [{"id": "x", "thinking_efforts": ["max"]}]}"#,
+                &catalog
+            )
+            .is_err()
+        );
+        assert!(parse_models_file(r#"{"amp": [{"id": "x"}]}"#, &catalog).is_err());
+    }
+
+    #[test]
+    fn malformed_models_file_keeps_discovered_models() {
+        let (catalog, error) = build_model_catalog(ModelCatalogSources {
+            codex_config: Some("model = \"gpt-5.4\"\n".to_owned()),
+            droid_help: None,
+            models_file: Some("{ not json".to_owned()),
+        });
+        assert!(error.is_some());
+        assert!(catalog.model_valid_for_runner(AgentRunnerKind::Codex, "gpt-5.4"));
+    }
+}
@@ -0,0 +1,432 @@
+use super::GitWorkspaceService;
+use super::forge::{ForgeKind, forge_remote_from_url};
+use super::gh_cli::{ensure_gh_cli, run_gh_in, run_gh_json_in};
+use anyhow::{Context as _, anyhow};
+use luban_domain::PullRequestReviewComment;
+use serde::Deserialize;
+use std::path::Path;
+
+const REVIEW_THREADS_QUERY: &str =
+    "query($owner: String!, $name: String!, $number: Int!, $cursor: String) {
+  repository(owner: $owner, name: $name) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100, after: $cursor) {
+        pageInfo { hasNextPage endCursor }
+        nodes {
+          id
+          isResolved
+          path
+          line
+          startLine
+          originalLine
+          originalStartLine
+          comments(first: 100) {
+            pageInfo { hasNextPage endCursor }
+            nodes { databaseId author { login } body url diffHunk }
+          }
+        }
+      }
+    }
+  }
+}";
+
+const THREAD_COMMENTS_QUERY: &str = "query($id: ID!, $cursor: String) {
+  node(id: $id) {
+    ... on PullRequestReviewThread {
+      comments(first: 100, after: $cursor) {
+        pageInfo { hasNextPage endCursor }
+        nodes { databaseId author { login } body url diffHunk }
+      }
+    }
+  }
+}";
+
+/// Appended to every reply Luban posts, so its own replies are not imported back as review
+/// comments. Replies are posted with the user's `gh` identity, which also authors the reviews
+/// this feature is for, so the author cannot tell them apart. Renders as nothing on GitHub.
+const LUBAN_REPLY_MARKER: &str = "<!-- luban:review-reply -->";
+
+#[derive(Deserialize)]
+struct GraphqlResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct ThreadsData {
+    repository: Option<Repository>,
+}
+
+#[derive(Deserialize)]
+struct CommentsData {
+    node: Option<ThreadComments>,
+}
+
+#[derive(Deserialize)]
+struct Login {
+    login: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Repository {
+    pull_request: Option<PullRequestThreads>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequestThreads {
+    review_threads: Page<ReviewThread>,
+}
+
+#[derive(Deserialize)]
+struct ThreadComments {
+    comments: Page<ThreadComment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Page<T> {
+    page_info: PageInfo,
+    nodes: Vec<T>,
+}
+
+impl<T> Page<T> {
+    /// Cursor of the following page, if there is one.
+    fn next_cursor(&self) -> Option<String> {
+        self.page_info
+            .has_next_page
+            .then(|| self.page_info.end_cursor.clone())
+            .flatten()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewThread {
+    id: String,
+    is_resolved: bool,
+    path: String,
+    line: Option<u32>,
+    start_line: Option<u32>,
+    original_line: Option<u32>,
+    original_start_line: Option<u32>,
+    comments: Page<ThreadComment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadComment {
+    database_id: Option<u64>,
+    /// Missing for deleted accounts.
+    author: Option<Login>,
+    body: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    diff_hunk: String,
+}
+
+/// Walks every page of review threads, and of the comments of unresolved threads that do not fit
+/// in the first page. `fetch_threads` gets the threads cursor, `fetch_comments` a thread id and
+/// comments cursor.
+fn collect_review_threads(
+    mut fetch_threads: impl FnMut(Option<&str>) -> anyhow::Result<Option<Page<ReviewThread>>>,
+    mut fetch_comments: impl FnMut(&str, &str) -> anyhow::Result<Option<Page<ThreadComment>>>,
+) -> anyhow::Result<Vec<ReviewThread>> {
+    let mut threads = Vec::new();
+    let mut cursor = None;
+    loop {
+        let Some(page) = fetch_threads(cursor.as_deref())? else {
+            break;
+        };
+        cursor = page.next_cursor();
+        threads.extend(page.nodes);
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    for thread in threads.iter_mut().filter(|t| !t.is_resolved) {
+        while let Some(cursor) = thread.comments.next_cursor() {
+            let Some(page) = fetch_comments(&thread.id, &cursor)? else {
+                break;
+            };
+            thread.comments.page_info = page.page_info;
+            thread.comments.nodes.extend(page.nodes);
+        }
+    }
+    Ok(threads)
+}
+
+/// Flattens unresolved review threads into their comments, leaving out the replies Luban posted.
+fn unresolved_review_comments(threads: Vec<ReviewThread>) -> Vec<PullRequestReviewComment> {
+    let mut out = Vec::new();
+    for thread in threads {
+        if thread.is_resolved {
+            continue;
+        }
+        // Outdated threads only keep their original position.
+        let end_line = thread.line.or(thread.original_line).unwrap_or(0);
+        let start_line = thread
+            .start_line
+            .or(thread.original_start_line)
+            .filter(|start| *start > 0 && *start <= end_line)
+            .unwrap_or(end_line);
+        for comment in thread.comments.nodes {
+            let Some(comment_id) = comment.database_id else {
+                continue;
+            };
+            if comment.body.trim().is_empty() || comment.body.contains(LUBAN_REPLY_MARKER) {
+                continue;
+            }
+            let author = comment
+                .author
+                .map(|a| a.login)
+                .unwrap_or_else(|| "ghost".to_owned());
+            out.push(PullRequestReviewComment {
+                comment_id,
+                author,
+                path: thread.path.clone(),
+                start_line,
+                end_line,
+                body: comment.body.trim().to_owned(),
+                url: comment.url,
+                diff_hunk: comment.diff_hunk,
+            });
+        }
+    }
+    out
+}
+
+impl GitWorkspaceService {
+    /// Fails for remotes on forges other than GitHub; `feature` names what needs it.
+    pub(super) fn ensure_github_remote(
+        &self,
+        worktree_path: &Path,
+        feature: &str,
+    ) -> anyhow::Result<()> {
+        let Some(remote) = self.select_remote_best_effort(worktree_path)? else {
+            return Err(anyhow!("workdir has no git remote"));
+        };
+        let url = self.run_git(worktree_path, ["remote", "get-url", &remote])?;
+        // Anything that is not a known GitLab or Gitea host is left to `gh`.
+        match forge_remote_from_url(&url).map(|r| r.kind) {
+            Ok(ForgeKind::GitLab | ForgeKind::Gitea) => {
+                Err(anyhow!("{feature} are only supported for GitHub remotes"))
+            }
+            Ok(ForgeKind::GitHub) | Err(_) => Ok(()),
+        }
+    }
+
+    pub(super) fn fetch_pull_request_review_comments(
+        &self,
+        worktree_path: &Path,
+        pr_number: u64,
+    ) -> anyhow::Result<Vec<PullRequestReviewComment>> {
+        self.ensure_github_remote(worktree_path, "pull request review comments")?;
+        ensure_gh_cli()?;
+        let number = format!("number={pr_number}");
+        let threads = collect_review_threads(
+            |cursor| {
+                let query = format!("query={REVIEW_THREADS_QUERY}");
+                let cursor = cursor.map(|c| format!("cursor={c}"));
+                let mut args = vec![
+                    "api",
+                    "graphql",
+                    "-F",
+                    "owner={owner}",
+                    "-F",
+                    "name={repo}",
+                    "-F",
+                    &number,
+                    "-f",
+                    &query,
+                ];
+                if let Some(cursor) = &cursor {
+                    args.extend(["-f", cursor]);
+                }
+                let response: GraphqlResponse<ThreadsData> = run_gh_json_in(worktree_path, &args)?;
+                Ok(response
+                    .data
+                    .repository
+                    .and_then(|r| r.pull_request)
+                    .map(|pr| pr.review_threads))
+            },
+            |thread_id, cursor| {
+                let query = format!("query={THREAD_COMMENTS_QUERY}");
+                let id = format!("id={thread_id}");
+                let cursor = format!("cursor={cursor}");
+                let response: GraphqlResponse<CommentsData> = run_gh_json_in(
+                    worktree_path,
+                    &["api", "graphql", "-f", &id, "-f", &cursor, "-f", &query],
+                )?;
+                Ok(response.data.node.map(|thread| thread.comments))
+            },
+        )
+        .with_context(|| format!("failed to fetch review threads for PR #{pr_number}"))?;
+        Ok(unresolved_review_comments(threads))
+    }
+
+    pub(super) fn reply_to_pull_request_review_comment(
+        &self,
+        worktree_path: &Path,
+        pr_number: u64,
+        comment_id: u64,
+        body: &str,
+    ) -> anyhow::Result<()> {
+        self.ensure_github_remote(worktree_path, "pull request review comments")?;
+        ensure_gh_cli()?;
+        let endpoint =
+            format!("repos/{{owner}}/{{repo}}/pulls/{pr_number}/comments/{comment_id}/replies");
+        let body = format!("body={body}\n\n{LUBAN_REPLY_MARKER}");
+        run_gh_in(
+            worktree_path,
+            &["api", "--method", "POST", &endpoint, "-f", &body],
+        )
+        .with_context(|| format!("failed to reply to review comment {comment_id}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thread_page(value: serde_json::Value) -> Page<ReviewThread> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn no_more() -> serde_json::Value {
+        serde_json::json!({ "hasNextPage": false, "endCursor": null })
+    }
+
+    #[test]
+    fn unresolved_review_comments_skip_resolved_threads_and_luban_replies() {
+        let page = thread_page(serde_json::json!({
+            "pageInfo": no_more(),
+            "nodes": [
+                {
+                    "id": "T1", "isResolved": false, "path": "src/lib.rs",
+                    "line": 12, "startLine": 10, "originalLine": 9, "originalStartLine": 8,
+                    "comments": { "pageInfo": no_more(), "nodes": [
+                        { "databaseId": 1, "author": { "login": "octocat" },
+                          "body": " Use a const. ", "url": "https://x/1", "diffHunk": "@@" },
+                        { "databaseId": 2, "author": { "login": "me" },
+                          "body": format!("Addressed.\n\n{LUBAN_REPLY_MARKER}"),
+                          "url": "https://x/2", "diffHunk": "@@" },
+                        { "databaseId": 6, "author": { "login": "me" },
+                          "body": "Also rename this.", "url": "https://x/6", "diffHunk": "@@" }
+                    ] }
+                },
+                {
+                    "id": "T2", "isResolved": true, "path": "src/main.rs", "line": 3,
+                    "comments": { "pageInfo": no_more(), "nodes": [
+                        { "databaseId": 3, "author": { "login": "octocat" },
+                          "body": "done", "url": "", "diffHunk": "" }
+                    ] }
+                },
+                {
+                    "id": "T3", "isResolved": false, "path": "README.md",
+                    "line": null, "originalLine": 5,
+                    "comments": { "pageInfo": no_more(), "nodes": [
+                        { "databaseId": 4, "author": null,
+                          "body": "Outdated but open.", "url": "", "diffHunk": "" }
+                    ] }
+                },
+                {
+                    "id": "T4", "isResolved": false, "path": "Cargo.toml",
+                    "comments": { "pageInfo": no_more(), "nodes": [
+                        { "databaseId": 5, "author": { "login": "octocat" },
+                          "body": "Why this dependency?", "url": "", "diffHunk": "" }
+                    ] }
+                }
+            ]
+        }));
+
+        let comments = unresolved_review_comments(page.nodes);
+        let summary = comments
+            .iter()
+            .map(|c| {
+                (
+                    c.comment_id,
+                    c.author.as_str(),
+                    c.path.as_str(),
+                    c.start_line,
+                    c.end_line,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "octocat", "src/lib.rs", 10, 12),
+                (6, "me", "src/lib.rs", 10, 12),
+                (4, "ghost", "README.md", 5, 5),
+                (5, "octocat", "Cargo.toml", 0, 0),
+            ]
+        );
+        assert_eq!(comments[0].body, "Use a const.");
+    }
+
+    #[test]
+    fn collect_review_threads_follows_thread_and_comment_pages() {
+        let comment = |id: u64| serde_json::json!({ "databaseId": id, "author": { "login": "octocat" }, "body": "fix" });
+        let more = |cursor: &str| serde_json::json!({ "hasNextPage": true, "endCursor": cursor });
+
+        let mut thread_cursors = Vec::new();
+        let mut comment_requests = Vec::new();
+        let threads = collect_review_threads(
+            |cursor| {
+                thread_cursors.push(cursor.map(str::to_owned));
+                let page = match cursor {
+                    None => serde_json::json!({
+                        "pageInfo": more("t1"),
+                        "nodes": [{
+                            "id": "A", "isResolved": false, "path": "a.rs",
+                            "comments": { "pageInfo": more("c1"), "nodes": [comment(1)] }
+                        }]
+                    }),
+                    Some("t1") => serde_json::json!({
+                        "pageInfo": no_more(),
+                        "nodes": [{
+                            "id": "B", "isResolved": true, "path": "b.rs",
+                            "comments": { "pageInfo": more("skipped"), "nodes": [comment(9)] }
+                        }]
+                    }),
+                    Some(other) => panic!("unexpected thread cursor {other}"),
+                };
+                Ok(Some(thread_page(page)))
+            },
+            |thread_id, cursor| {
+                comment_requests.push((thread_id.to_owned(), cursor.to_owned()));
+                let page = match cursor {
+                    "c1" => serde_json::json!({ "pageInfo": more("c2"), "nodes": [comment(2)] }),
+                    "c2" => serde_json::json!({ "pageInfo": no_more(), "nodes": [comment(3)] }),
+                    other => panic!("unexpected comment cursor {other}"),
+                };
+                Ok(Some(serde_json::from_value(page).unwrap()))
+            },
+        )
+        .unwrap();
+
+        assert_eq!(thread_cursors, vec![None, Some("t1".to_owned())]);
+        assert_eq!(
+            comment_requests,
+            vec![
+                ("A".to_owned(), "c1".to_owned()),
+                ("A".to_owned(), "c2".to_owned()),
+            ]
+        );
+        let ids = unresolved_review_comments(threads)
+            .into_iter()
+            .map(|c| c.comment_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+}
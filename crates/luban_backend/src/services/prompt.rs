@@ -1,4 +1,4 @@
-use luban_domain::{AttachmentKind, AttachmentRef};
+use luban_domain::{AgentRunnerKind, AttachmentKind, AttachmentRef, ReviewComment};
 use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug)]
@@ -6,6 +6,38 @@ pub(super) struct PromptAttachment {
     pub(super) kind: AttachmentKind,
     pub(super) name: String,
     pub(super) path: PathBuf,
+    pub(super) text: Option<PromptAttachmentText>,
+}
+
+/// Text extracted from a document attachment.
+#[derive(Clone, Debug)]
+pub(super) struct PromptAttachmentText {
+    pub(super) path: PathBuf,
+    /// The part of the text to inline into the prompt, if inlining is enabled.
+    pub(super) inline: Option<String>,
+    pub(super) truncated: bool,
+}
+
+impl PromptAttachmentText {
+    pub(super) fn new(path: PathBuf, text: &str, max_inline_bytes: u64) -> Self {
+        let max_inline_bytes = usize::try_from(max_inline_bytes).unwrap_or(usize::MAX);
+        if max_inline_bytes == 0 {
+            return Self {
+                path,
+                inline: None,
+                truncated: false,
+            };
+        }
+        let mut end = text.len().min(max_inline_bytes);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        Self {
+            path,
+            inline: Some(text[..end].to_owned()),
+            truncated: end < text.len(),
+        }
+    }
 }
 
 pub(super) fn resolve_prompt_attachments(
@@ -18,10 +50,35 @@ pub(super) fn resolve_prompt_attachments(
             kind: attachment.kind,
             name: attachment.name.clone(),
             path: blobs_dir.join(format!("{}.{}", attachment.id, attachment.extension)),
+            text: None,
         })
         .collect()
 }
 
+fn attachment_display_name(attachment: &PromptAttachment) -> &str {
+    let name = attachment.name.trim();
+    if !name.is_empty() {
+        return name;
+    }
+    match attachment.kind {
+        AttachmentKind::Image => "image",
+        AttachmentKind::Text => "text",
+        AttachmentKind::File => "file",
+        AttachmentKind::Url => "web page",
+    }
+}
+
+/// A backtick fence longer than any backtick run in `text`.
+fn code_fence_for(text: &str) -> String {
+    let mut longest = 0usize;
+    let mut run = 0usize;
+    for ch in text.chars() {
+        run = if ch == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
 fn format_prompt(
     prompt: &str,
     attachments: &[PromptAttachment],
@@ -36,19 +93,41 @@ fn format_prompt(
     out.push_str("\n\nAttached files:\n");
     for attachment in attachments {
         out.push_str("- ");
-        let name = attachment.name.trim();
-        if name.is_empty() {
-            out.push_str(match attachment.kind {
-                AttachmentKind::Image => "image",
-                AttachmentKind::Text => "text",
-                AttachmentKind::File => "file",
-            });
-        } else {
-            out.push_str(name);
-        }
+        out.push_str(attachment_display_name(attachment));
         out.push_str(": ");
         out.push_str(attachment_path_prefix);
         out.push_str(&attachment.path.to_string_lossy());
+        if attachment.kind == AttachmentKind::Url {
+            out.push_str(" (web page snapshot)");
+        }
+        if let Some(text) = &attachment.text {
+            out.push_str(" (extracted text: ");
+            out.push_str(attachment_path_prefix);
+            out.push_str(&text.path.to_string_lossy());
+            out.push(')');
+        }
+        out.push('\n');
+    }
+
+    for attachment in attachments {
+        let Some(text) = &attachment.text else {
+            continue;
+        };
+        let Some(inline) = text.inline.as_deref() else {
+            continue;
+        };
+        out.push_str("\nText extracted from ");
+        out.push_str(attachment_display_name(attachment));
+        if text.truncated {
+            out.push_str(" (truncated; read the extracted text file for the rest)");
+        }
+        out.push_str(":\n");
+        let fence = code_fence_for(inline);
+        out.push_str(&fence);
+        out.push('\n');
+        out.push_str(inline.trim_end());
+        out.push('\n');
+        out.push_str(&fence);
         out.push('\n');
     }
     out
@@ -61,3 +140,133 @@ pub(super) fn format_amp_prompt(prompt: &str, attachments: &[PromptAttachment])
 pub(super) fn format_codex_prompt(prompt: &str, attachments: &[PromptAttachment]) -> String {
     format_prompt(prompt, attachments, "")
 }
+
+const REVIEW_QUOTE_MAX_LINES: usize = 12;
+const DEFAULT_REVIEW_NOTE: &str =
+    "Please address these review comments on your changes. Reply with what you changed for each.";
+
+fn review_line_range(comment: &ReviewComment) -> String {
+    if comment.start_line == comment.end_line {
+        comment.start_line.to_string()
+    } else {
+        format!("{}-{}", comment.start_line, comment.end_line)
+    }
+}
+
+/// The commented lines, cut to a few lines so a long selection does not crowd out the comment.
+fn review_quote_lines(comment: &ReviewComment) -> Vec<&str> {
+    let mut lines = comment
+        .quote
+        .trim_end()
+        .lines()
+        .take(REVIEW_QUOTE_MAX_LINES + 1)
+        .collect::<Vec<_>>();
+    while lines.first().is_some_and(|l| l.trim().is_empty()) {
+        lines.remove(0);
+    }
+    if lines.len() > REVIEW_QUOTE_MAX_LINES {
+        lines.truncate(REVIEW_QUOTE_MAX_LINES);
+        lines.push("…");
+    }
+    lines
+}
+
+/// Renders diff review comments as a single follow-up prompt. Comments imported from a pull
+/// request name their reviewer; a start line of 0 marks a comment on the whole file.
+///
+/// Claude reads tagged blocks best, Amp resolves `@path` mentions into file context, and the
+/// other runners get plain markdown with the quoted lines as `>` blocks.
+pub fn format_review_prompt(
+    note: Option<&str>,
+    comments: &[ReviewComment],
+    runner: AgentRunnerKind,
+) -> String {
+    let note = note
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .unwrap_or(DEFAULT_REVIEW_NOTE);
+    let mut out = String::with_capacity(note.len() + comments.len() * 160);
+    out.push_str(note);
+    out.push_str("\n\n");
+
+    if runner == AgentRunnerKind::Claude {
+        for comment in comments {
+            out.push_str(&format!("<review_comment file=\"{}\"", comment.path));
+            if comment.start_line > 0 {
+                out.push_str(&format!(" lines=\"{}\"", review_line_range(comment)));
+            }
+            if let Some(origin) = &comment.pull_request {
+                out.push_str(&format!(" author=\"{}\"", origin.author));
+            }
+            out.push_str(">\n");
+            let quote = review_quote_lines(comment);
+            if !quote.is_empty() {
+                out.push_str("<code>\n");
+                out.push_str(&quote.join("\n"));
+                out.push_str("\n</code>\n");
+            }
+            out.push_str(comment.body.trim());
+            out.push_str("\n</review_comment>\n");
+        }
+        return out;
+    }
+
+    let mention = if runner == AgentRunnerKind::Amp {
+        "@"
+    } else {
+        ""
+    };
+    for (index, comment) in comments.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("{}. {mention}{}", index + 1, comment.path));
+        if comment.start_line > 0 {
+            out.push(':');
+            out.push_str(&review_line_range(comment));
+        }
+        if let Some(origin) = &comment.pull_request {
+            out.push_str(&format!(
+                " (from {} on PR #{})",
+                origin.author, origin.pr_number
+            ));
+        }
+        out.push('\n');
+        for line in review_quote_lines(comment) {
+            out.push_str("> ");
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push_str(comment.body.trim());
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders the follow-up prompt for one CI auto-fix attempt; the trimmed failed job log is
+/// attached to the turn rather than inlined.
+pub fn format_ci_fix_prompt(
+    pr_number: u64,
+    attempt: u32,
+    max_attempts: u32,
+    workflow: &str,
+    url: &str,
+) -> String {
+    let workflow = if workflow.trim().is_empty() {
+        "CI"
+    } else {
+        workflow.trim()
+    };
+    let mut out = format!(
+        "The `{workflow}` workflow failed on PR #{pr_number} (auto-fix attempt {attempt} of {max_attempts})."
+    );
+    if !url.is_empty() {
+        out.push_str(&format!(" Run: {url}"));
+    }
+    out.push_str(
+        "\n\nThe attached ci-failure.log holds the failed job output, trimmed to the part around \
+         the first error. Find the root cause, fix it, run the failing check locally if you can, \
+         and commit and push the fix.",
+    );
+    out
+}
@@ -47,3 +47,17 @@ pub(super) fn is_merge_ready(
     }
     matches!(merge_state_status, "CLEAN" | "HAS_HOOKS")
 }
+
+/// Merge readiness for forges that report a single mergeability flag (GitLab's
+/// `detailed_merge_status`, Gitea's `mergeable`) instead of GitHub's review decision.
+pub(super) fn is_forge_merge_ready(
+    pr_state: PullRequestState,
+    is_draft: bool,
+    mergeable: bool,
+    ci_state: Option<PullRequestCiState>,
+) -> bool {
+    pr_state == PullRequestState::Open
+        && !is_draft
+        && mergeable
+        && ci_state == Some(PullRequestCiState::Success)
+}
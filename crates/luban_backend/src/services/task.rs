@@ -1,19 +1,22 @@
 use crate::services::GitWorkspaceService;
 use anyhow::anyhow;
 use luban_domain::{
-    AgentRunnerKind, ProjectWorkspaceService, SystemTaskKind, THREAD_TITLE_MAX_CHARS,
-    TaskIntentKind, TaskStatus, TaskStatusAutoUpdateSuggestion, ThinkingEffort,
-    default_system_prompt_template, derive_thread_title, parse_task_status,
+    AgentRunnerKind, ProjectWorkspaceService, SYSTEM_PROMPT_VARIABLES, SystemTaskKind,
+    TASK_PROMPT_VARIABLES, THREAD_TITLE_MAX_CHARS, TaskIntentKind, TaskStatus,
+    TaskStatusAutoUpdateSuggestion, ThinkingEffort, default_system_prompt_template,
+    derive_thread_title, parse_task_status, render_prompt_template,
 };
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
 fn render_system_prompt_template(template: &str, task_input: &str, context_json: &str) -> String {
-    let mut out = template.to_owned();
-    out = out.replace("{{task_input}}", task_input.trim());
-    out = out.replace("{{context_json}}", context_json.trim());
-    out
+    let values = HashMap::from([
+        ("task_input".to_owned(), task_input.trim().to_owned()),
+        ("context_json".to_owned(), context_json.trim().to_owned()),
+    ]);
+    render_prompt_template(template, SYSTEM_PROMPT_VARIABLES, &values)
 }
 
 fn system_prompt_for_task(
@@ -113,12 +116,16 @@ pub(super) fn render_task_prompt_template(
     task_input: &str,
     intent_label: &str,
     known_context: &str,
+    extra: HashMap<String, String>,
 ) -> String {
-    let mut out = template.to_owned();
-    out = out.replace("{{task_input}}", task_input.trim());
-    out = out.replace("{{intent_label}}", intent_label);
-    out = out.replace("{{known_context}}", known_context.trim_end());
-    out
+    let mut values = extra;
+    values.insert("task_input".to_owned(), task_input.trim().to_owned());
+    values.insert("intent_label".to_owned(), intent_label.to_owned());
+    values.insert(
+        "known_context".to_owned(),
+        known_context.trim_end().to_owned(),
+    );
+    render_prompt_template(template, TASK_PROMPT_VARIABLES, &values)
 }
 
 fn run_system_task_and_collect_messages(
@@ -1,4 +1,6 @@
 use std::{
+    io::{BufRead as _, BufReader, Write as _},
+    net::TcpListener,
     path::{Path, PathBuf},
     process::{Command, Output},
     sync::MutexGuard,
@@ -49,11 +51,63 @@ pub(super) fn git_rev_parse(repo_path: &Path, rev: &str) -> String {
 
 pub(super) fn stored_blob_path(
     service: &super::GitWorkspaceService,
-    project_slug: &str,
-    workspace_name: &str,
     attachment: &AttachmentRef,
 ) -> PathBuf {
     service
-        .context_blobs_dir(project_slug, workspace_name)
+        .context_blobs_dir()
         .join(format!("{}.{}", attachment.id, attachment.extension))
 }
+
+/// Serves canned raw HTTP responses keyed by request path (query included) until the test
+/// exits. Unknown paths get an empty 404.
+pub(super) fn serve_stub(routes: Vec<(&'static str, String)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind stub server");
+    let addr = listener.local_addr().expect("stub addr");
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+            loop {
+                let mut header = String::new();
+                match reader.read_line(&mut header) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) if header == "\r\n" => break,
+                    Ok(_) => {}
+                }
+            }
+            let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let response = routes
+                .iter()
+                .find(|(path, _)| *path == target)
+                .map(|(_, response)| response.clone())
+                .unwrap_or_else(|| {
+                    "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                        .to_owned()
+                });
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    format!("http://{addr}")
+}
+
+/// Like [`serve_stub`], answering every known path with a 200 JSON body.
+pub(super) fn serve_json_stub(routes: Vec<(&'static str, &'static str)>) -> String {
+    serve_stub(
+        routes
+            .into_iter()
+            .map(|(path, body)| {
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                (path, response)
+            })
+            .collect(),
+    )
+}
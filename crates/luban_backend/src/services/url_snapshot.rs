@@ -0,0 +1,906 @@
+use super::GitWorkspaceService;
+use crate::time::rfc3339_utc;
+use anyhow::{Context as _, anyhow};
+use luban_domain::{AttachmentKind, AttachmentRef, UrlAttachmentSettings};
+use reqwest::Url;
+use std::io::Read as _;
+use std::time::Duration;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+const MAX_REDIRECTS: usize = 5;
+const MAX_BODY_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_NAME_CHARS: usize = 120;
+
+#[derive(Debug)]
+pub(super) struct UrlSnapshot {
+    pub(super) title: Option<String>,
+    pub(super) markdown: String,
+}
+
+impl GitWorkspaceService {
+    pub(super) fn store_context_url_snapshot(
+        &self,
+        raw_url: &str,
+        settings: &UrlAttachmentSettings,
+    ) -> anyhow::Result<AttachmentRef> {
+        let fetched_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let snapshot = fetch_url_snapshot(raw_url, settings, fetched_at)?;
+        let bytes = snapshot.markdown.into_bytes();
+        let byte_len = bytes.len() as u64;
+        let (id, _path) = self.store_context_bytes(&bytes, "md")?;
+        let name = snapshot
+            .title
+            .map(|title| title.chars().take(MAX_NAME_CHARS).collect::<String>())
+            .unwrap_or_else(|| raw_url.trim().chars().take(MAX_NAME_CHARS).collect());
+        Ok(AttachmentRef {
+            id,
+            kind: AttachmentKind::Url,
+            name,
+            extension: "md".to_owned(),
+            mime: Some("text/markdown".to_owned()),
+            byte_len,
+        })
+    }
+}
+
+fn allowed_url(url: &Url, settings: &UrlAttachmentSettings) -> anyhow::Result<()> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(anyhow!("only http and https URLs can be attached"));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(anyhow!("URLs with credentials cannot be attached"));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("URL has no host: {url}"))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if !settings.allows_host(host) {
+        return Err(anyhow!(
+            "{host} is not in the URL attachment allowlist (Settings > Storage)"
+        ));
+    }
+    Ok(())
+}
+
+/// Fetches `raw_url` once and renders it as a Markdown document with a front matter header
+/// recording where and when it was captured. Every redirect hop is checked against the allowlist.
+pub(super) fn fetch_url_snapshot(
+    raw_url: &str,
+    settings: &UrlAttachmentSettings,
+    fetched_at_unix_secs: u64,
+) -> anyhow::Result<UrlSnapshot> {
+    let url = Url::parse(raw_url.trim()).with_context(|| format!("invalid URL: {raw_url}"))?;
+    allowed_url(&url, settings)?;
+
+    let redirect_settings = settings.clone();
+    let client = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent("luban")
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() > MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            match allowed_url(attempt.url(), &redirect_settings) {
+                Ok(()) => attempt.follow(),
+                Err(err) => attempt.error(err.to_string()),
+            }
+        }))
+        .build()
+        .context("failed to build http client")?;
+
+    let response = client
+        .get(url.clone())
+        .header(
+            reqwest::header::ACCEPT,
+            "text/html,application/xhtml+xml,text/plain;q=0.9,*/*;q=0.5",
+        )
+        .send()
+        .with_context(|| format!("failed to fetch {url}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow!("fetching {url} failed with status {status}"));
+    }
+    let final_url = response.url().clone();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| {
+            v.split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .unwrap_or_default();
+
+    let mut body = Vec::new();
+    response
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_end(&mut body)
+        .with_context(|| format!("failed to read {url}"))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(anyhow!(
+            "{url} is larger than {} MiB",
+            MAX_BODY_BYTES / 1024 / 1024
+        ));
+    }
+    let body = String::from_utf8_lossy(&body);
+
+    let is_html = content_type.contains("html")
+        || (content_type.is_empty() && body.trim_start().starts_with('<'));
+    let (title, content) = if is_html {
+        let page = html_to_markdown(&body, Some(&final_url));
+        (page.title, page.markdown)
+    } else if content_type.starts_with("text/") {
+        (None, body.trim().to_owned())
+    } else if content_type.ends_with("json") || content_type.ends_with("xml") {
+        let lang = if content_type.ends_with("json") {
+            "json"
+        } else {
+            "xml"
+        };
+        (None, format!("```{lang}\n{}\n```", body.trim()))
+    } else {
+        return Err(anyhow!(
+            "{url} returned unsupported content type {content_type:?}; attach it as a file instead"
+        ));
+    };
+
+    let mut markdown = String::with_capacity(content.len() + 256);
+    markdown.push_str("---\n");
+    markdown.push_str(&format!("source_url: {url}\n"));
+    if final_url != url {
+        markdown.push_str(&format!("final_url: {final_url}\n"));
+    }
+    markdown.push_str(&format!(
+        "fetched_at: {}\n",
+        rfc3339_utc(fetched_at_unix_secs)
+    ));
+    if let Some(title) = &title {
+        let quoted = serde_json::to_string(title).unwrap_or_default();
+        markdown.push_str(&format!("title: {quoted}\n"));
+    }
+    markdown.push_str("---\n\n");
+    if let Some(title) = &title
+        && !content.starts_with("# ")
+    {
+        markdown.push_str(&format!("# {title}\n\n"));
+    }
+    markdown.push_str(&content);
+    markdown.push('\n');
+
+    Ok(UrlSnapshot { title, markdown })
+}
+
+pub(super) struct MarkdownPage {
+    pub(super) title: Option<String>,
+    pub(super) markdown: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum HtmlToken<'a> {
+    Text(&'a str),
+    Start {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    End(String),
+}
+
+impl HtmlToken<'_> {
+    fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            HtmlToken::Start { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str()),
+            _ => None,
+        }
+    }
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "aside", "button", "footer", "form", "head", "iframe", "nav", "noscript", "script", "select",
+    "style", "svg", "template", "textarea", "title",
+];
+
+/// A tolerant tokenizer: malformed markup degrades to text rather than failing.
+fn tokenize_html(html: &str) -> Vec<HtmlToken<'_>> {
+    let lower = html.to_ascii_lowercase();
+    let bytes = html.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0usize;
+    let mut text_start = 0usize;
+
+    while pos < bytes.len() {
+        if bytes[pos] != b'<' {
+            pos += 1;
+            continue;
+        }
+        let rest = &lower[pos..];
+        let next = bytes.get(pos + 1).copied().unwrap_or(b' ');
+        if rest.starts_with("<!--") {
+            if text_start < pos {
+                tokens.push(HtmlToken::Text(&html[text_start..pos]));
+            }
+            pos = rest.find("-->").map(|i| pos + i + 3).unwrap_or(bytes.len());
+            text_start = pos;
+            continue;
+        }
+        if !(next == b'!' || next == b'?' || next == b'/' || next.is_ascii_alphabetic()) {
+            pos += 1;
+            continue;
+        }
+        let Some(close) = html[pos..].find('>') else {
+            break;
+        };
+
+        if text_start < pos {
+            tokens.push(HtmlToken::Text(&html[text_start..pos]));
+        }
+        let inner = &html[pos + 1..pos + close];
+        pos += close + 1;
+        text_start = pos;
+
+        if next == b'/' {
+            let name = tag_name(&inner[1..]);
+            if !name.is_empty() {
+                tokens.push(HtmlToken::End(name));
+            }
+            continue;
+        }
+        if !next.is_ascii_alphabetic() {
+            continue;
+        }
+
+        let name = tag_name(inner);
+        let attrs = parse_attrs(&inner[name.len().min(inner.len())..]);
+        let raw = RAW_TEXT_ELEMENTS.contains(&name.as_str());
+        tokens.push(HtmlToken::Start {
+            name: name.clone(),
+            attrs,
+        });
+        if raw {
+            let close = format!("</{name}");
+            let end = lower[pos..]
+                .find(&close)
+                .map(|i| pos + i)
+                .unwrap_or(bytes.len());
+            if pos < end {
+                tokens.push(HtmlToken::Text(&html[pos..end]));
+            }
+            tokens.push(HtmlToken::End(name));
+            pos = lower[end..]
+                .find('>')
+                .map(|i| end + i + 1)
+                .unwrap_or(bytes.len());
+            text_start = pos;
+        }
+    }
+    if text_start < bytes.len() {
+        tokens.push(HtmlToken::Text(&html[text_start..]));
+    }
+    tokens
+}
+
+fn tag_name(raw: &str) -> String {
+    raw.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn parse_attrs(raw: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut chars = raw.trim_end_matches('/').char_indices().peekable();
+    let raw = raw.trim_end_matches('/');
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == '/' {
+            chars.next();
+            continue;
+        }
+        let mut end = start;
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() || c == '=' {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let key = raw[start..end].to_ascii_lowercase();
+        while chars.peek().is_some_and(|(_, c)| c.is_whitespace()) {
+            chars.next();
+        }
+        let mut value = String::new();
+        if chars.peek().is_some_and(|(_, c)| *c == '=') {
+            chars.next();
+            while chars.peek().is_some_and(|(_, c)| c.is_whitespace()) {
+                chars.next();
+            }
+            let quote = chars
+                .peek()
+                .map(|(_, c)| *c)
+                .filter(|c| *c == '"' || *c == '\'');
+            if quote.is_some() {
+                chars.next();
+            }
+            while let Some(&(_, c)) = chars.peek() {
+                if Some(c) == quote || (quote.is_none() && c.is_whitespace()) {
+                    chars.next();
+                    break;
+                }
+                value.push(c);
+                chars.next();
+            }
+        }
+        if !key.is_empty() {
+            attrs.push((key, decode_entities(&value)));
+        }
+    }
+    attrs
+}
+
+fn decode_entities(raw: &str) -> String {
+    if !raw.contains('&') {
+        return raw.to_owned();
+    }
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let end = rest[1..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+            .map(|i| i + 1)
+            .unwrap_or(rest.len());
+        let entity = &rest[1..end];
+        let decoded = if let Some(num) = entity.strip_prefix('#') {
+            let code = match num.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => num.parse::<u32>().ok(),
+            };
+            code.and_then(char::from_u32)
+        } else {
+            named_entity(entity)
+        };
+        match decoded {
+            Some(ch) => {
+                out.push(ch);
+                rest = &rest[end..];
+                if rest.starts_with(';') {
+                    rest = &rest[1..];
+                }
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn named_entity(name: &str) -> Option<char> {
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "middot" => '·',
+        "bull" => '•',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "times" => '×',
+        "rarr" => '→',
+        "larr" => '←',
+        _ => return None,
+    })
+}
+
+fn collapse_whitespace(raw: &str) -> String {
+    raw.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Restricts rendering to the first `<main>` or `<article>` element when the page has one.
+fn main_content<'t, 'a>(tokens: &'t [HtmlToken<'a>]) -> &'t [HtmlToken<'a>] {
+    for wanted in ["main", "article"] {
+        let Some(start) = tokens
+            .iter()
+            .position(|t| matches!(t, HtmlToken::Start { name, .. } if name == wanted))
+        else {
+            continue;
+        };
+        let mut depth = 0usize;
+        for (i, token) in tokens.iter().enumerate().skip(start) {
+            match token {
+                HtmlToken::Start { name, .. } if name == wanted => depth += 1,
+                HtmlToken::End(name) if name == wanted => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return &tokens[start..=i];
+                    }
+                }
+                _ => {}
+            }
+        }
+        return &tokens[start..];
+    }
+    tokens
+}
+
+struct ListState {
+    ordered: bool,
+    next: u64,
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    out: String,
+    at_line_start: bool,
+    pending_space: bool,
+    just_marked: bool,
+    pre: usize,
+    quote: usize,
+    lists: Vec<ListState>,
+    links: Vec<(usize, Option<String>)>,
+    cell: usize,
+    table_rows: usize,
+    row_cells: usize,
+    row_is_header: bool,
+}
+
+impl MarkdownWriter {
+    fn trim_trailing_spaces(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+    }
+
+    fn line_break(&mut self) {
+        self.trim_trailing_spaces();
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.at_line_start = true;
+        self.pending_space = false;
+        self.just_marked = false;
+    }
+
+    fn block_break(&mut self) {
+        if self.cell > 0 {
+            self.pending_space = true;
+            return;
+        }
+        if !self.lists.is_empty() {
+            if !self.just_marked {
+                self.line_break();
+            }
+            return;
+        }
+        self.line_break();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn line_prefix(&mut self) {
+        if !self.at_line_start {
+            return;
+        }
+        for _ in 0..self.quote {
+            self.out.push_str("> ");
+        }
+        if !self.lists.is_empty() && !self.just_marked {
+            self.out.push_str(&"  ".repeat(self.lists.len()));
+        }
+        self.at_line_start = false;
+    }
+
+    fn write_raw(&mut self, text: &str) {
+        if self.pending_space && !self.at_line_start && !self.out.ends_with([' ', '\n', '[']) {
+            self.out.push(' ');
+        }
+        self.pending_space = false;
+        self.line_prefix();
+        self.out.push_str(text);
+        self.just_marked = false;
+    }
+
+    fn write_text(&mut self, raw: &str) {
+        let text = decode_entities(raw);
+        if self.pre > 0 {
+            self.out.push_str(&text);
+            return;
+        }
+        if text.starts_with(char::is_whitespace) {
+            self.pending_space = true;
+        }
+        let collapsed = collapse_whitespace(&text);
+        if !collapsed.is_empty() {
+            self.write_raw(&collapsed);
+        }
+        if text.ends_with(char::is_whitespace) {
+            self.pending_space = true;
+        }
+    }
+
+    fn start(&mut self, token: &HtmlToken<'_>, name: &str, base: Option<&Url>) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break();
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                self.write_raw(&format!("{} ", "#".repeat(level)));
+                self.pending_space = false;
+            }
+            "p" | "div" | "section" | "article" | "main" | "header" | "figure" | "dl" | "dt"
+            | "dd" | "table" | "details" | "summary" => self.block_break(),
+            "br" => self.line_break(),
+            "hr" => {
+                self.block_break();
+                self.write_raw("---");
+                self.block_break();
+            }
+            "pre" => {
+                self.block_break();
+                self.line_prefix();
+                self.out.push_str("```\n");
+                self.pre += 1;
+            }
+            "code" if self.pre == 0 => self.write_raw("`"),
+            "strong" | "b" if self.pre == 0 => self.write_raw("**"),
+            "em" | "i" if self.pre == 0 => self.write_raw("_"),
+            "blockquote" => {
+                self.block_break();
+                self.quote += 1;
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block_break();
+                } else {
+                    self.line_break();
+                }
+                let next = token
+                    .attr("start")
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(1);
+                self.lists.push(ListState {
+                    ordered: name == "ol",
+                    next,
+                });
+            }
+            "li" => {
+                self.line_break();
+                let depth = self.lists.len().max(1);
+                let marker = match self.lists.last_mut() {
+                    Some(list) if list.ordered => {
+                        list.next += 1;
+                        format!("{}. ", list.next - 1)
+                    }
+                    _ => "- ".to_owned(),
+                };
+                for _ in 0..self.quote {
+                    self.out.push_str("> ");
+                }
+                self.out.push_str(&"  ".repeat(depth - 1));
+                self.out.push_str(&marker);
+                self.at_line_start = false;
+                self.just_marked = true;
+            }
+            "a" => {
+                let href = token
+                    .attr("href")
+                    .map(str::trim)
+                    .filter(|href| !href.is_empty() && !href.starts_with('#'))
+                    .filter(|href| !href.to_ascii_lowercase().starts_with("javascript:"))
+                    .map(|href| resolve_href(href, base));
+                self.write_raw("[");
+                self.links.push((self.out.len() - 1, href));
+            }
+            "img" => {
+                let alt = collapse_whitespace(token.attr("alt").unwrap_or_default());
+                if let Some(src) = token.attr("src").filter(|_| !alt.is_empty()) {
+                    let src = resolve_href(src.trim(), base);
+                    self.write_raw(&format!("![{alt}]({src})"));
+                }
+            }
+            "tr" => {
+                self.line_break();
+                self.line_prefix();
+                self.out.push('|');
+                self.row_cells = 0;
+                self.row_is_header = false;
+            }
+            "td" | "th" => {
+                self.cell += 1;
+                self.row_cells += 1;
+                self.row_is_header |= name == "th";
+                self.out.push(' ');
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "section" | "article"
+            | "main" | "header" | "figure" | "dl" | "dt" | "dd" | "details" | "summary" => {
+                self.block_break()
+            }
+            "pre" if self.pre > 0 => {
+                self.pre -= 1;
+                if !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.out.push_str("```");
+                self.at_line_start = false;
+                self.block_break();
+            }
+            "code" if self.pre == 0 => self.out.push('`'),
+            "strong" | "b" if self.pre == 0 => self.out.push_str("**"),
+            "em" | "i" if self.pre == 0 => self.out.push('_'),
+            "blockquote" => {
+                self.block_break();
+                self.quote = self.quote.saturating_sub(1);
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.block_break();
+            }
+            "li" => self.line_break(),
+            "a" => {
+                let Some((open, href)) = self.links.pop() else {
+                    return;
+                };
+                let label = self.out[open + 1..].trim();
+                match href {
+                    Some(href) if !label.is_empty() => {
+                        let trimmed = self.out.trim_end().len();
+                        self.out.truncate(trimmed);
+                        self.out.push_str(&format!("]({href})"));
+                    }
+                    _ => {
+                        self.out.remove(open);
+                    }
+                }
+            }
+            "td" | "th" => {
+                self.cell = self.cell.saturating_sub(1);
+                self.trim_trailing_spaces();
+                self.out.push_str(" |");
+                self.pending_space = false;
+            }
+            "tr" => {
+                self.table_rows += 1;
+                if self.table_rows == 1 && self.row_cells > 0 {
+                    self.line_break();
+                    self.out.push('|');
+                    self.out.push_str(&" --- |".repeat(self.row_cells));
+                }
+                self.line_break();
+            }
+            "table" => {
+                self.table_rows = 0;
+                self.block_break();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resolve_href(href: &str, base: Option<&Url>) -> String {
+    base.and_then(|base| base.join(href).ok())
+        .map(|url| url.to_string())
+        .unwrap_or_else(|| href.to_owned())
+}
+
+/// Converts an HTML page to Markdown, keeping headings, paragraphs, lists, links, code blocks,
+/// emphasis, quotes and tables while dropping scripts, styles and navigation chrome.
+pub(super) fn html_to_markdown(html: &str, base: Option<&Url>) -> MarkdownPage {
+    let tokens = tokenize_html(html);
+
+    let mut title = None;
+    for pair in tokens.windows(2) {
+        if let [HtmlToken::Start { name, .. }, HtmlToken::Text(text)] = pair
+            && name == "title"
+        {
+            title = Some(collapse_whitespace(&decode_entities(text))).filter(|t| !t.is_empty());
+            break;
+        }
+    }
+
+    let mut writer = MarkdownWriter {
+        at_line_start: true,
+        ..MarkdownWriter::default()
+    };
+    let mut skipping: Option<(String, usize)> = None;
+    for token in main_content(&tokens) {
+        if let Some((skipped, depth)) = skipping.as_mut() {
+            match token {
+                HtmlToken::Start { name, .. } if name == skipped => *depth += 1,
+                HtmlToken::End(name) if name == skipped => {
+                    *depth -= 1;
+                    if *depth == 0 {
+                        skipping = None;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match token {
+            HtmlToken::Text(text) => writer.write_text(text),
+            HtmlToken::Start { name, .. } => {
+                if SKIPPED_ELEMENTS.contains(&name.as_str()) {
+                    if !VOID_ELEMENTS.contains(&name.as_str()) {
+                        skipping = Some((name.clone(), 1));
+                    }
+                    continue;
+                }
+                writer.start(token, name, base);
+            }
+            HtmlToken::End(name) => writer.end(name),
+        }
+    }
+
+    let mut markdown = String::with_capacity(writer.out.len());
+    let mut blank_lines = 0usize;
+    for line in writer.out.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        markdown.push_str(line);
+        markdown.push('\n');
+    }
+
+    MarkdownPage {
+        title,
+        markdown: markdown.trim().to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::serve_stub;
+
+    fn html_response(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/html; charset=utf-8\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    fn redirect_response(location: &str) -> String {
+        format!(
+            "HTTP/1.1 302 Found\r\nlocation: {location}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+        )
+    }
+
+    fn local_only() -> UrlAttachmentSettings {
+        UrlAttachmentSettings::from_domains(&["127.0.0.1".to_owned()]).unwrap()
+    }
+
+    #[test]
+    fn html_converts_to_markdown() {
+        let html = r#"<!doctype html>
+<html><head><title>Guide &amp; Notes</title><style>body { color: red }</style></head>
+<body>
+<nav><a href="/">Home</a></nav>
+<main>
+  <h1>Getting   started</h1>
+  <p>Read the <a href="/docs/intro">intro</a> and <strong>bold</strong> <em>text</em>.<br>Next line</p>
+  <ul><li>one</li><li>two <code>x &lt; y</code></li></ul>
+  <ol start="3"><li>three</li></ol>
+  <pre><code>fn main() {
+    println!("hi");
+}</code></pre>
+  <blockquote><p>quoted</p></blockquote>
+  <table><tr><th>Name</th><th>Value</th></tr><tr><td>a</td><td>1</td></tr></table>
+  <script>alert("no")</script>
+</main>
+<footer>ignored</footer>
+</body></html>"#;
+        let base = Url::parse("https://example.com/guide/").unwrap();
+        let page = html_to_markdown(html, Some(&base));
+        assert_eq!(page.title.as_deref(), Some("Guide & Notes"));
+        assert_eq!(
+            page.markdown,
+            concat!(
+                "# Getting started\n",
+                "\n",
+                "Read the [intro](https://example.com/docs/intro) and **bold** _text_.\n",
+                "Next line\n",
+                "\n",
+                "- one\n",
+                "- two `x < y`\n",
+                "\n",
+                "3. three\n",
+                "\n",
+                "```\n",
+                "fn main() {\n",
+                "    println!(\"hi\");\n",
+                "}\n",
+                "```\n",
+                "\n",
+                "> quoted\n",
+                "\n",
+                "| Name | Value |\n",
+                "| --- | --- |\n",
+                "| a | 1 |",
+            )
+        );
+    }
+
+    #[test]
+    fn snapshot_fetches_local_page_with_front_matter() {
+        let base_url = serve_stub(vec![
+            (
+                "/page",
+                html_response("<title>Local page</title><p>Hello <b>world</b></p>"),
+            ),
+            ("/moved", redirect_response("/page")),
+            ("/escape", redirect_response("http://localhost/page")),
+        ]);
+
+        let snapshot =
+            fetch_url_snapshot(&format!("{base_url}/moved"), &local_only(), 1_767_225_600)
+                .expect("snapshot should succeed");
+        assert_eq!(snapshot.title.as_deref(), Some("Local page"));
+        assert_eq!(
+            snapshot.markdown,
+            format!(
+                "---\nsource_url: {base_url}/moved\nfinal_url: {base_url}/page\nfetched_at: 2026-01-01T00:00:00Z\ntitle: \"Local page\"\n---\n\n# Local page\n\nHello **world**\n"
+            )
+        );
+
+        let err = fetch_url_snapshot(&format!("{base_url}/escape"), &local_only(), 0)
+            .expect_err("redirects outside the allowlist should fail");
+        assert!(format!("{err:#}").contains("allowlist"), "{err:#}");
+
+        let err = fetch_url_snapshot(&format!("{base_url}/missing"), &local_only(), 0)
+            .expect_err("404 should fail");
+        assert!(err.to_string().contains("404"), "{err}");
+    }
+
+    #[test]
+    fn snapshot_rejects_urls_outside_the_allowlist() {
+        let err = fetch_url_snapshot("https://example.com/", &local_only(), 0)
+            .expect_err("host should be denied");
+        assert!(err.to_string().contains("allowlist"), "{err}");
+
+        let err = fetch_url_snapshot("file:///etc/passwd", &local_only(), 0)
+            .expect_err("scheme should be denied");
+        assert!(err.to_string().contains("http"), "{err}");
+
+        let err = fetch_url_snapshot("http://127.0.0.1/", &UrlAttachmentSettings::default(), 0)
+            .expect_err("empty allowlist denies everything");
+        assert!(err.to_string().contains("allowlist"), "{err}");
+    }
+}
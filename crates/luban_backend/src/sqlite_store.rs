@@ -26,7 +26,33 @@ impl std::fmt::Display for SqliteStoreError {
 
 impl std::error::Error for SqliteStoreError {}
 
-const LATEST_SCHEMA_VERSION: u32 = 23;
+fn count_attachment_ids(value: &serde_json::Value, out: &mut HashMap<String, u64>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                if key == "attachments"
+                    && let serde_json::Value::Array(items) = value
+                {
+                    for item in items {
+                        if let Some(id) = item.get("id").and_then(|v| v.as_str()) {
+                            *out.entry(id.to_owned()).or_insert(0) += 1;
+                        }
+                    }
+                } else {
+                    count_attachment_ids(value, out);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                count_attachment_ids(item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+const LATEST_SCHEMA_VERSION: u32 = 26;
 const WORKSPACE_CHAT_SCROLL_PREFIX: &str = "workspace_chat_scroll_y10_";
 const WORKSPACE_CHAT_SCROLL_ANCHOR_PREFIX: &str = "workspace_chat_scroll_anchor_";
 const WORKSPACE_ACTIVE_THREAD_PREFIX: &str = "workspace_active_thread_id_";
@@ -60,6 +86,16 @@ const TELEGRAM_BOT_TOKEN_KEY: &str = "telegram_bot_token";
 const TELEGRAM_BOT_USERNAME_KEY: &str = "telegram_bot_username";
 const TELEGRAM_PAIRED_CHAT_ID_KEY: &str = "telegram_paired_chat_id";
 const TELEGRAM_TOPIC_BINDINGS_KEY: &str = "telegram_topic_bindings";
+const TELEGRAM_GROUP_CHATS_KEY: &str = "telegram_group_chats";
+const NOTIFICATION_SETTINGS_KEY: &str = "notification_settings";
+const ATTACHMENT_TEXT_LIMITS_KEY: &str = "attachment_text_limits";
+const URL_ATTACHMENT_SETTINGS_KEY: &str = "url_attachment_settings";
+const TASK_ISSUE_LINKS_KEY: &str = "task_issue_links";
+const DELETION_SETTINGS_KEY: &str = "deletion_settings";
+const DELETED_PROJECTS_KEY: &str = "deleted_projects";
+const TURN_LIMITS_KEY: &str = "turn_limits";
+const TASK_REVIEW_COMMENTS_KEY: &str = "task_review_comments";
+const TASK_CI_AUTO_FIX_KEY: &str = "task_ci_auto_fix";
 
 const MIGRATIONS: &[(u32, &str)] = &[
     (
@@ -223,6 +259,27 @@ const MIGRATIONS: &[(u32, &str)] = &[
             "/migrations/0023_task_documents.sql"
         )),
     ),
+    (
+        24,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/migrations/0024_audit_log.sql"
+        )),
+    ),
+    (
+        25,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/migrations/0025_conversation_forks.sql"
+        )),
+    ),
+    (
+        26,
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/migrations/0026_workspace_archived_at.sql"
+        )),
+    ),
 ];
 
 #[derive(Clone)]
@@ -331,6 +388,50 @@ enum DbCommand {
         thread_local_id: u64,
         reply: mpsc::Sender<anyhow::Result<()>>,
     },
+    TruncateConversationAtCheckpoint {
+        project_slug: String,
+        workspace_name: String,
+        thread_local_id: u64,
+        checkpoint_id: String,
+        reply: mpsc::Sender<anyhow::Result<u64>>,
+    },
+    ForkConversationThread {
+        project_slug: String,
+        source_workspace_name: String,
+        workspace_name: String,
+        thread_local_id: u64,
+        origin: luban_domain::ThreadForkOrigin,
+        reply: mpsc::Sender<anyhow::Result<()>>,
+    },
+    SetConversationThreadDeleted {
+        project_slug: String,
+        workspace_name: String,
+        thread_local_id: u64,
+        deleted_at_unix_ms: Option<u64>,
+        reply: mpsc::Sender<anyhow::Result<()>>,
+    },
+    PurgeDeletedConversationThreads {
+        deleted_before_unix_ms: u64,
+        reply: mpsc::Sender<anyhow::Result<u64>>,
+    },
+    AppendAuditEntry {
+        origin: luban_domain::AuditOrigin,
+        record: luban_domain::AuditRecord,
+        reply: mpsc::Sender<anyhow::Result<luban_domain::AuditEntry>>,
+    },
+    ListAuditEntries {
+        before_id: Option<u64>,
+        limit: u64,
+        reply: mpsc::Sender<anyhow::Result<Vec<luban_domain::AuditEntry>>>,
+    },
+    LatestUndoableAuditEntry {
+        reply: mpsc::Sender<anyhow::Result<Option<luban_domain::AuditEntry>>>,
+    },
+    MarkAuditEntryUndone {
+        entry_id: u64,
+        undone_at_unix_ms: u64,
+        reply: mpsc::Sender<anyhow::Result<()>>,
+    },
     SaveConversationQueueState {
         project_slug: String,
         workspace_name: String,
@@ -427,6 +528,10 @@ enum DbCommand {
         context_id: u64,
         reply: mpsc::Sender<anyhow::Result<()>>,
     },
+    CountAttachmentReferences {
+        scope: Option<(String, String)>,
+        reply: mpsc::Sender<anyhow::Result<HashMap<String, u64>>>,
+    },
     ListNewTaskDrafts {
         reply: mpsc::Sender<anyhow::Result<Vec<luban_domain::NewTaskDraft>>>,
     },
@@ -657,6 +762,104 @@ impl SqliteStore {
                                 thread_local_id,
                             ));
                         }
+                        (
+                            Ok(db),
+                            DbCommand::TruncateConversationAtCheckpoint {
+                                project_slug,
+                                workspace_name,
+                                thread_local_id,
+                                checkpoint_id,
+                                reply,
+                            },
+                        ) => {
+                            let _ = reply.send(db.truncate_conversation_at_checkpoint(
+                                &project_slug,
+                                &workspace_name,
+                                thread_local_id,
+                                &checkpoint_id,
+                            ));
+                        }
+                        (
+                            Ok(db),
+                            DbCommand::ForkConversationThread {
+                                project_slug,
+                                source_workspace_name,
+                                workspace_name,
+                                thread_local_id,
+                                origin,
+                                reply,
+                            },
+                        ) => {
+                            let _ = reply.send(db.fork_conversation_thread(
+                                &project_slug,
+                                &source_workspace_name,
+                                &workspace_name,
+                                thread_local_id,
+                                &origin,
+                            ));
+                        }
+                        (
+                            Ok(db),
+                            DbCommand::SetConversationThreadDeleted {
+                                project_slug,
+                                workspace_name,
+                                thread_local_id,
+                                deleted_at_unix_ms,
+                                reply,
+                            },
+                        ) => {
+                            let _ = reply.send(db.set_conversation_thread_deleted(
+                                &project_slug,
+                                &workspace_name,
+                                thread_local_id,
+                                deleted_at_unix_ms,
+                            ));
+                        }
+                        (
+                            Ok(db),
+                            DbCommand::PurgeDeletedConversationThreads {
+                                deleted_before_unix_ms,
+                                reply,
+                            },
+                        ) => {
+                            let _ = reply.send(
+                                db.purge_deleted_conversation_threads(deleted_before_unix_ms),
+                            );
+                        }
+                        (
+                            Ok(db),
+                            DbCommand::AppendAuditEntry {
+                                origin,
+                                record,
+                                reply,
+                            },
+                        ) => {
+                            let _ = reply.send(db.append_audit_entry(origin, &record));
+                        }
+                        (
+                            Ok(db),
+                            DbCommand::ListAuditEntries {
+                                before_id,
+                                limit,
+                                reply,
+                            },
+                        ) => {
+                            let _ = reply.send(db.list_audit_entries(before_id, limit));
+                        }
+                        (Ok(db), DbCommand::LatestUndoableAuditEntry { reply }) => {
+                            let _ = reply.send(db.latest_undoable_audit_entry());
+                        }
+                        (
+                            Ok(db),
+                            DbCommand::MarkAuditEntryUndone {
+                                entry_id,
+                                undone_at_unix_ms,
+                                reply,
+                            },
+                        ) => {
+                            let _ =
+                                reply.send(db.mark_audit_entry_undone(entry_id, undone_at_unix_ms));
+                        }
                         (
                             Ok(db),
                             DbCommand::SaveConversationQueueState {
@@ -886,6 +1089,11 @@ impl SqliteStore {
                                 context_id,
                             ));
                         }
+                        (Ok(db), DbCommand::CountAttachmentReferences { scope, reply }) => {
+                            let _ = reply.send(db.count_attachment_references(scope.as_ref().map(
+                                |(project, workspace)| (project.as_str(), workspace.as_str()),
+                            )));
+                        }
                         (Ok(db), DbCommand::ListNewTaskDrafts { reply }) => {
                             let _ = reply.send(db.list_new_task_drafts());
                         }
@@ -1198,6 +1406,138 @@ impl SqliteStore {
         reply_rx.recv().context("sqlite worker terminated")?
     }
 
+    pub fn truncate_conversation_at_checkpoint(
+        &self,
+        project_slug: String,
+        workspace_name: String,
+        thread_local_id: u64,
+        checkpoint_id: String,
+    ) -> anyhow::Result<u64> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::TruncateConversationAtCheckpoint {
+                project_slug,
+                workspace_name,
+                thread_local_id,
+                checkpoint_id,
+                reply: reply_tx,
+            })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
+    pub fn fork_conversation_thread(
+        &self,
+        project_slug: String,
+        source_workspace_name: String,
+        workspace_name: String,
+        thread_local_id: u64,
+        origin: luban_domain::ThreadForkOrigin,
+    ) -> anyhow::Result<()> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::ForkConversationThread {
+                project_slug,
+                source_workspace_name,
+                workspace_name,
+                thread_local_id,
+                origin,
+                reply: reply_tx,
+            })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
+    pub fn set_conversation_thread_deleted(
+        &self,
+        project_slug: String,
+        workspace_name: String,
+        thread_local_id: u64,
+        deleted_at_unix_ms: Option<u64>,
+    ) -> anyhow::Result<()> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::SetConversationThreadDeleted {
+                project_slug,
+                workspace_name,
+                thread_local_id,
+                deleted_at_unix_ms,
+                reply: reply_tx,
+            })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
+    pub fn purge_deleted_conversation_threads(
+        &self,
+        deleted_before_unix_ms: u64,
+    ) -> anyhow::Result<u64> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::PurgeDeletedConversationThreads {
+                deleted_before_unix_ms,
+                reply: reply_tx,
+            })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
+    pub fn append_audit_entry(
+        &self,
+        origin: luban_domain::AuditOrigin,
+        record: luban_domain::AuditRecord,
+    ) -> anyhow::Result<luban_domain::AuditEntry> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::AppendAuditEntry {
+                origin,
+                record,
+                reply: reply_tx,
+            })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
+    pub fn list_audit_entries(
+        &self,
+        before_id: Option<u64>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<luban_domain::AuditEntry>> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::ListAuditEntries {
+                before_id,
+                limit,
+                reply: reply_tx,
+            })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
+    pub fn latest_undoable_audit_entry(&self) -> anyhow::Result<Option<luban_domain::AuditEntry>> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::LatestUndoableAuditEntry { reply: reply_tx })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
+    pub fn mark_audit_entry_undone(
+        &self,
+        entry_id: u64,
+        undone_at_unix_ms: u64,
+    ) -> anyhow::Result<()> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::MarkAuditEntryUndone {
+                entry_id,
+                undone_at_unix_ms,
+                reply: reply_tx,
+            })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn save_conversation_queue_state(
         &self,
@@ -1471,6 +1811,22 @@ impl SqliteStore {
         reply_rx.recv().context("sqlite worker terminated")?
     }
 
+    /// Counts references to each attachment id from context items, messages and queued prompts,
+    /// across all workspaces or within `scope` (`(project_slug, workspace_name)`).
+    pub fn count_attachment_references(
+        &self,
+        scope: Option<(String, String)>,
+    ) -> anyhow::Result<HashMap<String, u64>> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(DbCommand::CountAttachmentReferences {
+                scope,
+                reply: reply_tx,
+            })
+            .context("sqlite worker is not running")?;
+        reply_rx.recv().context("sqlite worker terminated")?
+    }
+
     pub fn list_new_task_drafts(&self) -> anyhow::Result<Vec<luban_domain::NewTaskDraft>> {
         let (reply_tx, reply_rx) = mpsc::channel();
         self.tx
@@ -1604,6 +1960,30 @@ fn respond_db_open_error(err: &anyhow::Error, cmd: DbCommand) {
         DbCommand::DeleteConversationThread { reply, .. } => {
             let _ = reply.send(Err(anyhow!(message)));
         }
+        DbCommand::TruncateConversationAtCheckpoint { reply, .. } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
+        DbCommand::ForkConversationThread { reply, .. } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
+        DbCommand::SetConversationThreadDeleted { reply, .. } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
+        DbCommand::PurgeDeletedConversationThreads { reply, .. } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
+        DbCommand::AppendAuditEntry { reply, .. } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
+        DbCommand::ListAuditEntries { reply, .. } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
+        DbCommand::LatestUndoableAuditEntry { reply } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
+        DbCommand::MarkAuditEntryUndone { reply, .. } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
         DbCommand::SaveConversationQueueState { reply, .. } => {
             let _ = reply.send(Err(anyhow!(message)));
         }
@@ -1643,6 +2023,9 @@ fn respond_db_open_error(err: &anyhow::Error, cmd: DbCommand) {
         DbCommand::DeleteContextItem { reply, .. } => {
             let _ = reply.send(Err(anyhow!(message)));
         }
+        DbCommand::CountAttachmentReferences { reply, .. } => {
+            let _ = reply.send(Err(anyhow!(message)));
+        }
         DbCommand::ListNewTaskDrafts { reply } => {
             let _ = reply.send(Err(anyhow!(message)));
         }
@@ -1722,7 +2105,7 @@ impl SqliteDatabase {
         }
 
         let mut stmt = self.conn.prepare(
-            "SELECT id, project_id, workspace_name, worktree_path, status, last_activity_at
+            "SELECT id, project_id, workspace_name, worktree_path, status, last_activity_at, archived_at
              FROM workspaces ORDER BY id ASC",
         )?;
         let rows = stmt.query_map([], |row| {
@@ -1733,13 +2116,23 @@ impl SqliteDatabase {
                 row.get::<_, String>(3)?,
                 row.get::<_, i64>(4)?,
                 row.get::<_, Option<i64>>(5)?,
+                row.get::<_, Option<i64>>(6)?,
             ))
         })?;
 
         for row in rows {
-            let (id, project_id, workspace_name, worktree_path, status, last_activity_at) = row?;
+            let (
+                id,
+                project_id,
+                workspace_name,
+                worktree_path,
+                status,
+                last_activity_at,
+                archived_at,
+            ) = row?;
             let status = workspace_status_from_i64(status)?;
             let last_activity_at_unix_seconds = last_activity_at.map(|v| v as u64);
+            let archived_at_unix_seconds = archived_at.map(|v| v as u64);
 
             let Some(project) = projects.iter_mut().find(|p| p.id == project_id) else {
                 continue;
@@ -1753,6 +2146,7 @@ impl SqliteDatabase {
                 worktree_path: PathBuf::from(worktree_path),
                 status,
                 last_activity_at_unix_seconds,
+                archived_at_unix_seconds,
             });
         }
 
@@ -1903,14 +2297,114 @@ impl SqliteDatabase {
             .optional()
             .context("failed to load telegram topic bindings")?;
 
-        let mut task_prompt_templates = HashMap::new();
-        let mut stmt = self.conn.prepare(
-            "SELECT key, value FROM app_settings_text WHERE key LIKE 'task_prompt_template_%'",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-        })?;
-        for row in rows {
+        let telegram_group_chats = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![TELEGRAM_GROUP_CHATS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load telegram group chats")?;
+
+        let notification_settings = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![NOTIFICATION_SETTINGS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load notification settings")?;
+
+        let attachment_text_limits = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![ATTACHMENT_TEXT_LIMITS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load attachment text limits")?;
+
+        let url_attachment_settings = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![URL_ATTACHMENT_SETTINGS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load url attachment settings")?;
+
+        let task_issue_links = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![TASK_ISSUE_LINKS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load task issue links")?;
+
+        let deletion_settings = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![DELETION_SETTINGS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load deletion settings")?;
+
+        let deleted_projects = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![DELETED_PROJECTS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load deleted projects")?;
+
+        let turn_limits = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![TURN_LIMITS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load turn limits")?;
+
+        let task_review_comments = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![TASK_REVIEW_COMMENTS_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load task review comments")?;
+
+        let task_ci_auto_fix = self
+            .conn
+            .query_row(
+                "SELECT value FROM app_settings_text WHERE key = ?1",
+                params![TASK_CI_AUTO_FIX_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .context("failed to load task CI auto-fix")?;
+
+        let mut task_prompt_templates = HashMap::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT key, value FROM app_settings_text WHERE key LIKE 'task_prompt_template_%'",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
             let (key, value) = row?;
             let Some(kind) = key.strip_prefix(TASK_PROMPT_TEMPLATE_PREFIX) else {
                 continue;
@@ -1997,6 +2491,16 @@ impl SqliteDatabase {
                 telegram_bot_username,
                 telegram_paired_chat_id,
                 telegram_topic_bindings,
+                telegram_group_chats,
+                notification_settings,
+                attachment_text_limits,
+                url_attachment_settings,
+                task_issue_links,
+                deletion_settings,
+                deleted_projects,
+                turn_limits,
+                task_review_comments,
+                task_ci_auto_fix,
             });
         }
 
@@ -2412,6 +2916,16 @@ impl SqliteDatabase {
             telegram_bot_username,
             telegram_paired_chat_id,
             telegram_topic_bindings,
+            telegram_group_chats,
+            notification_settings,
+            attachment_text_limits,
+            url_attachment_settings,
+            task_issue_links,
+            deletion_settings,
+            deleted_projects,
+            turn_limits,
+            task_review_comments,
+            task_ci_auto_fix,
         })
     }
 
@@ -2499,14 +3013,15 @@ impl SqliteDatabase {
                 workspace_ids.push(workspace.id);
                 let worktree_path = workspace.worktree_path.to_string_lossy().into_owned();
                 tx.execute(
-                    "INSERT INTO workspaces (id, project_id, workspace_name, worktree_path, status, last_activity_at, created_at, updated_at)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, COALESCE((SELECT created_at FROM workspaces WHERE id = ?1), ?7), ?7)
+                    "INSERT INTO workspaces (id, project_id, workspace_name, worktree_path, status, last_activity_at, archived_at, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, COALESCE((SELECT created_at FROM workspaces WHERE id = ?1), ?8), ?8)
                      ON CONFLICT(id) DO UPDATE SET
                        project_id = excluded.project_id,
                        workspace_name = excluded.workspace_name,
                        worktree_path = excluded.worktree_path,
                        status = excluded.status,
                        last_activity_at = excluded.last_activity_at,
+                       archived_at = excluded.archived_at,
                        updated_at = excluded.updated_at",
                     params![
                         workspace.id as i64,
@@ -2515,6 +3030,7 @@ impl SqliteDatabase {
                         worktree_path,
                         workspace_status_to_i64(workspace.status),
                         workspace.last_activity_at_unix_seconds.map(|v| v as i64),
+                        workspace.archived_at_unix_seconds.map(|v| v as i64),
                         now,
                     ],
                 )?;
@@ -2574,32 +3090,43 @@ impl SqliteDatabase {
 
         {
             use std::collections::HashSet;
+            // Workspaces of soft-deleted projects keep their conversations until the project
+            // is purged from `deleted_projects`.
+            let retained_deleted_workspaces =
+                deleted_project_workspace_keys(snapshot.deleted_projects.as_deref());
+            let previously_deleted_workspaces = deleted_project_workspace_keys(
+                tx.query_row(
+                    "SELECT value FROM app_settings_text WHERE key = ?1",
+                    params![DELETED_PROJECTS_KEY],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?
+                .as_deref(),
+            );
             let snapshot_workspace_ids: HashSet<u64> = workspace_ids.iter().copied().collect();
-            for (workspace_id, (project_slug, workspace_name)) in existing_workspace_keys {
-                if snapshot_workspace_ids.contains(&workspace_id) {
+            let snapshot_workspace_keys: HashSet<(&str, &str)> = snapshot
+                .projects
+                .iter()
+                .flat_map(|p| {
+                    p.workspaces
+                        .iter()
+                        .map(|w| (p.slug.as_str(), w.workspace_name.as_str()))
+                })
+                .collect();
+            let mut purged_workspace_ids = HashSet::new();
+            for (workspace_id, (project_slug, workspace_name)) in existing_workspace_keys
+                .into_iter()
+                .chain(previously_deleted_workspaces)
+            {
+                if snapshot_workspace_ids.contains(&workspace_id)
+                    || retained_deleted_workspaces.contains_key(&workspace_id)
+                    || snapshot_workspace_keys
+                        .contains(&(project_slug.as_str(), workspace_name.as_str()))
+                    || !purged_workspace_ids.insert(workspace_id)
+                {
                     continue;
                 }
-
-                tx.execute(
-                    "DELETE FROM conversation_entries
-                     WHERE project_slug = ?1 AND workspace_name = ?2",
-                    params![project_slug, workspace_name],
-                )?;
-                tx.execute(
-                    "DELETE FROM conversations
-                     WHERE project_slug = ?1 AND workspace_name = ?2",
-                    params![project_slug, workspace_name],
-                )?;
-                tx.execute(
-                    "DELETE FROM task_documents
-                     WHERE project_slug = ?1 AND workspace_name = ?2",
-                    params![project_slug, workspace_name],
-                )?;
-                tx.execute(
-                    "DELETE FROM task_document_events
-                     WHERE project_slug = ?1 AND workspace_name = ?2",
-                    params![project_slug, workspace_name],
-                )?;
+                delete_workspace_conversation_data(&tx, &project_slug, &workspace_name)?;
             }
         }
 
@@ -2627,6 +3154,33 @@ impl SqliteDatabase {
             )?;
         }
 
+        // Not gated on `persist_ui_state`: deleted projects own conversation data that the
+        // workspace cleanup above would otherwise drop, token tallies back task budgets, and
+        // review comments and CI auto-fix attempts are task content.
+        for (key, value) in [
+            (DELETION_SETTINGS_KEY, snapshot.deletion_settings.as_deref()),
+            (DELETED_PROJECTS_KEY, snapshot.deleted_projects.as_deref()),
+            (TURN_LIMITS_KEY, snapshot.turn_limits.as_deref()),
+            (
+                TASK_REVIEW_COMMENTS_KEY,
+                snapshot.task_review_comments.as_deref(),
+            ),
+            (TASK_CI_AUTO_FIX_KEY, snapshot.task_ci_auto_fix.as_deref()),
+        ] {
+            if let Some(value) = value {
+                tx.execute(
+                    "INSERT INTO app_settings_text (key, value, created_at, updated_at)
+                     VALUES (?1, ?2, COALESCE((SELECT created_at FROM app_settings_text WHERE key = ?1), ?3), ?3)
+                     ON CONFLICT(key) DO UPDATE SET
+                       value = excluded.value,
+                       updated_at = excluded.updated_at",
+                    params![key, value, now],
+                )?;
+            } else {
+                tx.execute("DELETE FROM app_settings_text WHERE key = ?1", params![key])?;
+            }
+        }
+
         if self.persist_ui_state {
             let upsert_text = |tx: &rusqlite::Transaction<'_>, key: &str, value: Option<&str>| {
                 if let Some(value) = value {
@@ -2975,6 +3529,86 @@ impl SqliteDatabase {
             )?;
         }
 
+        if let Some(value) = snapshot.telegram_group_chats.as_deref() {
+            tx.execute(
+                "INSERT INTO app_settings_text (key, value, created_at, updated_at)
+                 VALUES (?1, ?2, COALESCE((SELECT created_at FROM app_settings_text WHERE key = ?1), ?3), ?3)
+                 ON CONFLICT(key) DO UPDATE SET
+                   value = excluded.value,
+                   updated_at = excluded.updated_at",
+                params![TELEGRAM_GROUP_CHATS_KEY, value, now],
+            )?;
+        } else {
+            tx.execute(
+                "DELETE FROM app_settings_text WHERE key = ?1",
+                params![TELEGRAM_GROUP_CHATS_KEY],
+            )?;
+        }
+
+        if let Some(value) = snapshot.notification_settings.as_deref() {
+            tx.execute(
+                "INSERT INTO app_settings_text (key, value, created_at, updated_at)
+                 VALUES (?1, ?2, COALESCE((SELECT created_at FROM app_settings_text WHERE key = ?1), ?3), ?3)
+                 ON CONFLICT(key) DO UPDATE SET
+                   value = excluded.value,
+                   updated_at = excluded.updated_at",
+                params![NOTIFICATION_SETTINGS_KEY, value, now],
+            )?;
+        } else {
+            tx.execute(
+                "DELETE FROM app_settings_text WHERE key = ?1",
+                params![NOTIFICATION_SETTINGS_KEY],
+            )?;
+        }
+
+        if let Some(value) = snapshot.attachment_text_limits.as_deref() {
+            tx.execute(
+                "INSERT INTO app_settings_text (key, value, created_at, updated_at)
+                 VALUES (?1, ?2, COALESCE((SELECT created_at FROM app_settings_text WHERE key = ?1), ?3), ?3)
+                 ON CONFLICT(key) DO UPDATE SET
+                   value = excluded.value,
+                   updated_at = excluded.updated_at",
+                params![ATTACHMENT_TEXT_LIMITS_KEY, value, now],
+            )?;
+        } else {
+            tx.execute(
+                "DELETE FROM app_settings_text WHERE key = ?1",
+                params![ATTACHMENT_TEXT_LIMITS_KEY],
+            )?;
+        }
+
+        if let Some(value) = snapshot.url_attachment_settings.as_deref() {
+            tx.execute(
+                "INSERT INTO app_settings_text (key, value, created_at, updated_at)
+                 VALUES (?1, ?2, COALESCE((SELECT created_at FROM app_settings_text WHERE key = ?1), ?3), ?3)
+                 ON CONFLICT(key) DO UPDATE SET
+                   value = excluded.value,
+                   updated_at = excluded.updated_at",
+                params![URL_ATTACHMENT_SETTINGS_KEY, value, now],
+            )?;
+        } else {
+            tx.execute(
+                "DELETE FROM app_settings_text WHERE key = ?1",
+                params![URL_ATTACHMENT_SETTINGS_KEY],
+            )?;
+        }
+
+        if let Some(value) = snapshot.task_issue_links.as_deref() {
+            tx.execute(
+                "INSERT INTO app_settings_text (key, value, created_at, updated_at)
+                 VALUES (?1, ?2, COALESCE((SELECT created_at FROM app_settings_text WHERE key = ?1), ?3), ?3)
+                 ON CONFLICT(key) DO UPDATE SET
+                   value = excluded.value,
+                   updated_at = excluded.updated_at",
+                params![TASK_ISSUE_LINKS_KEY, value, now],
+            )?;
+        } else {
+            tx.execute(
+                "DELETE FROM app_settings_text WHERE key = ?1",
+                params![TASK_ISSUE_LINKS_KEY],
+            )?;
+        }
+
         tx.execute(
             "DELETE FROM app_settings_text WHERE key LIKE 'task_prompt_template_%'",
             [],
@@ -3379,9 +4013,13 @@ impl SqliteDatabase {
                        AND e.thread_local_id = c.thread_local_id
                        AND e.kind IN ('turn_error', 'turn_canceled', 'turn_duration')
                      ORDER BY e.seq DESC
-                     LIMIT 1) AS last_turn_kind
+                     LIMIT 1) AS last_turn_kind,
+                    c.forked_from_workspace_id,
+                    c.forked_from_thread_local_id,
+                    c.forked_from_entry_id
              FROM conversations c
              WHERE c.project_slug = ?1 AND c.workspace_name = ?2
+               AND c.deleted_at_unix_ms IS NULL
              ORDER BY c.updated_at DESC, c.thread_local_id DESC",
         )?;
         let rows = stmt.query_map(params![project_slug, workspace_name], |row| {
@@ -3399,6 +4037,9 @@ impl SqliteDatabase {
                 row.get::<_, Option<i64>>(10)?,
                 row.get::<_, i64>(11)?,
                 row.get::<_, Option<String>>(12)?,
+                row.get::<_, Option<i64>>(13)?,
+                row.get::<_, Option<i64>>(14)?,
+                row.get::<_, Option<String>>(15)?,
             ))
         })?;
 
@@ -3418,6 +4059,9 @@ impl SqliteDatabase {
                 run_finished_at_unix_ms,
                 pending_prompt_count,
                 last_turn_kind,
+                forked_from_workspace_id,
+                forked_from_thread_local_id,
+                forked_from_entry_id,
             ) = row?;
             let Some(thread_local_id) = u64::try_from(thread_local_id).ok() else {
                 continue;
@@ -3454,6 +4098,20 @@ impl SqliteDatabase {
                 }
                 _ => None,
             };
+            let forked_from = match (
+                forked_from_workspace_id.and_then(|id| u64::try_from(id).ok()),
+                forked_from_thread_local_id.and_then(|id| u64::try_from(id).ok()),
+                forked_from_entry_id,
+            ) {
+                (Some(workspace_id), Some(thread_id), Some(entry_id)) => {
+                    Some(luban_domain::ThreadForkOrigin {
+                        workspace_id: luban_domain::WorkspaceId::from_u64(workspace_id),
+                        thread_id: WorkspaceThreadId::from_u64(thread_id),
+                        entry_id,
+                    })
+                }
+                _ => None,
+            };
             threads.push(ConversationThreadMeta {
                 thread_id: WorkspaceThreadId::from_u64(thread_local_id),
                 remote_thread_id,
@@ -3465,6 +4123,7 @@ impl SqliteDatabase {
                 task_status_last_analyzed_message_seq,
                 turn_status,
                 last_turn_result,
+                forked_from,
             });
         }
 
@@ -3947,75 +4606,353 @@ impl SqliteDatabase {
         }
 
         let mut stmt = self.conn.prepare(
-            "SELECT payload_json
-             FROM conversation_queued_prompts
-             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3
-             ORDER BY seq ASC, prompt_id ASC",
+            "SELECT payload_json
+             FROM conversation_queued_prompts
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3
+             ORDER BY seq ASC, prompt_id ASC",
+        )?;
+        let rows = stmt.query_map(
+            params![project_slug, workspace_name, thread_local_id as i64],
+            |row| row.get::<_, String>(0),
+        )?;
+
+        let mut pending_prompts = Vec::new();
+        for row in rows {
+            let json = row?;
+            let prompt: QueuedPrompt =
+                serde_json::from_str(&json).context("failed to parse queued prompt")?;
+            pending_prompts.push(prompt);
+        }
+
+        Ok(ConversationSnapshot {
+            title,
+            thread_id,
+            task_status,
+            runner: agent_runner,
+            agent_model_id: model_id,
+            thinking_effort,
+            amp_mode,
+            entries,
+            entries_total: total_entries,
+            entries_start: start as u64,
+            pending_prompts,
+            queue_paused,
+            run_started_at_unix_ms,
+            run_finished_at_unix_ms,
+        })
+    }
+
+    fn delete_conversation_thread(
+        &mut self,
+        project_slug: &str,
+        workspace_name: &str,
+        thread_local_id: u64,
+    ) -> anyhow::Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM conversation_queued_prompts
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
+            params![project_slug, workspace_name, thread_local_id as i64],
+        )?;
+        tx.execute(
+            "DELETE FROM conversation_entries
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
+            params![project_slug, workspace_name, thread_local_id as i64],
+        )?;
+        tx.execute(
+            "DELETE FROM conversations
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
+            params![project_slug, workspace_name, thread_local_id as i64],
+        )?;
+        tx.execute(
+            "DELETE FROM task_documents
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
+            params![project_slug, workspace_name, thread_local_id as i64],
+        )?;
+        tx.execute(
+            "DELETE FROM task_document_events
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
+            params![project_slug, workspace_name, thread_local_id as i64],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn truncate_conversation_at_checkpoint(
+        &mut self,
+        project_slug: &str,
+        workspace_name: &str,
+        thread_local_id: u64,
+        checkpoint_id: &str,
+    ) -> anyhow::Result<u64> {
+        let tx = self.conn.transaction()?;
+        let (seqs, entries): (Vec<i64>, Vec<ConversationEntry>) = {
+            let mut stmt = tx.prepare(
+                "SELECT seq, payload_json
+                 FROM conversation_entries
+                 WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3
+                 ORDER BY seq ASC",
+            )?;
+            let rows = stmt.query_map(
+                params![project_slug, workspace_name, thread_local_id as i64],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )?;
+            let mut out = Vec::new();
+            for row in rows {
+                let (seq, payload_json) = row?;
+                if let Ok(entry) = serde_json::from_str::<ConversationEntry>(&payload_json) {
+                    out.push((seq, entry));
+                }
+            }
+            out.into_iter().unzip()
+        };
+        let Some(index) = luban_domain::rewind_truncate_index(&entries, checkpoint_id) else {
+            return Err(anyhow!("checkpoint not found in conversation"));
+        };
+        let removed = tx.execute(
+            "DELETE FROM conversation_entries
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3
+               AND seq >= ?4",
+            params![
+                project_slug,
+                workspace_name,
+                thread_local_id as i64,
+                seqs[index]
+            ],
+        )?;
+        // The runner session still remembers the dropped turns; the next turn starts a new one.
+        tx.execute(
+            "UPDATE conversations
+             SET thread_id = NULL, updated_at = ?4
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
+            params![
+                project_slug,
+                workspace_name,
+                thread_local_id as i64,
+                now_unix_seconds()
+            ],
+        )?;
+        tx.commit()?;
+        Ok(removed as u64)
+    }
+
+    fn fork_conversation_thread(
+        &mut self,
+        project_slug: &str,
+        source_workspace_name: &str,
+        workspace_name: &str,
+        thread_local_id: u64,
+        origin: &luban_domain::ThreadForkOrigin,
+    ) -> anyhow::Result<()> {
+        let source_thread_local_id = origin.thread_id.as_u64() as i64;
+        let tx = self.conn.transaction()?;
+        let fork_seq = tx
+            .query_row(
+                "SELECT seq FROM conversation_entries
+                 WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3
+                   AND entry_id = ?4",
+                params![
+                    project_slug,
+                    source_workspace_name,
+                    source_thread_local_id,
+                    origin.entry_id
+                ],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("fork point not found in conversation"))?;
+        let now = now_unix_seconds();
+        let inserted = tx.execute(
+            "INSERT INTO conversations
+             (project_slug, workspace_name, thread_local_id, thread_id, title, created_at, updated_at,
+              agent_model_id, thinking_effort, agent_runner, amp_mode, task_status,
+              forked_from_workspace_id, forked_from_thread_local_id, forked_from_entry_id)
+             SELECT project_slug, ?4, ?5, NULL,
+                    COALESCE(title, 'Thread ' || thread_local_id) || ' (fork)', ?6, ?6,
+                    agent_model_id, thinking_effort, agent_runner, amp_mode,
+                    CASE WHEN task_status IN ('done', 'canceled') THEN 'iterating'
+                         ELSE task_status END,
+                    ?7, thread_local_id, ?8
+             FROM conversations
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3
+               AND deleted_at_unix_ms IS NULL",
+            params![
+                project_slug,
+                source_workspace_name,
+                source_thread_local_id,
+                workspace_name,
+                thread_local_id as i64,
+                now,
+                origin.workspace_id.as_u64() as i64,
+                origin.entry_id
+            ],
+        )?;
+        if inserted == 0 {
+            return Err(anyhow!("conversation thread not found"));
+        }
+        tx.execute(
+            "INSERT INTO conversation_entries
+             (project_slug, workspace_name, thread_local_id, seq, entry_id, kind, codex_item_id, payload_json, created_at)
+             SELECT project_slug, ?4, ?5, seq, entry_id, kind, codex_item_id, payload_json, created_at
+             FROM conversation_entries
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3
+               AND seq <= ?6
+             ORDER BY seq ASC",
+            params![
+                project_slug,
+                source_workspace_name,
+                source_thread_local_id,
+                workspace_name,
+                thread_local_id as i64,
+                fork_seq
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn set_conversation_thread_deleted(
+        &mut self,
+        project_slug: &str,
+        workspace_name: &str,
+        thread_local_id: u64,
+        deleted_at_unix_ms: Option<u64>,
+    ) -> anyhow::Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE conversations
+             SET deleted_at_unix_ms = ?4
+             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
+            params![
+                project_slug,
+                workspace_name,
+                thread_local_id as i64,
+                deleted_at_unix_ms.map(|v| v as i64)
+            ],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!("conversation thread not found"));
+        }
+        Ok(())
+    }
+
+    fn purge_deleted_conversation_threads(
+        &mut self,
+        deleted_before_unix_ms: u64,
+    ) -> anyhow::Result<u64> {
+        let keys = {
+            let mut stmt = self.conn.prepare(
+                "SELECT project_slug, workspace_name, thread_local_id
+                 FROM conversations
+                 WHERE deleted_at_unix_ms IS NOT NULL AND deleted_at_unix_ms <= ?1",
+            )?;
+            let rows = stmt.query_map(params![deleted_before_unix_ms as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })?;
+            let mut out = Vec::new();
+            for row in rows {
+                out.push(row?);
+            }
+            out
+        };
+        for (project_slug, workspace_name, thread_local_id) in &keys {
+            self.delete_conversation_thread(project_slug, workspace_name, *thread_local_id as u64)?;
+        }
+        Ok(keys.len() as u64)
+    }
+
+    fn append_audit_entry(
+        &mut self,
+        origin: luban_domain::AuditOrigin,
+        record: &luban_domain::AuditRecord,
+    ) -> anyhow::Result<luban_domain::AuditEntry> {
+        let created_at_unix_ms = now_unix_millis();
+        let undo_json = record
+            .undo
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("failed to encode audit undo")?;
+        self.conn.execute(
+            "INSERT INTO audit_log
+             (created_at_unix_ms, origin, action, summary, workspace_id, thread_local_id, undo_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                created_at_unix_ms as i64,
+                origin.as_str(),
+                record.action,
+                record.summary,
+                record.workspace_id.map(|v| v as i64),
+                record.thread_id.map(|v| v as i64),
+                undo_json,
+            ],
+        )?;
+        Ok(luban_domain::AuditEntry {
+            id: self.conn.last_insert_rowid() as u64,
+            created_at_unix_ms,
+            origin,
+            action: record.action.clone(),
+            summary: record.summary.clone(),
+            workspace_id: record.workspace_id,
+            thread_id: record.thread_id,
+            undo: record.undo.clone(),
+            undone_at_unix_ms: None,
+        })
+    }
+
+    fn list_audit_entries(
+        &mut self,
+        before_id: Option<u64>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<luban_domain::AuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, created_at_unix_ms, origin, action, summary, workspace_id,
+                    thread_local_id, undo_json, undone_at_unix_ms
+             FROM audit_log
+             WHERE ?1 IS NULL OR id < ?1
+             ORDER BY id DESC
+             LIMIT ?2",
         )?;
         let rows = stmt.query_map(
-            params![project_slug, workspace_name, thread_local_id as i64],
-            |row| row.get::<_, String>(0),
+            params![before_id.map(|v| v as i64), limit as i64],
+            audit_entry_from_row,
         )?;
-
-        let mut pending_prompts = Vec::new();
+        let mut out = Vec::new();
         for row in rows {
-            let json = row?;
-            let prompt: QueuedPrompt =
-                serde_json::from_str(&json).context("failed to parse queued prompt")?;
-            pending_prompts.push(prompt);
+            out.push(row?);
         }
+        Ok(out)
+    }
 
-        Ok(ConversationSnapshot {
-            title,
-            thread_id,
-            task_status,
-            runner: agent_runner,
-            agent_model_id: model_id,
-            thinking_effort,
-            amp_mode,
-            entries,
-            entries_total: total_entries,
-            entries_start: start as u64,
-            pending_prompts,
-            queue_paused,
-            run_started_at_unix_ms,
-            run_finished_at_unix_ms,
-        })
+    fn latest_undoable_audit_entry(&mut self) -> anyhow::Result<Option<luban_domain::AuditEntry>> {
+        self.conn
+            .query_row(
+                "SELECT id, created_at_unix_ms, origin, action, summary, workspace_id,
+                        thread_local_id, undo_json, undone_at_unix_ms
+                 FROM audit_log
+                 WHERE undo_json IS NOT NULL AND undone_at_unix_ms IS NULL
+                 ORDER BY id DESC
+                 LIMIT 1",
+                [],
+                audit_entry_from_row,
+            )
+            .optional()
+            .context("failed to load latest undoable audit entry")
     }
 
-    fn delete_conversation_thread(
+    fn mark_audit_entry_undone(
         &mut self,
-        project_slug: &str,
-        workspace_name: &str,
-        thread_local_id: u64,
+        entry_id: u64,
+        undone_at_unix_ms: u64,
     ) -> anyhow::Result<()> {
-        let tx = self.conn.transaction()?;
-        tx.execute(
-            "DELETE FROM conversation_queued_prompts
-             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
-            params![project_slug, workspace_name, thread_local_id as i64],
-        )?;
-        tx.execute(
-            "DELETE FROM conversation_entries
-             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
-            params![project_slug, workspace_name, thread_local_id as i64],
-        )?;
-        tx.execute(
-            "DELETE FROM conversations
-             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
-            params![project_slug, workspace_name, thread_local_id as i64],
-        )?;
-        tx.execute(
-            "DELETE FROM task_documents
-             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
-            params![project_slug, workspace_name, thread_local_id as i64],
-        )?;
-        tx.execute(
-            "DELETE FROM task_document_events
-             WHERE project_slug = ?1 AND workspace_name = ?2 AND thread_local_id = ?3",
-            params![project_slug, workspace_name, thread_local_id as i64],
+        self.conn.execute(
+            "UPDATE audit_log SET undone_at_unix_ms = ?2
+             WHERE id = ?1 AND undone_at_unix_ms IS NULL",
+            params![entry_id as i64, undone_at_unix_ms as i64],
         )?;
-        tx.commit()?;
         Ok(())
     }
 
@@ -4272,7 +5209,8 @@ impl SqliteDatabase {
                  WHERE project_slug = ?1
                    AND workspace_name = ?2
                    AND task_status = 'validating'
-                   AND task_validation_pr_number = ?3",
+                   AND task_validation_pr_number = ?3
+                   AND deleted_at_unix_ms IS NULL",
             )?;
             let rows = stmt.query_map(
                 params![project_slug, workspace_name, pr_number as i64],
@@ -4477,6 +5415,7 @@ impl SqliteDatabase {
             AttachmentKind::Image => "image",
             AttachmentKind::Text => "text",
             AttachmentKind::File => "file",
+            AttachmentKind::Url => "url",
         };
 
         self.conn.execute(
@@ -4524,6 +5463,7 @@ impl SqliteDatabase {
                 "image" => AttachmentKind::Image,
                 "text" => AttachmentKind::Text,
                 "file" => AttachmentKind::File,
+                "url" => AttachmentKind::Url,
                 _ => AttachmentKind::File,
             };
 
@@ -4562,6 +5502,45 @@ impl SqliteDatabase {
         Ok(())
     }
 
+    fn count_attachment_references(
+        &mut self,
+        scope: Option<(&str, &str)>,
+    ) -> anyhow::Result<HashMap<String, u64>> {
+        let (project_slug, workspace_name) = scope.unwrap_or_default();
+        let scoped = scope.is_some();
+        let mut out = HashMap::new();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT attachment_id FROM context_items
+             WHERE ?1 = 0 OR (project_slug = ?2 AND workspace_name = ?3)",
+        )?;
+        let rows = stmt.query_map(params![scoped, project_slug, workspace_name], |row| {
+            row.get::<_, String>(0)
+        })?;
+        for row in rows {
+            *out.entry(row?).or_insert(0) += 1;
+        }
+
+        for table in ["conversation_entries", "conversation_queued_prompts"] {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT payload_json FROM {table}
+                 WHERE (?1 = 0 OR (project_slug = ?2 AND workspace_name = ?3))
+                   AND instr(payload_json, '\"attachments\":[{{') > 0"
+            ))?;
+            let rows = stmt.query_map(params![scoped, project_slug, workspace_name], |row| {
+                row.get::<_, String>(0)
+            })?;
+            for row in rows {
+                let payload = row?;
+                if let Ok(value) = serde_json::from_str::<serde_json::Value>(&payload) {
+                    count_attachment_ids(&value, &mut out);
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
     fn list_new_task_drafts(&mut self) -> anyhow::Result<Vec<luban_domain::NewTaskDraft>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, text, project_id, workspace_id, created_at_ms, updated_at_ms
@@ -4743,6 +5722,66 @@ impl SqliteDatabase {
     }
 }
 
+fn deleted_project_workspace_keys(raw: Option<&str>) -> HashMap<u64, (String, String)> {
+    raw.and_then(|raw| serde_json::from_str::<Vec<luban_domain::DeletedProject>>(raw).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|project| {
+            let slug = project.slug;
+            project
+                .workspaces
+                .into_iter()
+                .map(move |w| (w.id, (slug.clone(), w.workspace_name)))
+        })
+        .collect()
+}
+
+fn delete_workspace_conversation_data(
+    tx: &rusqlite::Transaction<'_>,
+    project_slug: &str,
+    workspace_name: &str,
+) -> anyhow::Result<()> {
+    tx.execute(
+        "DELETE FROM conversation_entries
+         WHERE project_slug = ?1 AND workspace_name = ?2",
+        params![project_slug, workspace_name],
+    )?;
+    tx.execute(
+        "DELETE FROM conversations
+         WHERE project_slug = ?1 AND workspace_name = ?2",
+        params![project_slug, workspace_name],
+    )?;
+    tx.execute(
+        "DELETE FROM task_documents
+         WHERE project_slug = ?1 AND workspace_name = ?2",
+        params![project_slug, workspace_name],
+    )?;
+    tx.execute(
+        "DELETE FROM task_document_events
+         WHERE project_slug = ?1 AND workspace_name = ?2",
+        params![project_slug, workspace_name],
+    )?;
+    Ok(())
+}
+
+fn audit_entry_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<luban_domain::AuditEntry> {
+    let origin = row.get::<_, String>(2)?;
+    Ok(luban_domain::AuditEntry {
+        id: row.get::<_, i64>(0)? as u64,
+        created_at_unix_ms: row.get::<_, i64>(1)? as u64,
+        origin: luban_domain::AuditOrigin::parse(&origin).unwrap_or_default(),
+        action: row.get(3)?,
+        summary: row.get(4)?,
+        workspace_id: row.get::<_, Option<i64>>(5)?.map(|v| v as u64),
+        thread_id: row.get::<_, Option<i64>>(6)?.map(|v| v as u64),
+        // Entries whose undo data no longer parses are treated as irreversible.
+        undo: row
+            .get::<_, Option<String>>(7)?
+            .and_then(|raw| serde_json::from_str(&raw).ok()),
+        undone_at_unix_ms: row.get::<_, Option<i64>>(8)?.map(|v| v as u64),
+    })
+}
+
 fn hex_lower(bytes: &[u8]) -> String {
     const HEX: &[u8; 16] = b"0123456789abcdef";
     let mut out = String::with_capacity(bytes.len() * 2);
@@ -4848,7 +5887,11 @@ fn migrate_conversation_entries_v17(conn: &mut Connection) -> anyhow::Result<()>
                 ConversationEntry::UserEvent {
                     entry_id: String::new(),
                     created_at_unix_ms: 0,
-                    event: luban_domain::UserEvent::Message { text, attachments },
+                    event: luban_domain::UserEvent::Message {
+                        text,
+                        attachments,
+                        author: None,
+                    },
                 }
             }
             LegacyConversationEntry::CodexItem { item } => match *item {
@@ -5112,6 +6155,53 @@ mod tests {
         assert_eq!(version as u32, LATEST_SCHEMA_VERSION);
     }
 
+    #[test]
+    fn attachment_reference_counts_cover_context_items_and_messages() {
+        let path = temp_db_path("attachment_reference_counts_cover_context_items_and_messages");
+        let mut db = open_db(&path);
+
+        let attachment = |id: &str| AttachmentRef {
+            id: id.to_owned(),
+            kind: AttachmentKind::File,
+            name: format!("{id}.txt"),
+            extension: "txt".to_owned(),
+            mime: None,
+            byte_len: 1,
+        };
+
+        db.insert_context_item("p", "w", &attachment("context"), 0)
+            .unwrap();
+        db.insert_context_item("p", "other", &attachment("message"), 0)
+            .unwrap();
+        db.ensure_conversation("p", "w", 1).unwrap();
+        db.append_conversation_entries(
+            "p",
+            "w",
+            1,
+            &[ConversationEntry::UserEvent {
+                entry_id: String::new(),
+                created_at_unix_ms: 0,
+                event: luban_domain::UserEvent::Message {
+                    text: "see file".to_owned(),
+                    attachments: vec![attachment("message")],
+                    author: None,
+                },
+            }],
+        )
+        .unwrap();
+
+        let scoped = db.count_attachment_references(Some(("p", "w"))).unwrap();
+        assert_eq!(
+            scoped,
+            HashMap::from([("context".to_owned(), 1), ("message".to_owned(), 1)])
+        );
+        let global = db.count_attachment_references(None).unwrap();
+        assert_eq!(
+            global,
+            HashMap::from([("context".to_owned(), 1), ("message".to_owned(), 2)])
+        );
+    }
+
     #[test]
     fn task_status_last_analyzed_tracks_last_message_seq() {
         let path = temp_db_path("task_status_last_analyzed_tracks_last_message_seq");
@@ -5128,6 +6218,7 @@ mod tests {
                 event: luban_domain::UserEvent::Message {
                     text: "hello".to_owned(),
                     attachments: Vec::new(),
+                    author: None,
                 },
             }],
         )
@@ -5243,6 +6334,207 @@ mod tests {
         assert_eq!(ids, vec![1, 2]);
     }
 
+    #[test]
+    fn soft_deleted_threads_are_hidden_until_restored_or_purged() {
+        let path = temp_db_path("soft_deleted_threads_are_hidden_until_restored_or_purged");
+        let mut db = open_db(&path);
+
+        db.ensure_conversation("p", "w", 1).unwrap();
+        db.ensure_conversation("p", "w", 2).unwrap();
+
+        db.set_conversation_thread_deleted("p", "w", 1, Some(1_000))
+            .unwrap();
+        let threads = db.list_conversation_threads("p", "w").unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].thread_id.as_u64(), 2);
+
+        db.set_conversation_thread_deleted("p", "w", 1, None)
+            .unwrap();
+        assert_eq!(db.list_conversation_threads("p", "w").unwrap().len(), 2);
+
+        db.set_conversation_thread_deleted("p", "w", 1, Some(1_000))
+            .unwrap();
+        db.set_conversation_thread_deleted("p", "w", 2, Some(5_000))
+            .unwrap();
+        assert_eq!(db.purge_deleted_conversation_threads(2_000).unwrap(), 1);
+        assert!(db.load_conversation_page("p", "w", 1, None, 10).is_err());
+        db.set_conversation_thread_deleted("p", "w", 2, None)
+            .unwrap();
+        assert_eq!(db.list_conversation_threads("p", "w").unwrap().len(), 1);
+
+        assert!(
+            db.set_conversation_thread_deleted("p", "w", 9, None)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn truncate_at_checkpoint_drops_the_turn_prompt_and_later_entries() {
+        let path = temp_db_path("truncate_at_checkpoint_drops_the_turn_prompt_and_later_entries");
+        let mut db = open_db(&path);
+
+        let prompt = |text: &str| ConversationEntry::UserEvent {
+            entry_id: String::new(),
+            created_at_unix_ms: 0,
+            event: luban_domain::UserEvent::Message {
+                text: text.to_owned(),
+                attachments: Vec::new(),
+                author: None,
+            },
+        };
+        let checkpoint = |id: &str| ConversationEntry::SystemEvent {
+            entry_id: String::new(),
+            created_at_unix_ms: 0,
+            event: luban_domain::ConversationSystemEvent::TurnCheckpoint {
+                checkpoint_id: id.to_owned(),
+                commit: "abc".to_owned(),
+            },
+        };
+        db.append_conversation_entries(
+            "p",
+            "w",
+            1,
+            &[
+                prompt("one"),
+                checkpoint("1-1"),
+                prompt("two"),
+                checkpoint("2-2"),
+                ConversationEntry::AgentEvent {
+                    entry_id: String::new(),
+                    created_at_unix_ms: 0,
+                    runner: None,
+                    event: luban_domain::AgentEvent::TurnDuration { duration_ms: 5 },
+                },
+            ],
+        )
+        .unwrap();
+        db.set_conversation_thread_id("p", "w", 1, "session-1")
+            .unwrap();
+
+        assert!(
+            db.truncate_conversation_at_checkpoint("p", "w", 1, "9-9")
+                .is_err()
+        );
+        assert_eq!(
+            db.truncate_conversation_at_checkpoint("p", "w", 1, "2-2")
+                .unwrap(),
+            3
+        );
+        // The `task_created` event plus the first turn remain.
+        let snapshot = db.load_conversation_page("p", "w", 1, None, 10).unwrap();
+        assert_eq!(snapshot.entries.len(), 3);
+        assert_eq!(snapshot.entries_total, 3);
+        assert_eq!(db.get_conversation_thread_id("p", "w", 1).unwrap(), None);
+    }
+
+    #[test]
+    fn fork_copies_entries_up_to_the_fork_point_without_the_runner_session() {
+        let path =
+            temp_db_path("fork_copies_entries_up_to_the_fork_point_without_the_runner_session");
+        let mut db = open_db(&path);
+
+        let prompt = |text: &str| ConversationEntry::UserEvent {
+            entry_id: String::new(),
+            created_at_unix_ms: 0,
+            event: luban_domain::UserEvent::Message {
+                text: text.to_owned(),
+                attachments: Vec::new(),
+                author: None,
+            },
+        };
+        db.append_conversation_entries(
+            "p",
+            "w",
+            1,
+            &[prompt("one"), prompt("two"), prompt("three")],
+        )
+        .unwrap();
+        db.set_conversation_thread_id("p", "w", 1, "remote-1")
+            .unwrap();
+        let source = db.load_conversation_page("p", "w", 1, None, 10).unwrap();
+        let fork_entry_id = match &source.entries[2] {
+            ConversationEntry::UserEvent { entry_id, .. } => entry_id.clone(),
+            other => panic!("unexpected entry: {other:?}"),
+        };
+        let origin = |entry_id: &str| luban_domain::ThreadForkOrigin {
+            workspace_id: luban_domain::WorkspaceId::from_u64(7),
+            thread_id: WorkspaceThreadId::from_u64(1),
+            entry_id: entry_id.to_owned(),
+        };
+
+        assert!(
+            db.fork_conversation_thread("p", "w", "w2", 1, &origin("missing"))
+                .is_err()
+        );
+        db.fork_conversation_thread("p", "w", "w2", 1, &origin(&fork_entry_id))
+            .unwrap();
+
+        let threads = db.list_conversation_threads("p", "w2").unwrap();
+        assert_eq!(threads.len(), 1);
+        assert!(threads[0].remote_thread_id.is_none());
+        assert!(threads[0].title.ends_with(" (fork)"));
+        assert_eq!(threads[0].forked_from, Some(origin(&fork_entry_id)));
+        // The `task_created` event and the first two prompts.
+        let fork = db.load_conversation_page("p", "w2", 1, None, 10).unwrap();
+        assert_eq!(fork.entries.len(), 3);
+        assert!(
+            db.list_conversation_threads("p", "w").unwrap()[0]
+                .forked_from
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn audit_log_pages_newest_first_and_tracks_undo() {
+        let path = temp_db_path("audit_log_pages_newest_first_and_tracks_undo");
+        let mut db = open_db(&path);
+
+        let record =
+            |action: &str, undo: Option<luban_domain::AuditUndo>| luban_domain::AuditRecord {
+                action: action.to_owned(),
+                summary: format!("did {action}"),
+                workspace_id: Some(3),
+                thread_id: Some(4),
+                undo,
+            };
+        let undo = luban_domain::AuditUndo::SetTaskStarred {
+            workspace_id: 3,
+            thread_id: 4,
+            starred: false,
+        };
+        let first = db
+            .append_audit_entry(
+                luban_domain::AuditOrigin::Web,
+                &record("a", Some(undo.clone())),
+            )
+            .unwrap();
+        let second = db
+            .append_audit_entry(luban_domain::AuditOrigin::Telegram, &record("b", None))
+            .unwrap();
+
+        let entries = db.list_audit_entries(None, 10).unwrap();
+        assert_eq!(
+            entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![second.id, first.id]
+        );
+        assert_eq!(entries[0].origin, luban_domain::AuditOrigin::Telegram);
+        assert_eq!(entries[1].undo, Some(undo));
+        assert_eq!(db.list_audit_entries(Some(second.id), 10).unwrap().len(), 1);
+
+        let latest = db.latest_undoable_audit_entry().unwrap().unwrap();
+        assert_eq!(latest.id, first.id);
+        db.mark_audit_entry_undone(first.id, 42).unwrap();
+        assert!(db.latest_undoable_audit_entry().unwrap().is_none());
+        assert_eq!(
+            db.list_audit_entries(None, 1).unwrap()[0].undone_at_unix_ms,
+            None
+        );
+        assert_eq!(
+            db.list_audit_entries(Some(second.id), 1).unwrap()[0].undone_at_unix_ms,
+            Some(42)
+        );
+    }
+
     #[test]
     fn delete_conversation_thread_removes_conversation_and_entries() {
         let path = temp_db_path("delete_conversation_thread_removes_conversation_and_entries");
@@ -5259,6 +6551,7 @@ mod tests {
                 event: luban_domain::UserEvent::Message {
                     text: "hello".to_owned(),
                     attachments: Vec::new(),
+                    author: None,
                 },
             }],
         )
@@ -5314,6 +6607,7 @@ mod tests {
                 event: luban_domain::UserEvent::Message {
                     text: "Hello world".to_owned(),
                     attachments: Vec::new(),
+                    author: None,
                 },
             }],
         )
@@ -5387,6 +6681,7 @@ mod tests {
                     worktree_path: PathBuf::from("/tmp/p/worktrees/w"),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: None,
@@ -5424,6 +6719,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
 
         db.save_app_state(&snapshot).unwrap();
@@ -5507,6 +6812,7 @@ mod tests {
                     worktree_path: PathBuf::from("/tmp/my-project/worktrees/alpha"),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: Some(280),
@@ -5562,6 +6868,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
 
         db.save_app_state(&snapshot).unwrap();
@@ -5589,6 +6905,7 @@ mod tests {
                     worktree_path: PathBuf::from("/tmp/p/worktrees/w"),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: None,
@@ -5626,6 +6943,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
         db.save_app_state(&snapshot).unwrap();
 
@@ -5712,6 +7039,7 @@ mod tests {
                     thinking_effort: ThinkingEffort::Minimal,
                     amp_mode: None,
                 },
+                author: None,
             },
             QueuedPrompt {
                 id: 7,
@@ -5723,6 +7051,7 @@ mod tests {
                     thinking_effort: ThinkingEffort::Minimal,
                     amp_mode: None,
                 },
+                author: None,
             },
         ];
 
@@ -5851,6 +7180,7 @@ mod tests {
                     worktree_path: PathBuf::from("/tmp/p/worktrees/w"),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: None,
@@ -5888,6 +7218,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
         db.save_app_state(&snapshot).unwrap();
 
@@ -5956,6 +7296,7 @@ mod tests {
                         worktree_path: PathBuf::from("/tmp/p1/worktrees/w1"),
                         status: WorkspaceStatus::Active,
                         last_activity_at_unix_seconds: None,
+                        archived_at_unix_seconds: None,
                     }],
                 },
                 PersistedProject {
@@ -5972,6 +7313,7 @@ mod tests {
                         worktree_path: PathBuf::from("/tmp/p2/worktrees/w"),
                         status: WorkspaceStatus::Active,
                         last_activity_at_unix_seconds: None,
+                        archived_at_unix_seconds: None,
                     }],
                 },
             ],
@@ -6010,6 +7352,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
 
         db.save_app_state(&snapshot_before).unwrap();
@@ -6021,6 +7373,7 @@ mod tests {
             event: luban_domain::UserEvent::Message {
                 text: "hello".to_owned(),
                 attachments: Vec::new(),
+                author: None,
             },
         };
         db.append_conversation_entries("p2", "w", 1, std::slice::from_ref(&entry))
@@ -6042,6 +7395,7 @@ mod tests {
                         worktree_path: PathBuf::from("/tmp/p1/worktrees/w1"),
                         status: WorkspaceStatus::Active,
                         last_activity_at_unix_seconds: None,
+                        archived_at_unix_seconds: None,
                     },
                     PersistedWorkspace {
                         id: 20,
@@ -6050,6 +7404,7 @@ mod tests {
                         worktree_path: PathBuf::from("/tmp/p2/worktrees/w"),
                         status: WorkspaceStatus::Active,
                         last_activity_at_unix_seconds: None,
+                        archived_at_unix_seconds: None,
                     },
                 ],
             }],
@@ -6088,6 +7443,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
 
         db.save_app_state(&snapshot_after).unwrap();
@@ -6124,6 +7489,7 @@ mod tests {
                     worktree_path: PathBuf::from("/tmp/p/worktrees/w"),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: None,
@@ -6161,6 +7527,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
 
         db.save_app_state(&snapshot).unwrap();
@@ -6171,6 +7547,7 @@ mod tests {
             event: luban_domain::UserEvent::Message {
                 text: "hello".to_owned(),
                 attachments: Vec::new(),
+                author: None,
             },
         };
         db.append_conversation_entries("p", "w", 1, std::slice::from_ref(&entry))
@@ -6223,6 +7600,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
         db.save_app_state(&empty).unwrap();
 
@@ -6245,4 +7632,49 @@ mod tests {
         assert_eq!(conversations_count, 0);
         assert_eq!(entries_count, 0);
     }
+
+    #[test]
+    fn save_app_state_keeps_conversations_of_soft_deleted_projects() {
+        let path = temp_db_path("save_app_state_keeps_conversations_of_soft_deleted_projects");
+        let mut db = open_db(&path);
+
+        let mut state = luban_domain::AppState::new();
+        state.apply(luban_domain::Action::AddProject {
+            path: PathBuf::from("/tmp/p"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        state.apply(luban_domain::Action::WorkspaceCreated {
+            project_id,
+            workspace_name: "w".to_owned(),
+            branch_name: "w".to_owned(),
+            worktree_path: PathBuf::from("/tmp/p/worktrees/w"),
+        });
+        let slug = state.projects[0].slug.clone();
+        db.save_app_state(&state.to_persisted()).unwrap();
+        db.ensure_conversation(&slug, "w", 1).unwrap();
+
+        let count_conversations = |db: &mut SqliteDatabase| -> i64 {
+            db.conn
+                .query_row(
+                    "SELECT COUNT(*) FROM conversations WHERE project_slug = ?1 AND workspace_name = 'w'",
+                    params![slug],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+
+        state.apply(luban_domain::Action::DeleteProject { project_id });
+        db.save_app_state(&state.to_persisted()).unwrap();
+        assert_eq!(count_conversations(&mut db), 1);
+        let loaded = db.load_app_state().unwrap();
+        assert!(loaded.projects.is_empty());
+        assert!(loaded.deleted_projects.is_some());
+
+        state.apply(luban_domain::Action::DeletedProjectsPurged {
+            before_unix_ms: u64::MAX,
+        });
+        db.save_app_state(&state.to_persisted()).unwrap();
+        assert_eq!(count_conversations(&mut db), 0);
+    }
 }
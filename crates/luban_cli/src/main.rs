@@ -23,6 +23,17 @@ enum Command {
         #[arg(long, default_value_t = false)]
         no_open: bool,
     },
+    /// Serve Luban tasks to other agents over the Model Context Protocol (stdio).
+    Mcp {
+        /// Forward requests to a running Luban server (for example `http://127.0.0.1:8421`)
+        /// instead of starting an in-process engine.
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Session token for servers running with single-user auth.
+        #[arg(long, requires = "url")]
+        token: Option<String>,
+    },
 }
 
 fn random_hex(bytes: usize) -> String {
@@ -50,13 +61,19 @@ async fn main() -> anyhow::Result<()> {
         eprintln!("warning: failed to apply shell environment defaults: {err:#}");
     }
 
+    // stdout carries the MCP stream, so logs always go to stderr.
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
         .init();
 
     let cli = Cli::parse();
     match cli.cmd {
         Command::Ui { addr, no_open } => ui(addr, no_open).await,
+        Command::Mcp { url, token } => match url {
+            Some(url) => luban_server::mcp::run_stdio_proxy(&url, token.as_deref()).await,
+            None => luban_server::mcp::run_stdio().await,
+        },
     }
 }
 
@@ -1,10 +1,12 @@
 use crate::{
-    AgentRunnerKind, AgentThreadEvent, AppearanceTheme, AttachmentRef, ChatScrollAnchor,
-    ContextTokenKind, ConversationSnapshot, ConversationThreadMeta, OpenTarget, PersistedAppState,
-    ProjectId, SystemTaskKind, TaskIntentKind, TaskStatus, ThinkingEffort, WorkspaceId,
-    WorkspaceThreadId,
+    AgentRunnerKind, AgentThreadEvent, AppearanceTheme, AttachmentRef, AttachmentTextLimits,
+    ChatScrollAnchor, CiAutoFixStopReason, ContextTokenKind, ConversationSnapshot,
+    ConversationThreadMeta, IssueMergeAction, ModelCatalog, OpenTarget, PersistedAppState,
+    ProjectId, ProjectPromptSettings, PullRequestReviewComment, PullRequestReviewSync, QuietHours,
+    SystemTaskKind, TaskIntentKind, TaskIssueLink, TaskStatus, TelegramGroupMember,
+    TelegramTopicBinding, ThinkingEffort, TurnLimits, WorkspaceId, WorkspaceThreadId,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
@@ -27,9 +29,24 @@ pub enum Action {
     DeleteProject {
         project_id: ProjectId,
     },
+    /// Brings a soft-deleted project back with its original ids.
+    ProjectRestored {
+        project_id: ProjectId,
+    },
+    /// Drops soft-deleted projects deleted at or before `before_unix_ms`.
+    DeletedProjectsPurged {
+        before_unix_ms: u64,
+    },
+    DeletionRetentionChanged {
+        retention_days: u32,
+    },
     OpenProjectSettings {
         project_id: ProjectId,
     },
+    ProjectTurnLimitsChanged {
+        project_id: ProjectId,
+        limits: TurnLimits,
+    },
 
     CreateWorkspace {
         project_id: ProjectId,
@@ -140,6 +157,7 @@ pub enum Action {
         thread_id: WorkspaceThreadId,
         text: String,
         attachments: Vec<AttachmentRef>,
+        author: Option<String>,
         runner: Option<AgentRunnerKind>,
         amp_mode: Option<String>,
     },
@@ -239,6 +257,29 @@ pub enum Action {
         run_id: u64,
         started_at_unix_ms: u64,
     },
+    TurnCheckpointCreated {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        checkpoint_id: String,
+        commit: String,
+    },
+    TurnChangesRecorded {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        checkpoint_id: String,
+        commit: String,
+        files_changed: u64,
+        additions: u64,
+        deletions: u64,
+    },
+    /// The worktree was restored to a turn checkpoint. `snapshot` is the reloaded
+    /// conversation when entries after the checkpoint were dropped.
+    TaskRewound {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        checkpoint_id: String,
+        snapshot: Option<ConversationSnapshot>,
+    },
     AgentRunFinishedAt {
         workspace_id: WorkspaceId,
         thread_id: WorkspaceThreadId,
@@ -254,6 +295,85 @@ pub enum Action {
         workspace_id: WorkspaceId,
         thread_id: WorkspaceThreadId,
     },
+    /// The wall-clock timer armed for `run_id` ran out after `timeout_secs`.
+    AgentTurnTimedOut {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        run_id: u64,
+        timeout_secs: u64,
+    },
+    TaskTurnLimitsChanged {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        limits: TurnLimits,
+    },
+    AddReviewComment {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        path: String,
+        start_line: u32,
+        end_line: u32,
+        quote: String,
+        body: String,
+    },
+    EditReviewComment {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        comment_id: u64,
+        body: String,
+    },
+    ResolveReviewComment {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        comment_id: u64,
+        resolved: bool,
+    },
+    /// The comments went out to the agent in a review turn.
+    ReviewCommentsSent {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        comment_ids: Vec<u64>,
+    },
+    /// Unresolved comments fetched from the workdir's pull request; ones already imported
+    /// into the task are skipped.
+    PullRequestReviewCommentsFetched {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        pr_number: u64,
+        comments: Vec<PullRequestReviewComment>,
+    },
+    PullRequestReviewSyncChanged {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        sync: PullRequestReviewSync,
+    },
+    /// A reply marking the imported comment as addressed was posted on the pull request.
+    ReviewCommentReplyPosted {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        comment_id: u64,
+    },
+    /// Turns the CI auto-fix loop on or off; turning it on starts a fresh attempt budget.
+    CiAutoFixChanged {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        enabled: bool,
+        max_attempts: u32,
+    },
+    /// A "fix CI" turn is about to be queued for the failed run.
+    CiFixAttemptStarted {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        pr_number: u64,
+        run_id: u64,
+        workflow: String,
+        url: String,
+    },
+    CiAutoFixStopped {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        reason: CiAutoFixStopReason,
+    },
 
     CreateWorkspaceThread {
         workspace_id: WorkspaceId,
@@ -264,6 +384,23 @@ pub enum Action {
         workspace_id: WorkspaceId,
         thread_id: WorkspaceThreadId,
     },
+    /// Starts a new task in `target_workspace_id` from the conversation of `thread_id` up to
+    /// and including `entry_id`.
+    ForkWorkspaceThread {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        entry_id: String,
+        target_workspace_id: WorkspaceId,
+    },
+    WorkspaceThreadForked {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    },
+    WorkspaceThreadForkFailed {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        message: String,
+    },
     CloseWorkspaceThreadTab {
         workspace_id: WorkspaceId,
         thread_id: WorkspaceThreadId,
@@ -357,14 +494,81 @@ pub enum Action {
         message_thread_id: i64,
     },
     TelegramTopicBindingsCleared,
+    TelegramGroupPaired {
+        chat_id: i64,
+        project_slug: String,
+        title: String,
+        member: TelegramGroupMember,
+    },
+    TelegramGroupUnpaired {
+        chat_id: i64,
+    },
+    TelegramGroupMemberAdded {
+        chat_id: i64,
+        member: TelegramGroupMember,
+    },
+    TelegramGroupMemberRemoved {
+        chat_id: i64,
+        user_id: i64,
+    },
+    TelegramGroupTopicBound {
+        chat_id: i64,
+        binding: TelegramTopicBinding,
+    },
+    TelegramGroupTopicUnbound {
+        chat_id: i64,
+        message_thread_id: i64,
+    },
+    DesktopNotificationsEnabledChanged {
+        enabled: bool,
+    },
+    NotificationQuietHoursChanged {
+        quiet_hours: Option<QuietHours>,
+    },
+    ProjectNotificationsMutedChanged {
+        project_id: ProjectId,
+        muted: bool,
+    },
+    AttachmentTextLimitsChanged {
+        limits: AttachmentTextLimits,
+    },
+    UrlAttachmentDomainsChanged {
+        domains: Vec<String>,
+    },
+    TaskIssueLinked {
+        link: TaskIssueLink,
+    },
+    TaskIssueMergeActionChanged {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        on_merge: IssueMergeAction,
+    },
+    /// The linked issue was closed or commented on after `pr_number` merged.
+    TaskIssueResolved {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        pr_number: u64,
+    },
     CodexDefaultsLoaded {
         model_id: Option<String>,
         thinking_effort: Option<ThinkingEffort>,
     },
+    ModelCatalogLoaded {
+        catalog: ModelCatalog,
+    },
     TaskPromptTemplateChanged {
+        project_id: Option<ProjectId>,
         intent_kind: TaskIntentKind,
         template: String,
     },
+    ProjectPromptVariablesChanged {
+        project_id: ProjectId,
+        variables: BTreeMap<String, String>,
+    },
+    ProjectPromptSettingsLoaded {
+        project_id: ProjectId,
+        settings: ProjectPromptSettings,
+    },
     TaskPromptTemplatesLoaded {
         templates: HashMap<TaskIntentKind, String>,
     },
@@ -396,6 +600,13 @@ pub enum Action {
         thread_id: WorkspaceThreadId,
         task_status: TaskStatus,
     },
+    /// Puts a task back into an open status, reopening it when it was Done or Canceled.
+    /// Used to undo status changes; closing a task always goes through `TaskStatusSet`.
+    TaskStatusRestored {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        task_status: TaskStatus,
+    },
     TaskStatusSuggestionCreated {
         workspace_id: WorkspaceId,
         thread_id: WorkspaceThreadId,
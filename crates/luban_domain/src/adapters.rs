@@ -1,9 +1,11 @@
 use crate::{
-    AgentRunnerKind, AgentThreadEvent, AttachmentRef, ContextItem, ConversationEntry,
-    ConversationSnapshot, ConversationThreadMeta, PersistedAppState, QueuedPrompt, SystemTaskKind,
-    TaskStatus, ThinkingEffort,
+    AgentRunnerKind, AgentThreadEvent, AttachmentRef, AttachmentTextLimits, AuditEntry,
+    AuditOrigin, AuditRecord, CiFailureLog, ContextItem, ConversationEntry, ConversationSnapshot,
+    ConversationThreadMeta, ImportedIssue, IssueMergeAction, ModelCatalog, PersistedAppState,
+    ProjectPromptSettings, PullRequestReviewComment, QueuedPrompt, SystemTaskKind, TaskStatus,
+    ThinkingEffort, ThreadForkOrigin, UrlAttachmentSettings,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::{path::PathBuf, sync::Arc, sync::atomic::AtomicBool};
 
 #[derive(Clone, Debug)]
@@ -61,6 +63,9 @@ pub struct RunAgentTurnRequest {
     pub amp_mode: Option<String>,
     pub model: Option<String>,
     pub model_reasoning_effort: Option<String>,
+    pub attachment_text_limits: AttachmentTextLimits,
+    /// Recorded in the conversation right after the prompt when set.
+    pub checkpoint: Option<crate::TurnCheckpoint>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
@@ -348,6 +353,41 @@ pub struct TaskDocumentEvent {
     pub created_at_unix_ms: u64,
 }
 
+/// Bytes a workspace occupies on disk.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WorkspaceDiskUsage {
+    pub worktree_bytes: u64,
+    pub context_blobs_bytes: u64,
+}
+
+/// The worktree of an archived workdir and the repository that owns it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchivedWorktree {
+    pub project_path: PathBuf,
+    pub worktree_path: PathBuf,
+}
+
+/// Model catalog produced at startup, plus problems with optional sources that were skipped.
+#[derive(Clone, Debug)]
+pub struct LoadedModelCatalog {
+    pub catalog: ModelCatalog,
+    pub warnings: Vec<String>,
+}
+
+/// Outcome of a disk cleanup pass.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DiskCleanupReport {
+    pub removed_entries: u64,
+    pub freed_bytes: u64,
+}
+
+impl DiskCleanupReport {
+    pub fn merge(&mut self, other: DiskCleanupReport) {
+        self.removed_entries = self.removed_entries.saturating_add(other.removed_entries);
+        self.freed_bytes = self.freed_bytes.saturating_add(other.freed_bytes);
+    }
+}
+
 pub trait ProjectWorkspaceService: Send + Sync {
     fn load_app_state(&self) -> Result<PersistedAppState, String>;
 
@@ -360,6 +400,18 @@ pub trait ProjectWorkspaceService: Send + Sync {
         branch_name_hint: Option<String>,
     ) -> Result<CreatedWorkspace, String>;
 
+    /// Like `create_workspace`, but branches off the commit `source_worktree_path` has checked
+    /// out instead of the upstream default branch.
+    fn create_workspace_from_worktree(
+        &self,
+        _project_path: PathBuf,
+        _project_slug: String,
+        _source_worktree_path: PathBuf,
+        _branch_name_hint: Option<String>,
+    ) -> Result<CreatedWorkspace, String> {
+        Err("unimplemented".to_owned())
+    }
+
     fn open_workspace_in_ide(&self, worktree_path: PathBuf) -> Result<(), String>;
 
     fn open_workspace_with(
@@ -439,6 +491,81 @@ pub trait ProjectWorkspaceService: Send + Sync {
         Err("unimplemented".to_owned())
     }
 
+    /// Marks a thread as soft-deleted (`Some`) or restores it (`None`). Soft-deleted threads
+    /// are hidden from listings until purged.
+    fn set_conversation_thread_deleted(
+        &self,
+        _project_slug: String,
+        _workspace_name: String,
+        _thread_id: u64,
+        _deleted_at_unix_ms: Option<u64>,
+    ) -> Result<(), String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Permanently deletes threads soft-deleted at or before the cutoff. Returns how many.
+    fn purge_deleted_conversation_threads(
+        &self,
+        _deleted_before_unix_ms: u64,
+    ) -> Result<u64, String> {
+        Ok(0)
+    }
+
+    /// Creates thread `thread_id` in `workspace_name` holding the entries of the origin thread
+    /// (in `source_workspace_name`) up to and including `origin.entry_id`, with the origin's
+    /// run config and no runner session.
+    fn fork_conversation_thread(
+        &self,
+        _project_slug: String,
+        _source_workspace_name: String,
+        _workspace_name: String,
+        _thread_id: u64,
+        _origin: ThreadForkOrigin,
+    ) -> Result<(), String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Drops the entries of the turn that starts at `checkpoint_id`, and everything after it.
+    /// Returns how many entries were removed.
+    fn truncate_conversation_at_checkpoint(
+        &self,
+        _project_slug: String,
+        _workspace_name: String,
+        _thread_id: u64,
+        _checkpoint_id: String,
+    ) -> Result<u64, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    fn append_audit_entry(
+        &self,
+        _origin: AuditOrigin,
+        _record: AuditRecord,
+    ) -> Result<AuditEntry, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Newest entries first, optionally only those older than `before_id`.
+    fn list_audit_entries(
+        &self,
+        _before_id: Option<u64>,
+        _limit: u64,
+    ) -> Result<Vec<AuditEntry>, String> {
+        Ok(Vec::new())
+    }
+
+    fn latest_undoable_audit_entry(&self) -> Result<Option<AuditEntry>, String> {
+        Ok(None)
+    }
+
+    fn mark_audit_entry_undone(
+        &self,
+        _entry_id: u64,
+        _undone_at_unix_ms: u64,
+    ) -> Result<(), String> {
+        Err("unimplemented".to_owned())
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn save_conversation_queue_state(
         &self,
@@ -528,6 +655,17 @@ pub trait ProjectWorkspaceService: Send + Sync {
         source_path: PathBuf,
     ) -> Result<AttachmentRef, String>;
 
+    /// Fetches `url` once and stores a Markdown snapshot of the page as a context blob.
+    fn store_context_url(
+        &self,
+        _project_slug: String,
+        _workspace_name: String,
+        _url: String,
+        _settings: UrlAttachmentSettings,
+    ) -> Result<AttachmentRef, String> {
+        Err("unimplemented".to_owned())
+    }
+
     fn record_context_item(
         &self,
         project_slug: String,
@@ -653,6 +791,79 @@ pub trait ProjectWorkspaceService: Send + Sync {
         // Default: no-op
     }
 
+    /// Snapshots the worktree, including untracked files that are not ignored, into a commit
+    /// pinned by `ref_name`. Leaves the index, `HEAD` and the checked-out branch untouched.
+    /// Returns the commit id.
+    fn create_turn_checkpoint(
+        &self,
+        _worktree_path: PathBuf,
+        _ref_name: String,
+    ) -> Result<String, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Writes the current worktree contents as a tree object, the same way a checkpoint does,
+    /// without pinning it. Returns the tree id.
+    fn snapshot_worktree_tree(&self, _worktree_path: PathBuf) -> Result<String, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Restores the worktree files to the snapshot pinned by `ref_name`: changed and deleted
+    /// files are rewritten and files created since are removed. Ignored files, the index and
+    /// `HEAD` are left as they are.
+    fn restore_turn_checkpoint(
+        &self,
+        _worktree_path: PathBuf,
+        _ref_name: String,
+    ) -> Result<(), String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Deletes every checkpoint ref starting with `ref_prefix` in the repository at `repo_path`.
+    fn delete_turn_checkpoints(
+        &self,
+        _repo_path: PathBuf,
+        _ref_prefix: String,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Measures the worktree and the context blob directory of a workspace.
+    fn workspace_disk_usage(
+        &self,
+        _project_slug: String,
+        _workspace_name: String,
+        _worktree_path: PathBuf,
+    ) -> Result<WorkspaceDiskUsage, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Deletes entries under `worktree_path` whose relative path matches one of `globs`.
+    fn purge_build_artifacts(
+        &self,
+        _worktree_path: PathBuf,
+        _globs: Vec<String>,
+    ) -> Result<DiskCleanupReport, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Removes the worktrees of `archived` workdirs that are still on disk and prunes their
+    /// repositories. Paths outside the worktrees root or resolving to a path in `keep` are left
+    /// alone.
+    fn delete_stale_worktrees(
+        &self,
+        _archived: Vec<ArchivedWorktree>,
+        _keep: Vec<PathBuf>,
+    ) -> Result<DiskCleanupReport, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Deletes shared context blobs that no context item, message or queued prompt references
+    /// in any workspace.
+    fn gc_context_blobs(&self) -> Result<DiskCleanupReport, String> {
+        Err("unimplemented".to_owned())
+    }
+
     fn gh_is_authorized(&self) -> Result<bool, String>;
 
     fn gh_pull_request_info(
@@ -660,6 +871,10 @@ pub trait ProjectWorkspaceService: Send + Sync {
         worktree_path: PathBuf,
     ) -> Result<Option<PullRequestInfo>, String>;
 
+    fn gh_pull_request_url(&self, _worktree_path: PathBuf) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
     fn gh_open_pull_request(&self, worktree_path: PathBuf) -> Result<(), String>;
 
     fn gh_open_pull_request_failed_action(&self, worktree_path: PathBuf) -> Result<(), String>;
@@ -681,6 +896,64 @@ pub trait ProjectWorkspaceService: Send + Sync {
         Err("unimplemented".to_owned())
     }
 
+    /// Fetches an issue from the project's forge by number (`42`, `#42`) or issue URL.
+    fn issue_import(
+        &self,
+        _project_path: PathBuf,
+        _issue_ref: String,
+    ) -> Result<ImportedIssue, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Closes or comments on an imported issue after the task's pull request merged.
+    fn issue_resolve_on_merge(
+        &self,
+        _issue_url: String,
+        _action: IssueMergeAction,
+        _pr_number: u64,
+    ) -> Result<(), String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Fetches the comments in the pull request's unresolved review threads.
+    fn pull_request_review_comments(
+        &self,
+        _worktree_path: PathBuf,
+        _pr_number: u64,
+    ) -> Result<Vec<PullRequestReviewComment>, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Replies to a pull request review comment.
+    fn pull_request_review_comment_reply(
+        &self,
+        _worktree_path: PathBuf,
+        _pr_number: u64,
+        _comment_id: u64,
+        _body: String,
+    ) -> Result<(), String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Fetches the trimmed failed job logs of the pull request's newest failed CI run on its
+    /// head commit, or `None` when nothing failed there.
+    fn ci_failure_log(
+        &self,
+        _worktree_path: PathBuf,
+        _pr_number: u64,
+    ) -> Result<Option<CiFailureLog>, String> {
+        Err("unimplemented".to_owned())
+    }
+
+    /// Loads the model catalog: built-in models merged with runner discovery and the user's
+    /// models file.
+    fn model_catalog_load(&self) -> Result<LoadedModelCatalog, String> {
+        Ok(LoadedModelCatalog {
+            catalog: ModelCatalog::builtin(),
+            warnings: Vec::new(),
+        })
+    }
+
     fn task_prompt_templates_load(&self) -> Result<HashMap<TaskIntentKind, String>, String> {
         Ok(HashMap::new())
     }
@@ -713,6 +986,38 @@ pub trait ProjectWorkspaceService: Send + Sync {
         Ok(())
     }
 
+    fn project_prompt_settings_load(
+        &self,
+        _project_slug: String,
+    ) -> Result<ProjectPromptSettings, String> {
+        Ok(ProjectPromptSettings::default())
+    }
+
+    fn project_task_prompt_template_store(
+        &self,
+        _project_slug: String,
+        _intent_kind: TaskIntentKind,
+        _template: String,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn project_task_prompt_template_delete(
+        &self,
+        _project_slug: String,
+        _intent_kind: TaskIntentKind,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn project_prompt_variables_store(
+        &self,
+        _project_slug: String,
+        _variables: BTreeMap<String, String>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
     fn task_suggest_branch_name(
         &self,
         _input: String,
@@ -0,0 +1,386 @@
+use crate::{Action, AppState, ProjectId, TaskStatus, WorkspaceId, WorkspaceThreadId};
+
+/// Where a state-changing action came from.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOrigin {
+    Web,
+    Telegram,
+    Slack,
+    Cli,
+    /// Tool calls made by agents through the MCP server.
+    Mcp,
+    /// Background work inside the server, e.g. the merge watcher or maintenance ticks.
+    #[default]
+    System,
+}
+
+impl AuditOrigin {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditOrigin::Web => "web",
+            AuditOrigin::Telegram => "telegram",
+            AuditOrigin::Slack => "slack",
+            AuditOrigin::Cli => "cli",
+            AuditOrigin::Mcp => "mcp",
+            AuditOrigin::System => "system",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "web" => Some(AuditOrigin::Web),
+            "telegram" => Some(AuditOrigin::Telegram),
+            "slack" => Some(AuditOrigin::Slack),
+            "cli" => Some(AuditOrigin::Cli),
+            "mcp" => Some(AuditOrigin::Mcp),
+            "system" => Some(AuditOrigin::System),
+            _ => None,
+        }
+    }
+}
+
+/// How to reverse an audited action. Stored as JSON next to the entry.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditUndo {
+    RestoreProject {
+        project_id: u64,
+    },
+    RestoreThread {
+        workspace_id: u64,
+        thread_id: u64,
+    },
+    RestoreTaskStatus {
+        workspace_id: u64,
+        thread_id: u64,
+        task_status: TaskStatus,
+    },
+    SetTaskStarred {
+        workspace_id: u64,
+        thread_id: u64,
+        starred: bool,
+    },
+    /// Reverts one bulk status change as a whole.
+    RestoreTaskStatuses {
+        batch_id: String,
+        tasks: Vec<AuditTaskStatus>,
+    },
+}
+
+/// A task and the status it had before a grouped change.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AuditTaskStatus {
+    pub workspace_id: u64,
+    pub thread_id: u64,
+    pub task_status: TaskStatus,
+}
+
+impl AuditUndo {
+    /// Soft-deleted data is only reversible while it is still retained.
+    pub fn needs_retention(&self) -> bool {
+        matches!(
+            self,
+            AuditUndo::RestoreProject { .. } | AuditUndo::RestoreThread { .. }
+        )
+    }
+}
+
+/// What gets written to the audit log for one applied action.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditRecord {
+    /// Stable action name, e.g. `delete_project`.
+    pub action: String,
+    pub summary: String,
+    pub workspace_id: Option<u64>,
+    pub thread_id: Option<u64>,
+    pub undo: Option<AuditUndo>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub created_at_unix_ms: u64,
+    pub origin: AuditOrigin,
+    pub action: String,
+    pub summary: String,
+    pub workspace_id: Option<u64>,
+    pub thread_id: Option<u64>,
+    pub undo: Option<AuditUndo>,
+    pub undone_at_unix_ms: Option<u64>,
+}
+
+impl AuditRecord {
+    pub fn thread_deleted(
+        state: &AppState,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        reversible: bool,
+    ) -> Self {
+        Self {
+            action: "delete_thread".to_owned(),
+            summary: format!("Deleted {}", task_label(state, workspace_id, thread_id)),
+            workspace_id: Some(workspace_id.as_u64()),
+            thread_id: Some(thread_id.as_u64()),
+            undo: reversible.then_some(AuditUndo::RestoreThread {
+                workspace_id: workspace_id.as_u64(),
+                thread_id: thread_id.as_u64(),
+            }),
+        }
+    }
+
+    pub fn bulk_task_status(
+        batch_id: String,
+        task_status: TaskStatus,
+        tasks: Vec<AuditTaskStatus>,
+    ) -> Self {
+        let count = tasks.len();
+        Self {
+            action: "bulk_set_task_status".to_owned(),
+            summary: format!(
+                "Moved {count} task{} to {}",
+                if count == 1 { "" } else { "s" },
+                task_status.as_str()
+            ),
+            workspace_id: None,
+            thread_id: None,
+            undo: Some(AuditUndo::RestoreTaskStatuses { batch_id, tasks }),
+        }
+    }
+
+    pub fn undone(entry: &AuditEntry) -> Self {
+        Self {
+            action: "undo".to_owned(),
+            summary: format!("Undid: {}", entry.summary),
+            workspace_id: entry.workspace_id,
+            thread_id: entry.thread_id,
+            undo: None,
+        }
+    }
+}
+
+/// Describes `action` for the audit log, or `None` when it is not a user-facing state
+/// change worth recording. Must be called before the action is applied so the summary and
+/// undo data reflect the state being changed.
+pub fn audit_record_for_action(state: &AppState, action: &Action) -> Option<AuditRecord> {
+    match action {
+        Action::AddProject { path, .. } => Some(AuditRecord {
+            action: "add_project".to_owned(),
+            summary: format!("Added project {}", path.display()),
+            workspace_id: None,
+            thread_id: None,
+            undo: None,
+        }),
+        Action::DeleteProject { project_id } => {
+            let project = state.project(*project_id)?;
+            Some(AuditRecord {
+                action: "delete_project".to_owned(),
+                summary: format!("Deleted project {}", project.name),
+                workspace_id: None,
+                thread_id: None,
+                undo: state.deletion_settings().soft_delete_enabled().then_some(
+                    AuditUndo::RestoreProject {
+                        project_id: project_id.0,
+                    },
+                ),
+            })
+        }
+        Action::ProjectRestored { project_id } => {
+            let deleted = state
+                .deleted_projects()
+                .iter()
+                .find(|p| p.project_id() == *project_id)?;
+            Some(AuditRecord {
+                action: "restore_project".to_owned(),
+                summary: format!("Restored project {}", deleted.name),
+                workspace_id: None,
+                thread_id: None,
+                undo: None,
+            })
+        }
+        Action::CreateWorkspace { project_id, .. } => Some(AuditRecord {
+            action: "create_workdir".to_owned(),
+            summary: format!("Created a workdir in {}", project_label(state, *project_id)),
+            workspace_id: None,
+            thread_id: None,
+            undo: None,
+        }),
+        Action::ArchiveWorkspace { workspace_id } => {
+            let workspace = state.workspace(*workspace_id)?;
+            Some(AuditRecord {
+                action: "archive_workdir".to_owned(),
+                summary: format!("Archived workdir {}", workspace.workspace_name),
+                workspace_id: Some(workspace_id.as_u64()),
+                thread_id: None,
+                undo: None,
+            })
+        }
+        Action::CreateWorkspaceThread { workspace_id, .. } => {
+            let workspace = state.workspace(*workspace_id)?;
+            Some(AuditRecord {
+                action: "create_task".to_owned(),
+                summary: format!("Created a task in {}", workspace.workspace_name),
+                workspace_id: Some(workspace_id.as_u64()),
+                thread_id: None,
+                undo: None,
+            })
+        }
+        Action::TaskStatusSet {
+            workspace_id,
+            thread_id,
+            task_status,
+        } => {
+            let previous = state
+                .workspace_thread_conversation(*workspace_id, *thread_id)?
+                .task_status;
+            // Mirrors the reducer: closed tasks only reopen through `TaskStatusRestored`.
+            if previous == *task_status
+                || matches!(previous, TaskStatus::Done | TaskStatus::Canceled)
+            {
+                return None;
+            }
+            Some(AuditRecord {
+                action: "set_task_status".to_owned(),
+                summary: format!(
+                    "Moved {} from {} to {}",
+                    task_label(state, *workspace_id, *thread_id),
+                    previous.as_str(),
+                    task_status.as_str()
+                ),
+                workspace_id: Some(workspace_id.as_u64()),
+                thread_id: Some(thread_id.as_u64()),
+                undo: Some(AuditUndo::RestoreTaskStatus {
+                    workspace_id: workspace_id.as_u64(),
+                    thread_id: thread_id.as_u64(),
+                    task_status: previous,
+                }),
+            })
+        }
+        Action::TaskStarSet {
+            workspace_id,
+            thread_id,
+            starred,
+        } => {
+            let was_starred = state.starred_tasks.contains(&(*workspace_id, *thread_id));
+            if was_starred == *starred {
+                return None;
+            }
+            Some(AuditRecord {
+                action: "set_task_starred".to_owned(),
+                summary: format!(
+                    "{} {}",
+                    if *starred { "Starred" } else { "Unstarred" },
+                    task_label(state, *workspace_id, *thread_id)
+                ),
+                workspace_id: Some(workspace_id.as_u64()),
+                thread_id: Some(thread_id.as_u64()),
+                undo: Some(AuditUndo::SetTaskStarred {
+                    workspace_id: workspace_id.as_u64(),
+                    thread_id: thread_id.as_u64(),
+                    starred: was_starred,
+                }),
+            })
+        }
+        Action::TaskRewound {
+            workspace_id,
+            thread_id,
+            snapshot,
+            ..
+        } => Some(AuditRecord {
+            action: "rewind_task".to_owned(),
+            summary: format!(
+                "Rewound {} to an earlier turn{}",
+                task_label(state, *workspace_id, *thread_id),
+                if snapshot.is_some() {
+                    " and dropped the later conversation"
+                } else {
+                    ""
+                }
+            ),
+            workspace_id: Some(workspace_id.as_u64()),
+            thread_id: Some(thread_id.as_u64()),
+            undo: None,
+        }),
+        Action::DeletionRetentionChanged { retention_days } => Some(AuditRecord {
+            action: "set_deletion_retention".to_owned(),
+            summary: format!("Set deletion retention to {retention_days} days"),
+            workspace_id: None,
+            thread_id: None,
+            undo: None,
+        }),
+        _ => None,
+    }
+}
+
+fn project_label(state: &AppState, project_id: ProjectId) -> String {
+    state
+        .project(project_id)
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| "a project".to_owned())
+}
+
+fn task_label(state: &AppState, workspace_id: WorkspaceId, thread_id: WorkspaceThreadId) -> String {
+    match state
+        .workspace_thread_conversation(workspace_id, thread_id)
+        .map(|c| c.title.trim())
+        .filter(|title| !title.is_empty())
+    {
+        Some(title) => format!("task \"{title}\""),
+        None => format!("task #{}", thread_id.as_u64()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn delete_project_is_reversible_only_with_retention() {
+        let mut state = AppState::new();
+        state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/repo"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        let action = Action::DeleteProject { project_id };
+
+        let record = audit_record_for_action(&state, &action).expect("record");
+        assert_eq!(record.action, "delete_project");
+        assert_eq!(
+            record.undo,
+            Some(AuditUndo::RestoreProject {
+                project_id: project_id.0
+            })
+        );
+
+        state.apply(Action::DeletionRetentionChanged { retention_days: 0 });
+        let record = audit_record_for_action(&state, &action).expect("record");
+        assert_eq!(record.undo, None);
+
+        assert_eq!(
+            audit_record_for_action(&state, &Action::ToggleProjectExpanded { project_id }),
+            None
+        );
+    }
+
+    #[test]
+    fn undo_serializes_with_type_tag() {
+        let undo = AuditUndo::RestoreTaskStatus {
+            workspace_id: 1,
+            thread_id: 2,
+            task_status: TaskStatus::Iterating,
+        };
+        let json = serde_json::to_string(&undo).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"restore_task_status","workspace_id":1,"thread_id":2,"task_status":"iterating"}"#
+        );
+        assert_eq!(serde_json::from_str::<AuditUndo>(&json).unwrap(), undo);
+        assert_eq!(AuditOrigin::parse("telegram"), Some(AuditOrigin::Telegram));
+        assert_eq!(
+            AuditOrigin::parse(AuditOrigin::Mcp.as_str()),
+            Some(AuditOrigin::Mcp)
+        );
+    }
+}
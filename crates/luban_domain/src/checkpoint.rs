@@ -0,0 +1,165 @@
+use crate::{
+    ConversationEntry, ConversationSystemEvent, UserEvent, WorkspaceId, WorkspaceThreadId,
+};
+
+/// Hidden refs under this namespace pin worktree snapshots taken at the start of each turn.
+/// They live outside `refs/heads` so they never show up as branches.
+pub const TURN_CHECKPOINT_REF_PREFIX: &str = "refs/luban/checkpoints";
+
+/// A worktree snapshot taken before an agent turn started.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TurnCheckpoint {
+    pub checkpoint_id: String,
+    /// Commit holding the snapshot; its parent is `HEAD` at the time it was taken.
+    pub commit: String,
+}
+
+/// Run ids restart at 1 with the server, so the start time keeps checkpoint ids unique.
+pub fn new_turn_checkpoint_id(run_id: u64, started_at_unix_ms: u64) -> String {
+    format!("{started_at_unix_ms}-{run_id}")
+}
+
+/// Checkpoint ids come back from clients and are spliced into ref names.
+pub fn is_valid_turn_checkpoint_id(checkpoint_id: &str) -> bool {
+    !checkpoint_id.is_empty()
+        && checkpoint_id.len() <= 64
+        && checkpoint_id
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b == b'-')
+}
+
+pub fn workspace_checkpoint_ref_prefix(workspace_id: WorkspaceId) -> String {
+    format!("{TURN_CHECKPOINT_REF_PREFIX}/{}/", workspace_id.as_u64())
+}
+
+pub fn turn_checkpoint_ref(
+    workspace_id: WorkspaceId,
+    thread_id: WorkspaceThreadId,
+    checkpoint_id: &str,
+) -> String {
+    format!(
+        "{}{}/{checkpoint_id}",
+        workspace_checkpoint_ref_prefix(workspace_id),
+        thread_id.as_u64()
+    )
+}
+
+/// Pins the worktree as the turn started at `checkpoint_id` left it.
+pub fn turn_end_checkpoint_ref(
+    workspace_id: WorkspaceId,
+    thread_id: WorkspaceThreadId,
+    checkpoint_id: &str,
+) -> String {
+    format!(
+        "{}-end",
+        turn_checkpoint_ref(workspace_id, thread_id, checkpoint_id)
+    )
+}
+
+/// Index of the first entry to drop when rewinding to `checkpoint_id`: the prompt that
+/// started the turn when it directly precedes the checkpoint, otherwise the checkpoint itself.
+pub fn rewind_truncate_index(entries: &[ConversationEntry], checkpoint_id: &str) -> Option<usize> {
+    let index = entries.iter().position(|entry| {
+        matches!(
+            entry,
+            ConversationEntry::SystemEvent {
+                event: ConversationSystemEvent::TurnCheckpoint { checkpoint_id: id, .. },
+                ..
+            } if id == checkpoint_id
+        )
+    })?;
+    let starts_with_prompt = index > 0
+        && matches!(
+            entries[index - 1],
+            ConversationEntry::UserEvent {
+                event: UserEvent::Message { .. },
+                ..
+            }
+        );
+    Some(if starts_with_prompt { index - 1 } else { index })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AgentEvent;
+
+    fn prompt(text: &str) -> ConversationEntry {
+        ConversationEntry::UserEvent {
+            entry_id: String::new(),
+            created_at_unix_ms: 0,
+            event: UserEvent::Message {
+                text: text.to_owned(),
+                attachments: Vec::new(),
+                author: None,
+            },
+        }
+    }
+
+    fn checkpoint(id: &str) -> ConversationEntry {
+        ConversationEntry::SystemEvent {
+            entry_id: String::new(),
+            created_at_unix_ms: 0,
+            event: ConversationSystemEvent::TurnCheckpoint {
+                checkpoint_id: id.to_owned(),
+                commit: "abc".to_owned(),
+            },
+        }
+    }
+
+    fn reply(text: &str) -> ConversationEntry {
+        ConversationEntry::AgentEvent {
+            entry_id: String::new(),
+            created_at_unix_ms: 0,
+            runner: None,
+            event: AgentEvent::Message {
+                id: text.to_owned(),
+                text: text.to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn rewind_drops_the_prompt_that_started_the_turn() {
+        let entries = vec![
+            prompt("one"),
+            checkpoint("1-1"),
+            reply("a"),
+            prompt("two"),
+            checkpoint("2-2"),
+            reply("b"),
+        ];
+        assert_eq!(rewind_truncate_index(&entries, "2-2"), Some(3));
+        assert_eq!(rewind_truncate_index(&entries, "1-1"), Some(0));
+        assert_eq!(rewind_truncate_index(&entries, "3-3"), None);
+
+        let entries = vec![reply("a"), checkpoint("1-1")];
+        assert_eq!(rewind_truncate_index(&entries, "1-1"), Some(1));
+    }
+
+    #[test]
+    fn checkpoint_refs_are_namespaced_per_task() {
+        let id = new_turn_checkpoint_id(3, 1_700_000_000_000);
+        assert_eq!(id, "1700000000000-3");
+        assert!(is_valid_turn_checkpoint_id(&id));
+        assert!(!is_valid_turn_checkpoint_id("../heads/main"));
+        assert!(!is_valid_turn_checkpoint_id(""));
+        assert_eq!(
+            turn_checkpoint_ref(
+                WorkspaceId::from_u64(4),
+                WorkspaceThreadId::from_u64(2),
+                &id
+            ),
+            "refs/luban/checkpoints/4/2/1700000000000-3"
+        );
+        assert_eq!(
+            turn_end_checkpoint_ref(
+                WorkspaceId::from_u64(4),
+                WorkspaceThreadId::from_u64(2),
+                &id
+            ),
+            "refs/luban/checkpoints/4/2/1700000000000-3-end"
+        );
+        assert!(!is_valid_turn_checkpoint_id("1700000000000-3-end"));
+    }
+}
@@ -5,6 +5,7 @@ pub enum ContextTokenKind {
     Image,
     Text,
     File,
+    Url,
 }
 
 impl ContextTokenKind {
@@ -19,6 +20,9 @@ impl ContextTokenKind {
         if raw.eq_ignore_ascii_case("file") {
             return Some(Self::File);
         }
+        if raw.eq_ignore_ascii_case("url") {
+            return Some(Self::Url);
+        }
         None
     }
 
@@ -27,6 +31,7 @@ impl ContextTokenKind {
             Self::Image => "image",
             Self::Text => "text",
             Self::File => "file",
+            Self::Url => "url",
         }
     }
 }
@@ -126,6 +131,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn url_tokens_round_trip_their_kind() {
+        let text = "see <<context:url:/blobs/page.md>>>";
+        let tokens = find_context_tokens(text);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].kind, ContextTokenKind::Url);
+        assert_eq!(tokens[0].kind.as_str(), "url");
+        assert!(extract_context_image_paths_in_order(text).is_empty());
+    }
+
     #[test]
     fn unknown_kinds_are_ignored() {
         let text = "<<context:unknown:/x>>>";
@@ -2,13 +2,14 @@ use crate::{
     AgentRunConfig, AttachmentRef, OpenTarget, ProjectId, SystemTaskKind, TaskIntentKind,
     WorkspaceId, WorkspaceThreadId,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Clone, Debug)]
 pub enum Effect {
     LoadAppState,
     SaveAppState,
 
+    LoadModelCatalog,
     LoadCodexDefaults,
 
     LoadTaskPromptTemplates,
@@ -30,6 +31,20 @@ pub enum Effect {
     DeleteSystemPromptTemplate {
         kind: SystemTaskKind,
     },
+    LoadProjectPromptSettings,
+    StoreProjectTaskPromptTemplate {
+        project_id: ProjectId,
+        intent_kind: TaskIntentKind,
+        template: String,
+    },
+    DeleteProjectTaskPromptTemplate {
+        project_id: ProjectId,
+        intent_kind: TaskIntentKind,
+    },
+    StoreProjectPromptVariables {
+        project_id: ProjectId,
+        variables: BTreeMap<String, String>,
+    },
 
     CreateWorkspace {
         project_id: ProjectId,
@@ -72,6 +87,12 @@ pub enum Effect {
         workspace_id: WorkspaceId,
         thread_id: WorkspaceThreadId,
     },
+    /// Copies the forked conversation and its task documents into the new thread.
+    ForkConversation {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        origin: crate::ThreadForkOrigin,
+    },
     RunAgentTurn {
         workspace_id: WorkspaceId,
         thread_id: WorkspaceThreadId,
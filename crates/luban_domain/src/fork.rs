@@ -0,0 +1,207 @@
+use crate::{
+    AgentEvent, ConversationEntry, ConversationSystemEvent, UserEvent, WorkspaceId,
+    WorkspaceThreadId,
+};
+
+/// Where a forked task branched off: the source task and the last entry it carried over.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ThreadForkOrigin {
+    pub workspace_id: WorkspaceId,
+    pub thread_id: WorkspaceThreadId,
+    pub entry_id: String,
+}
+
+const FORK_SUMMARY_MAX_CHARS: usize = 8_000;
+const FORK_SUMMARY_MESSAGE_MAX_CHARS: usize = 800;
+
+/// Index of the fork point in `entries`, i.e. the last entry a fork at `entry_id` keeps.
+pub fn fork_entry_index(entries: &[ConversationEntry], entry_id: &str) -> Option<usize> {
+    entries.iter().position(|entry| {
+        let id = match entry {
+            ConversationEntry::SystemEvent { entry_id, .. }
+            | ConversationEntry::UserEvent { entry_id, .. }
+            | ConversationEntry::AgentEvent { entry_id, .. } => entry_id,
+        };
+        id == entry_id
+    })
+}
+
+/// A fork starts its runner session fresh, so its first prompt carries a digest of the
+/// conversation it inherited: the user and agent messages up to the fork point, oldest first,
+/// keeping the most recent ones when they do not all fit.
+pub fn fork_seed_summary(entries: &[ConversationEntry], entry_id: &str) -> Option<String> {
+    let end = fork_entry_index(entries, entry_id)?;
+    messages_digest(&entries[..=end])
+}
+
+/// A truncating rewind drops the runner session along with the later turns, so the first prompt
+/// after it carries a digest of the messages that were kept. `None` once a turn has run since.
+pub fn rewind_seed_summary(entries: &[ConversationEntry]) -> Option<String> {
+    let rewind = entries.iter().rposition(|entry| {
+        matches!(
+            entry,
+            ConversationEntry::SystemEvent {
+                event: ConversationSystemEvent::TurnRewound {
+                    truncated: true,
+                    ..
+                },
+                ..
+            }
+        )
+    })?;
+    if entries[rewind..]
+        .iter()
+        .any(|entry| matches!(entry, ConversationEntry::AgentEvent { .. }))
+    {
+        return None;
+    }
+    messages_digest(&entries[..rewind])
+}
+
+fn messages_digest(entries: &[ConversationEntry]) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut total = 0usize;
+    for entry in entries.iter().rev() {
+        let (speaker, text) = match entry {
+            ConversationEntry::UserEvent {
+                event: UserEvent::Message { text, .. },
+                ..
+            } => ("User", text),
+            ConversationEntry::AgentEvent {
+                event: AgentEvent::Message { text, .. },
+                ..
+            } => ("Agent", text),
+            _ => continue,
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let line = format!("- {speaker}: {}", truncate_chars(text));
+        total = total.saturating_add(line.chars().count() + 1);
+        if total > FORK_SUMMARY_MAX_CHARS && !lines.is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(lines.join("\n"))
+}
+
+pub fn seed_forked_prompt(summary: &str, prompt: &str) -> String {
+    format!(
+        "This task was forked from an earlier conversation and starts a fresh agent session. Summary of that conversation up to the fork point:\n\n{summary}\n\n---\n\n{prompt}"
+    )
+}
+
+pub fn seed_rewound_prompt(summary: &str, prompt: &str) -> String {
+    format!(
+        "This task was rewound to an earlier turn and starts a fresh agent session; later turns and their file changes were undone. Summary of the conversation that was kept:\n\n{summary}\n\n---\n\n{prompt}"
+    )
+}
+
+fn truncate_chars(text: &str) -> String {
+    let mut out = text
+        .chars()
+        .take(FORK_SUMMARY_MESSAGE_MAX_CHARS)
+        .collect::<String>();
+    if text.chars().count() > FORK_SUMMARY_MESSAGE_MAX_CHARS {
+        out.push('…');
+    }
+    out.replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(entry_id: &str, text: &str) -> ConversationEntry {
+        ConversationEntry::UserEvent {
+            entry_id: entry_id.to_owned(),
+            created_at_unix_ms: 0,
+            event: UserEvent::Message {
+                text: text.to_owned(),
+                attachments: Vec::new(),
+                author: None,
+            },
+        }
+    }
+
+    fn reply(entry_id: &str, text: &str) -> ConversationEntry {
+        ConversationEntry::AgentEvent {
+            entry_id: entry_id.to_owned(),
+            created_at_unix_ms: 0,
+            runner: None,
+            event: AgentEvent::Message {
+                id: entry_id.to_owned(),
+                text: text.to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn fork_summary_stops_at_the_fork_point() {
+        let entries = vec![
+            prompt("e_1", "try approach A"),
+            reply("e_2", "A is done"),
+            prompt("e_3", "now approach B"),
+            reply("e_4", "B is done"),
+        ];
+
+        let summary = fork_seed_summary(&entries, "e_2").expect("summary");
+        assert_eq!(summary, "- User: try approach A\n- Agent: A is done");
+        assert!(fork_seed_summary(&entries, "e_9").is_none());
+        assert_eq!(fork_entry_index(&entries, "e_3"), Some(2));
+
+        let seeded = seed_forked_prompt(&summary, "continue");
+        assert!(seeded.contains("- Agent: A is done"));
+        assert!(seeded.ends_with("continue"));
+    }
+
+    #[test]
+    fn rewind_summary_covers_the_kept_turns_until_the_next_turn_runs() {
+        let rewound = ConversationEntry::SystemEvent {
+            entry_id: "sys_3".to_owned(),
+            created_at_unix_ms: 0,
+            event: ConversationSystemEvent::TurnRewound {
+                checkpoint_id: "2-2".to_owned(),
+                truncated: true,
+            },
+        };
+        let mut entries = vec![
+            prompt("e_1", "try approach A"),
+            reply("e_2", "A is done"),
+            rewound,
+            prompt("e_4", "now approach C"),
+        ];
+
+        let summary = rewind_seed_summary(&entries).expect("summary");
+        assert_eq!(summary, "- User: try approach A\n- Agent: A is done");
+        assert!(seed_rewound_prompt(&summary, "now approach C").ends_with("now approach C"));
+
+        entries.push(reply("e_5", "C is done"));
+        assert!(rewind_seed_summary(&entries).is_none());
+        assert!(rewind_seed_summary(&entries[..2]).is_none());
+    }
+
+    #[test]
+    fn fork_summary_keeps_the_most_recent_messages() {
+        let long = "x".repeat(FORK_SUMMARY_MESSAGE_MAX_CHARS * 2);
+        let mut entries = (0..40)
+            .map(|i| prompt(&format!("e_{i}"), &long))
+            .collect::<Vec<_>>();
+        entries.push(reply("e_last", "latest"));
+
+        let summary = fork_seed_summary(&entries, "e_last").expect("summary");
+        assert!(summary.chars().count() <= FORK_SUMMARY_MAX_CHARS);
+        assert!(summary.ends_with("- Agent: latest"));
+        assert!(
+            summary
+                .lines()
+                .all(|line| line.chars().count() < long.len())
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{TaskIntentKind, TaskIssueInfo};
+
+/// Longest title slug kept in the branch name of an imported issue.
+const BRANCH_TITLE_SLUG_MAX_CHARS: usize = 40;
+
+/// A comment on an issue fetched from the project's forge.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportedIssueComment {
+    pub author: String,
+    pub body: String,
+    /// Timestamp as reported by the forge; empty when unknown.
+    pub created_at: String,
+}
+
+/// An issue fetched from the project's forge, used to seed a new task.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportedIssue {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub body: String,
+    pub labels: Vec<String>,
+    pub comments: Vec<ImportedIssueComment>,
+}
+
+impl ImportedIssue {
+    pub fn info(&self) -> TaskIssueInfo {
+        TaskIssueInfo {
+            number: self.number,
+            title: self.title.clone(),
+            url: self.url.clone(),
+        }
+    }
+
+    pub fn intent_kind(&self) -> TaskIntentKind {
+        intent_kind_for_issue_labels(&self.labels)
+    }
+
+    /// Workdir name for the task, e.g. `issue-42-fix-login-crash`.
+    pub fn branch_name_hint(&self) -> String {
+        let mut slug = String::new();
+        for word in self
+            .title
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            let next_len = slug.len() + usize::from(!slug.is_empty()) + word.len();
+            if next_len > BRANCH_TITLE_SLUG_MAX_CHARS {
+                break;
+            }
+            if !slug.is_empty() {
+                slug.push('-');
+            }
+            slug.push_str(&word.to_ascii_lowercase());
+        }
+
+        if slug.is_empty() {
+            format!("issue-{}", self.number)
+        } else {
+            format!("issue-{}-{slug}", self.number)
+        }
+    }
+
+    /// Initial TASK.md content: the issue description followed by its discussion.
+    pub fn task_document(&self) -> String {
+        let mut out = format!("# {} (#{})\n\n", self.title.trim(), self.number);
+        out.push_str(&format!("Source: {}\n", self.url));
+        if !self.labels.is_empty() {
+            out.push_str(&format!("Labels: {}\n", self.labels.join(", ")));
+        }
+
+        out.push_str("\n## Description\n\n");
+        let body = self.body.trim();
+        if body.is_empty() {
+            out.push_str("_No description provided._\n");
+        } else {
+            out.push_str(body);
+            out.push('\n');
+        }
+
+        if !self.comments.is_empty() {
+            out.push_str("\n## Discussion\n");
+            for comment in &self.comments {
+                let author = comment.author.trim();
+                let author = if author.is_empty() { "unknown" } else { author };
+                out.push_str(&format!("\n### {author}"));
+                if !comment.created_at.trim().is_empty() {
+                    out.push_str(&format!(" ({})", comment.created_at.trim()));
+                }
+                out.push_str("\n\n");
+                out.push_str(comment.body.trim());
+                out.push('\n');
+            }
+        }
+
+        out.push_str("\n## Status\n\nImported from the issue; work has not started yet.\n");
+        out
+    }
+}
+
+/// Picks the task intent from issue labels such as `bug`, `type: feature` or `kind/question`.
+///
+/// Bug-like labels win over feature-like ones, which win over discussion labels. Issues
+/// without a recognized label fall back to [`TaskIntentKind::Other`].
+pub fn intent_kind_for_issue_labels(labels: &[String]) -> TaskIntentKind {
+    const FIX: &[&str] = &["bug", "bugs", "defect", "regression", "crash", "fix"];
+    const IMPLEMENT: &[&str] = &[
+        "enhancement",
+        "feature",
+        "feat",
+        "improvement",
+        "refactor",
+        "docs",
+        "documentation",
+        "chore",
+    ];
+    const DISCUSS: &[&str] = &[
+        "question",
+        "discussion",
+        "discuss",
+        "rfc",
+        "proposal",
+        "design",
+    ];
+    const REVIEW: &[&str] = &["review"];
+
+    let words = labels
+        .iter()
+        .flat_map(|label| {
+            label
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|w| !w.is_empty())
+                .map(str::to_ascii_lowercase)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let has_any = |candidates: &[&str]| words.iter().any(|w| candidates.contains(&w.as_str()));
+
+    if has_any(FIX) {
+        TaskIntentKind::Fix
+    } else if has_any(IMPLEMENT) {
+        TaskIntentKind::Implement
+    } else if has_any(DISCUSS) {
+        TaskIntentKind::Discuss
+    } else if has_any(REVIEW) {
+        TaskIntentKind::Review
+    } else {
+        TaskIntentKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue() -> ImportedIssue {
+        ImportedIssue {
+            number: 42,
+            title: "Login crashes when the password contains a colon!".to_owned(),
+            url: "https://github.com/acme/app/issues/42".to_owned(),
+            body: "Steps:\n1. Type `a:b`\n".to_owned(),
+            labels: vec!["type: bug".to_owned(), "good first issue".to_owned()],
+            comments: vec![ImportedIssueComment {
+                author: "octocat".to_owned(),
+                body: "Reproduced on main.".to_owned(),
+                created_at: "2026-01-02T03:04:05Z".to_owned(),
+            }],
+        }
+    }
+
+    #[test]
+    fn labels_map_to_intent_kinds() {
+        let labels = |raw: &[&str]| raw.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            intent_kind_for_issue_labels(&labels(&["kind/feature", "bug"])),
+            TaskIntentKind::Fix
+        );
+        assert_eq!(
+            intent_kind_for_issue_labels(&labels(&["Enhancement"])),
+            TaskIntentKind::Implement
+        );
+        assert_eq!(
+            intent_kind_for_issue_labels(&labels(&["question"])),
+            TaskIntentKind::Discuss
+        );
+        assert_eq!(
+            intent_kind_for_issue_labels(&labels(&["debugging", "help wanted"])),
+            TaskIntentKind::Other
+        );
+        assert_eq!(intent_kind_for_issue_labels(&[]), TaskIntentKind::Other);
+    }
+
+    #[test]
+    fn branch_name_hint_uses_number_and_truncated_title() {
+        assert_eq!(
+            issue().branch_name_hint(),
+            "issue-42-login-crashes-when-the-password-contains"
+        );
+
+        let mut untitled = issue();
+        untitled.title = "???".to_owned();
+        assert_eq!(untitled.branch_name_hint(), "issue-42");
+    }
+
+    #[test]
+    fn task_document_includes_description_and_discussion() {
+        let doc = issue().task_document();
+        assert!(doc.starts_with("# Login crashes when the password contains a colon! (#42)\n"));
+        assert!(doc.contains("Source: https://github.com/acme/app/issues/42\n"));
+        assert!(doc.contains("Labels: type: bug, good first issue\n"));
+        assert!(doc.contains("## Description\n\nSteps:\n1. Type `a:b`\n"));
+        assert!(doc.contains("### octocat (2026-01-02T03:04:05Z)\n\nReproduced on main.\n"));
+    }
+}
@@ -14,13 +14,31 @@ pub use agent_thread::{
 
 mod adapters;
 pub use adapters::{
-    AmpConfigEntry, AmpConfigEntryKind, ClaudeConfigEntry, ClaudeConfigEntryKind, CodexConfigEntry,
-    CodexConfigEntryKind, ContextImage, CreatedWorkspace, DroidConfigEntry, DroidConfigEntryKind,
-    NewTaskDraft, NewTaskStash, OpenTarget, ProjectIdentity, ProjectWorkspaceService,
-    PullRequestCiState, PullRequestInfo, PullRequestState, RunAgentTurnRequest, TaskDocumentEvent,
-    TaskDocumentEventType, TaskDocumentIndex, TaskDocumentKind, TaskIntentKind, TaskIssueInfo,
-    TaskStatusAutoUpdateSuggestion,
+    AmpConfigEntry, AmpConfigEntryKind, ArchivedWorktree, ClaudeConfigEntry, ClaudeConfigEntryKind,
+    CodexConfigEntry, CodexConfigEntryKind, ContextImage, CreatedWorkspace, DiskCleanupReport,
+    DroidConfigEntry, DroidConfigEntryKind, LoadedModelCatalog, NewTaskDraft, NewTaskStash,
+    OpenTarget, ProjectIdentity, ProjectWorkspaceService, PullRequestCiState, PullRequestInfo,
+    PullRequestState, RunAgentTurnRequest, TaskDocumentEvent, TaskDocumentEventType,
+    TaskDocumentIndex, TaskDocumentKind, TaskIntentKind, TaskIssueInfo,
+    TaskStatusAutoUpdateSuggestion, WorkspaceDiskUsage,
 };
+mod audit;
+pub use audit::{
+    AuditEntry, AuditOrigin, AuditRecord, AuditTaskStatus, AuditUndo, audit_record_for_action,
+};
+mod checkpoint;
+pub use checkpoint::{
+    TURN_CHECKPOINT_REF_PREFIX, TurnCheckpoint, is_valid_turn_checkpoint_id,
+    new_turn_checkpoint_id, rewind_truncate_index, turn_checkpoint_ref, turn_end_checkpoint_ref,
+    workspace_checkpoint_ref_prefix,
+};
+mod fork;
+pub use fork::{
+    ThreadForkOrigin, fork_entry_index, fork_seed_summary, rewind_seed_summary, seed_forked_prompt,
+    seed_rewound_prompt,
+};
+mod issue_import;
+pub use issue_import::{ImportedIssue, ImportedIssueComment, intent_kind_for_issue_labels};
 mod context_tokens;
 pub use context_tokens::{
     ContextToken, ContextTokenKind, extract_context_image_paths_in_order, find_context_tokens,
@@ -45,6 +63,14 @@ pub use agent_settings::{
     thinking_effort_supported,
 };
 pub use task_prompts::{default_task_prompt_template, default_task_prompt_templates};
+mod model_catalog;
+pub use model_catalog::{AgentModel, ModelCatalog, STANDARD_THINKING_EFFORTS};
+mod prompt_variables;
+pub use prompt_variables::{
+    CUSTOM_PROMPT_VARIABLE_PREFIX, ProjectPromptSettings, SYSTEM_PROMPT_VARIABLES,
+    TASK_PROMPT_VARIABLES, is_valid_custom_prompt_variable_name, prompt_template_variables,
+    render_prompt_template, unknown_prompt_template_variables,
+};
 mod system_prompts;
 pub use system_prompts::{
     SystemTaskKind, default_system_prompt_template, default_system_prompt_templates,
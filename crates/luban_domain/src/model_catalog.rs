@@ -0,0 +1,217 @@
+use crate::agent_settings::{agent_models, default_agent_model_id, droid_models};
+use crate::{AgentRunnerKind, ThinkingEffort, default_thinking_effort};
+
+/// Thinking efforts accepted by Codex models that do not declare their own list.
+pub const STANDARD_THINKING_EFFORTS: &[ThinkingEffort] = &ThinkingEffort::ALL;
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AgentModel {
+    pub id: String,
+    pub label: String,
+    #[serde(default)]
+    pub supported_thinking_efforts: Vec<ThinkingEffort>,
+}
+
+/// Runtime model catalog: the built-in table merged with models discovered from runner
+/// configuration and the user's models file.
+///
+/// Only Codex and Droid expose a model selector; Amp and Claude have empty catalogs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModelCatalog {
+    codex: Vec<AgentModel>,
+    droid: Vec<AgentModel>,
+}
+
+impl ModelCatalog {
+    pub fn builtin() -> Self {
+        let convert = |specs: &[crate::AgentModelSpec]| {
+            specs
+                .iter()
+                .map(|spec| AgentModel {
+                    id: spec.id.to_owned(),
+                    label: spec.label.to_owned(),
+                    supported_thinking_efforts: spec.supported_thinking_efforts.to_vec(),
+                })
+                .collect()
+        };
+        Self {
+            codex: convert(agent_models()),
+            droid: convert(droid_models()),
+        }
+    }
+
+    /// Adds `models` to the runner's catalog, replacing entries with the same id in place.
+    pub fn merge(&mut self, runner: AgentRunnerKind, models: impl IntoIterator<Item = AgentModel>) {
+        let Some(catalog) = self.catalog_mut(runner) else {
+            return;
+        };
+        for mut model in models {
+            model.id = model.id.trim().to_owned();
+            if model.id.is_empty() {
+                continue;
+            }
+            match catalog.iter_mut().find(|m| m.id == model.id) {
+                Some(existing) => *existing = model,
+                None => catalog.push(model),
+            }
+        }
+    }
+
+    pub fn models_for_runner(&self, runner: AgentRunnerKind) -> &[AgentModel] {
+        match runner {
+            AgentRunnerKind::Codex => &self.codex,
+            AgentRunnerKind::Droid => &self.droid,
+            // Amp and Claude don't use the model selector
+            _ => &[],
+        }
+    }
+
+    fn catalog_mut(&mut self, runner: AgentRunnerKind) -> Option<&mut Vec<AgentModel>> {
+        match runner {
+            AgentRunnerKind::Codex => Some(&mut self.codex),
+            AgentRunnerKind::Droid => Some(&mut self.droid),
+            _ => None,
+        }
+    }
+
+    /// Look up a model by ID, preferring the Codex catalog for shared models.
+    pub fn find(&self, model_id: &str) -> Option<&AgentModel> {
+        self.codex
+            .iter()
+            .chain(self.droid.iter())
+            .find(|m| m.id == model_id)
+    }
+
+    pub fn model_label(&self, model_id: &str) -> Option<&str> {
+        self.find(model_id).map(|m| m.label.as_str())
+    }
+
+    pub fn thinking_effort_supported(&self, model_id: &str, effort: ThinkingEffort) -> bool {
+        self.find(model_id)
+            .is_some_and(|m| m.supported_thinking_efforts.contains(&effort))
+    }
+
+    pub fn normalize_thinking_effort(
+        &self,
+        model_id: &str,
+        effort: ThinkingEffort,
+    ) -> ThinkingEffort {
+        let Some(model) = self.find(model_id) else {
+            return default_thinking_effort();
+        };
+        let supported = &model.supported_thinking_efforts;
+        if supported.contains(&effort) {
+            return effort;
+        }
+
+        let fallback = default_thinking_effort();
+        if supported.contains(&fallback) {
+            return fallback;
+        }
+
+        supported.first().copied().unwrap_or(fallback)
+    }
+
+    /// Like [`Self::normalize_thinking_effort`], but keeps `effort` for models the catalog does
+    /// not know, since they may still be discovered later.
+    pub fn normalize_known_thinking_effort(
+        &self,
+        model_id: &str,
+        effort: ThinkingEffort,
+    ) -> ThinkingEffort {
+        if self.find(model_id).is_none() {
+            return effort;
+        }
+        self.normalize_thinking_effort(model_id, effort)
+    }
+
+    /// Check whether `model_id` exists in the given runner's catalog.
+    pub fn model_valid_for_runner(&self, runner: AgentRunnerKind, model_id: &str) -> bool {
+        let catalog = self.models_for_runner(runner);
+        // Reason: Amp/Claude have empty catalogs — any model is "valid" (ignored).
+        catalog.is_empty() || catalog.iter().any(|m| m.id == model_id)
+    }
+
+    /// Return the first catalog entry for `runner`, falling back to `default_agent_model_id()`.
+    pub fn default_model_for_runner(&self, runner: AgentRunnerKind) -> &str {
+        self.models_for_runner(runner)
+            .first()
+            .map(|m| m.id.as_str())
+            .unwrap_or(default_agent_model_id())
+    }
+
+    /// Infer which runner owns `model_id`, preferring Codex for shared models.
+    pub fn runner_for_model(&self, model_id: &str) -> Option<AgentRunnerKind> {
+        if self.codex.iter().any(|m| m.id == model_id) {
+            return Some(AgentRunnerKind::Codex);
+        }
+        if self.droid.iter().any(|m| m.id == model_id) {
+            return Some(AgentRunnerKind::Droid);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(id: &str, efforts: &[ThinkingEffort]) -> AgentModel {
+        AgentModel {
+            id: id.to_owned(),
+            label: id.to_owned(),
+            supported_thinking_efforts: efforts.to_vec(),
+        }
+    }
+
+    #[test]
+    fn builtin_catalog_matches_static_tables() {
+        let catalog = ModelCatalog::builtin();
+        assert_eq!(
+            catalog.default_model_for_runner(AgentRunnerKind::Codex),
+            agent_models()[0].id
+        );
+        assert_eq!(
+            catalog.runner_for_model("kimi-k2.5"),
+            Some(AgentRunnerKind::Droid)
+        );
+        assert!(catalog.model_valid_for_runner(AgentRunnerKind::Amp, "anything"));
+    }
+
+    #[test]
+    fn merged_models_are_valid_and_keep_declared_efforts() {
+        let mut catalog = ModelCatalog::builtin();
+        assert!(!catalog.model_valid_for_runner(AgentRunnerKind::Codex, "gpt-9"));
+
+        catalog.merge(
+            AgentRunnerKind::Codex,
+            [model("gpt-9", &[ThinkingEffort::Low, ThinkingEffort::High])],
+        );
+        assert!(catalog.model_valid_for_runner(AgentRunnerKind::Codex, "gpt-9"));
+        assert_eq!(
+            catalog.normalize_thinking_effort("gpt-9", ThinkingEffort::High),
+            ThinkingEffort::High
+        );
+        assert_eq!(
+            catalog.normalize_thinking_effort("gpt-9", ThinkingEffort::XHigh),
+            ThinkingEffort::Low
+        );
+    }
+
+    #[test]
+    fn merge_replaces_existing_entries_in_place() {
+        let mut catalog = ModelCatalog::builtin();
+        let before = catalog.models_for_runner(AgentRunnerKind::Codex).len();
+        catalog.merge(
+            AgentRunnerKind::Codex,
+            [model("gpt-5.2", &[ThinkingEffort::Medium])],
+        );
+
+        let models = catalog.models_for_runner(AgentRunnerKind::Codex);
+        assert_eq!(models.len(), before);
+        assert!(!catalog.thinking_effort_supported("gpt-5.2", ThinkingEffort::High));
+
+        catalog.merge(AgentRunnerKind::Amp, [model("ignored", &[])]);
+        assert!(catalog.models_for_runner(AgentRunnerKind::Amp).is_empty());
+    }
+}
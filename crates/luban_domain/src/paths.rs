@@ -21,6 +21,11 @@ pub fn conversations_root(luban_root: &Path) -> PathBuf {
     luban_root.join("conversations")
 }
 
+/// Content-addressed attachment blobs shared by all workspaces.
+pub fn context_blobs_root(luban_root: &Path) -> PathBuf {
+    luban_root.join("blobs")
+}
+
 pub fn sqlite_path(luban_root: &Path) -> PathBuf {
     luban_root.join("luban.db")
 }
@@ -29,6 +34,10 @@ pub fn task_prompts_root(luban_root: &Path) -> PathBuf {
     luban_root.join("task")
 }
 
+pub fn models_path(luban_root: &Path) -> PathBuf {
+    luban_root.join("models.json")
+}
+
 pub fn workspace_conversation_dir(
     conversations_root: &Path,
     project_slug: &str,
@@ -74,6 +83,7 @@ mod tests {
         assert_eq!(worktrees_root(&base), base.join("worktrees"));
         assert_eq!(projects_root(&base), base.join("projects"));
         assert_eq!(conversations_root(&base), base.join("conversations"));
+        assert_eq!(context_blobs_root(&base), base.join("blobs"));
         assert_eq!(sqlite_path(&base), base.join("luban.db"));
         assert_eq!(task_prompts_root(&base), base.join("task"));
         assert_eq!(LUBAN_CODEX_BIN_ENV, "LUBAN_CODEX_BIN");
@@ -7,7 +7,7 @@ use crate::{
     PersistedAppState, PersistedProject, Project, ProjectId, RightPane, TaskIntentKind, Workspace,
     WorkspaceId, WorkspaceStatus, WorkspaceTabs, WorkspaceThreadId, default_agent_model_id,
     default_agent_runner_kind, default_amp_mode, default_system_prompt_templates,
-    default_task_prompt_templates, default_thinking_effort, normalize_thinking_effort,
+    default_task_prompt_templates, default_thinking_effort,
 };
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
@@ -62,8 +62,9 @@ pub(crate) fn apply_persisted_app_state(
         .as_deref()
         .and_then(parse_thinking_effort)
         .unwrap_or_else(default_thinking_effort);
-    let agent_default_thinking_effort =
-        normalize_thinking_effort(&agent_default_model_id, agent_default_thinking_effort);
+    let agent_default_thinking_effort = state
+        .model_catalog
+        .normalize_known_thinking_effort(&agent_default_model_id, agent_default_thinking_effort);
 
     let agent_default_runner = persisted
         .agent_default_runner
@@ -108,9 +109,35 @@ pub(crate) fn apply_persisted_app_state(
     state.telegram_bot_username =
         normalize_optional_string(persisted.telegram_bot_username.as_deref(), 64);
     state.telegram_paired_chat_id = persisted.telegram_paired_chat_id;
+    state.telegram_group_chats =
+        load_telegram_group_chats(persisted.telegram_group_chats.as_deref());
+    state.notification_settings =
+        load_notification_settings(persisted.notification_settings.as_deref());
+    state.attachment_text_limits =
+        load_attachment_text_limits(persisted.attachment_text_limits.as_deref());
+    state.url_attachment_settings =
+        load_url_attachment_settings(persisted.url_attachment_settings.as_deref());
+    state.task_issue_links = load_task_issue_links(persisted.task_issue_links.as_deref());
+    state.deletion_settings = load_deletion_settings(persisted.deletion_settings.as_deref());
+    state.deleted_projects = load_deleted_projects(
+        persisted.deleted_projects.as_deref(),
+        &state.projects,
+        state.deletion_settings,
+    );
+    let (project_turn_limits, task_turn_limits, task_tokens_used) =
+        load_turn_limits(persisted.turn_limits.as_deref());
+    state.project_turn_limits = project_turn_limits;
+    state.task_turn_limits = task_turn_limits;
+    state.task_tokens_used = task_tokens_used;
+    (
+        state.task_review_comments,
+        state.task_pull_request_review_sync,
+    ) = load_task_review_comments(persisted.task_review_comments.as_deref());
+    state.task_ci_auto_fix = load_task_ci_auto_fix(persisted.task_ci_auto_fix.as_deref());
     state.telegram_config_rev = if state.telegram_enabled
         || state.telegram_bot_token.is_some()
         || state.telegram_paired_chat_id.is_some()
+        || !state.telegram_group_chats.is_empty()
     {
         1
     } else {
@@ -238,7 +265,9 @@ pub(crate) fn apply_persisted_app_state(
                 .filter(|v| v.len() <= 128)
                 .map(ToOwned::to_owned);
             let parsed_effort = parse_thinking_effort(&run_config.thinking_effort)?;
-            let normalized = normalize_thinking_effort(model_id, parsed_effort);
+            let normalized = state
+                .model_catalog
+                .normalize_known_thinking_effort(model_id, parsed_effort);
             Some((
                 (WorkspaceId(workspace_id), WorkspaceThreadId(thread_id)),
                 crate::PersistedWorkspaceThreadRunConfigOverride {
@@ -333,7 +362,9 @@ pub(crate) fn apply_persisted_app_state(
                     overridden = true;
                 }
                 if let Some(parsed_effort) = parse_thinking_effort(&run_config.thinking_effort) {
-                    let normalized = normalize_thinking_effort(&run_config.model_id, parsed_effort);
+                    let normalized = state
+                        .model_catalog
+                        .normalize_known_thinking_effort(&run_config.model_id, parsed_effort);
                     conversation.run_config_overridden_by_user = true;
                     conversation.agent_model_id = run_config.model_id.clone();
                     conversation.thinking_effort = normalized;
@@ -384,12 +415,26 @@ pub(crate) fn apply_persisted_app_state(
         })
         .collect();
 
-    let max_project_id = state.projects.iter().map(|p| p.id.0).max().unwrap_or(0);
+    // Soft-deleted projects keep their ids so they can be restored without remapping.
+    let max_project_id = state
+        .projects
+        .iter()
+        .map(|p| p.id.0)
+        .chain(state.deleted_projects.iter().map(|p| p.id))
+        .max()
+        .unwrap_or(0);
     let max_workspace_id = state
         .projects
         .iter()
         .flat_map(|p| &p.workspaces)
         .map(|w| w.id.0)
+        .chain(
+            state
+                .deleted_projects
+                .iter()
+                .flat_map(|p| &p.workspaces)
+                .map(|w| w.id),
+        )
         .max()
         .unwrap_or(0);
 
@@ -405,9 +450,11 @@ pub(crate) fn apply_persisted_app_state(
             templates: legacy_templates,
         });
     }
-    effects.push(Effect::LoadCodexDefaults);
+    // Reason: Codex defaults are normalized against the catalog, so they load once it arrives.
+    effects.push(Effect::LoadModelCatalog);
     effects.push(Effect::LoadTaskPromptTemplates);
     effects.push(Effect::LoadSystemPromptTemplates);
+    effects.push(Effect::LoadProjectPromptSettings);
     if projects_upgraded || clear_legacy_templates {
         effects.push(Effect::SaveAppState);
     }
@@ -465,6 +512,266 @@ fn load_telegram_topic_bindings(
     out
 }
 
+fn load_task_issue_links(
+    raw: Option<&str>,
+) -> HashMap<(WorkspaceId, WorkspaceThreadId), crate::TaskIssueLink> {
+    const MAX_LINKS: usize = 4096;
+
+    let Some(raw) = raw.map(str::trim).filter(|v| !v.is_empty()) else {
+        return HashMap::new();
+    };
+    let Ok(links) = serde_json::from_str::<Vec<crate::TaskIssueLink>>(raw) else {
+        return HashMap::new();
+    };
+
+    let mut out = HashMap::new();
+    for link in links.into_iter().take(MAX_LINKS) {
+        if link.workspace_id == 0
+            || link.thread_id == 0
+            || link.number == 0
+            || link.url.trim().is_empty()
+        {
+            continue;
+        }
+        out.insert(
+            (
+                WorkspaceId::from_u64(link.workspace_id),
+                WorkspaceThreadId::from_u64(link.thread_id),
+            ),
+            link,
+        );
+    }
+    out
+}
+
+type LoadedTurnLimits = (
+    HashMap<crate::ProjectId, crate::TurnLimits>,
+    HashMap<(WorkspaceId, WorkspaceThreadId), crate::TurnLimits>,
+    HashMap<(WorkspaceId, WorkspaceThreadId), u64>,
+);
+
+fn load_turn_limits(raw: Option<&str>) -> LoadedTurnLimits {
+    const MAX_RECORDS: usize = 4096;
+
+    let mut loaded = LoadedTurnLimits::default();
+    let Some(store) = raw
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .and_then(|raw| serde_json::from_str::<crate::TurnLimitsStore>(raw).ok())
+    else {
+        return loaded;
+    };
+
+    for record in store.projects.into_iter().take(MAX_RECORDS) {
+        if record.project_id == 0 || record.limits.is_empty() || !record.limits.is_valid() {
+            continue;
+        }
+        loaded
+            .0
+            .insert(crate::ProjectId(record.project_id), record.limits);
+    }
+    for record in store.tasks.into_iter().take(MAX_RECORDS) {
+        if record.workspace_id == 0 || record.thread_id == 0 {
+            continue;
+        }
+        let key = (
+            WorkspaceId::from_u64(record.workspace_id),
+            WorkspaceThreadId::from_u64(record.thread_id),
+        );
+        if !record.limits.is_empty() && record.limits.is_valid() {
+            loaded.1.insert(key, record.limits);
+        }
+        if record.tokens_used > 0 {
+            loaded.2.insert(key, record.tokens_used);
+        }
+    }
+    loaded
+}
+
+type LoadedTaskReviewComments = (
+    HashMap<(WorkspaceId, WorkspaceThreadId), Vec<crate::ReviewComment>>,
+    HashMap<(WorkspaceId, WorkspaceThreadId), crate::PullRequestReviewSync>,
+);
+
+fn load_task_review_comments(raw: Option<&str>) -> LoadedTaskReviewComments {
+    const MAX_TASKS: usize = 4096;
+    const MAX_COMMENTS_PER_TASK: usize = 1000;
+
+    let mut loaded = LoadedTaskReviewComments::default();
+    let Some(raw) = raw.map(str::trim).filter(|v| !v.is_empty()) else {
+        return loaded;
+    };
+    let Ok(records) = serde_json::from_str::<Vec<crate::TaskReviewCommentsRecord>>(raw) else {
+        return loaded;
+    };
+
+    for record in records.into_iter().take(MAX_TASKS) {
+        if record.workspace_id == 0 || record.thread_id == 0 {
+            continue;
+        }
+        let key = (
+            WorkspaceId::from_u64(record.workspace_id),
+            WorkspaceThreadId::from_u64(record.thread_id),
+        );
+        if let Some(sync) = record
+            .pull_request_sync
+            .filter(|sync| *sync != crate::PullRequestReviewSync::default())
+        {
+            loaded.1.insert(key, sync);
+        }
+        // Only comments imported from a pull request may cover a whole file (line 0).
+        let comments = record
+            .comments
+            .into_iter()
+            .filter(|c| {
+                c.id != 0
+                    && !c.path.trim().is_empty()
+                    && (c.start_line > 0 || c.pull_request.is_some())
+                    && c.end_line >= c.start_line
+                    && !c.body.trim().is_empty()
+            })
+            .take(MAX_COMMENTS_PER_TASK)
+            .collect::<Vec<_>>();
+        if comments.is_empty() {
+            continue;
+        }
+        loaded.0.insert(key, comments);
+    }
+    loaded
+}
+
+fn load_task_ci_auto_fix(
+    raw: Option<&str>,
+) -> HashMap<(WorkspaceId, WorkspaceThreadId), crate::CiAutoFix> {
+    const MAX_TASKS: usize = 4096;
+
+    let Some(raw) = raw.map(str::trim).filter(|v| !v.is_empty()) else {
+        return HashMap::new();
+    };
+    let Ok(records) = serde_json::from_str::<Vec<crate::TaskCiAutoFixRecord>>(raw) else {
+        return HashMap::new();
+    };
+
+    let mut out = HashMap::new();
+    for mut record in records.into_iter().take(MAX_TASKS) {
+        if record.workspace_id == 0
+            || record.thread_id == 0
+            || record.fix.max_attempts == 0
+            || record.fix.max_attempts > crate::CiAutoFix::MAX_MAX_ATTEMPTS
+        {
+            continue;
+        }
+        let max_attempts = usize::try_from(record.fix.max_attempts).unwrap_or(usize::MAX);
+        record.fix.attempts.truncate(max_attempts);
+        out.insert(
+            (
+                WorkspaceId::from_u64(record.workspace_id),
+                WorkspaceThreadId::from_u64(record.thread_id),
+            ),
+            record.fix,
+        );
+    }
+    out
+}
+
+fn load_notification_settings(raw: Option<&str>) -> crate::NotificationSettings {
+    let Some(raw) = raw.map(str::trim).filter(|v| !v.is_empty()) else {
+        return crate::NotificationSettings::default();
+    };
+    let Ok(mut settings) = serde_json::from_str::<crate::NotificationSettings>(raw) else {
+        return crate::NotificationSettings::default();
+    };
+    settings.quiet_hours = settings.quiet_hours.filter(|q| q.is_valid());
+    settings
+        .muted_project_slugs
+        .retain(|s| !s.trim().is_empty());
+    settings.muted_project_slugs.sort();
+    settings.muted_project_slugs.dedup();
+    settings
+}
+
+fn load_attachment_text_limits(raw: Option<&str>) -> crate::AttachmentTextLimits {
+    raw.map(str::trim)
+        .filter(|v| !v.is_empty())
+        .and_then(|raw| serde_json::from_str::<crate::AttachmentTextLimits>(raw).ok())
+        .filter(|limits| limits.is_valid())
+        .unwrap_or_default()
+}
+
+fn load_url_attachment_settings(raw: Option<&str>) -> crate::UrlAttachmentSettings {
+    raw.map(str::trim)
+        .filter(|v| !v.is_empty())
+        .and_then(|raw| serde_json::from_str::<crate::UrlAttachmentSettings>(raw).ok())
+        .map(|settings| {
+            let mut allowed_domains = settings
+                .allowed_domains
+                .iter()
+                .filter_map(|d| crate::UrlAttachmentSettings::normalize_domain(d))
+                .collect::<Vec<_>>();
+            allowed_domains.sort();
+            allowed_domains.dedup();
+            crate::UrlAttachmentSettings { allowed_domains }
+        })
+        .unwrap_or_default()
+}
+
+fn load_deletion_settings(raw: Option<&str>) -> crate::DeletionSettings {
+    raw.map(str::trim)
+        .filter(|v| !v.is_empty())
+        .and_then(|raw| serde_json::from_str::<crate::DeletionSettings>(raw).ok())
+        .filter(|settings| settings.is_valid())
+        .unwrap_or_default()
+}
+
+fn load_deleted_projects(
+    raw: Option<&str>,
+    live_projects: &[crate::Project],
+    settings: crate::DeletionSettings,
+) -> Vec<crate::DeletedProject> {
+    if !settings.soft_delete_enabled() {
+        return Vec::new();
+    }
+    let Some(mut projects) = raw
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .and_then(|raw| serde_json::from_str::<Vec<crate::DeletedProject>>(raw).ok())
+    else {
+        return Vec::new();
+    };
+    projects.retain(|deleted| !live_projects.iter().any(|p| p.id.0 == deleted.id));
+    projects.sort_by_key(|p| p.id);
+    projects.dedup_by_key(|p| p.id);
+    projects.sort_by_key(|p| (p.deleted_at_unix_ms, p.id));
+    projects
+}
+
+fn load_telegram_group_chats(raw: Option<&str>) -> HashMap<i64, crate::TelegramGroupChat> {
+    const MAX_GROUPS: usize = 64;
+    const MAX_BINDINGS: usize = 64;
+
+    let Some(raw) = raw.map(str::trim).filter(|v| !v.is_empty()) else {
+        return HashMap::new();
+    };
+
+    let Ok(groups) = serde_json::from_str::<Vec<crate::TelegramGroupChat>>(raw) else {
+        return HashMap::new();
+    };
+
+    let mut out = HashMap::new();
+    for mut group in groups.into_iter().take(MAX_GROUPS) {
+        if group.chat_id == 0 || group.project_slug.trim().is_empty() {
+            continue;
+        }
+        group.members.retain(|m| m.user_id > 0);
+        group
+            .topic_bindings
+            .retain(|b| b.message_thread_id > 0 && b.workspace_id != 0 && b.thread_id != 0);
+        group.topic_bindings.truncate(MAX_BINDINGS);
+        out.insert(group.chat_id, group);
+    }
+    out
+}
+
 fn load_projects(projects: Vec<PersistedProject>) -> (Vec<Project>, bool) {
     use std::collections::hash_map::Entry;
 
@@ -497,6 +804,9 @@ fn load_projects(projects: Vec<PersistedProject>) -> (Vec<Project>, bool) {
                     last_activity_at: w
                         .last_activity_at_unix_seconds
                         .map(system_time_from_unix_seconds),
+                    archived_at: w
+                        .archived_at_unix_seconds
+                        .map(system_time_from_unix_seconds),
                     archive_status: OperationStatus::Idle,
                     branch_rename_status: OperationStatus::Idle,
                 })
@@ -637,6 +947,7 @@ mod tests {
                     worktree_path: path.clone(),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             },
             PersistedProject {
@@ -653,6 +964,7 @@ mod tests {
                     worktree_path: path.clone(),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             },
         ];
@@ -683,6 +995,7 @@ mod tests {
                     worktree_path: path.clone(),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 },
                 PersistedWorkspace {
                     id: 11,
@@ -691,6 +1004,7 @@ mod tests {
                     worktree_path: path.clone(),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 },
             ],
         }];
@@ -712,6 +1026,7 @@ mod tests {
                 worktree_path: PathBuf::from("/tmp/repo/dev"),
                 status: WorkspaceStatus::Active,
                 last_activity_at: None,
+                archived_at: None,
                 archive_status: OperationStatus::Idle,
                 branch_rename_status: OperationStatus::Idle,
             },
@@ -722,6 +1037,7 @@ mod tests {
                 worktree_path: PathBuf::from("/tmp/repo/dev-2"),
                 status: WorkspaceStatus::Active,
                 last_activity_at: None,
+                archived_at: None,
                 archive_status: OperationStatus::Idle,
                 branch_rename_status: OperationStatus::Idle,
             },
@@ -732,6 +1048,7 @@ mod tests {
                 worktree_path: PathBuf::from("/tmp/repo/dev-3"),
                 status: WorkspaceStatus::Active,
                 last_activity_at: None,
+                archived_at: None,
                 archive_status: OperationStatus::Idle,
                 branch_rename_status: OperationStatus::Idle,
             },
@@ -742,6 +1059,7 @@ mod tests {
                 worktree_path: PathBuf::from("/tmp/repo/dev-4"),
                 status: WorkspaceStatus::Active,
                 last_activity_at: None,
+                archived_at: None,
                 archive_status: OperationStatus::Idle,
                 branch_rename_status: OperationStatus::Idle,
             },
@@ -772,6 +1090,7 @@ mod tests {
                     worktree_path: path.clone(),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: None,
@@ -809,6 +1128,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
 
         let mut state = AppState::new();
@@ -863,4 +1192,186 @@ mod tests {
         assert_eq!(open, vec![active, active]);
         assert_eq!(archived, vec![WorkspaceThreadId(2)]);
     }
+
+    #[test]
+    fn load_telegram_group_chats_drops_invalid_entries() {
+        let raw = serde_json::json!([
+            {
+                "chat_id": -100,
+                "project_slug": "repo",
+                "members": [{ "user_id": 7, "display_name": "Ada" }, { "user_id": 0 }],
+                "topic_bindings": [
+                    { "message_thread_id": 3, "workspace_id": 1, "thread_id": 2 },
+                    { "message_thread_id": 0, "workspace_id": 1, "thread_id": 2 }
+                ]
+            },
+            { "chat_id": -200, "project_slug": " " }
+        ])
+        .to_string();
+
+        let groups = load_telegram_group_chats(Some(&raw));
+        assert_eq!(groups.len(), 1);
+        let group = &groups[&-100];
+        assert_eq!(group.members.len(), 1);
+        assert!(group.is_member(7));
+        assert_eq!(group.topic_bindings.len(), 1);
+        assert_eq!(group.topic_bindings[0].message_thread_id, 3);
+    }
+
+    #[test]
+    fn load_notification_settings_drops_invalid_quiet_hours() {
+        let raw = serde_json::json!({
+            "desktop_enabled": true,
+            "quiet_hours": { "start_minute": 1440, "end_minute": 60 },
+            "muted_project_slugs": ["web", " ", "web"]
+        })
+        .to_string();
+
+        let settings = load_notification_settings(Some(&raw));
+        assert!(settings.desktop_enabled);
+        assert!(settings.quiet_hours.is_none());
+        assert_eq!(settings.muted_project_slugs, vec!["web".to_owned()]);
+        assert_eq!(
+            load_notification_settings(Some("not json")),
+            crate::NotificationSettings::default()
+        );
+    }
+
+    #[test]
+    fn load_attachment_text_limits_falls_back_to_defaults() {
+        let raw =
+            serde_json::json!({ "max_source_bytes": 1024, "max_inline_bytes": 0 }).to_string();
+        let limits = load_attachment_text_limits(Some(&raw));
+        assert_eq!(limits.max_source_bytes, 1024);
+        assert_eq!(limits.max_inline_bytes, 0);
+
+        let partial = serde_json::json!({ "max_inline_bytes": 10 }).to_string();
+        assert_eq!(
+            load_attachment_text_limits(Some(&partial)).max_source_bytes,
+            crate::AttachmentTextLimits::default().max_source_bytes
+        );
+
+        let invalid = serde_json::json!({ "max_source_bytes": 0 }).to_string();
+        assert_eq!(
+            load_attachment_text_limits(Some(&invalid)),
+            crate::AttachmentTextLimits::default()
+        );
+        assert_eq!(
+            load_attachment_text_limits(None),
+            crate::AttachmentTextLimits::default()
+        );
+    }
+
+    #[test]
+    fn load_url_attachment_settings_drops_invalid_domains() {
+        let raw = serde_json::json!({
+            "allowed_domains": ["Docs.Rs", "not a domain", "docs.rs", "*.github.com"]
+        })
+        .to_string();
+        let settings = load_url_attachment_settings(Some(&raw));
+        assert_eq!(settings.allowed_domains, vec!["docs.rs", "github.com"]);
+        assert!(
+            load_url_attachment_settings(Some("[]"))
+                .allowed_domains
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn load_task_issue_links_skips_incomplete_entries() {
+        let raw = serde_json::json!([
+            { "workspace_id": 1, "thread_id": 2, "number": 42,
+              "url": "https://github.com/acme/app/issues/42", "on_merge": "comment" },
+            { "workspace_id": 1, "thread_id": 3, "number": 0, "url": "x" },
+            { "workspace_id": 0, "thread_id": 4, "number": 7, "url": "y" }
+        ])
+        .to_string();
+        let links = load_task_issue_links(Some(&raw));
+        assert_eq!(links.len(), 1);
+        let link = &links[&(WorkspaceId::from_u64(1), WorkspaceThreadId::from_u64(2))];
+        assert_eq!(link.on_merge, crate::IssueMergeAction::Comment);
+        assert_eq!(link.resolved_by_pr, None);
+        assert!(load_task_issue_links(Some("not json")).is_empty());
+    }
+
+    #[test]
+    fn load_task_review_comments_skips_invalid_comments() {
+        let raw = serde_json::json!([
+            { "workspace_id": 1, "thread_id": 2, "comments": [
+                { "id": 1, "path": "src/lib.rs", "start_line": 3, "end_line": 4,
+                  "quote": "fn main() {}", "body": "rename this", "created_at_unix_ms": 10 },
+                { "id": 2, "path": "src/lib.rs", "start_line": 5, "end_line": 4,
+                  "body": "backwards range", "created_at_unix_ms": 10 },
+                { "id": 3, "path": "", "start_line": 1, "end_line": 1,
+                  "body": "no path", "created_at_unix_ms": 10 }
+            ] },
+            { "workspace_id": 0, "thread_id": 3, "comments": [] },
+            { "workspace_id": 1, "thread_id": 4, "comments": [
+                { "id": 1, "path": "Cargo.toml", "start_line": 0, "end_line": 0,
+                  "body": "why?", "created_at_unix_ms": 10,
+                  "pull_request": { "pr_number": 7, "comment_id": 99, "author": "octocat" } }
+            ], "pull_request_sync": { "auto_pull": true } }
+        ])
+        .to_string();
+        let (comments, sync) = load_task_review_comments(Some(&raw));
+        assert_eq!(comments.len(), 2);
+        let task = &comments[&(WorkspaceId::from_u64(1), WorkspaceThreadId::from_u64(2))];
+        assert_eq!(task.len(), 1);
+        assert!(task[0].is_pending());
+        let imported = &comments[&(WorkspaceId::from_u64(1), WorkspaceThreadId::from_u64(4))];
+        assert_eq!(
+            imported[0].pull_request.as_ref().map(|o| o.comment_id),
+            Some(99)
+        );
+        assert_eq!(sync.len(), 1);
+        assert!(sync[&(WorkspaceId::from_u64(1), WorkspaceThreadId::from_u64(4))].auto_pull);
+        assert!(load_task_review_comments(Some("not json")).0.is_empty());
+    }
+
+    #[test]
+    fn load_task_ci_auto_fix_skips_invalid_budgets() {
+        let raw = serde_json::json!([
+            { "workspace_id": 1, "thread_id": 2, "enabled": true, "max_attempts": 2, "attempts": [
+                { "pr_number": 7, "run_id": 11, "started_at_unix_ms": 10 }
+            ] },
+            { "workspace_id": 1, "thread_id": 3, "enabled": true, "max_attempts": 0 },
+            { "workspace_id": 1, "thread_id": 4, "enabled": true, "max_attempts": 99 }
+        ])
+        .to_string();
+        let fixes = load_task_ci_auto_fix(Some(&raw));
+        assert_eq!(fixes.len(), 1);
+        let fix = &fixes[&(WorkspaceId::from_u64(1), WorkspaceThreadId::from_u64(2))];
+        assert!(fix.enabled && fix.has_attempts_left());
+        assert!(fix.attempted_run(11));
+        assert!(load_task_ci_auto_fix(Some("not json")).is_empty());
+    }
+
+    #[test]
+    fn load_turn_limits_keeps_valid_limits_and_token_tallies() {
+        let raw = serde_json::json!({
+            "projects": [
+                { "project_id": 1, "turn_timeout_secs": 600 },
+                { "project_id": 2, "max_tool_calls_per_turn": 0 },
+                { "project_id": 3 }
+            ],
+            "tasks": [
+                { "workspace_id": 4, "thread_id": 5, "max_tokens_per_task": 100000,
+                  "tokens_used": 4200 },
+                { "workspace_id": 4, "thread_id": 6, "tokens_used": 99 },
+                { "workspace_id": 0, "thread_id": 7, "turn_token_budget": 10 }
+            ]
+        })
+        .to_string();
+        let (projects, tasks, tokens_used) = load_turn_limits(Some(&raw));
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[&crate::ProjectId(1)].turn_timeout_secs, Some(600));
+
+        let task = (WorkspaceId::from_u64(4), WorkspaceThreadId::from_u64(5));
+        let other = (WorkspaceId::from_u64(4), WorkspaceThreadId::from_u64(6));
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[&task].task_token_budget, Some(100_000));
+        assert_eq!(tokens_used[&task], 4200);
+        assert_eq!(tokens_used[&other], 99);
+        assert!(load_turn_limits(Some("not json")).0.is_empty());
+    }
 }
@@ -3,7 +3,7 @@ use crate::{
     AppState, PersistedAppState, PersistedProject, PersistedWorkspace,
     PersistedWorkspaceThreadRunConfigOverride,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub(crate) fn to_persisted_app_state(state: &AppState) -> PersistedAppState {
     let mut workspace_active_thread_id = HashMap::new();
@@ -48,6 +48,7 @@ pub(crate) fn to_persisted_app_state(state: &AppState) -> PersistedAppState {
                         worktree_path: w.worktree_path.clone(),
                         status: w.status,
                         last_activity_at_unix_seconds: w.last_activity_at.and_then(unix_seconds),
+                        archived_at_unix_seconds: w.archived_at.and_then(unix_seconds),
                     })
                     .collect(),
             })
@@ -127,6 +128,16 @@ pub(crate) fn to_persisted_app_state(state: &AppState) -> PersistedAppState {
         telegram_bot_username: state.telegram_bot_username.clone(),
         telegram_paired_chat_id: state.telegram_paired_chat_id,
         telegram_topic_bindings: serialize_telegram_topic_bindings(&state.telegram_topic_bindings),
+        telegram_group_chats: serialize_telegram_group_chats(&state.telegram_group_chats),
+        notification_settings: serialize_notification_settings(&state.notification_settings),
+        attachment_text_limits: serialize_attachment_text_limits(state.attachment_text_limits),
+        url_attachment_settings: serialize_url_attachment_settings(&state.url_attachment_settings),
+        task_issue_links: serialize_task_issue_links(&state.task_issue_links),
+        deletion_settings: serialize_deletion_settings(state.deletion_settings),
+        deleted_projects: serialize_deleted_projects(&state.deleted_projects),
+        turn_limits: serialize_turn_limits(state),
+        task_review_comments: serialize_task_review_comments(state),
+        task_ci_auto_fix: serialize_task_ci_auto_fix(&state.task_ci_auto_fix),
     }
 }
 
@@ -141,3 +152,146 @@ fn serialize_telegram_topic_bindings(
     list.sort_by_key(|b| b.message_thread_id);
     serde_json::to_string(&list).ok()
 }
+
+fn serialize_notification_settings(settings: &crate::NotificationSettings) -> Option<String> {
+    if *settings == crate::NotificationSettings::default() {
+        return None;
+    }
+    serde_json::to_string(settings).ok()
+}
+
+fn serialize_attachment_text_limits(limits: crate::AttachmentTextLimits) -> Option<String> {
+    if limits == crate::AttachmentTextLimits::default() {
+        return None;
+    }
+    serde_json::to_string(&limits).ok()
+}
+
+fn serialize_url_attachment_settings(settings: &crate::UrlAttachmentSettings) -> Option<String> {
+    if settings.allowed_domains.is_empty() {
+        return None;
+    }
+    serde_json::to_string(settings).ok()
+}
+
+fn serialize_task_issue_links(
+    links: &HashMap<(crate::WorkspaceId, crate::WorkspaceThreadId), crate::TaskIssueLink>,
+) -> Option<String> {
+    if links.is_empty() {
+        return None;
+    }
+
+    let mut list = links.values().cloned().collect::<Vec<_>>();
+    list.sort_by_key(|l| (l.workspace_id, l.thread_id));
+    serde_json::to_string(&list).ok()
+}
+
+fn serialize_deletion_settings(settings: crate::DeletionSettings) -> Option<String> {
+    if settings == crate::DeletionSettings::default() {
+        return None;
+    }
+    serde_json::to_string(&settings).ok()
+}
+
+fn serialize_turn_limits(state: &AppState) -> Option<String> {
+    let mut projects = state
+        .project_turn_limits
+        .iter()
+        .map(|(project_id, limits)| crate::ProjectTurnLimitsRecord {
+            project_id: project_id.0,
+            limits: *limits,
+        })
+        .collect::<Vec<_>>();
+    let mut tasks = state
+        .task_turn_limits
+        .keys()
+        .chain(state.task_tokens_used.keys())
+        .copied()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|(workspace_id, thread_id)| crate::TaskTurnLimitsRecord {
+            workspace_id: workspace_id.0,
+            thread_id: thread_id.0,
+            limits: state.task_turn_limits(workspace_id, thread_id),
+            tokens_used: state.task_tokens_used(workspace_id, thread_id),
+        })
+        .collect::<Vec<_>>();
+    if projects.is_empty() && tasks.is_empty() {
+        return None;
+    }
+    projects.sort_by_key(|r| r.project_id);
+    tasks.sort_by_key(|r| (r.workspace_id, r.thread_id));
+    serde_json::to_string(&crate::TurnLimitsStore { projects, tasks }).ok()
+}
+
+fn serialize_task_review_comments(state: &AppState) -> Option<String> {
+    let keys = state
+        .task_review_comments
+        .iter()
+        .filter(|(_, comments)| !comments.is_empty())
+        .map(|(key, _)| key)
+        .chain(state.task_pull_request_review_sync.keys())
+        .map(|(workspace_id, thread_id)| (workspace_id.0, thread_id.0))
+        .collect::<std::collections::BTreeSet<_>>();
+    if keys.is_empty() {
+        return None;
+    }
+    let records = keys
+        .into_iter()
+        .map(|(workspace_id, thread_id)| {
+            let key = (
+                crate::WorkspaceId(workspace_id),
+                crate::WorkspaceThreadId(thread_id),
+            );
+            crate::TaskReviewCommentsRecord {
+                workspace_id,
+                thread_id,
+                comments: state.task_review_comments(key.0, key.1).to_vec(),
+                pull_request_sync: state.task_pull_request_review_sync.get(&key).copied(),
+            }
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string(&records).ok()
+}
+
+fn serialize_task_ci_auto_fix(
+    fixes: &HashMap<(crate::WorkspaceId, crate::WorkspaceThreadId), crate::CiAutoFix>,
+) -> Option<String> {
+    if fixes.is_empty() {
+        return None;
+    }
+    let mut records = fixes
+        .iter()
+        .map(
+            |((workspace_id, thread_id), fix)| crate::TaskCiAutoFixRecord {
+                workspace_id: workspace_id.0,
+                thread_id: thread_id.0,
+                fix: fix.clone(),
+            },
+        )
+        .collect::<Vec<_>>();
+    records.sort_by_key(|r| (r.workspace_id, r.thread_id));
+    serde_json::to_string(&records).ok()
+}
+
+fn serialize_deleted_projects(projects: &[crate::DeletedProject]) -> Option<String> {
+    if projects.is_empty() {
+        return None;
+    }
+    serde_json::to_string(projects).ok()
+}
+
+fn serialize_telegram_group_chats(
+    groups: &HashMap<i64, crate::TelegramGroupChat>,
+) -> Option<String> {
+    if groups.is_empty() {
+        return None;
+    }
+
+    let mut list = groups.values().cloned().collect::<Vec<_>>();
+    list.sort_by_key(|g| g.chat_id);
+    for group in &mut list {
+        group.topic_bindings.sort_by_key(|b| b.message_thread_id);
+    }
+    serde_json::to_string(&list).ok()
+}
@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::TaskIntentKind;
+
+/// Prefix for per-project custom variables, e.g. `{{vars.review_checklist}}`.
+pub const CUSTOM_PROMPT_VARIABLE_PREFIX: &str = "vars.";
+
+/// Variables available to task prompt templates.
+pub const TASK_PROMPT_VARIABLES: &[&str] = &[
+    "task_input",
+    "intent_label",
+    "known_context",
+    "project_name",
+    "project_path",
+    "branch",
+    "base_ref",
+    "changed_files",
+    "pr_url",
+    "issue",
+    "task_document",
+    "plan_document",
+    "memory_document",
+];
+
+/// Variables available to system prompt templates.
+pub const SYSTEM_PROMPT_VARIABLES: &[&str] = &["task_input", "context_json"];
+
+/// Per-project prompt configuration: template overrides layered on top of the global templates,
+/// plus custom variables exposed as `{{vars.<name>}}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectPromptSettings {
+    pub task_prompt_templates: HashMap<TaskIntentKind, String>,
+    pub variables: BTreeMap<String, String>,
+}
+
+impl ProjectPromptSettings {
+    pub fn is_empty(&self) -> bool {
+        self.task_prompt_templates.is_empty() && self.variables.is_empty()
+    }
+}
+
+pub fn is_valid_custom_prompt_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+/// Returns the distinct `{{name}}` placeholders referenced by `template`, in order of first use.
+pub fn prompt_template_variables(template: &str) -> Vec<&str> {
+    let mut out: Vec<&str> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        if !name.is_empty() && !name.contains('{') && !out.contains(&name) {
+            out.push(name);
+        }
+        rest = &after[end + 2..];
+    }
+    out
+}
+
+/// Returns placeholders in `template` that are neither in `allowed` nor a custom variable.
+///
+/// When `custom` is provided, custom variables must also be defined there; otherwise any
+/// well-formed `vars.<name>` placeholder is accepted.
+pub fn unknown_prompt_template_variables(
+    template: &str,
+    allowed: &[&str],
+    custom: Option<&BTreeMap<String, String>>,
+) -> Vec<String> {
+    prompt_template_variables(template)
+        .into_iter()
+        .filter(|name| {
+            if allowed.contains(name) {
+                return false;
+            }
+            let Some(custom_name) = name.strip_prefix(CUSTOM_PROMPT_VARIABLE_PREFIX) else {
+                return true;
+            };
+            if !is_valid_custom_prompt_variable_name(custom_name) {
+                return true;
+            }
+            custom.is_some_and(|vars| !vars.contains_key(custom_name))
+        })
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Substitutes `{{name}}` placeholders in a single pass.
+///
+/// Placeholders listed in `known` but missing from `values` render as an empty string; anything
+/// else is left untouched so substituted values are never re-expanded.
+pub fn render_prompt_template(
+    template: &str,
+    known: &[&str],
+    values: &HashMap<String, String>,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = after[..end].trim();
+        match values.get(name) {
+            Some(value) => out.push_str(value),
+            None if known.contains(&name) || name.starts_with(CUSTOM_PROMPT_VARIABLE_PREFIX) => {}
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_variables_are_deduplicated_in_order() {
+        let vars = prompt_template_variables("{{a}} {{ b }} {{a}} {{vars.c}} {{");
+        assert_eq!(vars, vec!["a", "b", "vars.c"]);
+    }
+
+    #[test]
+    fn unknown_variables_respect_custom_definitions() {
+        let template = "{{task_input}} {{nope}} {{vars.checklist}} {{vars.Bad}}";
+        assert_eq!(
+            unknown_prompt_template_variables(template, TASK_PROMPT_VARIABLES, None),
+            vec!["nope".to_owned(), "vars.Bad".to_owned()]
+        );
+
+        let custom = BTreeMap::from([("other".to_owned(), "x".to_owned())]);
+        assert_eq!(
+            unknown_prompt_template_variables(template, TASK_PROMPT_VARIABLES, Some(&custom)),
+            vec![
+                "nope".to_owned(),
+                "vars.checklist".to_owned(),
+                "vars.Bad".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn render_does_not_reexpand_substituted_values() {
+        let values = HashMap::from([
+            ("task_input".to_owned(), "literal {{branch}}".to_owned()),
+            ("vars.team".to_owned(), "infra".to_owned()),
+        ]);
+        let out = render_prompt_template(
+            "{{task_input}} | {{branch}} | {{vars.team}} | {{vars.missing}} | {{other}}",
+            TASK_PROMPT_VARIABLES,
+            &values,
+        );
+        assert_eq!(out, "literal {{branch}} |  | infra |  | {{other}}");
+    }
+}
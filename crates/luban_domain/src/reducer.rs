@@ -1,17 +1,21 @@
 use crate::persistence;
-use crate::state::{apply_draft_text_diff, entries_is_prefix, entries_is_suffix};
+use crate::state::{
+    apply_draft_text_diff, entries_is_prefix, entries_is_suffix, is_tool_call_item,
+};
 use crate::{
     Action, AgentRunConfig, AppState, AttachmentRef, CodexThreadEvent, ConversationEntry,
-    DraftAttachment, Effect, MainPane, OperationStatus, PersistedAppState, Project, ProjectId,
-    QueuedPrompt, RightPane, ThinkingEffort, Workspace, WorkspaceConversation, WorkspaceId,
-    WorkspaceStatus, WorkspaceTabs, WorkspaceThreadId, default_agent_model_id,
-    default_system_prompt_template, default_system_prompt_templates, default_task_prompt_template,
-    default_task_prompt_templates, default_thinking_effort, normalize_thinking_effort,
-    thinking_effort_supported,
+    DraftAttachment, Effect, MainPane, ModelCatalog, OperationStatus, PersistedAppState, Project,
+    ProjectId, ProjectPromptSettings, QueuedPrompt, RightPane, SYSTEM_PROMPT_VARIABLES,
+    TASK_PROMPT_VARIABLES, TaskIntentKind, ThinkingEffort, TurnLimitKind, Workspace,
+    WorkspaceConversation, WorkspaceId, WorkspaceStatus, WorkspaceTabs, WorkspaceThreadId,
+    default_agent_model_id, default_system_prompt_template, default_system_prompt_templates,
+    default_task_prompt_template, default_task_prompt_templates, default_thinking_effort,
+    is_valid_custom_prompt_variable_name, unknown_prompt_template_variables,
 };
 use std::collections::VecDeque;
+use std::sync::Arc;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     path::PathBuf,
 };
 
@@ -30,6 +34,9 @@ fn now_unix_ms() -> u64 {
         .unwrap_or(0)
 }
 
+const TASK_TOKEN_BUDGET_EXHAUSTED: &str =
+    "Task token budget exhausted; raise the task's token limit to continue";
+
 fn cancel_running_turn(conversation: &mut WorkspaceConversation) -> Option<u64> {
     if conversation.run_status != OperationStatus::Running {
         return None;
@@ -49,6 +56,69 @@ fn cancel_running_turn(conversation: &mut WorkspaceConversation) -> Option<u64>
     Some(run_id)
 }
 
+fn push_turn_limit_reached(
+    conversation: &mut WorkspaceConversation,
+    kind: TurnLimitKind,
+    limit: u64,
+    observed: u64,
+) {
+    conversation.push_entry(ConversationEntry::SystemEvent {
+        entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+        created_at_unix_ms: now_unix_ms(),
+        event: crate::ConversationSystemEvent::TurnLimitReached {
+            kind,
+            limit,
+            observed,
+        },
+    });
+}
+
+/// Cancels the running turn once it has made more tool calls than `max_tool_calls` allows.
+fn enforce_tool_call_limit(
+    conversation: &mut WorkspaceConversation,
+    workspace_id: WorkspaceId,
+    thread_id: WorkspaceThreadId,
+    max_tool_calls: Option<u64>,
+) -> Vec<Effect> {
+    let Some(max) = max_tool_calls else {
+        return Vec::new();
+    };
+    let observed = conversation.current_turn_tool_calls();
+    if observed <= max {
+        return Vec::new();
+    }
+    push_turn_limit_reached(conversation, TurnLimitKind::ToolCalls, max, observed);
+    let Some(run_id) = cancel_running_turn(conversation) else {
+        return Vec::new();
+    };
+    vec![Effect::CancelAgentTurn {
+        workspace_id,
+        thread_id,
+        run_id,
+    }]
+}
+
+fn unknown_prompt_variables_error(unknown: &[String]) -> Option<String> {
+    if unknown.is_empty() {
+        return None;
+    }
+    let names = unknown
+        .iter()
+        .map(|name| format!("{{{{{name}}}}}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!("Unknown template variables: {names}"))
+}
+
+fn system_prompt_template_error(template: &str) -> Option<String> {
+    let unknown = unknown_prompt_template_variables(
+        template,
+        SYSTEM_PROMPT_VARIABLES,
+        Some(&BTreeMap::new()),
+    );
+    unknown_prompt_variables_error(&unknown)
+}
+
 fn runner_is_enabled(state: &AppState, runner: crate::AgentRunnerKind) -> bool {
     match runner {
         crate::AgentRunnerKind::Codex => state.agent_codex_enabled,
@@ -81,7 +151,7 @@ fn resolve_enabled_runner(state: &AppState) -> crate::AgentRunnerKind {
 
 /// Resolve runner + model for `CreateWorkspaceThread`.
 ///
-/// When `model_id` is `Some`, infer its runner via `runner_for_model()`; if
+/// When `model_id` is `Some`, infer its runner from the model catalog; if
 /// that runner is enabled, use it with the requested model. Otherwise fall
 /// back to the standard `resolve_enabled_runner` + default model path.
 fn resolve_runner_and_model_for_create(
@@ -89,7 +159,7 @@ fn resolve_runner_and_model_for_create(
     model_id: Option<String>,
 ) -> (crate::AgentRunnerKind, String) {
     if let Some(ref mid) = model_id {
-        if let Some(inferred_runner) = crate::runner_for_model(mid)
+        if let Some(inferred_runner) = state.model_catalog.runner_for_model(mid)
             && runner_is_enabled(state, inferred_runner)
         {
             return (inferred_runner, mid.clone());
@@ -98,7 +168,10 @@ fn resolve_runner_and_model_for_create(
         // Fall back to the enabled runner; if the model is still valid for
         // that runner, keep it; otherwise use the runner's default model.
         let fallback_runner = resolve_enabled_runner(state);
-        if crate::model_valid_for_runner(fallback_runner, mid) {
+        if state
+            .model_catalog
+            .model_valid_for_runner(fallback_runner, mid)
+        {
             return (fallback_runner, mid.clone());
         }
     }
@@ -237,6 +310,8 @@ impl AppState {
             workspace_thread_run_config_overrides: HashMap::new(),
             task_prompt_templates: default_task_prompt_templates(),
             system_prompt_templates: default_system_prompt_templates(),
+            project_prompt_settings: HashMap::new(),
+            model_catalog: Arc::new(ModelCatalog::builtin()),
             telegram_enabled: false,
             telegram_bot_token: None,
             telegram_bot_username: None,
@@ -244,6 +319,19 @@ impl AppState {
             telegram_config_rev: 0,
             telegram_last_error: None,
             telegram_topic_bindings: HashMap::new(),
+            telegram_group_chats: HashMap::new(),
+            notification_settings: crate::NotificationSettings::default(),
+            attachment_text_limits: crate::AttachmentTextLimits::default(),
+            url_attachment_settings: crate::UrlAttachmentSettings::default(),
+            task_issue_links: HashMap::new(),
+            deletion_settings: crate::DeletionSettings::default(),
+            deleted_projects: Vec::new(),
+            project_turn_limits: HashMap::new(),
+            task_turn_limits: HashMap::new(),
+            task_tokens_used: HashMap::new(),
+            task_review_comments: HashMap::new(),
+            task_pull_request_review_sync: HashMap::new(),
+            task_ci_auto_fix: HashMap::new(),
         }
     }
 
@@ -368,6 +456,49 @@ impl AppState {
                 vec![Effect::SaveAppState]
             }
             Action::DeleteProject { project_id } => self.delete_project(project_id),
+            Action::ProjectRestored { project_id } => self.restore_deleted_project(project_id),
+            Action::DeletedProjectsPurged { before_unix_ms } => {
+                let before = self.deleted_projects.len();
+                self.deleted_projects
+                    .retain(|deleted| deleted.deleted_at_unix_ms > before_unix_ms);
+                if self.deleted_projects.len() == before {
+                    return Vec::new();
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::DeletionRetentionChanged { retention_days } => {
+                let settings = crate::DeletionSettings { retention_days };
+                if !settings.is_valid() {
+                    self.last_error = Some("Invalid deletion retention".to_owned());
+                    return Vec::new();
+                }
+                if self.deletion_settings == settings {
+                    return Vec::new();
+                }
+                self.deletion_settings = settings;
+                if !settings.soft_delete_enabled() {
+                    self.deleted_projects.clear();
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::ProjectTurnLimitsChanged { project_id, limits } => {
+                if !limits.is_valid() {
+                    self.last_error = Some("Invalid turn limits".to_owned());
+                    return Vec::new();
+                }
+                if !self.projects.iter().any(|p| p.id == project_id) {
+                    return Vec::new();
+                }
+                if self.project_turn_limits(project_id) == limits {
+                    return Vec::new();
+                }
+                if limits.is_empty() {
+                    self.project_turn_limits.remove(&project_id);
+                } else {
+                    self.project_turn_limits.insert(project_id, limits);
+                }
+                vec![Effect::SaveAppState]
+            }
             Action::OpenProjectSettings { project_id } => {
                 self.main_pane = MainPane::ProjectSettings(project_id);
                 self.right_pane = RightPane::None;
@@ -559,6 +690,7 @@ impl AppState {
                     let workspace = &mut self.projects[project_idx].workspaces[workspace_idx];
                     workspace.archive_status = OperationStatus::Idle;
                     workspace.status = WorkspaceStatus::Archived;
+                    workspace.archived_at = Some(std::time::SystemTime::now());
                 }
                 if self.last_open_workspace_id == Some(workspace_id) {
                     self.last_open_workspace_id = None;
@@ -767,6 +899,7 @@ impl AppState {
                 let default_amp_mode = self.agent_amp_mode.clone();
                 let mut snapshot = snapshot;
                 snapshot.ensure_entry_ids();
+                let model_catalog = self.model_catalog.clone();
                 let conversation = self.ensure_conversation_mut(workspace_id, thread_id);
                 let local_has_non_system_entries = conversation
                     .entries
@@ -816,12 +949,12 @@ impl AppState {
                     if let Some(model_id) = snapshot_model_id {
                         let effort =
                             snapshot_thinking_effort.unwrap_or(conversation.thinking_effort);
-                        let normalized = normalize_thinking_effort(&model_id, effort);
+                        let normalized = model_catalog.normalize_thinking_effort(&model_id, effort);
                         conversation.agent_model_id = model_id;
                         conversation.thinking_effort = normalized;
                     } else if let Some(effort) = snapshot_thinking_effort {
-                        conversation.thinking_effort =
-                            normalize_thinking_effort(&conversation.agent_model_id, effort);
+                        conversation.thinking_effort = model_catalog
+                            .normalize_thinking_effort(&conversation.agent_model_id, effort);
                     }
                 }
 
@@ -920,6 +1053,7 @@ impl AppState {
                 thread_id,
                 text,
                 attachments,
+                author,
                 runner,
                 amp_mode,
             } => {
@@ -927,6 +1061,7 @@ impl AppState {
                 let tabs = self.ensure_workspace_tabs_mut(workspace_id);
                 tabs.activate(thread_id);
 
+                let budget_exhausted = self.task_token_budget_exhausted(workspace_id, thread_id);
                 let conversation = self.ensure_conversation_mut(workspace_id, thread_id);
                 if matches!(
                     conversation.task_status,
@@ -935,6 +1070,10 @@ impl AppState {
                     self.last_error = Some("Task is archived".to_owned());
                     return Vec::new();
                 }
+                if budget_exhausted && conversation.run_status != OperationStatus::Running {
+                    self.last_error = Some(TASK_TOKEN_BUDGET_EXHAUSTED.to_owned());
+                    return Vec::new();
+                }
                 conversation.draft.clear();
                 conversation.draft_attachments.clear();
 
@@ -998,6 +1137,7 @@ impl AppState {
                         id,
                         text,
                         attachments,
+                        author,
                         run_config,
                     });
                     return task_status_effects;
@@ -1011,6 +1151,7 @@ impl AppState {
                         thread_id,
                         text,
                         attachments,
+                        author,
                         run_config,
                     ));
                     return effects;
@@ -1026,6 +1167,7 @@ impl AppState {
                         thread_id,
                         text,
                         attachments,
+                        author,
                         run_config,
                     ));
                     if should_auto_title {
@@ -1053,6 +1195,7 @@ impl AppState {
                     id,
                     text,
                     attachments,
+                    author,
                     run_config,
                 });
                 let mut effects = task_status_effects;
@@ -1123,6 +1266,7 @@ impl AppState {
                     text,
                     attachments,
                     run_config,
+                    author: None,
                 });
                 Vec::new()
             }
@@ -1133,9 +1277,10 @@ impl AppState {
             } => {
                 let default_amp_mode = self.agent_amp_mode.clone();
                 let (thinking_effort, runner, amp_mode) = {
+                    let model_catalog = self.model_catalog.clone();
                     let conversation = self.ensure_conversation_mut(workspace_id, thread_id);
-                    let normalized =
-                        normalize_thinking_effort(&model_id, conversation.thinking_effort);
+                    let normalized = model_catalog
+                        .normalize_thinking_effort(&model_id, conversation.thinking_effort);
                     conversation.run_config_overridden_by_user = true;
                     conversation.agent_model_id = model_id.clone();
                     conversation.thinking_effort = normalized;
@@ -1182,6 +1327,7 @@ impl AppState {
                 // the conversation mutably (avoids double borrow on self).
                 let runner_default_model = self.resolve_default_model_for_runner(runner);
                 let (model_id, thinking_effort, amp_mode) = {
+                    let model_catalog = self.model_catalog.clone();
                     let conversation = self.ensure_conversation_mut(workspace_id, thread_id);
                     conversation.run_config_overridden_by_user = true;
                     conversation.agent_runner = runner;
@@ -1191,9 +1337,9 @@ impl AppState {
                     // Reason: When switching runners, the current model may not exist
                     // in the target runner's catalog (e.g. gpt-5.2-codex is Codex-only).
                     // Use the per-runner default so Droid gets the user's last choice.
-                    if !crate::model_valid_for_runner(runner, &conversation.agent_model_id) {
+                    if !model_catalog.model_valid_for_runner(runner, &conversation.agent_model_id) {
                         conversation.agent_model_id = runner_default_model;
-                        conversation.thinking_effort = normalize_thinking_effort(
+                        conversation.thinking_effort = model_catalog.normalize_thinking_effort(
                             &conversation.agent_model_id,
                             conversation.thinking_effort,
                         );
@@ -1280,8 +1426,11 @@ impl AppState {
             } => {
                 let default_amp_mode = self.agent_amp_mode.clone();
                 let (runner, model_id, thinking_effort, amp_mode) = {
+                    let model_catalog = self.model_catalog.clone();
                     let conversation = self.ensure_conversation_mut(workspace_id, thread_id);
-                    if !thinking_effort_supported(&conversation.agent_model_id, thinking_effort) {
+                    if !model_catalog
+                        .thinking_effort_supported(&conversation.agent_model_id, thinking_effort)
+                    {
                         return Vec::new();
                     }
                     conversation.run_config_overridden_by_user = true;
@@ -1443,6 +1592,7 @@ impl AppState {
                 model_id,
                 thinking_effort,
             } => {
+                let model_catalog = self.model_catalog.clone();
                 let conversation = self.ensure_conversation_mut(workspace_id, thread_id);
                 let Some(pos) = conversation
                     .pending_prompts
@@ -1458,7 +1608,8 @@ impl AppState {
                     return Vec::new();
                 }
 
-                let normalized_effort = normalize_thinking_effort(&model_id, thinking_effort);
+                let normalized_effort =
+                    model_catalog.normalize_thinking_effort(&model_id, thinking_effort);
                 let entry = conversation.pending_prompts.get_mut(pos).unwrap();
                 entry.text = trimmed;
                 entry.attachments = attachments;
@@ -1484,6 +1635,10 @@ impl AppState {
                 workspace_id,
                 thread_id,
             } => {
+                if self.task_token_budget_exhausted(workspace_id, thread_id) {
+                    self.last_error = Some(TASK_TOKEN_BUDGET_EXHAUSTED.to_owned());
+                    return Vec::new();
+                }
                 let conversation = self.ensure_conversation_mut(workspace_id, thread_id);
                 conversation.queue_paused = false;
                 start_next_queued_prompt(conversation, workspace_id, thread_id)
@@ -1507,6 +1662,79 @@ impl AppState {
                 conversation.run_finished_at_unix_ms = None;
                 Vec::new()
             }
+            Action::TurnCheckpointCreated {
+                workspace_id,
+                thread_id,
+                checkpoint_id,
+                commit,
+            } => {
+                let Some(conversation) = self.conversations.get_mut(&(workspace_id, thread_id))
+                else {
+                    return Vec::new();
+                };
+                conversation.push_entry(ConversationEntry::SystemEvent {
+                    entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+                    created_at_unix_ms: now_unix_ms(),
+                    event: crate::ConversationSystemEvent::TurnCheckpoint {
+                        checkpoint_id,
+                        commit,
+                    },
+                });
+                Vec::new()
+            }
+            Action::TurnChangesRecorded {
+                workspace_id,
+                thread_id,
+                checkpoint_id,
+                commit,
+                files_changed,
+                additions,
+                deletions,
+            } => {
+                let Some(conversation) = self.conversations.get_mut(&(workspace_id, thread_id))
+                else {
+                    return Vec::new();
+                };
+                conversation.push_entry(ConversationEntry::SystemEvent {
+                    entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+                    created_at_unix_ms: now_unix_ms(),
+                    event: crate::ConversationSystemEvent::TurnChanges {
+                        checkpoint_id,
+                        commit,
+                        files_changed,
+                        additions,
+                        deletions,
+                    },
+                });
+                Vec::new()
+            }
+            Action::TaskRewound {
+                workspace_id,
+                thread_id,
+                checkpoint_id,
+                snapshot,
+            } => {
+                let Some(conversation) = self.conversations.get_mut(&(workspace_id, thread_id))
+                else {
+                    return Vec::new();
+                };
+                let truncated = snapshot.is_some();
+                if let Some(mut snapshot) = snapshot {
+                    snapshot.ensure_entry_ids();
+                    conversation.reset_entries_from_snapshot(snapshot);
+                    // The runner session remembers the dropped turns; start a fresh one.
+                    conversation.thread_id = None;
+                }
+                conversation.push_entry(ConversationEntry::SystemEvent {
+                    entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+                    created_at_unix_ms: now_unix_ms(),
+                    event: crate::ConversationSystemEvent::TurnRewound {
+                        checkpoint_id,
+                        truncated,
+                    },
+                });
+                Vec::new()
+            }
             Action::AgentRunFinishedAt {
                 workspace_id,
                 thread_id,
@@ -1533,8 +1761,11 @@ impl AppState {
                 let agent_amp_enabled = self.agent_amp_enabled;
                 let agent_claude_enabled = self.agent_claude_enabled;
                 let agent_droid_enabled = self.agent_droid_enabled;
+                let limits = self.effective_turn_limits(workspace_id, thread_id);
+                let task_tokens_before = self.task_tokens_used(workspace_id, thread_id);
+                let mut task_tokens_after: Option<u64> = None;
                 let mut last_error_message: Option<String> = None;
-                let effects = {
+                let mut effects = {
                     let conversation = self.ensure_conversation_mut(workspace_id, thread_id);
                     match event {
                         CodexThreadEvent::ThreadStarted { thread_id } => {
@@ -1548,7 +1779,34 @@ impl AppState {
                             if conversation.active_run_id != Some(run_id) {
                                 return Vec::new();
                             }
-                            let _ = usage;
+                            let turn_tokens =
+                                usage.input_tokens.saturating_add(usage.output_tokens);
+                            let tokens_used = task_tokens_before.saturating_add(turn_tokens);
+                            if turn_tokens > 0 {
+                                task_tokens_after = Some(tokens_used);
+                            }
+                            if let Some(max) = limits.turn_token_budget
+                                && turn_tokens > max
+                            {
+                                push_turn_limit_reached(
+                                    conversation,
+                                    TurnLimitKind::TurnTokens,
+                                    max,
+                                    turn_tokens,
+                                );
+                                conversation.queue_paused = true;
+                            }
+                            if let Some(max) = limits.task_token_budget
+                                && tokens_used >= max
+                            {
+                                push_turn_limit_reached(
+                                    conversation,
+                                    TurnLimitKind::TaskTokens,
+                                    max,
+                                    tokens_used,
+                                );
+                                conversation.queue_paused = true;
+                            }
                             let finished_run_config = conversation
                                 .current_run_config
                                 .clone()
@@ -1562,36 +1820,33 @@ impl AppState {
                             conversation.current_run_config = None;
                             let next =
                                 start_next_queued_prompt(conversation, workspace_id, thread_id);
-                            if let Some(effect) = next {
-                                return vec![effect];
-                            }
-
-                            if !matches!(
-                                conversation.task_status,
-                                crate::TaskStatus::Iterating | crate::TaskStatus::Validating
-                            ) {
-                                return Vec::new();
-                            }
                             let runner_enabled = match finished_run_config.runner {
                                 crate::AgentRunnerKind::Codex => agent_codex_enabled,
                                 crate::AgentRunnerKind::Amp => agent_amp_enabled,
                                 crate::AgentRunnerKind::Claude => agent_claude_enabled,
                                 crate::AgentRunnerKind::Droid => agent_droid_enabled,
                             };
-                            if !runner_enabled {
-                                return Vec::new();
+                            if let Some(effect) = next {
+                                vec![effect]
+                            } else if !runner_enabled
+                                || !matches!(
+                                    conversation.task_status,
+                                    crate::TaskStatus::Iterating | crate::TaskStatus::Validating
+                                )
+                            {
+                                Vec::new()
+                            } else {
+                                vec![Effect::AiAutoUpdateTaskStatus {
+                                    workspace_id,
+                                    thread_id,
+                                    input: task_status_auto_update_input(conversation, "completed"),
+                                    expected_current_task_status: conversation.task_status,
+                                    runner: finished_run_config.runner,
+                                    model_id: finished_run_config.model_id.clone(),
+                                    thinking_effort: finished_run_config.thinking_effort,
+                                    amp_mode: finished_run_config.amp_mode.clone(),
+                                }]
                             }
-
-                            vec![Effect::AiAutoUpdateTaskStatus {
-                                workspace_id,
-                                thread_id,
-                                input: task_status_auto_update_input(conversation, "completed"),
-                                expected_current_task_status: conversation.task_status,
-                                runner: finished_run_config.runner,
-                                model_id: finished_run_config.model_id.clone(),
-                                thinking_effort: finished_run_config.thinking_effort,
-                                amp_mode: finished_run_config.amp_mode.clone(),
-                            }]
                         }
                         CodexThreadEvent::TurnDuration { duration_ms } => {
                             if conversation.active_run_id != Some(run_id) {
@@ -1659,19 +1914,23 @@ impl AppState {
                             }
                         }
                         CodexThreadEvent::ItemStarted { item }
-                        | CodexThreadEvent::ItemUpdated { item } => {
+                        | CodexThreadEvent::ItemUpdated { item }
+                        | CodexThreadEvent::ItemCompleted { item } => {
                             if conversation.active_run_id != Some(run_id) {
                                 return Vec::new();
                             }
+                            let is_tool_call = is_tool_call_item(&item);
                             conversation.push_codex_item(item);
-                            Vec::new()
-                        }
-                        CodexThreadEvent::ItemCompleted { item } => {
-                            if conversation.active_run_id != Some(run_id) {
-                                return Vec::new();
+                            if is_tool_call {
+                                enforce_tool_call_limit(
+                                    conversation,
+                                    workspace_id,
+                                    thread_id,
+                                    limits.max_tool_calls_per_turn,
+                                )
+                            } else {
+                                Vec::new()
                             }
-                            conversation.push_codex_item(item);
-                            Vec::new()
                         }
                         CodexThreadEvent::Error { message } => {
                             if conversation.active_run_id != Some(run_id) {
@@ -1696,6 +1955,11 @@ impl AppState {
                 if let Some(message) = last_error_message {
                     self.last_error = Some(message);
                 }
+                if let Some(tokens_used) = task_tokens_after {
+                    self.task_tokens_used
+                        .insert((workspace_id, thread_id), tokens_used);
+                    effects.push(Effect::SaveAppState);
+                }
 
                 effects
             }
@@ -1745,105 +2009,574 @@ impl AppState {
                     run_id,
                 }]
             }
-            Action::CreateWorkspaceThread {
+            Action::AgentTurnTimedOut {
                 workspace_id,
-                model_id,
-                thinking_effort,
+                thread_id,
+                run_id,
+                timeout_secs,
             } => {
-                let thread_id = {
-                    let tabs = self.ensure_workspace_tabs_mut(workspace_id);
-                    tabs.allocate_thread_id()
+                let Some(conversation) = self.conversations.get_mut(&(workspace_id, thread_id))
+                else {
+                    return Vec::new();
                 };
-                // Reason: When a model_id is provided, infer its runner and
-                // use it if enabled; otherwise fall back to defaults.
-                let (effective_runner, effective_model) =
-                    resolve_runner_and_model_for_create(self, model_id);
-                let effective_effort =
-                    thinking_effort.unwrap_or(self.agent_default_thinking_effort);
-                // Reason: Normalize effort for the chosen model — Droid models
-                // ignore the reasoning flag so we clamp to their supported set.
-                let effective_effort =
-                    crate::normalize_thinking_effort(&effective_model, effective_effort);
-                let mut conversation = Self::default_conversation_with_defaults(
-                    thread_id,
-                    effective_model,
-                    effective_effort,
-                    effective_runner,
+                if conversation.active_run_id != Some(run_id)
+                    || conversation.run_status != OperationStatus::Running
+                {
+                    return Vec::new();
+                }
+                let observed = conversation
+                    .run_started_at_unix_ms
+                    .map(|started| now_unix_ms().saturating_sub(started) / 1000)
+                    .unwrap_or(timeout_secs)
+                    .max(timeout_secs);
+                push_turn_limit_reached(
+                    conversation,
+                    TurnLimitKind::Timeout,
+                    timeout_secs,
+                    observed,
                 );
-                conversation.task_status = crate::TaskStatus::Backlog;
-                conversation.push_entry(ConversationEntry::SystemEvent {
-                    entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
-                    created_at_unix_ms: now_unix_ms(),
-                    event: crate::ConversationSystemEvent::TaskCreated,
-                });
-                self.conversations
-                    .insert((workspace_id, thread_id), conversation);
-                self.ensure_workspace_tabs_mut(workspace_id)
-                    .activate(thread_id);
-                vec![
-                    Effect::SaveAppState,
-                    Effect::EnsureConversation {
-                        workspace_id,
-                        thread_id,
-                    },
-                    Effect::LoadWorkspaceThreads { workspace_id },
-                ]
+                let Some(run_id) = cancel_running_turn(conversation) else {
+                    return Vec::new();
+                };
+                vec![Effect::CancelAgentTurn {
+                    workspace_id,
+                    thread_id,
+                    run_id,
+                }]
             }
-            Action::ActivateWorkspaceThread {
+            Action::TaskTurnLimitsChanged {
                 workspace_id,
                 thread_id,
+                limits,
             } => {
-                if self
-                    .conversations
-                    .get(&(workspace_id, thread_id))
-                    .is_some_and(|c| {
-                        matches!(
-                            c.task_status,
-                            crate::TaskStatus::Done | crate::TaskStatus::Canceled
-                        )
-                    })
+                if !limits.is_valid() {
+                    self.last_error = Some("Invalid turn limits".to_owned());
+                    return Vec::new();
+                }
+                let key = (workspace_id, thread_id);
+                if !self.conversations.contains_key(&key) {
+                    return Vec::new();
+                }
+                if self.task_turn_limits(workspace_id, thread_id) == limits {
+                    return Vec::new();
+                }
+                if limits.is_empty() {
+                    self.task_turn_limits.remove(&key);
+                } else {
+                    self.task_turn_limits.insert(key, limits);
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::AddReviewComment {
+                workspace_id,
+                thread_id,
+                path,
+                start_line,
+                end_line,
+                quote,
+                body,
+            } => {
+                let path = path.trim().trim_start_matches("./").to_owned();
+                let body = body.trim().to_owned();
+                if path.is_empty()
+                    || start_line == 0
+                    || end_line < start_line
+                    || body.is_empty()
+                    || body.chars().count() > crate::ReviewComment::MAX_BODY_CHARS
                 {
-                    self.last_error = Some("Task is archived".to_owned());
+                    self.last_error = Some("Invalid review comment".to_owned());
                     return Vec::new();
                 }
-                let tabs = self.ensure_workspace_tabs_mut(workspace_id);
-                tabs.activate(thread_id);
-                self.ensure_conversation_mut(workspace_id, thread_id);
-                vec![
-                    Effect::SaveAppState,
-                    Effect::LoadConversation {
-                        workspace_id,
-                        thread_id,
-                    },
-                ]
+                let key = (workspace_id, thread_id);
+                if !self.conversations.contains_key(&key) {
+                    self.last_error = Some("Task not found".to_owned());
+                    return Vec::new();
+                }
+                let comments = self.task_review_comments.entry(key).or_default();
+                let id = comments.iter().map(|c| c.id).max().unwrap_or(0) + 1;
+                let now = now_unix_ms();
+                comments.push(crate::ReviewComment {
+                    id,
+                    path,
+                    start_line,
+                    end_line,
+                    quote: quote
+                        .chars()
+                        .take(crate::ReviewComment::MAX_QUOTE_CHARS)
+                        .collect(),
+                    body,
+                    resolved: false,
+                    sent: false,
+                    created_at_unix_ms: now,
+                    updated_at_unix_ms: now,
+                    pull_request: None,
+                });
+                vec![Effect::SaveAppState]
             }
-            Action::CloseWorkspaceThreadTab {
+            Action::EditReviewComment {
                 workspace_id,
                 thread_id,
+                comment_id,
+                body,
             } => {
-                let tabs = self.ensure_workspace_tabs_mut(workspace_id);
-                if tabs.open_tabs.len() <= 1 {
+                let body = body.trim().to_owned();
+                if body.is_empty() || body.chars().count() > crate::ReviewComment::MAX_BODY_CHARS {
+                    self.last_error = Some("Invalid review comment".to_owned());
                     return Vec::new();
                 }
-                let previous_active = tabs.active_tab;
-                tabs.archive_tab(thread_id);
-                let mut effects = vec![
-                    Effect::SaveAppState,
-                    // Clean up any persistent Claude process associated with this thread
-                    Effect::CleanupClaudeProcess {
-                        workspace_id,
-                        thread_id,
-                    },
-                ];
-                if tabs.active_tab != previous_active {
-                    effects.push(Effect::LoadConversation {
-                        workspace_id,
-                        thread_id: tabs.active_tab,
-                    });
+                let Some(comment) = self
+                    .task_review_comments
+                    .get_mut(&(workspace_id, thread_id))
+                    .and_then(|comments| comments.iter_mut().find(|c| c.id == comment_id))
+                else {
+                    self.last_error = Some("Review comment not found".to_owned());
+                    return Vec::new();
+                };
+                if comment.body == body {
+                    return Vec::new();
                 }
-                effects
+                comment.body = body;
+                comment.sent = false;
+                comment.updated_at_unix_ms = now_unix_ms();
+                vec![Effect::SaveAppState]
             }
-            Action::RestoreWorkspaceThreadTab {
+            Action::ResolveReviewComment {
+                workspace_id,
+                thread_id,
+                comment_id,
+                resolved,
+            } => {
+                let Some(comment) = self
+                    .task_review_comments
+                    .get_mut(&(workspace_id, thread_id))
+                    .and_then(|comments| comments.iter_mut().find(|c| c.id == comment_id))
+                else {
+                    self.last_error = Some("Review comment not found".to_owned());
+                    return Vec::new();
+                };
+                if comment.resolved == resolved {
+                    return Vec::new();
+                }
+                comment.resolved = resolved;
+                comment.updated_at_unix_ms = now_unix_ms();
+                vec![Effect::SaveAppState]
+            }
+            Action::ReviewCommentsSent {
+                workspace_id,
+                thread_id,
+                comment_ids,
+            } => {
+                let Some(comments) = self
+                    .task_review_comments
+                    .get_mut(&(workspace_id, thread_id))
+                else {
+                    return Vec::new();
+                };
+                let mut changed = false;
+                for comment in comments
+                    .iter_mut()
+                    .filter(|c| !c.sent && comment_ids.contains(&c.id))
+                {
+                    comment.sent = true;
+                    changed = true;
+                }
+                if !changed {
+                    return Vec::new();
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::PullRequestReviewCommentsFetched {
+                workspace_id,
+                thread_id,
+                pr_number,
+                comments,
+            } => {
+                let key = (workspace_id, thread_id);
+                let Some(conversation) = self.conversations.get_mut(&key) else {
+                    return Vec::new();
+                };
+                let existing = self.task_review_comments.entry(key).or_default();
+                let mut next_id = existing.iter().map(|c| c.id).max().unwrap_or(0) + 1;
+                let now = now_unix_ms();
+                let mut imported = 0usize;
+                for comment in comments {
+                    let already_imported = existing.iter().any(|c| {
+                        c.pull_request.as_ref().is_some_and(|origin| {
+                            origin.pr_number == pr_number && origin.comment_id == comment.comment_id
+                        })
+                    });
+                    if already_imported
+                        || comment.path.trim().is_empty()
+                        || comment.body.trim().is_empty()
+                    {
+                        continue;
+                    }
+                    let comment =
+                        crate::ReviewComment::from_pull_request(next_id, pr_number, comment, now);
+                    let origin = comment.pull_request.as_ref().expect("imported comment");
+                    conversation.push_entry(ConversationEntry::SystemEvent {
+                        entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+                        created_at_unix_ms: now,
+                        event: crate::ConversationSystemEvent::PullRequestReviewComment {
+                            pr_number,
+                            review_comment_id: comment.id,
+                            author: origin.author.clone(),
+                            path: comment.path.clone(),
+                            start_line: comment.start_line,
+                            end_line: comment.end_line,
+                            body: comment.body.clone(),
+                            url: origin.url.clone(),
+                        },
+                    });
+                    existing.push(comment);
+                    next_id += 1;
+                    imported += 1;
+                }
+                if imported == 0 {
+                    if existing.is_empty() {
+                        self.task_review_comments.remove(&key);
+                    }
+                    return Vec::new();
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::PullRequestReviewSyncChanged {
+                workspace_id,
+                thread_id,
+                sync,
+            } => {
+                let key = (workspace_id, thread_id);
+                if !self.conversations.contains_key(&key) {
+                    self.last_error = Some("Task not found".to_owned());
+                    return Vec::new();
+                }
+                let previous = if sync == crate::PullRequestReviewSync::default() {
+                    self.task_pull_request_review_sync.remove(&key)
+                } else {
+                    self.task_pull_request_review_sync.insert(key, sync)
+                };
+                if previous.unwrap_or_default() == sync {
+                    return Vec::new();
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::ReviewCommentReplyPosted {
+                workspace_id,
+                thread_id,
+                comment_id,
+            } => {
+                let Some(origin) = self
+                    .task_review_comments
+                    .get_mut(&(workspace_id, thread_id))
+                    .and_then(|comments| comments.iter_mut().find(|c| c.id == comment_id))
+                    .and_then(|comment| comment.pull_request.as_mut())
+                else {
+                    return Vec::new();
+                };
+                if origin.replied {
+                    return Vec::new();
+                }
+                origin.replied = true;
+                vec![Effect::SaveAppState]
+            }
+            Action::CiAutoFixChanged {
+                workspace_id,
+                thread_id,
+                enabled,
+                max_attempts,
+            } => {
+                if max_attempts == 0 || max_attempts > crate::CiAutoFix::MAX_MAX_ATTEMPTS {
+                    self.last_error = Some("Invalid CI auto-fix attempt budget".to_owned());
+                    return Vec::new();
+                }
+                let key = (workspace_id, thread_id);
+                if !self.conversations.contains_key(&key) {
+                    self.last_error = Some("Task not found".to_owned());
+                    return Vec::new();
+                }
+                match self.task_ci_auto_fix.get_mut(&key) {
+                    Some(fix) if fix.enabled == enabled && fix.max_attempts == max_attempts => {
+                        return Vec::new();
+                    }
+                    Some(fix) if fix.enabled || !enabled => {
+                        fix.enabled = enabled;
+                        fix.max_attempts = max_attempts;
+                    }
+                    _ => {
+                        self.task_ci_auto_fix.insert(
+                            key,
+                            crate::CiAutoFix {
+                                enabled,
+                                max_attempts,
+                                attempts: Vec::new(),
+                            },
+                        );
+                    }
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::CiFixAttemptStarted {
+                workspace_id,
+                thread_id,
+                pr_number,
+                run_id,
+                workflow,
+                url,
+            } => {
+                let key = (workspace_id, thread_id);
+                let Some(fix) = self.task_ci_auto_fix.get_mut(&key) else {
+                    return Vec::new();
+                };
+                if !fix.enabled || !fix.has_attempts_left() || fix.attempted_run(run_id) {
+                    return Vec::new();
+                }
+                let Some(conversation) = self.conversations.get_mut(&key) else {
+                    return Vec::new();
+                };
+                let now = now_unix_ms();
+                fix.attempts.push(crate::CiFixAttempt {
+                    pr_number,
+                    run_id,
+                    workflow: workflow.clone(),
+                    url: url.clone(),
+                    started_at_unix_ms: now,
+                });
+                conversation.push_entry(ConversationEntry::SystemEvent {
+                    entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+                    created_at_unix_ms: now,
+                    event: crate::ConversationSystemEvent::CiFixAttempt {
+                        pr_number,
+                        attempt: fix.attempts_used(),
+                        max_attempts: fix.max_attempts,
+                        run_id,
+                        workflow,
+                        url,
+                    },
+                });
+                vec![Effect::SaveAppState]
+            }
+            Action::CiAutoFixStopped {
+                workspace_id,
+                thread_id,
+                reason,
+            } => {
+                let key = (workspace_id, thread_id);
+                let Some(fix) = self.task_ci_auto_fix.get_mut(&key).filter(|f| f.enabled) else {
+                    return Vec::new();
+                };
+                let Some(conversation) = self.conversations.get_mut(&key) else {
+                    return Vec::new();
+                };
+                fix.enabled = false;
+                conversation.push_entry(ConversationEntry::SystemEvent {
+                    entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+                    created_at_unix_ms: now_unix_ms(),
+                    event: crate::ConversationSystemEvent::CiAutoFixStopped {
+                        reason,
+                        attempts: fix.attempts_used(),
+                    },
+                });
+                vec![Effect::SaveAppState]
+            }
+            Action::CreateWorkspaceThread {
+                workspace_id,
+                model_id,
+                thinking_effort,
+            } => {
+                let thread_id = {
+                    let tabs = self.ensure_workspace_tabs_mut(workspace_id);
+                    tabs.allocate_thread_id()
+                };
+                // Reason: When a model_id is provided, infer its runner and
+                // use it if enabled; otherwise fall back to defaults.
+                let (effective_runner, effective_model) =
+                    resolve_runner_and_model_for_create(self, model_id);
+                let effective_effort =
+                    thinking_effort.unwrap_or(self.agent_default_thinking_effort);
+                // Reason: Normalize effort for the chosen model — Droid models
+                // ignore the reasoning flag so we clamp to their supported set.
+                let effective_effort = self
+                    .model_catalog
+                    .normalize_thinking_effort(&effective_model, effective_effort);
+                let mut conversation = Self::default_conversation_with_defaults(
+                    thread_id,
+                    effective_model,
+                    effective_effort,
+                    effective_runner,
+                );
+                conversation.task_status = crate::TaskStatus::Backlog;
+                conversation.push_entry(ConversationEntry::SystemEvent {
+                    entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+                    created_at_unix_ms: now_unix_ms(),
+                    event: crate::ConversationSystemEvent::TaskCreated,
+                });
+                self.conversations
+                    .insert((workspace_id, thread_id), conversation);
+                self.ensure_workspace_tabs_mut(workspace_id)
+                    .activate(thread_id);
+                vec![
+                    Effect::SaveAppState,
+                    Effect::EnsureConversation {
+                        workspace_id,
+                        thread_id,
+                    },
+                    Effect::LoadWorkspaceThreads { workspace_id },
+                ]
+            }
+            Action::ActivateWorkspaceThread {
+                workspace_id,
+                thread_id,
+            } => {
+                if self
+                    .conversations
+                    .get(&(workspace_id, thread_id))
+                    .is_some_and(|c| {
+                        matches!(
+                            c.task_status,
+                            crate::TaskStatus::Done | crate::TaskStatus::Canceled
+                        )
+                    })
+                {
+                    self.last_error = Some("Task is archived".to_owned());
+                    return Vec::new();
+                }
+                let tabs = self.ensure_workspace_tabs_mut(workspace_id);
+                tabs.activate(thread_id);
+                self.ensure_conversation_mut(workspace_id, thread_id);
+                vec![
+                    Effect::SaveAppState,
+                    Effect::LoadConversation {
+                        workspace_id,
+                        thread_id,
+                    },
+                ]
+            }
+            Action::ForkWorkspaceThread {
+                workspace_id,
+                thread_id,
+                entry_id,
+                target_workspace_id,
+            } => {
+                let Some(source) = self.conversations.get(&(workspace_id, thread_id)) else {
+                    self.last_error = Some("Task not found".to_owned());
+                    return Vec::new();
+                };
+                let same_project = self
+                    .projects
+                    .iter()
+                    .find(|p| p.workspaces.iter().any(|w| w.id == workspace_id))
+                    .is_some_and(|p| p.workspaces.iter().any(|w| w.id == target_workspace_id));
+                if !same_project {
+                    self.last_error = Some("Workspace not found".to_owned());
+                    return Vec::new();
+                }
+                // Reason: with the whole conversation in memory the fork point can be checked
+                // here and shown right away; otherwise the backend checks it while copying.
+                let fork_index = crate::fork_entry_index(&source.entries, &entry_id);
+                if fork_index.is_none() && source.entries_start == 0 {
+                    self.last_error = Some("Fork point not found".to_owned());
+                    return Vec::new();
+                }
+                let entries = match fork_index {
+                    Some(index) if source.entries_start == 0 => source.entries[..=index].to_vec(),
+                    _ => Vec::new(),
+                };
+                let title = format!("{} (fork)", source.title);
+                let task_status = match source.task_status {
+                    crate::TaskStatus::Done | crate::TaskStatus::Canceled => {
+                        crate::TaskStatus::Iterating
+                    }
+                    status => status,
+                };
+                let model_id = source.agent_model_id.clone();
+                let thinking_effort = source.thinking_effort;
+                let runner = source.agent_runner;
+                let amp_mode = source.amp_mode.clone();
+                let run_config_overridden_by_user = source.run_config_overridden_by_user;
+
+                let new_thread_id = self
+                    .ensure_workspace_tabs_mut(target_workspace_id)
+                    .allocate_thread_id();
+                let mut conversation = Self::default_conversation_with_defaults(
+                    new_thread_id,
+                    model_id,
+                    thinking_effort,
+                    runner,
+                );
+                conversation.amp_mode = amp_mode;
+                conversation.run_config_overridden_by_user = run_config_overridden_by_user;
+                conversation.title = title;
+                conversation.task_status = task_status;
+                conversation.entries_total = entries.len() as u64;
+                conversation.entries = entries;
+                let origin = crate::ThreadForkOrigin {
+                    workspace_id,
+                    thread_id,
+                    entry_id,
+                };
+                conversation.forked_from = Some(origin.clone());
+                self.conversations
+                    .insert((target_workspace_id, new_thread_id), conversation);
+                self.ensure_workspace_tabs_mut(target_workspace_id)
+                    .activate(new_thread_id);
+                vec![
+                    Effect::SaveAppState,
+                    Effect::ForkConversation {
+                        workspace_id: target_workspace_id,
+                        thread_id: new_thread_id,
+                        origin,
+                    },
+                ]
+            }
+            Action::WorkspaceThreadForked {
+                workspace_id,
+                thread_id,
+            } => vec![
+                Effect::LoadWorkspaceThreads { workspace_id },
+                Effect::LoadConversation {
+                    workspace_id,
+                    thread_id,
+                },
+            ],
+            Action::WorkspaceThreadForkFailed {
+                workspace_id,
+                thread_id,
+                message,
+            } => {
+                let effects = self.apply(Action::WorkspaceThreadsPurged {
+                    workspace_id,
+                    thread_ids: vec![thread_id],
+                });
+                self.last_error = Some(message);
+                effects
+            }
+            Action::CloseWorkspaceThreadTab {
+                workspace_id,
+                thread_id,
+            } => {
+                let tabs = self.ensure_workspace_tabs_mut(workspace_id);
+                if tabs.open_tabs.len() <= 1 {
+                    return Vec::new();
+                }
+                let previous_active = tabs.active_tab;
+                tabs.archive_tab(thread_id);
+                let mut effects = vec![
+                    Effect::SaveAppState,
+                    // Clean up any persistent Claude process associated with this thread
+                    Effect::CleanupClaudeProcess {
+                        workspace_id,
+                        thread_id,
+                    },
+                ];
+                if tabs.active_tab != previous_active {
+                    effects.push(Effect::LoadConversation {
+                        workspace_id,
+                        thread_id: tabs.active_tab,
+                    });
+                }
+                effects
+            }
+            Action::RestoreWorkspaceThreadTab {
                 workspace_id,
                 thread_id,
             } => {
@@ -1936,7 +2669,7 @@ impl AppState {
                                         &run_config.thinking_effort,
                                     )
                                 {
-                                    let normalized = normalize_thinking_effort(
+                                    let normalized = self.model_catalog.normalize_thinking_effort(
                                         &run_config.model_id,
                                         parsed_effort,
                                     );
@@ -1957,6 +2690,7 @@ impl AppState {
                     conversation.title = meta.title;
                     conversation.thread_id = meta.remote_thread_id;
                     conversation.task_status = meta.task_status;
+                    conversation.forked_from = meta.forked_from;
                 }
                 let mut did_update_tabs = false;
                 if let Some(tabs) = self.workspace_tabs.get_mut(&workspace_id) {
@@ -2028,6 +2762,12 @@ impl AppState {
                         .remove(&key)
                         .is_some();
                     changed |= self.starred_tasks.remove(&key);
+                    changed |= self.task_issue_links.remove(&key).is_some();
+                    changed |= self.task_turn_limits.remove(&key).is_some();
+                    changed |= self.task_tokens_used.remove(&key).is_some();
+                    changed |= self.task_review_comments.remove(&key).is_some();
+                    changed |= self.task_pull_request_review_sync.remove(&key).is_some();
+                    changed |= self.task_ci_auto_fix.remove(&key).is_some();
                     effects.push(Effect::CleanupClaudeProcess {
                         workspace_id,
                         thread_id: *thread_id,
@@ -2196,6 +2936,7 @@ impl AppState {
                     && self.telegram_bot_username.is_none()
                     && self.telegram_paired_chat_id.is_none()
                     && self.telegram_topic_bindings.is_empty()
+                    && self.telegram_group_chats.is_empty()
                     && self.telegram_last_error.is_none()
                 {
                     return Vec::new();
@@ -2206,6 +2947,7 @@ impl AppState {
                 self.telegram_paired_chat_id = None;
                 self.telegram_last_error = None;
                 self.telegram_topic_bindings.clear();
+                self.telegram_group_chats.clear();
                 self.telegram_config_rev = self.telegram_config_rev.saturating_add(1);
                 vec![Effect::SaveAppState]
             }
@@ -2294,6 +3036,193 @@ impl AppState {
                 self.telegram_topic_bindings.clear();
                 vec![Effect::SaveAppState]
             }
+            Action::TelegramGroupPaired {
+                chat_id,
+                project_slug,
+                title,
+                member,
+            } => {
+                // One group per project: pairing a new group replaces the old one.
+                self.telegram_group_chats
+                    .retain(|id, g| *id == chat_id || g.project_slug != project_slug);
+                let group = self.telegram_group_chats.entry(chat_id).or_insert_with(|| {
+                    crate::TelegramGroupChat {
+                        chat_id,
+                        project_slug: project_slug.clone(),
+                        title: String::new(),
+                        members: Vec::new(),
+                        topic_bindings: Vec::new(),
+                    }
+                });
+                if group.project_slug != project_slug {
+                    group.project_slug = project_slug;
+                    group.topic_bindings.clear();
+                }
+                group.title = title;
+                upsert_telegram_group_member(group, member);
+                self.telegram_last_error = None;
+                self.telegram_config_rev = self.telegram_config_rev.saturating_add(1);
+                vec![Effect::SaveAppState]
+            }
+            Action::TelegramGroupUnpaired { chat_id } => {
+                if self.telegram_group_chats.remove(&chat_id).is_none() {
+                    return Vec::new();
+                }
+                self.telegram_config_rev = self.telegram_config_rev.saturating_add(1);
+                vec![Effect::SaveAppState]
+            }
+            Action::TelegramGroupMemberAdded { chat_id, member } => {
+                let Some(group) = self.telegram_group_chats.get_mut(&chat_id) else {
+                    return Vec::new();
+                };
+                if !upsert_telegram_group_member(group, member) {
+                    return Vec::new();
+                }
+                self.telegram_config_rev = self.telegram_config_rev.saturating_add(1);
+                vec![Effect::SaveAppState]
+            }
+            Action::TelegramGroupMemberRemoved { chat_id, user_id } => {
+                let Some(group) = self.telegram_group_chats.get_mut(&chat_id) else {
+                    return Vec::new();
+                };
+                let before = group.members.len();
+                group.members.retain(|m| m.user_id != user_id);
+                if group.members.len() == before {
+                    return Vec::new();
+                }
+                self.telegram_config_rev = self.telegram_config_rev.saturating_add(1);
+                vec![Effect::SaveAppState]
+            }
+            Action::TelegramGroupTopicBound { chat_id, binding } => {
+                let Some(group) = self.telegram_group_chats.get_mut(&chat_id) else {
+                    return Vec::new();
+                };
+                if group.topic_bindings.contains(&binding) {
+                    return Vec::new();
+                }
+                group
+                    .topic_bindings
+                    .retain(|b| b.message_thread_id != binding.message_thread_id);
+                group.topic_bindings.push(binding);
+                vec![Effect::SaveAppState]
+            }
+            Action::TelegramGroupTopicUnbound {
+                chat_id,
+                message_thread_id,
+            } => {
+                let Some(group) = self.telegram_group_chats.get_mut(&chat_id) else {
+                    return Vec::new();
+                };
+                let before = group.topic_bindings.len();
+                group
+                    .topic_bindings
+                    .retain(|b| b.message_thread_id != message_thread_id);
+                if group.topic_bindings.len() == before {
+                    return Vec::new();
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::DesktopNotificationsEnabledChanged { enabled } => {
+                if self.notification_settings.desktop_enabled == enabled {
+                    return Vec::new();
+                }
+                self.notification_settings.desktop_enabled = enabled;
+                vec![Effect::SaveAppState]
+            }
+            Action::NotificationQuietHoursChanged { quiet_hours } => {
+                if quiet_hours.is_some_and(|q| !q.is_valid()) {
+                    self.last_error = Some("Invalid quiet hours".to_owned());
+                    return Vec::new();
+                }
+                if self.notification_settings.quiet_hours == quiet_hours {
+                    return Vec::new();
+                }
+                self.notification_settings.quiet_hours = quiet_hours;
+                vec![Effect::SaveAppState]
+            }
+            Action::ProjectNotificationsMutedChanged { project_id, muted } => {
+                let Some(slug) = self
+                    .projects
+                    .iter()
+                    .find(|p| p.id == project_id)
+                    .map(|p| p.slug.clone())
+                else {
+                    self.last_error = Some("Project not found".to_owned());
+                    return Vec::new();
+                };
+                let settings = &mut self.notification_settings;
+                if settings.is_project_muted(&slug) == muted {
+                    return Vec::new();
+                }
+                if muted {
+                    settings.muted_project_slugs.push(slug);
+                    settings.muted_project_slugs.sort();
+                } else {
+                    settings.muted_project_slugs.retain(|s| *s != slug);
+                }
+                vec![Effect::SaveAppState]
+            }
+            Action::AttachmentTextLimitsChanged { limits } => {
+                if !limits.is_valid() {
+                    self.last_error = Some("Invalid attachment text limits".to_owned());
+                    return Vec::new();
+                }
+                if self.attachment_text_limits == limits {
+                    return Vec::new();
+                }
+                self.attachment_text_limits = limits;
+                vec![Effect::SaveAppState]
+            }
+            Action::UrlAttachmentDomainsChanged { domains } => {
+                let Some(settings) = crate::UrlAttachmentSettings::from_domains(&domains) else {
+                    self.last_error = Some("Invalid URL attachment domain".to_owned());
+                    return Vec::new();
+                };
+                if self.url_attachment_settings == settings {
+                    return Vec::new();
+                }
+                self.url_attachment_settings = settings;
+                vec![Effect::SaveAppState]
+            }
+            Action::TaskIssueLinked { link } => {
+                let key = (
+                    WorkspaceId::from_u64(link.workspace_id),
+                    WorkspaceThreadId::from_u64(link.thread_id),
+                );
+                if self.task_issue_links.get(&key) == Some(&link) {
+                    return Vec::new();
+                }
+                self.task_issue_links.insert(key, link);
+                vec![Effect::SaveAppState]
+            }
+            Action::TaskIssueMergeActionChanged {
+                workspace_id,
+                thread_id,
+                on_merge,
+            } => {
+                let Some(link) = self.task_issue_links.get_mut(&(workspace_id, thread_id)) else {
+                    return Vec::new();
+                };
+                if link.on_merge == on_merge {
+                    return Vec::new();
+                }
+                link.on_merge = on_merge;
+                vec![Effect::SaveAppState]
+            }
+            Action::TaskIssueResolved {
+                workspace_id,
+                thread_id,
+                pr_number,
+            } => {
+                let Some(link) = self.task_issue_links.get_mut(&(workspace_id, thread_id)) else {
+                    return Vec::new();
+                };
+                if link.resolved_by_pr == Some(pr_number) {
+                    return Vec::new();
+                }
+                link.resolved_by_pr = Some(pr_number);
+                vec![Effect::SaveAppState]
+            }
             Action::CodexDefaultsLoaded {
                 model_id,
                 thinking_effort,
@@ -2308,11 +3237,12 @@ impl AppState {
                 }
 
                 if let Some(next_effort) = thinking_effort {
-                    let normalized =
-                        normalize_thinking_effort(&self.agent_default_model_id, next_effort);
+                    let normalized = self
+                        .model_catalog
+                        .normalize_thinking_effort(&self.agent_default_model_id, next_effort);
                     self.agent_default_thinking_effort = normalized;
                 } else {
-                    let normalized = normalize_thinking_effort(
+                    let normalized = self.model_catalog.normalize_thinking_effort(
                         &self.agent_default_model_id,
                         self.agent_default_thinking_effort,
                     );
@@ -2321,14 +3251,63 @@ impl AppState {
 
                 Vec::new()
             }
+            Action::ModelCatalogLoaded { catalog } => {
+                let catalog = Arc::new(catalog);
+                self.model_catalog = catalog.clone();
+                self.agent_default_thinking_effort = catalog.normalize_thinking_effort(
+                    &self.agent_default_model_id,
+                    self.agent_default_thinking_effort,
+                );
+
+                // Reason: Efforts for models missing from the built-in catalog were kept as-is
+                // while loading; settle them now that discovered models are known.
+                let mut overrides_changed = false;
+                for run_config in self.workspace_thread_run_config_overrides.values_mut() {
+                    let Some(effort) =
+                        crate::agent_settings::parse_thinking_effort(&run_config.thinking_effort)
+                    else {
+                        continue;
+                    };
+                    let normalized =
+                        catalog.normalize_thinking_effort(&run_config.model_id, effort);
+                    if normalized != effort {
+                        run_config.thinking_effort = normalized.as_str().to_owned();
+                        overrides_changed = true;
+                    }
+                }
+                for conversation in self.conversations.values_mut() {
+                    conversation.thinking_effort = catalog.normalize_thinking_effort(
+                        &conversation.agent_model_id,
+                        conversation.thinking_effort,
+                    );
+                }
+
+                let mut effects = vec![Effect::LoadCodexDefaults];
+                if overrides_changed {
+                    effects.push(Effect::SaveAppState);
+                }
+                effects
+            }
             Action::TaskPromptTemplateChanged {
+                project_id,
                 intent_kind,
                 template,
             } => {
                 let trimmed = template.trim();
+                if let Some(project_id) = project_id {
+                    return self.project_task_prompt_template_changed(
+                        project_id,
+                        intent_kind,
+                        trimmed,
+                    );
+                }
                 if trimmed.is_empty() {
                     return Vec::new();
                 }
+                if let Some(message) = self.task_prompt_template_error(None, trimmed) {
+                    self.last_error = Some(message);
+                    return Vec::new();
+                }
                 let existing = self
                     .task_prompt_templates
                     .get(&intent_kind)
@@ -2365,6 +3344,10 @@ impl AppState {
                 if trimmed.is_empty() {
                     return Vec::new();
                 }
+                if let Some(message) = system_prompt_template_error(trimmed) {
+                    self.last_error = Some(message);
+                    return Vec::new();
+                }
                 let existing = self.system_prompt_templates.get(&kind).map(|t| t.as_str());
                 if existing == Some(trimmed) {
                     return Vec::new();
@@ -2393,6 +3376,24 @@ impl AppState {
                 self.system_prompt_templates = next;
                 Vec::new()
             }
+            Action::ProjectPromptVariablesChanged {
+                project_id,
+                variables,
+            } => self.project_prompt_variables_changed(project_id, variables),
+            Action::ProjectPromptSettingsLoaded {
+                project_id,
+                settings,
+            } => {
+                if !self.projects.iter().any(|p| p.id == project_id) {
+                    return Vec::new();
+                }
+                if settings.is_empty() {
+                    self.project_prompt_settings.remove(&project_id);
+                } else {
+                    self.project_prompt_settings.insert(project_id, settings);
+                }
+                Vec::new()
+            }
             Action::WorkspaceChatScrollSaved {
                 workspace_id,
                 thread_id,
@@ -2554,6 +3555,56 @@ impl AppState {
                 }
                 effects
             }
+            Action::TaskStatusRestored {
+                workspace_id,
+                thread_id,
+                task_status,
+            } => {
+                if matches!(
+                    task_status,
+                    crate::TaskStatus::Done | crate::TaskStatus::Canceled
+                ) {
+                    return Vec::new();
+                }
+                let Some(conversation) = self.conversations.get_mut(&(workspace_id, thread_id))
+                else {
+                    return Vec::new();
+                };
+                let from_status = conversation.task_status;
+                if from_status == task_status {
+                    return Vec::new();
+                }
+                conversation.task_status = task_status;
+                conversation.push_entry(ConversationEntry::SystemEvent {
+                    entry_id: format!("sys_{}", conversation.entries_total.saturating_add(1)),
+                    created_at_unix_ms: now_unix_ms(),
+                    event: crate::ConversationSystemEvent::TaskStatusChanged {
+                        from: from_status,
+                        to: task_status,
+                    },
+                });
+
+                let mut effects = vec![
+                    Effect::StoreConversationTaskStatus {
+                        workspace_id,
+                        thread_id,
+                        task_status,
+                    },
+                    Effect::LoadWorkspaceThreads { workspace_id },
+                ];
+                let was_closed = matches!(
+                    from_status,
+                    crate::TaskStatus::Done | crate::TaskStatus::Canceled
+                );
+                if was_closed {
+                    let tabs = self.ensure_workspace_tabs_mut(workspace_id);
+                    if tabs.archived_tabs.contains(&thread_id) {
+                        tabs.restore_tab(thread_id, false);
+                        effects.push(Effect::SaveAppState);
+                    }
+                }
+                effects
+            }
             Action::TaskStatusSuggestionCreated {
                 workspace_id,
                 thread_id,
@@ -2779,8 +3830,9 @@ impl AppState {
                     if let Some(parsed_effort) =
                         crate::agent_settings::parse_thinking_effort(&run_config.thinking_effort)
                     {
-                        let normalized =
-                            normalize_thinking_effort(&run_config.model_id, parsed_effort);
+                        let normalized = self
+                            .model_catalog
+                            .normalize_known_thinking_effort(&run_config.model_id, parsed_effort);
                         conversation.run_config_overridden_by_user = true;
                         conversation.agent_model_id = run_config.model_id;
                         conversation.thinking_effort = normalized;
@@ -2806,15 +3858,20 @@ impl AppState {
         if let Some(model) = self
             .agent_runner_default_models
             .get(&runner)
-            .filter(|m| crate::model_valid_for_runner(runner, m))
+            .filter(|m| self.model_catalog.model_valid_for_runner(runner, m))
         {
             return model.clone();
         }
         // Fall back to global default if valid for this runner.
-        if crate::model_valid_for_runner(runner, &self.agent_default_model_id) {
+        if self
+            .model_catalog
+            .model_valid_for_runner(runner, &self.agent_default_model_id)
+        {
             self.agent_default_model_id.clone()
         } else {
-            crate::default_model_for_runner(runner).to_owned()
+            self.model_catalog
+                .default_model_for_runner(runner)
+                .to_owned()
         }
     }
 
@@ -2848,6 +3905,7 @@ impl AppState {
             next_queued_prompt_id: 1,
             pending_prompts: VecDeque::new(),
             queue_paused: false,
+            forked_from: None,
         }
     }
 
@@ -2909,6 +3967,132 @@ impl AppState {
         (id, self.projects.len() != before)
     }
 
+    /// Validates a task prompt template, returning a user-facing error for unknown variables.
+    ///
+    /// Project-scoped templates may only reference custom variables defined for that project.
+    pub fn task_prompt_template_error(
+        &self,
+        project_id: Option<ProjectId>,
+        template: &str,
+    ) -> Option<String> {
+        let empty = BTreeMap::new();
+        let custom = project_id.map(|project_id| {
+            self.project_prompt_settings
+                .get(&project_id)
+                .map(|settings| &settings.variables)
+                .unwrap_or(&empty)
+        });
+        let unknown = unknown_prompt_template_variables(template, TASK_PROMPT_VARIABLES, custom);
+        unknown_prompt_variables_error(&unknown)
+    }
+
+    /// Resolves the task prompt template for `intent_kind`: project override, then the global
+    /// template, then the built-in default.
+    pub fn task_prompt_template(
+        &self,
+        project_id: Option<ProjectId>,
+        intent_kind: TaskIntentKind,
+    ) -> String {
+        project_id
+            .and_then(|project_id| self.project_prompt_settings.get(&project_id))
+            .and_then(|settings| settings.task_prompt_templates.get(&intent_kind))
+            .or_else(|| self.task_prompt_templates.get(&intent_kind))
+            .cloned()
+            .unwrap_or_else(|| default_task_prompt_template(intent_kind))
+    }
+
+    fn project_task_prompt_template_changed(
+        &mut self,
+        project_id: ProjectId,
+        intent_kind: TaskIntentKind,
+        template: &str,
+    ) -> Vec<Effect> {
+        if !self.projects.iter().any(|p| p.id == project_id) {
+            self.last_error = Some("Project not found".to_owned());
+            return Vec::new();
+        }
+
+        if template.is_empty() {
+            let removed = self
+                .project_prompt_settings
+                .get_mut(&project_id)
+                .and_then(|settings| settings.task_prompt_templates.remove(&intent_kind));
+            if removed.is_none() {
+                return Vec::new();
+            }
+            if self
+                .project_prompt_settings
+                .get(&project_id)
+                .is_some_and(ProjectPromptSettings::is_empty)
+            {
+                self.project_prompt_settings.remove(&project_id);
+            }
+            return vec![Effect::DeleteProjectTaskPromptTemplate {
+                project_id,
+                intent_kind,
+            }];
+        }
+
+        if let Some(message) = self.task_prompt_template_error(Some(project_id), template) {
+            self.last_error = Some(message);
+            return Vec::new();
+        }
+
+        let settings = self.project_prompt_settings.entry(project_id).or_default();
+        if settings
+            .task_prompt_templates
+            .get(&intent_kind)
+            .map(String::as_str)
+            == Some(template)
+        {
+            return Vec::new();
+        }
+        settings
+            .task_prompt_templates
+            .insert(intent_kind, template.to_owned());
+        vec![Effect::StoreProjectTaskPromptTemplate {
+            project_id,
+            intent_kind,
+            template: template.to_owned(),
+        }]
+    }
+
+    fn project_prompt_variables_changed(
+        &mut self,
+        project_id: ProjectId,
+        variables: BTreeMap<String, String>,
+    ) -> Vec<Effect> {
+        if !self.projects.iter().any(|p| p.id == project_id) {
+            self.last_error = Some("Project not found".to_owned());
+            return Vec::new();
+        }
+
+        let mut normalized = BTreeMap::new();
+        for (name, value) in variables {
+            let name = name.trim().to_owned();
+            if !is_valid_custom_prompt_variable_name(&name) {
+                self.last_error = Some(format!(
+                    "Invalid template variable name: {name:?} (use lowercase letters, digits, '-' or '_')"
+                ));
+                return Vec::new();
+            }
+            normalized.insert(name, value);
+        }
+
+        let settings = self.project_prompt_settings.entry(project_id).or_default();
+        if settings.variables == normalized {
+            return Vec::new();
+        }
+        settings.variables = normalized.clone();
+        if settings.is_empty() {
+            self.project_prompt_settings.remove(&project_id);
+        }
+        vec![Effect::StoreProjectPromptVariables {
+            project_id,
+            variables: normalized,
+        }]
+    }
+
     fn delete_project(&mut self, project_id: ProjectId) -> Vec<Effect> {
         let Some(project_idx) = self.projects.iter().position(|p| p.id == project_id) else {
             return Vec::new();
@@ -2920,7 +4104,26 @@ impl AppState {
             .map(|w| w.id)
             .collect();
 
-        self.projects.remove(project_idx);
+        let project = self.projects.remove(project_idx);
+        self.project_prompt_settings.remove(&project_id);
+        if self.deletion_settings.soft_delete_enabled() {
+            self.deleted_projects
+                .retain(|deleted| deleted.project_id() != project_id);
+            let mut deleted = crate::DeletedProject::from_project(&project, now_unix_ms());
+            for workspace in &mut deleted.workspaces {
+                let workspace_id = WorkspaceId(workspace.id);
+                if let Some(tabs) = self.workspace_tabs.get(&workspace_id) {
+                    workspace.next_thread_id = tabs.next_thread_id;
+                }
+                workspace.thread_run_config_overrides = self
+                    .workspace_thread_run_config_overrides
+                    .iter()
+                    .filter(|((wid, _), _)| *wid == workspace_id)
+                    .map(|((_, tid), run_config)| (tid.0, run_config.clone()))
+                    .collect();
+            }
+            self.deleted_projects.push(deleted);
+        }
 
         for workspace_id in &workspace_ids {
             self.workspace_tabs.remove(workspace_id);
@@ -2961,6 +4164,48 @@ impl AppState {
         vec![Effect::SaveAppState]
     }
 
+    fn restore_deleted_project(&mut self, project_id: ProjectId) -> Vec<Effect> {
+        let Some(idx) = self
+            .deleted_projects
+            .iter()
+            .position(|deleted| deleted.project_id() == project_id)
+        else {
+            self.last_error = Some("Deleted project not found".to_owned());
+            return Vec::new();
+        };
+        let deleted = &self.deleted_projects[idx];
+        if self
+            .projects
+            .iter()
+            .any(|p| p.path == deleted.path || p.slug == deleted.slug)
+        {
+            self.last_error = Some("A project with the same path already exists".to_owned());
+            return Vec::new();
+        }
+
+        let deleted = self.deleted_projects.remove(idx);
+        let project = deleted.to_project();
+        for workspace in &deleted.workspaces {
+            let workspace_id = WorkspaceId(workspace.id);
+            let mut tabs = WorkspaceTabs::new_empty();
+            tabs.next_thread_id = workspace.next_thread_id.max(1);
+            self.workspace_tabs.insert(workspace_id, tabs);
+            for (thread_id, run_config) in &workspace.thread_run_config_overrides {
+                self.workspace_thread_run_config_overrides.insert(
+                    (workspace_id, WorkspaceThreadId(*thread_id)),
+                    run_config.clone(),
+                );
+            }
+        }
+        self.next_project_id = self.next_project_id.max(project.id.0 + 1);
+        if let Some(max_workspace_id) = project.workspaces.iter().map(|w| w.id.0).max() {
+            self.next_workspace_id = self.next_workspace_id.max(max_workspace_id + 1);
+        }
+        self.projects.push(project);
+        // Prompt templates and variables stay on disk under the project slug while it is deleted.
+        vec![Effect::SaveAppState, Effect::LoadProjectPromptSettings]
+    }
+
     fn insert_main_workspace(&mut self, project_id: ProjectId) -> WorkspaceId {
         let workspace_id = WorkspaceId(self.next_workspace_id);
         self.next_workspace_id += 1;
@@ -2976,6 +4221,7 @@ impl AppState {
             worktree_path: project.path.clone(),
             status: WorkspaceStatus::Active,
             last_activity_at: None,
+            archived_at: None,
             archive_status: OperationStatus::Idle,
             branch_rename_status: OperationStatus::Idle,
         });
@@ -3006,6 +4252,7 @@ impl AppState {
                 worktree_path,
                 status: WorkspaceStatus::Active,
                 last_activity_at: None,
+                archived_at: None,
                 archive_status: OperationStatus::Idle,
                 branch_rename_status: OperationStatus::Idle,
             });
@@ -3030,13 +4277,18 @@ impl AppState {
     }
 
     fn unique_project_slug(&self, base: String) -> String {
-        if !self.projects.iter().any(|p| p.slug == base) {
+        // Soft-deleted projects still own their conversations, which are keyed by slug.
+        let taken = |slug: &str| {
+            self.projects.iter().any(|p| p.slug == slug)
+                || self.deleted_projects.iter().any(|p| p.slug == slug)
+        };
+        if !taken(&base) {
             return base;
         }
 
         for i in 2.. {
             let candidate = format!("{base}-{i}");
-            if !self.projects.iter().any(|p| p.slug == candidate) {
+            if !taken(&candidate) {
                 return candidate;
             }
         }
@@ -3051,6 +4303,35 @@ impl Default for AppState {
     }
 }
 
+/// Adds the member to the group's allowlist, or refreshes their display name.
+/// Returns whether anything changed.
+fn upsert_telegram_group_member(
+    group: &mut crate::TelegramGroupChat,
+    member: crate::TelegramGroupMember,
+) -> bool {
+    const MAX_MEMBERS: usize = 64;
+
+    if member.user_id <= 0 {
+        return false;
+    }
+    if let Some(existing) = group
+        .members
+        .iter_mut()
+        .find(|m| m.user_id == member.user_id)
+    {
+        if *existing == member || member.display_name.is_empty() {
+            return false;
+        }
+        existing.display_name = member.display_name;
+        return true;
+    }
+    if group.members.len() >= MAX_MEMBERS {
+        return false;
+    }
+    group.members.push(member);
+    true
+}
+
 fn start_next_queued_prompt(
     conversation: &mut WorkspaceConversation,
     workspace_id: WorkspaceId,
@@ -3067,6 +4348,7 @@ fn start_next_queued_prompt(
         thread_id,
         queued.text,
         queued.attachments,
+        queued.author,
         queued.run_config,
     ))
 }
@@ -3077,6 +4359,7 @@ fn start_agent_run(
     thread_id: WorkspaceThreadId,
     text: String,
     attachments: Vec<AttachmentRef>,
+    author: Option<String>,
     run_config: AgentRunConfig,
 ) -> Effect {
     let run_id = conversation.next_run_id;
@@ -3089,6 +4372,7 @@ fn start_agent_run(
         event: crate::UserEvent::Message {
             text: text.clone(),
             attachments: attachments.clone(),
+            author,
         },
     });
     conversation.run_status = OperationStatus::Running;
@@ -3413,6 +4697,7 @@ mod tests {
                     task_status_last_analyzed_message_seq: 0,
                     turn_status: crate::TurnStatus::Idle,
                     last_turn_result: None,
+                    forked_from: None,
                 },
                 ConversationThreadMeta {
                     thread_id: WorkspaceThreadId(2),
@@ -3425,6 +4710,7 @@ mod tests {
                     task_status_last_analyzed_message_seq: 0,
                     turn_status: crate::TurnStatus::Idle,
                     last_turn_result: None,
+                    forked_from: None,
                 },
                 ConversationThreadMeta {
                     thread_id: WorkspaceThreadId(1),
@@ -3437,6 +4723,7 @@ mod tests {
                     task_status_last_analyzed_message_seq: 0,
                     turn_status: crate::TurnStatus::Idle,
                     last_turn_result: None,
+                    forked_from: None,
                 },
             ],
         });
@@ -3589,6 +4876,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         let (sent_model_id, sent_effort) = effects
             .iter()
@@ -3677,6 +4965,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         let conversation = state
@@ -3890,6 +5179,77 @@ mod tests {
         assert_eq!(conversation.agent_model_id, "gpt-5.2");
     }
 
+    #[test]
+    fn model_catalog_loaded_settles_efforts_kept_for_undiscovered_models() {
+        let mut state = AppState::new();
+        state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/repo"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        state.apply(Action::WorkspaceCreated {
+            project_id,
+            workspace_name: "w1".to_owned(),
+            branch_name: "repo/w1".to_owned(),
+            worktree_path: PathBuf::from("/tmp/luban/worktrees/repo/w1"),
+        });
+        let workspace_id = workspace_id_by_name(&state, "w1");
+        state.apply(Action::CreateWorkspaceThread {
+            workspace_id,
+            model_id: None,
+            thinking_effort: None,
+        });
+        let thread_id = default_thread_id();
+        let mut persisted = state.to_persisted();
+        persisted.workspace_thread_run_config_overrides.insert(
+            (workspace_id.as_u64(), thread_id.as_u64()),
+            crate::PersistedWorkspaceThreadRunConfigOverride {
+                runner: None,
+                amp_mode: None,
+                model_id: "my-model".to_owned(),
+                thinking_effort: "xhigh".to_owned(),
+            },
+        );
+
+        let mut restored = AppState::new();
+        let effects = restored.apply(Action::AppStateLoaded {
+            persisted: Box::new(persisted),
+        });
+        assert!(
+            effects
+                .iter()
+                .any(|effect| matches!(effect, Effect::LoadModelCatalog))
+        );
+        let conversation = restored
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation");
+        assert_eq!(conversation.thinking_effort, ThinkingEffort::XHigh);
+
+        let mut catalog = ModelCatalog::builtin();
+        catalog.merge(
+            crate::AgentRunnerKind::Codex,
+            [crate::AgentModel {
+                id: "my-model".to_owned(),
+                label: "My model".to_owned(),
+                supported_thinking_efforts: vec![ThinkingEffort::Low],
+            }],
+        );
+        let effects = restored.apply(Action::ModelCatalogLoaded { catalog });
+        assert!(matches!(effects[0], Effect::LoadCodexDefaults));
+        assert!(matches!(effects[1], Effect::SaveAppState));
+
+        let conversation = restored
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation");
+        assert_eq!(conversation.thinking_effort, ThinkingEffort::Low);
+        let saved = restored
+            .to_persisted()
+            .workspace_thread_run_config_overrides
+            .remove(&(workspace_id.as_u64(), thread_id.as_u64()))
+            .expect("missing run config override");
+        assert_eq!(saved.thinking_effort, "low");
+    }
+
     #[test]
     fn queued_turn_updates_current_run_config_when_started() {
         let mut state = AppState::new();
@@ -3933,6 +5293,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         state.apply(Action::ThinkingEffortChanged {
@@ -3947,6 +5308,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         let conversation = state
@@ -4016,6 +5378,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         assert!(
@@ -4058,6 +5421,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         let (run_id, run_config) = effects
@@ -4150,6 +5514,7 @@ mod tests {
                     event: crate::UserEvent::Message {
                         text: format!("Message {idx}"),
                         attachments: Vec::new(),
+                        author: None,
                     },
                 })
                 .collect(),
@@ -4378,6 +5743,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             }),
         });
         assert_eq!(state.terminal_pane_width, Some(480));
@@ -4433,6 +5808,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             }),
         });
         assert_eq!(restored.global_zoom_percent, 135);
@@ -4488,6 +5873,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             }),
         });
         assert_eq!(state.sidebar_width, Some(360));
@@ -4586,6 +5981,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             }),
         });
         assert_eq!(restored.appearance_theme, crate::AppearanceTheme::Light);
@@ -4869,6 +6274,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         let run_id = state
             .workspace_thread_conversation(workspace_id, thread_id)
@@ -5124,6 +6530,68 @@ mod tests {
         assert_eq!(state.right_pane, RightPane::None);
     }
 
+    #[test]
+    fn deleted_project_can_be_restored_with_its_ids() {
+        let mut state = AppState::new();
+        state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/repo"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        state.apply(Action::WorkspaceCreated {
+            project_id,
+            workspace_name: "main".to_owned(),
+            branch_name: "main".to_owned(),
+            worktree_path: PathBuf::from("/tmp/repo"),
+        });
+        let main_id = workspace_id_by_name(&state, "main");
+        state.ensure_workspace_tabs_mut(main_id).next_thread_id = 5;
+        let run_config = crate::PersistedWorkspaceThreadRunConfigOverride {
+            runner: Some("codex".to_owned()),
+            amp_mode: None,
+            model_id: "gpt-5.4".to_owned(),
+            thinking_effort: "high".to_owned(),
+        };
+        state
+            .workspace_thread_run_config_overrides
+            .insert((main_id, WorkspaceThreadId(3)), run_config.clone());
+
+        state.apply(Action::DeleteProject { project_id });
+        assert!(state.workspace_thread_run_config_overrides.is_empty());
+        assert!(state.projects.is_empty());
+        assert_eq!(state.deleted_projects().len(), 1);
+        assert_eq!(state.deleted_projects()[0].workspaces[0].next_thread_id, 5);
+
+        let persisted = state.to_persisted();
+        assert!(persisted.deleted_projects.is_some());
+        let mut reloaded = AppState::new();
+        reloaded.apply(Action::AppStateLoaded {
+            persisted: Box::new(persisted),
+        });
+        assert_eq!(reloaded.deleted_projects().len(), 1);
+        assert!(reloaded.next_workspace_id > main_id.0);
+
+        let effects = reloaded.apply(Action::ProjectRestored { project_id });
+        assert!(matches!(
+            effects.as_slice(),
+            [Effect::SaveAppState, Effect::LoadProjectPromptSettings]
+        ));
+        assert!(reloaded.deleted_projects().is_empty());
+        assert_eq!(reloaded.projects[0].id, project_id);
+        assert_eq!(reloaded.workspace(main_id).unwrap().workspace_name, "main");
+        assert_eq!(reloaded.workspace_tabs(main_id).unwrap().next_thread_id, 5);
+        assert_eq!(
+            reloaded
+                .workspace_thread_run_config_overrides
+                .get(&(main_id, WorkspaceThreadId(3))),
+            Some(&run_config)
+        );
+
+        reloaded.apply(Action::DeletionRetentionChanged { retention_days: 0 });
+        reloaded.apply(Action::DeleteProject { project_id });
+        assert!(reloaded.deleted_projects().is_empty());
+    }
+
     #[test]
     fn create_workspace_sets_busy_and_emits_effect() {
         let mut state = AppState::new();
@@ -5186,13 +6654,14 @@ mod tests {
             "expected main pane to restore workspace"
         );
         assert_eq!(loaded.right_pane, RightPane::Terminal);
-        assert_eq!(effects.len(), 5);
-        assert!(matches!(effects[0], Effect::LoadCodexDefaults));
+        assert_eq!(effects.len(), 6);
+        assert!(matches!(effects[0], Effect::LoadModelCatalog));
         assert!(matches!(effects[1], Effect::LoadTaskPromptTemplates));
         assert!(matches!(effects[2], Effect::LoadSystemPromptTemplates));
-        assert!(matches!(effects[3], Effect::LoadWorkspaceThreads { .. }));
+        assert!(matches!(effects[3], Effect::LoadProjectPromptSettings));
+        assert!(matches!(effects[4], Effect::LoadWorkspaceThreads { .. }));
         assert!(matches!(
-            effects[4],
+            effects[5],
             Effect::LoadConversation { workspace_id: id, .. } if id == workspace_id
         ));
     }
@@ -5382,6 +6851,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         let run_id = state
             .workspace_thread_conversation(workspace_id, thread_id)
@@ -5475,6 +6945,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         state.apply(Action::AgentEventReceived {
             workspace_id,
@@ -5504,6 +6975,7 @@ mod tests {
                     event: crate::UserEvent::Message {
                         text: "Hello".to_owned(),
                         attachments: Vec::new(),
+                        author: None,
                     },
                 }],
                 entries_total: 0,
@@ -5540,7 +7012,211 @@ mod tests {
     }
 
     #[test]
-    fn conversation_loaded_replaces_entries_when_snapshot_is_newer() {
+    fn conversation_loaded_replaces_entries_when_snapshot_is_newer() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+
+        state.apply(Action::ConversationLoaded {
+            workspace_id,
+            thread_id,
+            snapshot: ConversationSnapshot {
+                title: None,
+                thread_id: None,
+                task_status: crate::TaskStatus::Todo,
+                runner: None,
+                agent_model_id: None,
+                thinking_effort: None,
+                amp_mode: None,
+                entries: vec![ConversationEntry::UserEvent {
+                    entry_id: String::new(),
+                    created_at_unix_ms: 1,
+                    event: crate::UserEvent::Message {
+                        text: "Hello".to_owned(),
+                        attachments: Vec::new(),
+                        author: None,
+                    },
+                }],
+                entries_total: 0,
+                entries_start: 0,
+                pending_prompts: Vec::new(),
+                queue_paused: false,
+                run_started_at_unix_ms: None,
+                run_finished_at_unix_ms: None,
+            },
+        });
+
+        state.apply(Action::ConversationLoaded {
+            workspace_id,
+            thread_id,
+            snapshot: ConversationSnapshot {
+                title: None,
+                thread_id: None,
+                task_status: crate::TaskStatus::Todo,
+                runner: None,
+                agent_model_id: None,
+                thinking_effort: None,
+                amp_mode: None,
+                entries: vec![
+                    ConversationEntry::UserEvent {
+                        entry_id: String::new(),
+                        created_at_unix_ms: 1,
+                        event: crate::UserEvent::Message {
+                            text: "Hello".to_owned(),
+                            attachments: Vec::new(),
+                            author: None,
+                        },
+                    },
+                    ConversationEntry::AgentEvent {
+                        entry_id: String::new(),
+                        created_at_unix_ms: 2,
+                        runner: None,
+                        event: crate::AgentEvent::TurnDuration { duration_ms: 1234 },
+                    },
+                ],
+                entries_total: 0,
+                entries_start: 0,
+                pending_prompts: Vec::new(),
+                queue_paused: false,
+                run_started_at_unix_ms: None,
+                run_finished_at_unix_ms: None,
+            },
+        });
+
+        let after = &state.workspace_conversation(workspace_id).unwrap().entries;
+        assert!(matches!(
+            &after[..],
+            [
+                ConversationEntry::UserEvent {
+                    event: crate::UserEvent::Message { .. },
+                    ..
+                },
+                ConversationEntry::AgentEvent {
+                    event: crate::AgentEvent::TurnDuration { duration_ms: 1234 },
+                    ..
+                }
+            ]
+        ));
+    }
+
+    #[test]
+    fn task_rewound_replaces_entries_and_records_the_rewind() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+        let prompt = ConversationEntry::UserEvent {
+            entry_id: String::new(),
+            created_at_unix_ms: 1,
+            event: crate::UserEvent::Message {
+                text: "Hello".to_owned(),
+                attachments: Vec::new(),
+                author: None,
+            },
+        };
+        let empty_snapshot = |entries: Vec<ConversationEntry>| ConversationSnapshot {
+            title: None,
+            thread_id: None,
+            task_status: crate::TaskStatus::Iterating,
+            runner: None,
+            agent_model_id: None,
+            thinking_effort: None,
+            amp_mode: None,
+            entries,
+            entries_total: 0,
+            entries_start: 0,
+            pending_prompts: Vec::new(),
+            queue_paused: false,
+            run_started_at_unix_ms: None,
+            run_finished_at_unix_ms: None,
+        };
+        state.apply(Action::ConversationLoaded {
+            workspace_id,
+            thread_id,
+            snapshot: empty_snapshot(vec![prompt.clone()]),
+        });
+        state.apply(Action::TurnCheckpointCreated {
+            workspace_id,
+            thread_id,
+            checkpoint_id: "10-1".to_owned(),
+            commit: "abc".to_owned(),
+        });
+        let entries = &state.workspace_conversation(workspace_id).unwrap().entries;
+        assert_eq!(crate::rewind_truncate_index(entries, "10-1"), Some(0));
+
+        let effects = state.apply(Action::TaskRewound {
+            workspace_id,
+            thread_id,
+            checkpoint_id: "10-1".to_owned(),
+            snapshot: Some(empty_snapshot(Vec::new())),
+        });
+        assert!(effects.is_empty());
+        let conversation = state.workspace_conversation(workspace_id).unwrap();
+        assert_eq!(conversation.entries_total, 1);
+        assert!(matches!(
+            &conversation.entries[..],
+            [ConversationEntry::SystemEvent {
+                event: crate::ConversationSystemEvent::TurnRewound {
+                    truncated: true,
+                    ..
+                },
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn truncating_rewind_starts_a_fresh_runner_session() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+        let snapshot = |session: Option<&str>| ConversationSnapshot {
+            title: None,
+            thread_id: session.map(str::to_owned),
+            task_status: crate::TaskStatus::Iterating,
+            runner: None,
+            agent_model_id: None,
+            thinking_effort: None,
+            amp_mode: None,
+            entries: Vec::new(),
+            entries_total: 0,
+            entries_start: 0,
+            pending_prompts: Vec::new(),
+            queue_paused: false,
+            run_started_at_unix_ms: None,
+            run_finished_at_unix_ms: None,
+        };
+        let session = |state: &AppState| {
+            state
+                .workspace_thread_conversation(workspace_id, thread_id)
+                .and_then(|c| c.thread_id.clone())
+        };
+        state.apply(Action::ConversationLoaded {
+            workspace_id,
+            thread_id,
+            snapshot: snapshot(Some("session-1")),
+        });
+        assert_eq!(session(&state).as_deref(), Some("session-1"));
+
+        // Restoring files only keeps the turns, so the session still matches them.
+        state.apply(Action::TaskRewound {
+            workspace_id,
+            thread_id,
+            checkpoint_id: "10-1".to_owned(),
+            snapshot: None,
+        });
+        assert_eq!(session(&state).as_deref(), Some("session-1"));
+
+        state.apply(Action::TaskRewound {
+            workspace_id,
+            thread_id,
+            checkpoint_id: "10-1".to_owned(),
+            snapshot: Some(snapshot(Some("session-1"))),
+        });
+        assert_eq!(session(&state), None);
+    }
+
+    #[test]
+    fn conversation_loaded_restores_queued_prompts_when_local_is_empty() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
@@ -5556,23 +7232,40 @@ mod tests {
                 agent_model_id: None,
                 thinking_effort: None,
                 amp_mode: None,
-                entries: vec![ConversationEntry::UserEvent {
-                    entry_id: String::new(),
-                    created_at_unix_ms: 1,
-                    event: crate::UserEvent::Message {
-                        text: "Hello".to_owned(),
-                        attachments: Vec::new(),
-                    },
-                }],
+                entries: Vec::new(),
                 entries_total: 0,
                 entries_start: 0,
-                pending_prompts: Vec::new(),
-                queue_paused: false,
+                pending_prompts: vec![QueuedPrompt {
+                    id: 3,
+                    text: "Queued".to_owned(),
+                    attachments: Vec::new(),
+                    run_config: AgentRunConfig {
+                        runner: crate::AgentRunnerKind::Codex,
+                        model_id: "gpt-5.3-codex".to_owned(),
+                        thinking_effort: ThinkingEffort::Minimal,
+                        amp_mode: None,
+                    },
+                    author: None,
+                }],
+                queue_paused: true,
                 run_started_at_unix_ms: None,
                 run_finished_at_unix_ms: None,
             },
         });
 
+        let conversation = state.workspace_conversation(workspace_id).unwrap();
+        assert!(conversation.queue_paused);
+        assert_eq!(conversation.pending_prompts.len(), 1);
+        assert_eq!(conversation.pending_prompts[0].id, 3);
+        assert_eq!(conversation.next_queued_prompt_id, 4);
+    }
+
+    #[test]
+    fn conversation_loaded_applies_persisted_run_config() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+
         state.apply(Action::ConversationLoaded {
             workspace_id,
             thread_id,
@@ -5581,25 +7274,10 @@ mod tests {
                 thread_id: None,
                 task_status: crate::TaskStatus::Todo,
                 runner: None,
-                agent_model_id: None,
-                thinking_effort: None,
+                agent_model_id: Some("gpt-5.3-codex".to_owned()),
+                thinking_effort: Some(ThinkingEffort::High),
                 amp_mode: None,
-                entries: vec![
-                    ConversationEntry::UserEvent {
-                        entry_id: String::new(),
-                        created_at_unix_ms: 1,
-                        event: crate::UserEvent::Message {
-                            text: "Hello".to_owned(),
-                            attachments: Vec::new(),
-                        },
-                    },
-                    ConversationEntry::AgentEvent {
-                        entry_id: String::new(),
-                        created_at_unix_ms: 2,
-                        runner: None,
-                        event: crate::AgentEvent::TurnDuration { duration_ms: 1234 },
-                    },
-                ],
+                entries: Vec::new(),
                 entries_total: 0,
                 entries_start: 0,
                 pending_prompts: Vec::new(),
@@ -5609,102 +7287,297 @@ mod tests {
             },
         });
 
-        let after = &state.workspace_conversation(workspace_id).unwrap().entries;
+        let conversation = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation");
+        assert_eq!(conversation.agent_model_id, "gpt-5.3-codex");
+        assert_eq!(conversation.thinking_effort, ThinkingEffort::High);
+    }
+
+    #[test]
+    fn conversation_entries_are_bounded_in_memory() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+
+        state.apply(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
+        let run_id = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation")
+            .active_run_id
+            .expect("missing active run id");
+
+        let total = crate::state::MAX_CONVERSATION_ENTRIES_IN_MEMORY + 100;
+        for idx in 0..total {
+            state.apply(Action::AgentEventReceived {
+                workspace_id,
+                thread_id,
+                run_id,
+                event: CodexThreadEvent::TurnDuration {
+                    duration_ms: idx as u64,
+                },
+            });
+        }
+
+        let conversation = state.workspace_conversation(workspace_id).unwrap();
+        assert_eq!(
+            conversation.entries.len(),
+            crate::state::MAX_CONVERSATION_ENTRIES_IN_MEMORY
+        );
+        assert_eq!(conversation.entries_start, 102);
+        assert_eq!(conversation.entries_total, (total + 2) as u64);
+    }
+
+    #[test]
+    fn send_agent_message_sets_running_and_emits_effect() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+
+        let effects = state.apply(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
+        let run_effect = effects
+            .iter()
+            .find(|e| matches!(e, Effect::RunAgentTurn { .. }))
+            .expect("missing RunAgentTurn effect");
         assert!(matches!(
-            &after[..],
-            [
+            run_effect,
+            Effect::RunAgentTurn {
+                workspace_id: wid,
+                thread_id: tid,
+                text,
+                run_config,
+                ..
+            } if *wid == workspace_id
+                && *tid == thread_id
+                && text == "Hello"
+                && run_config.model_id == default_agent_model_id()
+                && run_config.thinking_effort == default_thinking_effort()
+        ));
+
+        let conversation = state.workspace_conversation(workspace_id).unwrap();
+        assert_eq!(conversation.run_status, OperationStatus::Running);
+        let user_messages = conversation
+            .entries
+            .iter()
+            .filter_map(|e| match e {
                 ConversationEntry::UserEvent {
-                    event: crate::UserEvent::Message { .. },
+                    event: crate::UserEvent::Message { text, .. },
                     ..
-                },
+                } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(user_messages, vec!["Hello"]);
+    }
+
+    #[test]
+    fn task_status_canceled_cancels_running_turn_and_emits_effect() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+
+        state.apply(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
+        let run_id = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation")
+            .active_run_id
+            .expect("missing active run id");
+
+        let effects = state.apply(Action::TaskStatusSet {
+            workspace_id,
+            thread_id,
+            task_status: crate::TaskStatus::Canceled,
+        });
+
+        assert!(
+            effects.iter().any(|e| matches!(
+                e,
+                Effect::CancelAgentTurn { workspace_id: wid, thread_id: tid, run_id: rid }
+                    if *wid == workspace_id && *tid == thread_id && *rid == run_id
+            )),
+            "expected CancelAgentTurn effect"
+        );
+
+        let conversation = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation");
+        assert_eq!(conversation.task_status, crate::TaskStatus::Canceled);
+        assert_eq!(conversation.run_status, OperationStatus::Idle);
+        assert_eq!(conversation.active_run_id, None);
+        assert!(conversation.queue_paused);
+        assert!(conversation.run_finished_at_unix_ms.is_some());
+        assert!(conversation.entries.iter().any(|e| {
+            matches!(
+                e,
                 ConversationEntry::AgentEvent {
-                    event: crate::AgentEvent::TurnDuration { duration_ms: 1234 },
+                    event: crate::AgentEvent::TurnCanceled,
                     ..
                 }
-            ]
-        ));
+            )
+        }));
+    }
+
+    #[test]
+    fn task_status_done_cancels_running_turn_and_triggers_auto_archive_check() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+
+        state.apply(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
+        let run_id = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation")
+            .active_run_id
+            .expect("missing active run id");
+
+        let effects = state.apply(Action::TaskStatusSet {
+            workspace_id,
+            thread_id,
+            task_status: crate::TaskStatus::Done,
+        });
+
+        assert!(
+            effects.iter().any(|e| matches!(
+                e,
+                Effect::CancelAgentTurn { workspace_id: wid, thread_id: tid, run_id: rid }
+                    if *wid == workspace_id && *tid == thread_id && *rid == run_id
+            )),
+            "expected CancelAgentTurn effect"
+        );
+        assert!(
+            effects.iter().any(|e| matches!(
+                e,
+                Effect::MaybeAutoArchiveWorkspace { workspace_id: wid } if *wid == workspace_id
+            )),
+            "expected MaybeAutoArchiveWorkspace effect"
+        );
+
+        let conversation = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation");
+        assert_eq!(conversation.task_status, crate::TaskStatus::Done);
+        assert_eq!(conversation.run_status, OperationStatus::Idle);
+        assert_eq!(conversation.active_run_id, None);
+        assert!(conversation.queue_paused);
     }
 
     #[test]
-    fn conversation_loaded_restores_queued_prompts_when_local_is_empty() {
+    fn send_agent_message_is_blocked_for_archived_tasks() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
 
-        state.apply(Action::ConversationLoaded {
+        state.apply(Action::SendAgentMessage {
             workspace_id,
             thread_id,
-            snapshot: ConversationSnapshot {
-                title: None,
-                thread_id: None,
-                task_status: crate::TaskStatus::Todo,
-                runner: None,
-                agent_model_id: None,
-                thinking_effort: None,
-                amp_mode: None,
-                entries: Vec::new(),
-                entries_total: 0,
-                entries_start: 0,
-                pending_prompts: vec![QueuedPrompt {
-                    id: 3,
-                    text: "Queued".to_owned(),
-                    attachments: Vec::new(),
-                    run_config: AgentRunConfig {
-                        runner: crate::AgentRunnerKind::Codex,
-                        model_id: "gpt-5.3-codex".to_owned(),
-                        thinking_effort: ThinkingEffort::Minimal,
-                        amp_mode: None,
-                    },
-                }],
-                queue_paused: true,
-                run_started_at_unix_ms: None,
-                run_finished_at_unix_ms: None,
-            },
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
         });
 
-        let conversation = state.workspace_conversation(workspace_id).unwrap();
-        assert!(conversation.queue_paused);
-        assert_eq!(conversation.pending_prompts.len(), 1);
-        assert_eq!(conversation.pending_prompts[0].id, 3);
-        assert_eq!(conversation.next_queued_prompt_id, 4);
+        state.apply(Action::TaskStatusSet {
+            workspace_id,
+            thread_id,
+            task_status: crate::TaskStatus::Done,
+        });
+
+        let effects = state.apply(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: "Should be blocked".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
+        assert!(effects.is_empty());
+        assert_eq!(state.last_error.as_deref(), Some("Task is archived"));
     }
 
     #[test]
-    fn conversation_loaded_applies_persisted_run_config() {
+    fn task_status_restored_reopens_closed_task() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
 
-        state.apply(Action::ConversationLoaded {
+        state.apply(Action::SendAgentMessage {
             workspace_id,
             thread_id,
-            snapshot: ConversationSnapshot {
-                title: None,
-                thread_id: None,
-                task_status: crate::TaskStatus::Todo,
-                runner: None,
-                agent_model_id: Some("gpt-5.3-codex".to_owned()),
-                thinking_effort: Some(ThinkingEffort::High),
-                amp_mode: None,
-                entries: Vec::new(),
-                entries_total: 0,
-                entries_start: 0,
-                pending_prompts: Vec::new(),
-                queue_paused: false,
-                run_started_at_unix_ms: None,
-                run_finished_at_unix_ms: None,
-            },
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
+        state.apply(Action::TaskStatusSet {
+            workspace_id,
+            thread_id,
+            task_status: crate::TaskStatus::Done,
+        });
+
+        let effects = state.apply(Action::TaskStatusRestored {
+            workspace_id,
+            thread_id,
+            task_status: crate::TaskStatus::Done,
         });
+        assert!(effects.is_empty(), "restoring cannot close a task");
 
+        let effects = state.apply(Action::TaskStatusRestored {
+            workspace_id,
+            thread_id,
+            task_status: crate::TaskStatus::Iterating,
+        });
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::StoreConversationTaskStatus {
+                task_status: crate::TaskStatus::Iterating,
+                ..
+            }
+        )));
         let conversation = state
             .workspace_thread_conversation(workspace_id, thread_id)
-            .expect("missing conversation");
-        assert_eq!(conversation.agent_model_id, "gpt-5.3-codex");
-        assert_eq!(conversation.thinking_effort, ThinkingEffort::High);
+            .expect("conversation");
+        assert_eq!(conversation.task_status, crate::TaskStatus::Iterating);
+        let tabs = state.workspace_tabs(workspace_id).expect("tabs");
+        assert!(!tabs.archived_tabs.contains(&thread_id));
     }
 
     #[test]
-    fn conversation_entries_are_bounded_in_memory() {
+    fn agent_item_completed_is_idempotent() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
@@ -5716,6 +7589,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         let run_id = state
             .workspace_thread_conversation(workspace_id, thread_id)
@@ -5723,361 +7597,710 @@ mod tests {
             .active_run_id
             .expect("missing active run id");
 
-        let total = crate::state::MAX_CONVERSATION_ENTRIES_IN_MEMORY + 100;
-        for idx in 0..total {
-            state.apply(Action::AgentEventReceived {
-                workspace_id,
-                thread_id,
-                run_id,
-                event: CodexThreadEvent::TurnDuration {
-                    duration_ms: idx as u64,
-                },
-            });
-        }
+        let item = CodexThreadItem::AgentMessage {
+            id: "item_0".to_owned(),
+            text: "Hi".to_owned(),
+        };
+
+        state.apply(Action::AgentEventReceived {
+            workspace_id,
+            thread_id,
+            run_id,
+            event: CodexThreadEvent::ItemCompleted { item: item.clone() },
+        });
+        state.apply(Action::AgentEventReceived {
+            workspace_id,
+            thread_id,
+            run_id,
+            event: CodexThreadEvent::ItemCompleted { item },
+        });
 
         let conversation = state.workspace_conversation(workspace_id).unwrap();
-        assert_eq!(
-            conversation.entries.len(),
-            crate::state::MAX_CONVERSATION_ENTRIES_IN_MEMORY
-        );
-        assert_eq!(conversation.entries_start, 102);
-        assert_eq!(conversation.entries_total, (total + 2) as u64);
+        let completed_items = conversation
+            .entries
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e,
+                    ConversationEntry::AgentEvent {
+                        event: crate::AgentEvent::Message { id, .. },
+                        ..
+                    } if id == "item_0"
+                )
+            })
+            .count();
+        assert_eq!(completed_items, 1);
+    }
+
+    #[test]
+    fn agent_item_completed_is_idempotent_even_if_not_last_entry() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+
+        state.apply(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
+        let run_id = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("missing conversation")
+            .active_run_id
+            .expect("missing active run id");
+
+        let item = CodexThreadItem::AgentMessage {
+            id: "item_0".to_owned(),
+            text: "Hi".to_owned(),
+        };
+
+        state.apply(Action::AgentEventReceived {
+            workspace_id,
+            thread_id,
+            run_id,
+            event: CodexThreadEvent::ItemCompleted { item: item.clone() },
+        });
+        state.apply(Action::AgentEventReceived {
+            workspace_id,
+            thread_id,
+            run_id,
+            event: CodexThreadEvent::TurnDuration { duration_ms: 1000 },
+        });
+        state.apply(Action::AgentEventReceived {
+            workspace_id,
+            thread_id,
+            run_id,
+            event: CodexThreadEvent::ItemCompleted { item },
+        });
+
+        let conversation = state.workspace_conversation(workspace_id).unwrap();
+        let completed_items = conversation
+            .entries
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e,
+                    ConversationEntry::AgentEvent {
+                        event: crate::AgentEvent::Message { id, .. },
+                        ..
+                    } if id == "item_0"
+                )
+            })
+            .count();
+        assert_eq!(completed_items, 1);
+    }
+
+    #[test]
+    fn cancel_agent_turn_sets_idle_and_emits_effect() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+
+        state.apply(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
+
+        let effects = state.apply(Action::CancelAgentTurn {
+            workspace_id,
+            thread_id,
+        });
+        assert_eq!(effects.len(), 1);
+        assert!(matches!(effects[0], Effect::CancelAgentTurn { .. }));
+
+        let conversation = state.workspace_conversation(workspace_id).unwrap();
+        assert_eq!(conversation.run_status, OperationStatus::Idle);
+        assert!(matches!(
+            conversation.entries.last(),
+            Some(ConversationEntry::AgentEvent {
+                event: crate::AgentEvent::TurnCanceled,
+                ..
+            })
+        ));
+    }
+
+    fn send_hello(state: &mut AppState, workspace_id: WorkspaceId, thread_id: WorkspaceThreadId) {
+        state.apply(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: "Hello".to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        });
     }
 
     #[test]
-    fn send_agent_message_sets_running_and_emits_effect() {
+    fn fork_copies_the_conversation_up_to_the_fork_point() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
+        send_hello(&mut state, workspace_id, thread_id);
+        let source = state.workspace_conversation(workspace_id).unwrap();
+        let fork_index = source
+            .entries
+            .iter()
+            .position(|entry| matches!(entry, ConversationEntry::UserEvent { .. }))
+            .unwrap();
+        let entry_id = match &source.entries[fork_index] {
+            ConversationEntry::UserEvent { entry_id, .. } => entry_id.clone(),
+            _ => unreachable!(),
+        };
 
-        let effects = state.apply(Action::SendAgentMessage {
+        let effects = state.apply(Action::ForkWorkspaceThread {
             workspace_id,
             thread_id,
-            text: "Hello".to_owned(),
-            attachments: Vec::new(),
-            runner: None,
-            amp_mode: None,
+            entry_id: "missing".to_owned(),
+            target_workspace_id: workspace_id,
         });
-        let run_effect = effects
-            .iter()
-            .find(|e| matches!(e, Effect::RunAgentTurn { .. }))
-            .expect("missing RunAgentTurn effect");
+        assert!(effects.is_empty());
+        assert_eq!(state.last_error.as_deref(), Some("Fork point not found"));
+
+        let effects = state.apply(Action::ForkWorkspaceThread {
+            workspace_id,
+            thread_id,
+            entry_id: entry_id.clone(),
+            target_workspace_id: workspace_id,
+        });
+        let forked_id = state.active_thread_id(workspace_id).unwrap();
+        assert_ne!(forked_id, thread_id);
         assert!(matches!(
-            run_effect,
-            Effect::RunAgentTurn {
-                workspace_id: wid,
-                thread_id: tid,
-                text,
-                run_config,
-                ..
-            } if *wid == workspace_id
-                && *tid == thread_id
-                && text == "Hello"
-                && run_config.model_id == default_agent_model_id()
-                && run_config.thinking_effort == default_thinking_effort()
+            effects.as_slice(),
+            [Effect::SaveAppState, Effect::ForkConversation { thread_id: tid, origin, .. }]
+                if *tid == forked_id && origin.thread_id == thread_id && origin.entry_id == entry_id
         ));
+        let fork = state
+            .workspace_thread_conversation(workspace_id, forked_id)
+            .unwrap();
+        assert_eq!(fork.entries.len(), fork_index + 1);
+        assert_eq!(fork.run_status, OperationStatus::Idle);
+        assert!(fork.thread_id.is_none());
+        assert_eq!(
+            fork.forked_from.as_ref().map(|origin| origin.thread_id),
+            Some(thread_id)
+        );
 
-        let conversation = state.workspace_conversation(workspace_id).unwrap();
-        assert_eq!(conversation.run_status, OperationStatus::Running);
-        let user_messages = conversation
+        state.apply(Action::WorkspaceThreadForkFailed {
+            workspace_id,
+            thread_id: forked_id,
+            message: "copy failed".to_owned(),
+        });
+        assert!(
+            state
+                .workspace_thread_conversation(workspace_id, forked_id)
+                .is_none()
+        );
+        assert_eq!(state.last_error.as_deref(), Some("copy failed"));
+    }
+
+    fn last_turn_limit_event(
+        state: &AppState,
+        workspace_id: WorkspaceId,
+    ) -> Option<(crate::TurnLimitKind, u64, u64)> {
+        let conversation = state.workspace_conversation(workspace_id)?;
+        conversation
             .entries
             .iter()
-            .filter_map(|e| match e {
-                ConversationEntry::UserEvent {
-                    event: crate::UserEvent::Message { text, .. },
+            .rev()
+            .find_map(|entry| match entry {
+                ConversationEntry::SystemEvent {
+                    event:
+                        crate::ConversationSystemEvent::TurnLimitReached {
+                            kind,
+                            limit,
+                            observed,
+                        },
                     ..
-                } => Some(text.as_str()),
+                } => Some((*kind, *limit, *observed)),
                 _ => None,
             })
-            .collect::<Vec<_>>();
-        assert_eq!(user_messages, vec!["Hello"]);
     }
 
     #[test]
-    fn task_status_canceled_cancels_running_turn_and_emits_effect() {
+    fn tool_call_limit_cancels_the_turn_once_exceeded() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
-
-        state.apply(Action::SendAgentMessage {
-            workspace_id,
-            thread_id,
-            text: "Hello".to_owned(),
-            attachments: Vec::new(),
-            runner: None,
-            amp_mode: None,
+        let project_id = state
+            .projects
+            .iter()
+            .find(|p| p.workspaces.iter().any(|w| w.id == workspace_id))
+            .map(|p| p.id)
+            .unwrap();
+        state.apply(Action::ProjectTurnLimitsChanged {
+            project_id,
+            limits: crate::TurnLimits {
+                max_tool_calls_per_turn: Some(2),
+                ..crate::TurnLimits::default()
+            },
         });
+        send_hello(&mut state, workspace_id, thread_id);
         let run_id = state
-            .workspace_thread_conversation(workspace_id, thread_id)
-            .expect("missing conversation")
-            .active_run_id
-            .expect("missing active run id");
+            .workspace_conversation(workspace_id)
+            .and_then(|c| c.active_run_id)
+            .unwrap();
+
+        let command = |id: &str| CodexThreadItem::CommandExecution {
+            id: id.to_owned(),
+            command: "ls".to_owned(),
+            aggregated_output: String::new(),
+            exit_code: None,
+            status: crate::CodexCommandExecutionStatus::InProgress,
+        };
+        for event in [
+            CodexThreadEvent::ItemStarted {
+                item: command("cmd_1"),
+            },
+            CodexThreadEvent::ItemCompleted {
+                item: command("cmd_1"),
+            },
+            CodexThreadEvent::ItemStarted {
+                item: command("cmd_2"),
+            },
+        ] {
+            let effects = state.apply(Action::AgentEventReceived {
+                workspace_id,
+                thread_id,
+                run_id,
+                event,
+            });
+            assert!(effects.is_empty());
+        }
 
-        let effects = state.apply(Action::TaskStatusSet {
+        let effects = state.apply(Action::AgentEventReceived {
             workspace_id,
             thread_id,
-            task_status: crate::TaskStatus::Canceled,
+            run_id,
+            event: CodexThreadEvent::ItemStarted {
+                item: command("cmd_3"),
+            },
         });
-
-        assert!(
-            effects.iter().any(|e| matches!(
-                e,
-                Effect::CancelAgentTurn { workspace_id: wid, thread_id: tid, run_id: rid }
-                    if *wid == workspace_id && *tid == thread_id && *rid == run_id
-            )),
-            "expected CancelAgentTurn effect"
+        assert!(matches!(
+            effects.as_slice(),
+            [Effect::CancelAgentTurn { run_id: canceled, .. }] if *canceled == run_id
+        ));
+        assert_eq!(
+            last_turn_limit_event(&state, workspace_id),
+            Some((crate::TurnLimitKind::ToolCalls, 2, 3))
         );
-
-        let conversation = state
-            .workspace_thread_conversation(workspace_id, thread_id)
-            .expect("missing conversation");
-        assert_eq!(conversation.task_status, crate::TaskStatus::Canceled);
+        let conversation = state.workspace_conversation(workspace_id).unwrap();
         assert_eq!(conversation.run_status, OperationStatus::Idle);
-        assert_eq!(conversation.active_run_id, None);
         assert!(conversation.queue_paused);
-        assert!(conversation.run_finished_at_unix_ms.is_some());
-        assert!(conversation.entries.iter().any(|e| {
-            matches!(
-                e,
-                ConversationEntry::AgentEvent {
-                    event: crate::AgentEvent::TurnCanceled,
-                    ..
-                }
-            )
-        }));
     }
 
     #[test]
-    fn task_status_done_cancels_running_turn_and_triggers_auto_archive_check() {
+    fn turn_timeout_cancels_only_the_run_it_was_armed_for() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
+        send_hello(&mut state, workspace_id, thread_id);
+        let run_id = state
+            .workspace_conversation(workspace_id)
+            .and_then(|c| c.active_run_id)
+            .unwrap();
 
-        state.apply(Action::SendAgentMessage {
+        let stale = state.apply(Action::AgentTurnTimedOut {
             workspace_id,
             thread_id,
-            text: "Hello".to_owned(),
-            attachments: Vec::new(),
-            runner: None,
-            amp_mode: None,
+            run_id: run_id + 1,
+            timeout_secs: 60,
         });
-        let run_id = state
-            .workspace_thread_conversation(workspace_id, thread_id)
-            .expect("missing conversation")
-            .active_run_id
-            .expect("missing active run id");
+        assert!(stale.is_empty());
 
-        let effects = state.apply(Action::TaskStatusSet {
+        let effects = state.apply(Action::AgentTurnTimedOut {
             workspace_id,
             thread_id,
-            task_status: crate::TaskStatus::Done,
+            run_id,
+            timeout_secs: 60,
         });
-
-        assert!(
-            effects.iter().any(|e| matches!(
-                e,
-                Effect::CancelAgentTurn { workspace_id: wid, thread_id: tid, run_id: rid }
-                    if *wid == workspace_id && *tid == thread_id && *rid == run_id
-            )),
-            "expected CancelAgentTurn effect"
-        );
-        assert!(
-            effects.iter().any(|e| matches!(
-                e,
-                Effect::MaybeAutoArchiveWorkspace { workspace_id: wid } if *wid == workspace_id
-            )),
-            "expected MaybeAutoArchiveWorkspace effect"
-        );
-
-        let conversation = state
-            .workspace_thread_conversation(workspace_id, thread_id)
-            .expect("missing conversation");
-        assert_eq!(conversation.task_status, crate::TaskStatus::Done);
-        assert_eq!(conversation.run_status, OperationStatus::Idle);
-        assert_eq!(conversation.active_run_id, None);
-        assert!(conversation.queue_paused);
+        assert!(matches!(
+            effects.as_slice(),
+            [Effect::CancelAgentTurn { .. }]
+        ));
+        assert!(matches!(
+            last_turn_limit_event(&state, workspace_id),
+            Some((crate::TurnLimitKind::Timeout, 60, _))
+        ));
     }
 
     #[test]
-    fn send_agent_message_is_blocked_for_archived_tasks() {
+    fn review_comments_track_pending_edited_and_resolved_state() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
+        send_hello(&mut state, workspace_id, thread_id);
 
-        state.apply(Action::SendAgentMessage {
+        let add = |state: &mut AppState, start_line: u32, end_line: u32, body: &str| {
+            state.apply(Action::AddReviewComment {
+                workspace_id,
+                thread_id,
+                path: "./src/lib.rs".to_owned(),
+                start_line,
+                end_line,
+                quote: "let x = 1;".to_owned(),
+                body: body.to_owned(),
+            })
+        };
+        assert!(matches!(
+            add(&mut state, 3, 4, " use a const ").as_slice(),
+            [Effect::SaveAppState]
+        ));
+        assert!(add(&mut state, 5, 2, "backwards").is_empty());
+        assert_eq!(state.last_error.as_deref(), Some("Invalid review comment"));
+        add(&mut state, 9, 9, "drop this");
+
+        let comments = state.task_review_comments(workspace_id, thread_id);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].path, "src/lib.rs");
+        assert_eq!(comments[0].body, "use a const");
+        assert_eq!(comments[1].id, 2);
+
+        state.apply(Action::ReviewCommentsSent {
             workspace_id,
             thread_id,
-            text: "Hello".to_owned(),
-            attachments: Vec::new(),
-            runner: None,
-            amp_mode: None,
+            comment_ids: vec![1, 2],
         });
-
-        state.apply(Action::TaskStatusSet {
+        state.apply(Action::ResolveReviewComment {
             workspace_id,
             thread_id,
-            task_status: crate::TaskStatus::Done,
+            comment_id: 2,
+            resolved: true,
         });
-
-        let effects = state.apply(Action::SendAgentMessage {
+        state.apply(Action::EditReviewComment {
             workspace_id,
             thread_id,
-            text: "Should be blocked".to_owned(),
-            attachments: Vec::new(),
-            runner: None,
-            amp_mode: None,
+            comment_id: 1,
+            body: "use a const named LIMIT".to_owned(),
         });
-        assert!(effects.is_empty());
-        assert_eq!(state.last_error.as_deref(), Some("Task is archived"));
+        let pending = state
+            .task_review_comments(workspace_id, thread_id)
+            .iter()
+            .filter(|c| c.is_pending())
+            .map(|c| c.id)
+            .collect::<Vec<_>>();
+        assert_eq!(pending, vec![1]);
+        assert!(state.task_review_comments(workspace_id, thread_id)[1].resolved);
     }
 
     #[test]
-    fn agent_item_completed_is_idempotent() {
+    fn pull_request_review_comments_import_once_with_a_system_event_each() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
+        send_hello(&mut state, workspace_id, thread_id);
+
+        let fetched = |comment_id: u64, path: &str| crate::PullRequestReviewComment {
+            comment_id,
+            author: "octocat".to_owned(),
+            path: path.to_owned(),
+            start_line: 2,
+            end_line: 3,
+            body: format!("comment {comment_id}"),
+            url: format!("https://github.com/acme/app/pull/7#discussion_r{comment_id}"),
+            diff_hunk: "@@ -1,3 +1,3 @@\n fn a() {}\n-let x = 1;\n+let x = 2;\n+let y = 3;"
+                .to_owned(),
+        };
+        let fetch = |state: &mut AppState, comments| {
+            state.apply(Action::PullRequestReviewCommentsFetched {
+                workspace_id,
+                thread_id,
+                pr_number: 7,
+                comments,
+            })
+        };
+        assert!(matches!(
+            fetch(&mut state, vec![fetched(11, "src/a.rs"), fetched(12, "")]).as_slice(),
+            [Effect::SaveAppState]
+        ));
+        assert!(fetch(&mut state, vec![fetched(11, "src/a.rs")]).is_empty());
+        fetch(
+            &mut state,
+            vec![fetched(11, "src/a.rs"), fetched(13, "src/b.rs")],
+        );
 
-        state.apply(Action::SendAgentMessage {
+        let comments = state.task_review_comments(workspace_id, thread_id);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].quote, "+let x = 2;\n+let y = 3;");
+        assert!(comments[1].is_pending());
+        let events = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("conversation exists")
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                ConversationEntry::SystemEvent {
+                    event:
+                        crate::ConversationSystemEvent::PullRequestReviewComment {
+                            review_comment_id,
+                            ..
+                        },
+                    ..
+                } => Some(*review_comment_id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![1, 2]);
+
+        state.apply(Action::ReviewCommentReplyPosted {
             workspace_id,
             thread_id,
-            text: "Hello".to_owned(),
-            attachments: Vec::new(),
-            runner: None,
-            amp_mode: None,
+            comment_id: 1,
         });
-        let run_id = state
-            .workspace_thread_conversation(workspace_id, thread_id)
-            .expect("missing conversation")
-            .active_run_id
-            .expect("missing active run id");
+        assert!(
+            state.task_review_comments(workspace_id, thread_id)[0]
+                .pull_request
+                .as_ref()
+                .is_some_and(|origin| origin.replied)
+        );
 
-        let item = CodexThreadItem::AgentMessage {
-            id: "item_0".to_owned(),
-            text: "Hi".to_owned(),
+        let sync = crate::PullRequestReviewSync {
+            auto_pull: true,
+            reply_when_addressed: false,
         };
+        assert!(matches!(
+            state
+                .apply(Action::PullRequestReviewSyncChanged {
+                    workspace_id,
+                    thread_id,
+                    sync,
+                })
+                .as_slice(),
+            [Effect::SaveAppState]
+        ));
+        assert_eq!(
+            state.pull_request_review_auto_pull_tasks(workspace_id),
+            vec![thread_id]
+        );
+    }
 
-        state.apply(Action::AgentEventReceived {
+    #[test]
+    fn ci_auto_fix_records_attempts_until_the_budget_runs_out() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+        send_hello(&mut state, workspace_id, thread_id);
+
+        assert!(
+            state
+                .apply(Action::CiAutoFixChanged {
+                    workspace_id,
+                    thread_id,
+                    enabled: true,
+                    max_attempts: 0,
+                })
+                .is_empty()
+        );
+        state.apply(Action::CiAutoFixChanged {
             workspace_id,
             thread_id,
-            run_id,
-            event: CodexThreadEvent::ItemCompleted { item: item.clone() },
+            enabled: true,
+            max_attempts: 2,
         });
-        state.apply(Action::AgentEventReceived {
+        assert_eq!(state.ci_auto_fix_tasks(workspace_id), vec![thread_id]);
+
+        let attempt = |state: &mut AppState, run_id: u64| {
+            state.apply(Action::CiFixAttemptStarted {
+                workspace_id,
+                thread_id,
+                pr_number: 7,
+                run_id,
+                workflow: "CI".to_owned(),
+                url: format!("https://github.com/acme/app/actions/runs/{run_id}"),
+            })
+        };
+        assert!(matches!(
+            attempt(&mut state, 100).as_slice(),
+            [Effect::SaveAppState]
+        ));
+        assert!(attempt(&mut state, 100).is_empty());
+        attempt(&mut state, 101);
+        assert!(attempt(&mut state, 102).is_empty());
+        let fix = state
+            .task_ci_auto_fix(workspace_id, thread_id)
+            .expect("loop exists");
+        assert_eq!(fix.attempts_used(), 2);
+        assert!(!fix.has_attempts_left());
+
+        state.apply(Action::CiAutoFixStopped {
             workspace_id,
             thread_id,
-            run_id,
-            event: CodexThreadEvent::ItemCompleted { item },
+            reason: crate::CiAutoFixStopReason::AttemptsExhausted,
         });
-
-        let conversation = state.workspace_conversation(workspace_id).unwrap();
-        let completed_items = conversation
+        assert!(state.ci_auto_fix_tasks(workspace_id).is_empty());
+        let events = state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("conversation exists")
             .entries
             .iter()
-            .filter(|e| {
-                matches!(
-                    e,
-                    ConversationEntry::AgentEvent {
-                        event: crate::AgentEvent::Message { id, .. },
-                        ..
-                    } if id == "item_0"
-                )
+            .filter_map(|entry| match entry {
+                ConversationEntry::SystemEvent { event, .. } => match event {
+                    crate::ConversationSystemEvent::CiFixAttempt { attempt, .. } => {
+                        Some(format!("attempt {attempt}"))
+                    }
+                    crate::ConversationSystemEvent::CiAutoFixStopped { attempts, .. } => {
+                        Some(format!("stopped after {attempts}"))
+                    }
+                    _ => None,
+                },
+                _ => None,
             })
-            .count();
-        assert_eq!(completed_items, 1);
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec!["attempt 1", "attempt 2", "stopped after 2"]);
+
+        state.apply(Action::CiAutoFixChanged {
+            workspace_id,
+            thread_id,
+            enabled: true,
+            max_attempts: 2,
+        });
+        assert!(
+            state
+                .task_ci_auto_fix(workspace_id, thread_id)
+                .is_some_and(|fix| fix.attempts.is_empty())
+        );
     }
 
     #[test]
-    fn agent_item_completed_is_idempotent_even_if_not_last_entry() {
+    fn turn_over_its_token_budget_does_not_start_the_next_queued_prompt() {
         let mut state = AppState::demo();
         let workspace_id = first_non_main_workspace_id(&state);
         let thread_id = default_thread_id();
-
-        state.apply(Action::SendAgentMessage {
+        state.apply(Action::TaskTurnLimitsChanged {
             workspace_id,
             thread_id,
-            text: "Hello".to_owned(),
-            attachments: Vec::new(),
-            runner: None,
-            amp_mode: None,
+            limits: crate::TurnLimits {
+                turn_token_budget: Some(1_000),
+                ..crate::TurnLimits::default()
+            },
         });
+        send_hello(&mut state, workspace_id, thread_id);
+        send_hello(&mut state, workspace_id, thread_id);
         let run_id = state
-            .workspace_thread_conversation(workspace_id, thread_id)
-            .expect("missing conversation")
-            .active_run_id
-            .expect("missing active run id");
-
-        let item = CodexThreadItem::AgentMessage {
-            id: "item_0".to_owned(),
-            text: "Hi".to_owned(),
-        };
+            .workspace_conversation(workspace_id)
+            .and_then(|c| c.active_run_id)
+            .unwrap();
 
-        state.apply(Action::AgentEventReceived {
+        let effects = state.apply(Action::AgentEventReceived {
             workspace_id,
             thread_id,
             run_id,
-            event: CodexThreadEvent::ItemCompleted { item: item.clone() },
+            event: CodexThreadEvent::TurnCompleted {
+                usage: CodexUsage {
+                    input_tokens: 1_500,
+                    cached_input_tokens: 0,
+                    output_tokens: 100,
+                },
+            },
         });
-        state.apply(Action::AgentEventReceived {
+        assert!(
+            !effects
+                .iter()
+                .any(|e| matches!(e, Effect::RunAgentTurn { .. }))
+        );
+        let conversation = state.workspace_conversation(workspace_id).unwrap();
+        assert!(conversation.queue_paused);
+        assert_eq!(conversation.pending_prompts.len(), 1);
+    }
+
+    #[test]
+    fn task_token_budget_blocks_new_turns_until_raised() {
+        let mut state = AppState::demo();
+        let workspace_id = first_non_main_workspace_id(&state);
+        let thread_id = default_thread_id();
+        send_hello(&mut state, workspace_id, thread_id);
+        let effects = state.apply(Action::TaskTurnLimitsChanged {
             workspace_id,
             thread_id,
-            run_id,
-            event: CodexThreadEvent::TurnDuration { duration_ms: 1000 },
+            limits: crate::TurnLimits {
+                turn_token_budget: Some(1_000),
+                task_token_budget: Some(1_500),
+                ..crate::TurnLimits::default()
+            },
         });
-        state.apply(Action::AgentEventReceived {
+        assert!(matches!(effects.as_slice(), [Effect::SaveAppState]));
+        let run_id = state
+            .workspace_conversation(workspace_id)
+            .and_then(|c| c.active_run_id)
+            .unwrap();
+
+        let effects = state.apply(Action::AgentEventReceived {
             workspace_id,
             thread_id,
             run_id,
-            event: CodexThreadEvent::ItemCompleted { item },
+            event: CodexThreadEvent::TurnCompleted {
+                usage: CodexUsage {
+                    input_tokens: 1_200,
+                    cached_input_tokens: 800,
+                    output_tokens: 400,
+                },
+            },
         });
-
-        let conversation = state.workspace_conversation(workspace_id).unwrap();
-        let completed_items = conversation
+        assert!(effects.iter().any(|e| matches!(e, Effect::SaveAppState)));
+        assert_eq!(state.task_tokens_used(workspace_id, thread_id), 1_600);
+        assert!(state.task_token_budget_exhausted(workspace_id, thread_id));
+        let kinds = state
+            .workspace_conversation(workspace_id)
+            .unwrap()
             .entries
             .iter()
-            .filter(|e| {
-                matches!(
-                    e,
-                    ConversationEntry::AgentEvent {
-                        event: crate::AgentEvent::Message { id, .. },
-                        ..
-                    } if id == "item_0"
-                )
+            .filter_map(|entry| match entry {
+                ConversationEntry::SystemEvent {
+                    event: crate::ConversationSystemEvent::TurnLimitReached { kind, .. },
+                    ..
+                } => Some(*kind),
+                _ => None,
             })
-            .count();
-        assert_eq!(completed_items, 1);
-    }
-
-    #[test]
-    fn cancel_agent_turn_sets_idle_and_emits_effect() {
-        let mut state = AppState::demo();
-        let workspace_id = first_non_main_workspace_id(&state);
-        let thread_id = default_thread_id();
-
-        state.apply(Action::SendAgentMessage {
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                crate::TurnLimitKind::TurnTokens,
+                crate::TurnLimitKind::TaskTokens
+            ]
+        );
+        state.apply(Action::AgentTurnFinished {
             workspace_id,
             thread_id,
-            text: "Hello".to_owned(),
-            attachments: Vec::new(),
-            runner: None,
-            amp_mode: None,
+            run_id,
         });
 
-        let effects = state.apply(Action::CancelAgentTurn {
+        send_hello(&mut state, workspace_id, thread_id);
+        assert!(
+            state
+                .last_error
+                .as_deref()
+                .is_some_and(|e| e.contains("token budget"))
+        );
+        assert!(
+            state
+                .workspace_conversation(workspace_id)
+                .is_some_and(|c| c.active_run_id.is_none())
+        );
+
+        state.apply(Action::TaskTurnLimitsChanged {
             workspace_id,
             thread_id,
+            limits: crate::TurnLimits {
+                task_token_budget: Some(10_000),
+                ..crate::TurnLimits::default()
+            },
         });
-        assert_eq!(effects.len(), 1);
-        assert!(matches!(effects[0], Effect::CancelAgentTurn { .. }));
-
-        let conversation = state.workspace_conversation(workspace_id).unwrap();
-        assert_eq!(conversation.run_status, OperationStatus::Idle);
-        assert!(matches!(
-            conversation.entries.last(),
-            Some(ConversationEntry::AgentEvent {
-                event: crate::AgentEvent::TurnCanceled,
-                ..
-            })
-        ));
+        send_hello(&mut state, workspace_id, thread_id);
+        assert!(
+            state
+                .workspace_conversation(workspace_id)
+                .is_some_and(|c| c.active_run_id.is_some())
+        );
     }
 
     #[test]
@@ -6093,6 +8316,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         let effects = state.apply(Action::SendAgentMessage {
             workspace_id,
@@ -6101,6 +8325,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         assert!(effects.is_empty());
 
@@ -6132,6 +8357,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         state.apply(Action::SendAgentMessage {
             workspace_id,
@@ -6140,6 +8366,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         state.apply(Action::SendAgentMessage {
             workspace_id,
@@ -6148,6 +8375,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         let conversation = state.workspace_conversation(workspace_id).unwrap();
@@ -6203,6 +8431,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         state.apply(Action::SendAgentMessage {
             workspace_id,
@@ -6211,6 +8440,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         let run_id = state
@@ -6276,6 +8506,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         state.apply(Action::SendAgentMessage {
             workspace_id,
@@ -6284,6 +8515,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         let run_id = state
@@ -6343,6 +8575,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         let run_id_a = state
             .workspace_thread_conversation(workspace_id, thread_id)
@@ -6362,6 +8595,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
         let run_id_b = state
             .workspace_thread_conversation(workspace_id, thread_id)
@@ -6519,4 +8753,239 @@ mod tests {
         assert!(effects.is_empty());
         assert_eq!(state.last_error.as_deref(), Some("Workspace not found"));
     }
+
+    #[test]
+    fn default_prompt_templates_only_use_known_variables() {
+        let state = AppState::new();
+        for kind in crate::TaskIntentKind::ALL {
+            let template = default_task_prompt_template(kind);
+            assert_eq!(state.task_prompt_template_error(None, &template), None);
+        }
+        for kind in crate::SystemTaskKind::ALL {
+            let template = default_system_prompt_template(kind);
+            assert_eq!(system_prompt_template_error(&template), None);
+        }
+    }
+
+    #[test]
+    fn task_prompt_template_with_unknown_variable_is_rejected() {
+        let mut state = AppState::new();
+        let effects = state.apply(Action::TaskPromptTemplateChanged {
+            project_id: None,
+            intent_kind: crate::TaskIntentKind::Review,
+            template: "Review {{task_input}} on {{brnach}}".to_owned(),
+        });
+        assert!(effects.is_empty());
+        assert_eq!(
+            state.last_error.as_deref(),
+            Some("Unknown template variables: {{brnach}}")
+        );
+        assert_eq!(
+            state.task_prompt_templates[&crate::TaskIntentKind::Review],
+            default_task_prompt_template(crate::TaskIntentKind::Review)
+        );
+    }
+
+    #[test]
+    fn project_task_prompt_override_requires_defined_custom_variables() {
+        let mut state = AppState::new();
+        state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/repo"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        let template = "{{task_input}}\n\n{{vars.checklist}}".to_owned();
+
+        let effects = state.apply(Action::TaskPromptTemplateChanged {
+            project_id: Some(project_id),
+            intent_kind: crate::TaskIntentKind::Review,
+            template: template.clone(),
+        });
+        assert!(effects.is_empty());
+        assert_eq!(
+            state.last_error.as_deref(),
+            Some("Unknown template variables: {{vars.checklist}}")
+        );
+
+        let effects = state.apply(Action::ProjectPromptVariablesChanged {
+            project_id,
+            variables: BTreeMap::from([("checklist".to_owned(), "- run tests".to_owned())]),
+        });
+        assert!(matches!(
+            effects.as_slice(),
+            [Effect::StoreProjectPromptVariables { .. }]
+        ));
+
+        let effects = state.apply(Action::TaskPromptTemplateChanged {
+            project_id: Some(project_id),
+            intent_kind: crate::TaskIntentKind::Review,
+            template: template.clone(),
+        });
+        assert!(matches!(
+            effects.as_slice(),
+            [Effect::StoreProjectTaskPromptTemplate {
+                intent_kind: crate::TaskIntentKind::Review,
+                ..
+            }]
+        ));
+        assert_eq!(
+            state.project_prompt_settings[&project_id].task_prompt_templates
+                [&crate::TaskIntentKind::Review],
+            template
+        );
+
+        let effects = state.apply(Action::TaskPromptTemplateChanged {
+            project_id: Some(project_id),
+            intent_kind: crate::TaskIntentKind::Review,
+            template: "  ".to_owned(),
+        });
+        assert!(matches!(
+            effects.as_slice(),
+            [Effect::DeleteProjectTaskPromptTemplate { .. }]
+        ));
+        assert!(
+            state.project_prompt_settings[&project_id]
+                .task_prompt_templates
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn project_prompt_variables_reject_invalid_names() {
+        let mut state = AppState::new();
+        state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/repo"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+
+        let effects = state.apply(Action::ProjectPromptVariablesChanged {
+            project_id,
+            variables: BTreeMap::from([("Bad Name".to_owned(), "x".to_owned())]),
+        });
+        assert!(effects.is_empty());
+        assert!(state.project_prompt_settings.is_empty());
+        assert!(
+            state
+                .last_error
+                .as_deref()
+                .is_some_and(|message| message.starts_with("Invalid template variable name"))
+        );
+    }
+
+    #[test]
+    fn telegram_group_pairing_replaces_previous_group_for_project() {
+        let mut state = AppState::new();
+        let member = |user_id: i64, name: &str| crate::TelegramGroupMember {
+            user_id,
+            display_name: name.to_owned(),
+        };
+
+        state.apply(Action::TelegramGroupPaired {
+            chat_id: -100,
+            project_slug: "repo".to_owned(),
+            title: "Old".to_owned(),
+            member: member(1, "Ada"),
+        });
+        let rev = state.telegram_config_rev();
+        let effects = state.apply(Action::TelegramGroupPaired {
+            chat_id: -200,
+            project_slug: "repo".to_owned(),
+            title: "Team".to_owned(),
+            member: member(1, "Ada"),
+        });
+        assert!(matches!(effects.as_slice(), [Effect::SaveAppState]));
+        assert!(state.telegram_config_rev() > rev);
+        assert_eq!(
+            state
+                .telegram_group_chats()
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![-200]
+        );
+
+        state.apply(Action::TelegramGroupMemberAdded {
+            chat_id: -200,
+            member: member(2, "Grace"),
+        });
+        let effects = state.apply(Action::TelegramGroupMemberAdded {
+            chat_id: -200,
+            member: member(2, "Grace"),
+        });
+        assert!(effects.is_empty());
+        state.apply(Action::TelegramGroupMemberRemoved {
+            chat_id: -200,
+            user_id: 1,
+        });
+        let group = &state.telegram_group_chats()[&-200];
+        assert!(!group.is_member(1));
+        assert!(group.is_member(2));
+    }
+
+    #[test]
+    fn notification_settings_mute_projects_and_validate_quiet_hours() {
+        let mut state = AppState::new();
+        state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/repo"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        let slug = state.projects[0].slug.clone();
+
+        let effects = state.apply(Action::DesktopNotificationsEnabledChanged { enabled: true });
+        assert!(matches!(effects.as_slice(), [Effect::SaveAppState]));
+        let effects = state.apply(Action::ProjectNotificationsMutedChanged {
+            project_id,
+            muted: true,
+        });
+        assert!(matches!(effects.as_slice(), [Effect::SaveAppState]));
+        assert!(state.notification_settings().is_project_muted(&slug));
+        let effects = state.apply(Action::ProjectNotificationsMutedChanged {
+            project_id,
+            muted: true,
+        });
+        assert!(effects.is_empty());
+
+        let effects = state.apply(Action::NotificationQuietHoursChanged {
+            quiet_hours: Some(crate::QuietHours {
+                start_minute: 22 * 60,
+                end_minute: 22 * 60,
+                utc_offset_minutes: 0,
+            }),
+        });
+        assert!(effects.is_empty());
+        assert_eq!(state.last_error.as_deref(), Some("Invalid quiet hours"));
+        assert!(state.notification_settings().quiet_hours.is_none());
+    }
+
+    #[test]
+    fn attachment_text_limits_are_validated_and_saved() {
+        let mut state = AppState::new();
+        let limits = crate::AttachmentTextLimits {
+            max_source_bytes: 4 * 1024 * 1024,
+            max_inline_bytes: 0,
+        };
+        let effects = state.apply(Action::AttachmentTextLimitsChanged { limits });
+        assert!(matches!(effects.as_slice(), [Effect::SaveAppState]));
+        assert_eq!(state.attachment_text_limits(), limits);
+        assert!(
+            state
+                .apply(Action::AttachmentTextLimitsChanged { limits })
+                .is_empty()
+        );
+
+        let effects = state.apply(Action::AttachmentTextLimitsChanged {
+            limits: crate::AttachmentTextLimits {
+                max_source_bytes: 0,
+                max_inline_bytes: 1024,
+            },
+        });
+        assert!(effects.is_empty());
+        assert_eq!(
+            state.last_error.as_deref(),
+            Some("Invalid attachment text limits")
+        );
+        assert_eq!(state.attachment_text_limits(), limits);
+    }
 }
@@ -16,5 +16,7 @@ pub struct QueuedPrompt {
     pub id: u64,
     pub text: String,
     pub attachments: Vec<AttachmentRef>,
+    #[serde(default)]
+    pub author: Option<String>,
     pub run_config: AgentRunConfig,
 }
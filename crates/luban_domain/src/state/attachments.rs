@@ -4,6 +4,7 @@ pub enum AttachmentKind {
     Image,
     Text,
     File,
+    Url,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -22,3 +23,115 @@ pub struct ContextItem {
     pub attachment: AttachmentRef,
     pub created_at_unix_ms: u64,
 }
+
+/// Limits for extracting text from document attachments (PDF, DOCX, ...) before a turn.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AttachmentTextLimits {
+    /// Documents larger than this are passed to the agent as a path only.
+    #[serde(default = "AttachmentTextLimits::default_max_source_bytes")]
+    pub max_source_bytes: u64,
+    /// Extracted text up to this size is inlined into the prompt; longer text is inlined as an
+    /// excerpt. Zero disables inlining.
+    #[serde(default = "AttachmentTextLimits::default_max_inline_bytes")]
+    pub max_inline_bytes: u64,
+}
+
+impl AttachmentTextLimits {
+    pub const MAX_SOURCE_BYTES_LIMIT: u64 = 256 * 1024 * 1024;
+    pub const MAX_INLINE_BYTES_LIMIT: u64 = 1024 * 1024;
+
+    fn default_max_source_bytes() -> u64 {
+        32 * 1024 * 1024
+    }
+
+    fn default_max_inline_bytes() -> u64 {
+        48 * 1024
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (1..=Self::MAX_SOURCE_BYTES_LIMIT).contains(&self.max_source_bytes)
+            && self.max_inline_bytes <= Self::MAX_INLINE_BYTES_LIMIT
+    }
+}
+
+impl Default for AttachmentTextLimits {
+    fn default() -> Self {
+        Self {
+            max_source_bytes: Self::default_max_source_bytes(),
+            max_inline_bytes: Self::default_max_inline_bytes(),
+        }
+    }
+}
+
+/// Which hosts URL attachments may be fetched from. An empty allowlist denies every URL.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct UrlAttachmentSettings {
+    /// Lowercase host names. An entry also allows its subdomains.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+}
+
+impl UrlAttachmentSettings {
+    pub const MAX_DOMAINS: usize = 256;
+
+    /// Normalizes a user-entered domain (`Example.com.`, `*.example.com`) to `example.com`.
+    pub fn normalize_domain(raw: &str) -> Option<String> {
+        let domain = raw.trim().trim_start_matches("*.").trim_end_matches('.');
+        if domain.is_empty() || domain.len() > 253 {
+            return None;
+        }
+        let valid = domain.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+        valid.then(|| domain.to_ascii_lowercase())
+    }
+
+    pub fn from_domains(domains: &[String]) -> Option<Self> {
+        if domains.len() > Self::MAX_DOMAINS {
+            return None;
+        }
+        let mut allowed_domains = domains
+            .iter()
+            .map(|d| Self::normalize_domain(d))
+            .collect::<Option<Vec<_>>>()?;
+        allowed_domains.sort();
+        allowed_domains.dedup();
+        Some(Self { allowed_domains })
+    }
+
+    pub fn allows_host(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.allowed_domains.iter().any(|domain| {
+            host == *domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_attachment_allowlist_matches_hosts_and_subdomains() {
+        let settings = UrlAttachmentSettings::from_domains(&[
+            " *.Example.com. ".to_owned(),
+            "127.0.0.1".to_owned(),
+            "example.com".to_owned(),
+        ])
+        .expect("valid domains");
+        assert_eq!(settings.allowed_domains, vec!["127.0.0.1", "example.com"]);
+
+        assert!(settings.allows_host("example.com"));
+        assert!(settings.allows_host("docs.EXAMPLE.com"));
+        assert!(settings.allows_host("127.0.0.1"));
+        assert!(!settings.allows_host("badexample.com"));
+        assert!(!settings.allows_host("example.com.evil.test"));
+        assert!(!UrlAttachmentSettings::default().allows_host("example.com"));
+
+        assert!(UrlAttachmentSettings::from_domains(&["https://example.com".to_owned()]).is_none());
+        assert!(UrlAttachmentSettings::from_domains(&["".to_owned()]).is_none());
+    }
+}
@@ -0,0 +1,66 @@
+/// Opt-in loop that queues a "fix CI" turn whenever the task's pull request CI fails.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CiAutoFix {
+    #[serde(default)]
+    pub enabled: bool,
+    pub max_attempts: u32,
+    /// Attempts made since the loop was last turned on, oldest first.
+    #[serde(default)]
+    pub attempts: Vec<CiFixAttempt>,
+}
+
+impl CiAutoFix {
+    pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+    pub const MAX_MAX_ATTEMPTS: u32 = 10;
+
+    pub fn attempts_used(&self) -> u32 {
+        u32::try_from(self.attempts.len()).unwrap_or(u32::MAX)
+    }
+
+    pub fn has_attempts_left(&self) -> bool {
+        self.attempts_used() < self.max_attempts
+    }
+
+    /// Whether a fix was already attempted for this failed workflow run.
+    pub fn attempted_run(&self, run_id: u64) -> bool {
+        self.attempts.iter().any(|a| a.run_id == run_id)
+    }
+}
+
+/// One "fix CI" turn queued for a failed workflow run.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CiFixAttempt {
+    pub pr_number: u64,
+    pub run_id: u64,
+    #[serde(default)]
+    pub workflow: String,
+    #[serde(default)]
+    pub url: String,
+    pub started_at_unix_ms: u64,
+}
+
+/// The failed run of a pull request's latest CI, with its failing job logs trimmed to the part
+/// worth reading.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CiFailureLog {
+    pub run_id: u64,
+    pub workflow: String,
+    pub url: String,
+    pub log: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CiAutoFixStopReason {
+    Passed,
+    AttemptsExhausted,
+}
+
+/// Stored form of one task's CI auto-fix loop.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct TaskCiAutoFixRecord {
+    pub workspace_id: u64,
+    pub thread_id: u64,
+    #[serde(flatten)]
+    pub fix: CiAutoFix,
+}
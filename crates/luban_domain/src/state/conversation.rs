@@ -2,10 +2,12 @@ use super::{
     MAX_CONVERSATION_ENTRIES_IN_MEMORY, WorkspaceThreadId,
     agent::{AgentRunConfig, QueuedPrompt},
     attachments::AttachmentRef,
+    ci_fix::CiAutoFixStopReason,
     layout::OperationStatus,
+    limits::TurnLimitKind,
 };
 use crate::{CodexThreadItem, CodexUsage, ContextTokenKind, TaskStatus, ThinkingEffort};
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 
 fn now_unix_ms() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -32,6 +34,69 @@ pub enum ConversationSystemEvent {
         #[serde(default)]
         explanation_markdown: String,
     },
+    TurnInterrupted {
+        #[serde(default)]
+        resumable: bool,
+        /// Whether the user had paused the queue before the turn was interrupted.
+        #[serde(default)]
+        queue_paused: bool,
+    },
+    /// Marks the worktree snapshot taken before the turn that follows.
+    TurnCheckpoint {
+        checkpoint_id: String,
+        commit: String,
+    },
+    TurnRewound {
+        checkpoint_id: String,
+        /// Whether the entries from the rewound turn onwards were dropped.
+        #[serde(default)]
+        truncated: bool,
+    },
+    /// What the turn started at `checkpoint_id` changed; `commit` pins the worktree as the
+    /// turn left it.
+    TurnChanges {
+        checkpoint_id: String,
+        commit: String,
+        files_changed: u64,
+        additions: u64,
+        deletions: u64,
+    },
+    /// A turn or task limit stopped the agent. `limit` and `observed` are in the kind's unit:
+    /// seconds for the timeout, tool calls, or tokens.
+    TurnLimitReached {
+        kind: TurnLimitKind,
+        limit: u64,
+        observed: u64,
+    },
+    /// A reviewer's comment on the pull request was imported as review comment
+    /// `review_comment_id`. A `start_line` of 0 marks a comment on the whole file.
+    PullRequestReviewComment {
+        pr_number: u64,
+        review_comment_id: u64,
+        author: String,
+        path: String,
+        start_line: u32,
+        end_line: u32,
+        body: String,
+        #[serde(default)]
+        url: String,
+    },
+    /// The CI auto-fix loop queued a "fix CI" turn for failed workflow run `run_id`.
+    CiFixAttempt {
+        pr_number: u64,
+        attempt: u32,
+        max_attempts: u32,
+        run_id: u64,
+        #[serde(default)]
+        workflow: String,
+        #[serde(default)]
+        url: String,
+    },
+    /// The CI auto-fix loop turned itself off after `attempts` attempts.
+    CiAutoFixStopped {
+        reason: CiAutoFixStopReason,
+        attempts: u32,
+    },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -41,6 +106,10 @@ pub enum UserEvent {
         text: String,
         #[serde(default)]
         attachments: Vec<AttachmentRef>,
+        /// Display name of the person who sent the message when it did not
+        /// come from the local user (for example a Telegram group member).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
     },
     TerminalCommandStarted {
         id: String,
@@ -123,6 +192,17 @@ pub(crate) fn codex_item_id(item: &CodexThreadItem) -> &str {
     }
 }
 
+/// Items that count towards a turn's tool call limit.
+pub(crate) fn is_tool_call_item(item: &CodexThreadItem) -> bool {
+    matches!(
+        item,
+        CodexThreadItem::CommandExecution { .. }
+            | CodexThreadItem::FileChange { .. }
+            | CodexThreadItem::McpToolCall { .. }
+            | CodexThreadItem::WebSearch { .. }
+    )
+}
+
 fn entry_is_same(a: &ConversationEntry, b: &ConversationEntry) -> bool {
     match (a, b) {
         (
@@ -286,6 +366,7 @@ pub struct ConversationThreadMeta {
     pub task_status_last_analyzed_message_seq: u64,
     pub turn_status: crate::TurnStatus,
     pub last_turn_result: Option<crate::TurnResult>,
+    pub forked_from: Option<crate::ThreadForkOrigin>,
 }
 
 #[derive(Clone, Debug)]
@@ -313,6 +394,7 @@ pub struct WorkspaceConversation {
     pub next_queued_prompt_id: u64,
     pub pending_prompts: VecDeque<QueuedPrompt>,
     pub queue_paused: bool,
+    pub forked_from: Option<crate::ThreadForkOrigin>,
 }
 
 impl WorkspaceConversation {
@@ -382,6 +464,27 @@ impl WorkspaceConversation {
         self.push_entry(entry);
     }
 
+    /// Distinct tool calls made since the latest user message, i.e. in the current turn.
+    pub(crate) fn current_turn_tool_calls(&self) -> u64 {
+        let mut ids = HashSet::new();
+        for entry in self.entries.iter().rev() {
+            match entry {
+                ConversationEntry::UserEvent {
+                    event: UserEvent::Message { .. },
+                    ..
+                } => break,
+                ConversationEntry::AgentEvent {
+                    event: AgentEvent::Item { item },
+                    ..
+                } if is_tool_call_item(item) => {
+                    ids.insert(codex_item_id(item));
+                }
+                _ => {}
+            }
+        }
+        ids.len() as u64
+    }
+
     fn should_skip_codex_item(&self, item: &CodexThreadItem) -> bool {
         let incoming_id = codex_item_id(item);
         for entry in self.entries.iter().rev() {
@@ -624,6 +727,7 @@ mod tests {
                 event: UserEvent::Message {
                     text: format!("user-{idx}"),
                     attachments: Vec::new(),
+                    author: None,
                 },
             });
         }
@@ -706,6 +810,7 @@ mod tests {
             event: UserEvent::Message {
                 text: "hello".to_owned(),
                 attachments: Vec::new(),
+                author: None,
             },
         });
         let user_created_at = match conversation.entries.last().expect("user entry") {
@@ -0,0 +1,171 @@
+use super::{
+    OperationStatus, PersistedWorkspaceThreadRunConfigOverride, Project, ProjectId, Workspace,
+    WorkspaceId, WorkspaceStatus,
+};
+use crate::time::{system_time_from_unix_seconds, unix_seconds_opt};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// How long deleted projects and threads are kept before they are purged for good.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeletionSettings {
+    /// Zero disables soft-delete: deletions are permanent and cannot be undone.
+    pub retention_days: u32,
+}
+
+impl DeletionSettings {
+    pub const DEFAULT_RETENTION_DAYS: u32 = 7;
+    pub const MAX_RETENTION_DAYS: u32 = 365;
+
+    pub fn is_valid(&self) -> bool {
+        self.retention_days <= Self::MAX_RETENTION_DAYS
+    }
+
+    pub fn soft_delete_enabled(&self) -> bool {
+        self.retention_days > 0
+    }
+
+    pub fn retention_ms(&self) -> u64 {
+        u64::from(self.retention_days) * 24 * 60 * 60 * 1000
+    }
+
+    /// Deletions made at or before this instant are past the retention window.
+    pub fn purge_cutoff_unix_ms(&self, now_unix_ms: u64) -> u64 {
+        now_unix_ms.saturating_sub(self.retention_ms())
+    }
+}
+
+impl Default for DeletionSettings {
+    fn default() -> Self {
+        Self {
+            retention_days: Self::DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+/// A workspace of a soft-deleted project, kept so the project can be restored as it was.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeletedWorkspace {
+    pub id: u64,
+    pub workspace_name: String,
+    pub branch_name: String,
+    pub worktree_path: PathBuf,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub last_activity_at_unix_seconds: Option<u64>,
+    #[serde(default)]
+    pub archived_at_unix_seconds: Option<u64>,
+    /// Keeps thread ids monotonic after a restore, since soft-deleted threads still hold theirs.
+    #[serde(default)]
+    pub next_thread_id: u64,
+    /// Per-task runner and model overrides, keyed by thread id.
+    #[serde(default)]
+    pub thread_run_config_overrides: BTreeMap<u64, PersistedWorkspaceThreadRunConfigOverride>,
+}
+
+/// A project removed from the sidebar that can still be restored until the retention
+/// window runs out.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeletedProject {
+    pub id: u64,
+    pub name: String,
+    pub path: PathBuf,
+    pub slug: String,
+    pub is_git: bool,
+    #[serde(default)]
+    pub expanded: bool,
+    #[serde(default)]
+    pub workspaces: Vec<DeletedWorkspace>,
+    pub deleted_at_unix_ms: u64,
+}
+
+impl DeletedProject {
+    pub fn from_project(project: &Project, deleted_at_unix_ms: u64) -> Self {
+        Self {
+            id: project.id.0,
+            name: project.name.clone(),
+            path: project.path.clone(),
+            slug: project.slug.clone(),
+            is_git: project.is_git,
+            expanded: project.expanded,
+            workspaces: project
+                .workspaces
+                .iter()
+                .map(|w| DeletedWorkspace {
+                    id: w.id.0,
+                    workspace_name: w.workspace_name.clone(),
+                    branch_name: w.branch_name.clone(),
+                    worktree_path: w.worktree_path.clone(),
+                    archived: w.status == WorkspaceStatus::Archived,
+                    last_activity_at_unix_seconds: unix_seconds_opt(w.last_activity_at),
+                    archived_at_unix_seconds: unix_seconds_opt(w.archived_at),
+                    next_thread_id: 1,
+                    thread_run_config_overrides: BTreeMap::new(),
+                })
+                .collect(),
+            deleted_at_unix_ms,
+        }
+    }
+
+    pub fn project_id(&self) -> ProjectId {
+        ProjectId(self.id)
+    }
+
+    pub fn to_project(&self) -> Project {
+        Project {
+            id: ProjectId(self.id),
+            name: self.name.clone(),
+            path: self.path.clone(),
+            slug: self.slug.clone(),
+            is_git: self.is_git,
+            expanded: self.expanded,
+            create_workspace_status: OperationStatus::Idle,
+            workspaces: self
+                .workspaces
+                .iter()
+                .map(|w| Workspace {
+                    id: WorkspaceId(w.id),
+                    workspace_name: w.workspace_name.clone(),
+                    branch_name: w.branch_name.clone(),
+                    worktree_path: w.worktree_path.clone(),
+                    status: if w.archived {
+                        WorkspaceStatus::Archived
+                    } else {
+                        WorkspaceStatus::Active
+                    },
+                    last_activity_at: w
+                        .last_activity_at_unix_seconds
+                        .map(system_time_from_unix_seconds),
+                    archived_at: w
+                        .archived_at_unix_seconds
+                        .map(system_time_from_unix_seconds),
+                    archive_status: OperationStatus::Idle,
+                    branch_rename_status: OperationStatus::Idle,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retention_cutoff_saturates_and_zero_disables_soft_delete() {
+        let settings = DeletionSettings { retention_days: 1 };
+        assert!(settings.soft_delete_enabled());
+        assert_eq!(settings.purge_cutoff_unix_ms(86_400_000 + 5), 5);
+        assert_eq!(settings.purge_cutoff_unix_ms(10), 0);
+
+        let disabled = DeletionSettings { retention_days: 0 };
+        assert!(!disabled.soft_delete_enabled());
+        assert!(
+            !DeletionSettings {
+                retention_days: DeletionSettings::MAX_RETENTION_DAYS + 1
+            }
+            .is_valid()
+        );
+    }
+}
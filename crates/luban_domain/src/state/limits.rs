@@ -0,0 +1,131 @@
+/// Caps on how long and how much a single agent turn, or a whole task, may run.
+///
+/// Every field is optional; a task's own limits override its project's field by field.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TurnLimits {
+    /// Wall-clock seconds a turn may run before it is canceled.
+    #[serde(default)]
+    pub turn_timeout_secs: Option<u64>,
+    /// Tool calls (commands, file changes, MCP calls, web searches) allowed in one turn.
+    #[serde(default)]
+    pub max_tool_calls_per_turn: Option<u64>,
+    /// Input plus output tokens one turn may use. Usage is only reported once a turn
+    /// completes, so the turn itself always finishes; going over pauses the queue so the next
+    /// queued prompt does not start.
+    #[serde(default, alias = "max_tokens_per_turn")]
+    pub turn_token_budget: Option<u64>,
+    /// Input plus output tokens a task may use across its turns. Checked after each turn like
+    /// the turn budget; once spent, new turns are refused until it is raised.
+    #[serde(default, alias = "max_tokens_per_task")]
+    pub task_token_budget: Option<u64>,
+}
+
+impl TurnLimits {
+    pub const MAX_TURN_TIMEOUT_SECS: u64 = 7 * 24 * 60 * 60;
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Zero is never a meaningful cap; clearing a limit is expressed with `None`.
+    pub fn is_valid(&self) -> bool {
+        self.turn_timeout_secs
+            .is_none_or(|secs| secs > 0 && secs <= Self::MAX_TURN_TIMEOUT_SECS)
+            && self.max_tool_calls_per_turn.is_none_or(|n| n > 0)
+            && self.turn_token_budget.is_none_or(|n| n > 0)
+            && self.task_token_budget.is_none_or(|n| n > 0)
+    }
+
+    /// Fills every limit left unset here from `fallback`.
+    pub fn or(self, fallback: TurnLimits) -> TurnLimits {
+        TurnLimits {
+            turn_timeout_secs: self.turn_timeout_secs.or(fallback.turn_timeout_secs),
+            max_tool_calls_per_turn: self
+                .max_tool_calls_per_turn
+                .or(fallback.max_tool_calls_per_turn),
+            turn_token_budget: self.turn_token_budget.or(fallback.turn_token_budget),
+            task_token_budget: self.task_token_budget.or(fallback.task_token_budget),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnLimitKind {
+    Timeout,
+    ToolCalls,
+    TurnTokens,
+    TaskTokens,
+}
+
+/// Stored form of every project and task limit along with the per-task token tally.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub(crate) struct TurnLimitsStore {
+    #[serde(default)]
+    pub projects: Vec<ProjectTurnLimitsRecord>,
+    #[serde(default)]
+    pub tasks: Vec<TaskTurnLimitsRecord>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ProjectTurnLimitsRecord {
+    pub project_id: u64,
+    #[serde(flatten)]
+    pub limits: TurnLimits,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct TaskTurnLimitsRecord {
+    pub workspace_id: u64,
+    pub thread_id: u64,
+    #[serde(flatten)]
+    pub limits: TurnLimits,
+    #[serde(default)]
+    pub tokens_used: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TurnLimits;
+
+    #[test]
+    fn task_limits_override_project_limits_field_by_field() {
+        let project = TurnLimits {
+            turn_timeout_secs: Some(600),
+            max_tool_calls_per_turn: Some(50),
+            turn_token_budget: None,
+            task_token_budget: Some(1_000_000),
+        };
+        let task = TurnLimits {
+            max_tool_calls_per_turn: Some(10),
+            turn_token_budget: Some(20_000),
+            ..TurnLimits::default()
+        };
+
+        let effective = task.or(project);
+        assert_eq!(effective.turn_timeout_secs, Some(600));
+        assert_eq!(effective.max_tool_calls_per_turn, Some(10));
+        assert_eq!(effective.turn_token_budget, Some(20_000));
+        assert_eq!(effective.task_token_budget, Some(1_000_000));
+        assert!(TurnLimits::default().or(TurnLimits::default()).is_empty());
+    }
+
+    #[test]
+    fn zero_and_oversized_limits_are_invalid() {
+        assert!(TurnLimits::default().is_valid());
+        assert!(
+            !TurnLimits {
+                max_tool_calls_per_turn: Some(0),
+                ..TurnLimits::default()
+            }
+            .is_valid()
+        );
+        assert!(
+            !TurnLimits {
+                turn_timeout_secs: Some(TurnLimits::MAX_TURN_TIMEOUT_SECS + 1),
+                ..TurnLimits::default()
+            }
+            .is_valid()
+        );
+    }
+}
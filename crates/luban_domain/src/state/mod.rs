@@ -1,31 +1,53 @@
 mod agent;
 mod appearance;
 mod attachments;
+mod ci_fix;
 mod conversation;
+mod deletion;
 mod ids;
 mod layout;
+mod limits;
 mod persisted;
+mod review;
 mod tabs;
 mod task;
 mod workspace;
 
 pub use agent::{AgentRunConfig, QueuedPrompt};
 pub use appearance::{AppearanceFonts, AppearanceTheme};
-pub use attachments::{AttachmentKind, AttachmentRef, ContextItem};
+pub use attachments::{
+    AttachmentKind, AttachmentRef, AttachmentTextLimits, ContextItem, UrlAttachmentSettings,
+};
+pub(crate) use ci_fix::TaskCiAutoFixRecord;
+pub use ci_fix::{CiAutoFix, CiAutoFixStopReason, CiFailureLog, CiFixAttempt};
 pub use conversation::{
     AgentEvent, ChatScrollAnchor, ConversationEntry, ConversationSnapshot, ConversationSystemEvent,
     ConversationThreadMeta, DraftAttachment, UserEvent, WorkspaceConversation,
 };
+pub use deletion::{DeletedProject, DeletedWorkspace, DeletionSettings};
 pub use ids::{ProjectId, WorkspaceId, WorkspaceThreadId};
 pub use layout::{MainPane, OperationStatus, RightPane, WorkspaceStatus};
+pub(crate) use limits::{ProjectTurnLimitsRecord, TaskTurnLimitsRecord, TurnLimitsStore};
+pub use limits::{TurnLimitKind, TurnLimits};
 pub use persisted::{
     PersistedAppState, PersistedProject, PersistedWorkspace,
     PersistedWorkspaceThreadRunConfigOverride,
 };
+pub(crate) use review::TaskReviewCommentsRecord;
+pub use review::{
+    PullRequestReviewComment, PullRequestReviewSync, ReviewComment, ReviewCommentOrigin,
+};
 pub use tabs::WorkspaceTabs;
-pub use task::{TaskStatus, TurnResult, TurnStatus, parse_task_status};
-pub use workspace::{AppState, Project, TelegramTopicBinding, Workspace};
+pub use task::{
+    IssueMergeAction, TaskIssueLink, TaskStatus, TurnResult, TurnStatus, parse_task_status,
+};
+pub use workspace::{
+    AppState, NotificationSettings, Project, QuietHours, TelegramGroupChat, TelegramGroupMember,
+    TelegramTopicBinding, Workspace,
+};
 
 pub(crate) const MAX_CONVERSATION_ENTRIES_IN_MEMORY: usize = 5000;
 
-pub(crate) use conversation::{apply_draft_text_diff, entries_is_prefix, entries_is_suffix};
+pub(crate) use conversation::{
+    apply_draft_text_diff, entries_is_prefix, entries_is_suffix, is_tool_call_item,
+};
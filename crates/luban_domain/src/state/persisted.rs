@@ -51,6 +51,16 @@ pub struct PersistedAppState {
     pub telegram_bot_username: Option<String>,
     pub telegram_paired_chat_id: Option<i64>,
     pub telegram_topic_bindings: Option<String>,
+    pub telegram_group_chats: Option<String>,
+    pub notification_settings: Option<String>,
+    pub attachment_text_limits: Option<String>,
+    pub url_attachment_settings: Option<String>,
+    pub task_issue_links: Option<String>,
+    pub deletion_settings: Option<String>,
+    pub deleted_projects: Option<String>,
+    pub turn_limits: Option<String>,
+    pub task_review_comments: Option<String>,
+    pub task_ci_auto_fix: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -72,4 +82,5 @@ pub struct PersistedWorkspace {
     pub worktree_path: PathBuf,
     pub status: WorkspaceStatus,
     pub last_activity_at_unix_seconds: Option<u64>,
+    pub archived_at_unix_seconds: Option<u64>,
 }
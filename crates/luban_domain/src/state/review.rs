@@ -0,0 +1,123 @@
+/// An inline comment left on a line range of the agent's changes in the diff view.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReviewComment {
+    pub id: u64,
+    /// Path relative to the worktree root.
+    pub path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// The commented lines as they read when the comment was left.
+    #[serde(default)]
+    pub quote: String,
+    pub body: String,
+    #[serde(default)]
+    pub resolved: bool,
+    /// Set once the comment went out to the agent; editing it clears the flag again.
+    #[serde(default)]
+    pub sent: bool,
+    pub created_at_unix_ms: u64,
+    #[serde(default)]
+    pub updated_at_unix_ms: u64,
+    /// Set when the comment was imported from a pull request review rather than left in Luban.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<ReviewCommentOrigin>,
+}
+
+/// Where an imported review comment lives on the forge.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReviewCommentOrigin {
+    pub pr_number: u64,
+    /// The forge's id for the comment, used to dedupe imports and to reply.
+    pub comment_id: u64,
+    pub author: String,
+    #[serde(default)]
+    pub url: String,
+    /// Set once Luban posted a reply marking the comment as addressed.
+    #[serde(default)]
+    pub replied: bool,
+}
+
+/// An unresolved comment fetched from a pull request review thread.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PullRequestReviewComment {
+    pub comment_id: u64,
+    pub author: String,
+    pub path: String,
+    /// 0 for comments on the whole file.
+    pub start_line: u32,
+    pub end_line: u32,
+    pub body: String,
+    pub url: String,
+    /// The diff hunk the comment was left on, ending at the commented line.
+    pub diff_hunk: String,
+}
+
+/// How a task follows review feedback on its workdir's pull request.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PullRequestReviewSync {
+    /// Import new review comments whenever the pull request refreshes.
+    #[serde(default)]
+    pub auto_pull: bool,
+    /// Reply on the pull request when an imported comment gets resolved.
+    #[serde(default)]
+    pub reply_when_addressed: bool,
+}
+
+impl ReviewComment {
+    pub const MAX_BODY_CHARS: usize = 8_000;
+    pub const MAX_QUOTE_CHARS: usize = 4_000;
+
+    /// Whether the next review turn should carry this comment.
+    pub fn is_pending(&self) -> bool {
+        !self.resolved && !self.sent
+    }
+
+    /// Builds the task-local copy of a comment imported from a pull request review.
+    pub fn from_pull_request(
+        id: u64,
+        pr_number: u64,
+        comment: PullRequestReviewComment,
+        now_unix_ms: u64,
+    ) -> Self {
+        // The hunk ends at the commented line, so its tail holds the commented range.
+        let range = usize::try_from(comment.end_line.saturating_sub(comment.start_line))
+            .unwrap_or(0)
+            .saturating_add(1);
+        let hunk_lines = comment.diff_hunk.lines().collect::<Vec<_>>();
+        let quote = if comment.start_line == 0 {
+            String::new()
+        } else {
+            hunk_lines[hunk_lines.len().saturating_sub(range)..].join("\n")
+        };
+        Self {
+            id,
+            path: comment.path,
+            start_line: comment.start_line,
+            end_line: comment.end_line,
+            quote: quote.chars().take(Self::MAX_QUOTE_CHARS).collect(),
+            body: comment.body.chars().take(Self::MAX_BODY_CHARS).collect(),
+            resolved: false,
+            sent: false,
+            created_at_unix_ms: now_unix_ms,
+            updated_at_unix_ms: now_unix_ms,
+            pull_request: Some(ReviewCommentOrigin {
+                pr_number,
+                comment_id: comment.comment_id,
+                author: comment.author,
+                url: comment.url,
+                replied: false,
+            }),
+        }
+    }
+}
+
+/// Stored form of one task's review comments.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct TaskReviewCommentsRecord {
+    pub workspace_id: u64,
+    pub thread_id: u64,
+    #[serde(default)]
+    pub comments: Vec<ReviewComment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request_sync: Option<PullRequestReviewSync>,
+}
@@ -94,3 +94,39 @@ impl TurnResult {
         }
     }
 }
+
+/// What happens to a task's linked issue once the task's pull request merges.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueMergeAction {
+    #[default]
+    Close,
+    Comment,
+    Leave,
+}
+
+impl IssueMergeAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IssueMergeAction::Close => "close",
+            IssueMergeAction::Comment => "comment",
+            IssueMergeAction::Leave => "leave",
+        }
+    }
+}
+
+/// A task created from a forge issue.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TaskIssueLink {
+    pub workspace_id: u64,
+    pub thread_id: u64,
+    pub number: u64,
+    #[serde(default)]
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub on_merge: IssueMergeAction,
+    /// Pull request whose merge already closed or commented on the issue.
+    #[serde(default)]
+    pub resolved_by_pr: Option<u64>,
+}
@@ -1,12 +1,15 @@
 use super::{
-    AppearanceFonts, AppearanceTheme, ChatScrollAnchor, MainPane, OperationStatus,
-    PersistedWorkspaceThreadRunConfigOverride, ProjectId, RightPane, WorkspaceConversation,
+    AppearanceFonts, AppearanceTheme, AttachmentTextLimits, ChatScrollAnchor, CiAutoFix,
+    DeletedProject, DeletionSettings, MainPane, OperationStatus,
+    PersistedWorkspaceThreadRunConfigOverride, ProjectId, PullRequestReviewSync, ReviewComment,
+    RightPane, TaskIssueLink, TurnLimits, UrlAttachmentSettings, WorkspaceConversation,
     WorkspaceId, WorkspaceStatus, WorkspaceTabs, WorkspaceThreadId,
 };
-use crate::{SystemTaskKind, TaskIntentKind};
+use crate::{ModelCatalog, ProjectPromptSettings, SystemTaskKind, TaskIntentKind};
 use std::{
     collections::{HashMap, HashSet},
     path::PathBuf,
+    sync::Arc,
 };
 
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -18,6 +21,82 @@ pub struct TelegramTopicBinding {
     pub replayed_up_to: Option<u64>,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TelegramGroupMember {
+    pub user_id: i64,
+    #[serde(default)]
+    pub display_name: String,
+}
+
+/// A Telegram group paired with a single project. Only listed members may
+/// talk to the agent, and each task gets its own forum topic in the group.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TelegramGroupChat {
+    pub chat_id: i64,
+    pub project_slug: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub members: Vec<TelegramGroupMember>,
+    #[serde(default)]
+    pub topic_bindings: Vec<TelegramTopicBinding>,
+}
+
+impl TelegramGroupChat {
+    pub fn is_member(&self, user_id: i64) -> bool {
+        self.members.iter().any(|m| m.user_id == user_id)
+    }
+}
+
+/// Local-time window during which desktop notifications are held back.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct QuietHours {
+    /// Minutes after local midnight; the window wraps past midnight when `end < start`.
+    pub start_minute: u16,
+    pub end_minute: u16,
+    /// Offset of the user's local time from UTC when the window was chosen.
+    #[serde(default)]
+    pub utc_offset_minutes: i16,
+}
+
+impl QuietHours {
+    pub const MINUTES_PER_DAY: u16 = 24 * 60;
+
+    pub fn is_valid(&self) -> bool {
+        self.start_minute < Self::MINUTES_PER_DAY
+            && self.end_minute < Self::MINUTES_PER_DAY
+            && self.start_minute != self.end_minute
+            && self.utc_offset_minutes.unsigned_abs() <= 14 * 60
+    }
+
+    pub fn contains(&self, unix_seconds: u64) -> bool {
+        let minutes_per_day = i64::from(Self::MINUTES_PER_DAY);
+        let local = (unix_seconds / 60) as i64 + i64::from(self.utc_offset_minutes);
+        let minute = local.rem_euclid(minutes_per_day) as u16;
+        if self.start_minute < self.end_minute {
+            (self.start_minute..self.end_minute).contains(&minute)
+        } else {
+            minute >= self.start_minute || minute < self.end_minute
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NotificationSettings {
+    #[serde(default)]
+    pub desktop_enabled: bool,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    #[serde(default)]
+    pub muted_project_slugs: Vec<String>,
+}
+
+impl NotificationSettings {
+    pub fn is_project_muted(&self, project_slug: &str) -> bool {
+        self.muted_project_slugs.iter().any(|s| s == project_slug)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Workspace {
     pub id: WorkspaceId,
@@ -26,6 +105,9 @@ pub struct Workspace {
     pub worktree_path: PathBuf,
     pub status: WorkspaceStatus,
     pub last_activity_at: Option<std::time::SystemTime>,
+    /// When the workdir was archived; `None` for active workdirs and ones archived before this
+    /// was recorded.
+    pub archived_at: Option<std::time::SystemTime>,
     pub archive_status: OperationStatus,
     pub branch_rename_status: OperationStatus,
 }
@@ -81,6 +163,8 @@ pub struct AppState {
         HashMap<(WorkspaceId, WorkspaceThreadId), PersistedWorkspaceThreadRunConfigOverride>,
     pub task_prompt_templates: HashMap<TaskIntentKind, String>,
     pub system_prompt_templates: HashMap<SystemTaskKind, String>,
+    pub project_prompt_settings: HashMap<ProjectId, ProjectPromptSettings>,
+    pub model_catalog: Arc<ModelCatalog>,
     pub(crate) telegram_enabled: bool,
     pub(crate) telegram_bot_token: Option<String>,
     pub(crate) telegram_bot_username: Option<String>,
@@ -88,6 +172,21 @@ pub struct AppState {
     pub(crate) telegram_config_rev: u64,
     pub(crate) telegram_last_error: Option<String>,
     pub(crate) telegram_topic_bindings: HashMap<i64, TelegramTopicBinding>,
+    pub(crate) telegram_group_chats: HashMap<i64, TelegramGroupChat>,
+    pub(crate) notification_settings: NotificationSettings,
+    pub(crate) attachment_text_limits: AttachmentTextLimits,
+    pub(crate) url_attachment_settings: UrlAttachmentSettings,
+    pub(crate) task_issue_links: HashMap<(WorkspaceId, WorkspaceThreadId), TaskIssueLink>,
+    pub(crate) deletion_settings: DeletionSettings,
+    pub(crate) deleted_projects: Vec<DeletedProject>,
+    pub(crate) project_turn_limits: HashMap<ProjectId, TurnLimits>,
+    pub(crate) task_turn_limits: HashMap<(WorkspaceId, WorkspaceThreadId), TurnLimits>,
+    /// Input plus output tokens spent by each task across all of its turns.
+    pub(crate) task_tokens_used: HashMap<(WorkspaceId, WorkspaceThreadId), u64>,
+    pub(crate) task_review_comments: HashMap<(WorkspaceId, WorkspaceThreadId), Vec<ReviewComment>>,
+    pub(crate) task_pull_request_review_sync:
+        HashMap<(WorkspaceId, WorkspaceThreadId), PullRequestReviewSync>,
+    pub(crate) task_ci_auto_fix: HashMap<(WorkspaceId, WorkspaceThreadId), CiAutoFix>,
 }
 
 impl AppState {
@@ -154,4 +253,171 @@ impl AppState {
     pub fn telegram_topic_bindings(&self) -> &HashMap<i64, TelegramTopicBinding> {
         &self.telegram_topic_bindings
     }
+
+    pub fn telegram_group_chats(&self) -> &HashMap<i64, TelegramGroupChat> {
+        &self.telegram_group_chats
+    }
+
+    pub fn notification_settings(&self) -> &NotificationSettings {
+        &self.notification_settings
+    }
+
+    pub fn attachment_text_limits(&self) -> AttachmentTextLimits {
+        self.attachment_text_limits
+    }
+
+    pub fn url_attachment_settings(&self) -> &UrlAttachmentSettings {
+        &self.url_attachment_settings
+    }
+
+    pub fn deletion_settings(&self) -> DeletionSettings {
+        self.deletion_settings
+    }
+
+    pub fn deleted_projects(&self) -> &[DeletedProject] {
+        &self.deleted_projects
+    }
+
+    pub fn task_issue_links(&self) -> &HashMap<(WorkspaceId, WorkspaceThreadId), TaskIssueLink> {
+        &self.task_issue_links
+    }
+
+    pub fn project_turn_limits(&self, project_id: ProjectId) -> TurnLimits {
+        self.project_turn_limits
+            .get(&project_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn task_turn_limits(
+        &self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> TurnLimits {
+        self.task_turn_limits
+            .get(&(workspace_id, thread_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn task_tokens_used(&self, workspace_id: WorkspaceId, thread_id: WorkspaceThreadId) -> u64 {
+        self.task_tokens_used
+            .get(&(workspace_id, thread_id))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn task_review_comments(
+        &self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> &[ReviewComment] {
+        self.task_review_comments
+            .get(&(workspace_id, thread_id))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn task_pull_request_review_sync(
+        &self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> PullRequestReviewSync {
+        self.task_pull_request_review_sync
+            .get(&(workspace_id, thread_id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn task_ci_auto_fix(
+        &self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> Option<&CiAutoFix> {
+        self.task_ci_auto_fix.get(&(workspace_id, thread_id))
+    }
+
+    /// Tasks in a workdir whose CI auto-fix loop is on.
+    pub fn ci_auto_fix_tasks(&self, workspace_id: WorkspaceId) -> Vec<WorkspaceThreadId> {
+        let mut tasks = self
+            .task_ci_auto_fix
+            .iter()
+            .filter(|((wid, _), fix)| *wid == workspace_id && fix.enabled)
+            .map(|((_, tid), _)| *tid)
+            .collect::<Vec<_>>();
+        tasks.sort_by_key(|tid| tid.as_u64());
+        tasks
+    }
+
+    /// Tasks in a workdir that import new pull request review comments on each refresh.
+    pub fn pull_request_review_auto_pull_tasks(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Vec<WorkspaceThreadId> {
+        let mut tasks = self
+            .task_pull_request_review_sync
+            .iter()
+            .filter(|((wid, _), sync)| *wid == workspace_id && sync.auto_pull)
+            .map(|((_, tid), _)| *tid)
+            .collect::<Vec<_>>();
+        tasks.sort_by_key(|tid| tid.as_u64());
+        tasks
+    }
+
+    /// The limits a task's turns run under: its own, falling back to its project's.
+    pub fn effective_turn_limits(
+        &self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> TurnLimits {
+        let project_limits = self
+            .projects
+            .iter()
+            .find(|p| p.workspaces.iter().any(|w| w.id == workspace_id))
+            .map(|p| self.project_turn_limits(p.id))
+            .unwrap_or_default();
+        self.task_turn_limits(workspace_id, thread_id)
+            .or(project_limits)
+    }
+
+    pub fn task_token_budget_exhausted(
+        &self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> bool {
+        self.effective_turn_limits(workspace_id, thread_id)
+            .task_token_budget
+            .is_some_and(|max| self.task_tokens_used(workspace_id, thread_id) >= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuietHours;
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight_in_local_time() {
+        let quiet = QuietHours {
+            start_minute: 22 * 60,
+            end_minute: 7 * 60,
+            utc_offset_minutes: 120,
+        };
+        let utc = |hour: u64, minute: u64| 86_400 * 100 + hour * 3600 + minute * 60;
+
+        // 20:30 UTC is 22:30 local.
+        assert!(quiet.contains(utc(20, 30)));
+        // 04:59 UTC is 06:59 local.
+        assert!(quiet.contains(utc(4, 59)));
+        // 05:00 UTC is 07:00 local.
+        assert!(!quiet.contains(utc(5, 0)));
+        assert!(!quiet.contains(utc(12, 0)));
+
+        let daytime = QuietHours {
+            start_minute: 9 * 60,
+            end_minute: 17 * 60,
+            utc_offset_minutes: -300,
+        };
+        assert!(daytime.contains(utc(14, 0)));
+        assert!(!daytime.contains(utc(22, 0)));
+    }
 }
@@ -0,0 +1,540 @@
+//! Native desktop notifications for turn outcomes, drained prompt queues and PR CI failures.
+//!
+//! The desktop app shows them through its own [`NotificationPresenter`] (the Tauri notification
+//! plugin) and reports clicks through [`TaskFocus`]. A headless server falls back to
+//! `notify-send` on unix hosts. Either way a click broadcasts `TaskFocusRequested` so connected
+//! clients bring the task into view.
+
+use crate::engine::EngineHandle;
+use crate::relay::{ci_turned_failed, truncate_label, turn_ended};
+use luban_api::{
+    AppSnapshot, NotificationsSnapshot, PullRequestCiState, ServerEvent, TaskSummarySnapshot,
+    TurnResult, WsServerMessage,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::process::Command;
+use tokio::sync::broadcast;
+
+const DESKTOP_NOTIFY_COMMAND_ENV: &str = "LUBAN_DESKTOP_NOTIFY_COMMAND";
+
+// `notify-send --wait` blocks until the notification is closed; give up on the click after this.
+const NOTIFICATION_CLICK_WAIT_SECS: u64 = 10 * 60;
+const NOTIFICATION_BODY_MAX_CHARS: usize = 200;
+const CLICK_ACTION: &str = "default";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DesktopEvent {
+    TurnFinished,
+    TurnFailed,
+    QueueDrained,
+    PrCiFailed,
+}
+
+impl DesktopEvent {
+    fn title(self) -> &'static str {
+        match self {
+            Self::TurnFinished => "Turn finished",
+            Self::TurnFailed => "Turn failed",
+            Self::QueueDrained => "Queued prompts finished",
+            Self::PrCiFailed => "CI failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DesktopNotification {
+    pub title: String,
+    pub body: String,
+    pub workspace_id: u64,
+    pub thread_id: u64,
+}
+
+/// Opens a notification's task in connected clients; handed to the presenter with each
+/// notification so it can report a click.
+#[derive(Clone, Debug)]
+pub struct TaskFocus {
+    events: broadcast::Sender<WsServerMessage>,
+    rev: u64,
+    workspace_id: u64,
+    thread_id: u64,
+}
+
+impl TaskFocus {
+    pub fn request(&self) {
+        let _ = self.events.send(WsServerMessage::Event {
+            rev: self.rev,
+            event: Box::new(ServerEvent::TaskFocusRequested {
+                workspace_id: luban_api::WorkspaceId(self.workspace_id),
+                thread_id: luban_api::WorkspaceThreadId(self.thread_id),
+            }),
+        });
+    }
+}
+
+/// Shows notifications natively on behalf of an embedding shell such as the desktop app.
+pub trait NotificationPresenter: Send + Sync {
+    /// Shows `notification`; call `focus.request()` when the user clicks it.
+    fn show(&self, notification: &DesktopNotification, focus: TaskFocus);
+}
+
+/// How notifications reach the desktop.
+#[derive(Clone)]
+enum NotifyBackend {
+    /// The embedding shell's native notifications.
+    Presenter(Arc<dyn NotificationPresenter>),
+    /// `notify-send` with a default action; prints the action name when clicked.
+    NotifySend,
+    /// A user-provided program called as `<program> <title> <body>`, following the `notify-send`
+    /// protocol of printing `default` on stdout when clicked.
+    Custom(String),
+}
+
+impl NotifyBackend {
+    fn detect(presenter: Option<Arc<dyn NotificationPresenter>>) -> Option<Self> {
+        if let Some(presenter) = presenter {
+            return Some(Self::Presenter(presenter));
+        }
+        if let Some(program) = std::env::var(DESKTOP_NOTIFY_COMMAND_ENV)
+            .ok()
+            .map(|v| v.trim().to_owned())
+            .filter(|v| !v.is_empty())
+        {
+            return Some(Self::Custom(program));
+        }
+        cfg!(unix).then_some(Self::NotifySend)
+    }
+
+    /// Shows the notification through a helper program and reports whether the user clicked it.
+    async fn run_helper(&self, notification: &DesktopNotification) -> anyhow::Result<bool> {
+        let mut command = match self {
+            Self::Presenter(_) => anyhow::bail!("native notifications are not a helper program"),
+            Self::NotifySend => {
+                let mut command = Command::new("notify-send");
+                command
+                    .arg("--app-name=Luban")
+                    .arg(format!("--action={CLICK_ACTION}=Open"))
+                    .arg("--wait")
+                    .arg(&notification.title)
+                    .arg(&notification.body);
+                command
+            }
+            Self::Custom(program) => {
+                let mut command = Command::new(program);
+                command.arg(&notification.title).arg(&notification.body);
+                command
+            }
+        };
+        command
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true);
+
+        let output = match tokio::time::timeout(
+            Duration::from_secs(NOTIFICATION_CLICK_WAIT_SECS),
+            command.output(),
+        )
+        .await
+        {
+            Ok(output) => output?,
+            Err(_) => return Ok(false),
+        };
+        if !output.status.success() {
+            anyhow::bail!("notification helper exited with {}", output.status);
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.lines().any(|line| line.trim() == CLICK_ACTION))
+    }
+}
+
+/// Starts the desktop notifier when the shell presents notifications or the host has a supported
+/// notification helper.
+pub(crate) fn start_notifier(
+    engine: EngineHandle,
+    events: broadcast::Sender<WsServerMessage>,
+    presenter: Option<Arc<dyn NotificationPresenter>>,
+) {
+    let Some(backend) = NotifyBackend::detect(presenter) else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut notifier = DesktopNotifier::new(engine, events, backend);
+        notifier.run().await;
+    });
+}
+
+struct DesktopNotifier {
+    engine: EngineHandle,
+    events_tx: broadcast::Sender<WsServerMessage>,
+    events: broadcast::Receiver<WsServerMessage>,
+    backend: NotifyBackend,
+    rev: u64,
+    settings: NotificationsSnapshot,
+    muted_projects: HashSet<String>,
+    project_names: HashMap<String, String>,
+    tasks: HashMap<(u64, u64), TaskSummarySnapshot>,
+    pull_request_ci: HashMap<u64, Option<PullRequestCiState>>,
+    pending_prompts: HashMap<(u64, u64), usize>,
+    draining_queues: HashSet<(u64, u64)>,
+}
+
+impl DesktopNotifier {
+    fn new(
+        engine: EngineHandle,
+        events_tx: broadcast::Sender<WsServerMessage>,
+        backend: NotifyBackend,
+    ) -> Self {
+        let events = events_tx.subscribe();
+        Self {
+            engine,
+            events_tx,
+            events,
+            backend,
+            rev: 0,
+            settings: NotificationsSnapshot::default(),
+            muted_projects: HashSet::new(),
+            project_names: HashMap::new(),
+            tasks: HashMap::new(),
+            pull_request_ci: HashMap::new(),
+            pending_prompts: HashMap::new(),
+            draining_queues: HashSet::new(),
+        }
+    }
+
+    async fn run(&mut self) {
+        self.seed().await;
+
+        loop {
+            match self.events.recv().await {
+                Ok(msg) => self.handle_server_message(msg),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+
+    /// Records the current state so only transitions after startup produce notifications.
+    async fn seed(&mut self) {
+        if let Ok(app) = self.engine.app_snapshot().await {
+            self.observe_app(&app);
+        }
+        match crate::server::collect_tasks_snapshot(
+            &self.engine,
+            None,
+            crate::server::WorkdirStatusFilter::All,
+            None,
+        )
+        .await
+        {
+            Ok(snapshot) => {
+                for task in snapshot.tasks {
+                    self.tasks
+                        .insert((task.workspace_id.0, task.thread_id.0), task);
+                }
+            }
+            Err(err) => tracing::warn!(error = %err, "desktop notifier failed to load tasks"),
+        }
+    }
+
+    fn handle_server_message(&mut self, msg: WsServerMessage) {
+        let WsServerMessage::Event { rev, event } = msg else {
+            return;
+        };
+        self.rev = self.rev.max(rev);
+
+        match *event {
+            ServerEvent::AppChanged { snapshot, .. } => {
+                for (project_id, notification) in self.observe_app(&snapshot) {
+                    self.notify(project_id, notification);
+                }
+            }
+            ServerEvent::TaskSummariesChanged { tasks, .. } => {
+                for task in tasks {
+                    let key = (task.workspace_id.0, task.thread_id.0);
+                    let Some(prev) = self.tasks.insert(key, task.clone()) else {
+                        continue;
+                    };
+                    let Some(result) = turn_ended(&prev, &task) else {
+                        continue;
+                    };
+                    if let Some(event) = self.turn_event(key, result) {
+                        let notification = self.task_notification(event, &task);
+                        self.notify(task.project_id.0.clone(), notification);
+                    }
+                }
+            }
+            ServerEvent::ConversationChanged { snapshot } => {
+                let key = (snapshot.workspace_id.0, snapshot.thread_id.0);
+                let pending = snapshot.pending_prompts.len();
+                if pending > 0 {
+                    self.draining_queues.insert(key);
+                }
+                self.pending_prompts.insert(key, pending);
+            }
+            _ => {}
+        }
+    }
+
+    /// Picks the notification for a finished turn, holding back while queued prompts remain.
+    fn turn_event(&mut self, key: (u64, u64), result: TurnResult) -> Option<DesktopEvent> {
+        if self.pending_prompts.get(&key).copied().unwrap_or(0) > 0 {
+            return None;
+        }
+        if self.draining_queues.remove(&key) {
+            return Some(DesktopEvent::QueueDrained);
+        }
+        Some(match result {
+            TurnResult::Completed => DesktopEvent::TurnFinished,
+            TurnResult::Failed => DesktopEvent::TurnFailed,
+        })
+    }
+
+    /// Updates settings and PR CI states, returning notifications for PRs whose CI just failed.
+    fn observe_app(&mut self, app: &AppSnapshot) -> Vec<(String, DesktopNotification)> {
+        let seeded = !self.project_names.is_empty();
+        self.settings = app.notifications.clone();
+        self.muted_projects = app
+            .projects
+            .iter()
+            .filter(|p| p.notifications_muted)
+            .map(|p| p.id.0.clone())
+            .collect();
+
+        let mut out = Vec::new();
+        for project in &app.projects {
+            self.project_names
+                .insert(project.id.0.clone(), project.name.clone());
+            for workspace in &project.workspaces {
+                let ci_state = workspace.pull_request.as_ref().and_then(|pr| pr.ci_state);
+                let prev = self.pull_request_ci.insert(workspace.id.0, ci_state);
+                let Some(pr) = workspace.pull_request.as_ref() else {
+                    continue;
+                };
+                if !seeded || !ci_turned_failed(prev.flatten(), ci_state) {
+                    continue;
+                }
+                let Some(thread_id) = self.latest_task(workspace.id.0) else {
+                    continue;
+                };
+                out.push((
+                    project.id.0.clone(),
+                    DesktopNotification {
+                        title: DesktopEvent::PrCiFailed.title().to_owned(),
+                        body: format!(
+                            "PR #{} · {} · {}",
+                            pr.number, project.name, workspace.workspace_name
+                        ),
+                        workspace_id: workspace.id.0,
+                        thread_id,
+                    },
+                ));
+            }
+        }
+        out
+    }
+
+    fn latest_task(&self, workspace_id: u64) -> Option<u64> {
+        self.tasks
+            .values()
+            .filter(|t| t.workspace_id.0 == workspace_id)
+            .max_by_key(|t| t.updated_at_unix_seconds)
+            .map(|t| t.thread_id.0)
+    }
+
+    fn task_notification(
+        &self,
+        event: DesktopEvent,
+        task: &TaskSummarySnapshot,
+    ) -> DesktopNotification {
+        let project_name = self
+            .project_names
+            .get(&task.project_id.0)
+            .map(String::as_str)
+            .unwrap_or(&task.project_id.0);
+        DesktopNotification {
+            title: event.title().to_owned(),
+            body: truncate_label(
+                &format!("{}\n{} · {}", task.title, project_name, task.workspace_name),
+                NOTIFICATION_BODY_MAX_CHARS,
+            ),
+            workspace_id: task.workspace_id.0,
+            thread_id: task.thread_id.0,
+        }
+    }
+
+    fn notify(&self, project_id: String, notification: DesktopNotification) {
+        if !should_notify(
+            &self.settings,
+            self.muted_projects.contains(&project_id),
+            now_unix_seconds(),
+        ) {
+            return;
+        }
+
+        let focus = TaskFocus {
+            events: self.events_tx.clone(),
+            rev: self.rev,
+            workspace_id: notification.workspace_id,
+            thread_id: notification.thread_id,
+        };
+        if let NotifyBackend::Presenter(presenter) = &self.backend {
+            presenter.show(&notification, focus);
+            return;
+        }
+
+        let backend = self.backend.clone();
+        tokio::spawn(async move {
+            match backend.run_helper(&notification).await {
+                Ok(true) => focus.request(),
+                Ok(false) => {}
+                Err(err) => tracing::debug!(error = %err, "desktop notification failed"),
+            }
+        });
+    }
+}
+
+fn now_unix_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn should_notify(settings: &NotificationsSnapshot, project_muted: bool, now: u64) -> bool {
+    if !settings.desktop_enabled || project_muted {
+        return false;
+    }
+    let Some(quiet) = settings.quiet_hours else {
+        return true;
+    };
+    let quiet = luban_domain::QuietHours {
+        start_minute: quiet.start_minute,
+        end_minute: quiet.end_minute,
+        utc_offset_minutes: quiet.utc_offset_minutes,
+    };
+    !quiet.contains(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luban_api::QuietHoursSnapshot;
+
+    #[test]
+    fn should_notify_respects_toggle_mute_and_quiet_hours() {
+        let mut settings = NotificationsSnapshot {
+            desktop_enabled: false,
+            quiet_hours: None,
+        };
+        let noon = 86_400 * 100 + 12 * 3600;
+        assert!(!should_notify(&settings, false, noon));
+
+        settings.desktop_enabled = true;
+        assert!(should_notify(&settings, false, noon));
+        assert!(!should_notify(&settings, true, noon));
+
+        settings.quiet_hours = Some(QuietHoursSnapshot {
+            start_minute: 11 * 60,
+            end_minute: 13 * 60,
+            utc_offset_minutes: 0,
+        });
+        assert!(!should_notify(&settings, false, noon));
+        assert!(should_notify(&settings, false, noon + 2 * 3600));
+    }
+
+    #[derive(Default)]
+    struct RecordingPresenter {
+        shown: std::sync::Mutex<Vec<(DesktopNotification, TaskFocus)>>,
+    }
+
+    impl NotificationPresenter for RecordingPresenter {
+        fn show(&self, notification: &DesktopNotification, focus: TaskFocus) {
+            self.shown
+                .lock()
+                .expect("mutex poisoned")
+                .push((notification.clone(), focus));
+        }
+    }
+
+    #[tokio::test]
+    async fn presenter_clicks_request_task_focus() {
+        let (tx, _rx) = tokio::sync::mpsc::channel(1);
+        let (events, mut events_rx) = broadcast::channel(4);
+        let presenter = Arc::new(RecordingPresenter::default());
+        let backend = NotifyBackend::detect(Some(presenter.clone())).expect("backend");
+        let mut notifier =
+            DesktopNotifier::new(EngineHandle::from_sender_for_tests(tx), events, backend);
+        notifier.settings.desktop_enabled = true;
+        notifier.rev = 7;
+
+        notifier.notify(
+            "p".to_owned(),
+            DesktopNotification {
+                title: "Turn finished".to_owned(),
+                body: "Fix login".to_owned(),
+                workspace_id: 1,
+                thread_id: 2,
+            },
+        );
+        let (notification, focus) = presenter
+            .shown
+            .lock()
+            .expect("mutex poisoned")
+            .pop()
+            .expect("notification shown");
+        assert_eq!(notification.body, "Fix login");
+
+        focus.request();
+        let WsServerMessage::Event { rev, event } = events_rx.recv().await.expect("focus event")
+        else {
+            panic!("expected an event");
+        };
+        assert_eq!(rev, 7);
+        assert!(matches!(
+            *event,
+            ServerEvent::TaskFocusRequested {
+                workspace_id: luban_api::WorkspaceId(1),
+                thread_id: luban_api::WorkspaceThreadId(2),
+            }
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn custom_backend_reports_clicks() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let script = dir.path().join("notify.sh");
+        let log = dir.path().join("args.txt");
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\nprintf '%s|%s' \"$1\" \"$2\" > '{}'\necho default\n",
+                log.display()
+            ),
+        )
+        .expect("write script");
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+                .expect("chmod");
+        }
+
+        let backend = NotifyBackend::Custom(script.to_string_lossy().to_string());
+        let clicked = backend
+            .run_helper(&DesktopNotification {
+                title: "Turn finished".to_owned(),
+                body: "Fix login".to_owned(),
+                workspace_id: 1,
+                thread_id: 2,
+            })
+            .await
+            .expect("show notification");
+        assert!(clicked);
+        assert_eq!(
+            std::fs::read_to_string(&log).expect("read args"),
+            "Turn finished|Fix login"
+        );
+    }
+}
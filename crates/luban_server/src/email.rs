@@ -0,0 +1,744 @@
+//! Email notification channel: per-event emails and an optional daily digest over SMTP.
+//!
+//! Configured through `LUBAN_SMTP_*` environment variables; see `docs/email.md`.
+
+use crate::engine::EngineHandle;
+use crate::relay::{ci_turned_failed, task_status_label, truncate_label, turn_ended};
+use crate::smtp::{EmailMessage, SmtpConfig, SmtpSecurity, send_email};
+use luban_api::{
+    AppSnapshot, ConversationEntry, PullRequestCiState, PullRequestState, ServerEvent, TaskStatus,
+    TaskSummarySnapshot, TurnResult, WsServerMessage,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+const SMTP_HOST_ENV: &str = "LUBAN_SMTP_HOST";
+const SMTP_PORT_ENV: &str = "LUBAN_SMTP_PORT";
+const SMTP_SECURITY_ENV: &str = "LUBAN_SMTP_SECURITY";
+const SMTP_USERNAME_ENV: &str = "LUBAN_SMTP_USERNAME";
+const SMTP_PASSWORD_ENV: &str = "LUBAN_SMTP_PASSWORD";
+const SMTP_FROM_ENV: &str = "LUBAN_SMTP_FROM";
+const SMTP_TO_ENV: &str = "LUBAN_SMTP_TO";
+const SMTP_EVENTS_ENV: &str = "LUBAN_SMTP_EVENTS";
+const SMTP_DIGEST_HOUR_ENV: &str = "LUBAN_SMTP_DIGEST_HOUR";
+
+const SMTP_PORT_DEFAULT: u16 = 587;
+const SMTP_FROM_DEFAULT: &str = "luban@localhost";
+const DIGEST_CHECK_INTERVAL_SECS: u64 = 60;
+const DIGEST_TASKS_PER_STATUS_LIMIT: usize = 20;
+const SUBJECT_TITLE_MAX_CHARS: usize = 80;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum EmailEvent {
+    TurnFinished,
+    TurnFailed,
+    TaskValidating,
+    PrCiFailed,
+}
+
+impl EmailEvent {
+    const ALL: [Self; 4] = [
+        Self::TurnFinished,
+        Self::TurnFailed,
+        Self::TaskValidating,
+        Self::PrCiFailed,
+    ];
+
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "turn_finished" => Some(Self::TurnFinished),
+            "turn_failed" => Some(Self::TurnFailed),
+            "task_validating" => Some(Self::TaskValidating),
+            "pr_ci_failed" => Some(Self::PrCiFailed),
+            _ => None,
+        }
+    }
+
+    fn headline(self) -> &'static str {
+        match self {
+            Self::TurnFinished => "Turn finished",
+            Self::TurnFailed => "Turn failed",
+            Self::TaskValidating => "Ready for validation",
+            Self::PrCiFailed => "CI failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EmailSettings {
+    smtp: SmtpConfig,
+    events: Vec<EmailEvent>,
+    /// UTC hour at which the daily digest is sent; `None` disables the digest.
+    digest_hour: Option<u32>,
+}
+
+impl EmailSettings {
+    fn from_env() -> Option<Self> {
+        let host = env_value(SMTP_HOST_ENV)?;
+        let to = env_value(SMTP_TO_ENV)
+            .map(|raw| parse_list(&raw))
+            .unwrap_or_default();
+        if to.is_empty() {
+            tracing::warn!("{SMTP_HOST_ENV} is set but {SMTP_TO_ENV} is empty; email disabled");
+            return None;
+        }
+
+        let port = match env_value(SMTP_PORT_ENV) {
+            Some(raw) => match raw.parse::<u16>() {
+                Ok(port) => port,
+                Err(_) => {
+                    tracing::warn!(value = %raw, "invalid {SMTP_PORT_ENV}; email disabled");
+                    return None;
+                }
+            },
+            None => SMTP_PORT_DEFAULT,
+        };
+        let security = match env_value(SMTP_SECURITY_ENV) {
+            Some(raw) => match SmtpSecurity::parse(&raw) {
+                Some(security) => security,
+                None => {
+                    tracing::warn!(value = %raw, "invalid {SMTP_SECURITY_ENV}; email disabled");
+                    return None;
+                }
+            },
+            None => SmtpSecurity::default_for_port(port),
+        };
+        let username = env_value(SMTP_USERNAME_ENV);
+        let from = env_value(SMTP_FROM_ENV)
+            .or_else(|| username.clone().filter(|u| u.contains('@')))
+            .unwrap_or_else(|| SMTP_FROM_DEFAULT.to_owned());
+
+        let events = match env_value(SMTP_EVENTS_ENV) {
+            Some(raw) => parse_events(&raw),
+            None => EmailEvent::ALL.to_vec(),
+        };
+        let digest_hour = env_value(SMTP_DIGEST_HOUR_ENV).and_then(|raw| {
+            let hour = raw.parse::<u32>().ok().filter(|h| *h < 24);
+            if hour.is_none() {
+                tracing::warn!(value = %raw, "invalid {SMTP_DIGEST_HOUR_ENV}; digest disabled");
+            }
+            hour
+        });
+
+        Some(Self {
+            smtp: SmtpConfig {
+                host,
+                port,
+                security,
+                username,
+                password: env_value(SMTP_PASSWORD_ENV),
+                from,
+                to,
+            },
+            events,
+            digest_hour,
+        })
+    }
+}
+
+fn env_value(key: &str) -> Option<String> {
+    std::env::var(key)
+        .ok()
+        .map(|v| v.trim().to_owned())
+        .filter(|v| !v.is_empty())
+}
+
+fn parse_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+fn parse_events(raw: &str) -> Vec<EmailEvent> {
+    let mut events = Vec::new();
+    for item in parse_list(raw) {
+        if item.eq_ignore_ascii_case("none") {
+            continue;
+        }
+        match EmailEvent::parse(&item) {
+            Some(event) if !events.contains(&event) => events.push(event),
+            Some(_) => {}
+            None => tracing::warn!(value = %item, "unknown {SMTP_EVENTS_ENV} entry ignored"),
+        }
+    }
+    events
+}
+
+/// Starts the email notifier when SMTP host and recipients are configured.
+pub(crate) fn start_notifier(engine: EngineHandle, events: broadcast::Sender<WsServerMessage>) {
+    let Some(settings) = EmailSettings::from_env() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut notifier = EmailNotifier::new(engine, events.subscribe(), settings);
+        notifier.run().await;
+    });
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct TokenUsage {
+    input_tokens: u64,
+    cached_input_tokens: u64,
+    output_tokens: u64,
+    turns: u64,
+}
+
+impl TokenUsage {
+    fn add_json(&mut self, usage: &serde_json::Value) {
+        let field = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        self.input_tokens += field("input_tokens");
+        self.cached_input_tokens += field("cached_input_tokens");
+        self.output_tokens += field("output_tokens");
+        self.turns += 1;
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct PullRequestDigestLine {
+    project_name: String,
+    workspace_name: String,
+    branch_name: String,
+    number: u64,
+    ci_state: Option<PullRequestCiState>,
+}
+
+struct EmailNotifier {
+    engine: EngineHandle,
+    events: broadcast::Receiver<WsServerMessage>,
+    settings: EmailSettings,
+    tasks: HashMap<(u64, u64), TaskSummarySnapshot>,
+    pull_request_ci: HashMap<u64, Option<PullRequestCiState>>,
+    project_names: HashMap<String, String>,
+    usage: TokenUsage,
+    usage_since_unix_ms: u64,
+    usage_seen_entry_index: HashMap<(u64, u64), u64>,
+    last_digest_day: Option<u64>,
+}
+
+impl EmailNotifier {
+    fn new(
+        engine: EngineHandle,
+        events: broadcast::Receiver<WsServerMessage>,
+        settings: EmailSettings,
+    ) -> Self {
+        Self {
+            engine,
+            events,
+            settings,
+            tasks: HashMap::new(),
+            pull_request_ci: HashMap::new(),
+            project_names: HashMap::new(),
+            usage: TokenUsage::default(),
+            usage_since_unix_ms: now_unix_ms(),
+            usage_seen_entry_index: HashMap::new(),
+            last_digest_day: None,
+        }
+    }
+
+    async fn run(&mut self) {
+        self.seed().await;
+
+        let mut digest_tick =
+            tokio::time::interval(Duration::from_secs(DIGEST_CHECK_INTERVAL_SECS));
+        digest_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                msg = self.events.recv() => match msg {
+                    Ok(msg) => self.handle_server_message(msg),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                _ = digest_tick.tick(), if self.settings.digest_hour.is_some() => {
+                    self.maybe_send_digest().await;
+                }
+            }
+        }
+    }
+
+    /// Records the current state so only transitions after startup produce emails.
+    async fn seed(&mut self) {
+        if let Ok(app) = self.engine.app_snapshot().await {
+            self.observe_app(&app);
+        }
+        match crate::server::collect_tasks_snapshot(
+            &self.engine,
+            None,
+            crate::server::WorkdirStatusFilter::All,
+            None,
+        )
+        .await
+        {
+            Ok(snapshot) => {
+                for task in snapshot.tasks {
+                    self.tasks
+                        .insert((task.workspace_id.0, task.thread_id.0), task);
+                }
+            }
+            Err(err) => tracing::warn!(error = %err, "email notifier failed to load tasks"),
+        }
+    }
+
+    fn handle_server_message(&mut self, msg: WsServerMessage) {
+        let WsServerMessage::Event { event, .. } = msg else {
+            return;
+        };
+
+        match *event {
+            ServerEvent::AppChanged { snapshot, .. } => {
+                for line in self.observe_app(&snapshot) {
+                    let message = pull_request_email(&line);
+                    self.send(EmailEvent::PrCiFailed, message);
+                }
+            }
+            ServerEvent::TaskSummariesChanged { tasks, .. } => {
+                for task in tasks {
+                    let key = (task.workspace_id.0, task.thread_id.0);
+                    let prev = self.tasks.insert(key, task.clone());
+                    let Some(prev) = prev else {
+                        continue;
+                    };
+                    for event in task_notifications(&prev, &task) {
+                        let project_name = self.project_name(&task.project_id.0);
+                        self.send(event, task_email(event, &task, project_name));
+                    }
+                }
+            }
+            ServerEvent::ConversationChanged { snapshot } => {
+                self.observe_usage(&snapshot);
+            }
+            _ => {}
+        }
+    }
+
+    /// Updates project names and PR CI states, returning PRs whose CI just turned red.
+    fn observe_app(&mut self, app: &AppSnapshot) -> Vec<PullRequestDigestLine> {
+        let seeded = !self.project_names.is_empty() || !self.pull_request_ci.is_empty();
+        let mut failed = Vec::new();
+        for project in &app.projects {
+            self.project_names
+                .insert(project.id.0.clone(), project.name.clone());
+            for workspace in &project.workspaces {
+                let ci_state = workspace.pull_request.as_ref().and_then(|pr| pr.ci_state);
+                let prev = self.pull_request_ci.insert(workspace.id.0, ci_state);
+                if !seeded {
+                    continue;
+                }
+                let Some(pr) = workspace.pull_request.as_ref() else {
+                    continue;
+                };
+                if ci_turned_failed(prev.flatten(), ci_state) {
+                    failed.push(PullRequestDigestLine {
+                        project_name: project.name.clone(),
+                        workspace_name: workspace.workspace_name.clone(),
+                        branch_name: workspace.branch_name.clone(),
+                        number: pr.number,
+                        ci_state,
+                    });
+                }
+            }
+        }
+        failed
+    }
+
+    fn observe_usage(&mut self, snapshot: &luban_api::ConversationSnapshot) {
+        let key = (snapshot.workspace_id.0, snapshot.thread_id.0);
+        let last_seen = self.usage_seen_entry_index.get(&key).copied();
+        let mut max_seen = last_seen;
+        for (idx, entry) in snapshot.entries.iter().enumerate() {
+            let global_idx = snapshot.entries_start.saturating_add(idx as u64);
+            if last_seen.is_some_and(|last_seen| global_idx <= last_seen) {
+                continue;
+            }
+            max_seen = Some(global_idx);
+
+            let ConversationEntry::AgentEvent(agent) = entry else {
+                continue;
+            };
+            if agent.created_at_unix_ms < self.usage_since_unix_ms {
+                continue;
+            }
+            if let luban_api::AgentEvent::TurnUsage {
+                usage_json: Some(usage),
+            } = &agent.event
+            {
+                self.usage.add_json(usage);
+            }
+        }
+        if let Some(max_seen) = max_seen {
+            self.usage_seen_entry_index.insert(key, max_seen);
+        }
+    }
+
+    async fn maybe_send_digest(&mut self) {
+        let Some(digest_hour) = self.settings.digest_hour else {
+            return;
+        };
+        let now_secs = now_unix_ms() / 1000;
+        let day = now_secs / 86_400;
+        let hour = ((now_secs % 86_400) / 3600) as u32;
+        if hour != digest_hour || self.last_digest_day == Some(day) {
+            return;
+        }
+        self.last_digest_day = Some(day);
+
+        let app = match self.engine.app_snapshot().await {
+            Ok(app) => app,
+            Err(err) => {
+                tracing::warn!(error = %err, "email digest failed to load app snapshot");
+                return;
+            }
+        };
+        let tasks = match crate::server::collect_tasks_snapshot(
+            &self.engine,
+            None,
+            crate::server::WorkdirStatusFilter::Active,
+            None,
+        )
+        .await
+        {
+            Ok(snapshot) => snapshot.tasks,
+            Err(err) => {
+                tracing::warn!(error = %err, "email digest failed to load tasks");
+                return;
+            }
+        };
+
+        let (year, month, day_of_month) = crate::smtp::civil_from_days(day as i64);
+        let subject = format!("[Luban] Daily digest {year:04}-{month:02}-{day_of_month:02}");
+        let body = render_digest(&tasks, &awaiting_review(&app), self.usage);
+        self.usage = TokenUsage::default();
+        self.usage_since_unix_ms = now_unix_ms();
+        self.deliver(EmailMessage { subject, body });
+    }
+
+    fn project_name<'a>(&'a self, project_id: &'a str) -> &'a str {
+        self.project_names
+            .get(project_id)
+            .map(String::as_str)
+            .unwrap_or(project_id)
+    }
+
+    fn send(&self, event: EmailEvent, message: EmailMessage) {
+        if self.settings.events.contains(&event) {
+            self.deliver(message);
+        }
+    }
+
+    /// Sends in the background so a slow SMTP server never stalls event processing.
+    fn deliver(&self, message: EmailMessage) {
+        let smtp = self.settings.smtp.clone();
+        tokio::spawn(async move {
+            if let Err(err) = send_email(&smtp, &message).await {
+                tracing::warn!(error = %err, subject = %message.subject, "failed to send email");
+            }
+        });
+    }
+}
+
+fn now_unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn task_notifications(prev: &TaskSummarySnapshot, next: &TaskSummarySnapshot) -> Vec<EmailEvent> {
+    let mut events = Vec::new();
+    match turn_ended(prev, next) {
+        Some(TurnResult::Completed) => events.push(EmailEvent::TurnFinished),
+        Some(TurnResult::Failed) => events.push(EmailEvent::TurnFailed),
+        None => {}
+    }
+    if prev.task_status != TaskStatus::Validating && next.task_status == TaskStatus::Validating {
+        events.push(EmailEvent::TaskValidating);
+    }
+    events
+}
+
+fn awaiting_review(app: &AppSnapshot) -> Vec<PullRequestDigestLine> {
+    let mut lines = Vec::new();
+    for project in &app.projects {
+        for workspace in &project.workspaces {
+            let Some(pr) = workspace.pull_request.as_ref() else {
+                continue;
+            };
+            if pr.state != PullRequestState::Open || pr.is_draft {
+                continue;
+            }
+            lines.push(PullRequestDigestLine {
+                project_name: project.name.clone(),
+                workspace_name: workspace.workspace_name.clone(),
+                branch_name: workspace.branch_name.clone(),
+                number: pr.number,
+                ci_state: pr.ci_state,
+            });
+        }
+    }
+    lines
+}
+
+fn task_summary_lines(task: &TaskSummarySnapshot, project_name: &str) -> String {
+    format!(
+        "Task: {}\nProject: {}\nWorkdir: {} ({})\nStatus: {}\nTask id: {}/{}\n",
+        task.title,
+        project_name,
+        task.workspace_name,
+        task.branch_name,
+        task_status_label(task.task_status),
+        task.workspace_id.0,
+        task.thread_id.0,
+    )
+}
+
+fn task_email(event: EmailEvent, task: &TaskSummarySnapshot, project_name: &str) -> EmailMessage {
+    let title = truncate_label(&task.title, SUBJECT_TITLE_MAX_CHARS);
+    EmailMessage {
+        subject: format!("[Luban] {}: {title}", event.headline()),
+        body: format!(
+            "{}.\n\n{}",
+            event.headline(),
+            task_summary_lines(task, project_name)
+        ),
+    }
+}
+
+fn pull_request_email(line: &PullRequestDigestLine) -> EmailMessage {
+    EmailMessage {
+        subject: format!(
+            "[Luban] {}: PR #{} ({})",
+            EmailEvent::PrCiFailed.headline(),
+            line.number,
+            line.workspace_name
+        ),
+        body: format!(
+            "CI failed for pull request #{}.\n\nProject: {}\nWorkdir: {} ({})\n",
+            line.number, line.project_name, line.workspace_name, line.branch_name
+        ),
+    }
+}
+
+fn ci_label(state: Option<PullRequestCiState>) -> &'static str {
+    match state {
+        Some(PullRequestCiState::Success) => "passing",
+        Some(PullRequestCiState::Failure) => "failing",
+        Some(PullRequestCiState::Pending) => "pending",
+        None => "unknown",
+    }
+}
+
+fn render_digest(
+    tasks: &[TaskSummarySnapshot],
+    pull_requests: &[PullRequestDigestLine],
+    usage: TokenUsage,
+) -> String {
+    const STATUS_ORDER: [TaskStatus; 6] = [
+        TaskStatus::Iterating,
+        TaskStatus::Validating,
+        TaskStatus::Todo,
+        TaskStatus::Backlog,
+        TaskStatus::Done,
+        TaskStatus::Canceled,
+    ];
+
+    let mut by_status: BTreeMap<usize, Vec<&TaskSummarySnapshot>> = BTreeMap::new();
+    for task in tasks {
+        let rank = STATUS_ORDER
+            .iter()
+            .position(|s| *s == task.task_status)
+            .unwrap_or(STATUS_ORDER.len());
+        by_status.entry(rank).or_default().push(task);
+    }
+
+    let mut out = String::from("Luban daily digest\n\nTasks by status\n");
+    if tasks.is_empty() {
+        out.push_str("  (no tasks)\n");
+    }
+    for (rank, mut group) in by_status {
+        group.sort_by(|a, b| {
+            b.updated_at_unix_seconds
+                .cmp(&a.updated_at_unix_seconds)
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        let label = STATUS_ORDER
+            .get(rank)
+            .map(|s| task_status_label(*s))
+            .unwrap_or("Other");
+        out.push_str(&format!("  {label} ({})\n", group.len()));
+        for task in group.iter().take(DIGEST_TASKS_PER_STATUS_LIMIT) {
+            out.push_str(&format!(
+                "    - {} [{}] ({}/{})\n",
+                task.title, task.workspace_name, task.workspace_id.0, task.thread_id.0
+            ));
+        }
+        if group.len() > DIGEST_TASKS_PER_STATUS_LIMIT {
+            out.push_str(&format!(
+                "    ... and {} more\n",
+                group.len() - DIGEST_TASKS_PER_STATUS_LIMIT
+            ));
+        }
+    }
+
+    out.push_str("\nPull requests awaiting review\n");
+    if pull_requests.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for pr in pull_requests {
+        out.push_str(&format!(
+            "  - #{} {} / {} ({}), CI {}\n",
+            pr.number,
+            pr.project_name,
+            pr.workspace_name,
+            pr.branch_name,
+            ci_label(pr.ci_state)
+        ));
+    }
+
+    out.push_str("\nToken usage since the last digest\n");
+    out.push_str(&format!(
+        "  Turns: {}\n  Input: {} ({} cached)\n  Output: {}\n",
+        usage.turns, usage.input_tokens, usage.cached_input_tokens, usage.output_tokens
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luban_api::{OperationStatus, ProjectId, TurnStatus, WorkspaceId, WorkspaceThreadId};
+
+    fn task(title: &str, status: TaskStatus, turn: TurnStatus) -> TaskSummarySnapshot {
+        TaskSummarySnapshot {
+            project_id: ProjectId("p1".to_owned()),
+            workspace_id: WorkspaceId(3),
+            thread_id: WorkspaceThreadId(7),
+            title: title.to_owned(),
+            created_at_unix_seconds: 0,
+            updated_at_unix_seconds: 0,
+            branch_name: "luban/fix-login".to_owned(),
+            workspace_name: "fix-login".to_owned(),
+            agent_run_status: OperationStatus::Idle,
+            has_unread_completion: false,
+            task_status: status,
+            turn_status: turn,
+            last_turn_result: None,
+            is_starred: false,
+        }
+    }
+
+    #[test]
+    fn task_notifications_detect_turn_end_and_validation() {
+        let running = task("Fix login", TaskStatus::Iterating, TurnStatus::Running);
+
+        let mut finished = running.clone();
+        finished.turn_status = TurnStatus::Idle;
+        finished.last_turn_result = Some(TurnResult::Completed);
+        assert_eq!(
+            task_notifications(&running, &finished),
+            vec![EmailEvent::TurnFinished]
+        );
+
+        let mut failed = finished.clone();
+        failed.last_turn_result = Some(TurnResult::Failed);
+        assert_eq!(
+            task_notifications(&running, &failed),
+            vec![EmailEvent::TurnFailed]
+        );
+
+        let mut validating = finished.clone();
+        validating.task_status = TaskStatus::Validating;
+        assert_eq!(
+            task_notifications(&running, &validating),
+            vec![EmailEvent::TurnFinished, EmailEvent::TaskValidating]
+        );
+        assert!(task_notifications(&validating, &validating).is_empty());
+        assert!(task_notifications(&running, &running).is_empty());
+    }
+
+    #[test]
+    fn parse_events_ignores_unknown_and_duplicates() {
+        assert_eq!(
+            parse_events("turn_failed, pr_ci_failed,bogus,turn_failed"),
+            vec![EmailEvent::TurnFailed, EmailEvent::PrCiFailed]
+        );
+        assert!(parse_events("none").is_empty());
+    }
+
+    #[test]
+    fn task_email_reuses_task_summary() {
+        let message = task_email(
+            EmailEvent::TaskValidating,
+            &task("Fix login", TaskStatus::Validating, TurnStatus::Idle),
+            "Web",
+        );
+        assert_eq!(message.subject, "[Luban] Ready for validation: Fix login");
+        assert!(message.body.contains("Project: Web\n"));
+        assert!(
+            message
+                .body
+                .contains("Workdir: fix-login (luban/fix-login)\n")
+        );
+        assert!(message.body.contains("Status: Validating\n"));
+        assert!(message.body.contains("Task id: 3/7\n"));
+    }
+
+    #[test]
+    fn render_digest_groups_tasks_and_sums_usage() {
+        let tasks = vec![
+            task("Write docs", TaskStatus::Todo, TurnStatus::Idle),
+            task("Fix login", TaskStatus::Iterating, TurnStatus::Running),
+            task("Ship it", TaskStatus::Validating, TurnStatus::Idle),
+        ];
+        let prs = vec![PullRequestDigestLine {
+            project_name: "Web".to_owned(),
+            workspace_name: "fix-login".to_owned(),
+            branch_name: "luban/fix-login".to_owned(),
+            number: 12,
+            ci_state: Some(PullRequestCiState::Success),
+        }];
+        let mut usage = TokenUsage::default();
+        usage.add_json(&serde_json::json!({
+            "input_tokens": 1200,
+            "cached_input_tokens": 200,
+            "output_tokens": 300
+        }));
+        usage.add_json(&serde_json::json!({ "input_tokens": 100 }));
+
+        let digest = render_digest(&tasks, &prs, usage);
+        let iterating = digest.find("Iterating (1)").expect("iterating group");
+        let validating = digest.find("Validating (1)").expect("validating group");
+        let todo = digest.find("Todo (1)").expect("todo group");
+        assert!(iterating < validating && validating < todo);
+        assert!(digest.contains("    - Fix login [fix-login] (3/7)\n"));
+        assert!(digest.contains("  - #12 Web / fix-login (luban/fix-login), CI passing\n"));
+        assert!(digest.contains("  Turns: 2\n  Input: 1300 (200 cached)\n  Output: 300\n"));
+    }
+
+    #[tokio::test]
+    async fn task_email_is_delivered_to_smtp_sink() {
+        let (port, sink) = crate::smtp::tests::start_smtp_sink(&[]).await;
+        let config = SmtpConfig {
+            host: "127.0.0.1".to_owned(),
+            port,
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from: "luban@example.com".to_owned(),
+            to: vec!["dev@example.com".to_owned()],
+        };
+        let message = task_email(
+            EmailEvent::TurnFailed,
+            &task("Fix login", TaskStatus::Iterating, TurnStatus::Idle),
+            "Web",
+        );
+        send_email(&config, &message).await.expect("send email");
+
+        let transcript = sink.await.expect("sink transcript");
+        assert!(transcript.contains(&"RCPT TO:<dev@example.com>".to_owned()));
+        assert!(transcript.contains(&"Subject: [Luban] Turn failed: Fix login".to_owned()));
+    }
+}
@@ -1,17 +1,22 @@
 use crate::branch_watch::BranchWatchHandle;
+use crate::idempotency::{Begin, IdempotencyStore};
 use crate::task_document_watch::TaskDocumentWatchHandle;
 use anyhow::Context as _;
 use luban_api::{
     AppSnapshot, ConversationSnapshot, PullRequestCiState, PullRequestSnapshot, PullRequestState,
     ThreadsSnapshot, WorkspaceTabsSnapshot, WsServerMessage,
 };
-use luban_backend::{GitWorkspaceService, SqliteStoreOptions};
+use luban_backend::{
+    GitWorkspaceService, SqliteStoreOptions, format_ci_fix_prompt, format_review_prompt,
+};
 use luban_domain::{
-    Action, AppState, AttachmentKind, AttachmentRef, CodexThreadEvent, CodexThreadItem,
-    ConversationEntry, ConversationThreadMeta, Effect, OpenTarget, OperationStatus,
-    ProjectWorkspaceService, PullRequestCiState as DomainPullRequestCiState, PullRequestInfo,
-    PullRequestState as DomainPullRequestState, TaskDocumentKind as DomainTaskDocumentKind,
-    ThinkingEffort, WorkspaceId, WorkspaceTabs, WorkspaceThreadId,
+    Action, AppState, AttachmentKind, AttachmentRef, AuditEntry, AuditOrigin, AuditRecord,
+    AuditUndo, CiAutoFixStopReason, CiFailureLog, CodexThreadEvent, CodexThreadItem,
+    ConversationEntry, ConversationThreadMeta, DiskCleanupReport, Effect, OpenTarget,
+    OperationStatus, ProjectWorkspaceService, PullRequestCiState as DomainPullRequestCiState,
+    PullRequestInfo, PullRequestState as DomainPullRequestState,
+    TaskDocumentKind as DomainTaskDocumentKind, ThinkingEffort, WorkspaceDiskUsage, WorkspaceId,
+    WorkspaceTabs, WorkspaceThreadId,
 };
 use rand::RngCore as _;
 use rand::rngs::OsRng;
@@ -32,6 +37,11 @@ pub struct EngineHandle {
 }
 
 impl EngineHandle {
+    #[cfg(test)]
+    pub(crate) fn from_sender_for_tests(tx: mpsc::Sender<EngineCommand>) -> Self {
+        Self { tx }
+    }
+
     pub async fn current_rev(&self) -> anyhow::Result<u64> {
         let (tx, rx) = oneshot::channel();
         self.tx
@@ -125,6 +135,7 @@ impl EngineHandle {
         &self,
         code: String,
         chat_id: i64,
+        group: Option<TelegramGroupPairing>,
     ) -> Result<(), String> {
         let (tx, rx) = oneshot::channel();
         if self
@@ -132,6 +143,7 @@ impl EngineHandle {
             .send(EngineCommand::ConsumeTelegramPairingCode {
                 code,
                 chat_id,
+                group,
                 reply: tx,
             })
             .await
@@ -157,11 +169,23 @@ impl EngineHandle {
         &self,
         request_id: String,
         action: luban_api::ClientAction,
+    ) -> Result<u64, String> {
+        self.apply_client_action_from(AuditOrigin::Web, request_id, action)
+            .await
+    }
+
+    /// Like `apply_client_action`, recording `origin` in the audit log instead of `web`.
+    pub async fn apply_client_action_from(
+        &self,
+        origin: AuditOrigin,
+        request_id: String,
+        action: luban_api::ClientAction,
     ) -> Result<u64, String> {
         let (tx, rx) = oneshot::channel();
         if self
             .tx
             .send(EngineCommand::ApplyClientAction {
+                origin,
                 request_id,
                 action,
                 reply: tx,
@@ -207,9 +231,11 @@ pub enum EngineCommand {
     ConsumeTelegramPairingCode {
         code: String,
         chat_id: i64,
+        group: Option<TelegramGroupPairing>,
         reply: oneshot::Sender<Result<(), String>>,
     },
     ApplyClientAction {
+        origin: AuditOrigin,
         request_id: String,
         action: luban_api::ClientAction,
         reply: oneshot::Sender<Result<u64, String>>,
@@ -227,7 +253,26 @@ pub enum EngineCommand {
         workspace_id: WorkspaceId,
         info: Option<PullRequestInfo>,
     },
+    /// The forge could not be asked (auth, network); the last known pull request is kept.
+    PullRequestLookupFailed {
+        workspace_id: WorkspaceId,
+        message: String,
+    },
+    CiFailureLogFetched {
+        workspace_id: WorkspaceId,
+        pr_number: u64,
+        log: CiFailureLog,
+    },
     PruneArchivedTasks,
+    RefreshDiskUsage,
+    DiskUsageMeasured {
+        usage: Vec<(WorkspaceId, WorkspaceDiskUsage)>,
+        measured_at_unix_ms: u64,
+    },
+    ResumeInterruptedTurn {
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    },
     WorkspaceThreadsInvalidated {
         workspace_id: WorkspaceId,
     },
@@ -262,11 +307,140 @@ const PULL_REQUEST_REFRESH_INTERVAL_EMPTY_INITIAL: Duration = Duration::from_sec
 const PULL_REQUEST_REFRESH_INTERVAL_EMPTY_MEDIUM: Duration = Duration::from_secs(3 * 60);
 const PULL_REQUEST_REFRESH_INTERVAL_EMPTY_MAX: Duration = Duration::from_secs(10 * 60);
 
+/// Name of the trimmed failed job log attached to a "fix CI" turn.
+const CI_FAILURE_LOG_ATTACHMENT_NAME: &str = "ci-failure.log";
+
+/// Posted on a pull request review comment once its imported copy is resolved in Luban.
+const REVIEW_COMMENT_ADDRESSED_REPLY: &str = "Addressed in the latest changes.";
+
 const TASK_ARCHIVE_AFTER_SECONDS: u64 = 7 * 24 * 60 * 60;
 const TASK_PURGE_AFTER_SECONDS: u64 = 2 * TASK_ARCHIVE_AFTER_SECONDS;
 const TASK_PURGE_TICK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
 const TASK_PURGE_STARTUP_DELAY: Duration = Duration::from_secs(60);
 
+const DISK_USAGE_REFRESH_TICK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const DISK_USAGE_REFRESH_STARTUP_DELAY: Duration = Duration::from_secs(30);
+const DEFAULT_BUILD_ARTIFACT_GLOBS: &[&str] =
+    &["target", "node_modules", ".next", ".turbo", "__pycache__"];
+
+const TASK_BULK_MAX_ITEMS: usize = 500;
+const TASK_BULK_IDEMPOTENCY_TTL: Duration = Duration::from_secs(10 * 60);
+const TASK_BULK_IDEMPOTENCY_MAX_ENTRIES: usize = 256;
+/// How far back in the audit log a bulk undo looks for its batch.
+const TASK_BULK_UNDO_LOOKBACK_ENTRIES: u64 = 200;
+
+const STALE_RUN_INTERRUPTED_MESSAGE: &str = "Agent run interrupted by server restart.";
+const INTERRUPTED_TURN_CONTINUATION_PROMPT: &str = "Your previous run was interrupted by a server restart before it finished. Review the current state of the workspace and continue where you left off.";
+
+/// A turn that was still marked as running when the server started.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct InterruptedTurn {
+    thread_id: WorkspaceThreadId,
+    /// Whether the runner recorded a session (Codex thread, Claude session, Amp thread) that a
+    /// continuation prompt can attach to.
+    resumable: bool,
+    queue_paused: bool,
+}
+
+fn auto_resume_interrupted_turns_enabled() -> bool {
+    std::env::var("LUBAN_AUTO_RESUME_INTERRUPTED_TURNS")
+        .ok()
+        .map(|v| v.trim().to_ascii_lowercase())
+        .is_some_and(|v| matches!(v.as_str(), "1" | "true" | "yes" | "on"))
+}
+
+fn reconcile_stale_running_turns_blocking(
+    services: &dyn ProjectWorkspaceService,
+    project_slug: String,
+    workspace_name: String,
+    finished_at_unix_ms: u64,
+) -> Result<Vec<InterruptedTurn>, String> {
+    let threads =
+        services.list_conversation_threads(project_slug.clone(), workspace_name.clone())?;
+    let mut interrupted = Vec::new();
+    for meta in threads {
+        if meta.turn_status != luban_domain::TurnStatus::Running {
+            continue;
+        }
+
+        let snapshot = services.load_conversation(
+            project_slug.clone(),
+            workspace_name.clone(),
+            meta.thread_id.as_u64(),
+        )?;
+
+        if snapshot.run_started_at_unix_ms.is_none() || snapshot.run_finished_at_unix_ms.is_some() {
+            continue;
+        }
+
+        let resumable = snapshot
+            .thread_id
+            .as_deref()
+            .is_some_and(|id| !id.trim().is_empty());
+
+        services.append_conversation_entries(
+            project_slug.clone(),
+            workspace_name.clone(),
+            meta.thread_id.as_u64(),
+            vec![
+                luban_domain::ConversationEntry::AgentEvent {
+                    entry_id: String::new(),
+                    created_at_unix_ms: 0,
+                    runner: None,
+                    event: luban_domain::AgentEvent::TurnError {
+                        message: STALE_RUN_INTERRUPTED_MESSAGE.to_owned(),
+                    },
+                },
+                luban_domain::ConversationEntry::SystemEvent {
+                    entry_id: String::new(),
+                    created_at_unix_ms: finished_at_unix_ms,
+                    event: luban_domain::ConversationSystemEvent::TurnInterrupted {
+                        resumable,
+                        queue_paused: snapshot.queue_paused,
+                    },
+                },
+            ],
+        )?;
+
+        // The queue stays paused until the interrupted turn is resumed, either by the user or by
+        // the auto-resume pass, so queued prompts never run ahead of the unfinished turn.
+        services.save_conversation_queue_state(
+            project_slug.clone(),
+            workspace_name.clone(),
+            meta.thread_id.as_u64(),
+            true,
+            snapshot.run_started_at_unix_ms,
+            Some(finished_at_unix_ms),
+            snapshot.pending_prompts,
+        )?;
+
+        interrupted.push(InterruptedTurn {
+            thread_id: meta.thread_id,
+            resumable,
+            queue_paused: snapshot.queue_paused,
+        });
+    }
+    Ok(interrupted)
+}
+
+/// Checks that the persisted thread ends in a resumable interrupted turn and returns whether its
+/// queue should run again afterwards, i.e. whether the user had not paused it themselves.
+fn interrupted_turn_resumes_queue(
+    snapshot: &luban_domain::ConversationSnapshot,
+) -> Result<bool, String> {
+    let Some(luban_domain::ConversationEntry::SystemEvent {
+        event: luban_domain::ConversationSystemEvent::TurnInterrupted { queue_paused, .. },
+        ..
+    }) = snapshot.entries.last()
+    else {
+        return Err("thread does not end in an interrupted turn".to_owned());
+    };
+    if snapshot.thread_id.is_none() {
+        return Err("interrupted turn has no agent session to resume".to_owned());
+    }
+    Ok(!queue_paused)
+}
+
 fn pull_request_refresh_jitter(workspace_id: WorkspaceId) -> Duration {
     let window = PULL_REQUEST_REFRESH_JITTER_WINDOW_SECS.max(1);
     Duration::from_secs(workspace_id.as_u64() % window)
@@ -334,9 +508,16 @@ pub struct Engine {
     cancel_flags: HashMap<(WorkspaceId, WorkspaceThreadId), CancelFlagEntry>,
     pull_requests: HashMap<WorkspaceId, PullRequestCacheEntry>,
     pull_requests_in_flight: HashSet<WorkspaceId>,
+    disk_usage: HashMap<WorkspaceId, DiskUsageEntry>,
+    disk_usage_in_flight: bool,
     workspace_threads_cache: HashMap<WorkspaceId, Vec<ConversationThreadMeta>>,
     auto_archive_workspaces: HashSet<WorkspaceId>,
     telegram_pairing: Option<TelegramPairingState>,
+    task_bulk: TaskBulkState,
+    /// Origin recorded in the audit log for actions applied by the current command.
+    action_origin: AuditOrigin,
+    /// Set while an undo is applied so the reverting actions are not audited themselves.
+    audit_suppressed: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -345,6 +526,23 @@ pub struct TelegramRuntimeConfig {
     pub bot_token: Option<String>,
     pub paired_chat_id: Option<i64>,
     pub topic_bindings: Vec<TelegramTopicBindingRoute>,
+    pub group_chats: Vec<TelegramGroupChatRoute>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TelegramGroupChatRoute {
+    pub chat_id: i64,
+    pub project_slug: String,
+    pub member_user_ids: Vec<i64>,
+    pub topic_bindings: Vec<TelegramTopicBindingRoute>,
+}
+
+/// Who sent a pairing code from a group chat, and which group it was.
+#[derive(Clone, Debug)]
+pub struct TelegramGroupPairing {
+    pub title: String,
+    pub user_id: i64,
+    pub display_name: String,
 }
 
 #[derive(Clone, Debug)]
@@ -359,6 +557,41 @@ pub struct TelegramTopicBindingRoute {
 struct TelegramPairingState {
     code: String,
     expires_at: Instant,
+    /// Set when the code pairs a group chat with this project.
+    project_slug: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct DiskUsageEntry {
+    usage: WorkspaceDiskUsage,
+    measured_at_unix_ms: u64,
+}
+
+/// Results of recent bulk task operations, replayed for repeated idempotency keys. Status
+/// batches are undone through their grouped audit log entry.
+struct TaskBulkState {
+    results: IdempotencyStore<luban_api::TaskBulkResult>,
+    next_batch: u64,
+}
+
+impl Default for TaskBulkState {
+    fn default() -> Self {
+        Self {
+            results: IdempotencyStore::new(
+                TASK_BULK_IDEMPOTENCY_TTL,
+                TASK_BULK_IDEMPOTENCY_MAX_ENTRIES,
+            ),
+            next_batch: 1,
+        }
+    }
+}
+
+impl TaskBulkState {
+    fn allocate_batch_id(&mut self) -> String {
+        let id = format!("bulk_{}_{}", now_unix_ms(), self.next_batch);
+        self.next_batch = self.next_batch.saturating_add(1);
+        id
+    }
 }
 
 #[derive(Clone)]
@@ -387,9 +620,14 @@ impl Engine {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         let refresh_tx = tx.clone();
@@ -415,6 +653,17 @@ impl Engine {
             }
         });
 
+        let disk_usage_tx = tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(DISK_USAGE_REFRESH_STARTUP_DELAY).await;
+            let mut interval = tokio::time::interval(DISK_USAGE_REFRESH_TICK_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let _ = disk_usage_tx.send(EngineCommand::RefreshDiskUsage).await;
+            }
+        });
+
         tokio::spawn(async move {
             engine.bootstrap().await;
             while let Some(cmd) = rx.recv().await {
@@ -501,189 +750,191 @@ impl Engine {
         });
     }
 
-    fn schedule_reconcile_stale_running_turns(&self) {
+    fn stale_run_reconcile_scopes(&self) -> Vec<(WorkspaceId, WorkspaceScope)> {
         let mut scopes = Vec::new();
         for project in &self.state.projects {
             for workspace in &project.workspaces {
                 if workspace.status != luban_domain::WorkspaceStatus::Active {
                     continue;
                 }
-                scopes.push(WorkspaceScope {
-                    project_slug: project.slug.clone(),
-                    workspace_name: workspace.workspace_name.clone(),
-                });
+                scopes.push((
+                    workspace.id,
+                    WorkspaceScope {
+                        project_slug: project.slug.clone(),
+                        workspace_name: workspace.workspace_name.clone(),
+                    },
+                ));
             }
         }
+        scopes
+    }
 
+    fn schedule_reconcile_stale_running_turns(&self) {
+        let scopes = self.stale_run_reconcile_scopes();
         if scopes.is_empty() {
             return;
         }
 
         let services = self.services.clone();
+        let tx = self.tx.clone();
+        let auto_resume = auto_resume_interrupted_turns_enabled();
         tokio::spawn(async move {
             let finished_at_unix_ms = now_unix_ms();
-            for scope in scopes {
+            for (workspace_id, scope) in scopes {
                 let services = services.clone();
                 let project_slug = scope.project_slug.clone();
                 let workspace_name = scope.workspace_name.clone();
                 let result = tokio::task::spawn_blocking(move || {
-                    let threads = services
-                        .list_conversation_threads(project_slug.clone(), workspace_name.clone())?;
-                    let mut reconciled = 0usize;
-                    for meta in threads {
-                        if meta.turn_status != luban_domain::TurnStatus::Running {
-                            continue;
-                        }
-
-                        let snapshot = services.load_conversation(
-                            project_slug.clone(),
-                            workspace_name.clone(),
-                            meta.thread_id.as_u64(),
-                        )?;
-
-                        if snapshot.run_started_at_unix_ms.is_none()
-                            || snapshot.run_finished_at_unix_ms.is_some()
-                        {
-                            continue;
-                        }
-
-                        services.append_conversation_entries(
-                            project_slug.clone(),
-                            workspace_name.clone(),
-                            meta.thread_id.as_u64(),
-                            vec![luban_domain::ConversationEntry::AgentEvent {
-                                entry_id: String::new(),
-                                created_at_unix_ms: 0,
-                                runner: None,
-                                event: luban_domain::AgentEvent::TurnError {
-                                    message: "Agent run interrupted by server restart.".to_owned(),
-                                },
-                            }],
-                        )?;
-
-                        services.save_conversation_queue_state(
-                            project_slug.clone(),
-                            workspace_name.clone(),
-                            meta.thread_id.as_u64(),
-                            true,
-                            snapshot.run_started_at_unix_ms,
-                            Some(finished_at_unix_ms),
-                            snapshot.pending_prompts,
-                        )?;
-                        reconciled += 1;
-                    }
-                    Ok::<_, String>(reconciled)
+                    reconcile_stale_running_turns_blocking(
+                        services.as_ref(),
+                        project_slug,
+                        workspace_name,
+                        finished_at_unix_ms,
+                    )
                 })
                 .await
                 .ok()
                 .unwrap_or_else(|| Err("failed to join stale run reconcile task".to_owned()));
 
-                let Ok(reconciled) = result else {
+                let Ok(interrupted) = result else {
                     continue;
                 };
-                if reconciled == 0 {
+                if interrupted.is_empty() {
                     continue;
                 }
 
                 tracing::info!(
                     project_slug = %scope.project_slug,
                     workspace_name = %scope.workspace_name,
-                    reconciled,
+                    reconciled = interrupted.len(),
                     "reconciled stale running turns"
                 );
-            }
-        });
-    }
 
-    #[cfg(test)]
-    async fn reconcile_stale_running_turns(&mut self) {
-        let mut scopes = Vec::new();
-        for project in &self.state.projects {
-            for workspace in &project.workspaces {
-                if workspace.status != luban_domain::WorkspaceStatus::Active {
+                if !auto_resume {
                     continue;
                 }
-                scopes.push(WorkspaceScope {
-                    project_slug: project.slug.clone(),
-                    workspace_name: workspace.workspace_name.clone(),
-                });
+                for turn in interrupted {
+                    if !turn.resumable || turn.queue_paused {
+                        continue;
+                    }
+                    let _ = tx
+                        .send(EngineCommand::ResumeInterruptedTurn {
+                            workspace_id,
+                            thread_id: turn.thread_id,
+                        })
+                        .await;
+                }
             }
-        }
-
-        if scopes.is_empty() {
-            return;
-        }
+        });
+    }
 
+    #[cfg(test)]
+    async fn reconcile_stale_running_turns(&mut self) -> Vec<InterruptedTurn> {
         let finished_at_unix_ms = now_unix_ms();
-        for scope in scopes {
+        let mut out = Vec::new();
+        for (_, scope) in self.stale_run_reconcile_scopes() {
             let services = self.services.clone();
-            let project_slug = scope.project_slug.clone();
-            let workspace_name = scope.workspace_name.clone();
             let result = tokio::task::spawn_blocking(move || {
-                let threads = services
-                    .list_conversation_threads(project_slug.clone(), workspace_name.clone())?;
-                let mut reconciled = 0usize;
-                for meta in threads {
-                    if meta.turn_status != luban_domain::TurnStatus::Running {
-                        continue;
-                    }
-
-                    let snapshot = services.load_conversation(
-                        project_slug.clone(),
-                        workspace_name.clone(),
-                        meta.thread_id.as_u64(),
-                    )?;
-
-                    if snapshot.run_started_at_unix_ms.is_none()
-                        || snapshot.run_finished_at_unix_ms.is_some()
-                    {
-                        continue;
-                    }
-
-                    services.append_conversation_entries(
-                        project_slug.clone(),
-                        workspace_name.clone(),
-                        meta.thread_id.as_u64(),
-                        vec![luban_domain::ConversationEntry::AgentEvent {
-                            entry_id: String::new(),
-                            created_at_unix_ms: 0,
-                            runner: None,
-                            event: luban_domain::AgentEvent::TurnError {
-                                message: "Agent run interrupted by server restart.".to_owned(),
-                            },
-                        }],
-                    )?;
-
-                    services.save_conversation_queue_state(
-                        project_slug.clone(),
-                        workspace_name.clone(),
-                        meta.thread_id.as_u64(),
-                        true,
-                        snapshot.run_started_at_unix_ms,
-                        Some(finished_at_unix_ms),
-                        snapshot.pending_prompts,
-                    )?;
-                    reconciled += 1;
-                }
-                Ok::<_, String>(reconciled)
+                reconcile_stale_running_turns_blocking(
+                    services.as_ref(),
+                    scope.project_slug,
+                    scope.workspace_name,
+                    finished_at_unix_ms,
+                )
             })
             .await
             .ok()
             .unwrap_or_else(|| Err("failed to join stale run reconcile task".to_owned()));
 
-            let Ok(reconciled) = result else {
-                continue;
-            };
-            if reconciled == 0 {
-                continue;
+            if let Ok(interrupted) = result {
+                out.extend(interrupted);
             }
+        }
+        out
+    }
 
-            tracing::info!(
-                project_slug = %scope.project_slug,
-                workspace_name = %scope.workspace_name,
-                reconciled,
-                "reconciled stale running turns"
-            );
+    async fn resume_interrupted_turn(
+        &mut self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> Result<(), String> {
+        let Some(scope) = workspace_scope(&self.state, workspace_id) else {
+            return Err("workspace not found".to_owned());
+        };
+
+        let services = self.services.clone();
+        let thread_local_id = thread_id.as_u64();
+        let snapshot = tokio::task::spawn_blocking(move || {
+            services.load_conversation_page(
+                scope.project_slug,
+                scope.workspace_name,
+                thread_local_id,
+                None,
+                5000,
+            )
+        })
+        .await
+        .ok()
+        .unwrap_or_else(|| Err("failed to join load conversation task".to_owned()))?;
+
+        let resume_queue = interrupted_turn_resumes_queue(&snapshot)?;
+
+        self.process_action_queue(Action::ConversationLoaded {
+            workspace_id,
+            thread_id,
+            snapshot,
+        })
+        .await;
+        self.process_action_queue(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: INTERRUPTED_TURN_CONTINUATION_PROMPT.to_owned(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        })
+        .await;
+        if resume_queue {
+            self.process_action_queue(Action::ResumeQueuedPrompts {
+                workspace_id,
+                thread_id,
+            })
+            .await;
+        }
+        Ok(())
+    }
+
+    /// Permanently removes soft-deleted threads and projects past the retention window.
+    async fn purge_expired_deletions(&mut self) {
+        let cutoff = self
+            .state
+            .deletion_settings()
+            .purge_cutoff_unix_ms(now_unix_ms());
+
+        let services = self.services.clone();
+        match tokio::task::spawn_blocking(move || {
+            services.purge_deleted_conversation_threads(cutoff)
+        })
+        .await
+        .ok()
+        .unwrap_or_else(|| Err("failed to join deleted thread purge task".to_owned()))
+        {
+            Ok(0) => {}
+            Ok(purged) => tracing::info!(purged, "purged deleted tasks"),
+            Err(message) => tracing::warn!(error = %message, "failed to purge deleted tasks"),
+        }
+
+        if self
+            .state
+            .deleted_projects()
+            .iter()
+            .any(|p| p.deleted_at_unix_ms <= cutoff)
+        {
+            self.process_action_queue(Action::DeletedProjectsPurged {
+                before_unix_ms: cutoff,
+            })
+            .await;
         }
     }
 
@@ -789,7 +1040,11 @@ impl Engine {
         }
     }
 
-    async fn telegram_pair_start(&mut self, request_id: String) -> Result<(), String> {
+    async fn telegram_pair_start(
+        &mut self,
+        request_id: String,
+        project_slug: Option<String>,
+    ) -> Result<(), String> {
         if crate::telegram::telegram_disabled() {
             return Err("telegram integration is disabled".to_owned());
         }
@@ -810,12 +1065,17 @@ impl Engine {
         let mut bytes = [0u8; 16];
         OsRng.fill_bytes(&mut bytes);
         let code = hex_lower(&bytes);
+        let url = if project_slug.is_some() {
+            format!("https://t.me/{username}?startgroup={code}")
+        } else {
+            format!("https://t.me/{username}?start={code}")
+        };
         self.telegram_pairing = Some(TelegramPairingState {
-            code: code.clone(),
+            code,
             expires_at: Instant::now() + Duration::from_secs(10 * 60),
+            project_slug,
         });
 
-        let url = format!("https://t.me/{username}?start={code}");
         let _ = self.events.send(WsServerMessage::Event {
             rev: self.rev,
             event: Box::new(luban_api::ServerEvent::TelegramPairReady { request_id, url }),
@@ -909,45 +1169,176 @@ impl Engine {
         Ok(created_thread_id)
     }
 
-    async fn execute_task_prompt(
-        &mut self,
-        prompt: String,
-        mode: luban_api::TaskExecuteMode,
-        workdir_id: Option<luban_api::WorkspaceId>,
-        attachments: Vec<luban_api::AttachmentRef>,
-        model_id: Option<String>,
-        thinking_effort: Option<luban_domain::ThinkingEffort>,
-    ) -> Result<luban_api::TaskExecuteResult, String> {
-        let Some(workdir_id) = workdir_id else {
-            return Err("workdir_id is required".to_owned());
-        };
-
-        let workspace_id = WorkspaceId::from_u64(workdir_id.0);
-        let Some(workspace) = self.state.workspace(workspace_id) else {
-            return Err("workdir not found".to_owned());
-        };
-        let worktree_path = workspace.worktree_path.to_string_lossy().to_string();
-
-        let Some(project_path) = self
-            .state
+    fn project_slug(&self, project_id: luban_domain::ProjectId) -> Option<String> {
+        self.state
             .projects
             .iter()
-            .find(|p| p.workspaces.iter().any(|w| w.id == workspace_id))
-            .map(|p| p.path.to_string_lossy().to_string())
-        else {
-            return Err("failed to locate project for workdir".to_owned());
-        };
-
-        self.process_action_queue(Action::OpenWorkspace { workspace_id })
-            .await;
-        let thread_id = self
-            .create_workspace_thread_safe(workspace_id, model_id, thinking_effort)
-            .await?;
+            .find(|p| p.id == project_id)
+            .map(|p| p.slug.clone())
+    }
+
+    /// Renders `task_input` through the resolved task template for `intent_kind`, filling in
+    /// workspace, git and task document variables plus the project's custom variables.
+    async fn render_task_prompt(
+        &self,
+        project_id: luban_domain::ProjectId,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        intent_kind: luban_domain::TaskIntentKind,
+        task_input: &str,
+    ) -> String {
+        let template = self
+            .state
+            .task_prompt_template(Some(project_id), intent_kind);
+        let mut values: HashMap<String, String> = HashMap::new();
+        if let Some(settings) = self.state.project_prompt_settings.get(&project_id) {
+            for (name, value) in &settings.variables {
+                values.insert(
+                    format!("{}{name}", luban_domain::CUSTOM_PROMPT_VARIABLE_PREFIX),
+                    value.clone(),
+                );
+            }
+        }
+
+        let mut known_context = String::from("Known context:\n");
+        if let Some(project) = self.state.projects.iter().find(|p| p.id == project_id) {
+            known_context.push_str(&format!(
+                "- Project: {} ({})\n",
+                project.name,
+                project.path.display()
+            ));
+            values.insert("project_name".to_owned(), project.name.clone());
+            values.insert(
+                "project_path".to_owned(),
+                project.path.to_string_lossy().to_string(),
+            );
+        }
+
+        if let Some(workspace) = self.state.workspace(workspace_id) {
+            known_context.push_str(&format!("- Branch: {}\n", workspace.branch_name));
+            values.insert("branch".to_owned(), workspace.branch_name.clone());
+
+            let has_pull_request = self
+                .pull_requests
+                .get(&workspace_id)
+                .is_some_and(|entry| entry.info.is_some());
+            let worktree_path = workspace.worktree_path.clone();
+            let services = self.services.clone();
+            let git_info = tokio::task::spawn_blocking(move || {
+                let base_ref = crate::git_changes::upstream_ref(&worktree_path);
+                let changed_files = crate::git_changes::collect_changes(&worktree_path)
+                    .map(|files| {
+                        files
+                            .into_iter()
+                            .map(|file| format!("- {}", file.path))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default();
+                let pr_url = if has_pull_request {
+                    services.gh_pull_request_url(worktree_path).ok().flatten()
+                } else {
+                    None
+                };
+                (base_ref, changed_files, pr_url)
+            })
+            .await;
+            if let Ok((base_ref, changed_files, pr_url)) = git_info {
+                if let Some(base_ref) = base_ref {
+                    known_context.push_str(&format!("- Base ref: {base_ref}\n"));
+                    values.insert("base_ref".to_owned(), base_ref);
+                }
+                values.insert("changed_files".to_owned(), changed_files);
+                if let Some(pr_url) = pr_url {
+                    known_context.push_str(&format!("- Pull request: {pr_url}\n"));
+                    values.insert("pr_url".to_owned(), pr_url);
+                }
+            }
+        }
+
+        if let Ok(paths) = resolve_task_document_paths(workspace_id, thread_id) {
+            for (name, path) in [
+                ("task_document", &paths.task_path),
+                ("plan_document", &paths.plan_path),
+                ("memory_document", &paths.memory_path),
+            ] {
+                values.insert(name.to_owned(), path.to_string_lossy().to_string());
+            }
+        }
+
+        values.insert("task_input".to_owned(), task_input.trim().to_owned());
+        values.insert("intent_label".to_owned(), intent_kind.label().to_owned());
+        values.insert("known_context".to_owned(), known_context);
+        luban_domain::render_prompt_template(
+            &template,
+            luban_domain::TASK_PROMPT_VARIABLES,
+            &values,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_task_prompt(
+        &mut self,
+        prompt: String,
+        mode: luban_api::TaskExecuteMode,
+        workdir_id: Option<luban_api::WorkspaceId>,
+        attachments: Vec<luban_api::AttachmentRef>,
+        model_id: Option<String>,
+        thinking_effort: Option<luban_domain::ThinkingEffort>,
+        intent_kind: Option<luban_domain::TaskIntentKind>,
+        task_document: Option<String>,
+    ) -> Result<luban_api::TaskExecuteResult, String> {
+        let Some(workdir_id) = workdir_id else {
+            return Err("workdir_id is required".to_owned());
+        };
+
+        let workspace_id = WorkspaceId::from_u64(workdir_id.0);
+        let Some(workspace) = self.state.workspace(workspace_id) else {
+            return Err("workdir not found".to_owned());
+        };
+        let worktree_path = workspace.worktree_path.to_string_lossy().to_string();
+
+        let Some((project_id, project_path)) = self
+            .state
+            .projects
+            .iter()
+            .find(|p| p.workspaces.iter().any(|w| w.id == workspace_id))
+            .map(|p| (p.id, p.path.to_string_lossy().to_string()))
+        else {
+            return Err("failed to locate project for workdir".to_owned());
+        };
+
+        self.process_action_queue(Action::OpenWorkspace { workspace_id })
+            .await;
+        let thread_id = self
+            .create_workspace_thread_safe(workspace_id, model_id, thinking_effort)
+            .await?;
+
+        if let Some(content) = task_document {
+            let written = resolve_task_document_paths(workspace_id, thread_id)
+                .and_then(|paths| write_text_atomic(&paths.task_path, &content));
+            if let Err(err) = written {
+                tracing::warn!(
+                    workspace_id = workspace_id.as_u64(),
+                    thread_id = thread_id.as_u64(),
+                    error = %err,
+                    "failed to seed task document"
+                );
+            }
+        }
 
         // Reason: CreateWorkspaceThread already sets the correct per-runner
         // model and thinking effort via resolve_default_model_for_runner, so
         // we no longer override them here with the global Codex default.
 
+        let prompt = match intent_kind {
+            Some(intent_kind) => {
+                self.render_task_prompt(project_id, workspace_id, thread_id, intent_kind, &prompt)
+                    .await
+            }
+            None => prompt,
+        };
+
         if mode == luban_api::TaskExecuteMode::Start {
             let text = match resolve_task_document_paths(workspace_id, thread_id) {
                 Ok(paths) => inject_task_document_prompt(&prompt, &paths),
@@ -969,6 +1360,7 @@ impl Engine {
                 attachments,
                 runner: None,
                 amp_mode: None,
+                author: None,
             })
             .await;
         }
@@ -983,116 +1375,604 @@ impl Engine {
         })
     }
 
-    async fn handle(&mut self, cmd: EngineCommand) {
-        match cmd {
-            EngineCommand::GetRev { reply } => {
-                let _ = reply.send(Ok(self.rev));
-            }
-            EngineCommand::GetAppSnapshot { reply } => {
-                self.refresh_pull_requests_for_all_workspaces();
-                let _ = reply.send(Ok(self.app_snapshot()));
-            }
-            EngineCommand::GetThreadsSnapshot {
-                workspace_id,
-                reply,
-            } => {
-                let wid = WorkspaceId::from_u64(workspace_id.0);
-                let Some(scope) = workspace_scope(&self.state, wid) else {
-                    let _ = reply.send(Err(anyhow::anyhow!("workspace not found")));
-                    return;
-                };
+    /// Creates a task from a forge issue: a workdir named after the issue, TASK.md seeded with
+    /// the issue and its discussion, and a link so merging the pull request can resolve it.
+    async fn import_issue_as_task(
+        &mut self,
+        project_id: &luban_api::ProjectId,
+        issue_ref: String,
+        mode: luban_api::TaskExecuteMode,
+        on_merge: luban_domain::IssueMergeAction,
+    ) -> Result<luban_api::TaskExecuteResult, String> {
+        let path = expand_user_path(&project_id.0);
+        let normalized_path = normalize_project_path(&path);
+        let Some((project_id, project_path)) = self
+            .state
+            .projects
+            .iter()
+            .find(|p| normalize_project_path(&p.path) == normalized_path)
+            .map(|p| (p.id, p.path.clone()))
+        else {
+            return Err("project not found".to_owned());
+        };
 
-                let services = self.services.clone();
-                let project_slug_for_list = scope.project_slug.clone();
-                let workspace_name_for_list = scope.workspace_name.clone();
-                let threads = tokio::task::spawn_blocking(move || {
-                    services
-                        .list_conversation_threads(project_slug_for_list, workspace_name_for_list)
-                })
-                .await
-                .ok()
-                .unwrap_or_else(|| Err("failed to join list threads task".to_owned()));
+        let services = self.services.clone();
+        let issue_project_path = project_path.clone();
+        let issue = tokio::task::spawn_blocking(move || {
+            services.issue_import(issue_project_path, issue_ref)
+        })
+        .await
+        .ok()
+        .unwrap_or_else(|| Err("failed to join issue import task".to_owned()))?;
 
-                let tabs = self
-                    .state
-                    .workspace_tabs(wid)
-                    .map(map_workspace_tabs_snapshot)
-                    .unwrap_or_default();
+        let existing = self
+            .state
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .map(|p| p.workspaces.iter().map(|w| w.id).collect::<HashSet<_>>())
+            .unwrap_or_default();
+        self.process_action_queue(Action::CreateWorkspace {
+            project_id,
+            branch_name_hint: Some(issue.branch_name_hint()),
+        })
+        .await;
+        let Some(workspace_id) = self
+            .state
+            .projects
+            .iter()
+            .find(|p| p.id == project_id)
+            .and_then(|p| {
+                p.workspaces
+                    .iter()
+                    .find(|w| !existing.contains(&w.id) && w.worktree_path != project_path)
+                    .map(|w| w.id)
+            })
+        else {
+            return Err(self
+                .state
+                .last_error
+                .clone()
+                .unwrap_or_else(|| "failed to create workdir for issue".to_owned()));
+        };
 
-                let snapshot = match threads {
-                    Ok(mut threads) => {
-                        dedup_thread_metas_in_place(&mut threads);
+        let prompt = format!("#{} {}\n{}", issue.number, issue.title.trim(), issue.url);
+        let result = self
+            .execute_task_prompt(
+                prompt,
+                mode,
+                Some(luban_api::WorkspaceId(workspace_id.as_u64())),
+                Vec::new(),
+                None,
+                None,
+                Some(issue.intent_kind()),
+                Some(issue.task_document()),
+            )
+            .await?;
 
-                        let mapped_threads = threads
-                            .iter()
-                            .map(|t| luban_api::ThreadMeta {
-                                thread_id: luban_api::WorkspaceThreadId(t.thread_id.as_u64()),
-                                remote_thread_id: t.remote_thread_id.clone(),
-                                title: t.title.clone(),
-                                created_at_unix_seconds: t.created_at_unix_seconds,
-                                updated_at_unix_seconds: t.updated_at_unix_seconds,
-                                task_status: match t.task_status {
-                                    luban_domain::TaskStatus::Backlog => {
-                                        luban_api::TaskStatus::Backlog
-                                    }
-                                    luban_domain::TaskStatus::Todo => luban_api::TaskStatus::Todo,
-                                    luban_domain::TaskStatus::Iterating => {
-                                        luban_api::TaskStatus::Iterating
-                                    }
-                                    luban_domain::TaskStatus::Validating => {
-                                        luban_api::TaskStatus::Validating
-                                    }
-                                    luban_domain::TaskStatus::Done => luban_api::TaskStatus::Done,
-                                    luban_domain::TaskStatus::Canceled => {
-                                        luban_api::TaskStatus::Canceled
-                                    }
-                                },
-                                turn_status: match t.turn_status {
-                                    luban_domain::TurnStatus::Idle => luban_api::TurnStatus::Idle,
-                                    luban_domain::TurnStatus::Running => {
-                                        luban_api::TurnStatus::Running
-                                    }
-                                    luban_domain::TurnStatus::Awaiting => {
-                                        luban_api::TurnStatus::Awaiting
-                                    }
-                                    luban_domain::TurnStatus::Paused => {
-                                        luban_api::TurnStatus::Paused
-                                    }
-                                },
-                                last_turn_result: t.last_turn_result.map(|v| match v {
-                                    luban_domain::TurnResult::Completed => {
-                                        luban_api::TurnResult::Completed
-                                    }
-                                    luban_domain::TurnResult::Failed => {
-                                        luban_api::TurnResult::Failed
-                                    }
-                                }),
-                            })
-                            .collect::<Vec<_>>();
+        self.process_action_queue(Action::TaskIssueLinked {
+            link: luban_domain::TaskIssueLink {
+                workspace_id: result.workspace_id.0,
+                thread_id: result.thread_id.0,
+                number: issue.number,
+                title: issue.title,
+                url: issue.url,
+                on_merge,
+                resolved_by_pr: None,
+            },
+        })
+        .await;
 
-                        self.workspace_threads_cache.insert(wid, threads);
+        Ok(result)
+    }
 
-                        Ok(ThreadsSnapshot {
-                            rev: self.rev,
+    async fn apply_task_bulk_idempotent(
+        &mut self,
+        idempotency_key: Option<String>,
+        items: Vec<luban_api::TaskBulkItem>,
+        operation: luban_api::TaskBulkOperation,
+    ) -> Result<luban_api::TaskBulkResult, String> {
+        let Some(key) = idempotency_key
+            .map(|key| key.trim().to_owned())
+            .filter(|key| !key.is_empty())
+        else {
+            return self.apply_task_bulk(items, operation).await;
+        };
+
+        match self.task_bulk.results.begin(key.clone()).await {
+            Begin::Owner => {}
+            Begin::Done(result) => return Ok(result),
+            // Reason: the engine applies one command at a time, so an in-flight entry
+            // belongs to an operation that never completed; waiting would stall the engine.
+            Begin::Wait(_) => {
+                return Err("a bulk operation with this idempotency key is in progress".to_owned());
+            }
+        }
+
+        let result = self.apply_task_bulk(items, operation).await;
+        self.task_bulk.results.complete(key, result.clone()).await;
+        result
+    }
+
+    async fn apply_task_bulk(
+        &mut self,
+        items: Vec<luban_api::TaskBulkItem>,
+        operation: luban_api::TaskBulkOperation,
+    ) -> Result<luban_api::TaskBulkResult, String> {
+        if items.is_empty() {
+            return Err("no tasks selected".to_owned());
+        }
+        if items.len() > TASK_BULK_MAX_ITEMS {
+            return Err(format!(
+                "at most {TASK_BULK_MAX_ITEMS} tasks can be changed at once"
+            ));
+        }
+        if let luban_api::TaskBulkOperation::SendPrompt { text } = &operation
+            && text.trim().is_empty()
+        {
+            return Err("prompt is empty".to_owned());
+        }
+
+        let mut seen = HashSet::new();
+        let items = items
+            .into_iter()
+            .filter(|item| seen.insert(*item))
+            .collect::<Vec<_>>();
+
+        let batch_id = self.task_bulk.allocate_batch_id();
+        let mut results = Vec::with_capacity(items.len());
+        let mut previous = Vec::new();
+        let mut archived = HashMap::<WorkspaceId, Result<(), String>>::new();
+        for item in items {
+            let workspace_id = WorkspaceId::from_u64(item.workspace_id.0);
+            let thread_id = WorkspaceThreadId::from_u64(item.thread_id.0);
+            let outcome = match &operation {
+                luban_api::TaskBulkOperation::SetStatus { task_status } => {
+                    // Reason: the batch is audited as one entry below, so it undoes as a whole.
+                    let audit_suppressed = std::mem::replace(&mut self.audit_suppressed, true);
+                    let outcome = self
+                        .bulk_set_task_status(
                             workspace_id,
-                            tabs,
-                            threads: mapped_threads,
+                            thread_id,
+                            map_api_task_status(*task_status),
+                        )
+                        .await;
+                    self.audit_suppressed = audit_suppressed;
+                    outcome.inspect(|from| {
+                        if let Some(from) = from {
+                            previous.push(luban_domain::AuditTaskStatus {
+                                workspace_id: workspace_id.as_u64(),
+                                thread_id: thread_id.as_u64(),
+                                task_status: *from,
+                            });
+                        }
+                    })
+                }
+                luban_api::TaskBulkOperation::SetStarred { starred } => {
+                    if workspace_scope(&self.state, workspace_id).is_none() {
+                        Err("workdir not found".to_owned())
+                    } else {
+                        self.process_action_queue(Action::TaskStarSet {
+                            workspace_id,
+                            thread_id,
+                            starred: *starred,
                         })
+                        .await;
+                        Ok(None)
                     }
-                    Err(e) => Err(anyhow::anyhow!(e)),
-                };
+                }
+                luban_api::TaskBulkOperation::ArchiveWorkdir => {
+                    let result = match archived.get(&workspace_id) {
+                        Some(result) => result.clone(),
+                        None => {
+                            let result = self.bulk_archive_workdir(workspace_id).await;
+                            archived.insert(workspace_id, result.clone());
+                            result
+                        }
+                    };
+                    result.map(|()| None)
+                }
+                luban_api::TaskBulkOperation::SendPrompt { text } => self
+                    .bulk_send_prompt(workspace_id, thread_id, text.clone())
+                    .await
+                    .map(|()| None),
+            };
 
-                let _ = reply.send(snapshot);
+            let (ok, message, previous_status) = match outcome {
+                Ok(from) => (true, None, from.map(map_domain_task_status)),
+                Err(message) => (false, Some(message), None),
+            };
+            results.push(luban_api::TaskBulkItemResult {
+                workspace_id: item.workspace_id,
+                thread_id: item.thread_id,
+                ok,
+                message,
+                previous_status,
+            });
+        }
+
+        let undoable = !previous.is_empty();
+        if let luban_api::TaskBulkOperation::SetStatus { task_status } = &operation
+            && undoable
+        {
+            self.record_audit(AuditRecord::bulk_task_status(
+                batch_id.clone(),
+                map_api_task_status(*task_status),
+                previous,
+            ))
+            .await;
+        }
+        Ok(luban_api::TaskBulkResult {
+            batch_id,
+            items: results,
+            undoable,
+        })
+    }
+
+    async fn undo_task_bulk(
+        &mut self,
+        batch_id: &str,
+    ) -> Result<luban_api::TaskBulkResult, String> {
+        let services = self.services.clone();
+        let entries = tokio::task::spawn_blocking(move || {
+            services.list_audit_entries(None, TASK_BULK_UNDO_LOOKBACK_ENTRIES)
+        })
+        .await
+        .ok()
+        .unwrap_or_else(|| Err("failed to join audit lookup task".to_owned()))?;
+        let Some((entry, tasks)) = entries.into_iter().find_map(|entry| match &entry.undo {
+            Some(AuditUndo::RestoreTaskStatuses {
+                batch_id: entry_batch_id,
+                tasks,
+            }) if entry_batch_id == batch_id && entry.undone_at_unix_ms.is_none() => {
+                let tasks = tasks.clone();
+                Some((entry, tasks))
             }
-            EngineCommand::GetConversationSnapshot {
-                workspace_id,
-                thread_id,
-                before,
-                limit,
-                reply,
-            } => {
-                let snapshot = self
-                    .get_conversation_snapshot(workspace_id, thread_id, before, limit)
+            _ => None,
+        }) else {
+            return Err("nothing to undo for this batch".to_owned());
+        };
+
+        self.audit_suppressed = true;
+        let results = self.restore_task_statuses(tasks).await;
+        self.audit_suppressed = false;
+        self.finish_audit_undo(&entry).await;
+
+        Ok(luban_api::TaskBulkResult {
+            batch_id: batch_id.to_owned(),
+            items: results,
+            undoable: false,
+        })
+    }
+
+    async fn restore_task_statuses(
+        &mut self,
+        tasks: Vec<luban_domain::AuditTaskStatus>,
+    ) -> Vec<luban_api::TaskBulkItemResult> {
+        let mut results = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let workspace_id = WorkspaceId::from_u64(task.workspace_id);
+            let thread_id = WorkspaceThreadId::from_u64(task.thread_id);
+            let _ = self
+                .ensure_thread_conversation_loaded(workspace_id, thread_id)
+                .await;
+            let from = self
+                .state
+                .workspace_thread_conversation(workspace_id, thread_id)
+                .map(|conversation| conversation.task_status);
+            self.process_action_queue(Action::TaskStatusRestored {
+                workspace_id,
+                thread_id,
+                task_status: task.task_status,
+            })
+            .await;
+            let restored = self
+                .state
+                .workspace_thread_conversation(workspace_id, thread_id)
+                .is_some_and(|conversation| conversation.task_status == task.task_status);
+            results.push(luban_api::TaskBulkItemResult {
+                workspace_id: luban_api::WorkspaceId(task.workspace_id),
+                thread_id: luban_api::WorkspaceThreadId(task.thread_id),
+                ok: restored,
+                message: (!restored).then(|| "task no longer exists".to_owned()),
+                previous_status: from.map(map_domain_task_status),
+            });
+        }
+        results
+    }
+
+    /// Loads a task's conversation into memory so reducer actions that need it can apply.
+    async fn ensure_thread_conversation_loaded(
+        &mut self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> Result<(), String> {
+        if self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .is_some()
+        {
+            return Ok(());
+        }
+        let Some(scope) = workspace_scope(&self.state, workspace_id) else {
+            return Err("workdir not found".to_owned());
+        };
+
+        let services = self.services.clone();
+        let thread_local_id = thread_id.as_u64();
+        let snapshot = tokio::task::spawn_blocking(move || {
+            let threads = services.list_conversation_threads(
+                scope.project_slug.clone(),
+                scope.workspace_name.clone(),
+            )?;
+            if !threads.iter().any(|meta| meta.thread_id == thread_id) {
+                return Err("task not found".to_owned());
+            }
+            services.load_conversation_page(
+                scope.project_slug,
+                scope.workspace_name,
+                thread_local_id,
+                None,
+                5000,
+            )
+        })
+        .await
+        .ok()
+        .unwrap_or_else(|| Err("failed to join load conversation task".to_owned()))?;
+
+        self.process_action_queue(Action::ConversationLoaded {
+            workspace_id,
+            thread_id,
+            snapshot,
+        })
+        .await;
+        Ok(())
+    }
+
+    /// Returns the status the task had before, or `None` when it already had `task_status`.
+    async fn bulk_set_task_status(
+        &mut self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        task_status: luban_domain::TaskStatus,
+    ) -> Result<Option<luban_domain::TaskStatus>, String> {
+        self.ensure_thread_conversation_loaded(workspace_id, thread_id)
+            .await?;
+        let Some(from) = self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .map(|conversation| conversation.task_status)
+        else {
+            return Err("task not found".to_owned());
+        };
+        if from == task_status {
+            return Ok(None);
+        }
+        if matches!(
+            from,
+            luban_domain::TaskStatus::Done | luban_domain::TaskStatus::Canceled
+        ) {
+            return Err("Task is archived".to_owned());
+        }
+
+        self.process_action_queue(Action::TaskStatusSet {
+            workspace_id,
+            thread_id,
+            task_status,
+        })
+        .await;
+        let updated = self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .is_some_and(|conversation| conversation.task_status == task_status);
+        if updated {
+            Ok(Some(from))
+        } else {
+            Err("failed to update task status".to_owned())
+        }
+    }
+
+    async fn bulk_archive_workdir(&mut self, workspace_id: WorkspaceId) -> Result<(), String> {
+        let Some(is_main) = self.state.projects.iter().find_map(|project| {
+            project
+                .workspaces
+                .iter()
+                .find(|workspace| workspace.id == workspace_id)
+                .map(|workspace| workspace.worktree_path == project.path)
+        }) else {
+            return Err("workdir not found".to_owned());
+        };
+        if is_main {
+            return Err("the main workdir cannot be archived".to_owned());
+        }
+
+        self.state.last_error = None;
+        self.process_action_queue(Action::ArchiveWorkspace { workspace_id })
+            .await;
+        let archived = self
+            .state
+            .workspace(workspace_id)
+            .is_none_or(|workspace| workspace.status == luban_domain::WorkspaceStatus::Archived);
+        if archived {
+            Ok(())
+        } else {
+            Err(self
+                .state
+                .last_error
+                .clone()
+                .unwrap_or_else(|| "failed to archive workdir".to_owned()))
+        }
+    }
+
+    async fn bulk_send_prompt(
+        &mut self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        text: String,
+    ) -> Result<(), String> {
+        self.ensure_thread_conversation_loaded(workspace_id, thread_id)
+            .await?;
+        let is_closed = self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .is_none_or(|conversation| {
+                matches!(
+                    conversation.task_status,
+                    luban_domain::TaskStatus::Done | luban_domain::TaskStatus::Canceled
+                )
+            });
+        if is_closed {
+            return Err("Task is archived".to_owned());
+        }
+
+        self.process_action_queue(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text,
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        })
+        .await;
+        Ok(())
+    }
+
+    fn reply_task_bulk(
+        &self,
+        request_id: String,
+        result: Result<luban_api::TaskBulkResult, String>,
+        reply: oneshot::Sender<Result<u64, String>>,
+    ) {
+        match result {
+            Ok(result) => {
+                let _ = self.events.send(WsServerMessage::Event {
+                    rev: self.rev,
+                    event: Box::new(luban_api::ServerEvent::TaskBulkApplied { request_id, result }),
+                });
+                let _ = reply.send(Ok(self.rev));
+            }
+            Err(message) => {
+                let _ = reply.send(Err(message));
+            }
+        }
+    }
+
+    async fn handle(&mut self, cmd: EngineCommand) {
+        self.action_origin = match &cmd {
+            EngineCommand::ApplyClientAction { origin, .. } => *origin,
+            _ => AuditOrigin::System,
+        };
+        match cmd {
+            EngineCommand::GetRev { reply } => {
+                let _ = reply.send(Ok(self.rev));
+            }
+            EngineCommand::GetAppSnapshot { reply } => {
+                self.refresh_pull_requests_for_all_workspaces();
+                let _ = reply.send(Ok(self.app_snapshot()));
+            }
+            EngineCommand::GetThreadsSnapshot {
+                workspace_id,
+                reply,
+            } => {
+                let wid = WorkspaceId::from_u64(workspace_id.0);
+                let Some(scope) = workspace_scope(&self.state, wid) else {
+                    let _ = reply.send(Err(anyhow::anyhow!("workspace not found")));
+                    return;
+                };
+
+                let services = self.services.clone();
+                let project_slug_for_list = scope.project_slug.clone();
+                let workspace_name_for_list = scope.workspace_name.clone();
+                let threads = tokio::task::spawn_blocking(move || {
+                    services
+                        .list_conversation_threads(project_slug_for_list, workspace_name_for_list)
+                })
+                .await
+                .ok()
+                .unwrap_or_else(|| Err("failed to join list threads task".to_owned()));
+
+                let tabs = self
+                    .state
+                    .workspace_tabs(wid)
+                    .map(map_workspace_tabs_snapshot)
+                    .unwrap_or_default();
+
+                let snapshot = match threads {
+                    Ok(mut threads) => {
+                        dedup_thread_metas_in_place(&mut threads);
+
+                        let mapped_threads = threads
+                            .iter()
+                            .map(|t| luban_api::ThreadMeta {
+                                thread_id: luban_api::WorkspaceThreadId(t.thread_id.as_u64()),
+                                remote_thread_id: t.remote_thread_id.clone(),
+                                title: t.title.clone(),
+                                created_at_unix_seconds: t.created_at_unix_seconds,
+                                updated_at_unix_seconds: t.updated_at_unix_seconds,
+                                task_status: match t.task_status {
+                                    luban_domain::TaskStatus::Backlog => {
+                                        luban_api::TaskStatus::Backlog
+                                    }
+                                    luban_domain::TaskStatus::Todo => luban_api::TaskStatus::Todo,
+                                    luban_domain::TaskStatus::Iterating => {
+                                        luban_api::TaskStatus::Iterating
+                                    }
+                                    luban_domain::TaskStatus::Validating => {
+                                        luban_api::TaskStatus::Validating
+                                    }
+                                    luban_domain::TaskStatus::Done => luban_api::TaskStatus::Done,
+                                    luban_domain::TaskStatus::Canceled => {
+                                        luban_api::TaskStatus::Canceled
+                                    }
+                                },
+                                turn_status: match t.turn_status {
+                                    luban_domain::TurnStatus::Idle => luban_api::TurnStatus::Idle,
+                                    luban_domain::TurnStatus::Running => {
+                                        luban_api::TurnStatus::Running
+                                    }
+                                    luban_domain::TurnStatus::Awaiting => {
+                                        luban_api::TurnStatus::Awaiting
+                                    }
+                                    luban_domain::TurnStatus::Paused => {
+                                        luban_api::TurnStatus::Paused
+                                    }
+                                },
+                                last_turn_result: t.last_turn_result.map(|v| match v {
+                                    luban_domain::TurnResult::Completed => {
+                                        luban_api::TurnResult::Completed
+                                    }
+                                    luban_domain::TurnResult::Failed => {
+                                        luban_api::TurnResult::Failed
+                                    }
+                                }),
+                                forked_from: t.forked_from.as_ref().map(map_fork_origin),
+                            })
+                            .collect::<Vec<_>>();
+
+                        self.workspace_threads_cache.insert(wid, threads);
+
+                        Ok(ThreadsSnapshot {
+                            rev: self.rev,
+                            workspace_id,
+                            tabs,
+                            threads: mapped_threads,
+                        })
+                    }
+                    Err(e) => Err(anyhow::anyhow!(e)),
+                };
+
+                let _ = reply.send(snapshot);
+            }
+            EngineCommand::GetConversationSnapshot {
+                workspace_id,
+                thread_id,
+                before,
+                limit,
+                reply,
+            } => {
+                let snapshot = self
+                    .get_conversation_snapshot(workspace_id, thread_id, before, limit)
                     .await;
                 let _ = reply.send(snapshot);
             }
@@ -1129,12 +2009,33 @@ impl Engine {
                             replayed_up_to: b.replayed_up_to,
                         })
                         .collect(),
+                    group_chats: self
+                        .state
+                        .telegram_group_chats()
+                        .values()
+                        .map(|g| TelegramGroupChatRoute {
+                            chat_id: g.chat_id,
+                            project_slug: g.project_slug.clone(),
+                            member_user_ids: g.members.iter().map(|m| m.user_id).collect(),
+                            topic_bindings: g
+                                .topic_bindings
+                                .iter()
+                                .map(|b| TelegramTopicBindingRoute {
+                                    message_thread_id: b.message_thread_id,
+                                    workspace_id: b.workspace_id,
+                                    thread_id: b.thread_id,
+                                    replayed_up_to: b.replayed_up_to,
+                                })
+                                .collect(),
+                        })
+                        .collect(),
                 };
                 let _ = reply.send(Ok(cfg));
             }
             EngineCommand::ConsumeTelegramPairingCode {
                 code,
                 chat_id,
+                group,
                 reply,
             } => {
                 let now = Instant::now();
@@ -1151,19 +2052,52 @@ impl Engine {
                     let _ = reply.send(Err("invalid pairing code".to_owned()));
                     return;
                 }
-                if let Some(existing) = self.state.telegram_paired_chat_id()
-                    && existing != chat_id
-                {
-                    let _ = reply.send(Err("telegram is already paired".to_owned()));
-                    return;
-                }
-
-                self.telegram_pairing = None;
-                self.process_action_queue(Action::TelegramChatPaired { chat_id })
+                match (pairing.project_slug.clone(), group) {
+                    (Some(project_slug), Some(group)) => {
+                        self.telegram_pairing = None;
+                        self.process_action_queue(Action::TelegramGroupPaired {
+                            chat_id,
+                            project_slug,
+                            title: group.title,
+                            member: luban_domain::TelegramGroupMember {
+                                user_id: group.user_id,
+                                display_name: group.display_name,
+                            },
+                        })
+                        .await;
+                        let _ = reply.send(Ok(()));
+                        return;
+                    }
+                    (Some(_), None) => {
+                        let _ =
+                            reply
+                                .send(Err("this code pairs a group chat; send it from the group"
+                                    .to_owned()));
+                        return;
+                    }
+                    (None, Some(_)) => {
+                        let _ = reply.send(Err(
+                            "this code pairs a private chat; send it to the bot directly"
+                                .to_owned(),
+                        ));
+                        return;
+                    }
+                    (None, None) => {}
+                }
+                if let Some(existing) = self.state.telegram_paired_chat_id()
+                    && existing != chat_id
+                {
+                    let _ = reply.send(Err("telegram is already paired".to_owned()));
+                    return;
+                }
+
+                self.telegram_pairing = None;
+                self.process_action_queue(Action::TelegramChatPaired { chat_id })
                     .await;
                 let _ = reply.send(Ok(()));
             }
             EngineCommand::ApplyClientAction {
+                origin: _,
                 request_id,
                 action,
                 reply,
@@ -1357,7 +2291,27 @@ impl Engine {
                 }
 
                 if matches!(action, luban_api::ClientAction::TelegramPairStart) {
-                    let res = self.telegram_pair_start(request_id.clone()).await;
+                    let res = self.telegram_pair_start(request_id.clone(), None).await;
+                    let _ = reply.send(res.map(|_| self.rev));
+                    return;
+                }
+
+                if let luban_api::ClientAction::TelegramGroupPairStart { project_id } = &action {
+                    let path = expand_user_path(&project_id.0);
+                    let normalized_path = normalize_project_path(&path);
+                    let Some(project_slug) = self
+                        .state
+                        .projects
+                        .iter()
+                        .find(|p| normalize_project_path(&p.path) == normalized_path)
+                        .map(|p| p.slug.clone())
+                    else {
+                        let _ = reply.send(Err("project not found".to_owned()));
+                        return;
+                    };
+                    let res = self
+                        .telegram_pair_start(request_id.clone(), Some(project_slug))
+                        .await;
                     let _ = reply.send(res.map(|_| self.rev));
                     return;
                 }
@@ -1369,6 +2323,7 @@ impl Engine {
                     attachments,
                     model_id,
                     thinking_effort,
+                    intent_kind,
                 } = &action
                 {
                     let prompt = prompt.clone();
@@ -1377,6 +2332,7 @@ impl Engine {
                     let attachments = attachments.clone();
                     let model_id = model_id.clone();
                     let thinking_effort = map_api_thinking_effort(*thinking_effort);
+                    let intent_kind = intent_kind.map(map_api_task_intent_kind);
 
                     match self
                         .execute_task_prompt(
@@ -1386,6 +2342,8 @@ impl Engine {
                             attachments,
                             model_id,
                             thinking_effort,
+                            intent_kind,
+                            None,
                         )
                         .await
                     {
@@ -1494,6 +2452,8 @@ impl Engine {
                                     Vec::new(),
                                     None,
                                     None,
+                                    None,
+                                    None,
                                 )
                                 .await
                             {
@@ -1522,6 +2482,62 @@ impl Engine {
                     return;
                 }
 
+                if let luban_api::ClientAction::TaskBulkApply {
+                    idempotency_key,
+                    items,
+                    operation,
+                } = &action
+                {
+                    let result = self
+                        .apply_task_bulk_idempotent(
+                            idempotency_key.clone(),
+                            items.clone(),
+                            operation.clone(),
+                        )
+                        .await;
+                    self.reply_task_bulk(request_id.clone(), result, reply);
+                    return;
+                }
+
+                if let luban_api::ClientAction::TaskBulkUndo { batch_id } = &action {
+                    let result = self.undo_task_bulk(batch_id).await;
+                    self.reply_task_bulk(request_id.clone(), result, reply);
+                    return;
+                }
+
+                if let luban_api::ClientAction::TaskImportIssue {
+                    project_id,
+                    issue,
+                    mode,
+                    on_merge,
+                } = &action
+                {
+                    match self
+                        .import_issue_as_task(
+                            project_id,
+                            issue.clone(),
+                            *mode,
+                            map_api_issue_merge_action(*on_merge),
+                        )
+                        .await
+                    {
+                        Ok(result) => {
+                            let _ = self.events.send(WsServerMessage::Event {
+                                rev: self.rev,
+                                event: Box::new(luban_api::ServerEvent::TaskExecuted {
+                                    request_id: request_id.clone(),
+                                    result,
+                                }),
+                            });
+                            let _ = reply.send(Ok(self.rev));
+                        }
+                        Err(message) => {
+                            let _ = reply.send(Err(message));
+                        }
+                    }
+                    return;
+                }
+
                 if matches!(action, luban_api::ClientAction::CodexCheck) {
                     let services = self.services.clone();
                     let events = self.events.clone();
@@ -2445,6 +3461,229 @@ impl Engine {
                 }
 
                 match &action {
+                    luban_api::ClientAction::TaskPromptTemplateChanged {
+                        intent_kind,
+                        template,
+                        project_id: Some(project_id),
+                    } => {
+                        let path = expand_user_path(&project_id.0);
+                        let Some(id) = find_project_id_by_path(&self.state, &path) else {
+                            let _ = reply.send(Err("project not found".to_owned()));
+                            return;
+                        };
+                        self.process_action_queue(Action::TaskPromptTemplateChanged {
+                            project_id: Some(id),
+                            intent_kind: map_api_task_intent_kind(*intent_kind),
+                            template: template.clone(),
+                        })
+                        .await;
+                        let _ = reply.send(Ok(self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::ProjectPromptVariablesChanged {
+                        project_id,
+                        variables,
+                    } => {
+                        let path = expand_user_path(&project_id.0);
+                        let Some(id) = find_project_id_by_path(&self.state, &path) else {
+                            let _ = reply.send(Err("project not found".to_owned()));
+                            return;
+                        };
+                        self.process_action_queue(Action::ProjectPromptVariablesChanged {
+                            project_id: id,
+                            variables: variables.clone(),
+                        })
+                        .await;
+                        let _ = reply.send(Ok(self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::DiskUsageRefresh => {
+                        self.start_disk_usage_refresh();
+                        let _ = reply.send(Ok(self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::BuildArtifactsPurge {
+                        workspace_id,
+                        globs,
+                    } => {
+                        let targets = match workspace_id {
+                            Some(id) => {
+                                let Some(workspace) =
+                                    self.state.workspace(WorkspaceId::from_u64(id.0))
+                                else {
+                                    let _ = reply.send(Err("workspace not found".to_owned()));
+                                    return;
+                                };
+                                vec![workspace.worktree_path.clone()]
+                            }
+                            // Main workdirs are the user's own checkout; only purge them on request.
+                            None => self
+                                .state
+                                .projects
+                                .iter()
+                                .flat_map(|p| p.workspaces.iter())
+                                .filter(|w| {
+                                    w.status == luban_domain::WorkspaceStatus::Active
+                                        && w.workspace_name != "main"
+                                })
+                                .map(|w| w.worktree_path.clone())
+                                .collect(),
+                        };
+                        let globs = if globs.iter().all(|g| g.trim().is_empty()) {
+                            DEFAULT_BUILD_ARTIFACT_GLOBS
+                                .iter()
+                                .map(|g| (*g).to_owned())
+                                .collect()
+                        } else {
+                            globs.clone()
+                        };
+                        self.spawn_disk_cleanup(move |services| {
+                            run_disk_cleanup(targets, |worktree_path| {
+                                services.purge_build_artifacts(worktree_path, globs.clone())
+                            })
+                        });
+                        let _ = reply.send(Ok(self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::StaleWorktreesDelete { older_than_days } => {
+                        let cutoff = std::time::SystemTime::now()
+                            .checked_sub(Duration::from_secs(
+                                u64::from(*older_than_days) * 24 * 60 * 60,
+                            ))
+                            .unwrap_or(std::time::UNIX_EPOCH);
+                        let mut archived = Vec::new();
+                        let mut keep = Vec::new();
+                        for project in &self.state.projects {
+                            keep.push(project.path.clone());
+                            for workspace in &project.workspaces {
+                                match workspace.status {
+                                    luban_domain::WorkspaceStatus::Active => {
+                                        keep.push(workspace.worktree_path.clone());
+                                    }
+                                    luban_domain::WorkspaceStatus::Archived => {
+                                        if project.is_git
+                                            && workspace
+                                                .archived_at
+                                                .is_some_and(|archived_at| archived_at <= cutoff)
+                                        {
+                                            archived.push(luban_domain::ArchivedWorktree {
+                                                project_path: project.path.clone(),
+                                                worktree_path: workspace.worktree_path.clone(),
+                                            });
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        self.spawn_disk_cleanup(move |services| {
+                            services.delete_stale_worktrees(archived, keep)
+                        });
+                        let _ = reply.send(Ok(self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::ContextBlobsGc => {
+                        self.spawn_disk_cleanup(|services| services.gc_context_blobs());
+                        let _ = reply.send(Ok(self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::ProjectNotificationsMutedChanged {
+                        project_id,
+                        muted,
+                    } => {
+                        let path = expand_user_path(&project_id.0);
+                        let Some(id) = find_project_id_by_path(&self.state, &path) else {
+                            let _ = reply.send(Err("project not found".to_owned()));
+                            return;
+                        };
+                        self.process_action_queue(Action::ProjectNotificationsMutedChanged {
+                            project_id: id,
+                            muted: *muted,
+                        })
+                        .await;
+                        let _ = reply.send(Ok(self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::TaskRewind {
+                        workspace_id,
+                        thread_id,
+                        checkpoint_id,
+                        truncate_conversation,
+                    } => {
+                        let result = self
+                            .rewind_task(
+                                WorkspaceId::from_u64(workspace_id.0),
+                                WorkspaceThreadId::from_u64(thread_id.0),
+                                checkpoint_id.clone(),
+                                *truncate_conversation,
+                            )
+                            .await;
+                        let _ = reply.send(result.map(|()| self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::TaskFork {
+                        workspace_id,
+                        thread_id,
+                        entry_id,
+                        target,
+                    } => {
+                        let result = self
+                            .fork_task(
+                                WorkspaceId::from_u64(workspace_id.0),
+                                WorkspaceThreadId::from_u64(thread_id.0),
+                                entry_id.clone(),
+                                *target,
+                            )
+                            .await;
+                        let _ = reply.send(result.map(|()| self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::TaskReviewCommentsSend {
+                        workspace_id,
+                        thread_id,
+                        note,
+                    } => {
+                        let result = self
+                            .send_review_comments(
+                                WorkspaceId::from_u64(workspace_id.0),
+                                WorkspaceThreadId::from_u64(thread_id.0),
+                                note.clone(),
+                            )
+                            .await;
+                        let _ = reply.send(result.map(|()| self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::TaskPullRequestReviewPull {
+                        workspace_id,
+                        thread_id,
+                    } => {
+                        let result = self
+                            .pull_pull_request_review_comments(
+                                WorkspaceId::from_u64(workspace_id.0),
+                                WorkspaceThreadId::from_u64(thread_id.0),
+                            )
+                            .await;
+                        let _ = reply.send(result.map(|()| self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::UndoLastAction => {
+                        let result = self.undo_last_action().await;
+                        let _ = reply.send(result.map(|()| self.rev));
+                        return;
+                    }
+                    luban_api::ClientAction::ProjectTurnLimitsChanged { project_id, limits } => {
+                        let path = expand_user_path(&project_id.0);
+                        let Some(id) = find_project_id_by_path(&self.state, &path) else {
+                            let _ = reply.send(Err("project not found".to_owned()));
+                            return;
+                        };
+                        self.process_action_queue(Action::ProjectTurnLimitsChanged {
+                            project_id: id,
+                            limits: map_api_turn_limits(*limits),
+                        })
+                        .await;
+                        let _ = reply.send(Ok(self.rev));
+                        return;
+                    }
                     luban_api::ClientAction::DeleteProject { project_id } => {
                         let path = expand_user_path(&project_id.0);
                         let Some(id) = find_project_id_by_path(&self.state, &path) else {
@@ -2466,15 +3705,33 @@ impl Engine {
                             let _ = reply.send(Err("workspace not found".to_owned()));
                             return;
                         };
+                        // With a retention window the thread is only hidden, so the deletion
+                        // can be undone until the prune tick purges it.
+                        let soft_delete = self.state.deletion_settings().soft_delete_enabled();
+                        let audit_record = AuditRecord::thread_deleted(
+                            &self.state,
+                            workspace_id,
+                            thread_id,
+                            soft_delete,
+                        );
                         let services = self.services.clone();
                         let project_slug = scope.project_slug.clone();
                         let workspace_name = scope.workspace_name.clone();
                         let delete_result = tokio::task::spawn_blocking(move || {
-                            services.delete_conversation_thread(
-                                project_slug,
-                                workspace_name,
-                                thread_id.as_u64(),
-                            )
+                            if soft_delete {
+                                services.set_conversation_thread_deleted(
+                                    project_slug,
+                                    workspace_name,
+                                    thread_id.as_u64(),
+                                    Some(now_unix_ms()),
+                                )
+                            } else {
+                                services.delete_conversation_thread(
+                                    project_slug,
+                                    workspace_name,
+                                    thread_id.as_u64(),
+                                )
+                            }
                         })
                         .await
                         .ok()
@@ -2485,6 +3742,8 @@ impl Engine {
                             return;
                         }
 
+                        self.record_audit(audit_record).await;
+
                         // Purge in-memory state for the deleted thread
                         self.process_action_queue(Action::WorkspaceThreadsPurged {
                             workspace_id,
@@ -2605,11 +3864,25 @@ impl Engine {
                                 .collect(),
                             runner,
                             amp_mode,
+                            author: None,
                         })
                         .await;
                         let _ = reply.send(Ok(self.rev));
                         return;
                     }
+                    luban_api::ClientAction::ResumeInterruptedTurn {
+                        workspace_id,
+                        thread_id,
+                    } => {
+                        let result = self
+                            .resume_interrupted_turn(
+                                WorkspaceId::from_u64(workspace_id.0),
+                                WorkspaceThreadId::from_u64(thread_id.0),
+                            )
+                            .await;
+                        let _ = reply.send(result.map(|()| self.rev));
+                        return;
+                    }
                     _ => {}
                 }
 
@@ -2642,6 +3915,7 @@ impl Engine {
 
                 let now = Instant::now();
                 let previous = self.pull_requests.get(&workspace_id);
+                let previous_ci_state = open_pull_request_ci_state(previous);
                 let (next_refresh_at, consecutive_empty) =
                     pull_request_next_refresh_at(workspace_id, now, previous, info.as_ref());
 
@@ -2672,11 +3946,91 @@ impl Engine {
                         .and_then(|entry| entry.info.as_ref())
                     && pr.state == DomainPullRequestState::Merged
                 {
-                    self.spawn_task_status_suggest_done_for_merged_pr(workspace_id, pr.number);
+                    let pr_number = pr.number;
+                    self.spawn_task_status_suggest_done_for_merged_pr(workspace_id, pr_number);
+                    self.spawn_linked_issue_resolution(workspace_id, pr_number);
+                }
+
+                let ci_state = open_pull_request_ci_state(self.pull_requests.get(&workspace_id));
+                if ci_state != previous_ci_state {
+                    match ci_state {
+                        Some((pr_number, Some(DomainPullRequestCiState::Failure))) => {
+                            self.maybe_start_ci_fix(workspace_id, pr_number);
+                        }
+                        Some((_, Some(DomainPullRequestCiState::Success))) => {
+                            self.stop_passed_ci_fixes(workspace_id).await;
+                        }
+                        _ => {}
+                    }
                 }
             }
+            EngineCommand::PullRequestLookupFailed {
+                workspace_id,
+                message,
+            } => {
+                self.pull_requests_in_flight.remove(&workspace_id);
+                tracing::warn!(
+                    workspace_id = workspace_id.as_u64(),
+                    error = %message,
+                    "pull request lookup failed"
+                );
+                let next_refresh_at = Instant::now() + PULL_REQUEST_REFRESH_INTERVAL_EMPTY_MEDIUM;
+                self.pull_requests
+                    .entry(workspace_id)
+                    .and_modify(|entry| entry.next_refresh_at = next_refresh_at)
+                    .or_insert(PullRequestCacheEntry {
+                        info: None,
+                        next_refresh_at,
+                        consecutive_empty: 0,
+                    });
+            }
+            EngineCommand::CiFailureLogFetched {
+                workspace_id,
+                pr_number,
+                log,
+            } => {
+                self.start_ci_fix_attempts(workspace_id, pr_number, log)
+                    .await;
+            }
             EngineCommand::PruneArchivedTasks => {
                 self.prune_archived_tasks().await;
+                self.purge_expired_deletions().await;
+            }
+            EngineCommand::RefreshDiskUsage => {
+                self.start_disk_usage_refresh();
+            }
+            EngineCommand::DiskUsageMeasured {
+                usage,
+                measured_at_unix_ms,
+            } => {
+                self.disk_usage_in_flight = false;
+                self.disk_usage = usage
+                    .into_iter()
+                    .map(|(workspace_id, usage)| {
+                        (
+                            workspace_id,
+                            DiskUsageEntry {
+                                usage,
+                                measured_at_unix_ms,
+                            },
+                        )
+                    })
+                    .collect();
+                self.rev = self.rev.saturating_add(1);
+                self.publish_app_snapshot();
+            }
+            EngineCommand::ResumeInterruptedTurn {
+                workspace_id,
+                thread_id,
+            } => {
+                if let Err(message) = self.resume_interrupted_turn(workspace_id, thread_id).await {
+                    tracing::warn!(
+                        workspace_id = workspace_id.as_u64(),
+                        thread_id = thread_id.as_u64(),
+                        error = %message,
+                        "failed to auto-resume interrupted turn"
+                    );
+                }
             }
             EngineCommand::WorkspaceThreadsInvalidated { workspace_id } => {
                 self.workspace_threads_cache.remove(&workspace_id);
@@ -2760,21 +4114,75 @@ impl Engine {
         });
     }
 
-    async fn get_conversation_snapshot(
-        &self,
-        workspace_id: luban_api::WorkspaceId,
-        thread_id: luban_api::WorkspaceThreadId,
-        before: Option<u64>,
-        limit: Option<u64>,
-    ) -> anyhow::Result<ConversationSnapshot> {
-        if let Ok(snapshot) = self.conversation_snapshot(workspace_id, thread_id, before, limit) {
-            return Ok(snapshot);
+    /// Closes or comments on the issues linked to a workspace's tasks once its PR merged.
+    fn spawn_linked_issue_resolution(&self, workspace_id: WorkspaceId, pr_number: u64) {
+        let links = self
+            .state
+            .task_issue_links()
+            .iter()
+            .filter(|((wid, _), link)| {
+                *wid == workspace_id
+                    && link.on_merge != luban_domain::IssueMergeAction::Leave
+                    && link.resolved_by_pr.is_none()
+            })
+            .map(|((_, thread_id), link)| (*thread_id, link.url.clone(), link.on_merge))
+            .collect::<Vec<_>>();
+        if links.is_empty() {
+            return;
         }
 
-        const DEFAULT_ENTRIES_LIMIT: usize = 2000;
-        const MAX_ENTRIES_LIMIT: usize = 5000;
+        let services = self.services.clone();
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            for (thread_id, issue_url, on_merge) in links {
+                let services = services.clone();
+                let url = issue_url.clone();
+                let resolved = tokio::task::spawn_blocking(move || {
+                    services.issue_resolve_on_merge(url, on_merge, pr_number)
+                })
+                .await
+                .ok()
+                .unwrap_or_else(|| Err("failed to join issue resolve task".to_owned()));
 
-        let limit = limit
+                match resolved {
+                    Ok(()) => {
+                        let _ = tx
+                            .send(EngineCommand::DispatchAction {
+                                action: Box::new(Action::TaskIssueResolved {
+                                    workspace_id,
+                                    thread_id,
+                                    pr_number,
+                                }),
+                            })
+                            .await;
+                    }
+                    Err(message) => {
+                        tracing::warn!(
+                            issue_url = %issue_url,
+                            message = %message,
+                            "failed to resolve linked issue after merge"
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    async fn get_conversation_snapshot(
+        &self,
+        workspace_id: luban_api::WorkspaceId,
+        thread_id: luban_api::WorkspaceThreadId,
+        before: Option<u64>,
+        limit: Option<u64>,
+    ) -> anyhow::Result<ConversationSnapshot> {
+        if let Ok(snapshot) = self.conversation_snapshot(workspace_id, thread_id, before, limit) {
+            return Ok(snapshot);
+        }
+
+        const DEFAULT_ENTRIES_LIMIT: usize = 2000;
+        const MAX_ENTRIES_LIMIT: usize = 5000;
+
+        let limit = limit
             .and_then(|v| usize::try_from(v).ok())
             .unwrap_or(DEFAULT_ENTRIES_LIMIT)
             .clamp(1, MAX_ENTRIES_LIMIT);
@@ -2906,7 +4314,676 @@ impl Engine {
             queue_paused: loaded.queue_paused,
             remote_thread_id: loaded.thread_id,
             title,
+            turn_limits: map_turn_limits(
+                self.state
+                    .task_turn_limits(wid, WorkspaceThreadId::from_u64(tid)),
+            ),
+            tokens_used: self
+                .state
+                .task_tokens_used(wid, WorkspaceThreadId::from_u64(tid)),
+            forked_from: self
+                .state
+                .workspace_thread_conversation(wid, WorkspaceThreadId::from_u64(tid))
+                .and_then(|c| c.forked_from.as_ref())
+                .map(map_fork_origin),
+            review_comments: self
+                .state
+                .task_review_comments(wid, WorkspaceThreadId::from_u64(tid))
+                .iter()
+                .map(map_review_comment)
+                .collect(),
+            pull_request_review_sync: map_pull_request_review_sync(
+                self.state
+                    .task_pull_request_review_sync(wid, WorkspaceThreadId::from_u64(tid)),
+            ),
+            ci_auto_fix: map_ci_auto_fix(
+                self.state
+                    .task_ci_auto_fix(wid, WorkspaceThreadId::from_u64(tid)),
+            ),
+        })
+    }
+
+    async fn rewind_task(
+        &mut self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        checkpoint_id: String,
+        truncate_conversation: bool,
+    ) -> Result<(), String> {
+        if !luban_domain::is_valid_turn_checkpoint_id(&checkpoint_id) {
+            return Err("invalid checkpoint id".to_owned());
+        }
+        let Some(scope) = workspace_scope(&self.state, workspace_id) else {
+            return Err("workspace not found".to_owned());
+        };
+        let Some(worktree_path) = self
+            .state
+            .workspace(workspace_id)
+            .map(|w| w.worktree_path.clone())
+        else {
+            return Err("workspace not found".to_owned());
+        };
+        if self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .is_some_and(|c| c.run_status == OperationStatus::Running)
+        {
+            return Err("cannot rewind while a turn is running".to_owned());
+        }
+
+        let services = self.services.clone();
+        let ref_name = luban_domain::turn_checkpoint_ref(workspace_id, thread_id, &checkpoint_id);
+        let thread_local_id = thread_id.as_u64();
+        let checkpoint = checkpoint_id.clone();
+        let snapshot = tokio::task::spawn_blocking(move || {
+            services.restore_turn_checkpoint(worktree_path, ref_name)?;
+            if !truncate_conversation {
+                return Ok(None);
+            }
+            services.truncate_conversation_at_checkpoint(
+                scope.project_slug.clone(),
+                scope.workspace_name.clone(),
+                thread_local_id,
+                checkpoint,
+            )?;
+            services
+                .load_conversation_page(
+                    scope.project_slug,
+                    scope.workspace_name,
+                    thread_local_id,
+                    None,
+                    5000,
+                )
+                .map(Some)
+        })
+        .await
+        .ok()
+        .unwrap_or_else(|| Err("failed to join rewind task".to_owned()))?;
+
+        self.process_action_queue(Action::TaskRewound {
+            workspace_id,
+            thread_id,
+            checkpoint_id,
+            snapshot,
+        })
+        .await;
+        Ok(())
+    }
+
+    async fn fork_task(
+        &mut self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        entry_id: String,
+        target: luban_api::TaskForkTarget,
+    ) -> Result<(), String> {
+        let entry_id = entry_id.trim().to_owned();
+        if entry_id.is_empty() {
+            return Err("entry_id is required".to_owned());
+        }
+        if self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .is_none()
+        {
+            return Err("task not found".to_owned());
+        }
+        let Some((project_id, project_path, project_slug)) = self
+            .state
+            .projects
+            .iter()
+            .find(|p| p.workspaces.iter().any(|w| w.id == workspace_id))
+            .map(|p| (p.id, p.path.clone(), p.slug.clone()))
+        else {
+            return Err("workspace not found".to_owned());
+        };
+        let Some(source) = self.state.workspace(workspace_id) else {
+            return Err("workspace not found".to_owned());
+        };
+
+        let target_workspace_id = match target {
+            luban_api::TaskForkTarget::SameWorkdir => workspace_id,
+            luban_api::TaskForkTarget::NewWorkdir => {
+                let services = self.services.clone();
+                let source_worktree_path = source.worktree_path.clone();
+                let branch_name_hint = Some(format!("{}-fork", source.workspace_name));
+                let created = tokio::task::spawn_blocking(move || {
+                    services.create_workspace_from_worktree(
+                        project_path,
+                        project_slug,
+                        source_worktree_path,
+                        branch_name_hint,
+                    )
+                })
+                .await
+                .ok()
+                .unwrap_or_else(|| Err("failed to join create workdir task".to_owned()))?;
+                let worktree_path = created.worktree_path.clone();
+                self.process_action_queue(Action::WorkspaceCreated {
+                    project_id,
+                    workspace_name: created.workspace_name,
+                    branch_name: created.branch_name,
+                    worktree_path: created.worktree_path,
+                })
+                .await;
+                self.state
+                    .projects
+                    .iter()
+                    .flat_map(|p| p.workspaces.iter())
+                    .find(|w| w.worktree_path == worktree_path)
+                    .map(|w| w.id)
+                    .ok_or_else(|| "failed to register the forked workdir".to_owned())?
+            }
+        };
+
+        self.state.last_error = None;
+        self.process_action_queue(Action::ForkWorkspaceThread {
+            workspace_id,
+            thread_id,
+            entry_id,
+            target_workspace_id,
+        })
+        .await;
+        match self.state.last_error.clone() {
+            Some(message) => Err(message),
+            None => Ok(()),
+        }
+    }
+
+    async fn send_review_comments(
+        &mut self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        note: Option<String>,
+    ) -> Result<(), String> {
+        let Some(conversation) = self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+        else {
+            return Err("task not found".to_owned());
+        };
+        let runner = conversation.agent_runner;
+        let pending = self
+            .state
+            .task_review_comments(workspace_id, thread_id)
+            .iter()
+            .filter(|c| c.is_pending())
+            .cloned()
+            .collect::<Vec<_>>();
+        if pending.is_empty() {
+            return Err("no pending review comments".to_owned());
+        }
+
+        let text = format_review_prompt(note.as_deref(), &pending, runner);
+        self.state.last_error = None;
+        self.process_action_queue(Action::SendAgentMessage {
+            workspace_id,
+            thread_id,
+            text: text.clone(),
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author: None,
+        })
+        .await;
+        // Effects of an accepted turn may report errors of their own, so check that the prompt
+        // landed, either as the latest message or in the queue behind a running turn.
+        let landed = self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .is_some_and(|c| {
+                c.pending_prompts.iter().any(|p| p.text == text)
+                    || c.entries.iter().rev().any(|entry| {
+                        matches!(
+                            entry,
+                            ConversationEntry::UserEvent {
+                                event: luban_domain::UserEvent::Message { text: sent, .. },
+                                ..
+                            } if *sent == text
+                        )
+                    })
+            });
+        if !landed {
+            return Err(self
+                .state
+                .last_error
+                .clone()
+                .unwrap_or_else(|| "failed to send review comments".to_owned()));
+        }
+        self.process_action_queue(Action::ReviewCommentsSent {
+            workspace_id,
+            thread_id,
+            comment_ids: pending.iter().map(|c| c.id).collect(),
+        })
+        .await;
+        Ok(())
+    }
+
+    async fn pull_pull_request_review_comments(
+        &mut self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+    ) -> Result<(), String> {
+        if self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .is_none()
+        {
+            return Err("task not found".to_owned());
+        }
+        let Some(workspace) = self.state.workspace(workspace_id) else {
+            return Err("workspace not found".to_owned());
+        };
+        let worktree_path = workspace.worktree_path.clone();
+        let cached = self
+            .pull_requests
+            .get(&workspace_id)
+            .and_then(|entry| entry.info.as_ref())
+            .map(|pr| pr.number);
+
+        let services = self.services.clone();
+        let (pr_number, comments) = tokio::task::spawn_blocking(move || {
+            let pr_number = match cached {
+                Some(number) => number,
+                None => services
+                    .gh_pull_request_info(worktree_path.clone())?
+                    .map(|pr| pr.number)
+                    .ok_or_else(|| "no pull request for this workdir".to_owned())?,
+            };
+            let comments = services.pull_request_review_comments(worktree_path, pr_number)?;
+            Ok::<_, String>((pr_number, comments))
+        })
+        .await
+        .map_err(|_| "failed to join review comment fetch task".to_owned())??;
+
+        self.process_action_queue(Action::PullRequestReviewCommentsFetched {
+            workspace_id,
+            thread_id,
+            pr_number,
+            comments,
+        })
+        .await;
+        Ok(())
+    }
+
+    /// Replies on the pull request once an imported comment is resolved, if the task asks for it.
+    fn spawn_review_comment_reply(
+        &self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        comment_id: u64,
+    ) {
+        if !self
+            .state
+            .task_pull_request_review_sync(workspace_id, thread_id)
+            .reply_when_addressed
+        {
+            return;
+        }
+        let Some(origin) = self
+            .state
+            .task_review_comments(workspace_id, thread_id)
+            .iter()
+            .find(|c| c.id == comment_id && c.resolved)
+            .and_then(|c| c.pull_request.as_ref())
+            .filter(|origin| !origin.replied)
+        else {
+            return;
+        };
+        let Some(workspace) = self.state.workspace(workspace_id) else {
+            return;
+        };
+
+        let services = self.services.clone();
+        let tx = self.tx.clone();
+        let worktree_path = workspace.worktree_path.clone();
+        let pr_number = origin.pr_number;
+        let forge_comment_id = origin.comment_id;
+        tokio::spawn(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                services.pull_request_review_comment_reply(
+                    worktree_path,
+                    pr_number,
+                    forge_comment_id,
+                    REVIEW_COMMENT_ADDRESSED_REPLY.to_owned(),
+                )
+            })
+            .await
+            .ok()
+            .unwrap_or_else(|| Err("failed to join review reply task".to_owned()));
+            match result {
+                Ok(()) => {
+                    let _ = tx
+                        .send(EngineCommand::DispatchAction {
+                            action: Box::new(Action::ReviewCommentReplyPosted {
+                                workspace_id,
+                                thread_id,
+                                comment_id,
+                            }),
+                        })
+                        .await;
+                }
+                Err(message) => {
+                    tracing::warn!(
+                        pr_number,
+                        comment_id = forge_comment_id,
+                        error = %message,
+                        "failed to reply to pull request review comment"
+                    );
+                }
+            }
+        });
+    }
+
+    /// Fetches the failed job logs for tasks whose CI auto-fix loop still has attempts left, and
+    /// stops the loop for tasks that ran out.
+    fn maybe_start_ci_fix(&self, workspace_id: WorkspaceId, pr_number: u64) {
+        let (with_attempts, exhausted): (Vec<_>, Vec<_>) = self
+            .state
+            .ci_auto_fix_tasks(workspace_id)
+            .into_iter()
+            .partition(|thread_id| {
+                self.state
+                    .task_ci_auto_fix(workspace_id, *thread_id)
+                    .is_some_and(|fix| fix.has_attempts_left())
+            });
+        if with_attempts.is_empty() && exhausted.is_empty() {
+            return;
+        }
+        let Some(workspace) = self.state.workspace(workspace_id) else {
+            return;
+        };
+
+        let services = self.services.clone();
+        let tx = self.tx.clone();
+        let worktree_path = workspace.worktree_path.clone();
+        tokio::spawn(async move {
+            for thread_id in exhausted {
+                let _ = tx
+                    .send(EngineCommand::DispatchAction {
+                        action: Box::new(Action::CiAutoFixStopped {
+                            workspace_id,
+                            thread_id,
+                            reason: CiAutoFixStopReason::AttemptsExhausted,
+                        }),
+                    })
+                    .await;
+            }
+            if with_attempts.is_empty() {
+                return;
+            }
+            let result = tokio::task::spawn_blocking(move || {
+                services.ci_failure_log(worktree_path, pr_number)
+            })
+            .await
+            .ok()
+            .unwrap_or_else(|| Err("failed to join CI log fetch task".to_owned()));
+            match result {
+                Ok(Some(log)) => {
+                    let _ = tx
+                        .send(EngineCommand::CiFailureLogFetched {
+                            workspace_id,
+                            pr_number,
+                            log,
+                        })
+                        .await;
+                }
+                Ok(None) => {}
+                Err(message) => {
+                    tracing::warn!(
+                        workspace_id = workspace_id.as_u64(),
+                        pr_number,
+                        error = %message,
+                        "failed to fetch CI failure log"
+                    );
+                }
+            }
+        });
+    }
+
+    /// Queues a "fix CI" turn with the failed job log attached for every task in the workdir
+    /// whose loop has attempts left and has not tried this run yet.
+    async fn start_ci_fix_attempts(
+        &mut self,
+        workspace_id: WorkspaceId,
+        pr_number: u64,
+        log: CiFailureLog,
+    ) {
+        let Some(scope) = workspace_scope(&self.state, workspace_id) else {
+            return;
+        };
+        for thread_id in self.state.ci_auto_fix_tasks(workspace_id) {
+            let Some(fix) = self
+                .state
+                .task_ci_auto_fix(workspace_id, thread_id)
+                .filter(|fix| fix.has_attempts_left() && !fix.attempted_run(log.run_id))
+            else {
+                continue;
+            };
+            let attempt = fix.attempts_used() + 1;
+            let max_attempts = fix.max_attempts;
+
+            let services = self.services.clone();
+            let project_slug = scope.project_slug.clone();
+            let workspace_name = scope.workspace_name.clone();
+            let text = log.log.clone();
+            let stored = tokio::task::spawn_blocking(move || {
+                services.store_context_text(project_slug, workspace_name, text, "log".to_owned())
+            })
+            .await
+            .ok()
+            .unwrap_or_else(|| Err("failed to join CI log store task".to_owned()));
+            let mut attachment = match stored {
+                Ok(attachment) => attachment,
+                Err(message) => {
+                    tracing::warn!(
+                        workspace_id = workspace_id.as_u64(),
+                        thread_id = thread_id.as_u64(),
+                        error = %message,
+                        "failed to store CI failure log"
+                    );
+                    continue;
+                }
+            };
+            attachment.name = CI_FAILURE_LOG_ATTACHMENT_NAME.to_owned();
+
+            self.process_action_queue(Action::CiFixAttemptStarted {
+                workspace_id,
+                thread_id,
+                pr_number,
+                run_id: log.run_id,
+                workflow: log.workflow.clone(),
+                url: log.url.clone(),
+            })
+            .await;
+            self.process_action_queue(Action::SendAgentMessage {
+                workspace_id,
+                thread_id,
+                text: format_ci_fix_prompt(
+                    pr_number,
+                    attempt,
+                    max_attempts,
+                    &log.workflow,
+                    &log.url,
+                ),
+                attachments: vec![attachment],
+                runner: None,
+                amp_mode: None,
+                author: None,
+            })
+            .await;
+        }
+    }
+
+    /// Ends the loop for tasks whose fixes got CI green; tasks that never needed an attempt stay
+    /// armed for the next failure.
+    async fn stop_passed_ci_fixes(&mut self, workspace_id: WorkspaceId) {
+        for thread_id in self.state.ci_auto_fix_tasks(workspace_id) {
+            if self
+                .state
+                .task_ci_auto_fix(workspace_id, thread_id)
+                .is_some_and(|fix| fix.attempts_used() > 0)
+            {
+                self.process_action_queue(Action::CiAutoFixStopped {
+                    workspace_id,
+                    thread_id,
+                    reason: CiAutoFixStopReason::Passed,
+                })
+                .await;
+            }
+        }
+    }
+
+    async fn undo_last_action(&mut self) -> Result<(), String> {
+        let services = self.services.clone();
+        let entry = tokio::task::spawn_blocking(move || services.latest_undoable_audit_entry())
+            .await
+            .ok()
+            .unwrap_or_else(|| Err("failed to join audit lookup task".to_owned()))?
+            .ok_or_else(|| "nothing to undo".to_owned())?;
+        let Some(undo) = entry.undo.clone() else {
+            return Err("nothing to undo".to_owned());
+        };
+
+        let settings = self.state.deletion_settings();
+        if undo.needs_retention()
+            && (!settings.soft_delete_enabled()
+                || entry.created_at_unix_ms <= settings.purge_cutoff_unix_ms(now_unix_ms()))
+        {
+            return Err(format!(
+                "cannot undo \"{}\": the retention window has passed",
+                entry.summary
+            ));
+        }
+
+        self.audit_suppressed = true;
+        let result = self.apply_audit_undo(undo).await;
+        self.audit_suppressed = false;
+        result?;
+
+        self.finish_audit_undo(&entry).await;
+        Ok(())
+    }
+
+    /// Marks `entry` as undone and records the undo itself.
+    async fn finish_audit_undo(&mut self, entry: &AuditEntry) {
+        let services = self.services.clone();
+        let entry_id = entry.id;
+        let marked = tokio::task::spawn_blocking(move || {
+            services.mark_audit_entry_undone(entry_id, now_unix_ms())
         })
+        .await
+        .ok()
+        .unwrap_or_else(|| Err("failed to join audit update task".to_owned()));
+        if let Err(message) = marked {
+            tracing::warn!(error = %message, "failed to mark audit entry as undone");
+        }
+        self.record_audit(AuditRecord::undone(entry)).await;
+    }
+
+    async fn apply_audit_undo(&mut self, undo: AuditUndo) -> Result<(), String> {
+        match undo {
+            AuditUndo::RestoreProject { project_id } => {
+                let Some(deleted) = self
+                    .state
+                    .deleted_projects()
+                    .iter()
+                    .find(|p| p.id == project_id)
+                else {
+                    return Err("the project is no longer restorable".to_owned());
+                };
+                let project_id = deleted.project_id();
+                self.process_action_queue(Action::ProjectRestored { project_id })
+                    .await;
+                if self.state.project(project_id).is_none() {
+                    return Err(self
+                        .state
+                        .last_error
+                        .clone()
+                        .unwrap_or_else(|| "failed to restore project".to_owned()));
+                }
+                Ok(())
+            }
+            AuditUndo::RestoreThread {
+                workspace_id,
+                thread_id,
+            } => {
+                let workspace_id = WorkspaceId::from_u64(workspace_id);
+                let Some(scope) = workspace_scope(&self.state, workspace_id) else {
+                    return Err("workspace not found".to_owned());
+                };
+                let services = self.services.clone();
+                let threads = tokio::task::spawn_blocking(move || {
+                    services.set_conversation_thread_deleted(
+                        scope.project_slug.clone(),
+                        scope.workspace_name.clone(),
+                        thread_id,
+                        None,
+                    )?;
+                    services.list_conversation_threads(scope.project_slug, scope.workspace_name)
+                })
+                .await
+                .ok()
+                .unwrap_or_else(|| Err("failed to join restore thread task".to_owned()))?;
+                self.process_action_queue(Action::WorkspaceThreadsLoaded {
+                    workspace_id,
+                    threads,
+                })
+                .await;
+                Ok(())
+            }
+            AuditUndo::RestoreTaskStatus {
+                workspace_id,
+                thread_id,
+                task_status,
+            } => {
+                let workspace_id = WorkspaceId::from_u64(workspace_id);
+                let thread_id = WorkspaceThreadId::from_u64(thread_id);
+                self.ensure_thread_conversation_loaded(workspace_id, thread_id)
+                    .await?;
+                self.process_action_queue(Action::TaskStatusRestored {
+                    workspace_id,
+                    thread_id,
+                    task_status,
+                })
+                .await;
+                Ok(())
+            }
+            AuditUndo::SetTaskStarred {
+                workspace_id,
+                thread_id,
+                starred,
+            } => {
+                self.process_action_queue(Action::TaskStarSet {
+                    workspace_id: WorkspaceId::from_u64(workspace_id),
+                    thread_id: WorkspaceThreadId::from_u64(thread_id),
+                    starred,
+                })
+                .await;
+                Ok(())
+            }
+            AuditUndo::RestoreTaskStatuses { tasks, .. } => {
+                let results = self.restore_task_statuses(tasks).await;
+                if results.iter().any(|result| result.ok) {
+                    Ok(())
+                } else {
+                    Err("none of the tasks could be restored".to_owned())
+                }
+            }
+        }
+    }
+
+    async fn record_audit(&mut self, record: AuditRecord) {
+        let services = self.services.clone();
+        let origin = self.action_origin;
+        let result =
+            tokio::task::spawn_blocking(move || services.append_audit_entry(origin, record))
+                .await
+                .ok()
+                .unwrap_or_else(|| Err("failed to join audit append task".to_owned()));
+        if let Err(message) = result {
+            tracing::warn!(error = %message, "failed to append audit entry");
+        }
     }
 
     async fn process_action_queue(&mut self, initial: Action) {
@@ -2921,6 +4998,10 @@ impl Engine {
                 Action::TerminalCommandStarted { .. }
                     | Action::TerminalCommandFinished { .. }
                     | Action::TaskStatusSuggestionCreated { .. }
+                    | Action::TaskRewound { .. }
+                    | Action::TurnChangesRecorded { .. }
+                    | Action::CiFixAttemptStarted { .. }
+                    | Action::CiAutoFixStopped { .. }
             );
             let should_sync_workspace_watchers = should_sync_branch_watchers(&action);
             let mut conversation_keys = Vec::<(WorkspaceId, WorkspaceThreadId)>::new();
@@ -2929,10 +5010,55 @@ impl Engine {
                 conversation_keys.push(key);
             }
             let queue_state_key = queue_state_key_for_action(&action);
+            let system_entries_from = if matches!(
+                &action,
+                Action::AgentEventReceived { .. }
+                    | Action::AgentTurnTimedOut { .. }
+                    | Action::PullRequestReviewCommentsFetched { .. }
+            ) {
+                action_conversation_key
+                    .and_then(|(wid, tid)| self.state.workspace_thread_conversation(wid, tid))
+                    .map(|conversation| conversation.entries_total)
+            } else {
+                None
+            };
+            let review_reply = match &action {
+                Action::ResolveReviewComment {
+                    workspace_id,
+                    thread_id,
+                    comment_id,
+                    resolved: true,
+                } => Some((*workspace_id, *thread_id, *comment_id)),
+                _ => None,
+            };
+            let ci_fix_enabled_workspace = match &action {
+                Action::CiAutoFixChanged {
+                    workspace_id,
+                    enabled: true,
+                    ..
+                } => Some(*workspace_id),
+                _ => None,
+            };
             let threads_event = threads_event_for_action(&action);
             let task_summaries_workspace_id = task_summaries_workspace_id_for_action(&action);
+            let audit_record = if self.audit_suppressed {
+                None
+            } else {
+                luban_domain::audit_record_for_action(&self.state, &action)
+            };
 
+            // Reason: Reducers report rejected actions through `last_error`; clear it while
+            // applying so only changes that actually landed reach the audit log.
+            let previous_error = audit_record
+                .as_ref()
+                .and_then(|_| self.state.last_error.take());
             let new_effects = self.state.apply(action);
+            if let Some(record) = audit_record
+                && self.state.last_error.is_none()
+            {
+                self.state.last_error = previous_error;
+                self.record_audit(record).await;
+            }
             conversation_keys.extend(conversation_keys_for_effects(&new_effects));
             if should_sync_workspace_watchers {
                 self.sync_branch_watchers();
@@ -2965,6 +5091,20 @@ impl Engine {
             {
                 self.persist_latest_conversation_entry(wid, tid).await;
             }
+            if let Some(from) = system_entries_from
+                && let Some((wid, tid)) = action_conversation_key
+            {
+                self.persist_pushed_system_entries(wid, tid, from).await;
+            }
+            if let Some((wid, tid, comment_id)) = review_reply {
+                self.spawn_review_comment_reply(wid, tid, comment_id);
+            }
+            if let Some(wid) = ci_fix_enabled_workspace
+                && let Some((pr_number, Some(DomainPullRequestCiState::Failure))) =
+                    open_pull_request_ci_state(self.pull_requests.get(&wid))
+            {
+                self.maybe_start_ci_fix(wid, pr_number);
+            }
 
             effects.extend(new_effects);
 
@@ -2979,6 +5119,66 @@ impl Engine {
         }
     }
 
+    /// Limit events are pushed by the reducer alongside streamed agent events, which the
+    /// backend persists on its own, and a review import can push several events at once, so
+    /// these are appended separately.
+    async fn persist_pushed_system_entries(
+        &self,
+        workspace_id: WorkspaceId,
+        thread_id: WorkspaceThreadId,
+        entries_total_before: u64,
+    ) {
+        let Some(scope) = workspace_scope(&self.state, workspace_id) else {
+            return;
+        };
+        let Some(conversation) = self
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+        else {
+            return;
+        };
+        let skip = usize::try_from(entries_total_before.saturating_sub(conversation.entries_start))
+            .unwrap_or(usize::MAX);
+        let entries = conversation
+            .entries
+            .iter()
+            .skip(skip)
+            .filter(|entry| {
+                matches!(
+                    entry,
+                    ConversationEntry::SystemEvent {
+                        event: luban_domain::ConversationSystemEvent::TurnLimitReached { .. }
+                            | luban_domain::ConversationSystemEvent::PullRequestReviewComment { .. },
+                        ..
+                    }
+                )
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return;
+        }
+
+        let services = self.services.clone();
+        let project_slug = scope.project_slug;
+        let workspace_name = scope.workspace_name;
+        let thread_local_id = thread_id.as_u64();
+        let result = tokio::task::spawn_blocking(move || {
+            services.append_conversation_entries(
+                project_slug,
+                workspace_name,
+                thread_local_id,
+                entries,
+            )
+        })
+        .await
+        .ok()
+        .unwrap_or_else(|| Err("failed to join conversation persistence task".to_owned()));
+        if let Err(message) = result {
+            tracing::error!(message = %message, "failed to persist system entries");
+        }
+    }
+
     async fn persist_latest_conversation_entry(
         &self,
         workspace_id: WorkspaceId,
@@ -3106,6 +5306,79 @@ impl Engine {
         }
     }
 
+    fn start_disk_usage_refresh(&mut self) {
+        if self.disk_usage_in_flight {
+            return;
+        }
+        let targets = self
+            .state
+            .projects
+            .iter()
+            .flat_map(|project| {
+                project.workspaces.iter().filter_map(|workspace| {
+                    if workspace.status != luban_domain::WorkspaceStatus::Active {
+                        return None;
+                    }
+                    Some((
+                        workspace.id,
+                        project.slug.clone(),
+                        workspace.workspace_name.clone(),
+                        workspace.worktree_path.clone(),
+                    ))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        self.disk_usage_in_flight = true;
+        let services = self.services.clone();
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let measured_at_unix_ms = now_unix_ms();
+            let usage = targets
+                .into_iter()
+                .filter_map(
+                    |(workspace_id, project_slug, workspace_name, worktree_path)| {
+                        services
+                            .workspace_disk_usage(project_slug, workspace_name, worktree_path)
+                            .ok()
+                            .map(|usage| (workspace_id, usage))
+                    },
+                )
+                .collect();
+            let _ = tx.blocking_send(EngineCommand::DiskUsageMeasured {
+                usage,
+                measured_at_unix_ms,
+            });
+        });
+    }
+
+    /// Runs a cleanup job off the engine loop, reports the outcome as a toast and re-measures.
+    fn spawn_disk_cleanup<F>(&self, job: F)
+    where
+        F: FnOnce(Arc<dyn ProjectWorkspaceService>) -> Result<DiskCleanupReport, String>
+            + Send
+            + 'static,
+    {
+        let services = self.services.clone();
+        let events = self.events.clone();
+        let tx = self.tx.clone();
+        let rev = self.rev;
+        tokio::spawn(async move {
+            let result = tokio::task::spawn_blocking(move || job(services))
+                .await
+                .unwrap_or_else(|_| Err("failed to join disk cleanup task".to_owned()));
+            let message = match result {
+                Ok(report) => disk_cleanup_message(report),
+                Err(message) => format!("Disk cleanup failed: {message}"),
+            };
+            let _ = events.send(WsServerMessage::Event {
+                rev,
+                event: Box::new(luban_api::ServerEvent::Toast { message }),
+            });
+            let _ = tx.send(EngineCommand::RefreshDiskUsage).await;
+        });
+    }
+
     fn refresh_pull_requests_for_all_workspaces(&mut self) {
         let now = Instant::now();
         let workspace_ids = self
@@ -3173,10 +5446,51 @@ impl Engine {
         let services = self.services.clone();
         let tx = self.tx.clone();
         let worktree_path = workspace.worktree_path.clone();
+        let auto_pull_tasks = self.state.pull_request_review_auto_pull_tasks(workspace_id);
 
         std::thread::spawn(move || {
-            let info = services.gh_pull_request_info(worktree_path).ok().flatten();
+            let info = match services.gh_pull_request_info(worktree_path.clone()) {
+                Ok(info) => info,
+                Err(message) => {
+                    let _ = tx.blocking_send(EngineCommand::PullRequestLookupFailed {
+                        workspace_id,
+                        message,
+                    });
+                    return;
+                }
+            };
+            let open_pr_number = info
+                .as_ref()
+                .filter(|pr| pr.state == DomainPullRequestState::Open)
+                .map(|pr| pr.number);
             let _ = tx.blocking_send(EngineCommand::PullRequestInfoUpdated { workspace_id, info });
+
+            let Some(pr_number) = open_pr_number.filter(|_| !auto_pull_tasks.is_empty()) else {
+                return;
+            };
+            match services.pull_request_review_comments(worktree_path, pr_number) {
+                Ok(comments) if !comments.is_empty() => {
+                    for thread_id in auto_pull_tasks {
+                        let _ = tx.blocking_send(EngineCommand::DispatchAction {
+                            action: Box::new(Action::PullRequestReviewCommentsFetched {
+                                workspace_id,
+                                thread_id,
+                                pr_number,
+                                comments: comments.clone(),
+                            }),
+                        });
+                    }
+                }
+                Ok(_) => {}
+                Err(message) => {
+                    tracing::warn!(
+                        workspace_id = workspace_id.as_u64(),
+                        pr_number,
+                        error = %message,
+                        "failed to pull pull request review comments"
+                    );
+                }
+            }
         });
     }
 
@@ -3196,6 +5510,36 @@ impl Engine {
                 };
                 Ok(VecDeque::from([action]))
             }
+            Effect::LoadModelCatalog => {
+                // Reason: Discovery shells out to runner CLIs, so it must not hold up the engine.
+                let services = self.services.clone();
+                let tx = self.tx.clone();
+                tokio::spawn(async move {
+                    let loaded = tokio::task::spawn_blocking(move || services.model_catalog_load())
+                        .await
+                        .ok()
+                        .unwrap_or_else(|| Err("failed to join model catalog task".to_owned()));
+                    let catalog = match loaded {
+                        Ok(loaded) => {
+                            for warning in &loaded.warnings {
+                                tracing::warn!(warning = %warning, "model catalog source skipped");
+                            }
+                            loaded.catalog
+                        }
+                        Err(message) => {
+                            tracing::warn!(error = %message, "failed to load model catalog");
+                            // Reason: Still settle persisted settings and load Codex defaults.
+                            luban_domain::ModelCatalog::builtin()
+                        }
+                    };
+                    let _ = tx
+                        .send(EngineCommand::DispatchAction {
+                            action: Box::new(Action::ModelCatalogLoaded { catalog }),
+                        })
+                        .await;
+                });
+                Ok(VecDeque::new())
+            }
             Effect::SaveAppState => {
                 let services = self.services.clone();
                 let snapshot = self.state.to_persisted();
@@ -3362,6 +5706,104 @@ impl Engine {
                 }
                 Ok(VecDeque::new())
             }
+            Effect::LoadProjectPromptSettings => {
+                let projects = self
+                    .state
+                    .projects
+                    .iter()
+                    .map(|p| (p.id, p.slug.clone()))
+                    .collect::<Vec<_>>();
+                let services = self.services.clone();
+                let loaded = tokio::task::spawn_blocking(move || {
+                    projects
+                        .into_iter()
+                        .filter_map(|(project_id, slug)| {
+                            match services.project_prompt_settings_load(slug.clone()) {
+                                Ok(settings) if settings.is_empty() => None,
+                                Ok(settings) => Some(Action::ProjectPromptSettingsLoaded {
+                                    project_id,
+                                    settings,
+                                }),
+                                Err(message) => {
+                                    tracing::warn!(
+                                        project_slug = %slug,
+                                        message = %message,
+                                        "failed to load project prompt settings"
+                                    );
+                                    None
+                                }
+                            }
+                        })
+                        .collect::<VecDeque<_>>()
+                })
+                .await
+                .unwrap_or_default();
+                Ok(loaded)
+            }
+            Effect::StoreProjectTaskPromptTemplate {
+                project_id,
+                intent_kind,
+                template,
+            } => {
+                let Some(project_slug) = self.project_slug(project_id) else {
+                    return Ok(VecDeque::new());
+                };
+                let services = self.services.clone();
+                let saved = tokio::task::spawn_blocking(move || {
+                    services.project_task_prompt_template_store(project_slug, intent_kind, template)
+                })
+                .await
+                .ok()
+                .unwrap_or_else(|| {
+                    Err("failed to join project prompt template store task".to_owned())
+                });
+                if let Err(message) = saved {
+                    tracing::warn!(message = %message, "failed to store project prompt template");
+                }
+                Ok(VecDeque::new())
+            }
+            Effect::DeleteProjectTaskPromptTemplate {
+                project_id,
+                intent_kind,
+            } => {
+                let Some(project_slug) = self.project_slug(project_id) else {
+                    return Ok(VecDeque::new());
+                };
+                let services = self.services.clone();
+                let deleted = tokio::task::spawn_blocking(move || {
+                    services.project_task_prompt_template_delete(project_slug, intent_kind)
+                })
+                .await
+                .ok()
+                .unwrap_or_else(|| {
+                    Err("failed to join project prompt template delete task".to_owned())
+                });
+                if let Err(message) = deleted {
+                    tracing::warn!(message = %message, "failed to delete project prompt template");
+                }
+                Ok(VecDeque::new())
+            }
+            Effect::StoreProjectPromptVariables {
+                project_id,
+                variables,
+            } => {
+                let Some(project_slug) = self.project_slug(project_id) else {
+                    return Ok(VecDeque::new());
+                };
+                let services = self.services.clone();
+                let saved = tokio::task::spawn_blocking(move || {
+                    services.project_prompt_variables_store(project_slug, variables)
+                })
+                .await
+                .ok()
+                .unwrap_or_else(|| {
+                    Err("failed to join project prompt variables store task".to_owned())
+                });
+                if let Err(message) = saved {
+                    tracing::warn!(message = %message, "failed to store project prompt variables");
+                }
+                Ok(VecDeque::new())
+            }
             Effect::CreateWorkspace {
                 project_id,
                 branch_name_hint,
@@ -3744,6 +6186,63 @@ impl Engine {
                 };
                 Ok(VecDeque::from([action]))
             }
+            Effect::ForkConversation {
+                workspace_id,
+                thread_id,
+                origin,
+            } => {
+                let (Some(scope), Some(source_scope)) = (
+                    workspace_scope(&self.state, workspace_id),
+                    workspace_scope(&self.state, origin.workspace_id),
+                ) else {
+                    return Ok(VecDeque::from([Action::WorkspaceThreadForkFailed {
+                        workspace_id,
+                        thread_id,
+                        message: "workspace not found".to_owned(),
+                    }]));
+                };
+                let services = self.services.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let source_workspace_id = origin.workspace_id;
+                    let source_thread_id = origin.thread_id;
+                    services.fork_conversation_thread(
+                        scope.project_slug,
+                        source_scope.workspace_name,
+                        scope.workspace_name,
+                        thread_id.as_u64(),
+                        origin,
+                    )?;
+                    // The fork keeps working from the documents as they are now; a stale copy
+                    // only costs the agent a re-read, so this is best effort.
+                    if let Err(err) = copy_task_documents(
+                        (source_workspace_id, source_thread_id),
+                        (workspace_id, thread_id),
+                    ) {
+                        tracing::warn!(
+                            workspace_id = workspace_id.as_u64(),
+                            thread_id = thread_id.as_u64(),
+                            error = %err,
+                            "failed to copy task documents into fork"
+                        );
+                    }
+                    Ok(())
+                })
+                .await
+                .ok()
+                .unwrap_or_else(|| Err("failed to join fork conversation task".to_owned()));
+                let action = match result {
+                    Ok(()) => Action::WorkspaceThreadForked {
+                        workspace_id,
+                        thread_id,
+                    },
+                    Err(message) => Action::WorkspaceThreadForkFailed {
+                        workspace_id,
+                        thread_id,
+                        message,
+                    },
+                };
+                Ok(VecDeque::from([action]))
+            }
             Effect::EnsureConversation {
                 workspace_id,
                 thread_id,
@@ -3859,10 +6358,23 @@ impl Engine {
                     .map(|w| w.worktree_path.clone())
                     .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-                let remote_thread_id = self
+                let conversation = self
                     .state
-                    .workspace_thread_conversation(workspace_id, thread_id)
-                    .and_then(|c| c.thread_id.clone());
+                    .workspace_thread_conversation(workspace_id, thread_id);
+                let remote_thread_id = conversation.and_then(|c| c.thread_id.clone());
+                // A fork's first turn, or the first turn after a truncating rewind, opens a new
+                // runner session; carry over the conversation it has to build on.
+                let seeded = conversation
+                    .filter(|_| remote_thread_id.is_none())
+                    .and_then(|c| {
+                        if let Some(summary) = luban_domain::rewind_seed_summary(&c.entries) {
+                            return Some(luban_domain::seed_rewound_prompt(&summary, &text));
+                        }
+                        let origin = c.forked_from.as_ref()?;
+                        luban_domain::fork_seed_summary(&c.entries, &origin.entry_id)
+                            .map(|summary| luban_domain::seed_forked_prompt(&summary, &text))
+                    });
+                let text = seeded.unwrap_or(text);
 
                 let request = luban_domain::RunAgentTurnRequest {
                     project_slug: scope.project_slug,
@@ -3876,6 +6388,8 @@ impl Engine {
                     amp_mode: run_config.amp_mode.clone(),
                     model: Some(run_config.model_id.clone()),
                     model_reasoning_effort: Some(run_config.thinking_effort.as_str().to_owned()),
+                    attachment_text_limits: self.state.attachment_text_limits(),
+                    checkpoint: None,
                 };
 
                 let cancel = Arc::new(AtomicBool::new(false));
@@ -3887,6 +6401,32 @@ impl Engine {
                     },
                 );
 
+                if let Some(timeout_secs) = self
+                    .state
+                    .effective_turn_limits(workspace_id, thread_id)
+                    .turn_timeout_secs
+                {
+                    let tx = self.tx.clone();
+                    let cancel = cancel.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(Duration::from_secs(timeout_secs)).await;
+                        if cancel.load(Ordering::SeqCst) {
+                            return;
+                        }
+                        // The reducer ignores this once the run has already finished.
+                        let _ = tx
+                            .send(EngineCommand::DispatchAction {
+                                action: Box::new(Action::AgentTurnTimedOut {
+                                    workspace_id,
+                                    thread_id,
+                                    run_id,
+                                    timeout_secs,
+                                }),
+                            })
+                            .await;
+                    });
+                }
+
                 if use_fake_agent {
                     let tx = self.tx.clone();
                     std::thread::spawn(move || {
@@ -4312,9 +6852,47 @@ impl Engine {
                     }]));
                 }
 
+                let take_checkpoint = self
+                    .state
+                    .projects
+                    .iter()
+                    .find(|p| p.workspaces.iter().any(|w| w.id == workspace_id))
+                    .is_some_and(|p| p.is_git);
                 let services = self.services.clone();
                 let tx = self.tx.clone();
                 std::thread::spawn(move || {
+                    let mut request = request;
+                    if take_checkpoint {
+                        let checkpoint_id =
+                            luban_domain::new_turn_checkpoint_id(run_id, started_at_unix_ms);
+                        let ref_name = luban_domain::turn_checkpoint_ref(
+                            workspace_id,
+                            thread_id,
+                            &checkpoint_id,
+                        );
+                        match services
+                            .create_turn_checkpoint(request.worktree_path.clone(), ref_name)
+                        {
+                            Ok(commit) => {
+                                let _ = tx.blocking_send(EngineCommand::DispatchAction {
+                                    action: Box::new(Action::TurnCheckpointCreated {
+                                        workspace_id,
+                                        thread_id,
+                                        checkpoint_id: checkpoint_id.clone(),
+                                        commit: commit.clone(),
+                                    }),
+                                });
+                                request.checkpoint = Some(luban_domain::TurnCheckpoint {
+                                    checkpoint_id,
+                                    commit,
+                                });
+                            }
+                            Err(message) => {
+                                tracing::warn!(message = %message, "failed to create turn checkpoint");
+                            }
+                        }
+                    }
+
                     let on_event: Arc<dyn Fn(luban_domain::AgentThreadEvent) + Send + Sync> = {
                         let tx = tx.clone();
                         Arc::new(move |event| {
@@ -4329,6 +6907,8 @@ impl Engine {
                         })
                     };
 
+                    let worktree_path = request.worktree_path.clone();
+                    let checkpoint = request.checkpoint.clone();
                     let result =
                         services.run_agent_turn_streamed(request, cancel.clone(), on_event);
                     if let Err(message) = result
@@ -4344,6 +6924,25 @@ impl Engine {
                         });
                     }
 
+                    if let Some(checkpoint) = checkpoint {
+                        match record_turn_changes(
+                            services.as_ref(),
+                            &worktree_path,
+                            workspace_id,
+                            thread_id,
+                            checkpoint,
+                        ) {
+                            Ok(action) => {
+                                let _ = tx.blocking_send(EngineCommand::DispatchAction {
+                                    action: Box::new(action),
+                                });
+                            }
+                            Err(message) => {
+                                tracing::warn!(message = %message, "failed to record turn changes");
+                            }
+                        }
+                    }
+
                     if cancel.load(Ordering::SeqCst) {
                         return;
                     }
@@ -4586,7 +7185,18 @@ impl Engine {
                     }
 
                     let result: Result<(), String> = (|| {
-                        services.archive_workspace(project_path, worktree_path, branch_name)?;
+                        services.archive_workspace(
+                            project_path.clone(),
+                            worktree_path,
+                            branch_name,
+                        )?;
+                        // Checkpoints only make sense while the worktree exists.
+                        if let Err(message) = services.delete_turn_checkpoints(
+                            project_path,
+                            luban_domain::workspace_checkpoint_ref_prefix(workspace_id),
+                        ) {
+                            tracing::warn!(message = %message, "failed to delete turn checkpoints");
+                        }
                         if !should_emit_task_archived_events {
                             return Ok(());
                         }
@@ -4768,6 +7378,7 @@ impl Engine {
                     luban_domain::TurnResult::Completed => luban_api::TurnResult::Completed,
                     luban_domain::TurnResult::Failed => luban_api::TurnResult::Failed,
                 }),
+                forked_from: t.forked_from.as_ref().map(map_fork_origin),
             })
             .collect::<Vec<_>>();
 
@@ -4933,8 +7544,20 @@ impl Engine {
                                     .get(&w.id)
                                     .and_then(|entry| entry.info)
                                     .map(map_pull_request_info),
+                                disk_usage: self.disk_usage.get(&w.id).map(|entry| {
+                                    luban_api::DiskUsageSnapshot {
+                                        worktree_bytes: entry.usage.worktree_bytes,
+                                        context_blobs_bytes: entry.usage.context_blobs_bytes,
+                                        measured_at_unix_ms: entry.measured_at_unix_ms,
+                                    }
+                                }),
                             })
                             .collect(),
+                        notifications_muted: self
+                            .state
+                            .notification_settings()
+                            .is_project_muted(&p.slug),
+                        turn_limits: map_turn_limits(self.state.project_turn_limits(p.id)),
                     }
                 })
                 .collect(),
@@ -4964,20 +7587,36 @@ impl Engine {
                     .iter()
                     .map(|(k, v)| (k.as_str().to_owned(), v.clone()))
                     .collect(),
-                default_thinking_effort: Some(match self.state.agent_default_thinking_effort() {
-                    ThinkingEffort::Minimal => luban_api::ThinkingEffort::Minimal,
-                    ThinkingEffort::Low => luban_api::ThinkingEffort::Low,
-                    ThinkingEffort::Medium => luban_api::ThinkingEffort::Medium,
-                    ThinkingEffort::High => luban_api::ThinkingEffort::High,
-                    ThinkingEffort::XHigh => luban_api::ThinkingEffort::XHigh,
-                }),
-                default_runner: Some(match self.state.agent_default_runner() {
-                    luban_domain::AgentRunnerKind::Codex => luban_api::AgentRunnerKind::Codex,
-                    luban_domain::AgentRunnerKind::Amp => luban_api::AgentRunnerKind::Amp,
-                    luban_domain::AgentRunnerKind::Claude => luban_api::AgentRunnerKind::Claude,
-                    luban_domain::AgentRunnerKind::Droid => luban_api::AgentRunnerKind::Droid,
-                }),
+                default_thinking_effort: Some(map_thinking_effort_to_api(
+                    self.state.agent_default_thinking_effort(),
+                )),
+                default_runner: Some(map_agent_runner_kind_to_api(
+                    self.state.agent_default_runner(),
+                )),
                 amp_mode: Some(self.state.agent_amp_mode().to_owned()),
+                models: [
+                    luban_domain::AgentRunnerKind::Codex,
+                    luban_domain::AgentRunnerKind::Droid,
+                ]
+                .into_iter()
+                .flat_map(|runner| {
+                    self.state
+                        .model_catalog
+                        .models_for_runner(runner)
+                        .iter()
+                        .map(move |model| luban_api::AgentModelSnapshot {
+                            runner: map_agent_runner_kind_to_api(runner),
+                            id: model.id.clone(),
+                            label: model.label.clone(),
+                            supported_thinking_efforts: model
+                                .supported_thinking_efforts
+                                .iter()
+                                .copied()
+                                .map(map_thinking_effort_to_api)
+                                .collect(),
+                        })
+                })
+                .collect(),
             },
             task: luban_api::TaskSettingsSnapshot {
                 prompt_templates: luban_domain::TaskIntentKind::ALL
@@ -5021,6 +7660,40 @@ impl Engine {
                         template: luban_domain::default_system_prompt_template(kind),
                     })
                     .collect(),
+                prompt_template_variables: luban_domain::TASK_PROMPT_VARIABLES
+                    .iter()
+                    .map(|name| (*name).to_owned())
+                    .collect(),
+                system_prompt_template_variables: luban_domain::SYSTEM_PROMPT_VARIABLES
+                    .iter()
+                    .map(|name| (*name).to_owned())
+                    .collect(),
+                project_prompt_settings: self
+                    .state
+                    .projects
+                    .iter()
+                    .filter_map(|project| {
+                        let settings = self.state.project_prompt_settings.get(&project.id)?;
+                        Some(luban_api::ProjectPromptSettingsSnapshot {
+                            project_id: luban_api::ProjectId(
+                                project.path.to_string_lossy().to_string(),
+                            ),
+                            prompt_templates: luban_domain::TaskIntentKind::ALL
+                                .iter()
+                                .copied()
+                                .filter_map(|kind| {
+                                    settings.task_prompt_templates.get(&kind).map(|template| {
+                                        luban_api::TaskPromptTemplateSnapshot {
+                                            intent_kind: map_task_intent_kind(kind),
+                                            template: template.clone(),
+                                        }
+                                    })
+                                })
+                                .collect(),
+                            variables: settings.variables.clone(),
+                        })
+                    })
+                    .collect(),
             },
             ui: {
                 let active_workspace_id = match self.state.main_pane {
@@ -5052,8 +7725,73 @@ impl Engine {
                     paired_chat_id: self.state.telegram_paired_chat_id(),
                     config_rev: self.state.telegram_config_rev(),
                     last_error: self.state.telegram_last_error().map(ToOwned::to_owned),
+                    group_chats: {
+                        let mut groups = self
+                            .state
+                            .telegram_group_chats()
+                            .values()
+                            .map(|g| luban_api::TelegramGroupChatSnapshot {
+                                chat_id: g.chat_id,
+                                project_slug: g.project_slug.clone(),
+                                title: g.title.clone(),
+                                members: g
+                                    .members
+                                    .iter()
+                                    .map(|m| luban_api::TelegramGroupMemberSnapshot {
+                                        user_id: m.user_id,
+                                        display_name: m.display_name.clone(),
+                                    })
+                                    .collect(),
+                                topic_count: g.topic_bindings.len() as u64,
+                            })
+                            .collect::<Vec<_>>();
+                        groups.sort_by_key(|g| g.chat_id);
+                        groups
+                    },
                 },
             },
+            notifications: {
+                let settings = self.state.notification_settings();
+                luban_api::NotificationsSnapshot {
+                    desktop_enabled: settings.desktop_enabled,
+                    quiet_hours: settings.quiet_hours.map(|q| luban_api::QuietHoursSnapshot {
+                        start_minute: q.start_minute,
+                        end_minute: q.end_minute,
+                        utc_offset_minutes: q.utc_offset_minutes,
+                    }),
+                }
+            },
+            attachment_text_limits: {
+                let limits = self.state.attachment_text_limits();
+                luban_api::AttachmentTextLimitsSnapshot {
+                    max_source_bytes: limits.max_source_bytes,
+                    max_inline_bytes: limits.max_inline_bytes,
+                }
+            },
+            url_attachments: luban_api::UrlAttachmentSettingsSnapshot {
+                allowed_domains: self.state.url_attachment_settings().allowed_domains.clone(),
+            },
+            deletion: luban_api::DeletionSettingsSnapshot {
+                retention_days: self.state.deletion_settings().retention_days,
+            },
+            task_issue_links: {
+                let mut links = self
+                    .state
+                    .task_issue_links()
+                    .values()
+                    .map(|link| luban_api::TaskIssueLinkSnapshot {
+                        workspace_id: luban_api::WorkspaceId(link.workspace_id),
+                        thread_id: luban_api::WorkspaceThreadId(link.thread_id),
+                        number: link.number,
+                        title: link.title.clone(),
+                        url: link.url.clone(),
+                        on_merge: map_domain_issue_merge_action(link.on_merge),
+                        resolved_by_pr: link.resolved_by_pr,
+                    })
+                    .collect::<Vec<_>>();
+                links.sort_by_key(|link| (link.workspace_id.0, link.thread_id.0));
+                links
+            },
         }
     }
 
@@ -5184,6 +7922,19 @@ impl Engine {
             queue_paused: conversation.queue_paused,
             remote_thread_id: conversation.thread_id.clone(),
             title: conversation.title.clone(),
+            turn_limits: map_turn_limits(self.state.task_turn_limits(wid, tid)),
+            tokens_used: self.state.task_tokens_used(wid, tid),
+            forked_from: conversation.forked_from.as_ref().map(map_fork_origin),
+            review_comments: self
+                .state
+                .task_review_comments(wid, tid)
+                .iter()
+                .map(map_review_comment)
+                .collect(),
+            pull_request_review_sync: map_pull_request_review_sync(
+                self.state.task_pull_request_review_sync(wid, tid),
+            ),
+            ci_auto_fix: map_ci_auto_fix(self.state.task_ci_auto_fix(wid, tid)),
         })
     }
 }
@@ -5290,6 +8041,72 @@ struct WorkspaceScope {
     workspace_name: String,
 }
 
+fn map_review_comment(comment: &luban_domain::ReviewComment) -> luban_api::ReviewCommentSnapshot {
+    luban_api::ReviewCommentSnapshot {
+        id: comment.id,
+        path: comment.path.clone(),
+        start_line: comment.start_line,
+        end_line: comment.end_line,
+        quote: comment.quote.clone(),
+        body: comment.body.clone(),
+        resolved: comment.resolved,
+        sent: comment.sent,
+        created_at_unix_ms: comment.created_at_unix_ms,
+        updated_at_unix_ms: comment.updated_at_unix_ms,
+        pull_request: comment.pull_request.as_ref().map(|origin| {
+            luban_api::ReviewCommentOriginSnapshot {
+                pr_number: origin.pr_number,
+                comment_id: origin.comment_id,
+                author: origin.author.clone(),
+                url: origin.url.clone(),
+                replied: origin.replied,
+            }
+        }),
+    }
+}
+
+fn map_pull_request_review_sync(
+    sync: luban_domain::PullRequestReviewSync,
+) -> luban_api::PullRequestReviewSyncSnapshot {
+    luban_api::PullRequestReviewSyncSnapshot {
+        auto_pull: sync.auto_pull,
+        reply_when_addressed: sync.reply_when_addressed,
+    }
+}
+
+fn map_ci_auto_fix(fix: Option<&luban_domain::CiAutoFix>) -> luban_api::CiAutoFixSnapshot {
+    fix.map_or_else(Default::default, |fix| luban_api::CiAutoFixSnapshot {
+        enabled: fix.enabled,
+        max_attempts: fix.max_attempts,
+        attempts_used: fix.attempts_used(),
+    })
+}
+
+fn map_ci_auto_fix_stop_reason(reason: CiAutoFixStopReason) -> luban_api::CiAutoFixStopReason {
+    match reason {
+        CiAutoFixStopReason::Passed => luban_api::CiAutoFixStopReason::Passed,
+        CiAutoFixStopReason::AttemptsExhausted => luban_api::CiAutoFixStopReason::AttemptsExhausted,
+    }
+}
+
+/// The PR number and CI state of a cached pull request, if it is open.
+fn open_pull_request_ci_state(
+    entry: Option<&PullRequestCacheEntry>,
+) -> Option<(u64, Option<DomainPullRequestCiState>)> {
+    entry
+        .and_then(|entry| entry.info.as_ref())
+        .filter(|pr| pr.state == DomainPullRequestState::Open)
+        .map(|pr| (pr.number, pr.ci_state))
+}
+
+fn map_fork_origin(origin: &luban_domain::ThreadForkOrigin) -> luban_api::TaskForkOrigin {
+    luban_api::TaskForkOrigin {
+        workspace_id: luban_api::WorkspaceId(origin.workspace_id.as_u64()),
+        thread_id: luban_api::WorkspaceThreadId(origin.thread_id.as_u64()),
+        entry_id: origin.entry_id.clone(),
+    }
+}
+
 fn workspace_scope(state: &AppState, workspace_id: WorkspaceId) -> Option<WorkspaceScope> {
     for project in &state.projects {
         for workspace in &project.workspaces {
@@ -5506,6 +8323,28 @@ fn resolve_task_document_paths(
     Ok(task_document_paths_for_dir(&task_dir))
 }
 
+fn copy_task_documents(
+    from: (WorkspaceId, WorkspaceThreadId),
+    to: (WorkspaceId, WorkspaceThreadId),
+) -> anyhow::Result<()> {
+    let from = resolve_task_document_paths(from.0, from.1)?;
+    let to = resolve_task_document_paths(to.0, to.1)?;
+    for (src, dst) in [
+        (&from.task_path, &to.task_path),
+        (&from.plan_path, &to.plan_path),
+        (&from.memory_path, &to.memory_path),
+    ] {
+        match std::fs::read_to_string(src) {
+            Ok(content) => write_text_atomic(dst, &content)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", src.display()));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn inject_task_document_prompt(prompt: &str, paths: &TaskDocumentPaths) -> String {
     format!(
         "{prompt}\n\n---\nTask document maintenance (required)\nEdit these files directly on disk as you work. Do not use API calls for task documents.\n- TASK.md: {}\n- PLAN.md: {}\n- MEMORY.md: {}\n\nPolicy scope for these three files:\n- TASK.md / PLAN.md / MEMORY.md are task-conversation artifacts, not repository source files.\n- Repository policy files (including AGENTS.md in the target repo) do not constrain the language/style/content for these three files.\n\nCreation rule:\n- If a file does not exist, create it yourself at the exact path above before updating it.\n\nUpdate expectations:\n1. Keep TASK.md current on status/progress/blockers.\n2. Keep PLAN.md current when plan or milestones change.\n3. Keep MEMORY.md current for durable decisions, constraints, and facts.\n4. Before your final reply, make sure all three files reflect the latest state.\n---",
@@ -5525,11 +8364,51 @@ fn should_sync_branch_watchers(action: &Action) -> bool {
             | Action::WorkspaceCreated { .. }
             | Action::WorkspaceArchived { .. }
             | Action::DeleteProject { .. }
+            | Action::ProjectRestored { .. }
     )
 }
 
+/// Pins the worktree as the turn left it and measures what changed since its checkpoint.
+fn record_turn_changes(
+    services: &dyn ProjectWorkspaceService,
+    worktree_path: &Path,
+    workspace_id: WorkspaceId,
+    thread_id: WorkspaceThreadId,
+    checkpoint: luban_domain::TurnCheckpoint,
+) -> Result<Action, String> {
+    let end_ref =
+        luban_domain::turn_end_checkpoint_ref(workspace_id, thread_id, &checkpoint.checkpoint_id);
+    let commit = services.create_turn_checkpoint(worktree_path.to_path_buf(), end_ref)?;
+    let stat = crate::git_changes::diff_stat(worktree_path, &checkpoint.commit, &commit)
+        .map_err(|err| err.to_string())?;
+    Ok(Action::TurnChangesRecorded {
+        workspace_id,
+        thread_id,
+        checkpoint_id: checkpoint.checkpoint_id,
+        commit,
+        files_changed: stat.files_changed,
+        additions: stat.additions,
+        deletions: stat.deletions,
+    })
+}
+
 fn conversation_key_for_action(action: &Action) -> Option<(WorkspaceId, WorkspaceThreadId)> {
     match action {
+        Action::TurnCheckpointCreated {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::TurnChangesRecorded {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::TaskRewound {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
         Action::TerminalCommandStarted {
             workspace_id,
             thread_id,
@@ -5550,6 +8429,11 @@ fn conversation_key_for_action(action: &Action) -> Option<(WorkspaceId, Workspac
             thread_id,
             ..
         } => Some((*workspace_id, *thread_id)),
+        Action::TaskStatusRestored {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
         Action::TaskStatusSuggestionCreated {
             workspace_id,
             thread_id,
@@ -5594,6 +8478,66 @@ fn conversation_key_for_action(action: &Action) -> Option<(WorkspaceId, Workspac
             workspace_id,
             thread_id,
         } => Some((*workspace_id, *thread_id)),
+        Action::AgentTurnTimedOut {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::TaskTurnLimitsChanged {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::AddReviewComment {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::EditReviewComment {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::ResolveReviewComment {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::ReviewCommentsSent {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::PullRequestReviewCommentsFetched {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::PullRequestReviewSyncChanged {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::ReviewCommentReplyPosted {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::CiAutoFixChanged {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::CiFixAttemptStarted {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
+        Action::CiAutoFixStopped {
+            workspace_id,
+            thread_id,
+            ..
+        } => Some((*workspace_id, *thread_id)),
         Action::ChatModelChanged {
             workspace_id,
             thread_id,
@@ -5663,6 +8607,11 @@ fn conversation_keys_for_effects(effects: &[Effect]) -> Vec<(WorkspaceId, Worksp
                 workspace_id,
                 thread_id,
             }
+            | Effect::ForkConversation {
+                workspace_id,
+                thread_id,
+                ..
+            }
             | Effect::RunAgentTurn {
                 workspace_id,
                 thread_id,
@@ -5799,6 +8748,17 @@ fn dedup_thread_metas_in_place(metas: &mut Vec<ConversationThreadMeta>) {
     metas.retain(|t| seen.insert(t.thread_id));
 }
 
+fn map_api_task_status(status: luban_api::TaskStatus) -> luban_domain::TaskStatus {
+    match status {
+        luban_api::TaskStatus::Backlog => luban_domain::TaskStatus::Backlog,
+        luban_api::TaskStatus::Todo => luban_domain::TaskStatus::Todo,
+        luban_api::TaskStatus::Iterating => luban_domain::TaskStatus::Iterating,
+        luban_api::TaskStatus::Validating => luban_domain::TaskStatus::Validating,
+        luban_api::TaskStatus::Done => luban_domain::TaskStatus::Done,
+        luban_api::TaskStatus::Canceled => luban_domain::TaskStatus::Canceled,
+    }
+}
+
 fn map_domain_task_status(status: luban_domain::TaskStatus) -> luban_api::TaskStatus {
     match status {
         luban_domain::TaskStatus::Backlog => luban_api::TaskStatus::Backlog,
@@ -5952,21 +8912,75 @@ fn workspace_short_id(project_slug: &str, workspace_id: u64) -> String {
         suffix.insert(0, '0');
     }
 
-    format!("{prefix}{suffix}")
+    format!("{prefix}{suffix}")
+}
+
+fn to_base36(mut n: u64) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    if n == 0 {
+        return "0".to_owned();
+    }
+    let mut out = Vec::new();
+    while n > 0 {
+        out.push(DIGITS[(n % 36) as usize]);
+        n /= 36;
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_else(|_| "0".to_owned())
+}
+
+/// Applies `job` to every target, keeping going past failures. Fails only when nothing could be
+/// cleaned and at least one target failed.
+fn run_disk_cleanup<T>(
+    targets: Vec<T>,
+    mut job: impl FnMut(T) -> Result<DiskCleanupReport, String>,
+) -> Result<DiskCleanupReport, String> {
+    let mut report = DiskCleanupReport::default();
+    let mut first_error = None;
+    for target in targets {
+        match job(target) {
+            Ok(r) => report.merge(r),
+            Err(message) => {
+                tracing::warn!(error = %message, "disk cleanup step failed");
+                first_error.get_or_insert(message);
+            }
+        }
+    }
+    match first_error {
+        Some(message) if report.removed_entries == 0 => Err(message),
+        _ => Ok(report),
+    }
+}
+
+fn disk_cleanup_message(report: DiskCleanupReport) -> String {
+    if report.removed_entries == 0 {
+        return "Nothing to clean up".to_owned();
+    }
+    let noun = if report.removed_entries == 1 {
+        "entry"
+    } else {
+        "entries"
+    };
+    format!(
+        "Freed {} ({} {noun} removed)",
+        format_byte_size(report.freed_bytes),
+        report.removed_entries
+    )
 }
 
-fn to_base36(mut n: u64) -> String {
-    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
-    if n == 0 {
-        return "0".to_owned();
+fn format_byte_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
     }
-    let mut out = Vec::new();
-    while n > 0 {
-        out.push(DIGITS[(n % 36) as usize]);
-        n /= 36;
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
     }
-    out.reverse();
-    String::from_utf8(out).unwrap_or_else(|_| "0".to_owned())
 }
 
 fn now_unix_seconds() -> u64 {
@@ -6066,6 +9080,87 @@ fn map_conversation_entry(entry: &ConversationEntry) -> luban_api::ConversationE
                     title: title.clone(),
                     explanation_markdown: explanation_markdown.clone(),
                 },
+                luban_domain::ConversationSystemEvent::TurnInterrupted { resumable, .. } => {
+                    luban_api::ConversationSystemEvent::TurnInterrupted {
+                        resumable: *resumable,
+                    }
+                }
+                luban_domain::ConversationSystemEvent::TurnCheckpoint {
+                    checkpoint_id,
+                    commit,
+                } => luban_api::ConversationSystemEvent::TurnCheckpoint {
+                    checkpoint_id: checkpoint_id.clone(),
+                    commit: commit.clone(),
+                },
+                luban_domain::ConversationSystemEvent::TurnRewound {
+                    checkpoint_id,
+                    truncated,
+                } => luban_api::ConversationSystemEvent::TurnRewound {
+                    checkpoint_id: checkpoint_id.clone(),
+                    truncated: *truncated,
+                },
+                luban_domain::ConversationSystemEvent::TurnChanges {
+                    checkpoint_id,
+                    commit,
+                    files_changed,
+                    additions,
+                    deletions,
+                } => luban_api::ConversationSystemEvent::TurnChanges {
+                    checkpoint_id: checkpoint_id.clone(),
+                    commit: commit.clone(),
+                    files_changed: *files_changed,
+                    additions: *additions,
+                    deletions: *deletions,
+                },
+                luban_domain::ConversationSystemEvent::TurnLimitReached {
+                    kind,
+                    limit,
+                    observed,
+                } => luban_api::ConversationSystemEvent::TurnLimitReached {
+                    kind: map_turn_limit_kind(*kind),
+                    limit: *limit,
+                    observed: *observed,
+                },
+                luban_domain::ConversationSystemEvent::PullRequestReviewComment {
+                    pr_number,
+                    review_comment_id,
+                    author,
+                    path,
+                    start_line,
+                    end_line,
+                    body,
+                    url,
+                } => luban_api::ConversationSystemEvent::PullRequestReviewComment {
+                    pr_number: *pr_number,
+                    review_comment_id: *review_comment_id,
+                    author: author.clone(),
+                    path: path.clone(),
+                    start_line: *start_line,
+                    end_line: *end_line,
+                    body: body.clone(),
+                    url: url.clone(),
+                },
+                luban_domain::ConversationSystemEvent::CiFixAttempt {
+                    pr_number,
+                    attempt,
+                    max_attempts,
+                    run_id,
+                    workflow,
+                    url,
+                } => luban_api::ConversationSystemEvent::CiFixAttempt {
+                    pr_number: *pr_number,
+                    attempt: *attempt,
+                    max_attempts: *max_attempts,
+                    run_id: *run_id,
+                    workflow: workflow.clone(),
+                    url: url.clone(),
+                },
+                luban_domain::ConversationSystemEvent::CiAutoFixStopped { reason, attempts } => {
+                    luban_api::ConversationSystemEvent::CiAutoFixStopped {
+                        reason: map_ci_auto_fix_stop_reason(*reason),
+                        attempts: *attempts,
+                    }
+                }
             },
         }),
         ConversationEntry::UserEvent {
@@ -6074,12 +9169,15 @@ fn map_conversation_entry(entry: &ConversationEntry) -> luban_api::ConversationE
             event,
         } => {
             let event = match event {
-                luban_domain::UserEvent::Message { text, attachments } => {
-                    luban_api::UserEvent::Message(luban_api::UserMessage {
-                        text: text.clone(),
-                        attachments: attachments.iter().map(map_attachment_ref).collect(),
-                    })
-                }
+                luban_domain::UserEvent::Message {
+                    text,
+                    attachments,
+                    author,
+                } => luban_api::UserEvent::Message(luban_api::UserMessage {
+                    text: text.clone(),
+                    attachments: attachments.iter().map(map_attachment_ref).collect(),
+                    author: author.clone(),
+                }),
                 luban_domain::UserEvent::TerminalCommandStarted {
                     id,
                     command,
@@ -6176,13 +9274,14 @@ fn map_codex_thread_item_to_agent_event(item: &CodexThreadItem) -> luban_api::Ag
     }
 }
 
-fn map_attachment_ref(att: &AttachmentRef) -> luban_api::AttachmentRef {
+pub(crate) fn map_attachment_ref(att: &AttachmentRef) -> luban_api::AttachmentRef {
     luban_api::AttachmentRef {
         id: att.id.clone(),
         kind: match att.kind {
             AttachmentKind::Image => luban_api::AttachmentKind::Image,
             AttachmentKind::Text => luban_api::AttachmentKind::Text,
             AttachmentKind::File => luban_api::AttachmentKind::File,
+            AttachmentKind::Url => luban_api::AttachmentKind::Url,
         },
         name: att.name.clone(),
         extension: att.extension.clone(),
@@ -6236,6 +9335,144 @@ fn map_client_action(action: luban_api::ClientAction) -> Option<Action> {
         luban_api::ClientAction::TelegramBotTokenClear => Some(Action::TelegramBotTokenCleared),
         luban_api::ClientAction::TelegramPairStart => None,
         luban_api::ClientAction::TelegramUnpair => Some(Action::TelegramUnpaired),
+        luban_api::ClientAction::TelegramGroupPairStart { .. } => None,
+        luban_api::ClientAction::TelegramGroupUnpair { chat_id } => {
+            Some(Action::TelegramGroupUnpaired { chat_id })
+        }
+        luban_api::ClientAction::TelegramGroupMemberAdd {
+            chat_id,
+            user_id,
+            display_name,
+        } => Some(Action::TelegramGroupMemberAdded {
+            chat_id,
+            member: luban_domain::TelegramGroupMember {
+                user_id,
+                display_name: display_name.trim().to_owned(),
+            },
+        }),
+        luban_api::ClientAction::TelegramGroupMemberRemove { chat_id, user_id } => {
+            Some(Action::TelegramGroupMemberRemoved { chat_id, user_id })
+        }
+        luban_api::ClientAction::DesktopNotificationsEnabledChanged { enabled } => {
+            Some(Action::DesktopNotificationsEnabledChanged { enabled })
+        }
+        luban_api::ClientAction::NotificationQuietHoursChanged { quiet_hours } => {
+            Some(Action::NotificationQuietHoursChanged {
+                quiet_hours: quiet_hours.map(|q| luban_domain::QuietHours {
+                    start_minute: q.start_minute,
+                    end_minute: q.end_minute,
+                    utc_offset_minutes: q.utc_offset_minutes,
+                }),
+            })
+        }
+        luban_api::ClientAction::ProjectNotificationsMutedChanged { .. } => None,
+        luban_api::ClientAction::AttachmentTextLimitsChanged { limits } => {
+            Some(Action::AttachmentTextLimitsChanged {
+                limits: luban_domain::AttachmentTextLimits {
+                    max_source_bytes: limits.max_source_bytes,
+                    max_inline_bytes: limits.max_inline_bytes,
+                },
+            })
+        }
+        luban_api::ClientAction::DeletionRetentionChanged { retention_days } => {
+            Some(Action::DeletionRetentionChanged { retention_days })
+        }
+        luban_api::ClientAction::ProjectTurnLimitsChanged { .. } => None,
+        luban_api::ClientAction::TaskTurnLimitsChanged {
+            workspace_id,
+            thread_id,
+            limits,
+        } => Some(Action::TaskTurnLimitsChanged {
+            workspace_id: WorkspaceId::from_u64(workspace_id.0),
+            thread_id: WorkspaceThreadId::from_u64(thread_id.0),
+            limits: map_api_turn_limits(limits),
+        }),
+        luban_api::ClientAction::UrlAttachmentDomainsChanged { domains } => {
+            Some(Action::UrlAttachmentDomainsChanged { domains })
+        }
+        luban_api::ClientAction::TaskImportIssue { .. } => None,
+        luban_api::ClientAction::TaskBulkApply { .. } => None,
+        luban_api::ClientAction::TaskBulkUndo { .. } => None,
+        luban_api::ClientAction::UndoLastAction => None,
+        luban_api::ClientAction::TaskRewind { .. } => None,
+        luban_api::ClientAction::TaskFork { .. } => None,
+        luban_api::ClientAction::TaskReviewCommentAdd {
+            workspace_id,
+            thread_id,
+            path,
+            start_line,
+            end_line,
+            quote,
+            body,
+        } => Some(Action::AddReviewComment {
+            workspace_id: WorkspaceId::from_u64(workspace_id.0),
+            thread_id: WorkspaceThreadId::from_u64(thread_id.0),
+            path,
+            start_line,
+            end_line,
+            quote,
+            body,
+        }),
+        luban_api::ClientAction::TaskReviewCommentEdit {
+            workspace_id,
+            thread_id,
+            comment_id,
+            body,
+        } => Some(Action::EditReviewComment {
+            workspace_id: WorkspaceId::from_u64(workspace_id.0),
+            thread_id: WorkspaceThreadId::from_u64(thread_id.0),
+            comment_id,
+            body,
+        }),
+        luban_api::ClientAction::TaskReviewCommentResolve {
+            workspace_id,
+            thread_id,
+            comment_id,
+            resolved,
+        } => Some(Action::ResolveReviewComment {
+            workspace_id: WorkspaceId::from_u64(workspace_id.0),
+            thread_id: WorkspaceThreadId::from_u64(thread_id.0),
+            comment_id,
+            resolved,
+        }),
+        luban_api::ClientAction::TaskReviewCommentsSend { .. } => None,
+        luban_api::ClientAction::TaskPullRequestReviewPull { .. } => None,
+        luban_api::ClientAction::TaskPullRequestReviewSyncChanged {
+            workspace_id,
+            thread_id,
+            sync,
+        } => Some(Action::PullRequestReviewSyncChanged {
+            workspace_id: WorkspaceId::from_u64(workspace_id.0),
+            thread_id: WorkspaceThreadId::from_u64(thread_id.0),
+            sync: luban_domain::PullRequestReviewSync {
+                auto_pull: sync.auto_pull,
+                reply_when_addressed: sync.reply_when_addressed,
+            },
+        }),
+        luban_api::ClientAction::TaskCiAutoFixChanged {
+            workspace_id,
+            thread_id,
+            enabled,
+            max_attempts,
+        } => Some(Action::CiAutoFixChanged {
+            workspace_id: WorkspaceId::from_u64(workspace_id.0),
+            thread_id: WorkspaceThreadId::from_u64(thread_id.0),
+            enabled,
+            max_attempts,
+        }),
+        luban_api::ClientAction::TaskIssueMergeActionSet {
+            workspace_id,
+            thread_id,
+            on_merge,
+        } => Some(Action::TaskIssueMergeActionChanged {
+            workspace_id: WorkspaceId::from_u64(workspace_id.0),
+            thread_id: WorkspaceThreadId::from_u64(thread_id.0),
+            on_merge: map_api_issue_merge_action(on_merge),
+        }),
+        luban_api::ClientAction::DiskUsageRefresh => None,
+        luban_api::ClientAction::BuildArtifactsPurge { .. } => None,
+        luban_api::ClientAction::StaleWorktreesDelete { .. } => None,
+        luban_api::ClientAction::ContextBlobsGc => None,
         luban_api::ClientAction::TaskStarSet {
             workspace_id,
             thread_id,
@@ -6252,14 +9489,7 @@ fn map_client_action(action: luban_api::ClientAction) -> Option<Action> {
         } => Some(Action::TaskStatusSet {
             workspace_id: WorkspaceId::from_u64(workspace_id.0),
             thread_id: WorkspaceThreadId::from_u64(thread_id.0),
-            task_status: match task_status {
-                luban_api::TaskStatus::Backlog => luban_domain::TaskStatus::Backlog,
-                luban_api::TaskStatus::Todo => luban_domain::TaskStatus::Todo,
-                luban_api::TaskStatus::Iterating => luban_domain::TaskStatus::Iterating,
-                luban_api::TaskStatus::Validating => luban_domain::TaskStatus::Validating,
-                luban_api::TaskStatus::Done => luban_domain::TaskStatus::Done,
-                luban_api::TaskStatus::Canceled => luban_domain::TaskStatus::Canceled,
-            },
+            task_status: map_api_task_status(task_status),
         }),
         luban_api::ClientAction::FeedbackSubmit { .. } => None,
         luban_api::ClientAction::DeleteProject { .. } => None,
@@ -6352,6 +9582,7 @@ fn map_client_action(action: luban_api::ClientAction) -> Option<Action> {
             attachments,
             runner,
             amp_mode,
+            author,
         } => Some(Action::SendAgentMessage {
             workspace_id: WorkspaceId::from_u64(workspace_id.0),
             thread_id: WorkspaceThreadId::from_u64(thread_id.0),
@@ -6359,8 +9590,10 @@ fn map_client_action(action: luban_api::ClientAction) -> Option<Action> {
             attachments: attachments.into_iter().map(map_api_attachment).collect(),
             runner: runner.map(map_api_agent_runner_kind),
             amp_mode,
+            author,
         }),
         luban_api::ClientAction::CancelAndSendAgentMessage { .. } => None,
+        luban_api::ClientAction::ResumeInterruptedTurn { .. } => None,
         luban_api::ClientAction::QueueAgentMessage {
             workspace_id,
             thread_id,
@@ -6534,19 +9767,20 @@ fn map_client_action(action: luban_api::ClientAction) -> Option<Action> {
         luban_api::ClientAction::AgentAmpModeChanged { mode } => {
             Some(Action::AgentAmpModeChanged { mode })
         }
+        luban_api::ClientAction::TaskPromptTemplateChanged {
+            project_id: Some(_),
+            ..
+        } => None,
         luban_api::ClientAction::TaskPromptTemplateChanged {
             intent_kind,
             template,
+            project_id: None,
         } => Some(Action::TaskPromptTemplateChanged {
-            intent_kind: match intent_kind {
-                luban_api::TaskIntentKind::Fix => luban_domain::TaskIntentKind::Fix,
-                luban_api::TaskIntentKind::Implement => luban_domain::TaskIntentKind::Implement,
-                luban_api::TaskIntentKind::Review => luban_domain::TaskIntentKind::Review,
-                luban_api::TaskIntentKind::Discuss => luban_domain::TaskIntentKind::Discuss,
-                luban_api::TaskIntentKind::Other => luban_domain::TaskIntentKind::Other,
-            },
+            project_id: None,
+            intent_kind: map_api_task_intent_kind(intent_kind),
             template,
         }),
+        luban_api::ClientAction::ProjectPromptVariablesChanged { .. } => None,
         luban_api::ClientAction::SystemPromptTemplateChanged { kind, template } => {
             Some(Action::SystemPromptTemplateChanged {
                 kind: match kind {
@@ -6605,6 +9839,63 @@ fn expand_user_path(raw: &str) -> PathBuf {
     PathBuf::from(trimmed)
 }
 
+fn map_api_task_intent_kind(kind: luban_api::TaskIntentKind) -> luban_domain::TaskIntentKind {
+    match kind {
+        luban_api::TaskIntentKind::Fix => luban_domain::TaskIntentKind::Fix,
+        luban_api::TaskIntentKind::Implement => luban_domain::TaskIntentKind::Implement,
+        luban_api::TaskIntentKind::Review => luban_domain::TaskIntentKind::Review,
+        luban_api::TaskIntentKind::Discuss => luban_domain::TaskIntentKind::Discuss,
+        luban_api::TaskIntentKind::Other => luban_domain::TaskIntentKind::Other,
+    }
+}
+
+fn map_turn_limits(limits: luban_domain::TurnLimits) -> luban_api::TurnLimitsSnapshot {
+    luban_api::TurnLimitsSnapshot {
+        turn_timeout_secs: limits.turn_timeout_secs,
+        max_tool_calls_per_turn: limits.max_tool_calls_per_turn,
+        turn_token_budget: limits.turn_token_budget,
+        task_token_budget: limits.task_token_budget,
+    }
+}
+
+fn map_api_turn_limits(limits: luban_api::TurnLimitsSnapshot) -> luban_domain::TurnLimits {
+    luban_domain::TurnLimits {
+        turn_timeout_secs: limits.turn_timeout_secs,
+        max_tool_calls_per_turn: limits.max_tool_calls_per_turn,
+        turn_token_budget: limits.turn_token_budget,
+        task_token_budget: limits.task_token_budget,
+    }
+}
+
+fn map_turn_limit_kind(kind: luban_domain::TurnLimitKind) -> luban_api::TurnLimitKind {
+    match kind {
+        luban_domain::TurnLimitKind::Timeout => luban_api::TurnLimitKind::Timeout,
+        luban_domain::TurnLimitKind::ToolCalls => luban_api::TurnLimitKind::ToolCalls,
+        luban_domain::TurnLimitKind::TurnTokens => luban_api::TurnLimitKind::TurnTokens,
+        luban_domain::TurnLimitKind::TaskTokens => luban_api::TurnLimitKind::TaskTokens,
+    }
+}
+
+fn map_api_issue_merge_action(
+    action: luban_api::IssueMergeAction,
+) -> luban_domain::IssueMergeAction {
+    match action {
+        luban_api::IssueMergeAction::Close => luban_domain::IssueMergeAction::Close,
+        luban_api::IssueMergeAction::Comment => luban_domain::IssueMergeAction::Comment,
+        luban_api::IssueMergeAction::Leave => luban_domain::IssueMergeAction::Leave,
+    }
+}
+
+fn map_domain_issue_merge_action(
+    action: luban_domain::IssueMergeAction,
+) -> luban_api::IssueMergeAction {
+    match action {
+        luban_domain::IssueMergeAction::Close => luban_api::IssueMergeAction::Close,
+        luban_domain::IssueMergeAction::Comment => luban_api::IssueMergeAction::Comment,
+        luban_domain::IssueMergeAction::Leave => luban_api::IssueMergeAction::Leave,
+    }
+}
+
 fn map_api_attachment(att: luban_api::AttachmentRef) -> AttachmentRef {
     AttachmentRef {
         id: att.id,
@@ -6612,6 +9903,7 @@ fn map_api_attachment(att: luban_api::AttachmentRef) -> AttachmentRef {
             luban_api::AttachmentKind::Image => AttachmentKind::Image,
             luban_api::AttachmentKind::Text => AttachmentKind::Text,
             luban_api::AttachmentKind::File => AttachmentKind::File,
+            luban_api::AttachmentKind::Url => AttachmentKind::Url,
         },
         name: att.name,
         extension: att.extension,
@@ -6641,6 +9933,25 @@ fn map_api_agent_runner_kind(kind: luban_api::AgentRunnerKind) -> luban_domain::
     }
 }
 
+fn map_thinking_effort_to_api(effort: luban_domain::ThinkingEffort) -> luban_api::ThinkingEffort {
+    match effort {
+        luban_domain::ThinkingEffort::Minimal => luban_api::ThinkingEffort::Minimal,
+        luban_domain::ThinkingEffort::Low => luban_api::ThinkingEffort::Low,
+        luban_domain::ThinkingEffort::Medium => luban_api::ThinkingEffort::Medium,
+        luban_domain::ThinkingEffort::High => luban_api::ThinkingEffort::High,
+        luban_domain::ThinkingEffort::XHigh => luban_api::ThinkingEffort::XHigh,
+    }
+}
+
+fn map_agent_runner_kind_to_api(kind: luban_domain::AgentRunnerKind) -> luban_api::AgentRunnerKind {
+    match kind {
+        luban_domain::AgentRunnerKind::Codex => luban_api::AgentRunnerKind::Codex,
+        luban_domain::AgentRunnerKind::Amp => luban_api::AgentRunnerKind::Amp,
+        luban_domain::AgentRunnerKind::Claude => luban_api::AgentRunnerKind::Claude,
+        luban_domain::AgentRunnerKind::Droid => luban_api::AgentRunnerKind::Droid,
+    }
+}
+
 pub fn new_default_services() -> anyhow::Result<Arc<dyn ProjectWorkspaceService>> {
     Ok(GitWorkspaceService::new_with_options(SqliteStoreOptions {
         persist_ui_state: true,
@@ -6836,6 +10147,7 @@ mod tests {
 
     #[derive(Default)]
     struct ReconcileRecordingServices {
+        remote_thread_id: Option<String>,
         appended_entries: Mutex<Vec<ConversationEntry>>,
         saved_queue_state: Mutex<Vec<SavedQueueState>>,
     }
@@ -6904,6 +10216,7 @@ mod tests {
                 task_status_last_analyzed_message_seq: 0,
                 turn_status: luban_domain::TurnStatus::Running,
                 last_turn_result: None,
+                forked_from: None,
             }])
         }
 
@@ -6915,7 +10228,7 @@ mod tests {
         ) -> Result<DomainConversationSnapshot, String> {
             Ok(DomainConversationSnapshot {
                 title: Some("t1".to_owned()),
-                thread_id: None,
+                thread_id: self.remote_thread_id.clone(),
                 task_status: luban_domain::TaskStatus::Todo,
                 runner: None,
                 agent_model_id: None,
@@ -6927,6 +10240,7 @@ mod tests {
                     event: luban_domain::UserEvent::Message {
                         text: "hi".to_owned(),
                         attachments: Vec::new(),
+                        author: None,
                     },
                 }],
                 entries_total: 1,
@@ -6941,6 +10255,7 @@ mod tests {
                         thinking_effort: ThinkingEffort::Medium,
                         amp_mode: None,
                     },
+                    author: None,
                 }],
                 queue_paused: false,
                 run_started_at_unix_ms: Some(10),
@@ -7082,9 +10397,63 @@ mod tests {
         }
     }
 
-    struct IdentityServices;
+    #[derive(Default)]
+    struct IdentityServices {
+        audit_log: std::sync::Mutex<Vec<AuditEntry>>,
+    }
 
     impl ProjectWorkspaceService for IdentityServices {
+        fn append_audit_entry(
+            &self,
+            origin: AuditOrigin,
+            record: AuditRecord,
+        ) -> Result<AuditEntry, String> {
+            let mut log = self.audit_log.lock().expect("mutex poisoned");
+            let entry = AuditEntry {
+                id: log.len() as u64 + 1,
+                created_at_unix_ms: now_unix_ms(),
+                origin,
+                action: record.action,
+                summary: record.summary,
+                workspace_id: record.workspace_id,
+                thread_id: record.thread_id,
+                undo: record.undo,
+                undone_at_unix_ms: None,
+            };
+            log.push(entry.clone());
+            Ok(entry)
+        }
+
+        fn list_audit_entries(
+            &self,
+            _before_id: Option<u64>,
+            limit: u64,
+        ) -> Result<Vec<AuditEntry>, String> {
+            let log = self.audit_log.lock().expect("mutex poisoned");
+            Ok(log.iter().rev().take(limit as usize).cloned().collect())
+        }
+
+        fn latest_undoable_audit_entry(&self) -> Result<Option<AuditEntry>, String> {
+            let log = self.audit_log.lock().expect("mutex poisoned");
+            Ok(log
+                .iter()
+                .rev()
+                .find(|entry| entry.undo.is_some() && entry.undone_at_unix_ms.is_none())
+                .cloned())
+        }
+
+        fn mark_audit_entry_undone(
+            &self,
+            entry_id: u64,
+            undone_at_unix_ms: u64,
+        ) -> Result<(), String> {
+            let mut log = self.audit_log.lock().expect("mutex poisoned");
+            if let Some(entry) = log.iter_mut().find(|entry| entry.id == entry_id) {
+                entry.undone_at_unix_ms = Some(undone_at_unix_ms);
+            }
+            Ok(())
+        }
+
         fn load_app_state(&self) -> Result<PersistedAppState, String> {
             Ok(PersistedAppState {
                 projects: Vec::new(),
@@ -7123,6 +10492,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             })
         }
 
@@ -7323,9 +10702,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine.pull_requests.insert(
@@ -7388,9 +10772,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine.pull_requests.insert(
@@ -7502,6 +10891,7 @@ mod tests {
             attachments: Vec::new(),
             runner: None,
             amp_mode: None,
+            author: None,
         });
 
         let key = (workspace_id, thread_id);
@@ -7542,9 +10932,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         let api_wid = luban_api::WorkspaceId(workspace_id.as_u64());
@@ -7628,6 +11023,7 @@ mod tests {
                     worktree_path: PathBuf::from("/tmp/p"),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: None,
@@ -7665,6 +11061,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         };
 
         services
@@ -7739,6 +11145,7 @@ mod tests {
                 task_status_last_analyzed_message_seq: 0,
                 turn_status: luban_domain::TurnStatus::Idle,
                 last_turn_result: None,
+                forked_from: None,
             })
             .collect::<Vec<_>>();
 
@@ -7756,9 +11163,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine.publish_threads_event(workspace_id, &metas);
@@ -7826,6 +11238,7 @@ mod tests {
                 task_status_last_analyzed_message_seq: 0,
                 turn_status: luban_domain::TurnStatus::Idle,
                 last_turn_result: None,
+                forked_from: None,
             },
             ConversationThreadMeta {
                 thread_id,
@@ -7838,6 +11251,7 @@ mod tests {
                 task_status_last_analyzed_message_seq: 0,
                 turn_status: luban_domain::TurnStatus::Idle,
                 last_turn_result: None,
+                forked_from: None,
             },
         ];
 
@@ -7855,9 +11269,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine.publish_threads_event(workspace_id, &metas);
@@ -7893,9 +11312,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -7993,6 +11417,7 @@ mod tests {
                 task_status_last_analyzed_message_seq: 0,
                 turn_status: luban_domain::TurnStatus::Idle,
                 last_turn_result: Some(luban_domain::TurnResult::Completed),
+                forked_from: None,
             },
             ConversationThreadMeta {
                 thread_id: other_thread_id,
@@ -8005,6 +11430,7 @@ mod tests {
                 task_status_last_analyzed_message_seq: 0,
                 turn_status: luban_domain::TurnStatus::Awaiting,
                 last_turn_result: None,
+                forked_from: None,
             },
         ];
 
@@ -8022,9 +11448,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
         engine.workspace_threads_cache.insert(workspace_id, metas);
 
@@ -8097,6 +11528,7 @@ mod tests {
             task_status_last_analyzed_message_seq: 0,
             turn_status: luban_domain::TurnStatus::Idle,
             last_turn_result: None,
+            forked_from: None,
         }];
 
         let (events, _) = broadcast::channel::<WsServerMessage>(16);
@@ -8105,7 +11537,7 @@ mod tests {
         let mut engine = Engine {
             state,
             rev: 1,
-            services: Arc::new(IdentityServices),
+            services: Arc::new(IdentityServices::default()),
             events,
             tx,
             branch_watch: BranchWatchHandle::disabled(),
@@ -8113,9 +11545,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
         engine.workspace_threads_cache.insert(workspace_id, metas);
 
@@ -8153,6 +11590,153 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn task_bulk_status_is_idempotent_and_undoable() {
+        let mut state = AppState::new();
+        let _ = state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/luban-server-test"),
+            is_git: true,
+        });
+
+        let project_id = state.projects[0].id;
+        let _ = state.apply(Action::WorkspaceCreated {
+            project_id,
+            workspace_name: "main".to_owned(),
+            branch_name: "main".to_owned(),
+            worktree_path: PathBuf::from("/tmp/luban-server-test"),
+        });
+
+        let workspace_id = state.projects[0].workspaces[0].id;
+        state.apply(Action::OpenWorkspace { workspace_id });
+        let mut thread_ids = Vec::new();
+        for _ in 0..2 {
+            state.apply(Action::CreateWorkspaceThread {
+                workspace_id,
+                model_id: None,
+                thinking_effort: None,
+            });
+            thread_ids.push(
+                state
+                    .workspace_tabs(workspace_id)
+                    .expect("workspace tabs exist after creating thread")
+                    .active_tab,
+            );
+        }
+        let initial_status = state
+            .workspace_thread_conversation(workspace_id, thread_ids[0])
+            .expect("conversation")
+            .task_status;
+
+        let services = Arc::new(IdentityServices::default());
+        let (events, _) = broadcast::channel::<WsServerMessage>(16);
+        let (tx, _rx_cmd) = mpsc::channel::<EngineCommand>(1);
+        let mut engine = Engine {
+            state,
+            rev: 1,
+            services: services.clone(),
+            events,
+            tx,
+            branch_watch: BranchWatchHandle::disabled(),
+            task_document_watch: TaskDocumentWatchHandle::disabled(),
+            cancel_flags: HashMap::new(),
+            pull_requests: HashMap::new(),
+            pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
+            workspace_threads_cache: HashMap::new(),
+            auto_archive_workspaces: HashSet::new(),
+            telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
+        };
+
+        let item = |thread_id: u64| luban_api::TaskBulkItem {
+            workspace_id: luban_api::WorkspaceId(workspace_id.as_u64()),
+            thread_id: luban_api::WorkspaceThreadId(thread_id),
+        };
+        let items = vec![
+            item(thread_ids[0].as_u64()),
+            item(thread_ids[1].as_u64()),
+            item(thread_ids[0].as_u64()),
+            item(999),
+        ];
+        let operation = luban_api::TaskBulkOperation::SetStatus {
+            task_status: luban_api::TaskStatus::Done,
+        };
+
+        let first = engine
+            .apply_task_bulk_idempotent(Some("k1".to_owned()), items.clone(), operation.clone())
+            .await
+            .expect("bulk apply");
+        assert!(first.undoable);
+        assert_eq!(first.items.len(), 3, "duplicate items are applied once");
+        assert!(first.items[0].ok && first.items[1].ok);
+        assert!(!first.items[2].ok);
+        assert_eq!(
+            first.items[0].previous_status,
+            Some(map_domain_task_status(initial_status))
+        );
+        for thread_id in &thread_ids {
+            let status = engine
+                .state
+                .workspace_thread_conversation(workspace_id, *thread_id)
+                .expect("conversation")
+                .task_status;
+            assert_eq!(status, luban_domain::TaskStatus::Done);
+        }
+
+        let audit_actions = |services: &IdentityServices| {
+            services
+                .audit_log
+                .lock()
+                .expect("mutex poisoned")
+                .iter()
+                .map(|entry| entry.action.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(audit_actions(&services), vec!["bulk_set_task_status"]);
+
+        let replay = engine
+            .apply_task_bulk_idempotent(Some("k1".to_owned()), items.clone(), operation.clone())
+            .await
+            .expect("bulk replay");
+        assert_eq!(replay.batch_id, first.batch_id);
+
+        let undone = engine
+            .undo_task_bulk(&first.batch_id)
+            .await
+            .expect("bulk undo");
+        assert!(undone.items.iter().all(|item| item.ok));
+        for thread_id in &thread_ids {
+            let status = engine
+                .state
+                .workspace_thread_conversation(workspace_id, *thread_id)
+                .expect("conversation")
+                .task_status;
+            assert_eq!(status, initial_status);
+        }
+        assert!(engine.undo_task_bulk(&first.batch_id).await.is_err());
+        assert_eq!(
+            audit_actions(&services),
+            vec!["bulk_set_task_status", "undo"]
+        );
+
+        engine
+            .apply_task_bulk_idempotent(Some("k2".to_owned()), items, operation)
+            .await
+            .expect("second bulk apply");
+        engine.undo_last_action().await.expect("undo last action");
+        for thread_id in &thread_ids {
+            let status = engine
+                .state
+                .workspace_thread_conversation(workspace_id, *thread_id)
+                .expect("conversation")
+                .task_status;
+            assert_eq!(status, initial_status);
+        }
+    }
+
     #[tokio::test]
     async fn task_status_set_emits_conversation_changed() {
         let mut state = AppState::new();
@@ -8187,7 +11771,7 @@ mod tests {
         let mut engine = Engine {
             state,
             rev: 1,
-            services: Arc::new(IdentityServices),
+            services: Arc::new(IdentityServices::default()),
             events,
             tx,
             branch_watch: BranchWatchHandle::disabled(),
@@ -8195,9 +11779,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -8285,7 +11874,7 @@ mod tests {
         let mut engine = Engine {
             state,
             rev: 1,
-            services: Arc::new(IdentityServices),
+            services: Arc::new(IdentityServices::default()),
             events,
             tx,
             branch_watch: BranchWatchHandle::disabled(),
@@ -8293,9 +11882,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -8347,14 +11941,115 @@ mod tests {
         }
 
         assert!(
-            saw,
-            "expected a conversation_changed event reflecting the suggestion"
+            saw,
+            "expected a conversation_changed event reflecting the suggestion"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_review_comments_sends_pending_comments_as_one_turn() {
+        let mut state = AppState::new();
+        let _ = state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/luban-server-test"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        let _ = state.apply(Action::WorkspaceCreated {
+            project_id,
+            workspace_name: "main".to_owned(),
+            branch_name: "main".to_owned(),
+            worktree_path: PathBuf::from("/tmp/luban-server-test"),
+        });
+        let workspace_id = state.projects[0].workspaces[0].id;
+        state.apply(Action::OpenWorkspace { workspace_id });
+        state.apply(Action::CreateWorkspaceThread {
+            workspace_id,
+            model_id: None,
+            thinking_effort: None,
+        });
+        let thread_id = state
+            .workspace_tabs(workspace_id)
+            .expect("workspace tabs exist after creating thread")
+            .active_tab;
+        for (start_line, body) in [(3, "Use a const."), (8, "Already fine.")] {
+            state.apply(Action::AddReviewComment {
+                workspace_id,
+                thread_id,
+                path: "src/lib.rs".to_owned(),
+                start_line,
+                end_line: start_line,
+                quote: "let x = 1;".to_owned(),
+                body: body.to_owned(),
+            });
+        }
+        state.apply(Action::ResolveReviewComment {
+            workspace_id,
+            thread_id,
+            comment_id: 2,
+            resolved: true,
+        });
+
+        let (events, _) = broadcast::channel::<WsServerMessage>(16);
+        let (tx, _rx_cmd) = mpsc::channel::<EngineCommand>(16);
+        let mut engine = Engine {
+            state,
+            rev: 1,
+            services: Arc::new(IdentityServices::default()),
+            events,
+            tx,
+            branch_watch: BranchWatchHandle::disabled(),
+            task_document_watch: TaskDocumentWatchHandle::disabled(),
+            cancel_flags: HashMap::new(),
+            pull_requests: HashMap::new(),
+            pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
+            workspace_threads_cache: HashMap::new(),
+            auto_archive_workspaces: HashSet::new(),
+            telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
+        };
+
+        engine
+            .send_review_comments(workspace_id, thread_id, Some("Review notes".to_owned()))
+            .await
+            .expect("send should succeed");
+
+        let prompt = engine
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("conversation exists")
+            .entries
+            .iter()
+            .rev()
+            .find_map(|entry| match entry {
+                ConversationEntry::UserEvent {
+                    event: luban_domain::UserEvent::Message { text, .. },
+                    ..
+                } => Some(text.clone()),
+                _ => None,
+            })
+            .expect("review turn was sent");
+        assert!(
+            prompt.starts_with("Review notes\n\n1. src/lib.rs:3\n> let x = 1;\nUse a const.\n")
         );
+        assert!(!prompt.contains("Already fine."));
+        let comments = engine.state.task_review_comments(workspace_id, thread_id);
+        assert!(comments[0].sent);
+        assert!(!comments[1].sent);
+
+        let err = engine
+            .send_review_comments(workspace_id, thread_id, None)
+            .await
+            .expect_err("nothing left to send");
+        assert_eq!(err, "no pending review comments");
     }
 
     #[tokio::test]
     async fn add_project_reuses_existing_by_github_repo() {
-        let (engine, _events) = Engine::start(Arc::new(IdentityServices));
+        let (engine, _events) = Engine::start(Arc::new(IdentityServices::default()));
         engine
             .apply_client_action(
                 "req-1".to_owned(),
@@ -8384,9 +12079,32 @@ mod tests {
     struct ArchiveOkServices {
         calls: Arc<std::sync::Mutex<Vec<(PathBuf, PathBuf)>>>,
         cancel_flag: Option<Arc<AtomicBool>>,
+        audit_actions: Arc<std::sync::Mutex<Vec<String>>>,
     }
 
     impl ProjectWorkspaceService for ArchiveOkServices {
+        fn append_audit_entry(
+            &self,
+            origin: AuditOrigin,
+            record: luban_domain::AuditRecord,
+        ) -> Result<luban_domain::AuditEntry, String> {
+            self.audit_actions
+                .lock()
+                .expect("mutex poisoned")
+                .push(record.action.clone());
+            Ok(luban_domain::AuditEntry {
+                id: 1,
+                created_at_unix_ms: 0,
+                origin,
+                action: record.action,
+                summary: record.summary,
+                workspace_id: record.workspace_id,
+                thread_id: record.thread_id,
+                undo: record.undo,
+                undone_at_unix_ms: None,
+            })
+        }
+
         fn load_app_state(&self) -> Result<PersistedAppState, String> {
             Ok(PersistedAppState {
                 projects: Vec::new(),
@@ -8425,6 +12143,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             })
         }
 
@@ -8608,6 +12336,7 @@ mod tests {
         let services: Arc<dyn ProjectWorkspaceService> = Arc::new(ArchiveOkServices {
             calls: calls.clone(),
             cancel_flag: None,
+            audit_actions: Arc::default(),
         });
 
         let mut state = AppState::new();
@@ -8647,9 +12376,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -8675,6 +12409,59 @@ mod tests {
         assert_eq!(calls[0].1, worktree_path);
     }
 
+    #[tokio::test]
+    async fn rejected_actions_are_not_recorded_in_the_audit_log() {
+        let audit_actions = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let services: Arc<dyn ProjectWorkspaceService> = Arc::new(ArchiveOkServices {
+            calls: Arc::default(),
+            cancel_flag: None,
+            audit_actions: audit_actions.clone(),
+        });
+
+        let mut state = AppState::new();
+        state.last_error = Some("earlier error".to_owned());
+        let (events, _) = broadcast::channel::<WsServerMessage>(16);
+        let (tx, _rx) = mpsc::channel::<EngineCommand>(16);
+        let mut engine = Engine {
+            state,
+            rev: 1,
+            services,
+            events,
+            tx,
+            branch_watch: BranchWatchHandle::disabled(),
+            task_document_watch: TaskDocumentWatchHandle::disabled(),
+            cancel_flags: HashMap::new(),
+            pull_requests: HashMap::new(),
+            pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
+            workspace_threads_cache: HashMap::new(),
+            auto_archive_workspaces: HashSet::new(),
+            telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
+        };
+
+        engine
+            .process_action_queue(Action::DeletionRetentionChanged {
+                retention_days: luban_domain::DeletionSettings::MAX_RETENTION_DAYS + 1,
+            })
+            .await;
+        assert_eq!(
+            engine.state.last_error.as_deref(),
+            Some("Invalid deletion retention")
+        );
+        assert!(audit_actions.lock().expect("mutex poisoned").is_empty());
+
+        engine.state.last_error = Some("earlier error".to_owned());
+        engine
+            .process_action_queue(Action::DeletionRetentionChanged { retention_days: 14 })
+            .await;
+        assert_eq!(engine.state.last_error.as_deref(), Some("earlier error"));
+        assert_eq!(audit_actions.lock().expect("mutex poisoned").len(), 1);
+    }
+
     #[tokio::test]
     async fn archive_workspace_cancels_agent_turns_before_archiving() {
         let calls = Arc::new(std::sync::Mutex::new(Vec::<(PathBuf, PathBuf)>::new()));
@@ -8682,6 +12469,7 @@ mod tests {
         let services: Arc<dyn ProjectWorkspaceService> = Arc::new(ArchiveOkServices {
             calls: calls.clone(),
             cancel_flag: Some(cancel_flag.clone()),
+            audit_actions: Arc::default(),
         });
 
         let mut state = AppState::new();
@@ -8746,9 +12534,14 @@ mod tests {
             )]),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -8812,6 +12605,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             })
         }
 
@@ -8915,10 +12718,242 @@ mod tests {
             &self,
             _project_slug: String,
             _workspace_name: String,
-            _text: String,
-            _extension: String,
+            _text: String,
+            _extension: String,
+        ) -> Result<AttachmentRef, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn store_context_file(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+            _source_path: PathBuf,
+        ) -> Result<AttachmentRef, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn record_context_item(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+            _attachment: AttachmentRef,
+            _created_at_unix_ms: u64,
+        ) -> Result<u64, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn list_context_items(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+        ) -> Result<Vec<ContextItem>, String> {
+            Ok(Vec::new())
+        }
+
+        fn delete_context_item(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+            _context_id: u64,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn run_agent_turn_streamed(
+            &self,
+            _request: luban_domain::RunAgentTurnRequest,
+            _cancel: Arc<AtomicBool>,
+            _on_event: Arc<dyn Fn(luban_domain::AgentThreadEvent) + Send + Sync>,
+        ) -> Result<(), String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn gh_is_authorized(&self) -> Result<bool, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn gh_pull_request_info(
+            &self,
+            _worktree_path: PathBuf,
+        ) -> Result<Option<PullRequestInfo>, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn gh_open_pull_request(&self, _worktree_path: PathBuf) -> Result<(), String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn gh_open_pull_request_failed_action(
+            &self,
+            _worktree_path: PathBuf,
+        ) -> Result<(), String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn project_identity(
+            &self,
+            _path: PathBuf,
+        ) -> Result<luban_domain::ProjectIdentity, String> {
+            Err("unimplemented".to_owned())
+        }
+    }
+
+    #[derive(Default)]
+    struct PullRequestReviewServices {
+        replies: Arc<std::sync::Mutex<Vec<(u64, u64, String)>>>,
+        stored_logs: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl ProjectWorkspaceService for PullRequestReviewServices {
+        fn load_app_state(&self) -> Result<PersistedAppState, String> {
+            Ok(PersistedAppState {
+                projects: Vec::new(),
+                sidebar_width: None,
+                terminal_pane_width: None,
+                global_zoom_percent: None,
+                appearance_theme: None,
+                appearance_ui_font: None,
+                appearance_chat_font: None,
+                appearance_code_font: None,
+                appearance_terminal_font: None,
+                agent_default_model_id: None,
+                agent_runner_default_models: HashMap::new(),
+                agent_default_thinking_effort: None,
+                agent_default_runner: None,
+                agent_amp_mode: None,
+                agent_codex_enabled: Some(true),
+                agent_amp_enabled: Some(true),
+                agent_claude_enabled: Some(true),
+                agent_droid_enabled: Some(true),
+                last_open_workspace_id: None,
+                open_button_selection: None,
+                sidebar_project_order: Vec::new(),
+                workspace_active_thread_id: HashMap::new(),
+                workspace_open_tabs: HashMap::new(),
+                workspace_archived_tabs: HashMap::new(),
+                workspace_next_thread_id: HashMap::new(),
+                workspace_chat_scroll_y10: HashMap::new(),
+                workspace_chat_scroll_anchor: HashMap::new(),
+                workspace_unread_completions: HashMap::new(),
+                workspace_thread_run_config_overrides: HashMap::new(),
+                starred_tasks: HashMap::new(),
+                task_prompt_templates: HashMap::new(),
+                telegram_enabled: None,
+                telegram_bot_token: None,
+                telegram_bot_username: None,
+                telegram_paired_chat_id: None,
+                telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
+            })
+        }
+
+        fn save_app_state(&self, _snapshot: PersistedAppState) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn create_workspace(
+            &self,
+            _project_path: PathBuf,
+            _project_slug: String,
+            _branch_name_hint: Option<String>,
+        ) -> Result<luban_domain::CreatedWorkspace, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn open_workspace_in_ide(&self, _worktree_path: PathBuf) -> Result<(), String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn archive_workspace(
+            &self,
+            _project_path: PathBuf,
+            _worktree_path: PathBuf,
+            _branch_name: String,
+        ) -> Result<(), String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn rename_workspace_branch(
+            &self,
+            _worktree_path: PathBuf,
+            _requested_branch_name: String,
+        ) -> Result<String, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn ensure_conversation(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+            _thread_id: u64,
+        ) -> Result<(), String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn list_conversation_threads(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+        ) -> Result<Vec<ConversationThreadMeta>, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn load_conversation(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+            _thread_id: u64,
+        ) -> Result<DomainConversationSnapshot, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn load_conversation_page(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+            _thread_id: u64,
+            _before: Option<u64>,
+            _limit: u64,
+        ) -> Result<DomainConversationSnapshot, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn store_context_image(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+            _image: ContextImage,
+        ) -> Result<AttachmentRef, String> {
+            Err("unimplemented".to_owned())
+        }
+
+        fn store_context_text(
+            &self,
+            _project_slug: String,
+            _workspace_name: String,
+            text: String,
+            extension: String,
         ) -> Result<AttachmentRef, String> {
-            Err("unimplemented".to_owned())
+            let byte_len = text.len() as u64;
+            self.stored_logs.lock().expect("mutex poisoned").push(text);
+            Ok(AttachmentRef {
+                id: "ci-log".to_owned(),
+                kind: AttachmentKind::Text,
+                name: format!("text.{extension}"),
+                extension,
+                mime: None,
+                byte_len,
+            })
         }
 
         fn store_context_file(
@@ -8974,7 +13009,58 @@ mod tests {
             &self,
             _worktree_path: PathBuf,
         ) -> Result<Option<PullRequestInfo>, String> {
-            Err("unimplemented".to_owned())
+            Ok(Some(PullRequestInfo {
+                number: 7,
+                is_draft: false,
+                state: DomainPullRequestState::Open,
+                ci_state: None,
+                merge_ready: false,
+            }))
+        }
+
+        fn pull_request_review_comments(
+            &self,
+            _worktree_path: PathBuf,
+            pr_number: u64,
+        ) -> Result<Vec<luban_domain::PullRequestReviewComment>, String> {
+            assert_eq!(pr_number, 7);
+            Ok(vec![luban_domain::PullRequestReviewComment {
+                comment_id: 501,
+                author: "octocat".to_owned(),
+                path: "src/lib.rs".to_owned(),
+                start_line: 4,
+                end_line: 4,
+                body: "Handle the error here.".to_owned(),
+                url: "https://github.com/acme/app/pull/7#discussion_r501".to_owned(),
+                diff_hunk: "@@ -1,4 +1,4 @@\n+let v = parse().unwrap();".to_owned(),
+            }])
+        }
+
+        fn ci_failure_log(
+            &self,
+            _worktree_path: PathBuf,
+            _pr_number: u64,
+        ) -> Result<Option<luban_domain::CiFailureLog>, String> {
+            Ok(Some(luban_domain::CiFailureLog {
+                run_id: 900,
+                workflow: "CI".to_owned(),
+                url: "https://github.com/acme/app/actions/runs/900".to_owned(),
+                log: "== test / cargo test ==\nerror[E0425]: cannot find value `x`\n".to_owned(),
+            }))
+        }
+
+        fn pull_request_review_comment_reply(
+            &self,
+            _worktree_path: PathBuf,
+            pr_number: u64,
+            comment_id: u64,
+            body: String,
+        ) -> Result<(), String> {
+            self.replies
+                .lock()
+                .expect("mutex poisoned")
+                .push((pr_number, comment_id, body));
+            Ok(())
         }
 
         fn gh_open_pull_request(&self, _worktree_path: PathBuf) -> Result<(), String> {
@@ -8996,6 +13082,329 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn pulled_review_comments_import_once_and_reply_when_resolved() {
+        let mut state = AppState::new();
+        let _ = state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/luban-server-test"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        let _ = state.apply(Action::WorkspaceCreated {
+            project_id,
+            workspace_name: "main".to_owned(),
+            branch_name: "main".to_owned(),
+            worktree_path: PathBuf::from("/tmp/luban-server-test"),
+        });
+        let workspace_id = state.projects[0].workspaces[0].id;
+        state.apply(Action::OpenWorkspace { workspace_id });
+        state.apply(Action::CreateWorkspaceThread {
+            workspace_id,
+            model_id: None,
+            thinking_effort: None,
+        });
+        let thread_id = state
+            .workspace_tabs(workspace_id)
+            .expect("workspace tabs exist after creating thread")
+            .active_tab;
+        state.apply(Action::PullRequestReviewSyncChanged {
+            workspace_id,
+            thread_id,
+            sync: luban_domain::PullRequestReviewSync {
+                auto_pull: false,
+                reply_when_addressed: true,
+            },
+        });
+
+        let services = Arc::new(PullRequestReviewServices::default());
+        let replies = services.replies.clone();
+        let (events, _) = broadcast::channel::<WsServerMessage>(16);
+        let (tx, mut rx_cmd) = mpsc::channel::<EngineCommand>(16);
+        let mut engine = Engine {
+            state,
+            rev: 1,
+            services,
+            events,
+            tx,
+            branch_watch: BranchWatchHandle::disabled(),
+            task_document_watch: TaskDocumentWatchHandle::disabled(),
+            cancel_flags: HashMap::new(),
+            pull_requests: HashMap::new(),
+            pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
+            workspace_threads_cache: HashMap::new(),
+            auto_archive_workspaces: HashSet::new(),
+            telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
+        };
+
+        for _ in 0..2 {
+            engine
+                .pull_pull_request_review_comments(workspace_id, thread_id)
+                .await
+                .expect("pull should succeed");
+        }
+        let comments = engine.state.task_review_comments(workspace_id, thread_id);
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].quote, "+let v = parse().unwrap();");
+        assert!(comments[0].is_pending());
+        let imported_events = engine
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("conversation exists")
+            .entries
+            .iter()
+            .filter(|entry| {
+                matches!(
+                    entry,
+                    ConversationEntry::SystemEvent {
+                        event: luban_domain::ConversationSystemEvent::PullRequestReviewComment { .. },
+                        ..
+                    }
+                )
+            })
+            .count();
+        assert_eq!(imported_events, 1);
+
+        let comment_id = comments[0].id;
+        engine
+            .process_action_queue(Action::ResolveReviewComment {
+                workspace_id,
+                thread_id,
+                comment_id,
+                resolved: true,
+            })
+            .await;
+        let command = tokio::time::timeout(Duration::from_secs(5), rx_cmd.recv())
+            .await
+            .expect("reply should be posted")
+            .expect("command channel open");
+        let EngineCommand::DispatchAction { action } = command else {
+            panic!("expected a dispatched action");
+        };
+        assert!(matches!(*action, Action::ReviewCommentReplyPosted { .. }));
+        engine.process_action_queue(*action).await;
+
+        assert_eq!(
+            replies.lock().expect("mutex poisoned").as_slice(),
+            &[(7, 501, REVIEW_COMMENT_ADDRESSED_REPLY.to_owned())]
+        );
+        assert!(
+            engine.state.task_review_comments(workspace_id, thread_id)[0]
+                .pull_request
+                .as_ref()
+                .is_some_and(|origin| origin.replied)
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_ci_queues_fix_turns_until_the_attempt_budget_runs_out() {
+        let mut state = AppState::new();
+        let _ = state.apply(Action::AddProject {
+            path: PathBuf::from("/tmp/luban-server-test"),
+            is_git: true,
+        });
+        let project_id = state.projects[0].id;
+        let _ = state.apply(Action::WorkspaceCreated {
+            project_id,
+            workspace_name: "main".to_owned(),
+            branch_name: "main".to_owned(),
+            worktree_path: PathBuf::from("/tmp/luban-server-test"),
+        });
+        let workspace_id = state.projects[0].workspaces[0].id;
+        state.apply(Action::OpenWorkspace { workspace_id });
+        state.apply(Action::CreateWorkspaceThread {
+            workspace_id,
+            model_id: None,
+            thinking_effort: None,
+        });
+        let thread_id = state
+            .workspace_tabs(workspace_id)
+            .expect("workspace tabs exist after creating thread")
+            .active_tab;
+        state.apply(Action::CiAutoFixChanged {
+            workspace_id,
+            thread_id,
+            enabled: true,
+            max_attempts: 1,
+        });
+
+        let services = Arc::new(PullRequestReviewServices::default());
+        let stored_logs = services.stored_logs.clone();
+        let (events, _) = broadcast::channel::<WsServerMessage>(16);
+        let (tx, mut rx_cmd) = mpsc::channel::<EngineCommand>(16);
+        let mut engine = Engine {
+            state,
+            rev: 1,
+            services,
+            events,
+            tx,
+            branch_watch: BranchWatchHandle::disabled(),
+            task_document_watch: TaskDocumentWatchHandle::disabled(),
+            cancel_flags: HashMap::new(),
+            pull_requests: HashMap::new(),
+            pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
+            workspace_threads_cache: HashMap::new(),
+            auto_archive_workspaces: HashSet::new(),
+            telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
+        };
+        let pr_with_ci = |ci_state| {
+            Some(PullRequestInfo {
+                number: 7,
+                is_draft: false,
+                state: DomainPullRequestState::Open,
+                ci_state: Some(ci_state),
+                merge_ready: false,
+            })
+        };
+
+        engine
+            .handle(EngineCommand::PullRequestInfoUpdated {
+                workspace_id,
+                info: pr_with_ci(DomainPullRequestCiState::Failure),
+            })
+            .await;
+        let fetched = loop {
+            let command = tokio::time::timeout(Duration::from_secs(5), rx_cmd.recv())
+                .await
+                .expect("failure log should be fetched")
+                .expect("command channel open");
+            if matches!(command, EngineCommand::CiFailureLogFetched { .. }) {
+                break command;
+            }
+        };
+        engine.handle(fetched).await;
+        // A replayed log for the same run does not queue a second attempt.
+        engine
+            .handle(EngineCommand::CiFailureLogFetched {
+                workspace_id,
+                pr_number: 7,
+                log: luban_domain::CiFailureLog {
+                    run_id: 900,
+                    workflow: "CI".to_owned(),
+                    url: String::new(),
+                    log: "again".to_owned(),
+                },
+            })
+            .await;
+
+        assert_eq!(stored_logs.lock().expect("mutex poisoned").len(), 1);
+        let fix = engine
+            .state
+            .task_ci_auto_fix(workspace_id, thread_id)
+            .expect("loop exists");
+        assert_eq!(fix.attempts_used(), 1);
+        assert_eq!(fix.attempts[0].run_id, 900);
+        let conversation = engine
+            .state
+            .workspace_thread_conversation(workspace_id, thread_id)
+            .expect("conversation exists");
+        let fix_turn = conversation.entries.iter().find_map(|entry| match entry {
+            ConversationEntry::UserEvent {
+                event:
+                    luban_domain::UserEvent::Message {
+                        text, attachments, ..
+                    },
+                ..
+            } => Some((text.clone(), attachments.clone())),
+            _ => None,
+        });
+        let (text, attachments) = fix_turn.expect("fix turn was sent");
+        assert!(text.contains("PR #7 (auto-fix attempt 1 of 1)"));
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].name, CI_FAILURE_LOG_ATTACHMENT_NAME);
+
+        for ci_state in [
+            DomainPullRequestCiState::Pending,
+            DomainPullRequestCiState::Failure,
+        ] {
+            engine
+                .handle(EngineCommand::PullRequestInfoUpdated {
+                    workspace_id,
+                    info: pr_with_ci(ci_state),
+                })
+                .await;
+        }
+        let stop = loop {
+            let command = tokio::time::timeout(Duration::from_secs(5), rx_cmd.recv())
+                .await
+                .expect("loop should stop")
+                .expect("command channel open");
+            if let EngineCommand::DispatchAction { action } = command
+                && matches!(*action, Action::CiAutoFixStopped { .. })
+            {
+                break action;
+            }
+        };
+        assert!(matches!(
+            *stop,
+            Action::CiAutoFixStopped {
+                reason: CiAutoFixStopReason::AttemptsExhausted,
+                ..
+            }
+        ));
+        engine.process_action_queue(*stop).await;
+        assert!(engine.state.ci_auto_fix_tasks(workspace_id).is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_pull_request_lookup_keeps_the_last_known_pull_request() {
+        let workspace_id = WorkspaceId::from_u64(1);
+        let (events, _) = broadcast::channel::<WsServerMessage>(16);
+        let (tx, _rx_cmd) = mpsc::channel::<EngineCommand>(16);
+        let mut engine = Engine {
+            state: AppState::new(),
+            rev: 1,
+            services: Arc::new(PullRequestReviewServices::default()),
+            events,
+            tx,
+            branch_watch: BranchWatchHandle::disabled(),
+            task_document_watch: TaskDocumentWatchHandle::disabled(),
+            cancel_flags: HashMap::new(),
+            pull_requests: HashMap::new(),
+            pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
+            workspace_threads_cache: HashMap::new(),
+            auto_archive_workspaces: HashSet::new(),
+            telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
+        };
+        let info = Some(PullRequestInfo {
+            number: 7,
+            is_draft: false,
+            state: DomainPullRequestState::Open,
+            ci_state: None,
+            merge_ready: false,
+        });
+        engine
+            .handle(EngineCommand::PullRequestInfoUpdated { workspace_id, info })
+            .await;
+        let rev = engine.rev;
+
+        engine.pull_requests_in_flight.insert(workspace_id);
+        engine
+            .handle(EngineCommand::PullRequestLookupFailed {
+                workspace_id,
+                message: "401 Unauthorized".to_owned(),
+            })
+            .await;
+
+        assert!(!engine.pull_requests_in_flight.contains(&workspace_id));
+        assert_eq!(engine.pull_requests[&workspace_id].info, info);
+        assert_eq!(engine.rev, rev);
+    }
+
     #[tokio::test]
     async fn open_workspace_in_ide_runs_effect() {
         let opened = Arc::new(std::sync::Mutex::new(Vec::<PathBuf>::new()));
@@ -9033,9 +13442,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -9083,9 +13497,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -9145,6 +13564,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             })
         }
 
@@ -9357,6 +13786,16 @@ mod tests {
                 telegram_bot_username: None,
                 telegram_paired_chat_id: None,
                 telegram_topic_bindings: None,
+                telegram_group_chats: None,
+                notification_settings: None,
+                attachment_text_limits: None,
+                url_attachment_settings: None,
+                task_issue_links: None,
+                deletion_settings: None,
+                deleted_projects: None,
+                turn_limits: None,
+                task_review_comments: None,
+                task_ci_auto_fix: None,
             })
         }
 
@@ -9559,9 +13998,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         let rename = tokio::time::timeout(
@@ -9627,9 +14071,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -9640,6 +14089,7 @@ mod tests {
                 attachments: Vec::new(),
                 runner: None,
                 amp_mode: None,
+                author: None,
             })
             .await;
 
@@ -9724,9 +14174,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         let api_attachment = luban_api::AttachmentRef {
@@ -9746,6 +14201,8 @@ mod tests {
                 vec![api_attachment.clone()],
                 None,
                 None,
+                None,
+                None,
             )
             .await
             .expect("task execute prompt should succeed");
@@ -9837,9 +14294,14 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
         engine
@@ -9866,6 +14328,7 @@ mod tests {
                 attachments: Vec::new(),
                 runner: None,
                 amp_mode: None,
+                author: None,
             })
             .await;
 
@@ -9909,12 +14372,25 @@ mod tests {
             cancel_flags: HashMap::new(),
             pull_requests: HashMap::new(),
             pull_requests_in_flight: HashSet::new(),
+            disk_usage: HashMap::new(),
+            disk_usage_in_flight: false,
             workspace_threads_cache: HashMap::new(),
             auto_archive_workspaces: HashSet::new(),
             telegram_pairing: None,
+            task_bulk: TaskBulkState::default(),
+            action_origin: AuditOrigin::System,
+            audit_suppressed: false,
         };
 
-        engine.reconcile_stale_running_turns().await;
+        let interrupted = engine.reconcile_stale_running_turns().await;
+        assert_eq!(
+            interrupted,
+            vec![InterruptedTurn {
+                thread_id: WorkspaceThreadId::from_u64(1),
+                resumable: false,
+                queue_paused: false,
+            }]
+        );
 
         let appended = services.appended_entries.lock().expect("mutex ok").clone();
         assert!(
@@ -9927,6 +14403,16 @@ mod tests {
             )),
             "expected reconcile to append a turn_error entry"
         );
+        assert!(appended.iter().any(|e| matches!(
+            e,
+            ConversationEntry::SystemEvent {
+                event: luban_domain::ConversationSystemEvent::TurnInterrupted {
+                    resumable: false,
+                    ..
+                },
+                ..
+            }
+        )));
 
         let saved = services.saved_queue_state.lock().expect("mutex ok").clone();
         assert_eq!(saved.len(), 1);
@@ -9938,6 +14424,71 @@ mod tests {
         assert_eq!(pending[0].text, "queued");
     }
 
+    #[test]
+    fn reconcile_marks_turns_with_recorded_session_as_resumable() {
+        let services = ReconcileRecordingServices {
+            remote_thread_id: Some("session_123".to_owned()),
+            ..Default::default()
+        };
+
+        let interrupted = reconcile_stale_running_turns_blocking(
+            &services,
+            "repo".to_owned(),
+            "main".to_owned(),
+            42,
+        )
+        .expect("reconcile ok");
+        assert_eq!(interrupted.len(), 1);
+        assert!(interrupted[0].resumable);
+        assert!(!interrupted[0].queue_paused);
+
+        let appended = services.appended_entries.lock().expect("mutex ok").clone();
+        assert!(appended.iter().any(|e| matches!(
+            e,
+            ConversationEntry::SystemEvent {
+                created_at_unix_ms: 42,
+                event: luban_domain::ConversationSystemEvent::TurnInterrupted {
+                    resumable: true,
+                    ..
+                },
+                ..
+            }
+        )));
+
+        let saved = services.saved_queue_state.lock().expect("mutex ok").clone();
+        assert!(saved[0].0, "queue stays paused until the turn is resumed");
+    }
+
+    #[test]
+    fn interrupted_turn_resume_keeps_user_paused_queue() {
+        let services = ReconcileRecordingServices {
+            remote_thread_id: Some("session_123".to_owned()),
+            ..Default::default()
+        };
+        let mut snapshot = services
+            .load_conversation("repo".to_owned(), "main".to_owned(), 1)
+            .expect("snapshot");
+        assert!(interrupted_turn_resumes_queue(&snapshot).is_err());
+
+        let interrupted = |queue_paused| ConversationEntry::SystemEvent {
+            entry_id: "e_2".to_owned(),
+            created_at_unix_ms: 2,
+            event: luban_domain::ConversationSystemEvent::TurnInterrupted {
+                resumable: true,
+                queue_paused,
+            },
+        };
+        snapshot.entries.push(interrupted(false));
+        assert_eq!(interrupted_turn_resumes_queue(&snapshot), Ok(true));
+
+        snapshot.entries.pop();
+        snapshot.entries.push(interrupted(true));
+        assert_eq!(interrupted_turn_resumes_queue(&snapshot), Ok(false));
+
+        snapshot.thread_id = None;
+        assert!(interrupted_turn_resumes_queue(&snapshot).is_err());
+    }
+
     fn persisted_with_single_git_workspace(workspace_id: u64) -> PersistedAppState {
         PersistedAppState {
             projects: vec![PersistedProject {
@@ -9954,6 +14505,7 @@ mod tests {
                     worktree_path: PathBuf::from("/tmp/luban-engine-bootstrap/dev"),
                     status: WorkspaceStatus::Active,
                     last_activity_at_unix_seconds: None,
+                    archived_at_unix_seconds: None,
                 }],
             }],
             sidebar_width: None,
@@ -9991,6 +14543,16 @@ mod tests {
             telegram_bot_username: None,
             telegram_paired_chat_id: None,
             telegram_topic_bindings: None,
+            telegram_group_chats: None,
+            notification_settings: None,
+            attachment_text_limits: None,
+            url_attachment_settings: None,
+            task_issue_links: None,
+            deletion_settings: None,
+            deleted_projects: None,
+            turn_limits: None,
+            task_review_comments: None,
+            task_ci_auto_fix: None,
         }
     }
 
@@ -10067,6 +14629,7 @@ mod tests {
                 task_status_last_analyzed_message_seq: 0,
                 turn_status: luban_domain::TurnStatus::Idle,
                 last_turn_result: Some(luban_domain::TurnResult::Completed),
+                forked_from: None,
             }])
         }
 
@@ -10220,4 +14783,32 @@ mod tests {
             .expect("snapshot should succeed");
         assert_eq!(snap.projects.len(), 1);
     }
+
+    #[test]
+    fn disk_cleanup_keeps_going_past_failed_targets() {
+        let report = run_disk_cleanup(vec![1u64, 2, 3], |n| {
+            if n == 2 {
+                Err("boom".to_owned())
+            } else {
+                Ok(DiskCleanupReport {
+                    removed_entries: 1,
+                    freed_bytes: n * 1024 * 1024,
+                })
+            }
+        })
+        .expect("partial cleanup should succeed");
+        assert_eq!(report.removed_entries, 2);
+        assert_eq!(
+            disk_cleanup_message(report),
+            "Freed 4.0 MB (2 entries removed)"
+        );
+
+        let failed = run_disk_cleanup(vec![1u64], |_| Err("boom".to_owned()));
+        assert_eq!(failed, Err("boom".to_owned()));
+
+        let empty = run_disk_cleanup(Vec::<u64>::new(), |_| Err("unreachable".to_owned()));
+        assert_eq!(disk_cleanup_message(empty.unwrap()), "Nothing to clean up");
+        assert_eq!(format_byte_size(512), "512 B");
+        assert_eq!(format_byte_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
 }
@@ -3,6 +3,10 @@ use luban_api::{
     ChangedFileSnapshot, DiffFileContents, FileChangeGroup, FileChangeStatus,
     WorkspaceDiffFileSnapshot,
 };
+use luban_domain::{
+    ProjectWorkspaceService, WorkspaceId, WorkspaceThreadId, turn_checkpoint_ref,
+    turn_end_checkpoint_ref,
+};
 use std::{ffi::OsStr, path::Path, process::Command};
 
 fn run_git_bytes<I, S>(repo_path: &Path, args: I) -> anyhow::Result<Vec<u8>>
@@ -61,7 +65,7 @@ fn is_runtime_internal_path(path: &str) -> bool {
     normalized == ".luban" || normalized.starts_with(".luban/")
 }
 
-fn upstream_ref(repo_path: &Path) -> Option<String> {
+pub fn upstream_ref(repo_path: &Path) -> Option<String> {
     run_git_text(
         repo_path,
         ["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
@@ -434,6 +438,174 @@ pub fn collect_changes(repo_path: &Path) -> anyhow::Result<Vec<ChangedFileSnapsh
     Ok(staged_unstaged)
 }
 
+/// Unified patch of the workspace against its upstream (or `HEAD` when no upstream is set).
+pub fn collect_patch(repo_path: &Path) -> anyhow::Result<String> {
+    let base = upstream_ref(repo_path).unwrap_or_else(|| "HEAD".to_owned());
+    let out = run_git_bytes(
+        repo_path,
+        [
+            "diff",
+            "--find-renames",
+            base.as_str(),
+            "--",
+            ".",
+            ":(exclude).luban",
+        ],
+    )?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Line and file counts of the changes between two revisions.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DiffStat {
+    pub files_changed: u64,
+    pub additions: u64,
+    pub deletions: u64,
+}
+
+/// Counts the changes between two trees or commits, such as a turn checkpoint and the
+/// worktree as that turn left it. Binary files count as changed without lines.
+pub fn diff_stat(repo_path: &Path, from: &str, to: &str) -> anyhow::Result<DiffStat> {
+    let out = run_git_text(
+        repo_path,
+        ["diff-tree", "-r", "--numstat", "--no-renames", from, to],
+    )?;
+    let mut stat = DiffStat::default();
+    for line in out.lines() {
+        let mut parts = line.splitn(3, '\t');
+        let (Some(add), Some(del), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        if is_runtime_internal_path(path) {
+            continue;
+        }
+        stat.files_changed += 1;
+        stat.additions += add.parse::<u64>().unwrap_or(0);
+        stat.deletions += del.parse::<u64>().unwrap_or(0);
+    }
+    Ok(stat)
+}
+
+/// Unified patch between two trees or commits.
+pub fn collect_range_patch(repo_path: &Path, from: &str, to: &str) -> anyhow::Result<String> {
+    let out = run_git_bytes(
+        repo_path,
+        [
+            "diff",
+            "--find-renames",
+            from,
+            to,
+            "--",
+            ".",
+            ":(exclude).luban",
+        ],
+    )?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// File-level diff between two trees or commits. Files are reported in the `committed`
+/// group since both sides are fixed revisions.
+pub fn collect_range_diff(
+    repo_path: &Path,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Vec<WorkspaceDiffFileSnapshot>> {
+    let out = run_git_text(
+        repo_path,
+        [
+            "diff-tree",
+            "-r",
+            "--name-status",
+            "--find-renames",
+            from,
+            to,
+        ],
+    )?;
+    let mut files = out
+        .lines()
+        .filter_map(|line| parse_name_status_line(FileChangeGroup::Committed, line, Some(from)))
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut diffs = Vec::with_capacity(files.len());
+    for mut file in files {
+        let old_path = file.old_path.clone().unwrap_or_else(|| file.path.clone());
+        let mut args = vec![
+            "diff".to_owned(),
+            "--numstat".to_owned(),
+            "--find-renames".to_owned(),
+            from.to_owned(),
+            to.to_owned(),
+            "--".to_owned(),
+            file.path.clone(),
+        ];
+        if old_path != file.path {
+            args.push(old_path.clone());
+        }
+        let numstat = run_git_text(repo_path, &args).unwrap_or_default();
+        let mut parts = numstat.lines().next().unwrap_or("").split('\t');
+        file.additions = parts.next().and_then(|v| v.parse::<u64>().ok());
+        file.deletions = parts.next().and_then(|v| v.parse::<u64>().ok());
+
+        let old_contents = match file.status {
+            FileChangeStatus::Added => String::new(),
+            _ => git_show_commit_utf8(repo_path, from, &old_path),
+        };
+        let new_contents = match file.status {
+            FileChangeStatus::Deleted => String::new(),
+            _ => git_show_commit_utf8(repo_path, to, &file.path),
+        };
+        diffs.push(WorkspaceDiffFileSnapshot {
+            old_file: DiffFileContents {
+                name: file.name.clone(),
+                contents: old_contents,
+            },
+            new_file: DiffFileContents {
+                name: file.name.clone(),
+                contents: new_contents,
+            },
+            file,
+        });
+    }
+    Ok(diffs)
+}
+
+fn resolve_revision(repo_path: &Path, spec: &str) -> Option<String> {
+    run_git_text(repo_path, ["rev-parse", "--verify", "--quiet", spec])
+        .ok()
+        .filter(|rev| !rev.is_empty())
+}
+
+/// Trees bounding the turns `from` through `to` of a task: the checkpoint taken before `from`
+/// started and the snapshot taken when `to` finished. A turn that has no end snapshot yet,
+/// because it is still running, ends at the current worktree. Returns `None` when either
+/// checkpoint is unknown.
+pub fn turn_range_trees(
+    services: &dyn ProjectWorkspaceService,
+    worktree_path: &Path,
+    workspace_id: WorkspaceId,
+    thread_id: WorkspaceThreadId,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Option<(String, String)>> {
+    let start_ref = turn_checkpoint_ref(workspace_id, thread_id, from);
+    let Some(start) = resolve_revision(worktree_path, &format!("{start_ref}^{{tree}}")) else {
+        return Ok(None);
+    };
+    let end_ref = turn_end_checkpoint_ref(workspace_id, thread_id, to);
+    if let Some(end) = resolve_revision(worktree_path, &format!("{end_ref}^{{tree}}")) {
+        return Ok(Some((start, end)));
+    }
+    let to_ref = turn_checkpoint_ref(workspace_id, thread_id, to);
+    if resolve_revision(worktree_path, &to_ref).is_none() {
+        return Ok(None);
+    }
+    let end = services
+        .snapshot_worktree_tree(worktree_path.to_path_buf())
+        .map_err(|message| anyhow!(message))?;
+    Ok(Some((start, end)))
+}
+
 pub fn collect_diff(repo_path: &Path) -> anyhow::Result<Vec<WorkspaceDiffFileSnapshot>> {
     let upstream = upstream_ref(repo_path);
     let mut files = collect_changes(repo_path)?;
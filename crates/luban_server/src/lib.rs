@@ -1,17 +1,24 @@
 use anyhow::Context as _;
 use axum::Router;
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 mod auth;
 mod branch_watch;
+pub mod desktop_notify;
+mod email;
 pub mod engine;
 mod git_changes;
 mod idempotency;
+pub mod mcp;
 mod mentions;
 mod project_avatars;
 pub mod pty;
+mod relay;
 pub mod server;
 pub mod shell_env;
+mod slack;
+mod smtp;
 mod task_document_watch;
 mod telegram;
 
@@ -36,9 +43,12 @@ impl Default for AuthConfig {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Default)]
 pub struct ServerConfig {
     pub auth: AuthConfig,
+    /// Native notifications provided by an embedding shell; without one the server falls back to
+    /// the host's notification helper.
+    pub notification_presenter: Option<Arc<dyn desktop_notify::NotificationPresenter>>,
 }
 
 impl ServerConfig {
@@ -0,0 +1,895 @@
+//! Model Context Protocol (MCP) server exposing Luban projects and tasks as tools.
+//!
+//! The same JSON-RPC dispatcher backs two transports:
+//! - streamable HTTP via `POST /api/mcp` on the main router
+//! - stdio via `luban mcp`, either against an in-process engine or proxied to a running server
+//!
+//! Tools map onto existing `ClientAction`s and query endpoints, so MCP clients observe the same
+//! behavior as the web UI.
+
+use crate::engine::{Engine, EngineHandle, new_default_services};
+use anyhow::Context as _;
+use luban_api::WsServerMessage;
+use luban_domain::{AuditOrigin, TaskDocumentKind as DomainTaskDocumentKind};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader};
+use tokio::sync::broadcast;
+
+pub const MCP_PROTOCOL_VERSION: &str = "2025-03-26";
+
+const JSONRPC_PARSE_ERROR: i64 = -32700;
+const JSONRPC_INVALID_REQUEST: i64 = -32600;
+const JSONRPC_METHOD_NOT_FOUND: i64 = -32601;
+const JSONRPC_INVALID_PARAMS: i64 = -32602;
+
+const TRANSCRIPT_DEFAULT_LIMIT: u64 = 200;
+
+#[derive(Clone)]
+pub(crate) struct McpContext {
+    engine: EngineHandle,
+    events: broadcast::Sender<WsServerMessage>,
+}
+
+impl McpContext {
+    pub(crate) fn new(engine: EngineHandle, events: broadcast::Sender<WsServerMessage>) -> Self {
+        Self { engine, events }
+    }
+}
+
+fn jsonrpc_result(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn jsonrpc_error(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.into() },
+    })
+}
+
+pub(crate) fn parse_error_response(message: impl Into<String>) -> Value {
+    jsonrpc_error(Value::Null, JSONRPC_PARSE_ERROR, message)
+}
+
+/// Rejects HTTP requests a browser could send cross-site: bodies not declared as JSON (a
+/// `text/plain` POST needs no CORS preflight) and requests from origins other than this server or
+/// a loopback host. The streamable-HTTP transport requires servers to validate `Origin`.
+pub(crate) fn check_http_request(
+    headers: &axum::http::HeaderMap,
+) -> Result<(), (axum::http::StatusCode, &'static str)> {
+    let content_type = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(str::trim)
+        .unwrap_or_default();
+    if !content_type.eq_ignore_ascii_case("application/json") {
+        return Err((
+            axum::http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "mcp requests must be application/json",
+        ));
+    }
+
+    let Some(origin) = headers.get(axum::http::header::ORIGIN) else {
+        return Ok(());
+    };
+    let host = headers
+        .get(axum::http::header::HOST)
+        .and_then(|v| v.to_str().ok());
+    let allowed = origin
+        .to_str()
+        .ok()
+        .is_some_and(|origin| is_allowed_origin(origin, host));
+    if allowed {
+        Ok(())
+    } else {
+        Err((axum::http::StatusCode::FORBIDDEN, "origin not allowed"))
+    }
+}
+
+/// Same-origin requests (the web UI served by this server), the desktop shell's webview and
+/// loopback hosts.
+fn is_allowed_origin(origin: &str, host: Option<&str>) -> bool {
+    let Some((_, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    if host.is_some_and(|host| host.eq_ignore_ascii_case(authority)) {
+        return true;
+    }
+    let host_name = match authority.strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map(|(name, _)| name).unwrap_or(rest),
+        None => authority.split(':').next().unwrap_or_default(),
+    }
+    .to_ascii_lowercase();
+    host_name == "localhost"
+        || host_name.ends_with(".localhost")
+        || host_name
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Handles a single JSON-RPC message or batch. Returns `None` when nothing should be sent back
+/// (notifications and client responses).
+pub(crate) async fn handle_message(ctx: &McpContext, message: Value) -> Option<Value> {
+    match message {
+        Value::Array(items) => {
+            if items.is_empty() {
+                return Some(jsonrpc_error(
+                    Value::Null,
+                    JSONRPC_INVALID_REQUEST,
+                    "empty batch",
+                ));
+            }
+            let mut out = Vec::new();
+            for item in items {
+                if let Some(response) = handle_single(ctx, item).await {
+                    out.push(response);
+                }
+            }
+            if out.is_empty() {
+                None
+            } else {
+                Some(Value::Array(out))
+            }
+        }
+        other => handle_single(ctx, other).await,
+    }
+}
+
+async fn handle_single(ctx: &McpContext, message: Value) -> Option<Value> {
+    let Value::Object(message) = message else {
+        return Some(jsonrpc_error(
+            Value::Null,
+            JSONRPC_INVALID_REQUEST,
+            "expected a JSON-RPC object",
+        ));
+    };
+
+    let id = message.get("id").cloned();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        // Responses to server-initiated requests; this server never sends any.
+        return id.map(|id| jsonrpc_error(id, JSONRPC_INVALID_REQUEST, "missing method"));
+    };
+    // Notifications carry no id and never get a response.
+    let id = id?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let response = match method {
+        "initialize" => jsonrpc_result(id, initialize_result()),
+        "ping" => jsonrpc_result(id, json!({})),
+        "tools/list" => jsonrpc_result(id, json!({ "tools": tool_definitions() })),
+        "tools/call" => {
+            let Some(name) = params.get("name").and_then(Value::as_str) else {
+                return Some(jsonrpc_error(
+                    id,
+                    JSONRPC_INVALID_PARAMS,
+                    "missing tool name",
+                ));
+            };
+            let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+            match call_tool(ctx, name, &arguments).await {
+                Some(Ok(text)) => jsonrpc_result(id, tool_result(text, false)),
+                Some(Err(message)) => jsonrpc_result(id, tool_result(message, true)),
+                None => jsonrpc_error(id, JSONRPC_INVALID_PARAMS, format!("unknown tool: {name}")),
+            }
+        }
+        other => jsonrpc_error(
+            id,
+            JSONRPC_METHOD_NOT_FOUND,
+            format!("method not found: {other}"),
+        ),
+    };
+    Some(response)
+}
+
+fn initialize_result() -> Value {
+    json!({
+        "protocolVersion": MCP_PROTOCOL_VERSION,
+        "capabilities": { "tools": { "listChanged": false } },
+        "serverInfo": { "name": "luban", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Luban manages coding-agent tasks. Each task lives in a workdir (a git worktree) of a project. Use list_projects and list_tasks to discover ids, create_task to start work, send_message to continue a task, and get_transcript, read_task_document and get_workdir_diff to inspect progress.",
+    })
+}
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+fn tool_definitions() -> Value {
+    let workdir_id = json!({ "type": "integer", "description": "Workdir id (from list_projects or list_tasks)." });
+    let task_id = json!({ "type": "integer", "description": "Task id within the workdir." });
+    json!([
+        {
+            "name": "list_projects",
+            "description": "List projects and their workdirs.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "list_tasks",
+            "description": "List tasks across active workdirs.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project_id": { "type": "string", "description": "Only include tasks of this project." },
+                    "task_status": { "type": "string", "description": "Comma-separated task statuses (backlog, todo, iterating, validating, done, canceled)." },
+                },
+            },
+        },
+        {
+            "name": "create_task",
+            "description": "Create a task in a workdir. By default the prompt is sent to the agent immediately.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "workdir_id": workdir_id,
+                    "prompt": { "type": "string" },
+                    "start": { "type": "boolean", "description": "Start the agent right away (default true). When false the task is only created." },
+                },
+                "required": ["workdir_id", "prompt"],
+            },
+        },
+        {
+            "name": "send_message",
+            "description": "Send a message to a task's agent. Queued if the agent is busy.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "workdir_id": workdir_id, "task_id": task_id, "text": { "type": "string" } },
+                "required": ["workdir_id", "task_id", "text"],
+            },
+        },
+        {
+            "name": "get_transcript",
+            "description": "Read the conversation transcript of a task as plain text.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "workdir_id": workdir_id,
+                    "task_id": task_id,
+                    "limit": { "type": "integer", "description": "Maximum number of trailing entries (default 200)." },
+                },
+                "required": ["workdir_id", "task_id"],
+            },
+        },
+        {
+            "name": "read_task_document",
+            "description": "Read a task document (TASK.md, PLAN.md or MEMORY.md).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "workdir_id": workdir_id,
+                    "task_id": task_id,
+                    "kind": { "type": "string", "enum": ["task", "plan", "memory"] },
+                },
+                "required": ["workdir_id", "task_id", "kind"],
+            },
+        },
+        {
+            "name": "get_workdir_diff",
+            "description": "Unified diff of a workdir against its upstream branch (or HEAD).",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "workdir_id": workdir_id },
+                "required": ["workdir_id"],
+            },
+        },
+    ])
+}
+
+fn arg_u64(args: &Value, key: &str) -> Result<u64, String> {
+    args.get(key)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| format!("missing or invalid argument: {key}"))
+}
+
+fn arg_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("missing or invalid argument: {key}"))
+}
+
+fn arg_opt_str<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn to_pretty_json(value: &impl serde::Serialize) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|err| err.to_string())
+}
+
+fn serde_key(value: &impl serde::Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+fn new_request_id() -> String {
+    format!("mcp-{}", ulid::Ulid::new())
+}
+
+/// Returns `None` when `name` is not a known tool.
+async fn call_tool(ctx: &McpContext, name: &str, args: &Value) -> Option<Result<String, String>> {
+    let result = match name {
+        "list_projects" => list_projects(ctx).await,
+        "list_tasks" => list_tasks(ctx, args).await,
+        "create_task" => create_task(ctx, args).await,
+        "send_message" => send_message(ctx, args).await,
+        "get_transcript" => get_transcript(ctx, args).await,
+        "read_task_document" => read_task_document(args).await,
+        "get_workdir_diff" => get_workdir_diff(ctx, args).await,
+        _ => return None,
+    };
+    Some(result)
+}
+
+async fn list_projects(ctx: &McpContext) -> Result<String, String> {
+    let app = ctx
+        .engine
+        .app_snapshot()
+        .await
+        .map_err(|err| err.to_string())?;
+    let projects = app
+        .projects
+        .iter()
+        .map(|p| {
+            json!({
+                "project_id": p.id.0,
+                "name": p.name,
+                "path": p.path,
+                "is_git": p.is_git,
+                "workdirs": p.workspaces.iter().map(|w| json!({
+                    "workdir_id": w.id.0,
+                    "name": w.workspace_name,
+                    "branch_name": w.branch_name,
+                    "worktree_path": w.worktree_path,
+                    "status": serde_key(&w.status),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    to_pretty_json(&projects)
+}
+
+async fn list_tasks(ctx: &McpContext, args: &Value) -> Result<String, String> {
+    let task_status_filter =
+        crate::server::parse_task_status_filter(arg_opt_str(args, "task_status"))?;
+    let snapshot = crate::server::collect_tasks_snapshot(
+        &ctx.engine,
+        arg_opt_str(args, "project_id"),
+        crate::server::WorkdirStatusFilter::Active,
+        task_status_filter.as_deref(),
+    )
+    .await
+    .map_err(|err| err.to_string())?;
+
+    let tasks = snapshot
+        .tasks
+        .iter()
+        .map(|t| {
+            json!({
+                "project_id": t.project_id.0,
+                "workdir_id": t.workspace_id.0,
+                "task_id": t.thread_id.0,
+                "title": t.title,
+                "workdir_name": t.workspace_name,
+                "branch_name": t.branch_name,
+                "task_status": serde_key(&t.task_status),
+                "turn_status": serde_key(&t.turn_status),
+                "agent_running": t.agent_run_status == luban_api::OperationStatus::Running,
+                "updated_at_unix_seconds": t.updated_at_unix_seconds,
+            })
+        })
+        .collect::<Vec<_>>();
+    to_pretty_json(&tasks)
+}
+
+async fn create_task(ctx: &McpContext, args: &Value) -> Result<String, String> {
+    let workdir_id = arg_u64(args, "workdir_id")?;
+    let prompt = arg_str(args, "prompt")?.to_owned();
+    let start = args.get("start").and_then(Value::as_bool).unwrap_or(true);
+
+    let request_id = new_request_id();
+    let mut events = ctx.events.subscribe();
+    ctx.engine
+        .apply_client_action_from(
+            AuditOrigin::Mcp,
+            request_id.clone(),
+            luban_api::ClientAction::TaskExecute {
+                prompt,
+                mode: if start {
+                    luban_api::TaskExecuteMode::Start
+                } else {
+                    luban_api::TaskExecuteMode::Create
+                },
+                workdir_id: Some(luban_api::WorkspaceId(workdir_id)),
+                attachments: Vec::new(),
+                model_id: None,
+                thinking_effort: None,
+                intent_kind: None,
+            },
+        )
+        .await?;
+
+    // The engine publishes `TaskExecuted` before acknowledging the action, so the event is
+    // already buffered in the receiver.
+    loop {
+        match events.try_recv() {
+            Ok(WsServerMessage::Event { event, .. }) => {
+                if let luban_api::ServerEvent::TaskExecuted {
+                    request_id: got,
+                    result,
+                } = *event
+                    && got == request_id
+                {
+                    return to_pretty_json(&json!({
+                        "project_id": result.project_id.0,
+                        "workdir_id": result.workspace_id.0,
+                        "task_id": result.thread_id.0,
+                        "worktree_path": result.worktree_path,
+                        "started": start,
+                    }));
+                }
+            }
+            Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => {}
+            Err(_) => return Err("task was created but its id could not be resolved".to_owned()),
+        }
+    }
+}
+
+async fn send_message(ctx: &McpContext, args: &Value) -> Result<String, String> {
+    let workdir_id = arg_u64(args, "workdir_id")?;
+    let task_id = arg_u64(args, "task_id")?;
+    let text = arg_str(args, "text")?.to_owned();
+    if text.trim().is_empty() {
+        return Err("text must not be empty".to_owned());
+    }
+
+    ctx.engine
+        .apply_client_action_from(
+            AuditOrigin::Mcp,
+            new_request_id(),
+            luban_api::ClientAction::SendAgentMessage {
+                workspace_id: luban_api::WorkspaceId(workdir_id),
+                thread_id: luban_api::WorkspaceThreadId(task_id),
+                text,
+                attachments: Vec::new(),
+                runner: None,
+                amp_mode: None,
+                author: None,
+            },
+        )
+        .await?;
+    Ok("message sent".to_owned())
+}
+
+async fn get_transcript(ctx: &McpContext, args: &Value) -> Result<String, String> {
+    let workdir_id = arg_u64(args, "workdir_id")?;
+    let task_id = arg_u64(args, "task_id")?;
+    let limit = args
+        .get("limit")
+        .and_then(Value::as_u64)
+        .unwrap_or(TRANSCRIPT_DEFAULT_LIMIT);
+
+    let snapshot = ctx
+        .engine
+        .conversation_snapshot(
+            luban_api::WorkspaceId(workdir_id),
+            luban_api::WorkspaceThreadId(task_id),
+            None,
+            Some(limit),
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(render_transcript(&snapshot))
+}
+
+pub(crate) fn render_transcript(snapshot: &luban_api::ConversationSnapshot) -> String {
+    let mut out = format!(
+        "# {}\n\nStatus: {} (agent {})\n",
+        snapshot.title,
+        serde_key(&snapshot.task_status),
+        serde_key(&snapshot.run_status),
+    );
+    if snapshot.entries_truncated {
+        out.push_str(&format!(
+            "Showing the last {} of {} entries.\n",
+            snapshot.entries.len(),
+            snapshot.entries_total
+        ));
+    }
+
+    for entry in &snapshot.entries {
+        match entry {
+            luban_api::ConversationEntry::UserEvent(entry) => match &entry.event {
+                luban_api::UserEvent::Message(message) => {
+                    out.push_str("\n## User\n\n");
+                    out.push_str(message.text.trim());
+                    out.push('\n');
+                    for attachment in &message.attachments {
+                        out.push_str(&format!("[attachment: {}]\n", attachment.name));
+                    }
+                }
+                luban_api::UserEvent::TerminalCommandStarted(cmd) => {
+                    out.push_str(&format!("\n[terminal] $ {}\n", cmd.command));
+                }
+                luban_api::UserEvent::TerminalCommandFinished(_) => {}
+            },
+            luban_api::ConversationEntry::AgentEvent(entry) => match &entry.event {
+                luban_api::AgentEvent::Message(message) => {
+                    out.push_str("\n## Agent\n\n");
+                    out.push_str(message.text.trim());
+                    out.push('\n');
+                }
+                luban_api::AgentEvent::TurnCanceled => out.push_str("\n[turn canceled]\n"),
+                luban_api::AgentEvent::TurnError { message } => {
+                    out.push_str(&format!("\n[turn error] {message}\n"));
+                }
+                luban_api::AgentEvent::Item(_)
+                | luban_api::AgentEvent::TurnUsage { .. }
+                | luban_api::AgentEvent::TurnDuration { .. } => {}
+            },
+            luban_api::ConversationEntry::SystemEvent(entry) => match &entry.event {
+                luban_api::ConversationSystemEvent::TaskStatusChanged { from, to } => {
+                    out.push_str(&format!(
+                        "\n[status {} -> {}]\n",
+                        serde_key(from),
+                        serde_key(to)
+                    ));
+                }
+                luban_api::ConversationSystemEvent::TaskArchived => {
+                    out.push_str("\n[task archived]\n");
+                }
+                luban_api::ConversationSystemEvent::TurnInterrupted { .. } => {
+                    out.push_str("\n[turn interrupted by server restart]\n");
+                }
+                luban_api::ConversationSystemEvent::TurnRewound { truncated, .. } => {
+                    out.push_str(if *truncated {
+                        "\n[rewound to an earlier turn, later entries dropped]\n"
+                    } else {
+                        "\n[worktree rewound to an earlier turn]\n"
+                    });
+                }
+                luban_api::ConversationSystemEvent::TurnChanges {
+                    files_changed,
+                    additions,
+                    deletions,
+                    ..
+                } => {
+                    out.push_str(&format!(
+                        "\n[{}]\n",
+                        crate::relay::format_turn_changes(*files_changed, *additions, *deletions)
+                    ));
+                }
+                luban_api::ConversationSystemEvent::TurnLimitReached {
+                    kind,
+                    limit,
+                    observed,
+                } => {
+                    out.push_str(&format!(
+                        "\n[{}]\n",
+                        crate::relay::format_turn_limit_reached(*kind, *limit, *observed)
+                    ));
+                }
+                luban_api::ConversationSystemEvent::PullRequestReviewComment {
+                    pr_number,
+                    author,
+                    path,
+                    start_line,
+                    end_line,
+                    body,
+                    ..
+                } => {
+                    let location = match (*start_line, *end_line) {
+                        (0, _) => path.clone(),
+                        (start, end) if start == end => format!("{path}:{start}"),
+                        (start, end) => format!("{path}:{start}-{end}"),
+                    };
+                    out.push_str(&format!(
+                        "\n[review comment from {author} on PR #{pr_number} at {location}]\n{}\n",
+                        body.trim()
+                    ));
+                }
+                luban_api::ConversationSystemEvent::CiFixAttempt {
+                    pr_number,
+                    attempt,
+                    max_attempts,
+                    workflow,
+                    ..
+                } => {
+                    out.push_str(&format!(
+                        "\n[CI auto-fix attempt {attempt}/{max_attempts}: {workflow} failed on PR #{pr_number}]\n"
+                    ));
+                }
+                luban_api::ConversationSystemEvent::CiAutoFixStopped { reason, attempts } => {
+                    let reason = match reason {
+                        luban_api::CiAutoFixStopReason::Passed => "CI passed",
+                        luban_api::CiAutoFixStopReason::AttemptsExhausted => "attempts exhausted",
+                    };
+                    out.push_str(&format!(
+                        "\n[CI auto-fix stopped after {attempts} attempt(s): {reason}]\n"
+                    ));
+                }
+                luban_api::ConversationSystemEvent::TaskCreated
+                | luban_api::ConversationSystemEvent::TaskStatusSuggestion { .. }
+                | luban_api::ConversationSystemEvent::TurnCheckpoint { .. } => {}
+            },
+        }
+    }
+
+    if !snapshot.pending_prompts.is_empty() {
+        out.push_str(&format!(
+            "\n[{} queued prompt(s)]\n",
+            snapshot.pending_prompts.len()
+        ));
+    }
+    out
+}
+
+async fn read_task_document(args: &Value) -> Result<String, String> {
+    let workdir_id = arg_u64(args, "workdir_id")?;
+    let task_id = arg_u64(args, "task_id")?;
+    let kind = arg_str(args, "kind")?;
+    let Some(kind) = DomainTaskDocumentKind::parse_key(kind) else {
+        return Err(format!("invalid task document kind: {kind}"));
+    };
+
+    let luban_root = crate::server::resolve_luban_root().map_err(|err| err.to_string())?;
+    let document = tokio::task::spawn_blocking(move || {
+        crate::server::read_task_document(&luban_root, workdir_id, task_id, kind)
+    })
+    .await
+    .map_err(|err| format!("failed to join task document read task: {err}"))?
+    .map_err(|err| err.to_string())?;
+    Ok(document.content)
+}
+
+async fn get_workdir_diff(ctx: &McpContext, args: &Value) -> Result<String, String> {
+    let workdir_id = arg_u64(args, "workdir_id")?;
+    let Some((_project_slug, _workspace_name, worktree_path)) =
+        crate::server::workspace_info_from_snapshot(
+            &ctx.engine.app_snapshot().await.ok(),
+            workdir_id,
+        )
+    else {
+        return Err("workdir not found".to_owned());
+    };
+
+    let repo_path = PathBuf::from(worktree_path);
+    let patch = tokio::task::spawn_blocking(move || crate::git_changes::collect_patch(&repo_path))
+        .await
+        .map_err(|err| format!("failed to run git: {err}"))?
+        .map_err(|err| err.to_string())?;
+    if patch.trim().is_empty() {
+        return Ok("No changes.".to_owned());
+    }
+    Ok(patch)
+}
+
+/// Serves MCP over stdio (newline-delimited JSON-RPC) against an in-process engine.
+pub async fn run_stdio() -> anyhow::Result<()> {
+    let services = new_default_services()?;
+    let (engine, events) = Engine::start(services);
+    let ctx = McpContext::new(engine, events);
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await.context("failed to read stdin")? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(&ctx, message).await,
+            Err(err) => Some(parse_error_response(err.to_string())),
+        };
+        if let Some(response) = response {
+            write_stdio_message(&mut stdout, &response.to_string()).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Serves MCP over stdio by forwarding every message to a running server's `/api/mcp` endpoint.
+///
+/// `session_token` is sent as the `luban_session` cookie for servers with single-user auth.
+pub async fn run_stdio_proxy(base_url: &str, session_token: Option<&str>) -> anyhow::Result<()> {
+    let endpoint = format!("{}/api/mcp", base_url.trim_end_matches('/'));
+    let http = reqwest::Client::builder()
+        .user_agent("luban-mcp")
+        .build()
+        .context("failed to build http client")?;
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await.context("failed to read stdin")? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut request = http
+            .post(&endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                reqwest::header::ACCEPT,
+                "application/json, text/event-stream",
+            )
+            .body(line.clone());
+        if let Some(token) = session_token {
+            request = request.header(reqwest::header::COOKIE, format!("luban_session={token}"));
+        }
+
+        let body = match request.send().await {
+            Ok(resp) if resp.status() == reqwest::StatusCode::ACCEPTED => continue,
+            Ok(resp) if resp.status().is_success() => {
+                resp.text().await.context("failed to read mcp response")?
+            }
+            Ok(resp) => {
+                let status = resp.status();
+                proxy_error_response(&line, format!("luban server returned {status}"))
+            }
+            Err(err) => proxy_error_response(&line, format!("failed to reach luban server: {err}")),
+        };
+        if body.trim().is_empty() {
+            continue;
+        }
+        write_stdio_message(&mut stdout, body.trim()).await?;
+    }
+    Ok(())
+}
+
+fn proxy_error_response(request_line: &str, message: String) -> String {
+    let id = serde_json::from_str::<Value>(request_line)
+        .ok()
+        .and_then(|v| v.get("id").cloned());
+    match id {
+        Some(id) => jsonrpc_error(id, JSONRPC_INVALID_REQUEST, message).to_string(),
+        None => String::new(),
+    }
+}
+
+async fn write_stdio_message(stdout: &mut tokio::io::Stdout, message: &str) -> anyhow::Result<()> {
+    stdout
+        .write_all(message.as_bytes())
+        .await
+        .context("failed to write stdout")?;
+    stdout
+        .write_all(b"\n")
+        .await
+        .context("failed to write stdout")?;
+    stdout.flush().await.context("failed to flush stdout")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_same_origin_and_loopback_origins_are_allowed() {
+        let host = Some("127.0.0.1:8421");
+        assert!(is_allowed_origin("http://127.0.0.1:8421", host));
+        assert!(is_allowed_origin("http://localhost:3000", host));
+        assert!(is_allowed_origin("http://[::1]:3000", host));
+        assert!(is_allowed_origin("tauri://localhost", host));
+        assert!(is_allowed_origin("http://tauri.localhost", host));
+        assert!(is_allowed_origin(
+            "https://luban.example",
+            Some("luban.example")
+        ));
+        assert!(!is_allowed_origin("https://evil.example", host));
+        assert!(!is_allowed_origin("http://127.0.0.1.evil.example", host));
+        assert!(!is_allowed_origin("null", host));
+    }
+
+    fn test_context() -> McpContext {
+        let (tx, _rx) = tokio::sync::mpsc::channel(1);
+        let (events, _) = broadcast::channel(1);
+        McpContext::new(EngineHandle::from_sender_for_tests(tx), events)
+    }
+
+    #[tokio::test]
+    async fn initialize_reports_protocol_version_and_tools_capability() {
+        let ctx = test_context();
+        let response = handle_message(
+            &ctx,
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        )
+        .await
+        .expect("initialize response");
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], MCP_PROTOCOL_VERSION);
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+    }
+
+    #[tokio::test]
+    async fn notifications_do_not_produce_responses() {
+        let ctx = test_context();
+        let response = handle_message(
+            &ctx,
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        )
+        .await;
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn tools_list_includes_all_tools() {
+        let ctx = test_context();
+        let response = handle_message(
+            &ctx,
+            json!({ "jsonrpc": "2.0", "id": "a", "method": "tools/list" }),
+        )
+        .await
+        .expect("tools/list response");
+        let names = response["result"]["tools"]
+            .as_array()
+            .expect("tools array")
+            .iter()
+            .map(|t| t["name"].as_str().unwrap_or_default().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "list_projects",
+                "list_tasks",
+                "create_task",
+                "send_message",
+                "get_transcript",
+                "read_task_document",
+                "get_workdir_diff",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_method_and_tool_are_jsonrpc_errors() {
+        let ctx = test_context();
+        let response = handle_message(
+            &ctx,
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/list" }),
+        )
+        .await
+        .expect("response");
+        assert_eq!(response["error"]["code"], JSONRPC_METHOD_NOT_FOUND);
+
+        let response = handle_message(
+            &ctx,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": { "name": "nope", "arguments": {} },
+            }),
+        )
+        .await
+        .expect("response");
+        assert_eq!(response["error"]["code"], JSONRPC_INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn invalid_tool_arguments_are_reported_as_tool_errors() {
+        let ctx = test_context();
+        let response = handle_message(
+            &ctx,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 4,
+                "method": "tools/call",
+                "params": { "name": "send_message", "arguments": { "workdir_id": 1 } },
+            }),
+        )
+        .await
+        .expect("response");
+        assert_eq!(response["result"]["isError"], true);
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            "missing or invalid argument: task_id"
+        );
+    }
+}
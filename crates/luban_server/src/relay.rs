@@ -0,0 +1,414 @@
+//! Helpers shared by the chat gateways (Telegram, Slack) that relay task
+//! progress into an external chat and create tasks on behalf of chat users.
+
+use crate::engine::EngineHandle;
+use anyhow::Context as _;
+use luban_api::{
+    ConversationEntry, PullRequestCiState, TaskStatus, TaskSummarySnapshot, TurnLimitKind,
+    TurnResult, TurnStatus,
+};
+use luban_domain::AuditOrigin;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+pub(crate) fn truncate_label(raw: &str, max_chars: usize) -> String {
+    let trimmed = raw.trim();
+    if trimmed.chars().count() <= max_chars {
+        return trimmed.to_owned();
+    }
+    let mut out = trimmed
+        .chars()
+        .take(max_chars.saturating_sub(1))
+        .collect::<String>();
+    out.push('…');
+    out
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum ProgressUpdate {
+    Event(String),
+    Final(String),
+}
+
+pub(crate) fn format_conversation_entry_for_progress(
+    entry: &ConversationEntry,
+) -> Option<ProgressUpdate> {
+    match entry {
+        ConversationEntry::AgentEvent(v) => match &v.event {
+            luban_api::AgentEvent::Message(msg) => Some(ProgressUpdate::Final(msg.text.clone())),
+            luban_api::AgentEvent::Item(item) => {
+                format_agent_item_for_progress(item).map(ProgressUpdate::Event)
+            }
+            luban_api::AgentEvent::TurnError { message } => {
+                Some(ProgressUpdate::Final(format!("Turn failed: {message}")))
+            }
+            luban_api::AgentEvent::TurnCanceled => {
+                Some(ProgressUpdate::Final("Turn canceled.".to_owned()))
+            }
+            luban_api::AgentEvent::TurnDuration { .. } => None,
+            luban_api::AgentEvent::TurnUsage { .. } => None,
+        },
+        _ => None,
+    }
+}
+
+pub(crate) fn push_recent_progress_event(
+    out: &mut Vec<String>,
+    line: String,
+    limit: usize,
+) -> bool {
+    let line = line.trim().to_owned();
+    if line.is_empty() {
+        return false;
+    }
+    if out.last().is_some_and(|prev| prev == &line) {
+        return false;
+    }
+    out.push(line);
+    while out.len() > limit {
+        out.remove(0);
+    }
+    true
+}
+
+/// One-line summary of what a turn changed, e.g. `This turn changed 3 files (+40/−12)`.
+pub(crate) fn format_turn_changes(files_changed: u64, additions: u64, deletions: u64) -> String {
+    match files_changed {
+        0 => "This turn changed no files".to_owned(),
+        1 => format!("This turn changed 1 file (+{additions}/−{deletions})"),
+        n => format!("This turn changed {n} files (+{additions}/−{deletions})"),
+    }
+}
+
+/// Explains which limit stopped the agent, e.g. `Turn canceled after 12 tool calls (limit 10)`.
+pub(crate) fn format_turn_limit_reached(kind: TurnLimitKind, limit: u64, observed: u64) -> String {
+    match kind {
+        TurnLimitKind::Timeout => {
+            format!("Turn canceled after running for {observed}s (timeout {limit}s)")
+        }
+        TurnLimitKind::ToolCalls => {
+            format!("Turn canceled after {observed} tool calls (limit {limit})")
+        }
+        TurnLimitKind::TurnTokens => format!(
+            "Turn used {observed} tokens (limit {limit} per turn); queued messages are paused"
+        ),
+        TurnLimitKind::TaskTokens => format!(
+            "Task used {observed} tokens of its {limit} token budget; raise the limit to continue"
+        ),
+    }
+}
+
+pub(crate) fn task_status_label(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Backlog => "Backlog",
+        TaskStatus::Todo => "Todo",
+        TaskStatus::Iterating => "Iterating",
+        TaskStatus::Validating => "Validating",
+        TaskStatus::Done => "Done",
+        TaskStatus::Canceled => "Canceled",
+    }
+}
+
+/// Returns the result of a turn that was running in `prev` and is no longer running in `next`.
+pub(crate) fn turn_ended(
+    prev: &TaskSummarySnapshot,
+    next: &TaskSummarySnapshot,
+) -> Option<TurnResult> {
+    if prev.turn_status == TurnStatus::Running && next.turn_status != TurnStatus::Running {
+        next.last_turn_result
+    } else {
+        None
+    }
+}
+
+pub(crate) fn ci_turned_failed(
+    prev: Option<PullRequestCiState>,
+    next: Option<PullRequestCiState>,
+) -> bool {
+    next == Some(PullRequestCiState::Failure) && prev != Some(PullRequestCiState::Failure)
+}
+
+pub(crate) fn render_progress_body(final_text: Option<&str>, recent: &[String]) -> String {
+    if let Some(text) = final_text {
+        return text.to_owned();
+    }
+
+    let mut lines = vec!["Working...".to_owned()];
+    if !recent.is_empty() {
+        lines.push(String::new());
+        for item in recent {
+            lines.push(format!("• {item}"));
+        }
+    }
+    lines.join("\n")
+}
+
+pub(crate) fn format_agent_item_for_progress(item: &luban_api::AgentItem) -> Option<String> {
+    let payload = &item.payload;
+    let status = payload
+        .get("status")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    let status = match status {
+        "in_progress" => "running",
+        "completed" => "done",
+        other => other,
+    };
+    let status_suffix = if status.is_empty() {
+        String::new()
+    } else {
+        format!(" ({status})")
+    };
+
+    let line = match item.kind {
+        luban_api::AgentItemKind::CommandExecution => {
+            let command = payload
+                .get("command")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Command");
+            Some(format!(
+                "Command: {}{status_suffix}",
+                truncate_label(command, 120)
+            ))
+        }
+        luban_api::AgentItemKind::FileChange => {
+            let changes = payload
+                .get("changes")
+                .and_then(|v| v.as_array())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let mut paths = Vec::new();
+            let mut seen = std::collections::HashSet::new();
+            for change in changes {
+                let path = change
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .trim();
+                if path.is_empty() {
+                    continue;
+                }
+                if !seen.insert(path) {
+                    continue;
+                }
+                paths.push(path.to_owned());
+            }
+            if paths.is_empty() {
+                Some(format!("File changes: {}{status_suffix}", changes.len()))
+            } else {
+                let shown = paths.iter().take(3).cloned().collect::<Vec<_>>();
+                let remaining = paths.len().saturating_sub(shown.len());
+                let suffix = if remaining > 0 {
+                    format!(", +{remaining}")
+                } else {
+                    String::new()
+                };
+                Some(format!(
+                    "File changes: {}{suffix}{status_suffix}",
+                    shown.join(", ")
+                ))
+            }
+        }
+        luban_api::AgentItemKind::McpToolCall => {
+            let server = payload
+                .get("server")
+                .and_then(|v| v.as_str())
+                .unwrap_or("mcp");
+            let tool = payload
+                .get("tool")
+                .and_then(|v| v.as_str())
+                .unwrap_or("tool");
+            Some(format!("MCP: {server}.{tool}{status_suffix}"))
+        }
+        luban_api::AgentItemKind::WebSearch => {
+            let query = payload
+                .get("query")
+                .and_then(|v| v.as_str())
+                .unwrap_or("query");
+            Some(format!(
+                "Web search: {}{status_suffix}",
+                truncate_label(query, 120)
+            ))
+        }
+        luban_api::AgentItemKind::TodoList => Some(format!("Todo list updated{status_suffix}")),
+        luban_api::AgentItemKind::Error => {
+            let message = payload
+                .get("message")
+                .and_then(|v| v.as_str())
+                .or_else(|| payload.get("error").and_then(|v| v.as_str()))
+                .unwrap_or("error");
+            Some(format!(
+                "Error: {}{status_suffix}",
+                truncate_label(message, 160)
+            ))
+        }
+        luban_api::AgentItemKind::Reasoning => None,
+    };
+
+    line.map(|v| truncate_label(&v, 180))
+}
+
+/// Creates a fresh worktree for `project_slug` and a task inside it.
+///
+/// `source` tags the engine request ids so gateway traffic is easy to spot in logs.
+pub(crate) async fn create_task_in_new_worktree(
+    engine: &EngineHandle,
+    source: &str,
+    project_slug: &str,
+) -> anyhow::Result<(u64, u64)> {
+    let workspace_id = create_new_worktree(engine, source, project_slug).await?;
+    let thread_id = create_task(engine, source, workspace_id).await?;
+    Ok((workspace_id, thread_id))
+}
+
+async fn create_new_worktree(
+    engine: &EngineHandle,
+    source: &str,
+    project_slug: &str,
+) -> anyhow::Result<u64> {
+    let before = engine.app_snapshot().await.context("app snapshot")?;
+    let Some(project) = before.projects.iter().find(|p| p.slug == project_slug) else {
+        anyhow::bail!("project not found");
+    };
+
+    let project_id = project.id.clone();
+    let existing_ids: HashSet<u64> = project.workspaces.iter().map(|w| w.id.0).collect();
+
+    let action = luban_api::ClientAction::CreateWorkspace { project_id };
+    let _ = engine
+        .apply_client_action_from(
+            AuditOrigin::parse(source).unwrap_or_default(),
+            format!("{source}_create_worktree"),
+            action,
+        )
+        .await;
+
+    wait_for_new_worktree_id(engine, project_slug, &existing_ids).await
+}
+
+async fn wait_for_new_worktree_id(
+    engine: &EngineHandle,
+    project_slug: &str,
+    existing_ids: &HashSet<u64>,
+) -> anyhow::Result<u64> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut last_seen_count = existing_ids.len();
+
+    loop {
+        let app = engine.app_snapshot().await.context("app snapshot")?;
+        let Some(project) = app.projects.iter().find(|p| p.slug == project_slug) else {
+            anyhow::bail!("project not found");
+        };
+
+        let mut candidates = project
+            .workspaces
+            .iter()
+            .filter(|w| !existing_ids.contains(&w.id.0))
+            .filter(|w| w.status == luban_api::WorkspaceStatus::Active)
+            .map(|w| w.id.0)
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+        if let Some(id) = candidates.last().copied() {
+            return Ok(id);
+        }
+
+        last_seen_count = last_seen_count.max(project.workspaces.len());
+        if Instant::now() >= deadline {
+            anyhow::bail!(
+                "timed out waiting for new worktree (project_slug={}, existing={}, latest_seen={})",
+                project_slug,
+                existing_ids.len(),
+                last_seen_count
+            );
+        }
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+/// Creates a task in an existing worktree and returns its thread id.
+pub(crate) async fn create_task(
+    engine: &EngineHandle,
+    source: &str,
+    workspace_id: u64,
+) -> anyhow::Result<u64> {
+    let action = luban_api::ClientAction::CreateWorkspaceThread {
+        workspace_id: luban_api::WorkspaceId(workspace_id),
+        model_id: None,
+        thinking_effort: None,
+    };
+    let _ = engine
+        .apply_client_action_from(
+            AuditOrigin::parse(source).unwrap_or_default(),
+            format!("{source}_new_task"),
+            action,
+        )
+        .await;
+
+    let snapshot = engine
+        .threads_snapshot(luban_api::WorkspaceId(workspace_id))
+        .await
+        .context("threads snapshot")?;
+
+    Ok(snapshot
+        .threads
+        .iter()
+        .map(|t| t.thread_id.0)
+        .max()
+        .unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_recent_progress_event_filters_empty_and_duplicate() {
+        let mut recent = Vec::new();
+        assert!(!push_recent_progress_event(
+            &mut recent,
+            "   ".to_owned(),
+            5
+        ));
+        assert!(push_recent_progress_event(
+            &mut recent,
+            "step 1".to_owned(),
+            5
+        ));
+        assert!(!push_recent_progress_event(
+            &mut recent,
+            "step 1".to_owned(),
+            5
+        ));
+        assert_eq!(recent, vec!["step 1".to_owned()]);
+    }
+
+    #[test]
+    fn ci_failure_is_reported_once_per_transition() {
+        assert!(ci_turned_failed(
+            Some(PullRequestCiState::Pending),
+            Some(PullRequestCiState::Failure)
+        ));
+        assert!(ci_turned_failed(None, Some(PullRequestCiState::Failure)));
+        assert!(!ci_turned_failed(
+            Some(PullRequestCiState::Failure),
+            Some(PullRequestCiState::Failure)
+        ));
+        assert!(!ci_turned_failed(
+            Some(PullRequestCiState::Failure),
+            Some(PullRequestCiState::Success)
+        ));
+    }
+
+    #[test]
+    fn turn_limit_messages_name_the_limit() {
+        assert_eq!(
+            format_turn_limit_reached(TurnLimitKind::ToolCalls, 10, 11),
+            "Turn canceled after 11 tool calls (limit 10)"
+        );
+        assert!(
+            format_turn_limit_reached(TurnLimitKind::TaskTokens, 500_000, 512_345)
+                .contains("raise the limit")
+        );
+    }
+}
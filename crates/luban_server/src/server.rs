@@ -31,7 +31,15 @@ use tower_http::services::{ServeDir, ServeFile};
 pub async fn router(config: crate::ServerConfig) -> anyhow::Result<Router> {
     let services = new_default_services()?;
     let (engine, events) = Engine::start(services.clone());
-    crate::telegram::start_gateway(engine.clone(), events.clone());
+    let telegram_webhook =
+        crate::telegram::start_gateway(engine.clone(), events.clone(), services.clone());
+    let slack = crate::slack::start_gateway(engine.clone(), events.clone());
+    crate::email::start_notifier(engine.clone(), events.clone());
+    crate::desktop_notify::start_notifier(
+        engine.clone(),
+        events.clone(),
+        config.notification_presenter.clone(),
+    );
 
     let avatar_http = reqwest::Client::builder()
         .timeout(Duration::from_secs(10))
@@ -52,15 +60,23 @@ pub async fn router(config: crate::ServerConfig) -> anyhow::Result<Router> {
             std::time::Duration::from_secs(10 * 60),
             256,
         ),
+        telegram_webhook,
+        slack,
     };
 
-    let api_public = Router::new().route("/health", get(health));
+    let api_public = Router::new()
+        .route("/health", get(health))
+        .route("/telegram/webhook", post(post_telegram_webhook))
+        .route("/slack/events", post(post_slack_events))
+        .route("/slack/commands", post(post_slack_command))
+        .route("/slack/interactions", post(post_slack_interaction));
 
     let api_protected = Router::new()
         .route("/app", get(get_app))
         .route("/projects/avatar", get(get_project_avatar))
         .route("/codex/prompts", get(get_codex_prompts))
         .route("/tasks", get(get_tasks))
+        .route("/audit", get(get_audit_log))
         .route(
             "/new_task/drafts",
             get(list_new_task_drafts).post(create_new_task_draft),
@@ -92,12 +108,17 @@ pub async fn router(config: crate::ServerConfig) -> anyhow::Result<Router> {
             "/workdirs/{workdir_id}/attachments",
             post(upload_attachment),
         )
+        .route("/workdirs/{workdir_id}/url_attachments", post(attach_url))
         .route(
             "/workdirs/{workdir_id}/attachments/{attachment_id}",
             get(download_attachment),
         )
         .route("/workdirs/{workdir_id}/changes", get(get_changes))
         .route("/workdirs/{workdir_id}/diff", get(get_diff))
+        .route(
+            "/workdirs/{workdir_id}/tasks/{task_id}/turn_diff",
+            get(get_turn_diff),
+        )
         .route("/workdirs/{workdir_id}/context", get(get_context))
         .route(
             "/workdirs/{workdir_id}/mentions",
@@ -107,6 +128,7 @@ pub async fn router(config: crate::ServerConfig) -> anyhow::Result<Router> {
             "/workdirs/{workdir_id}/context/{context_id}",
             delete(delete_context_item),
         )
+        .route("/mcp", post(post_mcp))
         .route("/events", get(ws_events))
         .route("/pty/{workdir_id}/{task_id}", get(ws_pty))
         .route_layer(middleware::from_fn_with_state(
@@ -129,10 +151,68 @@ pub async fn router(config: crate::ServerConfig) -> anyhow::Result<Router> {
         .with_state(state))
 }
 
+async fn post_mcp(
+    State(state): State<AppStateHolder>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    if let Err(rejection) = crate::mcp::check_http_request(&headers) {
+        return rejection.into_response();
+    }
+    let message = match serde_json::from_slice::<serde_json::Value>(&body) {
+        Ok(message) => message,
+        Err(err) => {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                Json(crate::mcp::parse_error_response(err.to_string())),
+            )
+                .into_response();
+        }
+    };
+
+    let ctx = crate::mcp::McpContext::new(state.engine.clone(), state.events.clone());
+    match crate::mcp::handle_message(&ctx, message).await {
+        Some(response) => Json(response).into_response(),
+        None => axum::http::StatusCode::ACCEPTED.into_response(),
+    }
+}
+
 async fn health() -> &'static str {
     "ok"
 }
 
+async fn post_telegram_webhook(
+    State(state): State<AppStateHolder>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    crate::telegram::handle_webhook(state.telegram_webhook.as_ref(), &headers, &body).await
+}
+
+async fn post_slack_events(
+    State(state): State<AppStateHolder>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    crate::slack::handle_events(state.slack.as_ref(), &headers, &body).await
+}
+
+async fn post_slack_command(
+    State(state): State<AppStateHolder>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    crate::slack::handle_command(state.slack.as_ref(), &headers, &body).await
+}
+
+async fn post_slack_interaction(
+    State(state): State<AppStateHolder>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    crate::slack::handle_interaction(state.slack.as_ref(), &headers, &body).await
+}
+
 fn resolve_codex_root() -> anyhow::Result<PathBuf> {
     if let Some(root) = std::env::var_os(paths::LUBAN_CODEX_ROOT_ENV) {
         let root = root.to_string_lossy();
@@ -147,7 +227,7 @@ fn resolve_codex_root() -> anyhow::Result<PathBuf> {
     Ok(PathBuf::from(home).join(".codex"))
 }
 
-fn resolve_luban_root() -> anyhow::Result<PathBuf> {
+pub(crate) fn resolve_luban_root() -> anyhow::Result<PathBuf> {
     if let Some(root) = std::env::var_os(luban_domain::paths::LUBAN_ROOT_ENV) {
         let root = root.to_string_lossy();
         let trimmed = root.trim();
@@ -170,6 +250,8 @@ pub(crate) struct AppStateHolder {
     avatar_http: reqwest::Client,
     pub(crate) auth: auth::AuthState,
     idempotency_attachments: IdempotencyStore<luban_api::AttachmentRef>,
+    telegram_webhook: Option<crate::telegram::TelegramWebhookIntake>,
+    slack: Option<crate::slack::SlackIntake>,
 }
 
 async fn get_app(State(state): State<AppStateHolder>) -> impl IntoResponse {
@@ -378,60 +460,29 @@ struct TasksQuery {
     task_status: Option<String>,
 }
 
-async fn get_tasks(
-    State(state): State<AppStateHolder>,
-    Query(query): Query<TasksQuery>,
-) -> impl IntoResponse {
-    let app = match state.engine.app_snapshot().await {
-        Ok(snapshot) => snapshot,
-        Err(err) => {
-            return (
-                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                err.to_string(),
-            )
-                .into_response();
-        }
-    };
-
-    let starred = state
-        .engine
-        .starred_tasks_snapshot()
-        .await
-        .unwrap_or_default();
-
-    let mut tasks = Vec::<luban_api::TaskSummarySnapshot>::new();
-    let selected_project_id = query
-        .project_id
-        .as_deref()
-        .map(str::trim)
-        .filter(|s| !s.is_empty());
-
-    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-    enum WorkdirStatusFilter {
-        Active,
-        Archived,
-        All,
-    }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WorkdirStatusFilter {
+    Active,
+    Archived,
+    All,
+}
 
-    let workdir_status_filter = match query
-        .workdir_status
-        .as_deref()
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-    {
-        None => WorkdirStatusFilter::Active,
-        Some("active") => WorkdirStatusFilter::Active,
-        Some("archived") => WorkdirStatusFilter::Archived,
-        Some("all") => WorkdirStatusFilter::All,
-        Some(other) => {
-            return (
-                axum::http::StatusCode::BAD_REQUEST,
-                format!("invalid workdir_status: {other}"),
-            )
-                .into_response();
+impl WorkdirStatusFilter {
+    pub(crate) fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(str::trim).filter(|s| !s.is_empty()) {
+            None => Ok(Self::Active),
+            Some("active") => Ok(Self::Active),
+            Some("archived") => Ok(Self::Archived),
+            Some("all") => Ok(Self::All),
+            Some(other) => Err(format!("invalid workdir_status: {other}")),
         }
-    };
+    }
+}
 
+/// Parses a comma-separated `task_status` filter. `None` disables filtering.
+pub(crate) fn parse_task_status_filter(
+    value: Option<&str>,
+) -> Result<Option<Vec<luban_api::TaskStatus>>, String> {
     fn parse_task_status(value: &str) -> Option<luban_api::TaskStatus> {
         match value {
             "backlog" => Some(luban_api::TaskStatus::Backlog),
@@ -444,14 +495,9 @@ async fn get_tasks(
         }
     }
 
-    let task_status_filter: Option<Vec<luban_api::TaskStatus>> = match query
-        .task_status
-        .as_deref()
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-    {
-        None => None,
-        Some("all") => None,
+    match value.map(str::trim).filter(|s| !s.is_empty()) {
+        None => Ok(None),
+        Some("all") => Ok(None),
         Some(raw) => {
             let mut out = Vec::new();
             for part in raw.split(',') {
@@ -460,19 +506,28 @@ async fn get_tasks(
                     continue;
                 }
                 let Some(status) = parse_task_status(trimmed) else {
-                    return (
-                        axum::http::StatusCode::BAD_REQUEST,
-                        format!("invalid task_status: {trimmed}"),
-                    )
-                        .into_response();
+                    return Err(format!("invalid task_status: {trimmed}"));
                 };
                 if !out.contains(&status) {
                     out.push(status);
                 }
             }
-            Some(out)
+            Ok(Some(out))
         }
-    };
+    }
+}
+
+pub(crate) async fn collect_tasks_snapshot(
+    engine: &EngineHandle,
+    project_id: Option<&str>,
+    workdir_status_filter: WorkdirStatusFilter,
+    task_status_filter: Option<&[luban_api::TaskStatus]>,
+) -> anyhow::Result<luban_api::TasksSnapshot> {
+    let app = engine.app_snapshot().await?;
+    let starred = engine.starred_tasks_snapshot().await.unwrap_or_default();
+
+    let mut tasks = Vec::<luban_api::TaskSummarySnapshot>::new();
+    let selected_project_id = project_id.map(str::trim).filter(|s| !s.is_empty());
 
     for p in &app.projects {
         if let Some(selected) = selected_project_id
@@ -496,14 +551,14 @@ async fn get_tasks(
                 WorkdirStatusFilter::All => {}
             }
 
-            let snap = match state.engine.threads_snapshot(w.id).await {
+            let snap = match engine.threads_snapshot(w.id).await {
                 Ok(v) => v,
                 Err(_) => continue,
             };
 
             let active_task_id = snap.tabs.active_tab;
             for t in snap.threads {
-                if let Some(filter) = task_status_filter.as_ref()
+                if let Some(filter) = task_status_filter
                     && !filter.contains(&t.task_status)
                 {
                     continue;
@@ -540,11 +595,44 @@ async fn get_tasks(
         }
     }
 
-    Json(luban_api::TasksSnapshot {
+    Ok(luban_api::TasksSnapshot {
         rev: app.rev,
         tasks,
     })
-    .into_response()
+}
+
+async fn get_tasks(
+    State(state): State<AppStateHolder>,
+    Query(query): Query<TasksQuery>,
+) -> impl IntoResponse {
+    let workdir_status_filter = match WorkdirStatusFilter::parse(query.workdir_status.as_deref()) {
+        Ok(filter) => filter,
+        Err(message) => {
+            return (axum::http::StatusCode::BAD_REQUEST, message).into_response();
+        }
+    };
+    let task_status_filter = match parse_task_status_filter(query.task_status.as_deref()) {
+        Ok(filter) => filter,
+        Err(message) => {
+            return (axum::http::StatusCode::BAD_REQUEST, message).into_response();
+        }
+    };
+
+    match collect_tasks_snapshot(
+        &state.engine,
+        query.project_id.as_deref(),
+        workdir_status_filter,
+        task_status_filter.as_deref(),
+    )
+    .await
+    {
+        Ok(snapshot) => Json(snapshot).into_response(),
+        Err(err) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            err.to_string(),
+        )
+            .into_response(),
+    }
 }
 
 async fn get_threads(
@@ -877,6 +965,17 @@ fn read_task_document_snapshot_at(
     ))
 }
 
+pub(crate) fn read_task_document(
+    luban_root: &FsPath,
+    workspace_id: u64,
+    task_id: u64,
+    kind: DomainTaskDocumentKind,
+) -> anyhow::Result<luban_api::TaskDocumentSnapshot> {
+    let (task_ulid, fallback_updated_at_unix_ms, root) =
+        resolve_task_document_location(luban_root, workspace_id, task_id)?;
+    read_task_document_snapshot_at(&task_ulid, &root, fallback_updated_at_unix_ms, kind)
+}
+
 async fn get_task_document(
     State(_state): State<AppStateHolder>,
     Path((workspace_id, task_id, kind)): Path<(u64, u64, String)>,
@@ -901,9 +1000,7 @@ async fn get_task_document(
     };
 
     let result = tokio::task::spawn_blocking(move || {
-        let (task_ulid, fallback_updated_at_unix_ms, root) =
-            resolve_task_document_location(FsPath::new(&luban_root), workspace_id, task_id)?;
-        read_task_document_snapshot_at(&task_ulid, &root, fallback_updated_at_unix_ms, kind)
+        read_task_document(&luban_root, workspace_id, task_id, kind)
     })
     .await;
 
@@ -1384,11 +1481,11 @@ async fn download_attachment(
         return (axum::http::StatusCode::BAD_REQUEST, "invalid attachment id").into_response();
     }
 
-    let Some((project_slug, workspace_name)) =
-        workspace_scope_from_snapshot(&state.engine.app_snapshot().await.ok(), workspace_id)
-    else {
+    if workspace_scope_from_snapshot(&state.engine.app_snapshot().await.ok(), workspace_id)
+        .is_none()
+    {
         return (axum::http::StatusCode::NOT_FOUND, "workspace not found").into_response();
-    };
+    }
 
     let luban_root = match resolve_luban_root() {
         Ok(root) => root,
@@ -1400,12 +1497,7 @@ async fn download_attachment(
                 .into_response();
         }
     };
-    let conversations_root = luban_domain::paths::conversations_root(&luban_root);
-    let blob_path = conversations_root
-        .join(project_slug)
-        .join(workspace_name)
-        .join("context")
-        .join("blobs")
+    let blob_path = luban_domain::paths::context_blobs_root(&luban_root)
         .join(format!("{}.{}", attachment_id, query.ext));
 
     let bytes = match tokio::fs::read(&blob_path).await {
@@ -1492,6 +1584,67 @@ async fn get_diff(
     }
 }
 
+#[derive(serde::Deserialize)]
+struct TurnDiffQuery {
+    from: String,
+    to: Option<String>,
+}
+
+async fn get_turn_diff(
+    State(state): State<AppStateHolder>,
+    Path((workspace_id, task_id)): Path<(u64, u64)>,
+    Query(query): Query<TurnDiffQuery>,
+) -> impl IntoResponse {
+    let to = query.to.unwrap_or_else(|| query.from.clone());
+    if !luban_domain::is_valid_turn_checkpoint_id(&query.from)
+        || !luban_domain::is_valid_turn_checkpoint_id(&to)
+    {
+        return (axum::http::StatusCode::BAD_REQUEST, "invalid checkpoint id").into_response();
+    }
+    let Some((_project_slug, _workspace_name, worktree_path)) =
+        workspace_info_from_snapshot(&state.engine.app_snapshot().await.ok(), workspace_id)
+    else {
+        return (axum::http::StatusCode::NOT_FOUND, "workspace not found").into_response();
+    };
+
+    let services = state.services.clone();
+    let repo_path = PathBuf::from(worktree_path);
+    let result = tokio::task::spawn_blocking(move || {
+        let Some((from_tree, to_tree)) = crate::git_changes::turn_range_trees(
+            services.as_ref(),
+            &repo_path,
+            luban_domain::WorkspaceId::from_u64(workspace_id),
+            luban_domain::WorkspaceThreadId::from_u64(task_id),
+            &query.from,
+            &to,
+        )?
+        else {
+            return Ok(None);
+        };
+        crate::git_changes::collect_range_diff(&repo_path, &from_tree, &to_tree).map(Some)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(Some(files))) => Json(WorkspaceDiffSnapshot {
+            workspace_id: luban_api::WorkspaceId(workspace_id),
+            files,
+        })
+        .into_response(),
+        Ok(Ok(None)) => (axum::http::StatusCode::NOT_FOUND, "checkpoint not found").into_response(),
+        Ok(Err(err)) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            err.to_string(),
+        )
+            .into_response(),
+        Err(err) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to run git: {err}"),
+        )
+            .into_response(),
+    }
+}
+
 async fn get_context(
     State(state): State<AppStateHolder>,
     Path(workspace_id): Path<u64>,
@@ -1517,6 +1670,7 @@ async fn get_context(
                             luban_domain::AttachmentKind::Image => luban_api::AttachmentKind::Image,
                             luban_domain::AttachmentKind::Text => luban_api::AttachmentKind::Text,
                             luban_domain::AttachmentKind::File => luban_api::AttachmentKind::File,
+                            luban_domain::AttachmentKind::Url => luban_api::AttachmentKind::Url,
                         },
                         name: item.attachment.name,
                         extension: item.attachment.extension,
@@ -1605,6 +1759,61 @@ struct NewTaskDraftUpsertRequest {
     workdir_id: Option<u64>,
 }
 
+const AUDIT_PAGE_DEFAULT_LIMIT: u64 = 50;
+const AUDIT_PAGE_MAX_LIMIT: u64 = 500;
+
+#[derive(serde::Deserialize)]
+struct AuditQuery {
+    before: Option<u64>,
+    limit: Option<u64>,
+}
+
+async fn get_audit_log(
+    State(state): State<AppStateHolder>,
+    Query(query): Query<AuditQuery>,
+) -> impl IntoResponse {
+    let limit = query
+        .limit
+        .unwrap_or(AUDIT_PAGE_DEFAULT_LIMIT)
+        .clamp(1, AUDIT_PAGE_MAX_LIMIT);
+    let services = state.services.clone();
+    // Fetch one extra row to tell whether an older page exists.
+    let result =
+        tokio::task::spawn_blocking(move || services.list_audit_entries(query.before, limit + 1))
+            .await
+            .unwrap_or_else(|err| Err(format!("failed to join audit log task: {err}")));
+
+    match result {
+        Ok(mut entries) => {
+            let has_more = entries.len() as u64 > limit;
+            entries.truncate(limit as usize);
+            let next_before = has_more
+                .then(|| entries.last().map(|entry| entry.id))
+                .flatten();
+            Json(luban_api::AuditLogSnapshot {
+                entries: entries.into_iter().map(map_audit_entry_snapshot).collect(),
+                next_before,
+            })
+            .into_response()
+        }
+        Err(message) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, message).into_response(),
+    }
+}
+
+fn map_audit_entry_snapshot(entry: luban_domain::AuditEntry) -> luban_api::AuditEntrySnapshot {
+    luban_api::AuditEntrySnapshot {
+        id: entry.id,
+        created_at_unix_ms: entry.created_at_unix_ms,
+        origin: entry.origin.as_str().to_owned(),
+        action: entry.action,
+        summary: entry.summary,
+        workspace_id: entry.workspace_id.map(luban_api::WorkspaceId),
+        thread_id: entry.thread_id.map(luban_api::WorkspaceThreadId),
+        undoable: entry.undo.is_some() && entry.undone_at_unix_ms.is_none(),
+        undone_at_unix_ms: entry.undone_at_unix_ms,
+    }
+}
+
 async fn list_new_task_drafts(State(state): State<AppStateHolder>) -> impl IntoResponse {
     match state.services.list_new_task_drafts() {
         Ok(drafts) => Json(luban_api::NewTaskDraftsSnapshot {
@@ -1850,38 +2059,13 @@ async fn upload_attachment(
                 extension,
             )
         } else {
-            let unique = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos();
-            let tmp_dir = std::env::temp_dir().join(format!(
-                "luban-upload-{}-{}",
-                std::process::id(),
-                unique
-            ));
-            if let Err(err) = std::fs::create_dir_all(&tmp_dir) {
-                return Err((
-                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("failed to create tmp dir: {err}"),
-                ));
-            }
-
-            let tmp_path = tmp_dir.join(display_name.clone());
-            if let Err(err) = std::fs::write(&tmp_path, &bytes) {
-                let _ = std::fs::remove_dir_all(&tmp_dir);
-                return Err((
-                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("failed to write tmp file: {err}"),
-                ));
-            }
-
-            let stored = state.services.store_context_file(
+            store_context_file_bytes(
+                state.services.as_ref(),
                 project_slug.clone(),
                 workspace_name.clone(),
-                tmp_path,
-            );
-            let _ = std::fs::remove_dir_all(&tmp_dir);
-            stored
+                &display_name,
+                &bytes,
+            )
         };
 
         match stored {
@@ -1902,6 +2086,7 @@ async fn upload_attachment(
                         luban_domain::AttachmentKind::Image => luban_api::AttachmentKind::Image,
                         luban_domain::AttachmentKind::Text => luban_api::AttachmentKind::Text,
                         luban_domain::AttachmentKind::File => luban_api::AttachmentKind::File,
+                        luban_domain::AttachmentKind::Url => luban_api::AttachmentKind::Url,
                     },
                     name: att.name,
                     extension: att.extension,
@@ -1938,7 +2123,79 @@ async fn upload_attachment(
     }
 }
 
-fn append_timestamp_to_basename(name: &str, unix_ms: u64) -> String {
+#[derive(serde::Deserialize)]
+struct AttachUrlRequest {
+    url: String,
+}
+
+async fn attach_url(
+    State(state): State<AppStateHolder>,
+    Path(workspace_id): Path<u64>,
+    Json(req): Json<AttachUrlRequest>,
+) -> impl IntoResponse {
+    let snapshot = state.engine.app_snapshot().await.ok();
+    let Some((project_slug, workspace_name)) =
+        workspace_scope_from_snapshot(&snapshot, workspace_id)
+    else {
+        return (axum::http::StatusCode::NOT_FOUND, "workspace not found").into_response();
+    };
+    let settings = luban_domain::UrlAttachmentSettings {
+        allowed_domains: snapshot
+            .map(|snapshot| snapshot.url_attachments.allowed_domains)
+            .unwrap_or_default(),
+    };
+
+    let url = req.url.trim().to_owned();
+    let Ok(parsed) = reqwest::Url::parse(&url) else {
+        return (axum::http::StatusCode::BAD_REQUEST, "invalid URL").into_response();
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            "only http and https URLs can be attached",
+        )
+            .into_response();
+    }
+    let host = parsed.host_str().unwrap_or_default();
+    if !settings.allows_host(host.trim_start_matches('[').trim_end_matches(']')) {
+        return (
+            axum::http::StatusCode::FORBIDDEN,
+            format!("{host} is not in the URL attachment allowlist"),
+        )
+            .into_response();
+    }
+
+    let services = state.services.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let attached_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let att = services.store_context_url(
+            project_slug.clone(),
+            workspace_name.clone(),
+            url,
+            settings,
+        )?;
+        services
+            .record_context_item(project_slug, workspace_name, att.clone(), attached_at_ms)
+            .map_err(|message| format!("failed to record context item: {message}"))?;
+        Ok::<_, String>(att)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(att)) => Json(crate::engine::map_attachment_ref(&att)).into_response(),
+        Ok(Err(message)) => (axum::http::StatusCode::BAD_GATEWAY, message).into_response(),
+        Err(err) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("failed to join url attachment task: {err}"),
+        )
+            .into_response(),
+    }
+}
+
+pub(crate) fn append_timestamp_to_basename(name: &str, unix_ms: u64) -> String {
     let raw_name = std::path::Path::new(name)
         .file_name()
         .and_then(|s| s.to_str())
@@ -1966,7 +2223,35 @@ fn append_timestamp_to_basename(name: &str, unix_ms: u64) -> String {
     }
 }
 
-fn workspace_scope_from_snapshot(
+/// Stores `bytes` as a context file named `file_name`, going through a temporary file because
+/// `store_context_file` copies from disk.
+pub(crate) fn store_context_file_bytes(
+    services: &dyn ProjectWorkspaceService,
+    project_slug: String,
+    workspace_name: String,
+    file_name: &str,
+    bytes: &[u8],
+) -> Result<luban_domain::AttachmentRef, String> {
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let tmp_dir =
+        std::env::temp_dir().join(format!("luban-upload-{}-{}", std::process::id(), unique));
+    std::fs::create_dir_all(&tmp_dir).map_err(|err| format!("failed to create tmp dir: {err}"))?;
+
+    let tmp_path = tmp_dir.join(file_name);
+    if let Err(err) = std::fs::write(&tmp_path, bytes) {
+        let _ = std::fs::remove_dir_all(&tmp_dir);
+        return Err(format!("failed to write tmp file: {err}"));
+    }
+
+    let stored = services.store_context_file(project_slug, workspace_name, tmp_path);
+    let _ = std::fs::remove_dir_all(&tmp_dir);
+    stored
+}
+
+pub(crate) fn workspace_scope_from_snapshot(
     snapshot: &Option<AppSnapshot>,
     workspace_id: u64,
 ) -> Option<(String, String)> {
@@ -1981,7 +2266,7 @@ fn workspace_scope_from_snapshot(
     None
 }
 
-fn workspace_info_from_snapshot(
+pub(crate) fn workspace_info_from_snapshot(
     snapshot: &Option<AppSnapshot>,
     workspace_id: u64,
 ) -> Option<(String, String, String)> {
@@ -0,0 +1,1426 @@
+use crate::engine::EngineHandle;
+use crate::relay::{
+    ProgressUpdate, create_task_in_new_worktree, format_conversation_entry_for_progress,
+    push_recent_progress_event, render_progress_body, task_status_label, truncate_label,
+};
+use anyhow::{Context as _, anyhow};
+use axum::Json;
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use hmac::{Hmac, Mac as _};
+use luban_api::{ConversationEntry, ServerEvent, TaskStatus, WsServerMessage};
+use luban_domain::AuditOrigin;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc};
+
+const SLACK_BOT_TOKEN_ENV: &str = "LUBAN_SLACK_BOT_TOKEN";
+const SLACK_SIGNING_SECRET_ENV: &str = "LUBAN_SLACK_SIGNING_SECRET";
+const SLACK_API_BASE_URL_ENV: &str = "LUBAN_SLACK_API_BASE_URL";
+const SLACK_ALLOWED_USERS_ENV: &str = "LUBAN_SLACK_ALLOWED_USERS";
+const SLACK_ALLOWED_CHANNELS_ENV: &str = "LUBAN_SLACK_ALLOWED_CHANNELS";
+const SLACK_API_BASE_URL_DEFAULT: &str = "https://slack.com/api";
+
+const SLACK_REQUEST_TIMEOUT_SECS: u64 = 30;
+const SLACK_MAX_SIGNATURE_AGE_SECS: u64 = 5 * 60;
+// Slack rejects section blocks above 3000 characters.
+const SLACK_MAX_MESSAGE_CHARS: usize = 3000;
+const SLACK_INBOUND_QUEUE_CAPACITY: usize = 256;
+const SLACK_RECENT_TASKS_LIMIT: usize = 10;
+const SLACK_THREADS_FILE_NAME: &str = "slack_threads.json";
+
+const ACTION_TASK_STATUS: &str = "luban_task_status";
+const ACTION_CANCEL_TURN: &str = "luban_cancel_turn";
+
+const SLACK_HELP_TEXT: &str = "Usage:\n\
+• `/luban new <project-slug> <prompt>` starts a task in a new worktree\n\
+• `/luban tasks` lists recently updated tasks\n\
+• `/luban cancel <workdir_id>/<task_id>` cancels the running turn\n\
+Reply in a task thread to send a follow-up message.";
+
+const SLACK_NOT_ALLOWED_TEXT: &str = "You are not allowed to use Luban from Slack. Ask the Luban owner to add your user ID or this channel to `LUBAN_SLACK_ALLOWED_USERS` / `LUBAN_SLACK_ALLOWED_CHANNELS`.";
+
+#[derive(Clone, Debug)]
+struct SlackConfig {
+    bot_token: String,
+    signing_secret: String,
+    api_base: String,
+    access: SlackAccess,
+}
+
+impl SlackConfig {
+    fn from_env() -> Option<Self> {
+        let bot_token = env_value(SLACK_BOT_TOKEN_ENV)?;
+        let signing_secret = env_value(SLACK_SIGNING_SECRET_ENV)?;
+        let api_base = env_value(SLACK_API_BASE_URL_ENV)
+            .unwrap_or_else(|| SLACK_API_BASE_URL_DEFAULT.to_owned());
+        Some(Self {
+            bot_token,
+            signing_secret,
+            api_base: api_base.trim_end_matches('/').to_owned(),
+            access: SlackAccess::from_env(),
+        })
+    }
+}
+
+/// Who may drive Luban from Slack. A valid signature only proves the request came from the
+/// workspace, so requests must also come from an allowed user or an allowed channel. With
+/// neither list configured every request is rejected.
+#[derive(Clone, Debug, Default)]
+struct SlackAccess {
+    users: HashSet<String>,
+    channels: HashSet<String>,
+}
+
+impl SlackAccess {
+    fn from_env() -> Self {
+        Self {
+            users: env_list(SLACK_ALLOWED_USERS_ENV),
+            channels: env_list(SLACK_ALLOWED_CHANNELS_ENV),
+        }
+    }
+
+    fn allows(&self, user_id: &str, channel_id: &str) -> bool {
+        (!user_id.is_empty() && self.users.contains(user_id))
+            || (!channel_id.is_empty() && self.channels.contains(channel_id))
+    }
+}
+
+fn env_value(key: &str) -> Option<String> {
+    std::env::var(key)
+        .ok()
+        .map(|v| v.trim().to_owned())
+        .filter(|v| !v.is_empty())
+}
+
+/// Comma or whitespace separated Slack IDs.
+fn env_list(key: &str) -> HashSet<String> {
+    parse_id_list(&env_value(key).unwrap_or_default())
+}
+
+fn parse_id_list(raw: &str) -> HashSet<String> {
+    raw.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|id| !id.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Handle used by the HTTP routes to verify Slack requests and hand them to the gateway.
+#[derive(Clone)]
+pub(crate) struct SlackIntake {
+    signing_secret: String,
+    access: SlackAccess,
+    tx: mpsc::Sender<SlackInbound>,
+}
+
+/// Starts the Slack gateway when both the bot token and signing secret are configured.
+pub(crate) fn start_gateway(
+    engine: EngineHandle,
+    events: broadcast::Sender<WsServerMessage>,
+) -> Option<SlackIntake> {
+    let config = SlackConfig::from_env()?;
+    let (tx, rx) = mpsc::channel(SLACK_INBOUND_QUEUE_CAPACITY);
+    let intake = SlackIntake {
+        signing_secret: config.signing_secret.clone(),
+        access: config.access.clone(),
+        tx,
+    };
+
+    tokio::spawn(async move {
+        let mut gateway = SlackGateway::new(engine, events.subscribe(), rx, &config);
+        if let Err(err) = gateway.run().await {
+            tracing::warn!(error = %err, "slack gateway stopped");
+        }
+    });
+
+    Some(intake)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SlackCommand {
+    New {
+        project_slug: String,
+        prompt: String,
+    },
+    Tasks,
+    Cancel {
+        workspace_id: u64,
+        thread_id: u64,
+    },
+    Help,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SlackTaskAction {
+    SetStatus {
+        workspace_id: u64,
+        thread_id: u64,
+        status: TaskStatus,
+    },
+    CancelTurn {
+        workspace_id: u64,
+        thread_id: u64,
+    },
+}
+
+#[derive(Debug)]
+enum SlackInbound {
+    Command {
+        command: SlackCommand,
+        channel_id: String,
+        user_name: String,
+        response_url: String,
+    },
+    ThreadMessage {
+        channel: String,
+        thread_ts: String,
+        user: String,
+        text: String,
+    },
+    Action(SlackTaskAction),
+}
+
+#[derive(Deserialize)]
+struct SlackEventEnvelope {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    challenge: Option<String>,
+    #[serde(default)]
+    event: Option<SlackEvent>,
+}
+
+#[derive(Deserialize)]
+struct SlackEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    subtype: Option<String>,
+    #[serde(default)]
+    bot_id: Option<String>,
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    thread_ts: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SlackCommandForm {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    user_id: String,
+    #[serde(default)]
+    user_name: String,
+    channel_id: String,
+    response_url: String,
+}
+
+#[derive(Deserialize)]
+struct SlackInteractionForm {
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct SlackBlockActionsPayload {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    actions: Vec<SlackBlockAction>,
+    #[serde(default)]
+    user: Option<SlackIdRef>,
+    #[serde(default)]
+    channel: Option<SlackIdRef>,
+}
+
+#[derive(Deserialize)]
+struct SlackIdRef {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct SlackBlockAction {
+    action_id: String,
+    #[serde(default)]
+    value: Option<String>,
+}
+
+/// Events API endpoint: answers the URL verification handshake and forwards
+/// human replies posted in task threads.
+pub(crate) async fn handle_events(
+    intake: Option<&SlackIntake>,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Response {
+    let intake = match verified_intake(intake, headers, body) {
+        Ok(intake) => intake,
+        Err(status) => return status.into_response(),
+    };
+
+    let envelope = match serde_json::from_slice::<SlackEventEnvelope>(body) {
+        Ok(envelope) => envelope,
+        Err(err) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+    };
+
+    if envelope.kind == "url_verification" {
+        let challenge = envelope.challenge.unwrap_or_default();
+        return Json(serde_json::json!({ "challenge": challenge })).into_response();
+    }
+
+    // Slack retries deliveries it considers slow; the first attempt is already queued.
+    if headers.contains_key("x-slack-retry-num") {
+        return StatusCode::OK.into_response();
+    }
+
+    if envelope.kind == "event_callback"
+        && let Some(inbound) = envelope.event.and_then(thread_message_from_event)
+    {
+        if let SlackInbound::ThreadMessage { channel, user, .. } = &inbound
+            && !intake.access.allows(user, channel)
+        {
+            tracing::info!(user = %user, channel = %channel, "ignoring slack message from a user not allowed");
+            return StatusCode::OK.into_response();
+        }
+        intake.forward(inbound);
+    }
+    StatusCode::OK.into_response()
+}
+
+/// Slash command endpoint for `/luban`.
+pub(crate) async fn handle_command(
+    intake: Option<&SlackIntake>,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Response {
+    let intake = match verified_intake(intake, headers, body) {
+        Ok(intake) => intake,
+        Err(status) => return status.into_response(),
+    };
+
+    let form = match serde_urlencoded::from_bytes::<SlackCommandForm>(body) {
+        Ok(form) => form,
+        Err(err) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+    };
+
+    if !intake.access.allows(&form.user_id, &form.channel_id) {
+        return ephemeral_response(SLACK_NOT_ALLOWED_TEXT);
+    }
+
+    let command = match parse_command(&form.text) {
+        Ok(SlackCommand::Help) => return ephemeral_response(SLACK_HELP_TEXT),
+        Ok(command) => command,
+        Err(message) => return ephemeral_response(&format!("{message}\n\n{SLACK_HELP_TEXT}")),
+    };
+
+    let ack = match &command {
+        SlackCommand::New { project_slug, .. } => {
+            Some(format!("Creating a task in `{project_slug}`..."))
+        }
+        _ => None,
+    };
+
+    intake.forward(SlackInbound::Command {
+        command,
+        channel_id: form.channel_id,
+        user_name: form.user_name,
+        response_url: form.response_url,
+    });
+
+    match ack {
+        Some(text) => ephemeral_response(&text),
+        None => StatusCode::OK.into_response(),
+    }
+}
+
+/// Interactivity endpoint for Block Kit buttons on task messages.
+pub(crate) async fn handle_interaction(
+    intake: Option<&SlackIntake>,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Response {
+    let intake = match verified_intake(intake, headers, body) {
+        Ok(intake) => intake,
+        Err(status) => return status.into_response(),
+    };
+
+    let payload = serde_urlencoded::from_bytes::<SlackInteractionForm>(body)
+        .map_err(|err| err.to_string())
+        .and_then(|form| {
+            serde_json::from_str::<SlackBlockActionsPayload>(&form.payload)
+                .map_err(|err| err.to_string())
+        });
+    let payload = match payload {
+        Ok(payload) => payload,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
+
+    let user_id = payload.user.as_ref().map_or("", |u| u.id.as_str());
+    let channel_id = payload.channel.as_ref().map_or("", |c| c.id.as_str());
+    if !intake.access.allows(user_id, channel_id) {
+        tracing::info!(
+            user = user_id,
+            channel = channel_id,
+            "ignoring slack action from a user not allowed"
+        );
+        return StatusCode::OK.into_response();
+    }
+
+    if payload.kind == "block_actions" {
+        for action in &payload.actions {
+            let value = action.value.as_deref().unwrap_or_default();
+            if let Some(action) = parse_task_action(&action.action_id, value) {
+                intake.forward(SlackInbound::Action(action));
+            }
+        }
+    }
+    StatusCode::OK.into_response()
+}
+
+impl SlackIntake {
+    fn forward(&self, inbound: SlackInbound) {
+        if let Err(err) = self.tx.try_send(inbound) {
+            tracing::warn!(error = %err, "dropping slack request");
+        }
+    }
+}
+
+fn verified_intake<'a>(
+    intake: Option<&'a SlackIntake>,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<&'a SlackIntake, StatusCode> {
+    let intake = intake.ok_or(StatusCode::NOT_FOUND)?;
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if verify_slack_signature(
+        &intake.signing_secret,
+        header("x-slack-request-timestamp"),
+        body,
+        header("x-slack-signature"),
+        now,
+    ) {
+        Ok(intake)
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Checks the `v0=` HMAC-SHA256 signature Slack attaches to every request.
+fn verify_slack_signature(
+    signing_secret: &str,
+    timestamp: &str,
+    body: &[u8],
+    signature: &str,
+    now_unix_secs: u64,
+) -> bool {
+    let Ok(ts) = timestamp.trim().parse::<u64>() else {
+        return false;
+    };
+    if now_unix_secs.abs_diff(ts) > SLACK_MAX_SIGNATURE_AGE_SECS {
+        return false;
+    }
+    let Some(expected) = signature.trim().strip_prefix("v0=").and_then(decode_hex) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(format!("v0:{ts}:").as_bytes());
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+fn decode_hex(raw: &str) -> Option<Vec<u8>> {
+    if !raw.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..raw.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(raw.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn ephemeral_response(text: &str) -> Response {
+    Json(serde_json::json!({
+        "response_type": "ephemeral",
+        "text": text,
+    }))
+    .into_response()
+}
+
+fn thread_message_from_event(event: SlackEvent) -> Option<SlackInbound> {
+    if event.kind != "message" || event.subtype.is_some() || event.bot_id.is_some() {
+        return None;
+    }
+    let text = event.text.unwrap_or_default().trim().to_owned();
+    if text.is_empty() {
+        return None;
+    }
+    Some(SlackInbound::ThreadMessage {
+        channel: event.channel?,
+        thread_ts: event.thread_ts?,
+        user: event.user?,
+        text,
+    })
+}
+
+fn parse_command(raw: &str) -> Result<SlackCommand, String> {
+    let raw = raw.trim();
+    let (verb, rest) = raw
+        .split_once(char::is_whitespace)
+        .map(|(verb, rest)| (verb, rest.trim()))
+        .unwrap_or((raw, ""));
+
+    match verb.to_ascii_lowercase().as_str() {
+        "" | "help" => Ok(SlackCommand::Help),
+        "tasks" | "list" => Ok(SlackCommand::Tasks),
+        "new" => {
+            let (project_slug, prompt) = rest
+                .split_once(char::is_whitespace)
+                .map(|(slug, prompt)| (slug, prompt.trim()))
+                .unwrap_or((rest, ""));
+            if project_slug.is_empty() || prompt.is_empty() {
+                return Err("`new` needs a project slug and a prompt.".to_owned());
+            }
+            Ok(SlackCommand::New {
+                project_slug: project_slug.to_owned(),
+                prompt: prompt.to_owned(),
+            })
+        }
+        "cancel" => {
+            let (workspace_id, thread_id) = parse_task_ref(rest)
+                .ok_or_else(|| "`cancel` needs a task like `12/3`.".to_owned())?;
+            Ok(SlackCommand::Cancel {
+                workspace_id,
+                thread_id,
+            })
+        }
+        other => Err(format!("Unknown command `{other}`.")),
+    }
+}
+
+fn parse_task_ref(raw: &str) -> Option<(u64, u64)> {
+    let (workspace_id, thread_id) = raw.trim().split_once('/')?;
+    Some((
+        workspace_id.trim().parse().ok()?,
+        thread_id.trim().parse().ok()?,
+    ))
+}
+
+fn parse_task_action(action_id: &str, value: &str) -> Option<SlackTaskAction> {
+    match action_id {
+        ACTION_TASK_STATUS => {
+            let (task, status) = value.rsplit_once(':')?;
+            let (workspace_id, thread_id) = parse_task_ref(task)?;
+            let status =
+                serde_json::from_value::<TaskStatus>(serde_json::Value::String(status.to_owned()))
+                    .ok()?;
+            Some(SlackTaskAction::SetStatus {
+                workspace_id,
+                thread_id,
+                status,
+            })
+        }
+        ACTION_CANCEL_TURN => {
+            let (workspace_id, thread_id) = parse_task_ref(value)?;
+            Some(SlackTaskAction::CancelTurn {
+                workspace_id,
+                thread_id,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn task_status_value(status: TaskStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+/// Block Kit layout for the root message of a task thread.
+fn task_root_blocks(
+    title: &str,
+    status: TaskStatus,
+    workspace_id: u64,
+    thread_id: u64,
+) -> serde_json::Value {
+    let task = format!("{workspace_id}/{thread_id}");
+    let mut buttons = [
+        TaskStatus::Iterating,
+        TaskStatus::Validating,
+        TaskStatus::Done,
+    ]
+    .into_iter()
+    .filter(|candidate| *candidate != status)
+    .map(|candidate| {
+        serde_json::json!({
+            "type": "button",
+            "action_id": format!("{ACTION_TASK_STATUS}_{}", task_status_value(candidate)),
+            "text": { "type": "plain_text", "text": task_status_label(candidate) },
+            "value": format!("{task}:{}", task_status_value(candidate)),
+        })
+    })
+    .collect::<Vec<_>>();
+    buttons.push(serde_json::json!({
+        "type": "button",
+        "action_id": ACTION_CANCEL_TURN,
+        "style": "danger",
+        "text": { "type": "plain_text", "text": "Cancel turn" },
+        "value": task,
+    }));
+
+    serde_json::json!([
+        {
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!(
+                    "*{}*\nStatus: {} · `{task}`",
+                    truncate_label(title, 150),
+                    task_status_label(status)
+                ),
+            },
+        },
+        { "type": "actions", "elements": buttons },
+    ])
+}
+
+fn truncate_slack_text(raw: &str) -> String {
+    truncate_label(raw, SLACK_MAX_MESSAGE_CHARS)
+}
+
+struct SlackApi {
+    http: reqwest::Client,
+    api_base: String,
+    bot_token: String,
+}
+
+#[derive(Deserialize)]
+struct SlackApiResponse {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    ts: Option<String>,
+    #[serde(default)]
+    user: Option<SlackUserInfo>,
+}
+
+#[derive(Deserialize)]
+struct SlackUserInfo {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    real_name: Option<String>,
+}
+
+impl SlackApi {
+    async fn call(
+        &self,
+        request: reqwest::RequestBuilder,
+        method: &str,
+    ) -> anyhow::Result<SlackApiResponse> {
+        let res = request
+            .bearer_auth(&self.bot_token)
+            .send()
+            .await
+            .with_context(|| format!("slack {method} request failed"))?;
+        let body = res
+            .json::<SlackApiResponse>()
+            .await
+            .with_context(|| format!("slack {method} response decode failed"))?;
+        if !body.ok {
+            return Err(anyhow!(
+                "slack {method} failed: {}",
+                body.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        Ok(body)
+    }
+
+    async fn post_message(
+        &self,
+        channel: &str,
+        thread_ts: Option<&str>,
+        text: &str,
+        blocks: Option<serde_json::Value>,
+    ) -> anyhow::Result<String> {
+        let mut body = serde_json::json!({
+            "channel": channel,
+            "text": truncate_slack_text(text),
+        });
+        if let Some(thread_ts) = thread_ts {
+            body["thread_ts"] = thread_ts.into();
+        }
+        if let Some(blocks) = blocks {
+            body["blocks"] = blocks;
+        }
+        let request = self
+            .http
+            .post(format!("{}/chat.postMessage", self.api_base))
+            .json(&body);
+        self.call(request, "chat.postMessage")
+            .await?
+            .ts
+            .ok_or_else(|| anyhow!("slack chat.postMessage returned no ts"))
+    }
+
+    async fn update_message(
+        &self,
+        channel: &str,
+        ts: &str,
+        text: &str,
+        blocks: Option<serde_json::Value>,
+    ) -> anyhow::Result<()> {
+        let mut body = serde_json::json!({
+            "channel": channel,
+            "ts": ts,
+            "text": truncate_slack_text(text),
+        });
+        if let Some(blocks) = blocks {
+            body["blocks"] = blocks;
+        }
+        let request = self
+            .http
+            .post(format!("{}/chat.update", self.api_base))
+            .json(&body);
+        self.call(request, "chat.update").await.map(|_| ())
+    }
+
+    async fn user_name(&self, user_id: &str) -> anyhow::Result<String> {
+        let query = serde_urlencoded::to_string([("user", user_id)])
+            .context("encode slack users.info query")?;
+        let request = self
+            .http
+            .get(format!("{}/users.info?{query}", self.api_base));
+        let user = self
+            .call(request, "users.info")
+            .await?
+            .user
+            .ok_or_else(|| anyhow!("slack users.info returned no user"))?;
+        Ok(user
+            .real_name
+            .filter(|v| !v.trim().is_empty())
+            .or(user.name)
+            .unwrap_or_else(|| user_id.to_owned()))
+    }
+
+    async fn respond(&self, response_url: &str, text: &str) -> anyhow::Result<()> {
+        self.http
+            .post(response_url)
+            .json(&serde_json::json!({
+                "response_type": "ephemeral",
+                "text": truncate_slack_text(text),
+            }))
+            .send()
+            .await
+            .context("slack response_url request failed")?
+            .error_for_status()
+            .context("slack response_url rejected")?;
+        Ok(())
+    }
+}
+
+/// A Slack thread mirroring one task. Persisted so replies keep routing after a restart.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SlackThread {
+    workspace_id: u64,
+    thread_id: u64,
+    channel: String,
+    thread_ts: String,
+}
+
+#[derive(Clone, Debug, Default)]
+struct SlackProgress {
+    ts: Option<String>,
+    recent_events: Vec<String>,
+    final_text: Option<String>,
+}
+
+impl SlackProgress {
+    fn apply(&mut self, update: ProgressUpdate) -> bool {
+        match update {
+            ProgressUpdate::Event(line) => {
+                push_recent_progress_event(&mut self.recent_events, line, 5)
+            }
+            ProgressUpdate::Final(text) => {
+                if self.final_text.as_deref() == Some(text.as_str()) {
+                    return false;
+                }
+                self.final_text = Some(text);
+                true
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RootMessageState {
+    title: String,
+    status: TaskStatus,
+}
+
+struct SlackGateway {
+    engine: EngineHandle,
+    events: broadcast::Receiver<WsServerMessage>,
+    inbound: mpsc::Receiver<SlackInbound>,
+    api: SlackApi,
+    threads_path: Option<PathBuf>,
+    threads: HashMap<(u64, u64), SlackThread>,
+    progress: HashMap<(u64, u64), SlackProgress>,
+    last_seen_entry_index: HashMap<(u64, u64), u64>,
+    root_messages: HashMap<(u64, u64), RootMessageState>,
+    user_names: HashMap<String, String>,
+}
+
+impl SlackGateway {
+    fn new(
+        engine: EngineHandle,
+        events: broadcast::Receiver<WsServerMessage>,
+        inbound: mpsc::Receiver<SlackInbound>,
+        config: &SlackConfig,
+    ) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(SLACK_REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        let threads_path = crate::server::resolve_luban_root()
+            .ok()
+            .map(|root| root.join(SLACK_THREADS_FILE_NAME));
+        Self {
+            engine,
+            events,
+            inbound,
+            api: SlackApi {
+                http,
+                api_base: config.api_base.clone(),
+                bot_token: config.bot_token.clone(),
+            },
+            threads_path,
+            threads: HashMap::new(),
+            progress: HashMap::new(),
+            last_seen_entry_index: HashMap::new(),
+            root_messages: HashMap::new(),
+            user_names: HashMap::new(),
+        }
+    }
+
+    async fn run(&mut self) -> anyhow::Result<()> {
+        self.load_threads().await;
+
+        loop {
+            tokio::select! {
+                msg = self.events.recv() => match msg {
+                    Ok(msg) => self.handle_server_message(msg).await,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                },
+                inbound = self.inbound.recv() => match inbound {
+                    Some(inbound) => {
+                        if let Err(err) = self.handle_inbound(inbound).await {
+                            tracing::warn!(error = %err, "slack request failed");
+                        }
+                    }
+                    None => return Ok(()),
+                },
+            }
+        }
+    }
+
+    async fn load_threads(&mut self) {
+        let Some(path) = self.threads_path.as_ref() else {
+            return;
+        };
+        let threads = match tokio::fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice::<Vec<SlackThread>>(&bytes).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        for thread in threads {
+            let key = (thread.workspace_id, thread.thread_id);
+            // Only relay entries produced after startup; history was posted before.
+            if let Some(last) = self.last_entry_index(key).await {
+                self.last_seen_entry_index.insert(key, last);
+            }
+            self.threads.insert(key, thread);
+        }
+    }
+
+    async fn save_threads(&self) {
+        let Some(path) = self.threads_path.as_ref() else {
+            return;
+        };
+        let mut threads = self.threads.values().cloned().collect::<Vec<_>>();
+        threads.sort_by_key(|t| (t.workspace_id, t.thread_id));
+        let result = async {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let bytes = serde_json::to_vec_pretty(&threads)?;
+            tokio::fs::write(path, bytes).await?;
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(err) = result {
+            tracing::warn!(error = %err, "failed to save slack threads");
+        }
+    }
+
+    async fn last_entry_index(&self, key: (u64, u64)) -> Option<u64> {
+        let snapshot = self
+            .engine
+            .conversation_snapshot(
+                luban_api::WorkspaceId(key.0),
+                luban_api::WorkspaceThreadId(key.1),
+                None,
+                Some(1),
+            )
+            .await
+            .ok()?;
+        snapshot
+            .entries
+            .len()
+            .checked_sub(1)
+            .map(|idx| snapshot.entries_start.saturating_add(idx as u64))
+    }
+
+    async fn handle_server_message(&mut self, msg: WsServerMessage) {
+        let WsServerMessage::Event { event, .. } = msg else {
+            return;
+        };
+
+        match *event {
+            ServerEvent::ConversationChanged { snapshot } => {
+                self.relay_conversation(&snapshot).await;
+            }
+            ServerEvent::TaskSummariesChanged { tasks, .. } => {
+                for task in tasks {
+                    self.refresh_root_message(&task).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn relay_conversation(&mut self, snapshot: &luban_api::ConversationSnapshot) {
+        let key = (snapshot.workspace_id.0, snapshot.thread_id.0);
+        let Some(thread) = self.threads.get(&key).cloned() else {
+            return;
+        };
+
+        let last_seen = self.last_seen_entry_index.get(&key).copied();
+        let mut max_seen = last_seen;
+        let mut needs_render = false;
+        for (idx, entry) in snapshot.entries.iter().enumerate() {
+            let global_idx = snapshot.entries_start.saturating_add(idx as u64);
+            if last_seen.is_some_and(|last_seen| global_idx <= last_seen) {
+                continue;
+            }
+            max_seen = Some(global_idx);
+
+            if matches!(entry, ConversationEntry::UserEvent(user) if matches!(user.event, luban_api::UserEvent::Message(_)))
+            {
+                // A new user turn gets its own progress reply so earlier answers stay intact.
+                if needs_render {
+                    self.render_progress(key, &thread).await;
+                }
+                self.progress.insert(key, SlackProgress::default());
+                needs_render = true;
+                continue;
+            }
+
+            if let Some(update) = format_conversation_entry_for_progress(entry) {
+                let progress = self.progress.entry(key).or_default();
+                if progress.apply(update) {
+                    needs_render = true;
+                }
+            }
+        }
+
+        if let Some(max_seen) = max_seen {
+            self.last_seen_entry_index.insert(key, max_seen);
+        }
+        if needs_render {
+            self.render_progress(key, &thread).await;
+        }
+    }
+
+    async fn render_progress(&mut self, key: (u64, u64), thread: &SlackThread) {
+        let Some(progress) = self.progress.get(&key).cloned() else {
+            return;
+        };
+        let text = render_progress_body(progress.final_text.as_deref(), &progress.recent_events);
+
+        if let Some(ts) = progress.ts.as_deref() {
+            match self
+                .api
+                .update_message(&thread.channel, ts, &text, None)
+                .await
+            {
+                Ok(()) => return,
+                Err(err) => tracing::warn!(error = %err, "slack progress update failed"),
+            }
+        }
+
+        match self
+            .api
+            .post_message(&thread.channel, Some(&thread.thread_ts), &text, None)
+            .await
+        {
+            Ok(ts) => {
+                if let Some(progress) = self.progress.get_mut(&key) {
+                    progress.ts = Some(ts);
+                }
+            }
+            Err(err) => tracing::warn!(error = %err, "slack progress post failed"),
+        }
+    }
+
+    async fn refresh_root_message(&mut self, task: &luban_api::TaskSummarySnapshot) {
+        let key = (task.workspace_id.0, task.thread_id.0);
+        let Some(thread) = self.threads.get(&key).cloned() else {
+            return;
+        };
+        let next = RootMessageState {
+            title: task_title_or_default(&task.title),
+            status: task.task_status,
+        };
+        if self.root_messages.get(&key) == Some(&next) {
+            return;
+        }
+
+        let blocks = task_root_blocks(&next.title, next.status, key.0, key.1);
+        match self
+            .api
+            .update_message(
+                &thread.channel,
+                &thread.thread_ts,
+                &next.title,
+                Some(blocks),
+            )
+            .await
+        {
+            Ok(()) => {
+                self.root_messages.insert(key, next);
+            }
+            Err(err) => tracing::warn!(error = %err, "slack task message update failed"),
+        }
+    }
+
+    async fn handle_inbound(&mut self, inbound: SlackInbound) -> anyhow::Result<()> {
+        match inbound {
+            SlackInbound::Command {
+                command,
+                channel_id,
+                user_name,
+                response_url,
+            } => {
+                let reply = match self.handle_command(command, &channel_id, &user_name).await {
+                    Ok(reply) => reply,
+                    Err(err) => format!("Failed: {err:#}"),
+                };
+                self.api.respond(&response_url, &reply).await
+            }
+            SlackInbound::ThreadMessage {
+                channel,
+                thread_ts,
+                user,
+                text,
+            } => {
+                let Some(key) = self
+                    .threads
+                    .iter()
+                    .find(|(_, t)| t.channel == channel && t.thread_ts == thread_ts)
+                    .map(|(key, _)| *key)
+                else {
+                    return Ok(());
+                };
+                let author = self.display_name(&user).await;
+                self.send_agent_message(key, text, Some(author)).await;
+                Ok(())
+            }
+            SlackInbound::Action(action) => {
+                let (request_id, action) = match action {
+                    SlackTaskAction::SetStatus {
+                        workspace_id,
+                        thread_id,
+                        status,
+                    } => (
+                        "slack_task_status",
+                        luban_api::ClientAction::TaskStatusSet {
+                            workspace_id: luban_api::WorkspaceId(workspace_id),
+                            thread_id: luban_api::WorkspaceThreadId(thread_id),
+                            task_status: status,
+                        },
+                    ),
+                    SlackTaskAction::CancelTurn {
+                        workspace_id,
+                        thread_id,
+                    } => (
+                        "slack_cancel_turn",
+                        luban_api::ClientAction::CancelAgentTurn {
+                            workspace_id: luban_api::WorkspaceId(workspace_id),
+                            thread_id: luban_api::WorkspaceThreadId(thread_id),
+                        },
+                    ),
+                };
+                self.engine
+                    .apply_client_action_from(AuditOrigin::Slack, request_id.to_owned(), action)
+                    .await
+                    .map(|_| ())
+                    .map_err(|err| anyhow!(err))
+            }
+        }
+    }
+
+    async fn handle_command(
+        &mut self,
+        command: SlackCommand,
+        channel_id: &str,
+        user_name: &str,
+    ) -> anyhow::Result<String> {
+        match command {
+            SlackCommand::Help => Ok(SLACK_HELP_TEXT.to_owned()),
+            SlackCommand::Tasks => self.recent_tasks_text().await,
+            SlackCommand::Cancel {
+                workspace_id,
+                thread_id,
+            } => {
+                let action = luban_api::ClientAction::CancelAgentTurn {
+                    workspace_id: luban_api::WorkspaceId(workspace_id),
+                    thread_id: luban_api::WorkspaceThreadId(thread_id),
+                };
+                self.engine
+                    .apply_client_action_from(
+                        AuditOrigin::Slack,
+                        "slack_cancel_turn".to_owned(),
+                        action,
+                    )
+                    .await
+                    .map_err(|err| anyhow!(err))?;
+                Ok(format!(
+                    "Cancel requested for `{workspace_id}/{thread_id}`."
+                ))
+            }
+            SlackCommand::New {
+                project_slug,
+                prompt,
+            } => {
+                let key = create_task_in_new_worktree(&self.engine, "slack", &project_slug).await?;
+                let title = task_title_or_default(&truncate_label(&prompt, 80));
+                let blocks = task_root_blocks(&title, TaskStatus::default(), key.0, key.1);
+                let thread_ts = self
+                    .api
+                    .post_message(channel_id, None, &title, Some(blocks))
+                    .await?;
+
+                self.threads.insert(
+                    key,
+                    SlackThread {
+                        workspace_id: key.0,
+                        thread_id: key.1,
+                        channel: channel_id.to_owned(),
+                        thread_ts,
+                    },
+                );
+                self.root_messages.insert(
+                    key,
+                    RootMessageState {
+                        title: title.clone(),
+                        status: TaskStatus::default(),
+                    },
+                );
+                self.save_threads().await;
+
+                let author = Some(user_name.trim().to_owned()).filter(|v| !v.is_empty());
+                self.send_agent_message(key, prompt, author).await;
+                Ok(format!(
+                    "Started task `{}/{}` in `{project_slug}`.",
+                    key.0, key.1
+                ))
+            }
+        }
+    }
+
+    async fn recent_tasks_text(&self) -> anyhow::Result<String> {
+        let snapshot = crate::server::collect_tasks_snapshot(
+            &self.engine,
+            None,
+            crate::server::WorkdirStatusFilter::Active,
+            None,
+        )
+        .await?;
+        let mut tasks = snapshot.tasks;
+        tasks.sort_by_key(|t| std::cmp::Reverse(t.updated_at_unix_seconds));
+        if tasks.is_empty() {
+            return Ok("No tasks yet.".to_owned());
+        }
+
+        let lines = tasks
+            .iter()
+            .take(SLACK_RECENT_TASKS_LIMIT)
+            .map(|t| {
+                format!(
+                    "• `{}/{}` {} · {} ({})",
+                    t.workspace_id.0,
+                    t.thread_id.0,
+                    task_title_or_default(&t.title),
+                    t.workspace_name,
+                    task_status_label(t.task_status)
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(lines.join("\n"))
+    }
+
+    async fn send_agent_message(&self, key: (u64, u64), text: String, author: Option<String>) {
+        let action = luban_api::ClientAction::SendAgentMessage {
+            workspace_id: luban_api::WorkspaceId(key.0),
+            thread_id: luban_api::WorkspaceThreadId(key.1),
+            text,
+            attachments: Vec::new(),
+            runner: None,
+            amp_mode: None,
+            author,
+        };
+        let _ = self
+            .engine
+            .apply_client_action_from(AuditOrigin::Slack, "slack_send".to_owned(), action)
+            .await;
+    }
+
+    async fn display_name(&mut self, user_id: &str) -> String {
+        if let Some(name) = self.user_names.get(user_id) {
+            return name.clone();
+        }
+        let name = match self.api.user_name(user_id).await {
+            Ok(name) => name,
+            Err(err) => {
+                tracing::warn!(error = %err, "slack users.info failed");
+                return user_id.to_owned();
+            }
+        };
+        self.user_names.insert(user_id.to_owned(), name.clone());
+        name
+    }
+}
+
+fn task_title_or_default(raw: &str) -> String {
+    let title = raw.trim();
+    if title.is_empty() {
+        "Task".to_owned()
+    } else {
+        truncate_label(title, 80)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &str, ts: u64, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{ts}:").as_bytes());
+        mac.update(body);
+        let digest = mac.finalize().into_bytes();
+        let hex = digest
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+        format!("v0={hex}")
+    }
+
+    #[test]
+    fn slack_signature_accepts_valid_and_rejects_tampered_or_stale() {
+        let body = b"token=x&text=tasks";
+        let signature = sign("secret", 1_000, body);
+
+        assert!(verify_slack_signature(
+            "secret", "1000", body, &signature, 1_010
+        ));
+        assert!(!verify_slack_signature(
+            "other", "1000", body, &signature, 1_010
+        ));
+        assert!(!verify_slack_signature(
+            "secret",
+            "1000",
+            b"token=x&text=new",
+            &signature,
+            1_010
+        ));
+        assert!(!verify_slack_signature(
+            "secret",
+            "1000",
+            body,
+            &signature,
+            1_000 + SLACK_MAX_SIGNATURE_AGE_SECS + 1
+        ));
+        assert!(!verify_slack_signature(
+            "secret", "1000", body, "v0=zz", 1_010
+        ));
+    }
+
+    #[test]
+    fn slack_access_allows_listed_users_or_channels_only() {
+        let nobody = SlackAccess::default();
+        assert!(!nobody.allows("U1", "C1"));
+
+        let access = SlackAccess {
+            users: parse_id_list("U1, U2\nU3"),
+            channels: parse_id_list("C9"),
+        };
+        assert_eq!(access.users.len(), 3);
+        assert!(access.allows("U2", "C1"));
+        assert!(access.allows("U7", "C9"));
+        assert!(!access.allows("U7", "C1"));
+        assert!(!access.allows("", ""));
+    }
+
+    #[test]
+    fn parse_command_handles_verbs_and_errors() {
+        assert_eq!(parse_command(""), Ok(SlackCommand::Help));
+        assert_eq!(parse_command(" tasks "), Ok(SlackCommand::Tasks));
+        assert_eq!(
+            parse_command("new my-app  fix the login bug"),
+            Ok(SlackCommand::New {
+                project_slug: "my-app".to_owned(),
+                prompt: "fix the login bug".to_owned(),
+            })
+        );
+        assert_eq!(
+            parse_command("cancel 12/3"),
+            Ok(SlackCommand::Cancel {
+                workspace_id: 12,
+                thread_id: 3,
+            })
+        );
+        assert!(parse_command("new my-app").is_err());
+        assert!(parse_command("cancel 12").is_err());
+        assert!(parse_command("deploy").is_err());
+    }
+
+    #[test]
+    fn task_root_blocks_round_trip_through_action_parsing() {
+        let blocks = task_root_blocks("Fix login", TaskStatus::Iterating, 4, 7);
+        let elements = blocks[1]["elements"].as_array().unwrap();
+        let parsed = elements
+            .iter()
+            .map(|button| {
+                let action_id = button["action_id"].as_str().unwrap();
+                let action_id = if action_id.starts_with(ACTION_TASK_STATUS) {
+                    ACTION_TASK_STATUS
+                } else {
+                    action_id
+                };
+                parse_task_action(action_id, button["value"].as_str().unwrap()).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            parsed,
+            vec![
+                SlackTaskAction::SetStatus {
+                    workspace_id: 4,
+                    thread_id: 7,
+                    status: TaskStatus::Validating,
+                },
+                SlackTaskAction::SetStatus {
+                    workspace_id: 4,
+                    thread_id: 7,
+                    status: TaskStatus::Done,
+                },
+                SlackTaskAction::CancelTurn {
+                    workspace_id: 4,
+                    thread_id: 7,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn thread_message_from_event_ignores_bots_edits_and_top_level_messages() {
+        let event =
+            |subtype: Option<&str>, bot_id: Option<&str>, thread_ts: Option<&str>| SlackEvent {
+                kind: "message".to_owned(),
+                subtype: subtype.map(str::to_owned),
+                bot_id: bot_id.map(str::to_owned),
+                user: Some("U1".to_owned()),
+                channel: Some("C1".to_owned()),
+                text: Some("hello".to_owned()),
+                thread_ts: thread_ts.map(str::to_owned),
+            };
+
+        assert!(thread_message_from_event(event(None, None, Some("1.2"))).is_some());
+        assert!(
+            thread_message_from_event(event(Some("message_changed"), None, Some("1.2"))).is_none()
+        );
+        assert!(thread_message_from_event(event(None, Some("B1"), Some("1.2"))).is_none());
+        assert!(thread_message_from_event(event(None, None, None)).is_none());
+    }
+
+    #[tokio::test]
+    async fn slack_api_posts_and_updates_thread_messages() {
+        let (tx, mut rx) = mpsc::unbounded_channel::<(String, Option<String>, serde_json::Value)>();
+        let record = |method: &'static str| {
+            let tx = tx.clone();
+            axum::routing::post(
+                move |headers: HeaderMap, Json(body): Json<serde_json::Value>| async move {
+                    let auth = headers
+                        .get("authorization")
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_owned);
+                    let _ = tx.send((method.to_owned(), auth, body));
+                    Json(serde_json::json!({ "ok": true, "ts": "171.42" }))
+                },
+            )
+        };
+        let app = axum::Router::new()
+            .route("/chat.postMessage", record("chat.postMessage"))
+            .route("/chat.update", record("chat.update"));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let api = SlackApi {
+            http: reqwest::Client::new(),
+            api_base: format!("http://{addr}"),
+            bot_token: "xoxb-test".to_owned(),
+        };
+        let ts = api
+            .post_message("C1", Some("100.1"), "Working...", None)
+            .await
+            .unwrap();
+        assert_eq!(ts, "171.42");
+        api.update_message("C1", &ts, "Done", None).await.unwrap();
+
+        let (method, auth, body) = rx.recv().await.unwrap();
+        assert_eq!(method, "chat.postMessage");
+        assert_eq!(auth.as_deref(), Some("Bearer xoxb-test"));
+        assert_eq!(body["thread_ts"], "100.1");
+        assert_eq!(body["text"], "Working...");
+
+        let (method, _, body) = rx.recv().await.unwrap();
+        assert_eq!(method, "chat.update");
+        assert_eq!(body["ts"], "171.42");
+        assert_eq!(body["text"], "Done");
+    }
+}
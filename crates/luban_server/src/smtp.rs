@@ -0,0 +1,551 @@
+//! Minimal SMTP submission client used by the email notification channel.
+//!
+//! Supports plain connections (local relays and test sinks), STARTTLS and implicit TLS, with
+//! optional `AUTH PLAIN` / `AUTH LOGIN`.
+
+use anyhow::{Context as _, anyhow, bail};
+use base64::Engine as _;
+use rustls_platform_verifier::BuilderVerifierExt as _;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt as _, AsyncRead, AsyncWrite, AsyncWriteExt as _, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls;
+
+const SMTP_CONNECT_TIMEOUT_SECS: u64 = 10;
+const SMTP_SESSION_TIMEOUT_SECS: u64 = 60;
+const SMTP_BODY_LINE_CHARS: usize = 76;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SmtpSecurity {
+    None,
+    StartTls,
+    Tls,
+}
+
+impl SmtpSecurity {
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "none" | "plain" | "off" => Some(Self::None),
+            "starttls" => Some(Self::StartTls),
+            "tls" | "ssl" | "smtps" => Some(Self::Tls),
+            _ => None,
+        }
+    }
+
+    /// Port 465 is implicit TLS; every other submission port is expected to offer STARTTLS.
+    pub(crate) fn default_for_port(port: u16) -> Self {
+        if port == 465 {
+            Self::Tls
+        } else {
+            Self::StartTls
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SmtpConfig {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) security: SmtpSecurity,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+    pub(crate) from: String,
+    pub(crate) to: Vec<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct EmailMessage {
+    pub(crate) subject: String,
+    pub(crate) body: String,
+}
+
+pub(crate) async fn send_email(config: &SmtpConfig, message: &EmailMessage) -> anyhow::Result<()> {
+    if config.to.is_empty() {
+        bail!("no email recipients configured");
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let data = format_message(config, message, now, &ulid::Ulid::new().to_string());
+
+    tokio::time::timeout(
+        Duration::from_secs(SMTP_SESSION_TIMEOUT_SECS),
+        deliver(config, &data),
+    )
+    .await
+    .map_err(|_| anyhow!("smtp session timed out"))?
+}
+
+async fn deliver(config: &SmtpConfig, data: &str) -> anyhow::Result<()> {
+    let tcp = tokio::time::timeout(
+        Duration::from_secs(SMTP_CONNECT_TIMEOUT_SECS),
+        TcpStream::connect((config.host.as_str(), config.port)),
+    )
+    .await
+    .map_err(|_| anyhow!("smtp connect timed out"))?
+    .with_context(|| format!("failed to connect to {}:{}", config.host, config.port))?;
+
+    match config.security {
+        SmtpSecurity::None => {
+            let mut stream = BufReader::new(tcp);
+            expect_reply(&mut stream, 220).await?;
+            let extensions = ehlo(&mut stream).await?;
+            transact(&mut stream, config, &extensions, data).await
+        }
+        SmtpSecurity::Tls => {
+            let tls = tls_connect(&config.host, tcp).await?;
+            let mut stream = BufReader::new(tls);
+            expect_reply(&mut stream, 220).await?;
+            let extensions = ehlo(&mut stream).await?;
+            transact(&mut stream, config, &extensions, data).await
+        }
+        SmtpSecurity::StartTls => {
+            let mut stream = BufReader::new(tcp);
+            expect_reply(&mut stream, 220).await?;
+            let extensions = ehlo(&mut stream).await?;
+            if !extensions.iter().any(|ext| ext == "STARTTLS") {
+                bail!("smtp server does not offer STARTTLS");
+            }
+            command(&mut stream, "STARTTLS", 220).await?;
+            let tls = tls_connect(&config.host, stream.into_inner()).await?;
+            let mut stream = BufReader::new(tls);
+            let extensions = ehlo(&mut stream).await?;
+            transact(&mut stream, config, &extensions, data).await
+        }
+    }
+}
+
+async fn tls_connect(
+    host: &str,
+    tcp: TcpStream,
+) -> anyhow::Result<tokio_rustls::client::TlsStream<TcpStream>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .context("smtp tls protocol versions")?
+        .with_platform_verifier()
+        .context("smtp tls verifier")?
+        .with_no_client_auth();
+    let server_name = rustls::pki_types::ServerName::try_from(host.to_owned())
+        .with_context(|| format!("invalid smtp host name: {host}"))?;
+    TlsConnector::from(Arc::new(config))
+        .connect(server_name, tcp)
+        .await
+        .context("smtp tls handshake failed")
+}
+
+async fn transact<S>(
+    stream: &mut BufReader<S>,
+    config: &SmtpConfig,
+    extensions: &[String],
+    data: &str,
+) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        authenticate(stream, extensions, username, password).await?;
+    }
+
+    command(
+        stream,
+        &format!("MAIL FROM:<{}>", envelope_address(&config.from)),
+        250,
+    )
+    .await?;
+    for recipient in &config.to {
+        let (code, text) = send_line(
+            stream,
+            &format!("RCPT TO:<{}>", envelope_address(recipient)),
+        )
+        .await?;
+        if code != 250 && code != 251 {
+            bail!("smtp rejected recipient {recipient}: {code} {text}");
+        }
+    }
+    command(stream, "DATA", 354).await?;
+    stream.get_mut().write_all(data.as_bytes()).await?;
+    stream.get_mut().write_all(b"\r\n.\r\n").await?;
+    stream.get_mut().flush().await?;
+    expect_reply(stream, 250).await?;
+
+    // The message is accepted at this point; a failed QUIT is not worth reporting.
+    let _ = send_line(stream, "QUIT").await;
+    Ok(())
+}
+
+async fn authenticate<S>(
+    stream: &mut BufReader<S>,
+    extensions: &[String],
+    username: &str,
+    password: &str,
+) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let mechanisms = extensions
+        .iter()
+        .find_map(|ext| ext.strip_prefix("AUTH "))
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>();
+
+    // Credential lines go through `send_line` so they never end up in error messages.
+    let (code, text) = if mechanisms.contains(&"LOGIN") && !mechanisms.contains(&"PLAIN") {
+        command(stream, "AUTH LOGIN", 334).await?;
+        let (code, text) = send_line(stream, &b64.encode(username)).await?;
+        if code != 334 {
+            bail!("smtp authentication failed: {code} {text}");
+        }
+        send_line(stream, &b64.encode(password)).await?
+    } else {
+        let token = b64.encode(format!("\0{username}\0{password}"));
+        send_line(stream, &format!("AUTH PLAIN {token}")).await?
+    };
+    if code != 235 {
+        bail!("smtp authentication failed: {code} {text}");
+    }
+    Ok(())
+}
+
+/// Sends `EHLO` and returns the advertised extensions, upper-cased.
+async fn ehlo<S>(stream: &mut BufReader<S>) -> anyhow::Result<Vec<String>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (code, text) = send_line(stream, "EHLO luban").await?;
+    if code != 250 {
+        bail!("smtp EHLO failed: {code} {text}");
+    }
+    Ok(text
+        .lines()
+        .skip(1)
+        .map(|line| line.trim().to_ascii_uppercase())
+        .collect())
+}
+
+async fn command<S>(stream: &mut BufReader<S>, line: &str, expected: u16) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (code, text) = send_line(stream, line).await?;
+    if code != expected {
+        let verb = line.split_whitespace().next().unwrap_or_default();
+        bail!("smtp {verb} failed: {code} {text}");
+    }
+    Ok(())
+}
+
+async fn send_line<S>(stream: &mut BufReader<S>, line: &str) -> anyhow::Result<(u16, String)>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.get_mut().write_all(line.as_bytes()).await?;
+    stream.get_mut().write_all(b"\r\n").await?;
+    stream.get_mut().flush().await?;
+    read_reply(stream).await
+}
+
+async fn expect_reply<S>(stream: &mut BufReader<S>, expected: u16) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (code, text) = read_reply(stream).await?;
+    if code != expected {
+        bail!("unexpected smtp reply: {code} {text}");
+    }
+    Ok(())
+}
+
+/// Reads a possibly multi-line reply and returns its code and the text of each line.
+async fn read_reply<S>(stream: &mut BufReader<S>) -> anyhow::Result<(u16, String)>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            bail!("smtp connection closed");
+        }
+        let line = line.trim_end();
+        let code = line
+            .get(..3)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| anyhow!("malformed smtp reply: {line}"))?;
+        lines.push(line.get(4..).unwrap_or_default().to_owned());
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok((code, lines.join("\n")));
+        }
+    }
+}
+
+/// Extracts `user@host` from `Name <user@host>`.
+fn envelope_address(raw: &str) -> &str {
+    let raw = raw.trim();
+    match (raw.rfind('<'), raw.rfind('>')) {
+        (Some(start), Some(end)) if start < end => raw[start + 1..end].trim(),
+        _ => raw,
+    }
+}
+
+fn format_message(
+    config: &SmtpConfig,
+    message: &EmailMessage,
+    now_unix_secs: u64,
+    message_id: &str,
+) -> String {
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let domain = envelope_address(&config.from)
+        .rsplit_once('@')
+        .map(|(_, domain)| domain)
+        .unwrap_or("luban");
+
+    // A base64 body never contains a line starting with '.', so no dot-stuffing is needed.
+    let encoded = b64.encode(message.body.as_bytes());
+    let body = encoded
+        .as_bytes()
+        .chunks(SMTP_BODY_LINE_CHARS)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join("\r\n");
+
+    [
+        format!("From: {}", config.from.trim()),
+        format!("To: {}", config.to.join(", ")),
+        format!("Subject: {}", encode_header(&message.subject)),
+        format!("Date: {}", rfc2822_date(now_unix_secs)),
+        format!("Message-ID: <{message_id}@{domain}>"),
+        "MIME-Version: 1.0".to_owned(),
+        "Content-Type: text/plain; charset=utf-8".to_owned(),
+        "Content-Transfer-Encoding: base64".to_owned(),
+        String::new(),
+        body,
+    ]
+    .join("\r\n")
+}
+
+fn encode_header(raw: &str) -> String {
+    let raw = raw.replace(['\r', '\n'], " ");
+    if raw.is_ascii() {
+        return raw;
+    }
+    format!(
+        "=?UTF-8?B?{}?=",
+        base64::engine::general_purpose::STANDARD.encode(raw.as_bytes())
+    )
+}
+
+fn rfc2822_date(unix_secs: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let days = unix_secs / 86_400;
+    let secs = unix_secs % 86_400;
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{}, {day:02} {} {year} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[(days % 7) as usize],
+        MONTHS[(month - 1) as usize],
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) civil date.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Accepts one SMTP session, answers every command with success and returns the transcript.
+    pub(crate) async fn start_smtp_sink(
+        extensions: &'static [&'static str],
+    ) -> (u16, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(socket);
+            let mut transcript = Vec::new();
+            stream
+                .get_mut()
+                .write_all(b"220 sink ready\r\n")
+                .await
+                .unwrap();
+            let mut in_data = false;
+            let mut login_steps = 0;
+            loop {
+                let mut line = String::new();
+                if stream.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end_matches(['\r', '\n']).to_owned();
+                transcript.push(line.clone());
+                let reply = if login_steps > 0 {
+                    login_steps -= 1;
+                    if login_steps > 0 {
+                        "334 UGFzc3dvcmQ6\r\n"
+                    } else {
+                        "235 ok\r\n"
+                    }
+                    .to_owned()
+                } else if in_data {
+                    if line != "." {
+                        continue;
+                    }
+                    in_data = false;
+                    "250 queued\r\n".to_owned()
+                } else if line.starts_with("EHLO") {
+                    let mut reply = "250-sink\r\n".to_owned();
+                    for ext in extensions {
+                        reply.push_str(&format!("250-{ext}\r\n"));
+                    }
+                    reply.push_str("250 8BITMIME\r\n");
+                    reply
+                } else if line == "AUTH LOGIN" {
+                    login_steps = 2;
+                    "334 VXNlcm5hbWU6\r\n".to_owned()
+                } else if line.starts_with("AUTH") {
+                    "235 ok\r\n".to_owned()
+                } else if line == "DATA" {
+                    in_data = true;
+                    "354 go ahead\r\n".to_owned()
+                } else if line == "QUIT" {
+                    stream.get_mut().write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    "250 ok\r\n".to_owned()
+                };
+                stream.get_mut().write_all(reply.as_bytes()).await.unwrap();
+            }
+            transcript
+        });
+        (port, handle)
+    }
+
+    fn sink_config(port: u16) -> SmtpConfig {
+        SmtpConfig {
+            host: "127.0.0.1".to_owned(),
+            port,
+            security: SmtpSecurity::None,
+            username: Some("bot".to_owned()),
+            password: Some("pw".to_owned()),
+            from: "Luban <luban@example.com>".to_owned(),
+            to: vec!["a@example.com".to_owned(), "b@example.com".to_owned()],
+        }
+    }
+
+    #[tokio::test]
+    async fn send_email_delivers_to_smtp_sink() {
+        let (port, sink) = start_smtp_sink(&["AUTH PLAIN LOGIN"]).await;
+        send_email(
+            &sink_config(port),
+            &EmailMessage {
+                subject: "Turn finished: Fix löğin".to_owned(),
+                body: "Task: Fix login\n.hidden line".to_owned(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let transcript = sink.await.unwrap();
+        let token = base64::engine::general_purpose::STANDARD.encode("\0bot\0pw");
+        assert_eq!(transcript[0], "EHLO luban");
+        assert_eq!(transcript[1], format!("AUTH PLAIN {token}"));
+        assert_eq!(transcript[2], "MAIL FROM:<luban@example.com>");
+        assert_eq!(transcript[3], "RCPT TO:<a@example.com>");
+        assert_eq!(transcript[4], "RCPT TO:<b@example.com>");
+        assert_eq!(transcript[5], "DATA");
+        assert!(transcript.contains(&"To: a@example.com, b@example.com".to_owned()));
+        assert!(
+            transcript
+                .iter()
+                .any(|l| l.starts_with("Subject: =?UTF-8?B?"))
+        );
+        assert_eq!(transcript.last().map(String::as_str), Some("QUIT"));
+
+        let start = transcript.iter().position(|l| l.is_empty()).unwrap() + 1;
+        let end = transcript.iter().position(|l| l == ".").unwrap();
+        let body = base64::engine::general_purpose::STANDARD
+            .decode(transcript[start..end].concat())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "Task: Fix login\n.hidden line"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_email_uses_auth_login_when_plain_is_not_offered() {
+        let (port, sink) = start_smtp_sink(&["AUTH LOGIN"]).await;
+        send_email(
+            &sink_config(port),
+            &EmailMessage {
+                subject: "Digest".to_owned(),
+                body: "ok".to_owned(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let transcript = sink.await.unwrap();
+        let b64 = base64::engine::general_purpose::STANDARD;
+        assert_eq!(transcript[1], "AUTH LOGIN");
+        assert_eq!(transcript[2], b64.encode("bot"));
+        assert_eq!(transcript[3], b64.encode("pw"));
+        assert_eq!(transcript[4], "MAIL FROM:<luban@example.com>");
+    }
+
+    #[tokio::test]
+    async fn starttls_requires_server_support() {
+        let (port, _sink) = start_smtp_sink(&[]).await;
+        let mut config = sink_config(port);
+        config.security = SmtpSecurity::StartTls;
+        let err = send_email(
+            &config,
+            &EmailMessage {
+                subject: "x".to_owned(),
+                body: "y".to_owned(),
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("STARTTLS"), "{err:#}");
+    }
+
+    #[test]
+    fn rfc2822_date_formats_utc() {
+        assert_eq!(rfc2822_date(0), "Thu, 01 Jan 1970 00:00:00 +0000");
+        assert_eq!(
+            rfc2822_date(1_792_411_200),
+            "Mon, 19 Oct 2026 12:00:00 +0000"
+        );
+    }
+
+    #[test]
+    fn envelope_address_strips_display_name() {
+        assert_eq!(envelope_address("Luban <bot@x.dev>"), "bot@x.dev");
+        assert_eq!(envelope_address(" bot@x.dev "), "bot@x.dev");
+    }
+}
@@ -1,16 +1,27 @@
 use crate::engine::EngineHandle;
-use crate::engine::TelegramRuntimeConfig;
-use anyhow::Context as _;
+use crate::engine::{TelegramGroupPairing, TelegramRuntimeConfig};
+use crate::relay::{
+    ProgressUpdate, create_task, create_task_in_new_worktree,
+    format_conversation_entry_for_progress, push_recent_progress_event, render_progress_body,
+    truncate_label,
+};
+use anyhow::{Context as _, anyhow};
 use luban_api::{ConversationEntry, ServerEvent, TaskStatus, WsServerMessage};
-use luban_domain::Action;
+use luban_domain::{Action, AuditOrigin, ContextImage, ProjectWorkspaceService};
 use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
-use std::time::{Duration, Instant};
-use tokio::sync::broadcast;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc};
 
 const TELEGRAM_DISABLED_ENV: &str = "LUBAN_TELEGRAM_DISABLED";
 const TELEGRAM_API_BASE_URL_ENV: &str = "LUBAN_TELEGRAM_API_BASE_URL";
 const TELEGRAM_API_BASE_URL_DEFAULT: &str = "https://api.telegram.org";
+const TELEGRAM_WEBHOOK_URL_ENV: &str = "LUBAN_TELEGRAM_WEBHOOK_URL";
+const TELEGRAM_WEBHOOK_SECRET_ENV: &str = "LUBAN_TELEGRAM_WEBHOOK_SECRET";
+const TELEGRAM_WEBHOOK_SECRET_HEADER: &str = "x-telegram-bot-api-secret-token";
+const TELEGRAM_WEBHOOK_QUEUE_CAPACITY: usize = 256;
 
 const TELEGRAM_LONG_POLL_TIMEOUT_SECS: u64 = 20;
 const TELEGRAM_REQUEST_TIMEOUT_SECS: u64 = 30;
@@ -18,6 +29,18 @@ const TELEGRAM_MAX_MESSAGE_CHARS: usize = 3800;
 const TELEGRAM_REPLY_ROUTE_TTL_SECS: u64 = 6 * 60 * 60;
 const TELEGRAM_REPLY_ROUTE_MAX_ROUTES: usize = 256;
 const TELEGRAM_PARSE_MODE_MARKDOWN_V2: &str = "MarkdownV2";
+/// Bot API limit for `getFile` downloads.
+const TELEGRAM_MAX_DOWNLOAD_BYTES: u64 = 20 * 1024 * 1024;
+/// Bot API limit for `sendDocument` uploads.
+const TELEGRAM_MAX_UPLOAD_BYTES: u64 = 50 * 1024 * 1024;
+const TELEGRAM_ATTACHMENT_ONLY_PROMPT: &str = "See the attached file.";
+const TELEGRAM_TURN_DIFF_FILE_NAME: &str = "changes.diff";
+const TELEGRAM_WORKSPACE_DIFF_FILE_NAME: &str = "workspace.diff";
+const TELEGRAM_WORKSPACE_DIFF_CAPTION: &str =
+    "Workspace diff: all uncommitted changes, not only this turn's";
+const TELEGRAM_OUTPUT_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+/// Extensions `store_context_image` accepts; other image documents are stored as files.
+const CONTEXT_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
 
 const KB_HOME: &str = "Home";
 const KB_PROJECTS: &str = "Projects";
@@ -44,18 +67,111 @@ fn telegram_api_base_url() -> String {
         .unwrap_or_else(|| TELEGRAM_API_BASE_URL_DEFAULT.to_owned())
 }
 
-pub(crate) fn start_gateway(engine: EngineHandle, events: broadcast::Sender<WsServerMessage>) {
+/// Webhook settings read from the environment. When absent the gateway long-polls `getUpdates`.
+#[derive(Clone, Debug)]
+struct TelegramWebhookConfig {
+    url: String,
+    secret: String,
+}
+
+impl TelegramWebhookConfig {
+    fn from_env() -> Option<Self> {
+        let url = std::env::var(TELEGRAM_WEBHOOK_URL_ENV)
+            .ok()
+            .map(|v| v.trim().to_owned())
+            .filter(|v| !v.is_empty())?;
+        let secret = std::env::var(TELEGRAM_WEBHOOK_SECRET_ENV)
+            .ok()
+            .map(|v| v.trim().to_owned())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(generate_webhook_secret);
+        Some(Self { url, secret })
+    }
+}
+
+fn generate_webhook_secret() -> String {
+    use rand::RngCore as _;
+    let mut bytes = [0u8; 24];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Handle used by the webhook route to authenticate Telegram deliveries and hand them to the
+/// gateway.
+#[derive(Clone)]
+pub(crate) struct TelegramWebhookIntake {
+    secret: String,
+    tx: mpsc::Sender<TelegramUpdate>,
+}
+
+pub(crate) fn start_gateway(
+    engine: EngineHandle,
+    events: broadcast::Sender<WsServerMessage>,
+    services: Arc<dyn ProjectWorkspaceService>,
+) -> Option<TelegramWebhookIntake> {
     if telegram_disabled() {
         tracing::info!("telegram gateway disabled by env");
-        return;
+        return None;
     }
 
+    let webhook = TelegramWebhookConfig::from_env();
+    let (intake, updates) = match webhook.as_ref() {
+        Some(config) => {
+            let (tx, rx) = mpsc::channel(TELEGRAM_WEBHOOK_QUEUE_CAPACITY);
+            let intake = TelegramWebhookIntake {
+                secret: config.secret.clone(),
+                tx,
+            };
+            (Some(intake), Some(rx))
+        }
+        None => (None, None),
+    };
+
     tokio::spawn(async move {
-        let mut gateway = TelegramGateway::new(engine, events.subscribe());
+        let mut gateway = TelegramGateway::new(engine, events.subscribe(), services);
+        gateway.webhook = webhook;
+        gateway.webhook_updates = updates;
         if let Err(err) = gateway.run().await {
             tracing::warn!(error = %err, "telegram gateway stopped");
         }
     });
+
+    intake
+}
+
+/// Receives an update pushed by Telegram to the registered webhook.
+pub(crate) async fn handle_webhook(
+    intake: Option<&TelegramWebhookIntake>,
+    headers: &axum::http::HeaderMap,
+    body: &[u8],
+) -> axum::http::StatusCode {
+    use axum::http::StatusCode;
+
+    let Some(intake) = intake else {
+        return StatusCode::NOT_FOUND;
+    };
+    let provided = headers
+        .get(TELEGRAM_WEBHOOK_SECRET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !constant_time_eq(provided.as_bytes(), intake.secret.as_bytes()) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let update = match serde_json::from_slice::<TelegramUpdate>(body) {
+        Ok(update) => update,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+    if let Err(err) = intake.tx.try_send(update) {
+        tracing::warn!(error = %err, "dropping telegram webhook update");
+        // Ask Telegram to redeliver later instead of losing the update.
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+    StatusCode::OK
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
 pub(crate) async fn telegram_get_me_username(token: &str) -> Result<String, String> {
@@ -115,6 +231,18 @@ struct TelegramSendMessageResult {
     message_id: i64,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+struct TelegramForumTopic {
+    #[serde(default)]
+    message_thread_id: i64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct TelegramFile {
+    #[serde(default)]
+    file_path: Option<String>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 struct TelegramUpdate {
     update_id: i64,
@@ -139,8 +267,18 @@ struct TelegramMessage {
     message_id: i64,
     chat: TelegramChat,
     #[serde(default)]
+    from: Option<TelegramUser>,
+    #[serde(default)]
     text: Option<String>,
     #[serde(default)]
+    caption: Option<String>,
+    #[serde(default)]
+    photo: Vec<TelegramPhotoSize>,
+    #[serde(default)]
+    document: Option<TelegramDocument>,
+    #[serde(default)]
+    voice: Option<TelegramVoice>,
+    #[serde(default)]
     message_thread_id: Option<i64>,
     #[serde(default)]
     is_topic_message: Option<bool>,
@@ -148,15 +286,76 @@ struct TelegramMessage {
     reply_to_message: Option<Box<TelegramMessage>>,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+struct TelegramPhotoSize {
+    file_id: String,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    #[serde(default)]
+    file_size: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct TelegramDocument {
+    file_id: String,
+    #[serde(default)]
+    file_name: Option<String>,
+    #[serde(default)]
+    mime_type: Option<String>,
+    #[serde(default)]
+    file_size: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct TelegramVoice {
+    file_id: String,
+    #[serde(default)]
+    file_size: Option<u64>,
+}
+
+/// A photo, document or voice note to download and attach to the agent message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct InboundFile {
+    file_id: String,
+    name: String,
+    is_image: bool,
+    file_size: Option<u64>,
+}
+
+/// Files the agent produced during a completed turn, to be sent back as documents.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct TurnOutputs {
+    image_paths: Vec<String>,
+    has_file_changes: bool,
+    /// Checkpoint taken before the turn, so the diff sent back covers only this turn.
+    checkpoint_id: Option<String>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 struct TelegramChat {
     id: i64,
     #[serde(default, rename = "type")]
     kind: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct TelegramUser {
+    id: i64,
+    #[serde(default)]
+    first_name: String,
+    #[serde(default)]
+    last_name: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
 }
 
 struct TelegramGateway {
     engine: EngineHandle,
+    services: Arc<dyn ProjectWorkspaceService>,
     events: broadcast::Receiver<WsServerMessage>,
     http: reqwest::Client,
     api_base: String,
@@ -168,8 +367,13 @@ struct TelegramGateway {
     relay_messages: HashMap<(i64, Option<i64>, u64, u64), RelayMessageState>,
     reply_routes: HashMap<i64, ReplyRoute>,
     topic_bindings: HashMap<i64, TopicBinding>,
+    group_topic_bindings: HashMap<(i64, i64), TopicBinding>,
     inbox_initialized_workspaces: HashSet<u64>,
     inbox_task_status: HashMap<(u64, u64), TaskStatus>,
+    webhook: Option<TelegramWebhookConfig>,
+    webhook_updates: Option<mpsc::Receiver<TelegramUpdate>>,
+    /// Bot token the webhook is currently registered for; `None` means updates are polled.
+    webhook_token: Option<String>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -232,13 +436,18 @@ struct TopicBinding {
 }
 
 impl TelegramGateway {
-    fn new(engine: EngineHandle, events: broadcast::Receiver<WsServerMessage>) -> Self {
+    fn new(
+        engine: EngineHandle,
+        events: broadcast::Receiver<WsServerMessage>,
+        services: Arc<dyn ProjectWorkspaceService>,
+    ) -> Self {
         let http = reqwest::Client::builder()
             .timeout(Duration::from_secs(TELEGRAM_REQUEST_TIMEOUT_SECS))
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
         Self {
             engine,
+            services,
             events,
             http,
             api_base: telegram_api_base_url(),
@@ -248,6 +457,7 @@ impl TelegramGateway {
                 bot_token: None,
                 paired_chat_id: None,
                 topic_bindings: Vec::new(),
+                group_chats: Vec::new(),
             },
             session: TelegramSession::default(),
             last_seen_entry_index: HashMap::new(),
@@ -255,8 +465,12 @@ impl TelegramGateway {
             relay_messages: HashMap::new(),
             reply_routes: HashMap::new(),
             topic_bindings: HashMap::new(),
+            group_topic_bindings: HashMap::new(),
             inbox_initialized_workspaces: HashSet::new(),
             inbox_task_status: HashMap::new(),
+            webhook: None,
+            webhook_updates: None,
+            webhook_token: None,
         }
     }
 
@@ -289,80 +503,13 @@ impl TelegramGateway {
         Ok(out)
     }
 
-    async fn create_task_in_new_worktree(
-        &mut self,
-        project_slug: &str,
-    ) -> anyhow::Result<(u64, u64)> {
-        let workspace_id = self.create_new_worktree(project_slug).await?;
-        let thread_id = self.create_task(workspace_id).await?;
-        Ok((workspace_id, thread_id))
-    }
-
-    async fn create_new_worktree(&mut self, project_slug: &str) -> anyhow::Result<u64> {
-        let before = self.engine.app_snapshot().await.context("app snapshot")?;
-        let Some(project) = before.projects.iter().find(|p| p.slug == project_slug) else {
-            anyhow::bail!("project not found");
-        };
-
-        let project_id = project.id.clone();
-        let existing_ids: HashSet<u64> = project.workspaces.iter().map(|w| w.id.0).collect();
-
-        let action = luban_api::ClientAction::CreateWorkspace { project_id };
-        let _ = self
-            .engine
-            .apply_client_action("telegram_create_worktree".to_owned(), action)
-            .await;
-
-        self.wait_for_new_worktree_id(project_slug, &existing_ids)
-            .await
-    }
-
-    async fn wait_for_new_worktree_id(
-        &mut self,
-        project_slug: &str,
-        existing_ids: &HashSet<u64>,
-    ) -> anyhow::Result<u64> {
-        let deadline = Instant::now() + Duration::from_secs(10);
-        let mut last_seen_count = existing_ids.len();
-
-        loop {
-            let app = self.engine.app_snapshot().await.context("app snapshot")?;
-            let Some(project) = app.projects.iter().find(|p| p.slug == project_slug) else {
-                anyhow::bail!("project not found");
-            };
-
-            let mut candidates = project
-                .workspaces
-                .iter()
-                .filter(|w| !existing_ids.contains(&w.id.0))
-                .filter(|w| w.status == luban_api::WorkspaceStatus::Active)
-                .map(|w| w.id.0)
-                .collect::<Vec<_>>();
-            candidates.sort_unstable();
-            if let Some(id) = candidates.last().copied() {
-                return Ok(id);
-            }
-
-            last_seen_count = last_seen_count.max(project.workspaces.len());
-            if Instant::now() >= deadline {
-                anyhow::bail!(
-                    "timed out waiting for new worktree (project_slug={}, existing={}, latest_seen={})",
-                    project_slug,
-                    existing_ids.len(),
-                    last_seen_count
-                );
-            }
-
-            tokio::time::sleep(Duration::from_millis(200)).await;
-        }
-    }
-
     async fn run(&mut self) -> anyhow::Result<()> {
         self.refresh_runtime_config().await;
 
         let mut update_offset = 0i64;
 
         loop {
+            let webhook_mode = self.webhook_token.is_some();
             let token = self.runtime.bot_token.clone();
             let enabled = self.runtime.enabled;
             let api_base = self.api_base.clone();
@@ -389,7 +536,12 @@ impl TelegramGateway {
                         Err(broadcast::error::RecvError::Closed) => break,
                     }
                 }
-                polled = poll_fut => {
+                update = recv_webhook_update(self.webhook_updates.as_mut()), if webhook_mode => {
+                    if let Some(update) = update {
+                        self.handle_updates(vec![update]).await;
+                    }
+                }
+                polled = poll_fut, if !webhook_mode => {
                     match polled {
                         Ok(Some((updates, next_offset))) => {
                             update_offset = next_offset;
@@ -397,6 +549,10 @@ impl TelegramGateway {
                         }
                         Ok(None) => {}
                         Err(err) => {
+                            if telegram_webhook_conflict(&err) {
+                                // A webhook left behind by an earlier run blocks getUpdates.
+                                self.delete_stale_webhook().await;
+                            }
                             self.set_last_error(err).await;
                             tokio::time::sleep(Duration::from_secs(1)).await;
                         }
@@ -408,6 +564,103 @@ impl TelegramGateway {
         Ok(())
     }
 
+    /// Registers or removes the webhook so it follows the configured bot token. Falls back to
+    /// polling when registration fails.
+    async fn sync_webhook(&mut self) {
+        let desired = match (&self.webhook, self.runtime.enabled) {
+            (Some(config), true) => self
+                .runtime
+                .bot_token
+                .clone()
+                .map(|token| (token, config.clone())),
+            _ => None,
+        };
+
+        if let Some(registered) = self.webhook_token.clone()
+            && desired
+                .as_ref()
+                .is_none_or(|(token, _)| *token != registered)
+        {
+            if let Err(err) = self
+                .call_webhook_method(&registered, "deleteWebhook", serde_json::json!({}))
+                .await
+            {
+                self.set_last_error(format!("telegram deleteWebhook failed: {err}"))
+                    .await;
+            }
+            self.webhook_token = None;
+        }
+
+        let Some((token, config)) = desired else {
+            return;
+        };
+        if self.webhook_token.as_deref() == Some(token.as_str()) {
+            return;
+        }
+
+        let body = serde_json::json!({
+            "url": config.url,
+            "secret_token": config.secret,
+            "allowed_updates": ["message", "callback_query"],
+        });
+        match self.call_webhook_method(&token, "setWebhook", body).await {
+            Ok(()) => {
+                tracing::info!("telegram webhook registered");
+                self.webhook_token = Some(token);
+            }
+            Err(err) => {
+                self.set_last_error(format!(
+                    "telegram setWebhook failed, falling back to polling: {err}"
+                ))
+                .await;
+            }
+        }
+    }
+
+    async fn delete_stale_webhook(&mut self) {
+        let Some(token) = self.runtime.bot_token.clone() else {
+            return;
+        };
+        if let Err(err) = self
+            .call_webhook_method(&token, "deleteWebhook", serde_json::json!({}))
+            .await
+        {
+            tracing::warn!(error = %err, "telegram deleteWebhook failed");
+        }
+    }
+
+    async fn call_webhook_method(
+        &self,
+        token: &str,
+        method: &str,
+        body: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let url = format!(
+            "{}/bot{}/{}",
+            self.api_base.trim_end_matches('/'),
+            token,
+            method
+        );
+        let parsed = self
+            .http
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("telegram {method} request failed"))?
+            .json::<TelegramApiResponse<serde_json::Value>>()
+            .await
+            .with_context(|| format!("telegram {method} parse failed"))?;
+        if !parsed.ok {
+            return Err(anyhow!(
+                parsed
+                    .description
+                    .unwrap_or_else(|| format!("telegram {method} failed"))
+            ));
+        }
+        Ok(())
+    }
+
     async fn handle_server_message(&mut self, msg: WsServerMessage) {
         let WsServerMessage::Event { event, .. } = msg else {
             return;
@@ -425,6 +678,18 @@ impl TelegramGateway {
                 self.handle_task_summaries_changed(tasks).await;
             }
             ServerEvent::ConversationChanged { snapshot } => {
+                let task = (snapshot.workspace_id.0, snapshot.thread_id.0);
+                let group_topics = self
+                    .group_topic_bindings
+                    .iter()
+                    .filter(|(_, b)| (b.workspace_id, b.thread_id) == task)
+                    .map(|(key, _)| *key)
+                    .collect::<Vec<_>>();
+                for (chat_id, topic_id) in group_topics {
+                    self.forward_conversation_updates(chat_id, Some(topic_id), &snapshot)
+                        .await;
+                }
+
                 let Some(chat_id) = self.runtime.paired_chat_id else {
                     return;
                 };
@@ -517,22 +782,49 @@ impl TelegramGateway {
                     })
                     .collect();
 
-                if let Some(chat_id) = self.runtime.paired_chat_id {
-                    for (topic_id, binding) in &self.topic_bindings {
-                        let Some(replayed_up_to) = binding.replayed_up_to else {
-                            continue;
-                        };
-                        let key = (
-                            chat_id,
-                            Some(*topic_id),
-                            binding.workspace_id,
-                            binding.thread_id,
-                        );
-                        self.last_seen_entry_index
-                            .entry(key)
-                            .or_insert(replayed_up_to);
-                    }
+                self.group_topic_bindings = self
+                    .runtime
+                    .group_chats
+                    .iter()
+                    .flat_map(|g| {
+                        g.topic_bindings.iter().map(|b| {
+                            (
+                                (g.chat_id, b.message_thread_id),
+                                TopicBinding {
+                                    workspace_id: b.workspace_id,
+                                    thread_id: b.thread_id,
+                                    replayed_up_to: b.replayed_up_to,
+                                },
+                            )
+                        })
+                    })
+                    .collect();
+
+                let paired_topics = self.runtime.paired_chat_id.into_iter().flat_map(|chat_id| {
+                    self.topic_bindings
+                        .iter()
+                        .map(move |(topic_id, binding)| (chat_id, *topic_id, binding))
+                });
+                let group_topics = self
+                    .group_topic_bindings
+                    .iter()
+                    .map(|((chat_id, topic_id), binding)| (*chat_id, *topic_id, binding));
+                for (chat_id, topic_id, binding) in paired_topics.chain(group_topics) {
+                    let Some(replayed_up_to) = binding.replayed_up_to else {
+                        continue;
+                    };
+                    let key = (
+                        chat_id,
+                        Some(topic_id),
+                        binding.workspace_id,
+                        binding.thread_id,
+                    );
+                    self.last_seen_entry_index
+                        .entry(key)
+                        .or_insert(replayed_up_to);
                 }
+
+                self.sync_webhook().await;
             }
             Err(err) => {
                 tracing::warn!(error = %err, "failed to refresh telegram runtime config");
@@ -608,10 +900,7 @@ impl TelegramGateway {
             return Ok(());
         };
 
-        let kb = inline_keyboard(vec![vec![InlineButton::new(
-            "Comment",
-            &format!("comment:{workspace_id}:{thread_id}"),
-        )]]);
+        let kb = comment_keyboard(key.1, workspace_id, thread_id);
         let text = render_progress_message(&task_title, &final_text, &recent_events);
 
         match self
@@ -619,7 +908,7 @@ impl TelegramGateway {
                 chat_id,
                 existing_message_id,
                 &text,
-                Some(kb.clone()),
+                kb.clone(),
                 Some(TELEGRAM_PARSE_MODE_MARKDOWN_V2),
             )
             .await
@@ -634,7 +923,7 @@ impl TelegramGateway {
                         chat_id,
                         key.1,
                         &text,
-                        Some(kb),
+                        kb,
                         Some(TELEGRAM_PARSE_MODE_MARKDOWN_V2),
                     )
                     .await?
@@ -694,10 +983,7 @@ impl TelegramGateway {
             .task_title_and_last_entry_index(workspace_id, thread_id)
             .await;
         let formatted = render_progress_message(&title, &None, &[]);
-        let kb = inline_keyboard(vec![vec![InlineButton::new(
-            "Comment",
-            &format!("comment:{workspace_id}:{thread_id}"),
-        )]]);
+        let kb = comment_keyboard(message_thread_id, workspace_id, thread_id);
         let key = (chat_id, message_thread_id, workspace_id, thread_id);
 
         // Each user turn should start from a fresh Telegram message so we don't
@@ -710,7 +996,7 @@ impl TelegramGateway {
                 chat_id,
                 message_thread_id,
                 &formatted,
-                Some(kb),
+                kb,
                 Some(TELEGRAM_PARSE_MODE_MARKDOWN_V2),
             )
             .await;
@@ -754,12 +1040,21 @@ impl TelegramGateway {
 
     async fn handle_message(&mut self, msg: TelegramMessage) -> anyhow::Result<()> {
         let chat_id = msg.chat.id;
+        if matches!(msg.chat.kind.as_deref(), Some("group" | "supergroup")) {
+            return self.handle_group_message(msg).await;
+        }
         if msg.chat.kind.as_deref() != Some("private") {
             return Ok(());
         }
 
-        let text = msg.text.as_deref().unwrap_or_default().trim();
-        if text.is_empty() {
+        let files = inbound_files(&msg);
+        let text = msg
+            .text
+            .as_deref()
+            .or(msg.caption.as_deref())
+            .unwrap_or_default()
+            .trim();
+        if text.is_empty() && files.is_empty() {
             return Ok(());
         }
 
@@ -779,7 +1074,7 @@ impl TelegramGateway {
 
             match self
                 .engine
-                .consume_telegram_pairing_code(code, chat_id)
+                .consume_telegram_pairing_code(code, chat_id, None)
                 .await
             {
                 Ok(()) => {
@@ -818,7 +1113,7 @@ impl TelegramGateway {
             return Ok(());
         }
 
-        if self.handle_keyboard_input(chat_id, text).await? {
+        if files.is_empty() && self.handle_keyboard_input(chat_id, text).await? {
             return Ok(());
         }
 
@@ -827,18 +1122,20 @@ impl TelegramGateway {
             self.session.active_thread_id = Some(tid);
             self.begin_turn_progress_message(chat_id, None, wid, tid)
                 .await;
-            self.send_agent_message(wid, tid, text).await;
+            self.send_agent_message(chat_id, None, wid, tid, text, &files, None)
+                .await;
             return Ok(());
         }
 
         if let Some(project_slug) = self.session.pending_new_task_project_slug.take() {
-            match self.create_task_in_new_worktree(&project_slug).await {
+            match create_task_in_new_worktree(&self.engine, "telegram", &project_slug).await {
                 Ok((wid, tid)) => {
                     self.session.active_workspace_id = Some(wid);
                     self.session.active_thread_id = Some(tid);
                     self.begin_turn_progress_message(chat_id, None, wid, tid)
                         .await;
-                    self.send_agent_message(wid, tid, text).await;
+                    self.send_agent_message(chat_id, None, wid, tid, text, &files, None)
+                        .await;
                     return Ok(());
                 }
                 Err(err) => {
@@ -872,23 +1169,418 @@ impl TelegramGateway {
 
         self.begin_turn_progress_message(chat_id, None, wid, tid)
             .await;
-        self.send_agent_message(wid, tid, text).await;
+        self.send_agent_message(chat_id, None, wid, tid, text, &files, None)
+            .await;
         Ok(())
     }
 
-    async fn send_agent_message(&self, wid: u64, tid: u64, text: &str) {
+    async fn handle_group_message(&mut self, msg: TelegramMessage) -> anyhow::Result<()> {
+        let chat_id = msg.chat.id;
+        let Some(from) = msg.from.as_ref() else {
+            return Ok(());
+        };
+        let topic_id = msg
+            .message_thread_id
+            .filter(|_| msg.is_topic_message.unwrap_or(false));
+
+        let files = inbound_files(&msg);
+        let text = msg
+            .text
+            .as_deref()
+            .or(msg.caption.as_deref())
+            .unwrap_or_default()
+            .trim();
+        if text.is_empty() && files.is_empty() {
+            return Ok(());
+        }
+
+        let display_name = telegram_user_display_name(from);
+        if let Some(code) = group_pairing_code(text) {
+            let pairing = TelegramGroupPairing {
+                title: msg.chat.title.clone().unwrap_or_default(),
+                user_id: from.id,
+                display_name: display_name.clone(),
+            };
+            match self
+                .engine
+                .consume_telegram_pairing_code(code.to_owned(), chat_id, Some(pairing))
+                .await
+            {
+                Ok(()) => {
+                    self.set_last_error("".to_owned()).await;
+                    let text = format!(
+                        "{display_name} is paired. Post in General to start a task; each task gets its own topic."
+                    );
+                    self.send_message(chat_id, topic_id, &text, None).await?;
+                }
+                Err(message) => {
+                    self.send_message(chat_id, topic_id, &message, None).await?;
+                }
+            }
+            return Ok(());
+        }
+
+        let Some(group) = self
+            .runtime
+            .group_chats
+            .iter()
+            .find(|g| g.chat_id == chat_id)
+        else {
+            return Ok(());
+        };
+        if !group.member_user_ids.contains(&from.id) {
+            return Ok(());
+        }
+        let project_slug = group.project_slug.clone();
+
+        let (topic_id, wid, tid) = match topic_id {
+            Some(topic_id) => {
+                let Some(binding) = self.group_topic_bindings.get(&(chat_id, topic_id)) else {
+                    self.send_message(
+                        chat_id,
+                        Some(topic_id),
+                        "This topic is not linked to a task. Post in General to start a new one.",
+                        None,
+                    )
+                    .await?;
+                    return Ok(());
+                };
+                (topic_id, binding.workspace_id, binding.thread_id)
+            }
+            None => match self.start_group_task(chat_id, &project_slug, text).await {
+                Ok(target) => target,
+                Err(err) => {
+                    self.send_message(
+                        chat_id,
+                        None,
+                        &format!("Failed to start task: {err:#}"),
+                        None,
+                    )
+                    .await?;
+                    return Ok(());
+                }
+            },
+        };
+
+        self.begin_turn_progress_message(chat_id, Some(topic_id), wid, tid)
+            .await;
+        self.send_agent_message(
+            chat_id,
+            Some(topic_id),
+            wid,
+            tid,
+            text,
+            &files,
+            Some(display_name),
+        )
+        .await;
+        Ok(())
+    }
+
+    /// Creates a task in a new worktree of the group's project and opens a
+    /// forum topic for it, so follow-ups in that topic reach the same task.
+    async fn start_group_task(
+        &mut self,
+        chat_id: i64,
+        project_slug: &str,
+        text: &str,
+    ) -> anyhow::Result<(i64, u64, u64)> {
+        let topic_name = sanitize_telegram_topic_name(&luban_domain::derive_thread_title(text));
+        let topic_id = self
+            .create_forum_topic(chat_id, &topic_name)
+            .await
+            .context("enable topics for this group and make the bot an admin")?;
+        let (workspace_id, thread_id) =
+            create_task_in_new_worktree(&self.engine, "telegram", project_slug).await?;
+
+        self.group_topic_bindings.insert(
+            (chat_id, topic_id),
+            TopicBinding {
+                workspace_id,
+                thread_id,
+                replayed_up_to: None,
+            },
+        );
+        let _ = self
+            .engine
+            .dispatch_domain_action(Action::TelegramGroupTopicBound {
+                chat_id,
+                binding: luban_domain::TelegramTopicBinding {
+                    message_thread_id: topic_id,
+                    workspace_id,
+                    thread_id,
+                    replayed_up_to: None,
+                },
+            })
+            .await;
+        Ok((topic_id, workspace_id, thread_id))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn send_agent_message(
+        &self,
+        chat_id: i64,
+        message_thread_id: Option<i64>,
+        wid: u64,
+        tid: u64,
+        text: &str,
+        files: &[InboundFile],
+        author: Option<String>,
+    ) {
+        let attachments = match self.store_inbound_files(wid, files).await {
+            Ok(attachments) => attachments,
+            Err(err) => {
+                let _ = self
+                    .send_message(
+                        chat_id,
+                        message_thread_id,
+                        &format!("Failed to attach file: {err:#}"),
+                        None,
+                    )
+                    .await;
+                return;
+            }
+        };
+        let text = if text.is_empty() {
+            TELEGRAM_ATTACHMENT_ONLY_PROMPT
+        } else {
+            text
+        };
         let action = luban_api::ClientAction::SendAgentMessage {
             workspace_id: luban_api::WorkspaceId(wid),
             thread_id: luban_api::WorkspaceThreadId(tid),
             text: text.to_owned(),
-            attachments: Vec::new(),
+            attachments,
             runner: None,
             amp_mode: None,
+            author,
+        };
+        let _ = self
+            .engine
+            .apply_client_action_from(AuditOrigin::Telegram, "telegram_send".to_owned(), action)
+            .await;
+    }
+
+    async fn store_inbound_files(
+        &self,
+        workspace_id: u64,
+        files: &[InboundFile],
+    ) -> anyhow::Result<Vec<luban_api::AttachmentRef>> {
+        if files.is_empty() {
+            return Ok(Vec::new());
+        }
+        let snapshot = self.engine.app_snapshot().await.ok();
+        let (project_slug, workspace_name) =
+            crate::server::workspace_scope_from_snapshot(&snapshot, workspace_id)
+                .context("workspace not found")?;
+
+        let mut out = Vec::with_capacity(files.len());
+        for file in files {
+            if file
+                .file_size
+                .is_some_and(|size| size > TELEGRAM_MAX_DOWNLOAD_BYTES)
+            {
+                return Err(anyhow!("{} is larger than 20 MB", file.name));
+            }
+            let bytes = self.download_file(&file.file_id).await?;
+            let services = self.services.clone();
+            let project_slug = project_slug.clone();
+            let workspace_name = workspace_name.clone();
+            let file = file.clone();
+            let stored = tokio::task::spawn_blocking(move || {
+                store_inbound_file(
+                    services.as_ref(),
+                    project_slug,
+                    workspace_name,
+                    &file,
+                    bytes,
+                )
+            })
+            .await
+            .context("failed to join attachment store task")?
+            .map_err(|message| anyhow!(message))?;
+            out.push(stored);
+        }
+        Ok(out)
+    }
+
+    async fn download_file(&self, file_id: &str) -> anyhow::Result<Vec<u8>> {
+        let token = self
+            .runtime
+            .bot_token
+            .as_deref()
+            .context("telegram bot token is missing")?;
+        let base = self.api_base.trim_end_matches('/');
+
+        let parsed = self
+            .http
+            .post(format!("{base}/bot{token}/getFile"))
+            .json(&serde_json::json!({ "file_id": file_id }))
+            .send()
+            .await
+            .context("telegram getFile request failed")?
+            .json::<TelegramApiResponse<TelegramFile>>()
+            .await
+            .context("telegram getFile response parse failed")?;
+        if !parsed.ok {
+            return Err(anyhow!(
+                parsed
+                    .description
+                    .unwrap_or_else(|| "telegram getFile failed".to_owned())
+            ));
+        }
+        let file_path = parsed
+            .result
+            .file_path
+            .filter(|v| !v.trim().is_empty())
+            .context("telegram file path is missing")?;
+
+        let mut res = self
+            .http
+            .get(format!("{base}/file/bot{token}/{file_path}"))
+            .send()
+            .await
+            .context("telegram file download failed")?
+            .error_for_status()
+            .context("telegram file download failed")?;
+        // Reason: the advertised size can be missing or wrong, so the cap is enforced on the
+        // bytes actually received.
+        if res
+            .content_length()
+            .is_some_and(|len| len > TELEGRAM_MAX_DOWNLOAD_BYTES)
+        {
+            return Err(anyhow!("file is larger than 20 MB"));
+        }
+        let mut bytes = Vec::new();
+        while let Some(chunk) = res.chunk().await.context("telegram file download failed")? {
+            if (bytes.len() + chunk.len()) as u64 > TELEGRAM_MAX_DOWNLOAD_BYTES {
+                return Err(anyhow!("file is larger than 20 MB"));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    async fn send_turn_outputs(
+        &self,
+        chat_id: i64,
+        message_thread_id: Option<i64>,
+        workspace_id: u64,
+        thread_id: u64,
+        outputs: TurnOutputs,
+    ) {
+        let Ok(Some(worktree_path)) = self
+            .engine
+            .workspace_worktree_path(luban_api::WorkspaceId(workspace_id))
+            .await
+        else {
+            return;
+        };
+
+        for path in &outputs.image_paths {
+            let Some(full_path) = resolve_output_path(&worktree_path, path) else {
+                continue;
+            };
+            let Ok(bytes) = tokio::fs::read(&full_path).await else {
+                continue;
+            };
+            if bytes.len() as u64 > TELEGRAM_MAX_UPLOAD_BYTES {
+                continue;
+            }
+            let name = full_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("image")
+                .to_owned();
+            self.send_document(chat_id, message_thread_id, &name, bytes, None)
+                .await;
+        }
+
+        if outputs.has_file_changes {
+            let services = self.services.clone();
+            let path = worktree_path.clone();
+            let (patch, file_name, caption) = tokio::task::spawn_blocking(move || {
+                turn_patch(
+                    services.as_ref(),
+                    &path,
+                    workspace_id,
+                    thread_id,
+                    outputs.checkpoint_id.as_deref(),
+                )
+            })
+            .await
+            .unwrap_or((String::new(), TELEGRAM_TURN_DIFF_FILE_NAME, None));
+            if !patch.trim().is_empty() && patch.len() as u64 <= TELEGRAM_MAX_UPLOAD_BYTES {
+                self.send_document(
+                    chat_id,
+                    message_thread_id,
+                    file_name,
+                    patch.into_bytes(),
+                    caption.as_deref(),
+                )
+                .await;
+            }
+        }
+    }
+
+    async fn send_document(
+        &self,
+        chat_id: i64,
+        message_thread_id: Option<i64>,
+        file_name: &str,
+        bytes: Vec<u8>,
+        caption: Option<&str>,
+    ) {
+        let Some(token) = self.runtime.bot_token.as_deref() else {
+            return;
+        };
+        let url = format!(
+            "{}/bot{}/sendDocument",
+            self.api_base.trim_end_matches('/'),
+            token
+        );
+
+        let mut form = reqwest::multipart::Form::new()
+            .text("chat_id", chat_id.to_string())
+            .part(
+                "document",
+                reqwest::multipart::Part::bytes(bytes).file_name(file_name.to_owned()),
+            );
+        if let Some(topic_id) = message_thread_id {
+            form = form.text("message_thread_id", topic_id.to_string());
+        }
+        if let Some(caption) = caption {
+            form = form.text("caption", caption.to_owned());
+        }
+
+        let res = match self.http.post(url).multipart(form).send().await {
+            Ok(res) => res,
+            Err(err) => {
+                self.set_last_error(format!("telegram sendDocument failed: {err}"))
+                    .await;
+                return;
+            }
         };
-        let _ = self
-            .engine
-            .apply_client_action("telegram_send".to_owned(), action)
-            .await;
+        match res
+            .json::<TelegramApiResponse<TelegramSendMessageResult>>()
+            .await
+        {
+            Ok(parsed) if parsed.ok => {}
+            Ok(parsed) => {
+                self.set_last_error(
+                    parsed
+                        .description
+                        .unwrap_or_else(|| "telegram sendDocument failed".to_owned()),
+                )
+                .await;
+            }
+            Err(err) => {
+                self.set_last_error(format!(
+                    "telegram sendDocument response parse failed: {err}"
+                ))
+                .await;
+            }
+        }
     }
 
     async fn handle_callback_query(&mut self, cb: TelegramCallbackQuery) -> anyhow::Result<()> {
@@ -971,7 +1663,11 @@ impl TelegramGateway {
         };
         let _ = self
             .engine
-            .apply_client_action("telegram_new_task".to_owned(), action)
+            .apply_client_action_from(
+                AuditOrigin::Telegram,
+                "telegram_new_task".to_owned(),
+                action,
+            )
             .await;
 
         let snapshot = self
@@ -1028,7 +1724,7 @@ impl TelegramGateway {
                 topic_id,
                 workspace_id,
             } => {
-                let thread_id = self.create_task(workspace_id).await?;
+                let thread_id = create_task(&self.engine, "telegram", workspace_id).await?;
                 self.bind_topic_to_task(chat_id, topic_id, workspace_id, thread_id, false)
                     .await?;
             }
@@ -1042,7 +1738,7 @@ impl TelegramGateway {
                     return Ok(());
                 };
                 let (workspace_id, thread_id) =
-                    self.create_task_in_new_worktree(&project.slug).await?;
+                    create_task_in_new_worktree(&self.engine, "telegram", &project.slug).await?;
                 self.bind_topic_to_task(chat_id, topic_id, workspace_id, thread_id, false)
                     .await?;
             }
@@ -1137,31 +1833,6 @@ impl TelegramGateway {
         Ok(())
     }
 
-    async fn create_task(&mut self, workspace_id: u64) -> anyhow::Result<u64> {
-        let action = luban_api::ClientAction::CreateWorkspaceThread {
-            workspace_id: luban_api::WorkspaceId(workspace_id),
-            model_id: None,
-            thinking_effort: None,
-        };
-        let _ = self
-            .engine
-            .apply_client_action("telegram_topic_new_task".to_owned(), action)
-            .await;
-
-        let snapshot = self
-            .engine
-            .threads_snapshot(luban_api::WorkspaceId(workspace_id))
-            .await
-            .context("threads snapshot")?;
-
-        Ok(snapshot
-            .threads
-            .iter()
-            .map(|t| t.thread_id.0)
-            .max()
-            .unwrap_or(1))
-    }
-
     async fn bind_topic_to_task(
         &mut self,
         chat_id: i64,
@@ -1522,7 +2193,7 @@ impl TelegramGateway {
                 .await?;
             }
             KeyboardRoute::SelectWorktree { workspace_id } => {
-                let thread_id = self.create_task(workspace_id).await?;
+                let thread_id = create_task(&self.engine, "telegram", workspace_id).await?;
                 self.session.active_workspace_id = Some(workspace_id);
                 self.session.active_thread_id = Some(thread_id);
                 self.session.ui_state = TelegramUiState::Home;
@@ -1814,6 +2485,39 @@ impl TelegramGateway {
         chat_id: i64,
         message_thread_id: Option<i64>,
         snapshot: &luban_api::ConversationSnapshot,
+    ) {
+        let key = (
+            chat_id,
+            message_thread_id,
+            snapshot.workspace_id.0,
+            snapshot.thread_id.0,
+        );
+        let outputs = completed_turn_outputs(
+            &snapshot.entries,
+            snapshot.entries_start,
+            self.last_seen_entry_index.get(&key).copied(),
+        );
+
+        self.forward_conversation_messages(chat_id, message_thread_id, snapshot)
+            .await;
+
+        if let Some(outputs) = outputs {
+            self.send_turn_outputs(
+                chat_id,
+                message_thread_id,
+                snapshot.workspace_id.0,
+                snapshot.thread_id.0,
+                outputs,
+            )
+            .await;
+        }
+    }
+
+    async fn forward_conversation_messages(
+        &mut self,
+        chat_id: i64,
+        message_thread_id: Option<i64>,
+        snapshot: &luban_api::ConversationSnapshot,
     ) {
         self.prune_progress_messages();
         self.prune_relay_messages();
@@ -1875,13 +2579,11 @@ impl TelegramGateway {
         let title = if title.is_empty() { "Task" } else { title };
 
         let formatted = format_task_push_markdown(title, &text);
-        let kb = inline_keyboard(vec![vec![InlineButton::new(
-            "Comment",
-            &format!(
-                "comment:{}:{}",
-                snapshot.workspace_id.0, snapshot.thread_id.0
-            ),
-        )]]);
+        let kb = comment_keyboard(
+            message_thread_id,
+            snapshot.workspace_id.0,
+            snapshot.thread_id.0,
+        );
         let existing_message_id = self.relay_messages.get(&key).map(|state| state.message_id);
         let message_id = if let Some(message_id) = existing_message_id {
             match self
@@ -1889,7 +2591,7 @@ impl TelegramGateway {
                     chat_id,
                     message_id,
                     &formatted,
-                    Some(kb.clone()),
+                    kb.clone(),
                     Some(TELEGRAM_PARSE_MODE_MARKDOWN_V2),
                 )
                 .await
@@ -1904,7 +2606,7 @@ impl TelegramGateway {
                             chat_id,
                             message_thread_id,
                             &formatted,
-                            Some(kb),
+                            kb,
                             Some(TELEGRAM_PARSE_MODE_MARKDOWN_V2),
                         )
                         .await;
@@ -1920,7 +2622,7 @@ impl TelegramGateway {
                     chat_id,
                     message_thread_id,
                     &formatted,
-                    Some(kb),
+                    kb,
                     Some(TELEGRAM_PARSE_MODE_MARKDOWN_V2),
                 )
                 .await;
@@ -2131,6 +2833,36 @@ impl TelegramGateway {
         Ok(())
     }
 
+    async fn create_forum_topic(&self, chat_id: i64, name: &str) -> anyhow::Result<i64> {
+        let Some(token) = self.runtime.bot_token.as_deref() else {
+            return Err(anyhow!("telegram bot token is not configured"));
+        };
+        let url = format!(
+            "{}/bot{}/createForumTopic",
+            self.api_base.trim_end_matches('/'),
+            token
+        );
+        let body = serde_json::json!({ "chat_id": chat_id, "name": name });
+        let parsed = self
+            .http
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .context("telegram createForumTopic failed")?
+            .json::<TelegramApiResponse<TelegramForumTopic>>()
+            .await
+            .context("telegram createForumTopic response parse failed")?;
+        if !parsed.ok || parsed.result.message_thread_id == 0 {
+            return Err(anyhow!(
+                parsed
+                    .description
+                    .unwrap_or_else(|| "telegram createForumTopic failed".to_owned())
+            ));
+        }
+        Ok(parsed.result.message_thread_id)
+    }
+
     async fn answer_callback_query(&self, id: &str) -> anyhow::Result<()> {
         let Some(token) = self.runtime.bot_token.as_deref() else {
             return Ok(());
@@ -2162,6 +2894,20 @@ impl TelegramGateway {
     }
 }
 
+async fn recv_webhook_update(
+    updates: Option<&mut mpsc::Receiver<TelegramUpdate>>,
+) -> Option<TelegramUpdate> {
+    match updates {
+        Some(updates) => updates.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn telegram_webhook_conflict(error: &str) -> bool {
+    let error = error.to_ascii_lowercase();
+    error.contains("conflict") && error.contains("webhook")
+}
+
 async fn poll_updates(
     http: reqwest::Client,
     api_base: String,
@@ -2383,6 +3129,22 @@ impl InlineButton {
     }
 }
 
+/// The "Comment" shortcut only makes sense in the private chat; inside a forum
+/// topic every message already goes to the bound task.
+fn comment_keyboard(
+    message_thread_id: Option<i64>,
+    workspace_id: u64,
+    thread_id: u64,
+) -> Option<serde_json::Value> {
+    if message_thread_id.is_some() {
+        return None;
+    }
+    Some(inline_keyboard(vec![vec![InlineButton::new(
+        "Comment",
+        &format!("comment:{workspace_id}:{thread_id}"),
+    )]]))
+}
+
 fn inline_keyboard(rows: Vec<Vec<InlineButton>>) -> serde_json::Value {
     let inline_keyboard = rows
         .into_iter()
@@ -2489,19 +3251,6 @@ fn is_reserved_keyboard_label(raw: &str) -> bool {
     )
 }
 
-fn truncate_label(raw: &str, max_chars: usize) -> String {
-    let trimmed = raw.trim();
-    if trimmed.chars().count() <= max_chars {
-        return trimmed.to_owned();
-    }
-    let mut out = trimmed
-        .chars()
-        .take(max_chars.saturating_sub(1))
-        .collect::<String>();
-    out.push('…');
-    out
-}
-
 fn truncate_message(raw: &str) -> String {
     if raw.chars().count() <= TELEGRAM_MAX_MESSAGE_CHARS {
         return raw.to_owned();
@@ -2572,6 +3321,32 @@ fn escape_markdown_v2(raw: &str) -> String {
     out
 }
 
+/// Extracts the code from `/start <code>` or `/pair <code>` sent in a group,
+/// where Telegram may append the bot name to the command (`/start@bot`).
+fn group_pairing_code(text: &str) -> Option<&str> {
+    let mut parts = text.split_whitespace();
+    let command = parts.next()?;
+    let command = command.split('@').next().unwrap_or(command);
+    if command != "/start" && command != "/pair" {
+        return None;
+    }
+    parts.next().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn telegram_user_display_name(user: &TelegramUser) -> String {
+    let full_name = match user.last_name.as_deref().map(str::trim) {
+        Some(last) if !last.is_empty() => format!("{} {last}", user.first_name.trim()),
+        _ => user.first_name.trim().to_owned(),
+    };
+    if !full_name.trim().is_empty() {
+        return full_name.trim().to_owned();
+    }
+    match user.username.as_deref().map(str::trim) {
+        Some(username) if !username.is_empty() => format!("@{username}"),
+        _ => format!("Telegram user {}", user.id),
+    }
+}
+
 fn sanitize_telegram_topic_name(raw: &str) -> String {
     const LIMIT: usize = 128;
     let collapsed = raw
@@ -2591,47 +3366,6 @@ fn telegram_edit_message_not_modified(description: &str) -> bool {
         .contains("message is not modified")
 }
 
-#[derive(Clone, Debug)]
-enum ProgressUpdate {
-    Event(String),
-    Final(String),
-}
-
-fn format_conversation_entry_for_progress(entry: &ConversationEntry) -> Option<ProgressUpdate> {
-    match entry {
-        ConversationEntry::AgentEvent(v) => match &v.event {
-            luban_api::AgentEvent::Message(msg) => Some(ProgressUpdate::Final(msg.text.clone())),
-            luban_api::AgentEvent::Item(item) => {
-                format_agent_item_for_progress(item).map(ProgressUpdate::Event)
-            }
-            luban_api::AgentEvent::TurnError { message } => {
-                Some(ProgressUpdate::Final(format!("Turn failed: {message}")))
-            }
-            luban_api::AgentEvent::TurnCanceled => {
-                Some(ProgressUpdate::Final("Turn canceled.".to_owned()))
-            }
-            luban_api::AgentEvent::TurnDuration { .. } => None,
-            luban_api::AgentEvent::TurnUsage { .. } => None,
-        },
-        _ => None,
-    }
-}
-
-fn push_recent_progress_event(out: &mut Vec<String>, line: String, limit: usize) -> bool {
-    let line = line.trim().to_owned();
-    if line.is_empty() {
-        return false;
-    }
-    if out.last().is_some_and(|prev| prev == &line) {
-        return false;
-    }
-    out.push(line);
-    while out.len() > limit {
-        out.remove(0);
-    }
-    true
-}
-
 fn apply_progress_update(state: &mut ProgressMessageState, update: ProgressUpdate) -> bool {
     match update {
         ProgressUpdate::Event(line) => {
@@ -2652,138 +3386,236 @@ fn render_progress_message(
     final_text: &Option<String>,
     recent: &[String],
 ) -> String {
-    let mut lines = Vec::new();
-    if let Some(text) = final_text.as_deref() {
-        lines.push(text.to_owned());
-        return format_task_push_markdown(task_title, &lines.join("\n"));
+    format_task_push_markdown(
+        task_title,
+        &render_progress_body(final_text.as_deref(), recent),
+    )
+}
+
+fn format_conversation_entry_for_telegram(entry: &ConversationEntry) -> Option<String> {
+    match entry {
+        ConversationEntry::AgentEvent(v) => match &v.event {
+            luban_api::AgentEvent::Message(msg) => Some(msg.text.clone()),
+            luban_api::AgentEvent::TurnDuration { .. } => None,
+            luban_api::AgentEvent::TurnError { message } => Some(format!("Turn failed: {message}")),
+            luban_api::AgentEvent::TurnCanceled => Some("Turn canceled.".to_owned()),
+            _ => None,
+        },
+        _ => None,
     }
+}
 
-    lines.push("Working...".to_owned());
-    if !recent.is_empty() {
-        lines.push(String::new());
-        for item in recent {
-            lines.push(format!("• {item}"));
-        }
+fn inbound_files(msg: &TelegramMessage) -> Vec<InboundFile> {
+    let mut out = Vec::new();
+    // Telegram sends every resolution of a photo; keep the largest.
+    if let Some(photo) = msg
+        .photo
+        .iter()
+        .max_by_key(|p| u64::from(p.width) * u64::from(p.height))
+    {
+        out.push(InboundFile {
+            file_id: photo.file_id.clone(),
+            name: "photo.jpg".to_owned(),
+            is_image: true,
+            file_size: photo.file_size,
+        });
+    }
+    if let Some(doc) = &msg.document {
+        let name = doc
+            .file_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or("document")
+            .to_owned();
+        let extension = file_extension(&name);
+        let is_image = doc
+            .mime_type
+            .as_deref()
+            .is_some_and(|mime| mime.starts_with("image/"))
+            && CONTEXT_IMAGE_EXTENSIONS.contains(&extension.as_str());
+        out.push(InboundFile {
+            file_id: doc.file_id.clone(),
+            name,
+            is_image,
+            file_size: doc.file_size,
+        });
+    }
+    if let Some(voice) = &msg.voice {
+        out.push(InboundFile {
+            file_id: voice.file_id.clone(),
+            name: "voice.ogg".to_owned(),
+            is_image: false,
+            file_size: voice.file_size,
+        });
     }
-    format_task_push_markdown(task_title, &lines.join("\n"))
+    out
 }
 
-fn format_agent_item_for_progress(item: &luban_api::AgentItem) -> Option<String> {
-    let payload = &item.payload;
-    let status = payload
-        .get("status")
-        .and_then(|v| v.as_str())
-        .unwrap_or_default();
-    let status = match status {
-        "in_progress" => "running",
-        "completed" => "done",
-        other => other,
-    };
-    let status_suffix = if status.is_empty() {
-        String::new()
+fn file_extension(name: &str) -> String {
+    name.rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_else(|| "bin".to_owned())
+}
+
+fn store_inbound_file(
+    services: &dyn ProjectWorkspaceService,
+    project_slug: String,
+    workspace_name: String,
+    file: &InboundFile,
+    bytes: Vec<u8>,
+) -> Result<luban_api::AttachmentRef, String> {
+    let uploaded_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let display_name = crate::server::append_timestamp_to_basename(&file.name, uploaded_at_ms);
+
+    let mut att = if file.is_image {
+        services.store_context_image(
+            project_slug.clone(),
+            workspace_name.clone(),
+            ContextImage {
+                extension: file_extension(&file.name),
+                bytes,
+            },
+        )?
     } else {
-        format!(" ({status})")
+        crate::server::store_context_file_bytes(
+            services,
+            project_slug.clone(),
+            workspace_name.clone(),
+            &display_name,
+            &bytes,
+        )?
     };
+    att.name = display_name;
+    services.record_context_item(project_slug, workspace_name, att.clone(), uploaded_at_ms)?;
+    Ok(crate::engine::map_attachment_ref(&att))
+}
 
-    let line = match item.kind {
-        luban_api::AgentItemKind::CommandExecution => {
-            let command = payload
-                .get("command")
-                .and_then(|v| v.as_str())
-                .unwrap_or("Command");
-            Some(format!(
-                "Command: {}{status_suffix}",
-                truncate_label(command, 120)
-            ))
-        }
-        luban_api::AgentItemKind::FileChange => {
-            let changes = payload
-                .get("changes")
-                .and_then(|v| v.as_array())
-                .map(Vec::as_slice)
-                .unwrap_or_default();
-            let mut paths = Vec::new();
-            let mut seen = std::collections::HashSet::new();
-            for change in changes {
-                let path = change
-                    .get("path")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or_default()
-                    .trim();
-                if path.is_empty() {
-                    continue;
+/// Returns what the agent produced in the most recent turn that completed after `last_seen`.
+///
+/// Nothing is reported before the chat has started tracking the task, so history is not
+/// re-sent when a conversation is first observed.
+fn completed_turn_outputs(
+    entries: &[ConversationEntry],
+    entries_start: u64,
+    last_seen: Option<u64>,
+) -> Option<TurnOutputs> {
+    let last_seen = last_seen?;
+    let end = entries.iter().enumerate().rposition(|(idx, entry)| {
+        entries_start.saturating_add(idx as u64) > last_seen
+            && matches!(
+                entry,
+                ConversationEntry::AgentEvent(v)
+                    if matches!(v.event, luban_api::AgentEvent::TurnDuration { .. })
+            )
+    })?;
+
+    let mut outputs = TurnOutputs::default();
+    for entry in entries[..end].iter().rev() {
+        let item = match entry {
+            ConversationEntry::UserEvent(_) => break,
+            ConversationEntry::AgentEvent(v) => match &v.event {
+                luban_api::AgentEvent::Item(item)
+                    if item.kind == luban_api::AgentItemKind::FileChange =>
+                {
+                    item
                 }
-                if !seen.insert(path) {
-                    continue;
+                _ => continue,
+            },
+            ConversationEntry::SystemEvent(v) => {
+                if let luban_api::ConversationSystemEvent::TurnCheckpoint {
+                    checkpoint_id, ..
+                } = &v.event
+                {
+                    outputs.checkpoint_id = Some(checkpoint_id.clone());
                 }
-                paths.push(path.to_owned());
-            }
-            if paths.is_empty() {
-                Some(format!("File changes: {}{status_suffix}", changes.len()))
-            } else {
-                let shown = paths.iter().take(3).cloned().collect::<Vec<_>>();
-                let remaining = paths.len().saturating_sub(shown.len());
-                let suffix = if remaining > 0 {
-                    format!(", +{remaining}")
-                } else {
-                    String::new()
-                };
-                Some(format!(
-                    "File changes: {}{suffix}{status_suffix}",
-                    shown.join(", ")
-                ))
+                continue;
             }
-        }
-        luban_api::AgentItemKind::McpToolCall => {
-            let server = payload
-                .get("server")
+        };
+        let changes = item
+            .payload
+            .get("changes")
+            .and_then(|v| v.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for change in changes {
+            outputs.has_file_changes = true;
+            let path = change
+                .get("path")
                 .and_then(|v| v.as_str())
-                .unwrap_or("mcp");
-            let tool = payload
-                .get("tool")
+                .unwrap_or_default()
+                .trim();
+            let kind = change
+                .get("kind")
                 .and_then(|v| v.as_str())
-                .unwrap_or("tool");
-            Some(format!("MCP: {server}.{tool}{status_suffix}"))
+                .unwrap_or_default();
+            if path.is_empty()
+                || kind == "delete"
+                || !TELEGRAM_OUTPUT_IMAGE_EXTENSIONS.contains(&file_extension(path).as_str())
+                || outputs.image_paths.iter().any(|p| p == path)
+            {
+                continue;
+            }
+            outputs.image_paths.push(path.to_owned());
         }
-        luban_api::AgentItemKind::WebSearch => {
-            let query = payload
-                .get("query")
-                .and_then(|v| v.as_str())
-                .unwrap_or("query");
-            Some(format!(
-                "Web search: {}{status_suffix}",
-                truncate_label(query, 120)
-            ))
-        }
-        luban_api::AgentItemKind::TodoList => Some(format!("Todo list updated{status_suffix}")),
-        luban_api::AgentItemKind::Error => {
-            let message = payload
-                .get("message")
-                .and_then(|v| v.as_str())
-                .or_else(|| payload.get("error").and_then(|v| v.as_str()))
-                .unwrap_or("error");
-            Some(format!(
-                "Error: {}{status_suffix}",
-                truncate_label(message, 160)
-            ))
-        }
-        luban_api::AgentItemKind::Reasoning => None,
-    };
+    }
+    outputs.image_paths.reverse();
 
-    line.map(|v| truncate_label(&v, 180))
+    outputs.has_file_changes.then_some(outputs)
 }
 
-fn format_conversation_entry_for_telegram(entry: &ConversationEntry) -> Option<String> {
-    match entry {
-        ConversationEntry::AgentEvent(v) => match &v.event {
-            luban_api::AgentEvent::Message(msg) => Some(msg.text.clone()),
-            luban_api::AgentEvent::TurnDuration { .. } => None,
-            luban_api::AgentEvent::TurnError { message } => Some(format!("Turn failed: {message}")),
-            luban_api::AgentEvent::TurnCanceled => Some("Turn canceled.".to_owned()),
-            _ => None,
-        },
-        _ => None,
+/// Patch of what a finished turn changed, with its file name and a one-line caption. Turns
+/// without a checkpoint fall back to the whole workspace patch, labeled as such.
+fn turn_patch(
+    services: &dyn ProjectWorkspaceService,
+    worktree_path: &Path,
+    workspace_id: u64,
+    thread_id: u64,
+    checkpoint_id: Option<&str>,
+) -> (String, &'static str, Option<String>) {
+    if let Some(checkpoint_id) = checkpoint_id
+        && let Ok(Some((from, to))) = crate::git_changes::turn_range_trees(
+            services,
+            worktree_path,
+            luban_domain::WorkspaceId::from_u64(workspace_id),
+            luban_domain::WorkspaceThreadId::from_u64(thread_id),
+            checkpoint_id,
+            checkpoint_id,
+        )
+        && let Ok(patch) = crate::git_changes::collect_range_patch(worktree_path, &from, &to)
+    {
+        let summary = crate::git_changes::diff_stat(worktree_path, &from, &to)
+            .ok()
+            .map(|stat| {
+                crate::relay::format_turn_changes(
+                    stat.files_changed,
+                    stat.additions,
+                    stat.deletions,
+                )
+            });
+        return (patch, TELEGRAM_TURN_DIFF_FILE_NAME, summary);
     }
+    (
+        crate::git_changes::collect_patch(worktree_path).unwrap_or_default(),
+        TELEGRAM_WORKSPACE_DIFF_FILE_NAME,
+        Some(TELEGRAM_WORKSPACE_DIFF_CAPTION.to_owned()),
+    )
+}
+
+/// Resolves an agent-reported path inside the worktree, rejecting paths that escape it.
+fn resolve_output_path(worktree_path: &Path, path: &str) -> Option<std::path::PathBuf> {
+    let candidate = Path::new(path);
+    let full = if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        worktree_path.join(candidate)
+    };
+    let canonical = full.canonicalize().ok()?;
+    let root = worktree_path.canonicalize().ok()?;
+    canonical.starts_with(&root).then_some(canonical)
 }
 
 fn resolve_message_target(
@@ -2823,6 +3655,7 @@ mod tests {
             chat: TelegramChat {
                 id: 1,
                 kind: Some("private".to_owned()),
+                title: None,
             },
             text: Some("hello".to_owned()),
             message_thread_id: None,
@@ -2832,12 +3665,15 @@ mod tests {
                 chat: TelegramChat {
                     id: 1,
                     kind: Some("private".to_owned()),
+                    title: None,
                 },
                 text: None,
                 message_thread_id: None,
                 is_topic_message: None,
                 reply_to_message: None,
+                ..Default::default()
             })),
+            ..Default::default()
         }
     }
 
@@ -2904,11 +3740,13 @@ mod tests {
             chat: TelegramChat {
                 id: 1,
                 kind: Some("private".to_owned()),
+                title: None,
             },
             text: Some("hello".to_owned()),
             message_thread_id: None,
             is_topic_message: None,
             reply_to_message: None,
+            ..Default::default()
         };
         assert_eq!(
             resolve_message_target(&msg, &session, &routes, &topic_bindings, now),
@@ -2927,11 +3765,13 @@ mod tests {
             chat: TelegramChat {
                 id: 1,
                 kind: Some("private".to_owned()),
+                title: None,
             },
             text: Some("hello".to_owned()),
             message_thread_id: None,
             is_topic_message: None,
             reply_to_message: None,
+            ..Default::default()
         };
         assert_eq!(
             resolve_message_target(&msg, &session, &routes, &topic_bindings, now),
@@ -2958,11 +3798,13 @@ mod tests {
             chat: TelegramChat {
                 id: 1,
                 kind: Some("private".to_owned()),
+                title: None,
             },
             text: Some("hello".to_owned()),
             message_thread_id: Some(55),
             is_topic_message: Some(true),
             reply_to_message: None,
+            ..Default::default()
         };
         assert_eq!(
             resolve_message_target(&msg, &session, &routes, &topic_bindings, now),
@@ -2970,6 +3812,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn group_pairing_code_accepts_bot_suffixed_commands() {
+        assert_eq!(
+            group_pairing_code("/start@luban_bot abc123"),
+            Some("abc123")
+        );
+        assert_eq!(group_pairing_code("/pair abc123"), Some("abc123"));
+        assert_eq!(group_pairing_code("/start"), None);
+        assert_eq!(group_pairing_code("/starting abc"), None);
+        assert_eq!(group_pairing_code("please /pair abc"), None);
+    }
+
+    #[test]
+    fn telegram_user_display_name_falls_back_to_username() {
+        let user = TelegramUser {
+            id: 7,
+            first_name: "Ada".to_owned(),
+            last_name: Some("Lovelace".to_owned()),
+            username: Some("ada".to_owned()),
+        };
+        assert_eq!(telegram_user_display_name(&user), "Ada Lovelace");
+
+        let user = TelegramUser {
+            id: 7,
+            first_name: " ".to_owned(),
+            last_name: None,
+            username: Some("ada".to_owned()),
+        };
+        assert_eq!(telegram_user_display_name(&user), "@ada");
+    }
+
     fn workspace_snapshot(
         id: u64,
         workspace_name: &str,
@@ -2987,6 +3860,7 @@ mod tests {
             agent_run_status: luban_api::OperationStatus::Idle,
             has_unread_completion: false,
             pull_request: None,
+            disk_usage: None,
         }
     }
 
@@ -3000,6 +3874,7 @@ mod tests {
             task_status: status,
             turn_status: Default::default(),
             last_turn_result: None,
+            forked_from: None,
         }
     }
 
@@ -3100,25 +3975,107 @@ mod tests {
         assert!(format_conversation_entry_for_progress(&entry).is_none());
     }
 
+    fn agent_entry(event: luban_api::AgentEvent) -> ConversationEntry {
+        ConversationEntry::AgentEvent(luban_api::AgentEventEntry {
+            entry_id: "e".to_owned(),
+            created_at_unix_ms: 0,
+            runner: None,
+            event,
+        })
+    }
+
+    fn file_change_entry(changes: serde_json::Value) -> ConversationEntry {
+        agent_entry(luban_api::AgentEvent::Item(luban_api::AgentItem {
+            id: "fc".to_owned(),
+            kind: luban_api::AgentItemKind::FileChange,
+            payload: serde_json::json!({ "changes": changes }),
+        }))
+    }
+
     #[test]
-    fn push_recent_progress_event_filters_empty_and_duplicate() {
-        let mut recent = Vec::new();
-        assert!(!push_recent_progress_event(
-            &mut recent,
-            "   ".to_owned(),
-            5
-        ));
-        assert!(push_recent_progress_event(
-            &mut recent,
-            "step 1".to_owned(),
-            5
-        ));
-        assert!(!push_recent_progress_event(
-            &mut recent,
-            "step 1".to_owned(),
-            5
-        ));
-        assert_eq!(recent, vec!["step 1".to_owned()]);
+    fn inbound_files_keep_largest_photo_and_classify_documents() {
+        let msg = TelegramMessage {
+            caption: Some("broken layout".to_owned()),
+            photo: vec![
+                TelegramPhotoSize {
+                    file_id: "small".to_owned(),
+                    width: 90,
+                    height: 60,
+                    file_size: Some(1_000),
+                },
+                TelegramPhotoSize {
+                    file_id: "large".to_owned(),
+                    width: 1280,
+                    height: 853,
+                    file_size: Some(90_000),
+                },
+            ],
+            document: Some(TelegramDocument {
+                file_id: "doc".to_owned(),
+                file_name: Some("server.log".to_owned()),
+                mime_type: Some("text/plain".to_owned()),
+                file_size: None,
+            }),
+            ..Default::default()
+        };
+        let files = inbound_files(&msg);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].file_id, "large");
+        assert!(files[0].is_image);
+        assert_eq!(files[1].name, "server.log");
+        assert!(!files[1].is_image);
+
+        let png_doc = TelegramMessage {
+            document: Some(TelegramDocument {
+                file_id: "png".to_owned(),
+                file_name: Some("Screen.PNG".to_owned()),
+                mime_type: Some("image/png".to_owned()),
+                file_size: None,
+            }),
+            ..Default::default()
+        };
+        assert!(inbound_files(&png_doc)[0].is_image);
+    }
+
+    #[test]
+    fn completed_turn_outputs_collects_images_from_the_finished_turn() {
+        let entries = vec![
+            file_change_entry(serde_json::json!([{ "path": "old.png", "kind": "add" }])),
+            ConversationEntry::UserEvent(luban_api::UserEventEntry {
+                entry_id: "u".to_owned(),
+                created_at_unix_ms: 0,
+                event: luban_api::UserEvent::Message(luban_api::UserMessage {
+                    text: "make a chart".to_owned(),
+                    attachments: Vec::new(),
+                    author: None,
+                }),
+            }),
+            ConversationEntry::SystemEvent(luban_api::ConversationSystemEventEntry {
+                entry_id: "s".to_owned(),
+                created_at_unix_ms: 0,
+                event: luban_api::ConversationSystemEvent::TurnCheckpoint {
+                    checkpoint_id: "100-1".to_owned(),
+                    commit: "abc".to_owned(),
+                },
+            }),
+            file_change_entry(serde_json::json!([
+                { "path": "chart.png", "kind": "add" },
+                { "path": "src/main.rs", "kind": "update" },
+                { "path": "gone.jpg", "kind": "delete" }
+            ])),
+            agent_entry(luban_api::AgentEvent::TurnDuration { duration_ms: 10 }),
+        ];
+
+        assert_eq!(completed_turn_outputs(&entries, 10, None), None);
+        assert_eq!(completed_turn_outputs(&entries, 10, Some(14)), None);
+        assert_eq!(
+            completed_turn_outputs(&entries, 10, Some(11)),
+            Some(TurnOutputs {
+                image_paths: vec!["chart.png".to_owned()],
+                has_file_changes: true,
+                checkpoint_id: Some("100-1".to_owned()),
+            })
+        );
     }
 
     #[test]
@@ -3149,6 +4106,25 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn telegram_webhook_conflict_is_detected() {
+        assert!(telegram_webhook_conflict(
+            "Conflict: can't use getUpdates method while webhook is active; use deleteWebhook to delete the webhook first"
+        ));
+        assert!(!telegram_webhook_conflict(
+            "Conflict: terminated by other getUpdates request"
+        ));
+        assert!(!telegram_webhook_conflict("Unauthorized"));
+    }
+
+    #[test]
+    fn constant_time_eq_compares_length_and_content() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret-longer"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
     #[test]
     fn telegram_edit_message_not_modified_is_detected() {
         assert!(telegram_edit_message_not_modified(
@@ -12,6 +12,7 @@ async fn auth_bootstrap_sets_cookie_and_unlocks_api() {
                 mode: luban_server::AuthMode::SingleUser,
                 bootstrap_token: Some(token.clone()),
             },
+            ..Default::default()
         },
     )
     .await
@@ -167,6 +167,99 @@ async fn start_avatar_upstream(counter: Arc<AtomicUsize>) -> StartedTestServer {
     }
 }
 
+async fn start_page_upstream() -> StartedTestServer {
+    async fn page() -> impl axum::response::IntoResponse {
+        (
+            [(axum::http::header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            "<html><head><title>Contract page</title></head><body><p>Snapshot me</p></body></html>",
+        )
+    }
+
+    let app = axum::Router::new().route("/page", axum::routing::get(page));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind page upstream");
+    let addr = listener.local_addr().expect("page local addr");
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("page serve");
+    });
+
+    StartedTestServer {
+        addr,
+        handle: Some(handle),
+    }
+}
+
+async fn set_url_attachment_domains_via_ws(server_addr: SocketAddr, domains: Vec<String>) {
+    let url = format!("ws://{}/api/events", server_addr);
+    let (mut socket, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .expect("connect websocket");
+
+    let first = recv_ws_msg(&mut socket, Duration::from_secs(2)).await;
+    assert!(matches!(first, luban_api::WsServerMessage::Hello { .. }));
+
+    let hello = luban_api::WsClientMessage::Hello {
+        protocol_version: luban_api::PROTOCOL_VERSION,
+        last_seen_rev: None,
+    };
+    socket
+        .send(Message::Text(
+            serde_json::to_string(&hello)
+                .expect("serialize hello")
+                .into(),
+        ))
+        .await
+        .expect("send hello");
+
+    let action = luban_api::WsClientMessage::Action {
+        request_id: "req-url-domains".to_owned(),
+        action: Box::new(luban_api::ClientAction::UrlAttachmentDomainsChanged {
+            domains: domains.clone(),
+        }),
+    };
+    socket
+        .send(Message::Text(
+            serde_json::to_string(&action)
+                .expect("serialize url_attachment_domains_changed action")
+                .into(),
+        ))
+        .await
+        .expect("send url_attachment_domains_changed action");
+
+    let mut saw_ack = false;
+    let mut saw_app_changed = false;
+    for _ in 0..60 {
+        let msg = recv_ws_msg(&mut socket, Duration::from_secs(2)).await;
+        match msg {
+            luban_api::WsServerMessage::Ack { request_id, .. } => {
+                if request_id == "req-url-domains" {
+                    saw_ack = true;
+                }
+            }
+            luban_api::WsServerMessage::Event { event, .. } => {
+                if let luban_api::ServerEvent::AppChanged { snapshot, .. } = *event
+                    && snapshot.url_attachments.allowed_domains == domains
+                {
+                    saw_app_changed = true;
+                }
+            }
+            luban_api::WsServerMessage::Error { message, .. } => {
+                panic!("url_attachment_domains_changed error: {message}");
+            }
+            _ => {}
+        }
+        if saw_ack && saw_app_changed {
+            break;
+        }
+    }
+    assert!(saw_ack, "expected ack for url_attachment_domains_changed");
+    assert!(
+        saw_app_changed,
+        "expected an AppChanged event with the new URL attachment allowlist"
+    );
+}
+
 async fn create_workdir_via_ws(server_addr: SocketAddr, project_path: &str) -> (u64, String) {
     let url = format!("ws://{}/api/events", server_addr);
     let (mut socket, _) = tokio_tungstenite::connect_async(url)
@@ -1350,6 +1443,76 @@ async fn http_contracts_smoke() {
             .expect("diff json");
     }
 
+    // C-HTTP-TURN-DIFF
+    {
+        let app: luban_api::AppSnapshot = client
+            .get(format!("{base}/api/app"))
+            .send()
+            .await
+            .expect("GET /app")
+            .error_for_status()
+            .expect("app status")
+            .json()
+            .await
+            .expect("app json");
+        let worktree = PathBuf::from(
+            &app.projects
+                .iter()
+                .flat_map(|p| &p.workspaces)
+                .find(|w| w.id.0 == workdir_id)
+                .expect("workdir in app snapshot")
+                .worktree_path,
+        );
+        run_git(
+            &worktree,
+            &[
+                "update-ref",
+                &format!("refs/luban/checkpoints/{workdir_id}/{task_id}/100-1"),
+                "HEAD",
+            ],
+        );
+        std::fs::write(worktree.join("turn-diff.txt"), "one\ntwo\n").expect("write file");
+
+        let diff: luban_api::WorkspaceDiffSnapshot = client
+            .get(format!(
+                "{base}/api/workdirs/{workdir_id}/tasks/{task_id}/turn_diff?from=100-1"
+            ))
+            .send()
+            .await
+            .expect("GET /turn_diff")
+            .error_for_status()
+            .expect("turn diff status")
+            .json()
+            .await
+            .expect("turn diff json");
+        let file = diff
+            .files
+            .iter()
+            .find(|f| f.file.path == "turn-diff.txt")
+            .expect("expected the file written after the checkpoint");
+        assert_eq!(file.file.additions, Some(2));
+        assert_eq!(file.new_file.contents, "one\ntwo\n");
+        std::fs::remove_file(worktree.join("turn-diff.txt")).expect("remove file");
+
+        let missing = client
+            .get(format!(
+                "{base}/api/workdirs/{workdir_id}/tasks/{task_id}/turn_diff?from=999-1"
+            ))
+            .send()
+            .await
+            .expect("GET /turn_diff (missing)");
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let invalid = client
+            .get(format!(
+                "{base}/api/workdirs/{workdir_id}/tasks/{task_id}/turn_diff?from=main"
+            ))
+            .send()
+            .await
+            .expect("GET /turn_diff (invalid)");
+        assert_eq!(invalid.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
     // C-HTTP-MENTIONS
     {
         let res = client
@@ -1439,6 +1602,53 @@ async fn http_contracts_smoke() {
         );
     }
 
+    // C-HTTP-URL-ATTACHMENTS
+    {
+        let page_upstream = start_page_upstream().await;
+        let page_url = format!("http://{}/page", page_upstream.addr);
+
+        let denied = client
+            .post(format!("{base}/api/workdirs/{workdir_id}/url_attachments"))
+            .json(&serde_json::json!({ "url": page_url }))
+            .send()
+            .await
+            .expect("POST /url_attachments");
+        assert_eq!(denied.status(), reqwest::StatusCode::FORBIDDEN);
+
+        set_url_attachment_domains_via_ws(server.addr, vec!["127.0.0.1".to_owned()]).await;
+
+        let att: luban_api::AttachmentRef = client
+            .post(format!("{base}/api/workdirs/{workdir_id}/url_attachments"))
+            .json(&serde_json::json!({ "url": page_url }))
+            .send()
+            .await
+            .expect("POST /url_attachments")
+            .error_for_status()
+            .expect("url attachment status")
+            .json()
+            .await
+            .expect("url attachment json");
+        assert_eq!(att.kind, luban_api::AttachmentKind::Url);
+        assert_eq!(att.name, "Contract page");
+        assert_eq!(att.extension, "md");
+
+        let snapshot = client
+            .get(format!(
+                "{base}/api/workdirs/{workdir_id}/attachments/{}?ext={}",
+                att.id, att.extension
+            ))
+            .send()
+            .await
+            .expect("GET /attachments/{id}")
+            .error_for_status()
+            .expect("download status")
+            .text()
+            .await
+            .expect("download text");
+        assert!(snapshot.contains(&format!("source_url: {page_url}")));
+        assert!(snapshot.contains("# Contract page\n\nSnapshot me"));
+    }
+
     // C-HTTP-TASKS (workdir_status query)
     {
         // The project used earlier in this test has a GitHub `origin`, and workspace creation
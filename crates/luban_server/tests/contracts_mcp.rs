@@ -0,0 +1,210 @@
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+struct EnvGuard {
+    _lock: std::sync::MutexGuard<'static, ()>,
+    prev: Vec<(&'static str, Option<std::ffi::OsString>)>,
+}
+
+impl EnvGuard {
+    fn lock(keys: Vec<&'static str>) -> Self {
+        let lock = ENV_LOCK.lock().expect("env lock poisoned");
+        let mut prev = Vec::with_capacity(keys.len());
+        for key in keys {
+            prev.push((key, std::env::var_os(key)));
+        }
+        Self { _lock: lock, prev }
+    }
+
+    fn set(&self, key: &'static str, value: &std::path::Path) {
+        unsafe {
+            std::env::set_var(key, value);
+        }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, prev) in self.prev.drain(..) {
+            if let Some(prev) = prev {
+                unsafe {
+                    std::env::set_var(key, prev);
+                }
+            } else {
+                unsafe {
+                    std::env::remove_var(key);
+                }
+            }
+        }
+    }
+}
+
+async fn post_mcp(
+    client: &reqwest::Client,
+    addr: SocketAddr,
+    body: serde_json::Value,
+) -> (reqwest::StatusCode, Option<serde_json::Value>) {
+    let resp = client
+        .post(format!("http://{addr}/api/mcp"))
+        .json(&body)
+        .send()
+        .await
+        .expect("post /api/mcp");
+    let status = resp.status();
+    let text = resp.text().await.expect("read body");
+    if text.trim().is_empty() {
+        return (status, None);
+    }
+    (
+        status,
+        Some(serde_json::from_str(&text).expect("parse json-rpc response")),
+    )
+}
+
+#[tokio::test]
+async fn mcp_http_contract_smoke() {
+    let env = EnvGuard::lock(vec![luban_domain::paths::LUBAN_ROOT_ENV]);
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let luban_root = std::env::temp_dir().join(format!(
+        "luban-contracts-mcp-root-{}-{}",
+        std::process::id(),
+        unique
+    ));
+    env.set(luban_domain::paths::LUBAN_ROOT_ENV, &luban_root);
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let server =
+        luban_server::start_server_with_config(addr, luban_server::ServerConfig::default())
+            .await
+            .expect("start server");
+    let client = reqwest::Client::new();
+
+    let (status, body) = post_mcp(
+        &client,
+        server.addr,
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": luban_server::mcp::MCP_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "contracts", "version": "0" },
+            },
+        }),
+    )
+    .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    let body = body.expect("initialize body");
+    assert_eq!(
+        body["result"]["protocolVersion"],
+        luban_server::mcp::MCP_PROTOCOL_VERSION
+    );
+
+    let (status, body) = post_mcp(
+        &client,
+        server.addr,
+        serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+    )
+    .await;
+    assert_eq!(status, reqwest::StatusCode::ACCEPTED);
+    assert!(body.is_none());
+
+    let (status, body) = post_mcp(
+        &client,
+        server.addr,
+        serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+    )
+    .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    let tools = body.expect("tools/list body")["result"]["tools"]
+        .as_array()
+        .expect("tools array")
+        .len();
+    assert_eq!(tools, 7);
+
+    let (status, body) = post_mcp(
+        &client,
+        server.addr,
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/call",
+            "params": { "name": "list_projects", "arguments": {} },
+        }),
+    )
+    .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    let body = body.expect("tools/call body");
+    assert_eq!(body["result"]["isError"], false);
+    let text = body["result"]["content"][0]["text"]
+        .as_str()
+        .expect("text content");
+    let projects: serde_json::Value = serde_json::from_str(text).expect("projects json");
+    assert!(projects.is_array());
+
+    let (status, body) = post_mcp(
+        &client,
+        server.addr,
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "tools/call",
+            "params": { "name": "get_workdir_diff", "arguments": { "workdir_id": 999_999 } },
+        }),
+    )
+    .await;
+    assert_eq!(status, reqwest::StatusCode::OK);
+    assert_eq!(body.expect("tools/call body")["result"]["isError"], true);
+
+    let resp = client
+        .post(format!("http://{}/api/mcp", server.addr))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body("{not json")
+        .send()
+        .await
+        .expect("post invalid json");
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    // A cross-site "simple" request: no preflight, so the server itself must refuse it.
+    let list_projects = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 5,
+        "method": "tools/call",
+        "params": { "name": "list_projects", "arguments": {} },
+    })
+    .to_string();
+    let resp = client
+        .post(format!("http://{}/api/mcp", server.addr))
+        .header(reqwest::header::CONTENT_TYPE, "text/plain")
+        .body(list_projects.clone())
+        .send()
+        .await
+        .expect("post text/plain");
+    assert_eq!(resp.status(), reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let resp = client
+        .post(format!("http://{}/api/mcp", server.addr))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(reqwest::header::ORIGIN, "https://evil.example")
+        .body(list_projects.clone())
+        .send()
+        .await
+        .expect("post cross-origin");
+    assert_eq!(resp.status(), reqwest::StatusCode::FORBIDDEN);
+
+    let resp = client
+        .post(format!("http://{}/api/mcp", server.addr))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(reqwest::header::ORIGIN, format!("http://{}", server.addr))
+        .body(list_projects)
+        .send()
+        .await
+        .expect("post same-origin");
+    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+}
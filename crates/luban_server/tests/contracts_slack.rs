@@ -0,0 +1,239 @@
+use axum::Json;
+use hmac::{Hmac, Mac as _};
+use sha2::Sha256;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, oneshot};
+
+static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+const SIGNING_SECRET: &str = "test-signing-secret";
+
+struct EnvGuard {
+    _lock: std::sync::MutexGuard<'static, ()>,
+    prev: Vec<(&'static str, Option<std::ffi::OsString>)>,
+}
+
+impl EnvGuard {
+    fn lock(keys: Vec<&'static str>) -> Self {
+        let lock = ENV_LOCK.lock().expect("env lock poisoned");
+        let mut prev = Vec::with_capacity(keys.len());
+        for key in keys {
+            prev.push((key, std::env::var_os(key)));
+        }
+        Self { _lock: lock, prev }
+    }
+
+    fn set_str(&self, key: &'static str, value: &str) {
+        unsafe {
+            std::env::set_var(key, value);
+        }
+    }
+
+    fn remove(&self, key: &'static str) {
+        unsafe {
+            std::env::remove_var(key);
+        }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, prev) in self.prev.drain(..) {
+            if let Some(prev) = prev {
+                unsafe {
+                    std::env::set_var(key, prev);
+                }
+            } else {
+                unsafe {
+                    std::env::remove_var(key);
+                }
+            }
+        }
+    }
+}
+
+async fn start_fake_slack_api() -> (
+    String,
+    mpsc::UnboundedReceiver<serde_json::Value>,
+    oneshot::Sender<()>,
+) {
+    let (responses_tx, responses_rx) = mpsc::unbounded_channel::<serde_json::Value>();
+    let app = axum::Router::new().route(
+        "/response",
+        axum::routing::post(move |Json(body): Json<serde_json::Value>| {
+            let responses_tx = responses_tx.clone();
+            async move {
+                let _ = responses_tx.send(body);
+                "ok"
+            }
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind fake slack api");
+    let addr = listener.local_addr().expect("get addr");
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    tokio::spawn(async move {
+        let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        });
+        let _ = server.await;
+    });
+
+    (format!("http://{addr}"), responses_rx, shutdown_tx)
+}
+
+fn signed_request(
+    client: &reqwest::Client,
+    url: String,
+    body: String,
+    content_type: &str,
+) -> reqwest::RequestBuilder {
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock")
+        .as_secs();
+    let mut mac = Hmac::<Sha256>::new_from_slice(SIGNING_SECRET.as_bytes()).expect("hmac");
+    mac.update(format!("v0:{ts}:{body}").as_bytes());
+    let signature = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+
+    client
+        .post(url)
+        .header("content-type", content_type)
+        .header("x-slack-request-timestamp", ts.to_string())
+        .header("x-slack-signature", format!("v0={signature}"))
+        .body(body)
+}
+
+#[tokio::test]
+async fn slack_routes_verify_signatures_and_answer_commands() {
+    let (api_base, mut responses, shutdown) = start_fake_slack_api().await;
+    let env = EnvGuard::lock(vec![
+        "LUBAN_SLACK_BOT_TOKEN",
+        "LUBAN_SLACK_SIGNING_SECRET",
+        "LUBAN_SLACK_API_BASE_URL",
+        "LUBAN_SLACK_ALLOWED_USERS",
+        "LUBAN_SLACK_ALLOWED_CHANNELS",
+    ]);
+    env.set_str("LUBAN_SLACK_BOT_TOKEN", "xoxb-test");
+    env.set_str("LUBAN_SLACK_SIGNING_SECRET", SIGNING_SECRET);
+    env.set_str("LUBAN_SLACK_API_BASE_URL", &api_base);
+    env.set_str("LUBAN_SLACK_ALLOWED_USERS", "U1, U2");
+    env.remove("LUBAN_SLACK_ALLOWED_CHANNELS");
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let server =
+        luban_server::start_server_with_config(addr, luban_server::ServerConfig::default())
+            .await
+            .unwrap();
+    let client = reqwest::Client::new();
+
+    let unsigned = client
+        .post(format!("http://{}/api/slack/events", server.addr))
+        .body(r#"{"type":"url_verification","challenge":"abc"}"#)
+        .send()
+        .await
+        .expect("send unsigned event");
+    assert_eq!(unsigned.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let challenge = signed_request(
+        &client,
+        format!("http://{}/api/slack/events", server.addr),
+        r#"{"type":"url_verification","challenge":"abc"}"#.to_owned(),
+        "application/json",
+    )
+    .send()
+    .await
+    .expect("send url verification");
+    assert_eq!(challenge.status(), reqwest::StatusCode::OK);
+    let body: serde_json::Value = challenge.json().await.expect("challenge json");
+    assert_eq!(body["challenge"], "abc");
+
+    let help = signed_request(
+        &client,
+        format!("http://{}/api/slack/commands", server.addr),
+        "command=%2Fluban&text=help&user_id=U1&user_name=alice&channel_id=C1&response_url=http%3A%2F%2Funused"
+            .to_owned(),
+        "application/x-www-form-urlencoded",
+    )
+    .send()
+    .await
+    .expect("send help command");
+    let body: serde_json::Value = help.json().await.expect("help json");
+    assert_eq!(body["response_type"], "ephemeral");
+    assert!(
+        body["text"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("/luban new")
+    );
+
+    let response_url = format!("{api_base}/response");
+    let stranger_form = serde_urlencoded::to_string([
+        ("command", "/luban"),
+        ("text", "new my-app do something"),
+        ("user_id", "U9"),
+        ("user_name", "mallory"),
+        ("channel_id", "C1"),
+        ("response_url", response_url.as_str()),
+    ])
+    .expect("encode command form");
+    let stranger = signed_request(
+        &client,
+        format!("http://{}/api/slack/commands", server.addr),
+        stranger_form,
+        "application/x-www-form-urlencoded",
+    )
+    .send()
+    .await
+    .expect("send command from a user not allowed");
+    assert_eq!(stranger.status(), reqwest::StatusCode::OK);
+    let body: serde_json::Value = stranger.json().await.expect("rejection json");
+    assert_eq!(body["response_type"], "ephemeral");
+    assert!(
+        body["text"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("not allowed"),
+        "expected a rejection: {body}"
+    );
+
+    let form = serde_urlencoded::to_string([
+        ("command", "/luban"),
+        ("text", "cancel 999/1"),
+        ("user_id", "U2"),
+        ("user_name", "alice"),
+        ("channel_id", "C1"),
+        ("response_url", response_url.as_str()),
+    ])
+    .expect("encode command form");
+    let cancel = signed_request(
+        &client,
+        format!("http://{}/api/slack/commands", server.addr),
+        form,
+        "application/x-www-form-urlencoded",
+    )
+    .send()
+    .await
+    .expect("send cancel command");
+    assert_eq!(cancel.status(), reqwest::StatusCode::OK);
+
+    let reply = tokio::time::timeout(Duration::from_secs(5), responses.recv())
+        .await
+        .expect("timed out waiting for response_url reply")
+        .expect("response channel closed");
+    assert_eq!(reply["response_type"], "ephemeral");
+    assert!(
+        !reply["text"].as_str().unwrap_or_default().is_empty(),
+        "expected a reply text: {reply}"
+    );
+
+    let _ = shutdown.send(());
+}
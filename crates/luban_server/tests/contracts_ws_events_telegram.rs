@@ -3,7 +3,7 @@ use futures::{SinkExt as _, StreamExt as _};
 use serde_json::json;
 use std::net::SocketAddr;
 use std::time::Duration;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
 use tokio_tungstenite::tungstenite::Message;
 
 static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
@@ -28,6 +28,12 @@ impl EnvGuard {
             std::env::set_var(key, value);
         }
     }
+
+    fn remove(&self, key: &'static str) {
+        unsafe {
+            std::env::remove_var(key);
+        }
+    }
 }
 
 impl Drop for EnvGuard {
@@ -63,7 +69,37 @@ async fn recv_ws_msg(
     serde_json::from_str(&text).expect("failed to parse ws server message")
 }
 
-async fn start_fake_telegram_api(username: &'static str) -> (String, oneshot::Sender<()>) {
+type TelegramSocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+const ENV_KEYS: [&str; 3] = [
+    "LUBAN_TELEGRAM_API_BASE_URL",
+    "LUBAN_TELEGRAM_WEBHOOK_URL",
+    "LUBAN_TELEGRAM_WEBHOOK_SECRET",
+];
+
+/// Records the Bot API methods the gateway called, with their JSON bodies when present.
+async fn start_fake_telegram_api(
+    username: &'static str,
+) -> (
+    String,
+    mpsc::UnboundedReceiver<(String, serde_json::Value)>,
+    oneshot::Sender<()>,
+) {
+    let (calls_tx, calls_rx) = mpsc::unbounded_channel::<(String, serde_json::Value)>();
+    let record = |method: &'static str, result: serde_json::Value| {
+        let calls_tx = calls_tx.clone();
+        axum::routing::post(move |body: axum::body::Bytes| {
+            let calls_tx = calls_tx.clone();
+            let result = result.clone();
+            async move {
+                let body = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+                let _ = calls_tx.send((method.to_owned(), body));
+                Json(json!({ "ok": true, "result": result }))
+            }
+        })
+    };
+    let get_updates_tx = calls_tx.clone();
     let app = axum::Router::new()
         .route(
             "/{bot}/getMe",
@@ -76,26 +112,28 @@ async fn start_fake_telegram_api(username: &'static str) -> (String, oneshot::Se
         )
         .route(
             "/{bot}/getUpdates",
-            axum::routing::get(|| async move {
-                Json(json!({
-                    "ok": true,
-                    "result": [],
-                }))
+            axum::routing::get(move || {
+                let get_updates_tx = get_updates_tx.clone();
+                async move {
+                    let _ = get_updates_tx.send(("getUpdates".to_owned(), serde_json::Value::Null));
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Json(json!({
+                        "ok": true,
+                        "result": [],
+                    }))
+                }
             }),
         )
         .route(
             "/{bot}/sendMessage",
-            axum::routing::post(|| async move {
-                Json(json!({
-                    "ok": true,
-                    "result": { "message_id": 1 },
-                }))
-            }),
+            record("sendMessage", json!({ "message_id": 1 })),
         )
         .route(
             "/{bot}/answerCallbackQuery",
-            axum::routing::post(|| async move { Json(json!({ "ok": true, "result": true })) }),
-        );
+            record("answerCallbackQuery", json!(true)),
+        )
+        .route("/{bot}/setWebhook", record("setWebhook", json!(true)))
+        .route("/{bot}/deleteWebhook", record("deleteWebhook", json!(true)));
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
         .await
@@ -109,22 +147,30 @@ async fn start_fake_telegram_api(username: &'static str) -> (String, oneshot::Se
         let _ = server.await;
     });
 
-    (format!("http://{addr}"), shutdown_tx)
+    (format!("http://{addr}"), calls_rx, shutdown_tx)
 }
 
-#[tokio::test]
-async fn ws_events_telegram_pair_start_emits_pair_ready() {
-    let (api_base, shutdown) = start_fake_telegram_api("TestBot").await;
-    let env = EnvGuard::lock(vec!["LUBAN_TELEGRAM_API_BASE_URL"]);
-    env.set_str("LUBAN_TELEGRAM_API_BASE_URL", &api_base);
-
-    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
-    let server =
-        luban_server::start_server_with_config(addr, luban_server::ServerConfig::default())
+/// Waits for `method`, returning its body and the names of the calls seen before it.
+async fn wait_for_call(
+    calls: &mut mpsc::UnboundedReceiver<(String, serde_json::Value)>,
+    method: &str,
+) -> (serde_json::Value, Vec<String>) {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    let mut skipped = Vec::new();
+    loop {
+        let (name, body) = tokio::time::timeout_at(deadline, calls.recv())
             .await
-            .unwrap();
+            .unwrap_or_else(|_| panic!("timed out waiting for telegram {method}"))
+            .expect("telegram call channel closed");
+        if name == method {
+            return (body, skipped);
+        }
+        skipped.push(name);
+    }
+}
 
-    let url = format!("ws://{}/api/events", server.addr);
+async fn connect_events(addr: SocketAddr) -> TelegramSocket {
+    let url = format!("ws://{addr}/api/events");
     let (mut socket, _) = tokio_tungstenite::connect_async(url)
         .await
         .expect("connect websocket");
@@ -144,25 +190,41 @@ async fn ws_events_telegram_pair_start_emits_pair_ready() {
         ))
         .await
         .expect("send hello");
+    socket
+}
 
+async fn send_action(
+    socket: &mut TelegramSocket,
+    request_id: &str,
+    action: luban_api::ClientAction,
+) {
     let action = luban_api::WsClientMessage::Action {
-        request_id: "req-telegram-token".to_owned(),
-        action: Box::new(luban_api::ClientAction::TelegramBotTokenSet {
-            token: "test-token".to_owned(),
-        }),
+        request_id: request_id.to_owned(),
+        action: Box::new(action),
     };
     socket
         .send(Message::Text(
             serde_json::to_string(&action)
-                .expect("serialize telegram token set")
+                .expect("serialize action")
                 .into(),
         ))
         .await
-        .expect("send telegram token set");
+        .expect("send action");
+}
+
+async fn set_token(socket: &mut TelegramSocket) {
+    send_action(
+        socket,
+        "req-telegram-token",
+        luban_api::ClientAction::TelegramBotTokenSet {
+            token: "test-token".to_owned(),
+        },
+    )
+    .await;
 
     let mut saw_token_ack = false;
     for _ in 0..50 {
-        let msg = recv_ws_msg(&mut socket, Duration::from_secs(2)).await;
+        let msg = recv_ws_msg(socket, Duration::from_secs(2)).await;
         if let luban_api::WsServerMessage::Ack { request_id, .. } = msg
             && request_id == "req-telegram-token"
         {
@@ -171,24 +233,21 @@ async fn ws_events_telegram_pair_start_emits_pair_ready() {
         }
     }
     assert!(saw_token_ack, "expected ack for TelegramBotTokenSet");
+}
 
-    let action = luban_api::WsClientMessage::Action {
-        request_id: "req-telegram-pair".to_owned(),
-        action: Box::new(luban_api::ClientAction::TelegramPairStart),
-    };
-    socket
-        .send(Message::Text(
-            serde_json::to_string(&action)
-                .expect("serialize telegram pair start")
-                .into(),
-        ))
-        .await
-        .expect("send telegram pair start");
+/// Starts pairing and returns the code embedded in the deep link.
+async fn start_pairing(socket: &mut TelegramSocket) -> String {
+    send_action(
+        socket,
+        "req-telegram-pair",
+        luban_api::ClientAction::TelegramPairStart,
+    )
+    .await;
 
     let mut saw_ack = false;
-    let mut saw_ready = false;
+    let mut code = None;
     for _ in 0..80 {
-        let msg = recv_ws_msg(&mut socket, Duration::from_secs(2)).await;
+        let msg = recv_ws_msg(socket, Duration::from_secs(2)).await;
         match msg {
             luban_api::WsServerMessage::Ack { request_id, .. } => {
                 if request_id == "req-telegram-pair" {
@@ -201,21 +260,144 @@ async fn ws_events_telegram_pair_start_emits_pair_ready() {
                 {
                     let prefix = "https://t.me/TestBot?start=";
                     assert!(url.starts_with(prefix));
-                    let code = &url[prefix.len()..];
-                    assert_eq!(code.len(), 32);
-                    assert!(code.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')));
-                    saw_ready = true;
+                    let value = &url[prefix.len()..];
+                    assert_eq!(value.len(), 32);
+                    assert!(value.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')));
+                    code = Some(value.to_owned());
                 }
             }
             _ => {}
         }
-        if saw_ack && saw_ready {
+        if saw_ack && code.is_some() {
             break;
         }
     }
 
     assert!(saw_ack, "expected ack for TelegramPairStart");
-    assert!(saw_ready, "expected TelegramPairReady event");
+    code.expect("expected TelegramPairReady event")
+}
+
+#[tokio::test]
+async fn ws_events_telegram_pair_start_emits_pair_ready() {
+    let (api_base, mut calls, shutdown) = start_fake_telegram_api("TestBot").await;
+    let env = EnvGuard::lock(ENV_KEYS.to_vec());
+    env.set_str("LUBAN_TELEGRAM_API_BASE_URL", &api_base);
+    env.remove("LUBAN_TELEGRAM_WEBHOOK_URL");
+    env.remove("LUBAN_TELEGRAM_WEBHOOK_SECRET");
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let server =
+        luban_server::start_server_with_config(addr, luban_server::ServerConfig::default())
+            .await
+            .unwrap();
+
+    let mut socket = connect_events(server.addr).await;
+    set_token(&mut socket).await;
+    start_pairing(&mut socket).await;
+
+    // Without a webhook URL the gateway long-polls and never registers a webhook.
+    let (first, _) = tokio::time::timeout(Duration::from_secs(5), calls.recv())
+        .await
+        .expect("timed out waiting for getUpdates")
+        .expect("telegram call channel closed");
+    assert_eq!(first, "getUpdates");
+
+    let webhook = reqwest::Client::new()
+        .post(format!("http://{}/api/telegram/webhook", server.addr))
+        .json(&json!({ "update_id": 1 }))
+        .send()
+        .await
+        .expect("post webhook");
+    assert_eq!(webhook.status(), reqwest::StatusCode::NOT_FOUND);
+
+    let _ = shutdown.send(());
+    drop(env);
+}
+
+#[tokio::test]
+async fn telegram_webhook_mode_registers_webhook_and_accepts_updates() {
+    let (api_base, mut calls, shutdown) = start_fake_telegram_api("TestBot").await;
+    let env = EnvGuard::lock(ENV_KEYS.to_vec());
+    env.set_str("LUBAN_TELEGRAM_API_BASE_URL", &api_base);
+    env.set_str(
+        "LUBAN_TELEGRAM_WEBHOOK_URL",
+        "https://luban.example.com/api/telegram/webhook",
+    );
+    env.set_str("LUBAN_TELEGRAM_WEBHOOK_SECRET", "webhook-secret");
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let server =
+        luban_server::start_server_with_config(addr, luban_server::ServerConfig::default())
+            .await
+            .unwrap();
+
+    let mut socket = connect_events(server.addr).await;
+    set_token(&mut socket).await;
+
+    let (registration, mut seen) = wait_for_call(&mut calls, "setWebhook").await;
+    assert_eq!(
+        registration["url"],
+        "https://luban.example.com/api/telegram/webhook"
+    );
+    assert_eq!(registration["secret_token"], "webhook-secret");
+
+    let code = start_pairing(&mut socket).await;
+    let webhook_url = format!("http://{}/api/telegram/webhook", server.addr);
+    let update = json!({
+        "update_id": 7,
+        "message": {
+            "message_id": 1,
+            "chat": { "id": 4242, "type": "private" },
+            "text": format!("/start {code}"),
+        },
+    });
+    let client = reqwest::Client::new();
+
+    let rejected = client
+        .post(&webhook_url)
+        .header("x-telegram-bot-api-secret-token", "wrong")
+        .json(&update)
+        .send()
+        .await
+        .expect("post webhook with wrong secret");
+    assert_eq!(rejected.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let accepted = client
+        .post(&webhook_url)
+        .header("x-telegram-bot-api-secret-token", "webhook-secret")
+        .json(&update)
+        .send()
+        .await
+        .expect("post webhook");
+    assert_eq!(accepted.status(), reqwest::StatusCode::OK);
+
+    let mut paired = false;
+    for _ in 0..80 {
+        let msg = recv_ws_msg(&mut socket, Duration::from_secs(2)).await;
+        if let luban_api::WsServerMessage::Event { event, .. } = msg
+            && let luban_api::ServerEvent::AppChanged { snapshot, .. } = *event
+            && snapshot.integrations.telegram.paired_chat_id == Some(4242)
+        {
+            paired = true;
+            break;
+        }
+    }
+    assert!(paired, "expected the webhook update to pair the chat");
+
+    send_action(
+        &mut socket,
+        "req-telegram-clear",
+        luban_api::ClientAction::TelegramBotTokenClear,
+    )
+    .await;
+    let (_, before_delete) = wait_for_call(&mut calls, "deleteWebhook").await;
+    seen.extend(before_delete);
+
+    // Updates were pushed, so getUpdates must never have been polled.
+    assert!(
+        !seen.iter().any(|name| name == "getUpdates"),
+        "unexpected calls: {seen:?}"
+    );
 
     let _ = shutdown.send(());
     drop(env);
@@ -1,12 +1,14 @@
 use anyhow::Context as _;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::{Manager as _, WebviewUrl, WebviewWindowBuilder};
 use tauri_plugin_updater::UpdaterExt as _;
 
 #[cfg(target_os = "macos")]
 mod macos_process_name;
+mod notifications;
 mod path_env;
 
 static UPDATE_CHECK_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
@@ -68,6 +70,17 @@ fn open_external(url: String) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+fn focus_main_window(app: tauri::AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "main window not found".to_owned())?;
+    let _ = window.unminimize();
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 fn resolve_web_dist(app: &tauri::AppHandle) -> PathBuf {
     if let Ok(resource_dir) = app.path().resource_dir() {
         let candidates = [
@@ -307,13 +320,16 @@ fn main() -> anyhow::Result<()> {
                 });
             }
         })
-        .invoke_handler(tauri::generate_handler![open_external])
+        .invoke_handler(tauri::generate_handler![open_external, focus_main_window])
         .setup(|app| {
             let _ = path_env::fix_path_env();
             let handle = app.handle();
             handle
                 .plugin(tauri_plugin_updater::Builder::new().build())
                 .context("register updater plugin")?;
+            handle
+                .plugin(tauri_plugin_notification::init())
+                .context("register notification plugin")?;
             install_app_menu(app)?;
 
             let web_dist = resolve_web_dist(handle);
@@ -321,9 +337,17 @@ fn main() -> anyhow::Result<()> {
                 std::env::set_var("LUBAN_WEB_DIST_DIR", &web_dist);
             }
 
+            let pending_focus = Arc::new(notifications::PendingFocus::default());
+            let config = luban_server::ServerConfig {
+                notification_presenter: Some(Arc::new(notifications::TauriNotifications::new(
+                    handle.clone(),
+                    pending_focus.clone(),
+                ))),
+                ..luban_server::ServerConfig::from_env()
+            };
             let server = tauri::async_runtime::block_on(async {
                 let addr = resolve_server_addr()?;
-                luban_server::start_server(addr).await
+                luban_server::start_server_with_config(addr, config).await
             })
             .context("failed to start luban_server")?;
 
@@ -333,12 +357,19 @@ fn main() -> anyhow::Result<()> {
 
             app.manage(server);
 
-            WebviewWindowBuilder::new(app, "main", WebviewUrl::External(url))
+            let window = WebviewWindowBuilder::new(app, "main", WebviewUrl::External(url))
                 .title("Luban")
                 .inner_size(1280.0, 800.0)
                 .devtools(webview_devtools_enabled())
                 .build()
                 .context("failed to build window")?;
+            window.on_window_event(move |event| {
+                if let tauri::WindowEvent::Focused(true) = event
+                    && let Some(focus) = pending_focus.take(std::time::Instant::now())
+                {
+                    focus.request();
+                }
+            });
 
             if auto_update_enabled() {
                 let handle = app.handle().clone();
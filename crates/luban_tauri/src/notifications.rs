@@ -0,0 +1,52 @@
+use luban_server::desktop_notify::{DesktopNotification, NotificationPresenter, TaskFocus};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri_plugin_notification::NotificationExt as _;
+
+// The notification plugin does not report clicks on desktop; clicking a notification activates
+// the app instead, so the window regaining focus soon after one is shown stands in for the click.
+const CLICK_FOCUS_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Shows server notifications through the Tauri notification plugin.
+pub(crate) struct TauriNotifications {
+    app: tauri::AppHandle,
+    pending: Arc<PendingFocus>,
+}
+
+impl TauriNotifications {
+    pub(crate) fn new(app: tauri::AppHandle, pending: Arc<PendingFocus>) -> Self {
+        Self { app, pending }
+    }
+}
+
+impl NotificationPresenter for TauriNotifications {
+    fn show(&self, notification: &DesktopNotification, focus: TaskFocus) {
+        self.pending.set(focus, Instant::now());
+        if let Err(err) = self
+            .app
+            .notification()
+            .builder()
+            .title(&notification.title)
+            .body(&notification.body)
+            .show()
+        {
+            eprintln!("notification: failed to show: {err}");
+        }
+    }
+}
+
+/// The task of the most recent notification, opened when the main window is focused.
+#[derive(Default)]
+pub(crate) struct PendingFocus(Mutex<Option<(TaskFocus, Instant)>>);
+
+impl PendingFocus {
+    fn set(&self, focus: TaskFocus, shown_at: Instant) {
+        *self.0.lock().expect("pending focus lock poisoned") = Some((focus, shown_at));
+    }
+
+    /// Takes the pending task if its notification is recent enough to have been clicked.
+    pub(crate) fn take(&self, now: Instant) -> Option<TaskFocus> {
+        let (focus, shown_at) = self.0.lock().expect("pending focus lock poisoned").take()?;
+        (now.saturating_duration_since(shown_at) <= CLICK_FOCUS_WINDOW).then_some(focus)
+    }
+}
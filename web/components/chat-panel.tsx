@@ -13,7 +13,7 @@ import { useLuban } from "@/lib/luban-context"
 import { buildAgentActivities, buildMessages, type Message } from "@/lib/conversation-ui"
 import { ConversationView } from "@/components/conversation-view"
 import { VirtualizedConversationView } from "@/components/virtualized-conversation-view"
-import { fetchCodexCustomPrompts, fetchWorkspaceDiff, uploadAttachment } from "@/lib/luban-http"
+import { attachUrl, fetchCodexCustomPrompts, fetchWorkspaceDiff, uploadAttachment } from "@/lib/luban-http"
 import type {
   AttachmentRef,
   CodexCustomPromptSnapshot,
@@ -35,7 +35,7 @@ import { openSettingsPanel } from "@/lib/open-settings"
 import { focusChatInput } from "@/lib/focus-chat-input"
 import { useAgentCancelHotkey } from "@/lib/use-agent-cancel-hotkey"
 import { useThreadTabs, type ArchivedTab } from "@/lib/use-thread-tabs"
-import { DiffTabPanel, type DiffFileData, type DiffStyle } from "@/components/diff-tab-panel"
+import { DiffTabPanel, useTaskDiffReview, type DiffFileData, type DiffStyle } from "@/components/diff-tab-panel"
 import { QueuedPromptRow } from "@/components/queued-prompts"
 import { EscCancelHint } from "@/components/esc-cancel-hint"
 import { ChatComposer } from "@/components/chat-composer"
@@ -49,6 +49,21 @@ type PersistedChatDraft = {
   attachments?: AttachmentRef[]
 }
 
+// A paste that is a single http(s) URL on an allowlisted host is also attached as a page snapshot.
+function pastedAttachableUrl(text: string, allowedDomains: string[]): string | null {
+  const trimmed = text.trim()
+  if (!trimmed || /\s/.test(trimmed) || allowedDomains.length === 0) return null
+  let parsed: URL
+  try {
+    parsed = new URL(trimmed)
+  } catch {
+    return null
+  }
+  if (parsed.protocol !== "http:" && parsed.protocol !== "https:") return null
+  const host = parsed.hostname.toLowerCase()
+  return allowedDomains.some((d) => host === d || host.endsWith(`.${d}`)) ? trimmed : null
+}
+
 export function ChatPanel({
   pendingDiffFile,
   onDiffFileOpened,
@@ -123,7 +138,7 @@ export function ChatPanel({
           isImage && workspaceId != null ? attachmentHref({ workspaceId, attachment }) ?? undefined : undefined
         return {
           id: `ref-${attachment.id}`,
-          type: isImage ? "image" : "file",
+          type: isImage ? "image" : attachment.kind === "url" ? "url" : "file",
           name: attachment.name,
           size: attachment.byte_len,
           status: "ready",
@@ -135,6 +150,7 @@ export function ChatPanel({
     [],
   )
 
+  const diffReview = useTaskDiffReview()
   const queuedPrompts = useMemo(() => conversation?.pending_prompts ?? [], [conversation?.pending_prompts])
   const queuePaused = conversation?.queue_paused ?? false
   const [editingQueuedPromptId, setEditingQueuedPromptId] = useState<number | null>(null)
@@ -961,13 +977,41 @@ export function ChatPanel({
     }
   }, [activeThreadId, activeWorkspaceId])
 
+  const handleUrlPaste = (text: string) => {
+    if (activeWorkspaceId == null || activeThreadId == null) return
+    const url = pastedAttachableUrl(text, app?.url_attachments?.allowed_domains ?? [])
+    if (!url) return
+    if (attachments.some((a) => a.type === "url" && a.name === url)) return
+
+    const scopeAtStart = attachmentScopeRef.current
+    const id = `${Date.now()}-${Math.random().toString(36).slice(2)}`
+    setAttachments((prev) => [...prev, { id, type: "url", name: url, size: 0, status: "uploading" }])
+
+    void attachUrl({ workspaceId: activeWorkspaceId, url })
+      .then((attachment) => {
+        if (attachmentScopeRef.current !== scopeAtStart) return
+        setAttachments((prev) =>
+          prev.map((a) =>
+            a.id === id ? { ...a, status: "ready", attachment, name: attachment.name, size: attachment.byte_len } : a,
+          ),
+        )
+      })
+      .catch(() => {
+        if (attachmentScopeRef.current !== scopeAtStart) return
+        setAttachments((prev) => prev.map((a) => (a.id === id ? { ...a, status: "failed" } : a)))
+      })
+  }
+
   const handlePaste = (e: React.ClipboardEvent) => {
     if (activeWorkspaceId == null || activeThreadId == null) return
     const items = e.clipboardData?.items
     if (!items) return
 
     const imageItems = Array.from(items).filter((item) => item.type.startsWith("image/"))
-    if (imageItems.length === 0) return
+    if (imageItems.length === 0) {
+      handleUrlPaste(e.clipboardData.getData("text/plain"))
+      return
+    }
 
     e.preventDefault()
     const dt = new DataTransfer()
@@ -1104,6 +1148,7 @@ export function ChatPanel({
             activeFileId={diffActiveFileId}
             diffStyle={diffStyle}
             onStyleChange={setDiffStyle}
+            review={diffReview}
           />
         </div>
       ) : (
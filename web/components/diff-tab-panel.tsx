@@ -11,7 +11,13 @@ import {
   type WorkerInitializationRenderOptions,
   type WorkerPoolOptions,
 } from "@pierre/diffs/react"
-import type { ChangedFileSnapshot } from "@/lib/luban-api"
+import type {
+  ChangedFileSnapshot,
+  CiAutoFixSnapshot,
+  PullRequestReviewSyncSnapshot,
+  ReviewCommentSnapshot,
+} from "@/lib/luban-api"
+import { useLuban } from "@/lib/luban-context"
 
 export type DiffStyle = "split" | "unified"
 
@@ -23,6 +29,61 @@ export interface DiffFileData {
   newFile: FileContents
 }
 
+/** Inline review comments on the active task's changes; omitted where the diff has no task. */
+export interface DiffReview {
+  comments: ReviewCommentSnapshot[]
+  onAdd: (comment: { path: string; startLine: number; endLine: number; quote: string; body: string }) => void
+  onEdit: (commentId: number, body: string) => void
+  onResolve: (commentId: number, resolved: boolean) => void
+  onSend: (note: string | null) => void
+  /** How the task follows review comments left on its pull request. */
+  pullRequestSync: PullRequestReviewSyncSnapshot
+  onPullRequestPull: () => void
+  onPullRequestSyncChange: (sync: PullRequestReviewSyncSnapshot) => void
+  /** Whether failed pull request CI queues "fix CI" turns, and how many are left. */
+  ciAutoFix: CiAutoFixSnapshot
+  onCiAutoFixChange: (enabled: boolean, maxAttempts: number) => void
+}
+
+export function useTaskDiffReview(): DiffReview | undefined {
+  const {
+    conversation,
+    addReviewComment,
+    editReviewComment,
+    resolveReviewComment,
+    sendReviewComments,
+    pullPullRequestReview,
+    setPullRequestReviewSync,
+    setCiAutoFix,
+  } = useLuban()
+  return useMemo(() => {
+    if (!conversation) return undefined
+    const workdirId = conversation.workdir_id
+    const taskId = conversation.task_id
+    return {
+      comments: conversation.review_comments ?? [],
+      onAdd: (comment) => addReviewComment(workdirId, taskId, comment),
+      onEdit: (commentId, body) => editReviewComment(workdirId, taskId, commentId, body),
+      onResolve: (commentId, resolved) => resolveReviewComment(workdirId, taskId, commentId, resolved),
+      onSend: (note) => sendReviewComments(workdirId, taskId, note),
+      pullRequestSync: conversation.pull_request_review_sync ?? { auto_pull: false, reply_when_addressed: false },
+      onPullRequestPull: () => pullPullRequestReview(workdirId, taskId),
+      onPullRequestSyncChange: (sync) => setPullRequestReviewSync(workdirId, taskId, sync),
+      ciAutoFix: conversation.ci_auto_fix ?? { enabled: false, max_attempts: 3, attempts_used: 0 },
+      onCiAutoFixChange: (enabled, maxAttempts) => setCiAutoFix(workdirId, taskId, enabled, maxAttempts),
+    }
+  }, [
+    conversation,
+    addReviewComment,
+    editReviewComment,
+    resolveReviewComment,
+    sendReviewComments,
+    pullPullRequestReview,
+    setPullRequestReviewSync,
+    setCiAutoFix,
+  ])
+}
+
 export function DiffTabPanel({
   isLoading,
   error,
@@ -30,6 +91,7 @@ export function DiffTabPanel({
   activeFileId,
   diffStyle,
   onStyleChange,
+  review,
 }: {
   isLoading: boolean
   error: string | null
@@ -37,6 +99,7 @@ export function DiffTabPanel({
   activeFileId?: string
   diffStyle: DiffStyle
   onStyleChange: (style: DiffStyle) => void
+  review?: DiffReview
 }) {
   if (isLoading) {
     return <div className="px-4 py-3 text-xs text-muted-foreground">Loading…</div>
@@ -62,6 +125,7 @@ export function DiffTabPanel({
         activeFileId={activeFileId}
         diffStyle={diffStyle}
         onStyleChange={onStyleChange}
+        review={review}
       />
     </WorkerPoolContextProvider>
   )
@@ -79,11 +143,13 @@ function AllFilesDiffViewer({
   activeFileId,
   diffStyle,
   onStyleChange,
+  review,
 }: {
   files: DiffFileData[]
   activeFileId?: string
   diffStyle: DiffStyle
   onStyleChange: (style: DiffStyle) => void
+  review?: DiffReview
 }) {
   const fileRefs = useRef<Record<string, HTMLDivElement | null>>({})
   const prevActiveFileIdRef = useRef<string | undefined>(undefined)
@@ -272,7 +338,8 @@ function AllFilesDiffViewer({
           {totalAdditions > 0 && totalDeletions > 0 && <span className="mx-1">/</span>}
           {totalDeletions > 0 && <span className="text-status-error">-{totalDeletions}</span>}
         </span>
-        <div className="ml-auto flex items-center gap-0.5 p-0.5 bg-muted rounded">
+        {review && <SendReviewControls review={review} />}
+        <div className={cn(review ? "ml-2" : "ml-auto", "flex items-center gap-0.5 p-0.5 bg-muted rounded")}>
           <button
             onClick={() => onStyleChange("split")}
             className={cn(
@@ -346,6 +413,7 @@ function AllFilesDiffViewer({
 
               {!isCollapsed && (
                 <>
+                  {review && <FileReviewComments review={review} fileData={fileData} />}
                   {!isRendered ? (
                     <div className="px-4 py-3 text-xs text-muted-foreground">Rendering…</div>
                   ) : (
@@ -371,3 +439,236 @@ function AllFilesDiffViewer({
     </div>
   )
 }
+
+function reviewLineLabel(comment: { start_line: number; end_line: number }): string {
+  if (comment.start_line === 0) return "file"
+  return comment.start_line === comment.end_line ? `L${comment.start_line}` : `L${comment.start_line}-${comment.end_line}`
+}
+
+function SendReviewControls({ review }: { review: DiffReview }) {
+  const [note, setNote] = useState("")
+  const pending = review.comments.filter((c) => !c.sent && !c.resolved).length
+  const sync = review.pullRequestSync
+  const ci = review.ciAutoFix
+  return (
+    <div className="ml-auto flex items-center gap-1.5">
+      <div className="flex items-center gap-2 text-muted-foreground" data-testid="review-pull-request">
+        <button type="button" data-testid="review-pull-request-pull" className="underline" onClick={review.onPullRequestPull}>
+          Pull PR review
+        </button>
+        <label className="flex items-center gap-1" title="Import new review comments whenever the pull request refreshes">
+          <input
+            type="checkbox"
+            data-testid="review-pull-request-auto"
+            checked={sync.auto_pull}
+            onChange={(e) => review.onPullRequestSyncChange({ ...sync, auto_pull: e.target.checked })}
+          />
+          Auto
+        </label>
+        <label className="flex items-center gap-1" title="Reply on the pull request when an imported comment is resolved">
+          <input
+            type="checkbox"
+            data-testid="review-pull-request-reply"
+            checked={sync.reply_when_addressed}
+            onChange={(e) => review.onPullRequestSyncChange({ ...sync, reply_when_addressed: e.target.checked })}
+          />
+          Reply when resolved
+        </label>
+        <label
+          className="flex items-center gap-1"
+          title="When pull request CI fails, send the failed job log to the agent and ask it to fix the build"
+        >
+          <input
+            type="checkbox"
+            data-testid="ci-auto-fix-enabled"
+            checked={ci.enabled}
+            onChange={(e) => review.onCiAutoFixChange(e.target.checked, ci.max_attempts)}
+          />
+          Fix CI
+        </label>
+        <select
+          data-testid="ci-auto-fix-max-attempts"
+          value={ci.max_attempts}
+          onChange={(e) => review.onCiAutoFixChange(ci.enabled, Number(e.target.value))}
+          className="h-6 rounded border border-border bg-background text-xs"
+          title="Attempt budget"
+        >
+          {Array.from({ length: 10 }, (_, i) => i + 1).map((n) => (
+            <option key={n} value={n}>
+              {n}×
+            </option>
+          ))}
+        </select>
+        {ci.enabled && ci.attempts_used > 0 && (
+          <span data-testid="ci-auto-fix-attempts">
+            {ci.attempts_used}/{ci.max_attempts} used
+          </span>
+        )}
+      </div>
+      {pending > 0 && (
+        <>
+          <input
+            data-testid="review-send-note"
+            value={note}
+            onChange={(e) => setNote(e.target.value)}
+            placeholder="Note to the agent (optional)"
+            className="h-6 w-56 px-2 rounded border border-border bg-background text-xs"
+          />
+          <button
+            type="button"
+            data-testid="review-send"
+            className="h-6 px-2 rounded bg-primary text-primary-foreground text-xs"
+            onClick={() => {
+              review.onSend(note.trim() || null)
+              setNote("")
+            }}
+          >
+            Send {pending} comment{pending === 1 ? "" : "s"} to agent
+          </button>
+        </>
+      )}
+    </div>
+  )
+}
+
+function FileReviewComments({ review, fileData }: { review: DiffReview; fileData: DiffFileData }) {
+  const path = fileData.file.path
+  const comments = review.comments.filter((c) => c.path === path)
+  const [adding, setAdding] = useState(false)
+  const [startLine, setStartLine] = useState("")
+  const [endLine, setEndLine] = useState("")
+  const [body, setBody] = useState("")
+  const [editingId, setEditingId] = useState<number | null>(null)
+  const [editBody, setEditBody] = useState("")
+
+  const submit = () => {
+    const start = Number.parseInt(startLine, 10)
+    const end = endLine.trim() ? Number.parseInt(endLine, 10) : start
+    if (!Number.isFinite(start) || !Number.isFinite(end) || start < 1 || end < start || !body.trim()) return
+    const quote = (fileData.newFile.contents ?? "").split("\n").slice(start - 1, end).join("\n")
+    review.onAdd({ path, startLine: start, endLine: end, quote, body })
+    setAdding(false)
+    setStartLine("")
+    setEndLine("")
+    setBody("")
+  }
+
+  return (
+    <div className="px-4 py-1.5 border-b border-border/50 text-xs" data-testid="review-comments">
+      {comments.map((comment) => (
+        <div
+          key={comment.id}
+          data-testid="review-comment"
+          className={cn("py-1.5 border-b border-border/30 last:border-b-0", comment.resolved && "opacity-60")}
+        >
+          <div className="flex items-center gap-2 text-muted-foreground">
+            <span className="font-mono">{reviewLineLabel(comment)}</span>
+            {comment.pull_request && (
+              <a
+                href={comment.pull_request.url || undefined}
+                target="_blank"
+                rel="noreferrer"
+                data-testid="review-comment-origin"
+                className="text-foreground"
+              >
+                {comment.pull_request.author} on PR #{comment.pull_request.pr_number}
+              </a>
+            )}
+            {comment.resolved ? <span>resolved</span> : comment.sent ? <span>sent</span> : <span>pending</span>}
+            {comment.pull_request?.replied && <span>replied</span>}
+            <span className="ml-auto flex items-center gap-2">
+              <button
+                type="button"
+                className="underline"
+                onClick={() => {
+                  setEditingId(comment.id)
+                  setEditBody(comment.body)
+                }}
+              >
+                Edit
+              </button>
+              <button type="button" className="underline" onClick={() => review.onResolve(comment.id, !comment.resolved)}>
+                {comment.resolved ? "Reopen" : "Resolve"}
+              </button>
+            </span>
+          </div>
+          {comment.quote && (
+            <pre className="mt-1 px-2 py-1 bg-muted rounded font-mono whitespace-pre-wrap max-h-24 overflow-hidden">
+              {comment.quote}
+            </pre>
+          )}
+          {editingId === comment.id ? (
+            <div className="mt-1 flex flex-col gap-1">
+              <textarea
+                value={editBody}
+                onChange={(e) => setEditBody(e.target.value)}
+                className="w-full min-h-16 px-2 py-1 rounded border border-border bg-background"
+              />
+              <div className="flex gap-2">
+                <button
+                  type="button"
+                  className="underline"
+                  onClick={() => {
+                    review.onEdit(comment.id, editBody)
+                    setEditingId(null)
+                  }}
+                >
+                  Save
+                </button>
+                <button type="button" className="underline text-muted-foreground" onClick={() => setEditingId(null)}>
+                  Cancel
+                </button>
+              </div>
+            </div>
+          ) : (
+            <div className="mt-1 whitespace-pre-wrap text-foreground">{comment.body}</div>
+          )}
+        </div>
+      ))}
+      {adding ? (
+        <div className="py-1.5 flex flex-col gap-1" data-testid="review-comment-form">
+          <div className="flex items-center gap-2 text-muted-foreground">
+            <span>Lines</span>
+            <input
+              value={startLine}
+              onChange={(e) => setStartLine(e.target.value)}
+              inputMode="numeric"
+              placeholder="from"
+              className="h-6 w-16 px-2 rounded border border-border bg-background"
+            />
+            <input
+              value={endLine}
+              onChange={(e) => setEndLine(e.target.value)}
+              inputMode="numeric"
+              placeholder="to"
+              className="h-6 w-16 px-2 rounded border border-border bg-background"
+            />
+          </div>
+          <textarea
+            value={body}
+            onChange={(e) => setBody(e.target.value)}
+            placeholder="Leave a comment for the agent"
+            className="w-full min-h-16 px-2 py-1 rounded border border-border bg-background"
+          />
+          <div className="flex gap-2">
+            <button type="button" data-testid="review-comment-submit" className="underline" onClick={submit}>
+              Add comment
+            </button>
+            <button type="button" className="underline text-muted-foreground" onClick={() => setAdding(false)}>
+              Cancel
+            </button>
+          </div>
+        </div>
+      ) : (
+        <button
+          type="button"
+          data-testid="review-comment-add"
+          className="py-1 text-muted-foreground underline"
+          onClick={() => setAdding(true)}
+        >
+          Comment on lines
+        </button>
+      )}
+    </div>
+  )
+}
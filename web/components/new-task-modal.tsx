@@ -16,6 +16,7 @@ import { useLuban } from "@/lib/luban-context"
 import type { AgentRunnerKind, AttachmentRef, TaskExecuteMode, ThinkingEffort } from "@/lib/luban-api"
 import { draftKey } from "@/lib/ui-prefs"
 import { focusChatInput } from "@/lib/focus-chat-input"
+import { issueReferenceFromInput } from "@/lib/task-ui"
 import { uploadAttachment } from "@/lib/luban-http"
 import { isMockMode } from "@/lib/luban-mode"
 import type { NewTaskDraft } from "@/lib/new-task-drafts"
@@ -80,6 +81,7 @@ export function NewTaskModal({ open, onOpenChange, onTaskCreated, activeProjectI
   const {
     app,
     executeTask,
+    importIssueTask,
     openWorkdir,
     activateTask,
     activeWorkdirId,
@@ -514,6 +516,24 @@ export function NewTaskModal({ open, onOpenChange, onTaskCreated, activeProjectI
     try {
       const trimmed = input.trim()
 
+      const issueRef = isGitProject ? issueReferenceFromInput(trimmed) : null
+      if (issueRef) {
+        const result = await importIssueTask(selectedProject.id, issueRef, mode)
+        await openWorkdir(result.workdir_id)
+        await activateTask(result.task_id)
+        focusChatInput()
+        toast(mode === "create" ? "Issue imported" : "Issue imported and started")
+
+        setInput("")
+        revokeAttachmentUrls(attachments)
+        setAttachments([])
+        setEditingDraftId(null)
+        void clearNewTaskStash().catch((err) => console.warn("clearNewTaskStash failed", err))
+        onTaskCreated?.()
+        onOpenChange(false)
+        return
+      }
+
       const workdirId = await (async (): Promise<number> => {
         if (!isGitProject) {
           const main = selectedProject.workdirs.find(
@@ -1007,7 +1027,7 @@ export function NewTaskModal({ open, onOpenChange, onTaskCreated, activeProjectI
             }}
             onKeyDown={handleKeyDown}
             onPaste={handlePaste}
-            placeholder="Add task description..."
+            placeholder="Add task description, or paste an issue link or #number..."
             className="w-full text-[15px] resize-none focus:outline-none"
             style={{
               minHeight: "80px",
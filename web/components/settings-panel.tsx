@@ -6,6 +6,7 @@ import { useCallback, useEffect, useMemo, useRef, useState } from "react"
 import { useTheme } from "next-themes"
 import {
   ArrowLeft,
+  Bell,
   Check,
   ChevronDown,
   ChevronRight,
@@ -23,6 +24,7 @@ import {
   FileCode,
   FileText,
   Folder,
+  HardDrive,
   Loader2,
   Monitor,
   Moon,
@@ -52,12 +54,17 @@ import type {
   AgentRunnerKind,
   AmpConfigEntrySnapshot,
   AppearanceTheme,
+  AuditEntrySnapshot,
   ClaudeConfigEntrySnapshot,
   CodexConfigEntrySnapshot,
+  QuietHoursSnapshot,
   SystemTaskKind,
   TaskIntentKind,
+  TelegramGroupChatSnapshot,
+  TurnLimitsSnapshot,
 } from "@/lib/luban-api"
 import { addProjectAndOpen } from "@/lib/add-project-and-open"
+import { fetchAuditLog } from "@/lib/luban-http"
 
 interface SettingsPanelProps {
   open: boolean
@@ -94,6 +101,16 @@ const tocItems: TocItem[] = [
     label: "Task",
     icon: ListTodo,
   },
+  {
+    id: "notifications",
+    label: "Notifications",
+    icon: Bell,
+  },
+  {
+    id: "storage",
+    label: "Storage",
+    icon: HardDrive,
+  },
   {
     id: "integrations",
     label: "Integrations",
@@ -1597,6 +1614,9 @@ function AllSettings({
           Agent
         </h3>
         <AgentConfigPanel initialAgentId={initialAgentId} initialAgentFilePath={initialAgentFilePath} />
+        <div className="mt-4">
+          <TurnLimitsCard />
+        </div>
       </section>
 
       <section id="task" className="scroll-mt-8">
@@ -1614,6 +1634,22 @@ function AllSettings({
         />
       </section>
 
+      <section id="notifications" className="scroll-mt-8">
+        <h3 className="text-sm font-medium mb-4 flex items-center gap-2">
+          <Bell className="w-4 h-4 text-muted-foreground" />
+          Notifications
+        </h3>
+        <NotificationsPanel />
+      </section>
+
+      <section id="storage" className="scroll-mt-8">
+        <h3 className="text-sm font-medium mb-4 flex items-center gap-2">
+          <HardDrive className="w-4 h-4 text-muted-foreground" />
+          Storage
+        </h3>
+        <StoragePanel />
+      </section>
+
       <section id="telegram" className="scroll-mt-8">
         <h3 className="text-sm font-medium mb-4 flex items-center gap-2">
           <MessageSquare className="w-4 h-4 text-muted-foreground" />
@@ -1625,6 +1661,598 @@ function AllSettings({
   )
 }
 
+function minuteToTimeInput(minute: number): string {
+  const h = Math.floor(minute / 60)
+  const m = minute % 60
+  return `${String(h).padStart(2, "0")}:${String(m).padStart(2, "0")}`
+}
+
+function timeInputToMinute(value: string): number | null {
+  const match = /^(\d{2}):(\d{2})$/.exec(value)
+  if (!match) return null
+  const minute = Number(match[1]) * 60 + Number(match[2])
+  return minute < 24 * 60 ? minute : null
+}
+
+function NotificationsPanel() {
+  const { app, setDesktopNotificationsEnabled, setNotificationQuietHours, setProjectNotificationsMuted } = useLuban()
+  const notifications = app?.notifications ?? null
+  const enabled = notifications?.desktop_enabled ?? false
+  const quietHours = notifications?.quiet_hours ?? null
+  const projects = app?.projects ?? []
+
+  const updateQuietHours = (patch: Partial<QuietHoursSnapshot>) => {
+    const base: QuietHoursSnapshot = quietHours ?? { start_minute: 22 * 60, end_minute: 8 * 60, utc_offset_minutes: 0 }
+    setNotificationQuietHours({
+      ...base,
+      ...patch,
+      // Quiet hours are entered in local time; record the current offset alongside them.
+      utc_offset_minutes: -new Date().getTimezoneOffset(),
+    })
+  }
+
+  return (
+    <div className="space-y-4">
+      <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+        <label className="flex items-center justify-between gap-3">
+          <div>
+            <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+              Desktop notifications
+            </div>
+            <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+              Notify when a turn finishes or fails, queued prompts finish, or pull request CI fails. Clicking a
+              notification opens the task.
+            </div>
+          </div>
+          <input
+            type="checkbox"
+            data-testid="desktop-notifications-enabled"
+            checked={enabled}
+            onChange={(e) => setDesktopNotificationsEnabled(e.target.checked)}
+          />
+        </label>
+      </div>
+
+      <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+        <label className="flex items-center justify-between gap-3">
+          <div>
+            <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+              Do not disturb
+            </div>
+            <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+              Notifications during these hours are skipped.
+            </div>
+          </div>
+          <input
+            type="checkbox"
+            data-testid="notification-quiet-hours-enabled"
+            checked={quietHours != null}
+            onChange={(e) => (e.target.checked ? updateQuietHours({}) : setNotificationQuietHours(null))}
+          />
+        </label>
+        {quietHours ? (
+          <div className="flex items-center gap-2 text-[13px]" style={{ color: "#1b1b1b" }}>
+            <input
+              type="time"
+              data-testid="notification-quiet-hours-start"
+              value={minuteToTimeInput(quietHours.start_minute)}
+              onChange={(e) => {
+                const minute = timeInputToMinute(e.target.value)
+                if (minute != null) updateQuietHours({ start_minute: minute })
+              }}
+              className="px-2 py-1 rounded border outline-none"
+              style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff" }}
+            />
+            <span style={{ color: "#6b6b6b" }}>to</span>
+            <input
+              type="time"
+              data-testid="notification-quiet-hours-end"
+              value={minuteToTimeInput(quietHours.end_minute)}
+              onChange={(e) => {
+                const minute = timeInputToMinute(e.target.value)
+                if (minute != null) updateQuietHours({ end_minute: minute })
+              }}
+              className="px-2 py-1 rounded border outline-none"
+              style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff" }}
+            />
+          </div>
+        ) : null}
+      </div>
+
+      {projects.length > 0 ? (
+        <div className="rounded border p-3 space-y-2" style={{ borderColor: "#ebebeb" }}>
+          <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+            Projects
+          </div>
+          {projects.map((project) => (
+            <label key={project.id} className="flex items-center justify-between gap-3 text-[13px]">
+              <span className="truncate" style={{ color: "#1b1b1b" }}>
+                {project.name}
+              </span>
+              <input
+                type="checkbox"
+                data-testid="project-notifications-enabled"
+                checked={!project.notifications_muted}
+                onChange={(e) => setProjectNotificationsMuted(project.id, !e.target.checked)}
+              />
+            </label>
+          ))}
+        </div>
+      ) : null}
+    </div>
+  )
+}
+
+function formatByteSize(bytes: number): string {
+  const units = ["B", "KB", "MB", "GB", "TB"]
+  let value = bytes
+  let unit = 0
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024
+    unit += 1
+  }
+  return unit === 0 ? `${bytes} B` : `${value.toFixed(1)} ${units[unit]}`
+}
+
+const DEFAULT_BUILD_ARTIFACT_GLOBS = "target, node_modules, .next, .turbo, __pycache__"
+
+function StoragePanel() {
+  const { app, refreshDiskUsage, purgeBuildArtifacts, deleteStaleWorktrees, gcContextBlobs } = useLuban()
+  const [globs, setGlobs] = useState("")
+  const [olderThanDays, setOlderThanDays] = useState("14")
+
+  const rows = useMemo(() => {
+    const out: { id: number; project: string; name: string; workdirBytes: number; blobsBytes: number }[] = []
+    for (const project of app?.projects ?? []) {
+      for (const workdir of project.workdirs) {
+        if (!workdir.disk_usage) continue
+        out.push({
+          id: workdir.id,
+          project: project.name,
+          name: workdir.workdir_name,
+          workdirBytes: workdir.disk_usage.workdir_bytes,
+          blobsBytes: workdir.disk_usage.context_blobs_bytes,
+        })
+      }
+    }
+    out.sort((a, b) => b.workdirBytes + b.blobsBytes - (a.workdirBytes + a.blobsBytes))
+    return out
+  }, [app?.projects])
+
+  const total = rows.reduce((sum, row) => sum + row.workdirBytes + row.blobsBytes, 0)
+  const parsedGlobs = globs
+    .split(",")
+    .map((g) => g.trim())
+    .filter((g) => g.length > 0)
+  const days = Number(olderThanDays)
+
+  return (
+    <div className="space-y-4">
+      <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+        <div className="flex items-center justify-between gap-3">
+          <div>
+            <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+              Disk usage
+            </div>
+            <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+              {rows.length > 0
+                ? `${formatByteSize(total)} across ${rows.length} workdirs. Measured in the background.`
+                : "Not measured yet."}
+            </div>
+          </div>
+          <button
+            data-testid="disk-usage-refresh"
+            onClick={() => refreshDiskUsage()}
+            className="p-2 rounded transition-colors hover:bg-[#eeeeee]"
+            title="Measure now"
+          >
+            <RefreshCw className="w-4 h-4" style={{ color: "#6b6b6b" }} />
+          </button>
+        </div>
+        {rows.length > 0 ? (
+          <div className="space-y-1 max-h-[240px] overflow-y-auto">
+            {rows.map((row) => (
+              <div
+                key={row.id}
+                data-testid="disk-usage-row"
+                className="flex items-center justify-between gap-3 text-[13px]"
+              >
+                <span className="truncate" style={{ color: "#1b1b1b" }}>
+                  {row.project} / {row.name}
+                </span>
+                <span className="shrink-0 flex items-center gap-2" style={{ color: "#6b6b6b" }}>
+                  <span>{formatByteSize(row.workdirBytes)}</span>
+                  {row.blobsBytes > 0 ? <span>+ {formatByteSize(row.blobsBytes)} context</span> : null}
+                  <button
+                    data-testid="disk-usage-purge-workdir"
+                    onClick={() => purgeBuildArtifacts(row.id, parsedGlobs)}
+                    className="px-2 py-0.5 rounded text-[12px] transition-colors"
+                    style={{ backgroundColor: "#eeeeee", color: "#1b1b1b" }}
+                  >
+                    Purge
+                  </button>
+                </span>
+              </div>
+            ))}
+          </div>
+        ) : null}
+      </div>
+
+      <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+        <div>
+          <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+            Build artifacts
+          </div>
+          <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+            Delete matching files and folders from every workdir except main checkouts. Names match at any depth;
+            patterns with a slash match from the workdir root.
+          </div>
+        </div>
+        <div className="flex items-center gap-2">
+          <input
+            data-testid="build-artifacts-globs"
+            value={globs}
+            onChange={(e) => setGlobs(e.target.value)}
+            placeholder={DEFAULT_BUILD_ARTIFACT_GLOBS}
+            className="flex-1 px-3 py-2 rounded text-[13px] border outline-none"
+            style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff", color: "#1b1b1b" }}
+          />
+          <button
+            data-testid="build-artifacts-purge"
+            onClick={() => purgeBuildArtifacts(null, parsedGlobs)}
+            className="px-3 py-2 rounded text-[13px] transition-colors"
+            style={{ backgroundColor: "#5e6ad2", color: "#ffffff" }}
+          >
+            Purge all
+          </button>
+        </div>
+      </div>
+
+      <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+        <div>
+          <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+            Stale worktrees
+          </div>
+          <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+            Remove leftover worktrees of workdirs archived longer ago than this.
+          </div>
+        </div>
+        <div className="flex items-center gap-2 text-[13px]" style={{ color: "#1b1b1b" }}>
+          <span style={{ color: "#6b6b6b" }}>Archived more than</span>
+          <input
+            type="number"
+            min={0}
+            data-testid="stale-worktrees-days"
+            value={olderThanDays}
+            onChange={(e) => setOlderThanDays(e.target.value)}
+            className="w-20 px-2 py-1 rounded border outline-none"
+            style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff" }}
+          />
+          <span style={{ color: "#6b6b6b" }}>days</span>
+          <button
+            data-testid="stale-worktrees-delete"
+            onClick={() => deleteStaleWorktrees(days)}
+            disabled={!Number.isFinite(days) || days < 0 || olderThanDays.trim() === ""}
+            className="ml-auto px-3 py-2 rounded text-[13px] transition-colors disabled:opacity-50"
+            style={{ backgroundColor: "#eeeeee", color: "#1b1b1b" }}
+          >
+            Delete
+          </button>
+        </div>
+      </div>
+
+      <div className="rounded border p-3" style={{ borderColor: "#ebebeb" }}>
+        <div className="flex items-center justify-between gap-3">
+          <div>
+            <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+              Context files
+            </div>
+            <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+              Delete uploaded files and images that no message, queued prompt or context item references.
+            </div>
+          </div>
+          <button
+            data-testid="context-blobs-gc"
+            onClick={() => gcContextBlobs()}
+            className="px-3 py-2 rounded text-[13px] transition-colors"
+            style={{ backgroundColor: "#eeeeee", color: "#1b1b1b" }}
+          >
+            Clean up
+          </button>
+        </div>
+      </div>
+
+      <AttachmentTextLimitsCard />
+      <UrlAttachmentDomainsCard />
+      <HistoryCard />
+    </div>
+  )
+}
+
+const TURN_LIMIT_FIELDS: { key: keyof TurnLimitsSnapshot; label: string; testId: string }[] = [
+  { key: "turn_timeout_secs", label: "Timeout (s)", testId: "turn-limit-timeout" },
+  { key: "max_tool_calls_per_turn", label: "Tool calls / turn", testId: "turn-limit-tool-calls" },
+  { key: "turn_token_budget", label: "Token budget / turn", testId: "turn-limit-turn-tokens" },
+  { key: "task_token_budget", label: "Token budget / task", testId: "turn-limit-task-tokens" },
+]
+
+function ProjectTurnLimitsRow({ name, limits, onChange }: {
+  name: string
+  limits: TurnLimitsSnapshot
+  onChange: (limits: TurnLimitsSnapshot) => void
+}) {
+  const toDrafts = (l: TurnLimitsSnapshot) =>
+    Object.fromEntries(TURN_LIMIT_FIELDS.map((f) => [f.key, l[f.key] != null ? String(l[f.key]) : ""]))
+  const [drafts, setDrafts] = useState<Record<string, string>>(() => toDrafts(limits))
+
+  useEffect(() => {
+    setDrafts(toDrafts(limits))
+  }, [limits.turn_timeout_secs, limits.max_tool_calls_per_turn, limits.turn_token_budget, limits.task_token_budget])
+
+  const commit = () => {
+    const next: TurnLimitsSnapshot = {}
+    for (const field of TURN_LIMIT_FIELDS) {
+      const raw = (drafts[field.key] ?? "").trim()
+      if (raw === "") continue
+      const value = Math.floor(Number(raw))
+      if (!Number.isFinite(value) || value <= 0) {
+        setDrafts(toDrafts(limits))
+        return
+      }
+      next[field.key] = value
+    }
+    if (TURN_LIMIT_FIELDS.every((f) => (next[f.key] ?? null) === (limits[f.key] ?? null))) return
+    onChange(next)
+  }
+
+  return (
+    <div className="space-y-1">
+      <div className="text-[13px] truncate" style={{ color: "#1b1b1b" }}>
+        {name}
+      </div>
+      <div className="grid grid-cols-4 gap-2">
+        {TURN_LIMIT_FIELDS.map((field) => (
+          <label key={field.key} className="flex flex-col gap-1 text-[11px]" style={{ color: "#6b6b6b" }}>
+            {field.label}
+            <input
+              type="number"
+              min={1}
+              placeholder="No limit"
+              data-testid={field.testId}
+              value={drafts[field.key] ?? ""}
+              onChange={(e) => setDrafts((prev) => ({ ...prev, [field.key]: e.target.value }))}
+              onBlur={commit}
+              className="w-full px-2 py-1 rounded border outline-none text-[13px]"
+              style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff", color: "#1b1b1b" }}
+            />
+          </label>
+        ))}
+      </div>
+    </div>
+  )
+}
+
+function TurnLimitsCard() {
+  const { app, setProjectTurnLimits } = useLuban()
+  const projects = app?.projects ?? []
+
+  return (
+    <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+      <div>
+        <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+          Turn limits
+        </div>
+        <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+          Cancel a turn that runs too long or makes too many tool calls, and stop a task once it has spent its token
+          budget. Leave a field empty for no limit.
+        </div>
+      </div>
+      {projects.map((project) => (
+        <ProjectTurnLimitsRow
+          key={project.id}
+          name={project.name}
+          limits={project.turn_limits ?? {}}
+          onChange={(limits) => setProjectTurnLimits(project.id, limits)}
+        />
+      ))}
+    </div>
+  )
+}
+
+function AttachmentTextLimitsCard() {
+  const { app, setAttachmentTextLimits } = useLuban()
+  const limits = app?.attachment_text_limits ?? null
+  const [maxSourceMb, setMaxSourceMb] = useState("")
+  const [maxInlineKb, setMaxInlineKb] = useState("")
+
+  useEffect(() => {
+    if (!limits) return
+    setMaxSourceMb(String(Math.round(limits.max_source_bytes / (1024 * 1024))))
+    setMaxInlineKb(String(Math.round(limits.max_inline_bytes / 1024)))
+  }, [limits?.max_source_bytes, limits?.max_inline_bytes])
+
+  const commit = () => {
+    const sourceMb = Number(maxSourceMb)
+    const inlineKb = Number(maxInlineKb)
+    if (!Number.isFinite(sourceMb) || sourceMb <= 0 || !Number.isFinite(inlineKb) || inlineKb < 0) return
+    setAttachmentTextLimits({
+      max_source_bytes: sourceMb * 1024 * 1024,
+      max_inline_bytes: inlineKb * 1024,
+    })
+  }
+
+  return (
+    <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+      <div>
+        <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+          Document attachments
+        </div>
+        <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+          Text is extracted from attached PDF, DOCX, PPTX, ODT and ODP files and added to the prompt. Longer text is
+          added as an excerpt; set the inline size to 0 to only pass the extracted text file.
+        </div>
+      </div>
+      <div className="flex items-center gap-2 text-[13px]" style={{ color: "#1b1b1b" }}>
+        <span style={{ color: "#6b6b6b" }}>Extract from files up to</span>
+        <input
+          type="number"
+          min={1}
+          data-testid="attachment-text-max-source"
+          value={maxSourceMb}
+          onChange={(e) => setMaxSourceMb(e.target.value)}
+          onBlur={commit}
+          className="w-20 px-2 py-1 rounded border outline-none"
+          style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff" }}
+        />
+        <span style={{ color: "#6b6b6b" }}>MB, inline up to</span>
+        <input
+          type="number"
+          min={0}
+          data-testid="attachment-text-max-inline"
+          value={maxInlineKb}
+          onChange={(e) => setMaxInlineKb(e.target.value)}
+          onBlur={commit}
+          className="w-20 px-2 py-1 rounded border outline-none"
+          style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff" }}
+        />
+        <span style={{ color: "#6b6b6b" }}>KB</span>
+      </div>
+    </div>
+  )
+}
+
+function UrlAttachmentDomainsCard() {
+  const { app, setUrlAttachmentDomains } = useLuban()
+  const domains = app?.url_attachments?.allowed_domains ?? []
+  const [draft, setDraft] = useState("")
+
+  useEffect(() => {
+    setDraft(domains.join("\n"))
+  }, [domains.join("\n")])
+
+  const commit = () => {
+    const next = draft
+      .split(/[\s,]+/)
+      .map((d) => d.trim())
+      .filter((d) => d.length > 0)
+    if (next.join("\n") === domains.join("\n")) return
+    setUrlAttachmentDomains(next)
+  }
+
+  return (
+    <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+      <div>
+        <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+          URL attachments
+        </div>
+        <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+          Pasted links from these domains (and their subdomains) are fetched once and attached as a Markdown snapshot.
+          One domain per line; leave empty to disable URL attachments.
+        </div>
+      </div>
+      <textarea
+        data-testid="url-attachment-domains"
+        value={draft}
+        onChange={(e) => setDraft(e.target.value)}
+        onBlur={commit}
+        rows={4}
+        placeholder={"docs.rs\ndeveloper.mozilla.org"}
+        className="w-full px-2 py-1 rounded border outline-none text-[13px] font-mono"
+        style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff", color: "#1b1b1b" }}
+      />
+    </div>
+  )
+}
+
+function HistoryCard() {
+  const { app, undoLastAction, setDeletionRetentionDays } = useLuban()
+  const retentionDays = app?.deletion?.retention_days ?? 7
+  const [draft, setDraft] = useState(String(retentionDays))
+  const [entries, setEntries] = useState<AuditEntrySnapshot[]>([])
+
+  useEffect(() => {
+    setDraft(String(retentionDays))
+  }, [retentionDays])
+
+  useEffect(() => {
+    let cancelled = false
+    fetchAuditLog({ limit: 10 })
+      .then((log) => {
+        if (!cancelled) setEntries(log.entries)
+      })
+      .catch(() => {})
+    return () => {
+      cancelled = true
+    }
+  }, [app?.rev])
+
+  const commit = () => {
+    const next = Number(draft)
+    if (!Number.isFinite(next) || next < 0 || Math.floor(next) === retentionDays) {
+      setDraft(String(retentionDays))
+      return
+    }
+    setDeletionRetentionDays(next)
+  }
+
+  const canUndo = entries.some((e) => e.undoable)
+
+  return (
+    <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+      <div className="flex items-start justify-between gap-3">
+        <div>
+          <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+            History
+          </div>
+          <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+            Deleted projects and tasks are kept for the retention period and can be restored with undo. Set it to 0 to
+            delete permanently.
+          </div>
+        </div>
+        <button
+          data-testid="undo-last-action"
+          onClick={() => undoLastAction()}
+          disabled={!canUndo}
+          className="px-3 py-2 rounded text-[13px] transition-colors disabled:opacity-50"
+          style={{ backgroundColor: "#eeeeee", color: "#1b1b1b" }}
+        >
+          Undo last action
+        </button>
+      </div>
+      <div className="flex items-center gap-2 text-[13px]" style={{ color: "#1b1b1b" }}>
+        <span style={{ color: "#6b6b6b" }}>Keep deleted items for</span>
+        <input
+          type="number"
+          min={0}
+          max={365}
+          data-testid="deletion-retention-days"
+          value={draft}
+          onChange={(e) => setDraft(e.target.value)}
+          onBlur={commit}
+          className="w-20 px-2 py-1 rounded border outline-none"
+          style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff" }}
+        />
+        <span style={{ color: "#6b6b6b" }}>days</span>
+      </div>
+      {entries.length > 0 && (
+        <ul data-testid="audit-log" className="space-y-1 text-[12px]">
+          {entries.map((entry) => (
+            <li key={entry.id} className="flex items-center gap-2" style={{ color: "#1b1b1b" }}>
+              <span style={{ color: "#6b6b6b" }}>{new Date(entry.created_at_unix_ms).toLocaleString()}</span>
+              <span className="truncate" style={{ textDecoration: entry.undone_at_unix_ms ? "line-through" : undefined }}>
+                {entry.summary}
+              </span>
+              <span className="ml-auto" style={{ color: "#9b9b9b" }}>
+                {entry.origin}
+              </span>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  )
+}
+
 function TelegramIntegrationPanel() {
   const {
     app,
@@ -1793,6 +2421,162 @@ function TelegramIntegrationPanel() {
           </button>
         </div>
       </div>
+
+      <TelegramGroupChatsPanel hasToken={hasToken} />
+    </div>
+  )
+}
+
+function TelegramGroupChatsPanel({ hasToken }: { hasToken: boolean }) {
+  const { app, startTelegramGroupPairing, unpairTelegramGroup } = useLuban()
+  const projects = app?.projects ?? []
+  const groups = app?.integrations?.telegram?.group_chats ?? []
+  const [projectId, setProjectId] = useState<string>("")
+  const [pairUrl, setPairUrl] = useState<string | null>(null)
+  const [pairing, setPairing] = useState(false)
+
+  const selectedProjectId = projectId || projects[0]?.id || ""
+
+  return (
+    <div className="rounded border p-3 space-y-3" style={{ borderColor: "#ebebeb" }}>
+      <div>
+        <div className="text-[13px] font-medium" style={{ color: "#1b1b1b" }}>
+          Group Chats
+        </div>
+        <div className="text-[12px] mt-1" style={{ color: "#6b6b6b" }}>
+          Pair one forum-enabled group per project. Make the bot an admin so it can open a topic for each task.
+        </div>
+      </div>
+
+      <div className="flex items-center gap-2">
+        <select
+          data-testid="telegram-group-project"
+          value={selectedProjectId}
+          onChange={(e) => setProjectId(e.target.value)}
+          className="flex-1 px-3 py-2 rounded text-[13px] border outline-none"
+          style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff", color: "#1b1b1b" }}
+        >
+          {projects.map((p) => (
+            <option key={p.id} value={p.id}>
+              {p.name}
+            </option>
+          ))}
+        </select>
+        <button
+          data-testid="telegram-group-pair-generate"
+          onClick={async () => {
+            if (!selectedProjectId) return
+            setPairing(true)
+            setPairUrl(null)
+            try {
+              setPairUrl(await startTelegramGroupPairing(selectedProjectId))
+            } finally {
+              setPairing(false)
+            }
+          }}
+          disabled={!hasToken || pairing || !selectedProjectId}
+          className="px-3 py-2 rounded text-[13px] transition-colors disabled:opacity-50 flex items-center gap-2"
+          style={{ backgroundColor: "#eeeeee", color: "#1b1b1b" }}
+        >
+          {pairing ? <Loader2 className="w-4 h-4 animate-spin" /> : null}
+          Generate Group Link
+        </button>
+      </div>
+
+      {pairUrl ? (
+        <input
+          readOnly
+          data-testid="telegram-group-pair-url"
+          value={pairUrl}
+          className="w-full px-3 py-2 rounded text-[13px] border"
+          style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff", color: "#1b1b1b" }}
+        />
+      ) : null}
+
+      {groups.map((group) => (
+        <TelegramGroupChatRow
+          key={group.chat_id}
+          group={group}
+          onUnpair={() => unpairTelegramGroup(group.chat_id)}
+        />
+      ))}
+    </div>
+  )
+}
+
+function TelegramGroupChatRow({
+  group,
+  onUnpair,
+}: {
+  group: TelegramGroupChatSnapshot
+  onUnpair: () => void
+}) {
+  const { addTelegramGroupMember, removeTelegramGroupMember } = useLuban()
+  const [userId, setUserId] = useState("")
+  const [displayName, setDisplayName] = useState("")
+
+  return (
+    <div
+      data-testid="telegram-group-chat"
+      className="rounded border p-3 space-y-2"
+      style={{ borderColor: "#ebebeb" }}
+    >
+      <div className="flex items-center justify-between">
+        <div className="text-[12px]" style={{ color: "#1b1b1b" }}>
+          {group.title || `Group ${group.chat_id}`} · {group.project_slug} · {group.topic_count} topics
+        </div>
+        <button
+          onClick={onUnpair}
+          className="px-3 py-1 rounded text-[12px] transition-colors"
+          style={{ backgroundColor: "#eeeeee", color: "#1b1b1b" }}
+        >
+          Unpair
+        </button>
+      </div>
+
+      {group.members.map((member) => (
+        <div key={member.user_id} className="flex items-center justify-between text-[12px]" style={{ color: "#6b6b6b" }}>
+          <span>
+            {member.display_name || "Unnamed"} ({member.user_id})
+          </span>
+          <button
+            onClick={() => removeTelegramGroupMember(group.chat_id, member.user_id)}
+            className="px-2 py-1 rounded text-[12px] transition-colors"
+            style={{ backgroundColor: "#eeeeee", color: "#1b1b1b" }}
+          >
+            Remove
+          </button>
+        </div>
+      ))}
+
+      <div className="flex items-center gap-2">
+        <input
+          value={userId}
+          onChange={(e) => setUserId(e.target.value.replace(/[^0-9]/g, ""))}
+          placeholder="Telegram user id"
+          className="w-40 px-3 py-1 rounded text-[12px] border outline-none"
+          style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff", color: "#1b1b1b" }}
+        />
+        <input
+          value={displayName}
+          onChange={(e) => setDisplayName(e.target.value)}
+          placeholder="Name"
+          className="flex-1 px-3 py-1 rounded text-[12px] border outline-none"
+          style={{ borderColor: "#ebebeb", backgroundColor: "#ffffff", color: "#1b1b1b" }}
+        />
+        <button
+          onClick={() => {
+            addTelegramGroupMember(group.chat_id, Number(userId), displayName)
+            setUserId("")
+            setDisplayName("")
+          }}
+          disabled={!userId}
+          className="px-3 py-1 rounded text-[12px] transition-colors disabled:opacity-50"
+          style={{ backgroundColor: "#eeeeee", color: "#1b1b1b" }}
+        >
+          Allow
+        </button>
+      </div>
     </div>
   )
 }
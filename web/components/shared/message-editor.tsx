@@ -8,6 +8,7 @@ import {
   FileCode,
   FileText,
   Folder,
+  Globe,
   ImageIcon,
   Loader2,
   Paperclip,
@@ -20,7 +21,7 @@ import type { AttachmentRef, CodexCustomPromptSnapshot, MentionItemSnapshot } fr
 
 export type ComposerAttachment = {
   id: string
-  type: "image" | "file"
+  type: "image" | "file" | "url"
   name: string
   size: number
   preview?: string
@@ -652,7 +653,9 @@ export function MessageEditor({
                     />
                   ) : (
                     <div className="flex flex-col items-center gap-1.5">
-                      {attachment.name.toLowerCase().endsWith(".json") ? (
+                      {attachment.type === "url" ? (
+                        <Globe className="w-6 h-6 text-base0D" />
+                      ) : attachment.name.toLowerCase().endsWith(".json") ? (
                         <FileCode className="w-6 h-6 text-base09" />
                       ) : attachment.name.toLowerCase().endsWith(".pdf") ? (
                         <FileText className="w-6 h-6 text-base08" />
@@ -660,7 +663,7 @@ export function MessageEditor({
                         <FileText className="w-6 h-6 text-muted-foreground" />
                       )}
                       <span className="text-[9px] text-muted-foreground uppercase font-medium tracking-wide">
-                        {attachment.name.split(".").pop()}
+                        {attachment.type === "url" ? "link" : attachment.name.split(".").pop()}
                       </span>
                     </div>
                   )}
@@ -687,7 +690,7 @@ export function MessageEditor({
                 </div>
                 {attachment.status === "failed" && (
                   <div className="absolute inset-x-0 bottom-0 px-1 py-0.5 text-[9px] text-destructive bg-background/80 text-center">
-                    Upload failed
+                    {attachment.type === "url" ? "Fetch failed" : "Upload failed"}
                   </div>
                 )}
               </div>
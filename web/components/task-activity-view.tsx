@@ -32,6 +32,8 @@ import { attachmentHref } from "@/lib/attachment-href"
 import { WindowedList, type WindowedListItem } from "@/components/windowed-list"
 import { PtyTerminalSession } from "@/components/pty-terminal"
 import { useLuban } from "@/lib/luban-context"
+import { fetchTurnDiff } from "@/lib/luban-http"
+import { DiffTabPanel, type DiffFileData, type DiffStyle } from "@/components/diff-tab-panel"
 
 const AMP_MARK_URL = "/logos/amp.svg"
 
@@ -379,6 +381,8 @@ function ActivityEventItem({ event, isExpanded, onToggle, duration }: ActivityEv
 
 interface SystemEventItemProps {
   message: Message
+  workspaceId?: number
+  taskId?: number
   /** Who triggered this event - defaults to "You" for user events */
   actor?: {
     name: string
@@ -387,7 +391,141 @@ interface SystemEventItemProps {
   }
 }
 
-function SystemEventItem({ message, actor }: SystemEventItemProps) {
+function CheckpointRewindActions({
+  checkpointId,
+  workspaceId,
+  taskId,
+}: {
+  checkpointId: string
+  workspaceId: number
+  taskId: number
+}) {
+  const { rewindTask } = useLuban()
+  const linkStyle = { color: COLORS.textMuted, textDecoration: "underline" }
+  return (
+    <span className="inline-flex items-center gap-2" style={{ marginLeft: "6px" }}>
+      <button
+        type="button"
+        data-testid="checkpoint-restore-files"
+        style={linkStyle}
+        title="Restore the worktree files to how they were before this turn"
+        onClick={() => rewindTask(workspaceId, taskId, checkpointId, false)}
+      >
+        Restore files
+      </button>
+      <button
+        type="button"
+        data-testid="checkpoint-rewind"
+        style={linkStyle}
+        title="Restore the files and drop this turn and everything after it"
+        onClick={() => {
+          if (!window.confirm("Rewind to before this turn? Later messages in this task will be removed.")) return
+          rewindTask(workspaceId, taskId, checkpointId, true)
+        }}
+      >
+        Rewind
+      </button>
+    </span>
+  )
+}
+
+function ForkTaskActions({
+  entryId,
+  workspaceId,
+  taskId,
+}: {
+  entryId: string
+  workspaceId: number
+  taskId: number
+}) {
+  const { forkTask } = useLuban()
+  const linkStyle = { color: COLORS.textMuted, textDecoration: "underline" }
+  return (
+    <span className="inline-flex items-center gap-2" style={{ marginLeft: "6px" }}>
+      <button
+        type="button"
+        data-testid="turn-fork"
+        style={linkStyle}
+        title="Start a new task in this workdir from the conversation up to this turn"
+        onClick={() => forkTask(workspaceId, taskId, entryId, "same_workdir")}
+      >
+        Fork
+      </button>
+      <button
+        type="button"
+        data-testid="turn-fork-new-workdir"
+        style={linkStyle}
+        title="Start a new task in a new workdir created from the current HEAD"
+        onClick={() => forkTask(workspaceId, taskId, entryId, "new_workdir")}
+      >
+        Fork to new workdir
+      </button>
+    </span>
+  )
+}
+
+function TurnChangesDiff({
+  checkpointId,
+  workspaceId,
+  taskId,
+}: {
+  checkpointId: string
+  workspaceId: number
+  taskId: number
+}) {
+  const [open, setOpen] = useState(false)
+  const [files, setFiles] = useState<DiffFileData[] | null>(null)
+  const [error, setError] = useState<string | null>(null)
+  const [diffStyle, setDiffStyle] = useState<DiffStyle>("unified")
+
+  const toggle = () => {
+    const next = !open
+    setOpen(next)
+    if (!next || files != null) return
+    setError(null)
+    fetchTurnDiff({ workspaceId, taskId, fromCheckpointId: checkpointId })
+      .then((snap) =>
+        setFiles(
+          snap.files.map((file) => ({
+            file: file.file,
+            oldFile: { name: file.old_file.name, contents: file.old_file.contents },
+            newFile: { name: file.new_file.name, contents: file.new_file.contents },
+          })),
+        ),
+      )
+      .catch((err: unknown) => setError(err instanceof Error ? err.message : String(err)))
+  }
+
+  return (
+    <>
+      <button
+        type="button"
+        data-testid="turn-diff-toggle"
+        style={{ marginLeft: "6px", color: COLORS.textMuted, textDecoration: "underline" }}
+        onClick={toggle}
+      >
+        {open ? "Hide diff" : "View diff"}
+      </button>
+      {open && (
+        <span
+          className="block basis-full overflow-auto"
+          data-testid="turn-diff-panel"
+          style={{ maxHeight: "480px", marginTop: "6px", border: `1px solid ${COLORS.border}`, borderRadius: "6px" }}
+        >
+          <DiffTabPanel
+            isLoading={files == null && error == null}
+            error={error}
+            files={files ?? []}
+            diffStyle={diffStyle}
+            onStyleChange={setDiffStyle}
+          />
+        </span>
+      )}
+    </>
+  )
+}
+
+function SystemEventItem({ message, actor, workspaceId, taskId }: SystemEventItemProps) {
   const defaultActor = (() => {
     if (message.eventSource === "agent") {
       return { name: agentRunnerLabel(message.agentRunner), initial: "A", color: COLORS.textPrimary }
@@ -463,6 +601,15 @@ function SystemEventItem({ message, actor }: SystemEventItemProps) {
             <span data-testid="event-timestamp">{formatRelativeTime(message.timestamp)}</span>
           )}
         </span>
+        {message.checkpointId && workspaceId != null && taskId != null && (
+          <CheckpointRewindActions checkpointId={message.checkpointId} workspaceId={workspaceId} taskId={taskId} />
+        )}
+        {message.turnDiffCheckpointId && workspaceId != null && taskId != null && (
+          <TurnChangesDiff checkpointId={message.turnDiffCheckpointId} workspaceId={workspaceId} taskId={taskId} />
+        )}
+        {message.forkEntryId && workspaceId != null && taskId != null && (
+          <ForkTaskActions entryId={message.forkEntryId} workspaceId={workspaceId} taskId={taskId} />
+        )}
       </span>
     </div>
   )
@@ -508,11 +655,11 @@ function UserActivityEvent({ message, workspaceId }: UserActivityEventProps) {
               lineHeight: "16px",
             }}
           >
-            U
+            {message.author ? message.author.trim().charAt(0).toUpperCase() || "U" : "U"}
           </div>
         </div>
         <span data-testid="activity-card-author" style={{ fontSize: '13px', fontWeight: 500, color: COLORS.textPrimary }}>
-          You
+          {message.author ?? "You"}
         </span>
         <span style={{ fontSize: '14px', fontWeight: 400, color: COLORS.textMuted }}>
           {formatRelativeTime(message.timestamp)}
@@ -1273,7 +1420,7 @@ function ActivityStreamSection({
                       }}
                     />
                   )}
-                  <SystemEventItem message={message} />
+                  <SystemEventItem message={message} workspaceId={workspaceId} taskId={taskId} />
                 </div>
               )
             })}
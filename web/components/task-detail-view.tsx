@@ -1,7 +1,7 @@
 "use client"
 
 import { useCallback, useEffect, useRef, useState } from "react"
-import { MoreHorizontal, Star, Trash2 } from "lucide-react"
+import { Check, CircleDot, MoreHorizontal, Star, Trash2 } from "lucide-react"
 import { TaskDocumentPanel } from "./task-document-panel"
 import { TaskWorkspacePanel } from "./task-workspace-panel"
 import { TaskHeader } from "./shared/task-header"
@@ -9,6 +9,7 @@ import {
   DropdownMenu,
   DropdownMenuContent,
   DropdownMenuItem,
+  DropdownMenuSeparator,
   DropdownMenuTrigger,
 } from "@/components/ui/dropdown-menu"
 import { useLuban } from "@/lib/luban-context"
@@ -16,6 +17,13 @@ import { getActiveProjectInfo } from "@/lib/active-project-info"
 import { projectColorClass } from "@/lib/project-colors"
 import { buildSidebarProjects } from "@/lib/sidebar-view-model"
 import { fetchTasks } from "@/lib/luban-http"
+import type { IssueMergeAction } from "@/lib/luban-api"
+
+const ISSUE_MERGE_ACTIONS: { value: IssueMergeAction; label: string }[] = [
+  { value: "close", label: "Close issue on merge" },
+  { value: "comment", label: "Comment on issue on merge" },
+  { value: "leave", label: "Leave issue on merge" },
+]
 
 interface TaskDetailViewProps {
   taskId?: string
@@ -34,6 +42,7 @@ export function TaskDetailView({ taskId, taskTitle, workdir, projectName, projec
     activeTaskId: activeThreadId,
     tasks: threads,
     setTaskStarred,
+    setTaskIssueMergeAction,
     deleteTask,
   } = useLuban()
   const [isStarred, setIsStarred] = useState(false)
@@ -70,6 +79,13 @@ export function TaskDetailView({ taskId, taskTitle, workdir, projectName, projec
     (activeThreadId != null ? threads.find((t) => t.task_id === activeThreadId)?.title : null) ??
     "Task"
 
+  const issueLink =
+    activeWorkspaceId != null && activeThreadId != null
+      ? (app?.task_issue_links ?? []).find(
+          (l) => l.workdir_id === activeWorkspaceId && l.task_id === activeThreadId,
+        ) ?? null
+      : null
+
   const resolvedProjectColor = (() => {
     if (projectColor) return projectColor
     if (!app || activeWorkspaceId == null) return "bg-violet-500"
@@ -138,6 +154,19 @@ export function TaskDetailView({ taskId, taskTitle, workdir, projectName, projec
           onProjectClick={onBack}
           customActions={
             <div className="flex items-center gap-0.5">
+              {issueLink ? (
+                <a
+                  data-testid="task-issue-link"
+                  href={issueLink.url}
+                  target="_blank"
+                  rel="noreferrer"
+                  title={issueLink.title}
+                  className="h-6 px-1.5 flex items-center gap-1 rounded-[4px] text-[11px] hover:bg-[#eeeeee] transition-colors"
+                  style={{ color: '#6b6b6b' }}
+                >
+                  <CircleDot className="w-3.5 h-3.5" />#{issueLink.number}
+                </a>
+              ) : null}
               <button
                 data-testid="task-star-button"
                 className="w-6 h-6 flex items-center justify-center rounded-[4px] hover:bg-[#eeeeee] transition-colors"
@@ -162,6 +191,23 @@ export function TaskDetailView({ taskId, taskTitle, workdir, projectName, projec
                   </button>
                 </DropdownMenuTrigger>
                 <DropdownMenuContent align="end">
+                  {issueLink ? (
+                    <>
+                      {ISSUE_MERGE_ACTIONS.map(({ value, label }) => (
+                        <DropdownMenuItem
+                          key={value}
+                          onClick={() => setTaskIssueMergeAction(issueLink.workdir_id, issueLink.task_id, value)}
+                        >
+                          <Check
+                            className="w-3.5 h-3.5 mr-1.5"
+                            style={{ visibility: issueLink.on_merge === value ? 'visible' : 'hidden' }}
+                          />
+                          {label}
+                        </DropdownMenuItem>
+                      ))}
+                      <DropdownMenuSeparator />
+                    </>
+                  ) : null}
                   <DropdownMenuItem
                     className="text-red-600 focus:text-red-600 focus:bg-red-50"
                     onClick={() => {
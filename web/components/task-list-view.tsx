@@ -11,6 +11,7 @@ import {
   ListChecks,
   Plus,
 } from "lucide-react"
+import { toast } from "sonner"
 import { cn } from "@/lib/utils"
 import { ProjectIcon, type ProjectInfo } from "./shared/task-header"
 import { TaskStatusSelector } from "./shared/task-status-selector"
@@ -24,6 +25,7 @@ import { fetchTasks } from "@/lib/luban-http"
 import type {
   AgentRunnerKind,
   OperationStatus,
+  TaskBulkOperation,
   TaskStatus,
   TasksSnapshot,
   TurnResult,
@@ -59,7 +61,9 @@ type TaskRowModel = Task & {
 interface TaskRowProps {
   task: TaskRowModel
   selected?: boolean
+  bulkSelected?: boolean
   onClick?: () => void
+  onToggleBulkSelect?: () => void
   onMouseEnter?: () => void
   onMouseLeave?: () => void
   onStatusChange?: (status: TaskStatus) => void
@@ -68,7 +72,9 @@ interface TaskRowProps {
 function TaskRow({
   task,
   selected,
+  bulkSelected,
   onClick,
+  onToggleBulkSelect,
   onMouseEnter,
   onMouseLeave,
   onStatusChange,
@@ -77,13 +83,20 @@ function TaskRow({
   const isArchived = task.status === "done" || task.status === "canceled"
   return (
     <div
-      onClick={onClick}
+      onClick={(e) => {
+        if ((e.metaKey || e.ctrlKey || e.shiftKey) && onToggleBulkSelect) {
+          onToggleBulkSelect()
+          return
+        }
+        onClick?.()
+      }}
       onMouseEnter={onMouseEnter}
       onMouseLeave={onMouseLeave}
       data-task-row-id={task.id}
+      data-bulk-selected={bulkSelected ? "true" : undefined}
       className={cn(
         "group flex items-center gap-3 px-4 h-[44px] cursor-pointer transition-colors",
-        selected ? "bg-[#f0f0f0]" : "hover:bg-[#f7f7f7]"
+        bulkSelected ? "bg-[#e8f0fe]" : selected ? "bg-[#f0f0f0]" : "hover:bg-[#f7f7f7]"
       )}
       style={{ borderBottom: '1px solid #ebebeb' }}
     >
@@ -283,10 +296,11 @@ export function TaskListView({
   onTaskClick,
   statusPickerRequestSeq = 0,
 }: TaskListViewProps) {
-  const { app, wsConnected, setTaskStatus, subscribeServerEvents } = useLuban()
+  const { app, wsConnected, setTaskStatus, applyTaskBulk, undoTaskBulk, subscribeServerEvents } = useLuban()
   const [tasksSnapshot, setTasksSnapshot] = useState<TasksSnapshot | null>(null)
   const [selectedTask, setSelectedTask] = useState<string | null>(null)
   const [hoveredTaskId, setHoveredTaskId] = useState<string | null>(null)
+  const [bulkSelection, setBulkSelection] = useState<Set<string>>(() => new Set())
   const [statusMenuOpen, setStatusMenuOpen] = useState(false)
   const [statusMenuAnchorRect, setStatusMenuAnchorRect] = useState<AnchorRect | null>(null)
   const [statusMenuTaskRowId, setStatusMenuTaskRowId] = useState<string | null>(null)
@@ -501,6 +515,54 @@ export function TaskListView({
     return [...iteratingTasks, ...validatingTasks, ...todoTasks, ...backlogTasks, ...doneTasks, ...canceledTasks]
   }, [backlogTasks, canceledTasks, doneTasks, iteratingTasks, mode, todoTasks, validatingTasks])
 
+  const toggleBulkSelection = useCallback((taskRowId: string) => {
+    setBulkSelection((prev) => {
+      const next = new Set(prev)
+      if (next.has(taskRowId)) next.delete(taskRowId)
+      else next.add(taskRowId)
+      return next
+    })
+  }, [])
+
+  useEffect(() => {
+    setBulkSelection((prev) => {
+      const next = new Set([...prev].filter((id) => tasksByRowId.has(id)))
+      return next.size === prev.size ? prev : next
+    })
+  }, [tasksByRowId])
+
+  const runBulkOperation = useCallback(
+    async (operation: TaskBulkOperation, label: string) => {
+      const items = [...bulkSelection]
+        .map((id) => tasksByRowId.get(id))
+        .filter((t): t is TaskRowModel => t != null)
+        .map((t) => ({ workdir_id: t.workspaceId, task_id: t.taskId }))
+      if (items.length === 0) return
+      try {
+        const result = await applyTaskBulk(items, operation)
+        const failed = result.items.filter((item) => !item.ok).length
+        const summary = failed > 0 ? `${label}: ${result.items.length - failed} updated, ${failed} failed` : `${label}: ${result.items.length} updated`
+        toast(summary, {
+          action: result.undoable
+            ? {
+                label: "Undo",
+                onClick: () => {
+                  void undoTaskBulk(result.batch_id)
+                    .then(() => refreshTasks())
+                    .catch((err) => toast.error(err instanceof Error ? err.message : String(err)))
+                },
+              }
+            : undefined,
+        })
+        setBulkSelection(new Set())
+        void refreshTasks()
+      } catch (err) {
+        toast.error(err instanceof Error ? err.message : String(err))
+      }
+    },
+    [applyTaskBulk, bulkSelection, refreshTasks, tasksByRowId, undoTaskBulk],
+  )
+
   const activeTaskRowIdForStatus = hoveredTaskId ?? selectedTask
 
   const openStatusMenuForTaskRowId = useCallback(
@@ -644,6 +706,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -666,6 +730,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -686,6 +752,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -706,6 +774,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -729,6 +799,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -749,6 +821,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -769,6 +843,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -789,6 +865,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -809,6 +887,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -829,6 +909,8 @@ export function TaskListView({
                   task={task}
                   agentRunner={agentRunner}
                   selected={selectedTask === task.id}
+                  bulkSelected={bulkSelection.has(task.id)}
+                  onToggleBulkSelect={() => toggleBulkSelection(task.id)}
                   onClick={() => {
                     setSelectedTask(task.id)
                     onTaskClick?.(task)
@@ -844,6 +926,38 @@ export function TaskListView({
           </>
         )}
       </div>
+      {bulkSelection.size > 0 ? (
+        <div
+          className="flex items-center gap-2 h-[40px] flex-shrink-0 px-4 text-[12px]"
+          style={{ borderTop: "1px solid #ebebeb", color: "#6b6b6b" }}
+          data-testid="task-bulk-bar"
+        >
+          <span>{bulkSelection.size} selected</span>
+          <span className="flex-1" />
+          <button
+            className="h-6 px-2 rounded-[5px] flex items-center gap-1.5 hover:bg-[#eeeeee] transition-colors"
+            data-testid="task-bulk-done"
+            onClick={() => void runBulkOperation({ type: "set_status", task_status: "done" }, "Marked done")}
+          >
+            <CheckCircle2 className="w-3.5 h-3.5" />
+            Mark done
+          </button>
+          <button
+            className="h-6 px-2 rounded-[5px] hover:bg-[#eeeeee] transition-colors"
+            data-testid="task-bulk-star"
+            onClick={() => void runBulkOperation({ type: "set_starred", starred: true }, "Starred")}
+          >
+            Star
+          </button>
+          <button
+            className="h-6 px-2 rounded-[5px] hover:bg-[#eeeeee] transition-colors"
+            data-testid="task-bulk-clear"
+            onClick={() => setBulkSelection(new Set())}
+          >
+            Clear
+          </button>
+        </div>
+      ) : null}
       {statusMenuTask ? (
         <TaskStatusCommandMenu
           open={statusMenuOpen}
@@ -4,7 +4,7 @@ import { useCallback, useEffect, useMemo, useRef, useState, type ComponentType }
 import { FileCode2, FilePlus2, FileMinus2, FileEdit, FileSymlink, GitBranch, MonitorPlay, TerminalSquare } from "lucide-react"
 
 import type { ChangedFileSnapshot, FileChangeStatus, WorkspaceDiffFileSnapshot } from "@/lib/luban-api"
-import { DiffTabPanel, type DiffFileData, type DiffStyle } from "@/components/diff-tab-panel"
+import { DiffTabPanel, useTaskDiffReview, type DiffFileData, type DiffStyle } from "@/components/diff-tab-panel"
 import { PtyTerminal } from "@/components/pty-terminal"
 import { TaskActivityPanel } from "@/components/task-activity-panel"
 import type { WorkspaceChangesSnapshot } from "@/lib/luban-api"
@@ -155,6 +155,7 @@ function ChangesFileList({
 
 export function TaskWorkspacePanel() {
   const { activeWorkdirId, activeTaskId } = useLuban()
+  const diffReview = useTaskDiffReview()
   const [activeTab, setActiveTab] = useState<WorkspaceTab>("agents")
   const [changes, setChanges] = useState<ChangesState>({
     loading: false,
@@ -378,6 +379,7 @@ export function TaskWorkspacePanel() {
                   activeFileId={selectedDiff.fileId}
                   diffStyle={diffStyle}
                   onStyleChange={setDiffStyle}
+                  review={diffReview}
                 />
               ) : null}
             </div>
@@ -385,7 +387,7 @@ export function TaskWorkspacePanel() {
         )}
       </div>
     )
-  }, [activeWorkdirId, changes.error, changes.loading, files, selectedDiff, topHeightPercent, isDragging, diffStyle, handleSelectFile, handleResizePointerDown, handleResizePointerMove, handleResizePointerUp])
+  }, [activeWorkdirId, changes.error, changes.loading, files, selectedDiff, topHeightPercent, isDragging, diffStyle, diffReview, handleSelectFile, handleResizePointerDown, handleResizePointerMove, handleResizePointerUp])
 
   return (
     <div className="h-full min-h-0 flex flex-col border-l border-border bg-background" data-testid="task-workspace-panel">
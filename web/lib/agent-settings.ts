@@ -1,6 +1,6 @@
 "use client"
 
-import type { ThinkingEffort } from "./luban-api"
+import type { AgentModelSnapshot, ThinkingEffort } from "./luban-api"
 
 export type AgentModelSpec = {
   id: string
@@ -47,6 +47,23 @@ export const DROID_MODELS: AgentModelSpec[] = [
   { id: "kimi-k2.5", label: "Kimi K2.5", supportedThinkingEfforts: [] },
 ]
 
+/**
+ * Replaces the built-in tables with the server's model catalog. Arrays are updated in place so
+ * existing imports observe the new entries.
+ */
+export function applyAgentModelCatalog(models: AgentModelSnapshot[] | null | undefined) {
+  if (!models || models.length === 0) return
+  const toSpec = (m: AgentModelSnapshot): AgentModelSpec => ({
+    id: m.id,
+    label: m.label,
+    supportedThinkingEfforts: m.supported_thinking_efforts ?? [],
+  })
+  const codex = models.filter((m) => m.runner === "codex").map(toSpec)
+  const droid = models.filter((m) => m.runner === "droid").map(toSpec)
+  if (codex.length > 0) AGENT_MODELS.splice(0, AGENT_MODELS.length, ...codex)
+  if (droid.length > 0) DROID_MODELS.splice(0, DROID_MODELS.length, ...droid)
+}
+
 export function supportedThinkingEffortsForModel(modelId: string | null | undefined): ThinkingEffort[] {
   if (!modelId) return THINKING_EFFORTS
   return (
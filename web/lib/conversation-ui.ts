@@ -57,6 +57,7 @@ export interface Message {
   agentRunner?: AgentRunnerKind
   content: string
   attachments?: AttachmentRef[]
+  author?: string
   timestamp?: string
   isStreaming?: boolean
   isCancelled?: boolean
@@ -84,6 +85,12 @@ export interface Message {
     outputBase64?: string
     outputByteLen?: number
   }
+  /** Set on turn checkpoint events; the worktree can be rewound to it. */
+  checkpointId?: string
+  /** Set on turn change summaries; the turn's diff is fetched by this checkpoint. */
+  turnDiffCheckpointId?: string
+  /** Set on turn change summaries; a fork from here keeps the conversation through this turn. */
+  forkEntryId?: string
   taskStatusSuggestion?: {
     from: TaskStatus
     to: TaskStatus
@@ -92,6 +99,44 @@ export interface Message {
   }
 }
 
+function turnChangesLabel(ev: { files_changed: number; additions: number; deletions: number }): string {
+  if (ev.files_changed === 0) return "changed no files in this turn"
+  const files = ev.files_changed === 1 ? "1 file" : `${ev.files_changed} files`
+  return `changed ${files} in this turn (+${ev.additions}/−${ev.deletions})`
+}
+
+function turnLimitLabel(ev: { kind: string; limit: number; observed: number }): string {
+  if (ev.kind === "timeout") return `canceled the turn after ${ev.observed}s (timeout ${ev.limit}s)`
+  if (ev.kind === "tool_calls") return `canceled the turn after ${ev.observed} tool calls (limit ${ev.limit})`
+  if (ev.kind === "turn_tokens") {
+    return `paused the queue: the turn used ${ev.observed} tokens (limit ${ev.limit} per turn)`
+  }
+  return `stopped the task: ${ev.observed} tokens used of its ${ev.limit} token budget`
+}
+
+function pullRequestReviewCommentLabel(ev: {
+  author: string
+  pr_number: number
+  path: string
+  start_line: number
+  end_line: number
+}): string {
+  const lines =
+    ev.start_line === 0 ? "" : ev.start_line === ev.end_line ? `:${ev.start_line}` : `:${ev.start_line}-${ev.end_line}`
+  return `imported a review comment from ${ev.author} on PR #${ev.pr_number} (${ev.path}${lines})`
+}
+
+function ciFixAttemptLabel(ev: { attempt: number; max_attempts: number; workflow: string; pr_number: number }): string {
+  const workflow = ev.workflow || "CI"
+  return `started CI fix attempt ${ev.attempt} of ${ev.max_attempts}: ${workflow} failed on PR #${ev.pr_number}`
+}
+
+function ciAutoFixStoppedLabel(ev: { reason: string; attempts: number }): string {
+  const attempts = `${ev.attempts} attempt${ev.attempts === 1 ? "" : "s"}`
+  if (ev.reason === "passed") return `stopped CI auto-fix: CI passed after ${attempts}`
+  return `stopped CI auto-fix: CI still failing after ${attempts}`
+}
+
 function safeStringify(value: unknown): string {
   try {
     return JSON.stringify(value, null, 2)
@@ -698,6 +743,20 @@ function buildMessagesGroupedTurns(conversation: ConversationSnapshot): Message[
       const content = (() => {
         if (ev?.event_type === "task_created") return "created the task"
         if (ev?.event_type === "task_archived") return "archived the task"
+        if (ev?.event_type === "turn_interrupted") {
+          return ev.resumable ? "interrupted the run on restart (resumable)" : "interrupted the run on restart"
+        }
+        if (ev?.event_type === "turn_checkpoint") return "saved a checkpoint of the worktree"
+        if (ev?.event_type === "turn_rewound") {
+          return ev.truncated
+            ? "rewound the worktree and conversation to an earlier turn"
+            : "rewound the worktree to an earlier turn"
+        }
+        if (ev?.event_type === "turn_changes") return turnChangesLabel(ev)
+        if (ev?.event_type === "turn_limit_reached") return turnLimitLabel(ev)
+        if (ev?.event_type === "pull_request_review_comment") return pullRequestReviewCommentLabel(ev)
+        if (ev?.event_type === "ci_fix_attempt") return ciFixAttemptLabel(ev)
+        if (ev?.event_type === "ci_auto_fix_stopped") return ciAutoFixStoppedLabel(ev)
         if (ev?.event_type === "task_status_changed") {
           const from = taskStatusLabel(String(ev.from ?? ""))
           const to = taskStatusLabel(String(ev.to ?? ""))
@@ -716,6 +775,9 @@ function buildMessagesGroupedTurns(conversation: ConversationSnapshot): Message[
         eventType,
         content,
         timestamp: unixMsToIso(entry.created_at_unix_ms),
+        checkpointId: ev?.event_type === "turn_checkpoint" ? ev.checkpoint_id : undefined,
+        turnDiffCheckpointId: ev?.event_type === "turn_changes" ? ev.checkpoint_id : undefined,
+        forkEntryId: ev?.event_type === "turn_changes" ? entry.entry_id : undefined,
       })
       continue
     }
@@ -730,6 +792,7 @@ function buildMessagesGroupedTurns(conversation: ConversationSnapshot): Message[
           eventSource: "user",
           content: ev.text,
           attachments: ev.attachments,
+          author: ev.author,
           timestamp: unixMsToIso(entry.created_at_unix_ms),
         })
         lastUserOutIndex = out.length - 1
@@ -1087,6 +1150,20 @@ function buildMessagesFlatEvents(conversation: ConversationSnapshot): Message[]
       const content = (() => {
         if (ev?.event_type === "task_created") return "created the task"
         if (ev?.event_type === "task_archived") return "archived the task"
+        if (ev?.event_type === "turn_interrupted") {
+          return ev.resumable ? "interrupted the run on restart (resumable)" : "interrupted the run on restart"
+        }
+        if (ev?.event_type === "turn_checkpoint") return "saved a checkpoint of the worktree"
+        if (ev?.event_type === "turn_rewound") {
+          return ev.truncated
+            ? "rewound the worktree and conversation to an earlier turn"
+            : "rewound the worktree to an earlier turn"
+        }
+        if (ev?.event_type === "turn_changes") return turnChangesLabel(ev)
+        if (ev?.event_type === "turn_limit_reached") return turnLimitLabel(ev)
+        if (ev?.event_type === "pull_request_review_comment") return pullRequestReviewCommentLabel(ev)
+        if (ev?.event_type === "ci_fix_attempt") return ciFixAttemptLabel(ev)
+        if (ev?.event_type === "ci_auto_fix_stopped") return ciAutoFixStoppedLabel(ev)
         if (ev?.event_type === "task_status_changed") {
           const from = taskStatusLabel(String(ev.from ?? ""))
           const to = taskStatusLabel(String(ev.to ?? ""))
@@ -1105,6 +1182,9 @@ function buildMessagesFlatEvents(conversation: ConversationSnapshot): Message[]
         eventType,
         content,
         timestamp: unixMsToIso(entry.created_at_unix_ms),
+        checkpointId: ev?.event_type === "turn_checkpoint" ? ev.checkpoint_id : undefined,
+        turnDiffCheckpointId: ev?.event_type === "turn_changes" ? ev.checkpoint_id : undefined,
+        forkEntryId: ev?.event_type === "turn_changes" ? entry.entry_id : undefined,
       })
       continue
     }
@@ -1118,6 +1198,7 @@ function buildMessagesFlatEvents(conversation: ConversationSnapshot): Message[]
           eventSource: "user",
           content: ev.text,
           attachments: ev.attachments,
+          author: ev.author,
           timestamp: unixMsToIso(entry.created_at_unix_ms),
         })
         continue
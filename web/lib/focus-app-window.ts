@@ -0,0 +1,16 @@
+"use client"
+
+import { invoke, isTauri } from "@tauri-apps/api/core"
+
+export async function focusAppWindow(): Promise<void> {
+  if (isTauri()) {
+    try {
+      await invoke("focus_main_window")
+      return
+    } catch (err) {
+      console.warn("focus_main_window invoke failed, falling back to window.focus", err)
+    }
+  }
+
+  window.focus()
+}
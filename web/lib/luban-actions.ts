@@ -9,19 +9,28 @@ import type {
   AppearanceFontsSnapshot,
   AppearanceTheme,
   AttachmentRef,
+  AttachmentTextLimitsSnapshot,
   ClientAction,
   CodexConfigEntrySnapshot,
   FeedbackSubmitAction,
   FeedbackSubmitResult,
   FeedbackType,
+  IssueMergeAction,
   OpenTarget,
   ProjectId,
+  PullRequestReviewSyncSnapshot,
+  QuietHoursSnapshot,
   SystemTaskKind,
   TaskIntentKind,
+  TaskBulkItem,
+  TaskBulkOperation,
+  TaskBulkResult,
   TaskExecuteMode,
   TaskExecuteResult,
+  TaskForkTarget,
   TaskStatus,
   ThinkingEffort,
+  TurnLimitsSnapshot,
   WorkspaceId,
   WorkspaceThreadId,
 } from "./luban-api"
@@ -56,6 +65,36 @@ export type LubanActions = {
   clearTelegramBotToken: () => void
   startTelegramPairing: () => Promise<string>
   unpairTelegram: () => void
+  startTelegramGroupPairing: (projectId: ProjectId) => Promise<string>
+  unpairTelegramGroup: (chatId: number) => void
+  addTelegramGroupMember: (chatId: number, userId: number, displayName: string) => void
+  removeTelegramGroupMember: (chatId: number, userId: number) => void
+  setDesktopNotificationsEnabled: (enabled: boolean) => void
+  setNotificationQuietHours: (quietHours: QuietHoursSnapshot | null) => void
+  setAttachmentTextLimits: (limits: AttachmentTextLimitsSnapshot) => void
+  setUrlAttachmentDomains: (domains: string[]) => void
+  setProjectNotificationsMuted: (projectId: ProjectId, muted: boolean) => void
+  setProjectTurnLimits: (projectId: ProjectId, limits: TurnLimitsSnapshot) => void
+  setTaskTurnLimits: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, limits: TurnLimitsSnapshot) => void
+  addReviewComment: (
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    comment: { path: string; startLine: number; endLine: number; quote: string; body: string },
+  ) => void
+  editReviewComment: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, commentId: number, body: string) => void
+  resolveReviewComment: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, commentId: number, resolved: boolean) => void
+  sendReviewComments: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, note: string | null) => void
+  pullPullRequestReview: (workdirId: WorkspaceId, taskId: WorkspaceThreadId) => void
+  setPullRequestReviewSync: (
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    sync: PullRequestReviewSyncSnapshot,
+  ) => void
+  setCiAutoFix: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, enabled: boolean, maxAttempts: number) => void
+  refreshDiskUsage: () => void
+  purgeBuildArtifacts: (workdirId: WorkspaceId | null, globs: string[]) => void
+  deleteStaleWorktrees: (olderThanDays: number) => void
+  gcContextBlobs: () => void
   setTaskPromptTemplate: (intentKind: TaskIntentKind, template: string) => void
   setSystemPromptTemplate: (kind: SystemTaskKind, template: string) => void
   checkCodex: () => Promise<{ ok: boolean; message: string | null }>
@@ -87,7 +126,30 @@ export type LubanActions = {
     modelId?: string,
     thinkingEffort?: ThinkingEffort,
   ) => Promise<TaskExecuteResult>
+  importIssueTask: (
+    projectId: ProjectId,
+    issue: string,
+    mode: TaskExecuteMode,
+    onMerge?: IssueMergeAction,
+  ) => Promise<TaskExecuteResult>
+  setTaskIssueMergeAction: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, onMerge: IssueMergeAction) => void
   setTaskStarred: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, starred: boolean) => void
+  applyTaskBulk: (items: TaskBulkItem[], operation: TaskBulkOperation, idempotencyKey?: string) => Promise<TaskBulkResult>
+  undoTaskBulk: (batchId: string) => Promise<TaskBulkResult>
+  rewindTask: (
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    checkpointId: string,
+    truncateConversation: boolean,
+  ) => void
+  forkTask: (
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    entryId: string,
+    target: TaskForkTarget,
+  ) => void
+  undoLastAction: () => void
+  setDeletionRetentionDays: (days: number) => void
   setTaskStatus: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, taskStatus: TaskStatus) => void
   submitFeedback: (args: {
     title: string
@@ -265,6 +327,146 @@ export function createLubanActions(args: {
     args.sendAction({ type: "telegram_unpair" })
   }
 
+  function startTelegramGroupPairing(projectId: ProjectId): Promise<string> {
+    return args.request<string>({ type: "telegram_group_pair_start", project_id: projectId })
+  }
+
+  function unpairTelegramGroup(chatId: number) {
+    args.sendAction({ type: "telegram_group_unpair", chat_id: chatId })
+  }
+
+  function addTelegramGroupMember(chatId: number, userId: number, displayName: string) {
+    if (!Number.isSafeInteger(userId) || userId <= 0) return
+    args.sendAction({
+      type: "telegram_group_member_add",
+      chat_id: chatId,
+      user_id: userId,
+      display_name: displayName.trim(),
+    })
+  }
+
+  function removeTelegramGroupMember(chatId: number, userId: number) {
+    args.sendAction({ type: "telegram_group_member_remove", chat_id: chatId, user_id: userId })
+  }
+
+  function setDesktopNotificationsEnabled(enabled: boolean) {
+    args.sendAction({ type: "desktop_notifications_enabled_changed", enabled })
+  }
+
+  function setNotificationQuietHours(quietHours: QuietHoursSnapshot | null) {
+    if (quietHours && quietHours.start_minute === quietHours.end_minute) return
+    args.sendAction({ type: "notification_quiet_hours_changed", quiet_hours: quietHours })
+  }
+
+  function setProjectNotificationsMuted(projectId: ProjectId, muted: boolean) {
+    args.sendAction({ type: "project_notifications_muted_changed", project_id: projectId, muted })
+  }
+
+  function setProjectTurnLimits(projectId: ProjectId, limits: TurnLimitsSnapshot) {
+    args.sendAction({ type: "project_turn_limits_changed", project_id: projectId, limits })
+  }
+
+  function setTaskTurnLimits(workdirId: WorkspaceId, taskId: WorkspaceThreadId, limits: TurnLimitsSnapshot) {
+    args.sendAction({ type: "task_turn_limits_changed", workdir_id: workdirId, task_id: taskId, limits })
+  }
+
+  function addReviewComment(
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    comment: { path: string; startLine: number; endLine: number; quote: string; body: string },
+  ) {
+    const body = comment.body.trim()
+    if (!body || comment.startLine < 1 || comment.endLine < comment.startLine) return
+    args.sendAction({
+      type: "task_review_comment_add",
+      workdir_id: workdirId,
+      task_id: taskId,
+      path: comment.path,
+      start_line: comment.startLine,
+      end_line: comment.endLine,
+      quote: comment.quote,
+      body,
+    })
+  }
+
+  function editReviewComment(workdirId: WorkspaceId, taskId: WorkspaceThreadId, commentId: number, body: string) {
+    const trimmed = body.trim()
+    if (!trimmed) return
+    args.sendAction({ type: "task_review_comment_edit", workdir_id: workdirId, task_id: taskId, comment_id: commentId, body: trimmed })
+  }
+
+  function resolveReviewComment(workdirId: WorkspaceId, taskId: WorkspaceThreadId, commentId: number, resolved: boolean) {
+    args.sendAction({
+      type: "task_review_comment_resolve",
+      workdir_id: workdirId,
+      task_id: taskId,
+      comment_id: commentId,
+      resolved,
+    })
+  }
+
+  function sendReviewComments(workdirId: WorkspaceId, taskId: WorkspaceThreadId, note: string | null) {
+    args.sendAction({ type: "task_review_comments_send", workdir_id: workdirId, task_id: taskId, note: note?.trim() || null })
+  }
+
+  function pullPullRequestReview(workdirId: WorkspaceId, taskId: WorkspaceThreadId) {
+    args.sendAction({ type: "task_pull_request_review_pull", workdir_id: workdirId, task_id: taskId })
+  }
+
+  function setPullRequestReviewSync(
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    sync: PullRequestReviewSyncSnapshot,
+  ) {
+    args.sendAction({ type: "task_pull_request_review_sync_changed", workdir_id: workdirId, task_id: taskId, sync })
+  }
+
+  function setCiAutoFix(workdirId: WorkspaceId, taskId: WorkspaceThreadId, enabled: boolean, maxAttempts: number) {
+    if (!Number.isInteger(maxAttempts) || maxAttempts < 1 || maxAttempts > 10) return
+    args.sendAction({
+      type: "task_ci_auto_fix_changed",
+      workdir_id: workdirId,
+      task_id: taskId,
+      enabled,
+      max_attempts: maxAttempts,
+    })
+  }
+
+  function setAttachmentTextLimits(limits: AttachmentTextLimitsSnapshot) {
+    if (!Number.isFinite(limits.max_source_bytes) || limits.max_source_bytes <= 0) return
+    if (!Number.isFinite(limits.max_inline_bytes) || limits.max_inline_bytes < 0) return
+    args.sendAction({
+      type: "attachment_text_limits_changed",
+      limits: {
+        max_source_bytes: Math.floor(limits.max_source_bytes),
+        max_inline_bytes: Math.floor(limits.max_inline_bytes),
+      },
+    })
+  }
+
+  function setUrlAttachmentDomains(domains: string[]) {
+    const cleaned = domains.map((d) => d.trim()).filter((d) => d.length > 0)
+    args.sendAction({ type: "url_attachment_domains_changed", domains: cleaned })
+  }
+
+  function refreshDiskUsage() {
+    args.sendAction({ type: "disk_usage_refresh" })
+  }
+
+  function purgeBuildArtifacts(workdirId: WorkspaceId | null, globs: string[]) {
+    const cleaned = globs.map((g) => g.trim()).filter((g) => g.length > 0)
+    args.sendAction({ type: "build_artifacts_purge", workdir_id: workdirId, globs: cleaned })
+  }
+
+  function deleteStaleWorktrees(olderThanDays: number) {
+    if (!Number.isFinite(olderThanDays) || olderThanDays < 0) return
+    args.sendAction({ type: "stale_worktrees_delete", older_than_days: Math.floor(olderThanDays) })
+  }
+
+  function gcContextBlobs() {
+    args.sendAction({ type: "context_blobs_gc" })
+  }
+
   function setTaskPromptTemplate(intentKind: TaskIntentKind, template: string) {
     const trimmed = template.trim()
     if (!trimmed) return
@@ -434,6 +636,75 @@ export function createLubanActions(args: {
     })
   }
 
+  function importIssueTask(
+    projectId: ProjectId,
+    issue: string,
+    mode: TaskExecuteMode,
+    onMerge: IssueMergeAction = "close",
+  ): Promise<TaskExecuteResult> {
+    return args.request<TaskExecuteResult>({
+      type: "task_import_issue",
+      project_id: projectId,
+      issue,
+      mode,
+      on_merge: onMerge,
+    })
+  }
+
+  function setTaskIssueMergeAction(workdirId: WorkspaceId, taskId: WorkspaceThreadId, onMerge: IssueMergeAction) {
+    args.sendAction({ type: "task_issue_merge_action_set", workdir_id: workdirId, task_id: taskId, on_merge: onMerge })
+  }
+
+  function applyTaskBulk(
+    items: TaskBulkItem[],
+    operation: TaskBulkOperation,
+    idempotencyKey?: string,
+  ): Promise<TaskBulkResult> {
+    const resolvedKey =
+      idempotencyKey ??
+      (typeof crypto !== "undefined" && typeof crypto.randomUUID === "function"
+        ? crypto.randomUUID()
+        : `bulk_${Math.random().toString(16).slice(2)}_${Date.now().toString(16)}`)
+    return args.request<TaskBulkResult>({
+      type: "task_bulk_apply",
+      idempotency_key: resolvedKey,
+      items,
+      operation,
+    })
+  }
+
+  function undoTaskBulk(batchId: string): Promise<TaskBulkResult> {
+    return args.request<TaskBulkResult>({ type: "task_bulk_undo", batch_id: batchId })
+  }
+
+  function rewindTask(
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    checkpointId: string,
+    truncateConversation: boolean,
+  ) {
+    args.sendAction({
+      type: "task_rewind",
+      workdir_id: workdirId,
+      task_id: taskId,
+      checkpoint_id: checkpointId,
+      truncate_conversation: truncateConversation,
+    })
+  }
+
+  function forkTask(workdirId: WorkspaceId, taskId: WorkspaceThreadId, entryId: string, target: TaskForkTarget) {
+    args.sendAction({ type: "task_fork", workdir_id: workdirId, task_id: taskId, entry_id: entryId, target })
+  }
+
+  function undoLastAction() {
+    args.sendAction({ type: "undo_last_action" })
+  }
+
+  function setDeletionRetentionDays(days: number) {
+    const retentionDays = Math.max(0, Math.min(365, Math.floor(days)))
+    args.sendAction({ type: "deletion_retention_changed", retention_days: retentionDays })
+  }
+
   function setTaskStarred(workdirId: WorkspaceId, taskId: WorkspaceThreadId, starred: boolean) {
     args.sendAction({ type: "task_star_set", workdir_id: workdirId, task_id: taskId, starred })
   }
@@ -950,6 +1221,28 @@ export function createLubanActions(args: {
     clearTelegramBotToken,
     startTelegramPairing,
     unpairTelegram,
+    startTelegramGroupPairing,
+    unpairTelegramGroup,
+    addTelegramGroupMember,
+    removeTelegramGroupMember,
+    setDesktopNotificationsEnabled,
+    setNotificationQuietHours,
+    setAttachmentTextLimits,
+    setUrlAttachmentDomains,
+    setProjectNotificationsMuted,
+    setProjectTurnLimits,
+    setTaskTurnLimits,
+    addReviewComment,
+    editReviewComment,
+    resolveReviewComment,
+    sendReviewComments,
+    pullPullRequestReview,
+    setPullRequestReviewSync,
+    setCiAutoFix,
+    refreshDiskUsage,
+    purgeBuildArtifacts,
+    deleteStaleWorktrees,
+    gcContextBlobs,
     setTaskPromptTemplate,
     setSystemPromptTemplate,
     checkCodex,
@@ -973,6 +1266,14 @@ export function createLubanActions(args: {
     readDroidConfigFile,
     writeDroidConfigFile,
     executeTask,
+    importIssueTask,
+    applyTaskBulk,
+    undoTaskBulk,
+    rewindTask,
+    forkTask,
+    undoLastAction,
+    setDeletionRetentionDays,
+    setTaskIssueMergeAction,
     setTaskStarred,
     setTaskStatus,
     submitFeedback,
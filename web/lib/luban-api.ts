@@ -31,6 +31,14 @@ export type AgentSettingsSnapshot = {
   default_thinking_effort?: ThinkingEffort
   default_runner?: AgentRunnerKind
   amp_mode?: string
+  models?: AgentModelSnapshot[]
+}
+
+export type AgentModelSnapshot = {
+  runner: AgentRunnerKind
+  id: string
+  label: string
+  supported_thinking_efforts?: ThinkingEffort[]
 }
 
 export type TaskPromptTemplateSnapshot = {
@@ -54,6 +62,15 @@ export type TaskSettingsSnapshot = {
   default_prompt_templates: TaskPromptTemplateSnapshot[]
   system_prompt_templates: SystemPromptTemplateSnapshot[]
   default_system_prompt_templates: SystemPromptTemplateSnapshot[]
+  prompt_template_variables?: string[]
+  system_prompt_template_variables?: string[]
+  project_prompt_settings?: ProjectPromptSettingsSnapshot[]
+}
+
+export type ProjectPromptSettingsSnapshot = {
+  project_id: ProjectId
+  prompt_templates: TaskPromptTemplateSnapshot[]
+  variables: Record<string, string>
 }
 
 export type TelegramIntegrationSnapshot = {
@@ -63,12 +80,76 @@ export type TelegramIntegrationSnapshot = {
   paired_chat_id?: number
   config_rev: number
   last_error?: string
+  group_chats?: TelegramGroupChatSnapshot[]
+}
+
+export type TelegramGroupMemberSnapshot = {
+  user_id: number
+  display_name: string
+}
+
+export type TelegramGroupChatSnapshot = {
+  chat_id: number
+  project_slug: string
+  title: string
+  members: TelegramGroupMemberSnapshot[]
+  topic_count: number
 }
 
 export type IntegrationsSnapshot = {
   telegram: TelegramIntegrationSnapshot
 }
 
+export type QuietHoursSnapshot = {
+  start_minute: number
+  end_minute: number
+  utc_offset_minutes: number
+}
+
+export type NotificationsSnapshot = {
+  desktop_enabled: boolean
+  quiet_hours: QuietHoursSnapshot | null
+}
+
+export type AttachmentTextLimitsSnapshot = {
+  max_source_bytes: number
+  max_inline_bytes: number
+}
+
+export type UrlAttachmentSettingsSnapshot = {
+  allowed_domains: string[]
+}
+
+export type DeletionSettingsSnapshot = {
+  retention_days: number
+}
+
+export type TurnLimitsSnapshot = {
+  turn_timeout_secs?: number | null
+  max_tool_calls_per_turn?: number | null
+  turn_token_budget?: number | null
+  task_token_budget?: number | null
+}
+
+export type TurnLimitKind = "timeout" | "tool_calls" | "turn_tokens" | "task_tokens"
+
+export type AuditEntrySnapshot = {
+  id: number
+  created_at_unix_ms: number
+  origin: "web" | "telegram" | "slack" | "cli" | "mcp" | "system"
+  action: string
+  summary: string
+  workdir_id?: WorkspaceId | null
+  task_id?: WorkspaceThreadId | null
+  undoable: boolean
+  undone_at_unix_ms?: number | null
+}
+
+export type AuditLogSnapshot = {
+  entries: AuditEntrySnapshot[]
+  next_before?: number | null
+}
+
 export type AppSnapshot = {
   rev: number
   projects: ProjectSnapshot[]
@@ -77,6 +158,11 @@ export type AppSnapshot = {
   task: TaskSettingsSnapshot
   ui: UiSnapshot
   integrations: IntegrationsSnapshot
+  notifications?: NotificationsSnapshot
+  attachment_text_limits?: AttachmentTextLimitsSnapshot
+  url_attachments?: UrlAttachmentSettingsSnapshot
+  task_issue_links?: TaskIssueLinkSnapshot[]
+  deletion?: DeletionSettingsSnapshot
 }
 
 export type UiSnapshot = {
@@ -95,6 +181,8 @@ export type ProjectSnapshot = {
   expanded: boolean
   create_workdir_status: OperationStatus
   workdirs: WorkspaceSnapshot[]
+  notifications_muted?: boolean
+  turn_limits?: TurnLimitsSnapshot
 }
 
 export type WorkspaceSnapshot = {
@@ -109,6 +197,13 @@ export type WorkspaceSnapshot = {
   agent_run_status: OperationStatus
   has_unread_completion: boolean
   pull_request: PullRequestSnapshot | null
+  disk_usage?: DiskUsageSnapshot | null
+}
+
+export type DiskUsageSnapshot = {
+  workdir_bytes: number
+  context_blobs_bytes: number
+  measured_at_unix_ms: number
 }
 
 export type FileChangeStatus = "modified" | "added" | "deleted" | "renamed"
@@ -220,9 +315,18 @@ export type ThreadMeta = {
   task_status: TaskStatus
   turn_status: TurnStatus
   last_turn_result: TurnResult | null
+  forked_from?: TaskForkOrigin | null
 }
 
-export type AttachmentKind = "image" | "text" | "file"
+export type TaskForkOrigin = {
+  workdir_id: WorkspaceId
+  task_id: WorkspaceThreadId
+  entry_id: string
+}
+
+export type TaskForkTarget = "same_workdir" | "new_workdir"
+
+export type AttachmentKind = "image" | "text" | "file" | "url"
 
 export type AttachmentRef = {
   id: string
@@ -253,6 +357,47 @@ export type ConversationSnapshot = {
   queue_paused: boolean
   remote_thread_id: string | null
   title: string
+  turn_limits?: TurnLimitsSnapshot
+  tokens_used?: number
+  forked_from?: TaskForkOrigin | null
+  review_comments?: ReviewCommentSnapshot[]
+  pull_request_review_sync?: PullRequestReviewSyncSnapshot
+  ci_auto_fix?: CiAutoFixSnapshot
+}
+
+export type CiAutoFixSnapshot = {
+  enabled: boolean
+  max_attempts: number
+  attempts_used: number
+}
+
+export type CiAutoFixStopReason = "passed" | "attempts_exhausted"
+
+export type PullRequestReviewSyncSnapshot = {
+  auto_pull: boolean
+  reply_when_addressed: boolean
+}
+
+export type ReviewCommentOriginSnapshot = {
+  pr_number: number
+  comment_id: number
+  author: string
+  url: string
+  replied: boolean
+}
+
+export type ReviewCommentSnapshot = {
+  id: number
+  path: string
+  start_line: number
+  end_line: number
+  quote: string
+  body: string
+  resolved: boolean
+  sent: boolean
+  created_at_unix_ms: number
+  updated_at_unix_ms: number
+  pull_request?: ReviewCommentOriginSnapshot | null
 }
 
 export type ConversationSystemEvent =
@@ -266,6 +411,39 @@ export type ConversationSystemEvent =
       title: string
       explanation_markdown: string
     }
+  | { event_type: "turn_interrupted"; resumable: boolean }
+  | { event_type: "turn_checkpoint"; checkpoint_id: string; commit: string }
+  | { event_type: "turn_rewound"; checkpoint_id: string; truncated: boolean }
+  | {
+      event_type: "turn_changes"
+      checkpoint_id: string
+      commit: string
+      files_changed: number
+      additions: number
+      deletions: number
+    }
+  | { event_type: "turn_limit_reached"; kind: TurnLimitKind; limit: number; observed: number }
+  | {
+      event_type: "pull_request_review_comment"
+      pr_number: number
+      review_comment_id: number
+      author: string
+      path: string
+      start_line: number
+      end_line: number
+      body: string
+      url: string
+    }
+  | {
+      event_type: "ci_fix_attempt"
+      pr_number: number
+      attempt: number
+      max_attempts: number
+      run_id: number
+      workflow: string
+      url: string
+    }
+  | { event_type: "ci_auto_fix_stopped"; reason: CiAutoFixStopReason; attempts: number }
 
 export type ConversationSystemEventEntry = {
   entry_id: string
@@ -318,6 +496,18 @@ export type TaskIssueInfo = {
   url: string
 }
 
+export type IssueMergeAction = "close" | "comment" | "leave"
+
+export type TaskIssueLinkSnapshot = {
+  workdir_id: WorkspaceId
+  task_id: WorkspaceThreadId
+  number: number
+  title: string
+  url: string
+  on_merge: IssueMergeAction
+  resolved_by_pr: number | null
+}
+
 export type FeedbackType = "bug" | "feature" | "question"
 
 export type FeedbackSubmitAction = "create_issue" | "fix_it"
@@ -338,6 +528,31 @@ export type TaskExecuteResult = {
   mode: TaskExecuteMode
 }
 
+export type TaskBulkItem = {
+  workdir_id: WorkspaceId
+  task_id: WorkspaceThreadId
+}
+
+export type TaskBulkOperation =
+  | { type: "set_status"; task_status: TaskStatus }
+  | { type: "set_starred"; starred: boolean }
+  | { type: "archive_workdir" }
+  | { type: "send_prompt"; text: string }
+
+export type TaskBulkItemResult = {
+  workdir_id: WorkspaceId
+  task_id: WorkspaceThreadId
+  ok: boolean
+  message: string | null
+  previous_status: TaskStatus | null
+}
+
+export type TaskBulkResult = {
+  batch_id: string
+  items: TaskBulkItemResult[]
+  undoable: boolean
+}
+
 export type NewTaskDraftSnapshot = {
   id: string
   text: string
@@ -420,7 +635,7 @@ export type ConversationEntry =
   | { type: "agent_event"; entry_id: string; created_at_unix_ms: number; runner?: AgentRunnerKind; event: AgentEvent }
 
 export type UserEvent =
-  | { type: "message"; text: string; attachments: AttachmentRef[] }
+  | { type: "message"; text: string; attachments: AttachmentRef[]; author?: string }
   | { type: "terminal_command_started"; id: string; command: string; reconnect: string }
   | {
       type: "terminal_command_finished"
@@ -451,11 +666,80 @@ export type ClientAction =
       attachments?: AttachmentRef[]
       model_id?: string
       thinking_effort?: ThinkingEffort
+      intent_kind?: TaskIntentKind
     }
   | { type: "telegram_bot_token_set"; token: string }
   | { type: "telegram_bot_token_clear" }
   | { type: "telegram_pair_start" }
   | { type: "telegram_unpair" }
+  | { type: "telegram_group_pair_start"; project_id: ProjectId }
+  | { type: "telegram_group_unpair"; chat_id: number }
+  | { type: "telegram_group_member_add"; chat_id: number; user_id: number; display_name: string }
+  | { type: "telegram_group_member_remove"; chat_id: number; user_id: number }
+  | { type: "desktop_notifications_enabled_changed"; enabled: boolean }
+  | { type: "notification_quiet_hours_changed"; quiet_hours: QuietHoursSnapshot | null }
+  | { type: "project_notifications_muted_changed"; project_id: ProjectId; muted: boolean }
+  | { type: "attachment_text_limits_changed"; limits: AttachmentTextLimitsSnapshot }
+  | { type: "url_attachment_domains_changed"; domains: string[] }
+  | { type: "task_import_issue"; project_id: ProjectId; issue: string; mode: TaskExecuteMode; on_merge?: IssueMergeAction }
+  | { type: "task_issue_merge_action_set"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId; on_merge: IssueMergeAction }
+  | { type: "task_bulk_apply"; idempotency_key?: string | null; items: TaskBulkItem[]; operation: TaskBulkOperation }
+  | { type: "task_bulk_undo"; batch_id: string }
+  | {
+      type: "task_rewind"
+      workdir_id: WorkspaceId
+      task_id: WorkspaceThreadId
+      checkpoint_id: string
+      truncate_conversation: boolean
+    }
+  | {
+      type: "task_fork"
+      workdir_id: WorkspaceId
+      task_id: WorkspaceThreadId
+      entry_id: string
+      target: TaskForkTarget
+    }
+  | { type: "undo_last_action" }
+  | { type: "deletion_retention_changed"; retention_days: number }
+  | { type: "project_turn_limits_changed"; project_id: ProjectId; limits: TurnLimitsSnapshot }
+  | { type: "task_turn_limits_changed"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId; limits: TurnLimitsSnapshot }
+  | {
+      type: "task_review_comment_add"
+      workdir_id: WorkspaceId
+      task_id: WorkspaceThreadId
+      path: string
+      start_line: number
+      end_line: number
+      quote: string
+      body: string
+    }
+  | { type: "task_review_comment_edit"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId; comment_id: number; body: string }
+  | {
+      type: "task_review_comment_resolve"
+      workdir_id: WorkspaceId
+      task_id: WorkspaceThreadId
+      comment_id: number
+      resolved: boolean
+    }
+  | { type: "task_review_comments_send"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId; note?: string | null }
+  | { type: "task_pull_request_review_pull"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId }
+  | {
+      type: "task_pull_request_review_sync_changed"
+      workdir_id: WorkspaceId
+      task_id: WorkspaceThreadId
+      sync: PullRequestReviewSyncSnapshot
+    }
+  | {
+      type: "task_ci_auto_fix_changed"
+      workdir_id: WorkspaceId
+      task_id: WorkspaceThreadId
+      enabled: boolean
+      max_attempts: number
+    }
+  | { type: "disk_usage_refresh" }
+  | { type: "build_artifacts_purge"; workdir_id?: WorkspaceId | null; globs?: string[] }
+  | { type: "stale_worktrees_delete"; older_than_days: number }
+  | { type: "context_blobs_gc" }
   | { type: "task_star_set"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId; starred: boolean }
   | { type: "task_status_set"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId; task_status: TaskStatus }
   | {
@@ -528,6 +812,7 @@ export type ClientAction =
   | { type: "workdir_rename_branch"; workdir_id: WorkspaceId; branch_name: string }
   | { type: "workdir_ai_rename_branch"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId }
   | { type: "cancel_agent_turn"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId }
+  | { type: "resume_interrupted_turn"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId }
   | { type: "create_task"; workdir_id: WorkspaceId; model_id?: string; thinking_effort?: ThinkingEffort }
   | { type: "activate_task"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId }
   | { type: "close_task_tab"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId }
@@ -550,7 +835,13 @@ export type ClientAction =
   | { type: "droid_enabled_changed"; enabled: boolean }
   | { type: "agent_runner_changed"; runner: AgentRunnerKind }
   | { type: "agent_amp_mode_changed"; mode: string }
-  | { type: "task_prompt_template_changed"; intent_kind: TaskIntentKind; template: string }
+  | {
+      type: "task_prompt_template_changed"
+      intent_kind: TaskIntentKind
+      template: string
+      project_id?: ProjectId
+    }
+  | { type: "project_prompt_variables_changed"; project_id: ProjectId; variables: Record<string, string> }
   | { type: "system_prompt_template_changed"; kind: SystemTaskKind; template: string }
   | { type: "codex_check" }
   | { type: "codex_config_tree" }
@@ -581,9 +872,11 @@ export type ServerEvent =
   | { type: "conversation_changed"; snapshot: ConversationSnapshot }
   | { type: "task_document_changed"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId; kind: TaskDocumentKind }
   | { type: "toast"; message: string }
+  | { type: "task_focus_requested"; workdir_id: WorkspaceId; task_id: WorkspaceThreadId }
   | { type: "project_path_picked"; request_id: string; path: string | null }
   | { type: "add_project_and_open_ready"; request_id: string; project_id: ProjectId; workdir_id: WorkspaceId }
   | { type: "task_executed"; request_id: string; result: TaskExecuteResult }
+  | { type: "task_bulk_applied"; request_id: string; result: TaskBulkResult }
   | { type: "feedback_submitted"; request_id: string; result: FeedbackSubmitResult }
   | { type: "codex_check_ready"; request_id: string; ok: boolean; message: string | null }
   | { type: "codex_config_tree_ready"; request_id: string; tree: CodexConfigEntrySnapshot[] }
@@ -11,6 +11,7 @@ import type {
   AppearanceFontsSnapshot,
   AppearanceTheme,
   AttachmentRef,
+  AttachmentTextLimitsSnapshot,
   AgentRunnerKind,
   AgentRunConfigSnapshot,
   ClaudeConfigEntrySnapshot,
@@ -20,15 +21,23 @@ import type {
   FeedbackSubmitAction,
   FeedbackSubmitResult,
   FeedbackType,
+  IssueMergeAction,
   ProjectId,
+  PullRequestReviewSyncSnapshot,
+  QuietHoursSnapshot,
   ServerEvent,
   SystemTaskKind,
+  TaskBulkItem,
+  TaskBulkOperation,
+  TaskBulkResult,
   TaskExecuteMode,
   TaskExecuteResult,
+  TaskForkTarget,
   TaskIntentKind,
   TaskStatus,
   ThreadMeta,
   ThinkingEffort,
+  TurnLimitsSnapshot,
   OpenTarget,
   WorkspaceId,
   WorkspaceThreadId,
@@ -42,6 +51,7 @@ import { useLubanStore } from "./luban-store"
 import { createLubanServerEventHandler } from "./luban-store-events"
 import { useExternalLinkInterceptor } from "./external-link-interceptor"
 import { useLubanTransport } from "./luban-transport"
+import { focusAppWindow } from "./focus-app-window"
 import { focusChatInput } from "./focus-chat-input"
 import { normalizeWorkspaceTabsSnapshot } from "./workspace-tabs"
 
@@ -81,7 +91,30 @@ type LubanContextValue = {
     modelId?: string,
     thinkingEffort?: ThinkingEffort,
   ) => Promise<TaskExecuteResult>
+  importIssueTask: (
+    projectId: ProjectId,
+    issue: string,
+    mode: TaskExecuteMode,
+    onMerge?: IssueMergeAction,
+  ) => Promise<TaskExecuteResult>
+  setTaskIssueMergeAction: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, onMerge: IssueMergeAction) => void
   setTaskStarred: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, starred: boolean) => void
+  applyTaskBulk: (items: TaskBulkItem[], operation: TaskBulkOperation, idempotencyKey?: string) => Promise<TaskBulkResult>
+  undoTaskBulk: (batchId: string) => Promise<TaskBulkResult>
+  rewindTask: (
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    checkpointId: string,
+    truncateConversation: boolean,
+  ) => void
+  forkTask: (
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    entryId: string,
+    target: TaskForkTarget,
+  ) => void
+  undoLastAction: () => void
+  setDeletionRetentionDays: (days: number) => void
   setTaskStatus: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, taskStatus: TaskStatus) => void
   submitFeedback: (args: {
     title: string
@@ -160,6 +193,36 @@ type LubanContextValue = {
   clearTelegramBotToken: () => void
   startTelegramPairing: () => Promise<string>
   unpairTelegram: () => void
+  startTelegramGroupPairing: (projectId: ProjectId) => Promise<string>
+  unpairTelegramGroup: (chatId: number) => void
+  addTelegramGroupMember: (chatId: number, userId: number, displayName: string) => void
+  removeTelegramGroupMember: (chatId: number, userId: number) => void
+  setDesktopNotificationsEnabled: (enabled: boolean) => void
+  setNotificationQuietHours: (quietHours: QuietHoursSnapshot | null) => void
+  setAttachmentTextLimits: (limits: AttachmentTextLimitsSnapshot) => void
+  setUrlAttachmentDomains: (domains: string[]) => void
+  setProjectNotificationsMuted: (projectId: ProjectId, muted: boolean) => void
+  setProjectTurnLimits: (projectId: ProjectId, limits: TurnLimitsSnapshot) => void
+  setTaskTurnLimits: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, limits: TurnLimitsSnapshot) => void
+  addReviewComment: (
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    comment: { path: string; startLine: number; endLine: number; quote: string; body: string },
+  ) => void
+  editReviewComment: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, commentId: number, body: string) => void
+  resolveReviewComment: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, commentId: number, resolved: boolean) => void
+  sendReviewComments: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, note: string | null) => void
+  pullPullRequestReview: (workdirId: WorkspaceId, taskId: WorkspaceThreadId) => void
+  setPullRequestReviewSync: (
+    workdirId: WorkspaceId,
+    taskId: WorkspaceThreadId,
+    sync: PullRequestReviewSyncSnapshot,
+  ) => void
+  setCiAutoFix: (workdirId: WorkspaceId, taskId: WorkspaceThreadId, enabled: boolean, maxAttempts: number) => void
+  refreshDiskUsage: () => void
+  purgeBuildArtifacts: (workdirId: WorkspaceId | null, globs: string[]) => void
+  deleteStaleWorktrees: (olderThanDays: number) => void
+  gcContextBlobs: () => void
   setTaskPromptTemplate: (intentKind: TaskIntentKind, template: string) => void
   setSystemPromptTemplate: (kind: SystemTaskKind, template: string) => void
   checkCodex: () => Promise<{ ok: boolean; message: string | null }>
@@ -310,6 +373,13 @@ export function LubanProvider({ children }: { children: React.ReactNode }) {
         onSelectThreadInWorkspace: (workspaceId, threadId) => {
           void actions.selectThreadInWorkspace(workspaceId, threadId)
         },
+        onFocusTask: (workspaceId, threadId) => {
+          void focusAppWindow()
+          void (async () => {
+            await actions.openWorkdir(workspaceId)
+            await actions.selectThreadInWorkspace(workspaceId, threadId)
+          })()
+        },
       }),
     [actions, store],
   )
@@ -416,6 +486,14 @@ export function LubanProvider({ children }: { children: React.ReactNode }) {
     archiveWorkdir: actions.archiveWorkdir,
     toggleProjectExpanded: actions.toggleProjectExpanded,
     executeTask: actions.executeTask,
+    importIssueTask: actions.importIssueTask,
+    applyTaskBulk: actions.applyTaskBulk,
+    undoTaskBulk: actions.undoTaskBulk,
+    rewindTask: actions.rewindTask,
+    forkTask: actions.forkTask,
+    undoLastAction: actions.undoLastAction,
+    setDeletionRetentionDays: actions.setDeletionRetentionDays,
+    setTaskIssueMergeAction: actions.setTaskIssueMergeAction,
     setTaskStarred: actions.setTaskStarred,
     setTaskStatus: actions.setTaskStatus,
     submitFeedback: actions.submitFeedback,
@@ -456,6 +534,28 @@ export function LubanProvider({ children }: { children: React.ReactNode }) {
     clearTelegramBotToken: actions.clearTelegramBotToken,
     startTelegramPairing: actions.startTelegramPairing,
     unpairTelegram: actions.unpairTelegram,
+    startTelegramGroupPairing: actions.startTelegramGroupPairing,
+    unpairTelegramGroup: actions.unpairTelegramGroup,
+    addTelegramGroupMember: actions.addTelegramGroupMember,
+    removeTelegramGroupMember: actions.removeTelegramGroupMember,
+    setDesktopNotificationsEnabled: actions.setDesktopNotificationsEnabled,
+    setNotificationQuietHours: actions.setNotificationQuietHours,
+    setAttachmentTextLimits: actions.setAttachmentTextLimits,
+    setUrlAttachmentDomains: actions.setUrlAttachmentDomains,
+    setProjectNotificationsMuted: actions.setProjectNotificationsMuted,
+    setProjectTurnLimits: actions.setProjectTurnLimits,
+    setTaskTurnLimits: actions.setTaskTurnLimits,
+    addReviewComment: actions.addReviewComment,
+    editReviewComment: actions.editReviewComment,
+    resolveReviewComment: actions.resolveReviewComment,
+    sendReviewComments: actions.sendReviewComments,
+    pullPullRequestReview: actions.pullPullRequestReview,
+    setPullRequestReviewSync: actions.setPullRequestReviewSync,
+    setCiAutoFix: actions.setCiAutoFix,
+    refreshDiskUsage: actions.refreshDiskUsage,
+    purgeBuildArtifacts: actions.purgeBuildArtifacts,
+    deleteStaleWorktrees: actions.deleteStaleWorktrees,
+    gcContextBlobs: actions.gcContextBlobs,
     setTaskPromptTemplate: actions.setTaskPromptTemplate,
     setSystemPromptTemplate: actions.setSystemPromptTemplate,
     checkCodex: actions.checkCodex,
@@ -2,6 +2,7 @@ import type {
   AppSnapshot,
   AttachmentKind,
   AttachmentRef,
+  AuditLogSnapshot,
   CodexCustomPromptSnapshot,
   ConversationSnapshot,
   MentionItemSnapshot,
@@ -20,12 +21,14 @@ import type {
 import { isMockMode } from "./luban-mode"
 import {
   mockFetchApp,
+  mockFetchAuditLog,
   mockFetchCodexCustomPrompts,
   mockFetchConversation,
   mockFetchMentionItems,
   mockFetchTasks,
   mockFetchThreads,
   mockFetchWorkspaceDiff,
+  mockFetchTurnDiff,
   mockCreateNewTaskDraft,
   mockDeleteNewTaskDraft,
   mockFetchNewTaskDrafts,
@@ -37,6 +40,7 @@ import {
   mockUpdateNewTaskDraft,
   mockClearNewTaskStash,
   mockUploadAttachment,
+  mockAttachUrl,
 } from "./mock/mock-runtime"
 
 export async function fetchApp(): Promise<AppSnapshot> {
@@ -183,6 +187,22 @@ export async function uploadAttachment(args: {
   return (await res.json()) as AttachmentRef
 }
 
+export async function attachUrl(args: { workspaceId: number; url: string }): Promise<AttachmentRef> {
+  if (isMockMode()) return await mockAttachUrl(args)
+  const res = await fetch(`/api/workdirs/${args.workspaceId}/url_attachments`, {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ url: args.url }),
+  })
+  if (!res.ok) {
+    const text = await res.text().catch(() => "")
+    throw new Error(
+      `POST /api/workdirs/${args.workspaceId}/url_attachments failed: ${res.status}${text ? `: ${text}` : ""}`,
+    )
+  }
+  return (await res.json()) as AttachmentRef
+}
+
 export async function fetchWorkspaceDiff(workspaceId: number): Promise<WorkspaceDiffSnapshot> {
   if (isMockMode()) return await mockFetchWorkspaceDiff(workspaceId)
   const res = await fetch(`/api/workdirs/${workspaceId}/diff`)
@@ -190,6 +210,21 @@ export async function fetchWorkspaceDiff(workspaceId: number): Promise<Workspace
   return (await res.json()) as WorkspaceDiffSnapshot
 }
 
+export async function fetchTurnDiff(args: {
+  workspaceId: number
+  taskId: number
+  fromCheckpointId: string
+  toCheckpointId?: string
+}): Promise<WorkspaceDiffSnapshot> {
+  if (isMockMode()) return await mockFetchTurnDiff(args.workspaceId)
+  const params = new URLSearchParams({ from: args.fromCheckpointId })
+  if (args.toCheckpointId) params.set("to", args.toCheckpointId)
+  const path = `/api/workdirs/${args.workspaceId}/tasks/${args.taskId}/turn_diff`
+  const res = await fetch(`${path}?${params.toString()}`)
+  if (!res.ok) throw new Error(`GET ${path} failed: ${res.status}`)
+  return (await res.json()) as WorkspaceDiffSnapshot
+}
+
 export async function fetchWorkspaceChanges(workspaceId: number): Promise<WorkspaceChangesSnapshot> {
   if (isMockMode()) return await mockFetchWorkspaceChanges(workspaceId)
   const res = await fetch(`/api/workdirs/${workspaceId}/changes`)
@@ -239,6 +274,17 @@ export async function fetchTasks(args: {
   return (await res.json()) as TasksSnapshot
 }
 
+export async function fetchAuditLog(args: { before?: number; limit?: number } = {}): Promise<AuditLogSnapshot> {
+  if (isMockMode()) return await mockFetchAuditLog(args)
+  const params = new URLSearchParams()
+  if (args.before != null) params.set("before", String(args.before))
+  if (args.limit != null) params.set("limit", String(args.limit))
+  const suffix = params.toString() ? `?${params.toString()}` : ""
+  const res = await fetch(`/api/audit${suffix}`)
+  if (!res.ok) throw new Error(`GET /api/audit failed: ${res.status}`)
+  return (await res.json()) as AuditLogSnapshot
+}
+
 export async function fetchNewTaskDrafts(): Promise<NewTaskDraftsSnapshot> {
   if (isMockMode()) return await mockFetchNewTaskDrafts()
   const res = await fetch("/api/new_task/drafts")
@@ -9,6 +9,7 @@ export function createLubanServerEventHandler(args: {
   store: LubanStore
   onToast: (message: string) => void
   onSelectThreadInWorkspace: (workspaceId: WorkspaceId, threadId: number) => void
+  onFocusTask: (workspaceId: WorkspaceId, threadId: number) => void
 }): (event: ServerEvent) => void {
   return (event) => {
     switch (event.type) {
@@ -69,8 +70,13 @@ export function createLubanServerEventHandler(args: {
         args.onToast(event.message)
         return
       }
+      case "task_focus_requested": {
+        args.onFocusTask(event.workdir_id, event.task_id)
+        return
+      }
       case "project_path_picked":
       case "task_executed":
+      case "task_bulk_applied":
         return
     }
   }
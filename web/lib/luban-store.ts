@@ -11,6 +11,7 @@ import type {
   WorkspaceTabsSnapshot,
 } from "./luban-api"
 import { fetchApp } from "./luban-http"
+import { applyAgentModelCatalog } from "./agent-settings"
 
 export type PendingCreateThread = {
   workspaceId: WorkspaceId
@@ -118,6 +119,7 @@ export function useLubanStore(): LubanStore {
   }
 
   function setApp(next: React.SetStateAction<AppSnapshot | null>) {
+    if (next && typeof next !== "function") applyAgentModelCatalog(next.agent.models)
     _setApp(next)
   }
 
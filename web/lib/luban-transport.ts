@@ -193,6 +193,7 @@ export function useLubanTransport(args: {
             event.type === "project_path_picked" ||
             event.type === "add_project_and_open_ready" ||
             event.type === "task_executed" ||
+            event.type === "task_bulk_applied" ||
             event.type === "feedback_submitted" ||
             event.type === "telegram_pair_ready" ||
             event.type === "codex_check_ready" ||
@@ -223,6 +224,7 @@ export function useLubanTransport(args: {
               if (event.type === "add_project_and_open_ready")
                 pending.resolve({ projectId: event.project_id, workdirId: event.workdir_id })
               if (event.type === "task_executed") pending.resolve(event.result)
+              if (event.type === "task_bulk_applied") pending.resolve(event.result)
               if (event.type === "feedback_submitted") pending.resolve(event.result)
               if (event.type === "telegram_pair_ready") pending.resolve(event.url)
               if (event.type === "codex_check_ready") pending.resolve({ ok: event.ok, message: event.message })
@@ -3,6 +3,8 @@
 import type {
   AmpConfigEntrySnapshot,
   AppSnapshot,
+  AuditEntrySnapshot,
+  AuditLogSnapshot,
   AttachmentKind,
   AttachmentRef,
   ClientAction,
@@ -20,6 +22,9 @@ import type {
   NewTaskStashSnapshot,
   ProjectId,
   ServerEvent,
+  TaskBulkItem,
+  TaskBulkItemResult,
+  TaskBulkResult,
   TaskStatus,
   TaskDocumentKind,
   TaskDocumentSnapshot,
@@ -38,11 +43,15 @@ import type {
 } from "../luban-api"
 import { defaultMockFixtures } from "./fixtures"
 
+type MockAuditEntry = AuditEntrySnapshot & { undo: (() => void) | null }
+
 type RuntimeState = {
   rev: number
   app: AppSnapshot
   threadsByWorkdir: Map<WorkspaceId, ThreadsSnapshot>
   starredTasks: Set<string>
+  taskBulkUndo: Map<string, TaskBulkItemResult[]>
+  auditLog: MockAuditEntry[]
   conversationsByWorkdirTask: Map<string, ConversationSnapshot>
   attachmentUrlsById: Map<string, string>
   workdirChangesById: Map<WorkspaceId, WorkspaceChangesSnapshot>
@@ -175,6 +184,8 @@ function initRuntime(): RuntimeState {
     app: clone(fixtures.app),
     threadsByWorkdir,
     starredTasks: new Set<string>(),
+    taskBulkUndo: new Map<string, TaskBulkItemResult[]>(),
+    auditLog: [],
     conversationsByWorkdirTask,
     attachmentUrlsById,
     workdirChangesById,
@@ -275,6 +286,27 @@ export async function mockFetchApp(): Promise<AppSnapshot> {
   return clone(getRuntime().app)
 }
 
+export async function mockFetchAuditLog(args: { before?: number; limit?: number } = {}): Promise<AuditLogSnapshot> {
+  const state = getRuntime()
+  const limit = Math.max(1, Math.min(500, args.limit ?? 50))
+  const newestFirst = [...state.auditLog]
+    .reverse()
+    .filter((e) => args.before == null || e.id < args.before)
+  const page = newestFirst.slice(0, limit)
+  const entries = page.map(({ undo, ...entry }) => ({ ...clone(entry), undoable: undo != null && entry.undone_at_unix_ms == null }))
+  const last = page[page.length - 1]
+  return { entries, next_before: newestFirst.length > limit && last ? last.id : null }
+}
+
+function recordMockAudit(
+  state: RuntimeState,
+  entry: Omit<AuditEntrySnapshot, "id" | "created_at_unix_ms" | "origin" | "undoable" | "undone_at_unix_ms">,
+  undo: (() => void) | null,
+) {
+  const id = (state.auditLog[state.auditLog.length - 1]?.id ?? 0) + 1
+  state.auditLog.push({ ...entry, id, created_at_unix_ms: Date.now(), origin: "web", undoable: undo != null, undo })
+}
+
 export async function mockFetchTasks(args: {
   projectId?: string
   workdirStatus?: "active" | "archived" | "all"
@@ -474,6 +506,10 @@ export async function mockFetchWorkspaceDiff(workdirId: WorkspaceId): Promise<Wo
   return clone(state.workdirDiffById.get(workdirId) ?? { workdir_id: workdirId, files: [] })
 }
 
+export async function mockFetchTurnDiff(workdirId: WorkspaceId): Promise<WorkspaceDiffSnapshot> {
+  return await mockFetchWorkspaceDiff(workdirId)
+}
+
 export async function mockFetchCodexCustomPrompts(): Promise<CodexCustomPromptSnapshot[]> {
   return clone(getRuntime().codexCustomPrompts)
 }
@@ -506,6 +542,38 @@ export async function mockUploadAttachment(args: { workspaceId: number; file: Fi
   return clone(att)
 }
 
+export async function mockAttachUrl(args: { workspaceId: number; url: string }): Promise<AttachmentRef> {
+  const state = getRuntime()
+  const parsed = new URL(args.url)
+  const allowed = state.app.url_attachments?.allowed_domains ?? []
+  const host = parsed.hostname.toLowerCase()
+  if (!allowed.some((d) => host === d || host.endsWith(`.${d}`))) {
+    throw new Error(`${host} is not in the URL attachment allowlist`)
+  }
+  const markdown = [
+    "---",
+    `source_url: ${args.url}`,
+    `fetched_at: ${new Date().toISOString()}`,
+    "---",
+    "",
+    `# ${host}`,
+    "",
+    "Mock snapshot.",
+    "",
+  ].join("\n")
+  const blob = new Blob([markdown], { type: "text/markdown" })
+  const att: AttachmentRef = {
+    id: `mock_att_${Math.random().toString(16).slice(2)}`,
+    kind: "url",
+    name: args.url,
+    extension: "md",
+    mime: "text/markdown",
+    byte_len: blob.size,
+  }
+  state.attachmentUrlsById.set(att.id, URL.createObjectURL(blob))
+  return clone(att)
+}
+
 function ensureThreadsSnapshot(state: RuntimeState, workdirId: WorkspaceId): ThreadsSnapshot {
   const existing = state.threadsByWorkdir.get(workdirId) ?? null
   if (existing) return existing
@@ -620,6 +688,264 @@ export function mockDispatchAction(args: { action: ClientAction; onEvent: (event
     return
   }
 
+  if (a.type === "desktop_notifications_enabled_changed") {
+    const prev = state.app.notifications ?? { desktop_enabled: false, quiet_hours: null }
+    state.app.notifications = { ...prev, desktop_enabled: a.enabled }
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "notification_quiet_hours_changed") {
+    const prev = state.app.notifications ?? { desktop_enabled: false, quiet_hours: null }
+    state.app.notifications = { ...prev, quiet_hours: a.quiet_hours }
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "project_notifications_muted_changed") {
+    const project = state.app.projects.find((p) => p.id === a.project_id)
+    if (project) project.notifications_muted = a.muted
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "project_turn_limits_changed") {
+    const project = state.app.projects.find((p) => p.id === a.project_id)
+    if (project) project.turn_limits = a.limits
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "task_turn_limits_changed") {
+    const key = workdirTaskKey(a.workdir_id, a.task_id)
+    const convo = state.conversationsByWorkdirTask.get(key) ?? null
+    if (!convo) return
+    const rev = bumpRev(state)
+    state.conversationsByWorkdirTask.set(key, { ...convo, turn_limits: a.limits, rev })
+    emitConversationChanged({ state, workdirId: a.workdir_id, taskId: a.task_id, onEvent: args.onEvent })
+    return
+  }
+
+  if (
+    a.type === "task_review_comment_add" ||
+    a.type === "task_review_comment_edit" ||
+    a.type === "task_review_comment_resolve" ||
+    a.type === "task_review_comments_send"
+  ) {
+    const key = workdirTaskKey(a.workdir_id, a.task_id)
+    const convo = state.conversationsByWorkdirTask.get(key) ?? null
+    if (!convo) return
+    const now = Date.now()
+    let comments = convo.review_comments ?? []
+    if (a.type === "task_review_comment_add") {
+      const id = Math.max(0, ...comments.map((c) => c.id)) + 1
+      comments = [
+        ...comments,
+        {
+          id,
+          path: a.path,
+          start_line: a.start_line,
+          end_line: a.end_line,
+          quote: a.quote,
+          body: a.body,
+          resolved: false,
+          sent: false,
+          created_at_unix_ms: now,
+          updated_at_unix_ms: now,
+        },
+      ]
+    } else if (a.type === "task_review_comment_edit") {
+      comments = comments.map((c) =>
+        c.id === a.comment_id ? { ...c, body: a.body, sent: false, updated_at_unix_ms: now } : c,
+      )
+    } else if (a.type === "task_review_comment_resolve") {
+      const reply = a.resolved && (convo.pull_request_review_sync?.reply_when_addressed ?? false)
+      comments = comments.map((c) =>
+        c.id === a.comment_id
+          ? {
+              ...c,
+              resolved: a.resolved,
+              updated_at_unix_ms: now,
+              pull_request: c.pull_request && reply ? { ...c.pull_request, replied: true } : c.pull_request,
+            }
+          : c,
+      )
+    } else {
+      const pending = comments.filter((c) => !c.sent && !c.resolved)
+      if (pending.length === 0) {
+        args.onEvent({ type: "toast", message: "No pending review comments" })
+        return
+      }
+      const text = [
+        a.note?.trim() || "Please address these review comments on your changes.",
+        ...pending.map((c, i) => {
+          const lines = c.start_line === c.end_line ? `${c.start_line}` : `${c.start_line}-${c.end_line}`
+          const quote = c.quote ? c.quote.split("\n").map((l) => `> ${l}`).join("\n") + "\n" : ""
+          return `${i + 1}. ${c.path}:${lines}\n${quote}${c.body}`
+        }),
+      ].join("\n\n")
+      mockDispatchAction({
+        action: { type: "send_agent_message", workdir_id: a.workdir_id, task_id: a.task_id, text, attachments: [] },
+        onEvent: args.onEvent,
+      })
+      const sentIds = new Set(pending.map((c) => c.id))
+      comments = comments.map((c) => (sentIds.has(c.id) ? { ...c, sent: true } : c))
+    }
+    const latest = state.conversationsByWorkdirTask.get(key) ?? convo
+    const rev = bumpRev(state)
+    state.conversationsByWorkdirTask.set(key, { ...latest, review_comments: comments, rev })
+    emitConversationChanged({ state, workdirId: a.workdir_id, taskId: a.task_id, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "task_pull_request_review_pull" || a.type === "task_pull_request_review_sync_changed") {
+    const key = workdirTaskKey(a.workdir_id, a.task_id)
+    const convo = state.conversationsByWorkdirTask.get(key) ?? null
+    if (!convo) return
+    if (a.type === "task_pull_request_review_sync_changed") {
+      const rev = bumpRev(state)
+      state.conversationsByWorkdirTask.set(key, { ...convo, pull_request_review_sync: a.sync, rev })
+      emitConversationChanged({ state, workdirId: a.workdir_id, taskId: a.task_id, onEvent: args.onEvent })
+      return
+    }
+    const comments = convo.review_comments ?? []
+    const remote = {
+      pr_number: 1,
+      comment_id: 1001,
+      author: "octocat",
+      url: "https://github.com/example/luban/pull/1#discussion_r1001",
+      path: "README.md",
+      line: 1,
+      body: "Could you expand this intro a little?",
+    }
+    if (comments.some((c) => c.pull_request?.comment_id === remote.comment_id)) {
+      args.onEvent({ type: "toast", message: "No new review comments" })
+      return
+    }
+    const now = Date.now()
+    const id = Math.max(0, ...comments.map((c) => c.id)) + 1
+    const entries: ConversationEntry[] = [
+      ...convo.entries,
+      {
+        type: "system_event",
+        entry_id: newEntryId("se"),
+        created_at_unix_ms: now,
+        event: {
+          event_type: "pull_request_review_comment",
+          pr_number: remote.pr_number,
+          review_comment_id: id,
+          author: remote.author,
+          path: remote.path,
+          start_line: remote.line,
+          end_line: remote.line,
+          body: remote.body,
+          url: remote.url,
+        },
+      },
+    ]
+    const rev = bumpRev(state)
+    state.conversationsByWorkdirTask.set(key, {
+      ...convo,
+      entries,
+      entries_total: entries.length,
+      review_comments: [
+        ...comments,
+        {
+          id,
+          path: remote.path,
+          start_line: remote.line,
+          end_line: remote.line,
+          quote: "",
+          body: remote.body,
+          resolved: false,
+          sent: false,
+          created_at_unix_ms: now,
+          updated_at_unix_ms: now,
+          pull_request: {
+            pr_number: remote.pr_number,
+            comment_id: remote.comment_id,
+            author: remote.author,
+            url: remote.url,
+            replied: false,
+          },
+        },
+      ],
+      rev,
+    })
+    emitConversationChanged({ state, workdirId: a.workdir_id, taskId: a.task_id, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "task_ci_auto_fix_changed") {
+    const key = workdirTaskKey(a.workdir_id, a.task_id)
+    const convo = state.conversationsByWorkdirTask.get(key) ?? null
+    if (!convo) return
+    if (!Number.isInteger(a.max_attempts) || a.max_attempts < 1 || a.max_attempts > 10) {
+      args.onEvent({ type: "toast", message: "Invalid CI auto-fix attempt budget" })
+      return
+    }
+    const previous = convo.ci_auto_fix ?? { enabled: false, max_attempts: 3, attempts_used: 0 }
+    const attempts_used = a.enabled && !previous.enabled ? 0 : previous.attempts_used
+    const rev = bumpRev(state)
+    state.conversationsByWorkdirTask.set(key, {
+      ...convo,
+      ci_auto_fix: { enabled: a.enabled, max_attempts: a.max_attempts, attempts_used },
+      rev,
+    })
+    emitConversationChanged({ state, workdirId: a.workdir_id, taskId: a.task_id, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "attachment_text_limits_changed") {
+    state.app.attachment_text_limits = a.limits
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "url_attachment_domains_changed") {
+    const domains = a.domains
+      .map((d) => d.trim().replace(/^\*\./, "").replace(/\.$/, "").toLowerCase())
+      .filter((d) => d.length > 0)
+    state.app.url_attachments = { allowed_domains: Array.from(new Set(domains)).sort() }
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "disk_usage_refresh") {
+    const now = Date.now()
+    for (const project of state.app.projects) {
+      for (const workdir of project.workdirs) {
+        if (workdir.status !== "active") continue
+        workdir.disk_usage = {
+          workdir_bytes: (workdir.id % 7 + 1) * 180 * 1024 * 1024,
+          context_blobs_bytes: (workdir.id % 3) * 2 * 1024 * 1024,
+          measured_at_unix_ms: now,
+        }
+      }
+    }
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "build_artifacts_purge" || a.type === "stale_worktrees_delete" || a.type === "context_blobs_gc") {
+    args.onEvent({ type: "toast", message: "Nothing to clean up" })
+    return
+  }
+
+  if (a.type === "telegram_group_unpair") {
+    const prev = state.app.integrations?.telegram ?? { enabled: false, has_token: false, config_rev: 0 }
+    state.app.integrations = {
+      ...(state.app.integrations ?? { telegram: prev }),
+      telegram: {
+        ...prev,
+        group_chats: (prev.group_chats ?? []).filter((g) => g.chat_id !== a.chat_id),
+        config_rev: (prev.config_rev ?? 0) + 1,
+      },
+    }
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
   if (a.type === "add_project") {
     const projectId: ProjectId = `mock_project_${Math.random().toString(16).slice(2)}`
     state.app.projects.push({
@@ -636,7 +962,48 @@ export function mockDispatchAction(args: { action: ClientAction; onEvent: (event
     return
   }
 
+  if (a.type === "undo_last_action") {
+    const entry = [...state.auditLog].reverse().find((e) => e.undo != null && e.undone_at_unix_ms == null) ?? null
+    if (!entry || !entry.undo) {
+      args.onEvent({ type: "toast", message: "Nothing to undo" })
+      return
+    }
+    entry.undo()
+    entry.undone_at_unix_ms = Date.now()
+    recordMockAudit(state, { action: "undo", summary: `Undid: ${entry.summary}`, workdir_id: entry.workdir_id, task_id: entry.task_id }, null)
+    emitAppChanged({ state, onEvent: args.onEvent })
+    if (entry.workdir_id != null) {
+      emitTaskSummariesChanged({ state, workdirId: entry.workdir_id, onEvent: args.onEvent })
+    }
+    return
+  }
+
+  if (a.type === "deletion_retention_changed") {
+    state.app.deletion = { retention_days: Math.max(0, Math.min(365, Math.floor(a.retention_days))) }
+    recordMockAudit(
+      state,
+      { action: "set_deletion_retention", summary: `Set deletion retention to ${state.app.deletion.retention_days} days` },
+      null,
+    )
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
   if (a.type === "delete_project") {
+    const index = state.app.projects.findIndex((p) => p.id === a.project_id)
+    const removed = index >= 0 ? state.app.projects[index] : null
+    if (removed) {
+      const softDelete = (state.app.deletion?.retention_days ?? 7) > 0
+      recordMockAudit(
+        state,
+        { action: "delete_project", summary: `Deleted project ${removed.name}` },
+        softDelete
+          ? () => {
+              state.app.projects.splice(Math.min(index, state.app.projects.length), 0, removed)
+            }
+          : null,
+      )
+    }
     state.app.projects = state.app.projects.filter((p) => p.id !== a.project_id)
     emitAppChanged({ state, onEvent: args.onEvent })
     return
@@ -1093,8 +1460,27 @@ export function mockDispatchAction(args: { action: ClientAction; onEvent: (event
     return
   }
 
+  if (a.type === "task_issue_merge_action_set") {
+    state.app.task_issue_links = (state.app.task_issue_links ?? []).map((link) =>
+      link.workdir_id === a.workdir_id && link.task_id === a.task_id ? { ...link, on_merge: a.on_merge } : link,
+    )
+    emitAppChanged({ state, onEvent: args.onEvent })
+    return
+  }
+
   if (a.type === "task_star_set") {
     const key = workdirTaskKey(a.workdir_id, a.task_id)
+    const wasStarred = state.starredTasks.has(key)
+    if (wasStarred !== a.starred) {
+      recordMockAudit(
+        state,
+        { action: "set_task_starred", summary: `${a.starred ? "Starred" : "Unstarred"} task #${a.task_id}`, workdir_id: a.workdir_id, task_id: a.task_id },
+        () => {
+          if (wasStarred) state.starredTasks.add(key)
+          else state.starredTasks.delete(key)
+        },
+      )
+    }
     if (a.starred) {
       state.starredTasks.add(key)
     } else {
@@ -1105,6 +1491,79 @@ export function mockDispatchAction(args: { action: ClientAction; onEvent: (event
     return
   }
 
+  if (a.type === "task_rewind") {
+    const key = workdirTaskKey(a.workdir_id, a.task_id)
+    const convo = state.conversationsByWorkdirTask.get(key) ?? null
+    if (!convo) return
+    let entries = convo.entries
+    if (a.truncate_conversation) {
+      const index = entries.findIndex(
+        (e) =>
+          e.type === "system_event" &&
+          e.event.event_type === "turn_checkpoint" &&
+          e.event.checkpoint_id === a.checkpoint_id,
+      )
+      if (index >= 0) {
+        const prev = entries[index - 1]
+        const start = prev && prev.type === "user_event" && prev.event.type === "message" ? index - 1 : index
+        entries = entries.slice(0, start)
+      }
+    }
+    const next: ConversationEntry[] = [
+      ...entries,
+      {
+        type: "system_event",
+        entry_id: newEntryId("se"),
+        created_at_unix_ms: Date.now(),
+        event: { event_type: "turn_rewound", checkpoint_id: a.checkpoint_id, truncated: a.truncate_conversation },
+      },
+    ]
+    const rev = bumpRev(state)
+    state.conversationsByWorkdirTask.set(key, { ...convo, entries: next, entries_total: next.length, rev })
+    emitConversationChanged({ state, workdirId: a.workdir_id, taskId: a.task_id, onEvent: args.onEvent })
+    return
+  }
+
+  if (a.type === "task_fork") {
+    const source = state.conversationsByWorkdirTask.get(workdirTaskKey(a.workdir_id, a.task_id)) ?? null
+    if (!source) return
+    const index = source.entries.findIndex((e) => e.entry_id === a.entry_id)
+    if (index < 0) {
+      args.onEvent({ type: "toast", message: "Fork point not found" })
+      return
+    }
+    // The mock has no worktrees to branch, so both targets fork into the source workdir.
+    const title = `${source.title} (fork)`
+    const taskId = createTaskInWorkdir(state, a.workdir_id, title)
+    const forkedFrom = { workdir_id: a.workdir_id, task_id: a.task_id, entry_id: a.entry_id }
+    const key = workdirTaskKey(a.workdir_id, taskId)
+    const convo = state.conversationsByWorkdirTask.get(key)!
+    const entries = clone(source.entries.slice(0, index + 1))
+    const taskStatus = source.task_status === "done" || source.task_status === "canceled" ? "iterating" : source.task_status
+    state.conversationsByWorkdirTask.set(key, {
+      ...convo,
+      task_status: taskStatus,
+      agent_runner: source.agent_runner,
+      agent_model_id: source.agent_model_id,
+      thinking_effort: source.thinking_effort,
+      amp_mode: source.amp_mode ?? null,
+      entries,
+      entries_total: entries.length,
+      forked_from: forkedFrom,
+    })
+    const meta = state.threadsByWorkdir.get(a.workdir_id)?.tasks.find((t) => t.task_id === taskId) ?? null
+    if (meta) {
+      meta.task_status = taskStatus
+      meta.forked_from = forkedFrom
+    }
+    setActiveWorkdirTask(state, { workdirId: a.workdir_id, taskId })
+    emitWorkdirTasksChanged({ state, workdirId: a.workdir_id, onEvent: args.onEvent })
+    emitAppChanged({ state, onEvent: args.onEvent })
+    emitTaskSummariesChanged({ state, workdirId: a.workdir_id, onEvent: args.onEvent })
+    emitConversationChanged({ state, workdirId: a.workdir_id, taskId, onEvent: args.onEvent })
+    return
+  }
+
   args.onEvent({ type: "toast", message: `Mock: action not implemented: ${a.type}` })
 }
 
@@ -1198,11 +1657,81 @@ export async function mockRequest<T>(action: ClientAction): Promise<T> {
     return clone(result) as unknown as T
   }
 
+  if (action.type === "task_import_issue") {
+    const project = findProject(state.app, action.project_id)?.project ?? null
+    const workdirId = project?.workdirs[0]?.id ?? allWorkdirIds(state.app)[0] ?? 1
+    const number = Number.parseInt(action.issue.replace(/^.*[#/]/, ""), 10) || 1
+    const title = `Mock issue #${number}`
+    const taskId = createTaskInWorkdir(state, workdirId, title)
+    setActiveWorkdirTask(state, { workdirId, taskId })
+    const url = /^https?:\/\//.test(action.issue) ? action.issue : `https://example.invalid/issues/${number}`
+    state.app.task_issue_links = [
+      ...(state.app.task_issue_links ?? []),
+      { workdir_id: workdirId, task_id: taskId, number, title, url, on_merge: action.on_merge ?? "close", resolved_by_pr: null },
+    ]
+
+    const workdir = findWorkdir(state.app, workdirId)?.workdir ?? null
+    const result: TaskExecuteResult = {
+      project_id: action.project_id,
+      workdir_id: workdirId,
+      task_id: taskId,
+      workdir_path: workdir?.workdir_path ?? "/mock",
+      prompt: `#${number} ${title}\n${url}`,
+      mode: action.mode,
+    }
+    return clone(result) as unknown as T
+  }
+
+  if (action.type === "task_bulk_apply" || action.type === "task_bulk_undo") {
+    const batchId = action.type === "task_bulk_undo" ? action.batch_id : `bulk_mock_${Date.now()}`
+    const undo = action.type === "task_bulk_undo" ? (state.taskBulkUndo.get(batchId) ?? null) : null
+    if (action.type === "task_bulk_undo") {
+      if (!undo) throw new Error("nothing to undo for this batch")
+      state.taskBulkUndo.delete(batchId)
+    }
+    const setStatus = (item: TaskBulkItem, status: TaskStatus): TaskBulkItemResult => {
+      const task = state.threadsByWorkdir.get(item.workdir_id)?.tasks.find((t) => t.task_id === item.task_id) ?? null
+      if (!task) return { ...item, ok: false, message: "task not found", previous_status: null }
+      const previous = task.task_status
+      task.task_status = status
+      return { ...item, ok: true, message: null, previous_status: previous === status ? null : previous }
+    }
+
+    let items: TaskBulkItemResult[]
+    if (undo) {
+      items = undo.map((item) => setStatus(item, item.previous_status ?? "todo"))
+    } else if (action.type === "task_bulk_apply" && action.operation.type === "set_status") {
+      const status = action.operation.task_status
+      items = action.items.map((item) => setStatus(item, status))
+    } else if (action.type === "task_bulk_apply" && action.operation.type === "set_starred") {
+      const starred = action.operation.starred
+      items = action.items.map((item) => {
+        const key = workdirTaskKey(item.workdir_id, item.task_id)
+        if (starred) state.starredTasks.add(key)
+        else state.starredTasks.delete(key)
+        return { ...item, ok: true, message: null, previous_status: null }
+      })
+    } else {
+      const requested = action.type === "task_bulk_apply" ? action.items : []
+      items = requested.map((item) => ({ ...item, ok: false, message: "Mock: not supported", previous_status: null }))
+    }
+
+    const changed = undo ? [] : items.filter((item) => item.ok && item.previous_status != null)
+    if (changed.length > 0) state.taskBulkUndo.set(batchId, changed)
+    const result: TaskBulkResult = { batch_id: batchId, items, undoable: changed.length > 0 }
+    return clone(result) as unknown as T
+  }
+
   if (action.type === "telegram_pair_start") {
     const username = state.app.integrations?.telegram?.bot_username ?? "mock_bot"
     return `https://t.me/${username}?start=mock_pairing_code` as unknown as T
   }
 
+  if (action.type === "telegram_group_pair_start") {
+    const username = state.app.integrations?.telegram?.bot_username ?? "mock_bot"
+    return `https://t.me/${username}?startgroup=mock_pairing_code` as unknown as T
+  }
+
   if (action.type === "feedback_submit") {
     const issue = { number: 1, title: action.title, url: "https://example.invalid/issue/1" }
     if (action.action !== "fix_it") {
@@ -68,3 +68,11 @@ export function kanbanColumnForTask(args: { agentStatus: AgentStatus; prStatus:
       return "backlog"
   }
 }
+
+/** Returns the input when it is only an issue reference (`#42` or an issue URL), which imports that issue as the task. */
+export function issueReferenceFromInput(input: string): string | null {
+  const trimmed = input.trim()
+  if (/^#\d+$/.test(trimmed)) return trimmed
+  if (/^https?:\/\/\S+\/issues\/\d+\/?(?:[?#]\S*)?$/.test(trimmed)) return trimmed
+  return null
+}